@@ -32,6 +32,8 @@ const IGNORED_FILES: &'static [&'static str] = &[
 	"dapps_accounts.json",
 	"dapps_history.json",
 	"vault.json",
+	"unlock_audit.log",
+	"unlock_audit.log.1",
 ];
 
 /// Find a unique filename that does not exist using four-letter random suffix.
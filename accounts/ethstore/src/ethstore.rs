@@ -28,7 +28,7 @@ use accounts_dir::{KeyDirectory, VaultKeyDirectory, VaultKey, SetKeyError};
 use account::SafeAccount;
 use presale::PresaleWallet;
 use json::{self, Uuid, OpaqueKeyFile};
-use {import, Error, SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation, OpaqueSecret};
+use {import, Error, SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation, OpaqueSecret, AccountChanges};
 
 /// Accounts store.
 pub struct EthStore {
@@ -253,6 +253,10 @@ impl SecretStore for EthStore {
 		imported_addresses
 			.map(|a| a.into_iter().map(|a| StoreAccountRef::root(a)).collect())
 	}
+
+	fn refresh(&self) -> Result<AccountChanges, Error> {
+		self.store.refresh()
+	}
 }
 
 /// Similar to `EthStore` but may store many accounts (with different passwords) for the same `Address`
@@ -320,7 +324,20 @@ impl EthMultiStore {
 		Ok(())
 	}
 
-	fn reload_accounts(&self) -> Result<(), Error> {
+	/// Re-read the accounts if the key directory changed, regardless of the refresh timeout.
+	pub fn refresh(&self) -> Result<AccountChanges, Error> {
+		let mut last_timestamp = self.timestamp.lock();
+		let dir_hash = Some(self.dir.unique_repr()?);
+		last_timestamp.last_checked = Instant::now();
+		if last_timestamp.dir_hash == dir_hash {
+			return Ok(AccountChanges::default())
+		}
+		let changes = self.reload_accounts()?;
+		last_timestamp.dir_hash = dir_hash;
+		Ok(changes)
+	}
+
+	fn reload_accounts(&self) -> Result<AccountChanges, Error> {
 		let mut cache = self.cache.write();
 
 		let mut new_accounts = BTreeMap::new();
@@ -341,8 +358,12 @@ impl EthMultiStore {
 			}
 		}
 
+		let changes = AccountChanges {
+			added: new_accounts.keys().filter(|a| !cache.contains_key(*a)).cloned().collect(),
+			removed: cache.keys().filter(|a| !new_accounts.contains_key(*a)).cloned().collect(),
+		};
 		mem::replace(&mut *cache, new_accounts);
-		Ok(())
+		Ok(changes)
 	}
 
 	fn get_accounts(&self, account: &StoreAccountRef) -> Result<Vec<SafeAccount>, Error> {
@@ -742,6 +763,26 @@ mod tests {
 		assert_eq!(store.accounts().unwrap().len(), 1, "Should have one account.");
 	}
 
+	#[test]
+	fn should_pick_up_key_files_added_externally() {
+		// given
+		let dir = RootDiskDirectoryGuard::new();
+		let path = dir.key_dir.as_ref().unwrap().path().cloned().unwrap();
+		let store = EthStore::open(dir.key_dir.unwrap()).unwrap();
+		let other = EthStore::open(Box::new(RootDiskDirectory::create(&path).unwrap())).unwrap();
+		let keypair = keypair();
+
+		// when
+		let address = other.insert_account(SecretVaultRef::Root, keypair.secret().clone(), &"test".into()).unwrap();
+
+		// then
+		let changes = store.refresh().unwrap();
+		assert_eq!(changes.added, vec![address.clone()]);
+		assert!(changes.removed.is_empty());
+		assert!(store.get(&address).is_ok());
+		assert_eq!(store.refresh().unwrap(), Default::default());
+	}
+
 	#[test]
 	fn should_update_meta_and_name() {
 		// given
@@ -65,7 +65,7 @@ pub use self::json::OpaqueKeyFile as KeyFile;
 pub use self::presale::PresaleWallet;
 pub use self::secret_store::{
 	SecretVaultRef, StoreAccountRef, SimpleSecretStore, SecretStore,
-	Derivation, IndexDerivation,
+	Derivation, IndexDerivation, AccountChanges,
 };
 pub use self::random::random_string;
 pub use self::parity_wordlist::random_phrase;
@@ -54,6 +54,15 @@ impl ::std::borrow::Borrow<Address> for StoreAccountRef {
 	}
 }
 
+/// Accounts added to or removed from the key directory since it was last read.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AccountChanges {
+	/// Accounts of new key files.
+	pub added: Vec<StoreAccountRef>,
+	/// Accounts whose key files are gone.
+	pub removed: Vec<StoreAccountRef>,
+}
+
 /// Simple Secret Store API
 pub trait SimpleSecretStore: Send + Sync {
 	/// Inserts new accounts to the store (or vault) with given password.
@@ -144,6 +153,8 @@ pub trait SecretStore: SimpleSecretStore {
 	fn list_geth_accounts(&self, testnet: bool) -> Vec<Address>;
 	/// Imports geth accounts to the store/vault.
 	fn import_geth_accounts(&self, vault: SecretVaultRef, desired: Vec<Address>, testnet: bool) -> Result<Vec<StoreAccountRef>, Error>;
+	/// Re-reads the key directory if it changed since it was last read, e.g. key files were added externally.
+	fn refresh(&self) -> Result<AccountChanges, Error>;
 }
 
 impl StoreAccountRef {
@@ -0,0 +1,203 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Audit log of account unlocks and operations performed with unlocked accounts.
+
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{trace, warn};
+use parity_crypto::publickey::Address;
+use parking_lot::Mutex;
+use serde_derive::{Serialize, Deserialize};
+use serde_json;
+
+/// Number of most recent entries kept in memory.
+const MAX_ENTRIES: usize = 1024;
+/// Name of the audit log file.
+const LOG_FILE: &str = "unlock_audit.log";
+/// Name the audit log is moved to once it grows past `MAX_LOG_SIZE`.
+const ROTATED_LOG_FILE: &str = "unlock_audit.log.1";
+/// Size (in bytes) after which the audit log is rotated.
+const MAX_LOG_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Kind of audited event.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditEvent {
+	/// Account unlocked for the lifetime of the node.
+	UnlockPermanent,
+	/// Account unlocked for a single operation.
+	UnlockTemporary,
+	/// Account unlocked until the timeout passes.
+	UnlockTimed,
+	/// Unlock expired and account has been locked again.
+	Relock,
+	/// Message signed with an unlocked account.
+	Sign,
+	/// Message signed with a key derived from an unlocked account.
+	SignDerived,
+	/// Message decrypted with an unlocked account.
+	Decrypt,
+	/// Shared secret agreed with an unlocked account.
+	Agree,
+}
+
+/// Single audit log entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+	/// UNIX timestamp (in seconds) of the event.
+	pub timestamp: u64,
+	/// Account the event relates to.
+	pub account: Address,
+	/// What happened.
+	pub event: AuditEvent,
+	/// Unlock duration in seconds, for timed unlocks.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub duration: Option<u64>,
+	/// Who requested the event (e.g. the RPC transport), if known.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub origin: Option<String>,
+}
+
+/// Append-only audit log. Entries are stored on disk as one JSON object per line,
+/// the file is rotated once it grows past `MAX_LOG_SIZE` and only one rotated file is kept.
+pub struct AuditLog {
+	path: Option<PathBuf>,
+	entries: Mutex<VecDeque<AuditEntry>>,
+}
+
+impl AuditLog {
+	/// Opens the audit log in given directory, loading most recent entries.
+	pub fn new(path: &Path) -> Self {
+		let mut entries = VecDeque::new();
+		load_entries(&path.join(ROTATED_LOG_FILE), &mut entries);
+		load_entries(&path.join(LOG_FILE), &mut entries);
+
+		AuditLog {
+			path: Some(path.to_owned()),
+			entries: Mutex::new(entries),
+		}
+	}
+
+	/// Creates audit log which is not saved to disk.
+	pub fn transient() -> Self {
+		AuditLog {
+			path: None,
+			entries: Mutex::new(VecDeque::new()),
+		}
+	}
+
+	/// Records a new event.
+	pub fn record(&self, account: Address, event: AuditEvent, duration: Option<u64>, origin: Option<String>) {
+		let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+		let entry = AuditEntry { timestamp, account, event, duration, origin };
+
+		// hold the lock while writing, so that concurrent records don't race on rotation
+		let mut entries = self.entries.lock();
+		if let Some(ref dir) = self.path {
+			let path = dir.join(LOG_FILE);
+			if fs::metadata(&path).map(|m| m.len() >= MAX_LOG_SIZE).unwrap_or(false) {
+				let _ = fs::rename(&path, dir.join(ROTATED_LOG_FILE))
+					.map_err(|e| warn!(target: "accounts", "Couldn't rotate audit log at {:?}: {}", path, e));
+			}
+
+			let line = serde_json::to_string(&entry).expect("AuditEntry serialization is infallible; qed");
+			let _ = OpenOptions::new().create(true).append(true).open(&path)
+				.and_then(|mut file| writeln!(file, "{}", line))
+				.map_err(|e| warn!(target: "accounts", "Couldn't write to audit log at {:?}: {}", path, e));
+		}
+
+		push_bounded(&mut entries, entry);
+	}
+
+	/// Returns up to `limit` most recent entries (optionally only for given account), oldest first.
+	pub fn entries(&self, account: Option<Address>, limit: usize) -> Vec<AuditEntry> {
+		let entries = self.entries.lock();
+		let mut result: Vec<_> = entries.iter().rev()
+			.filter(|entry| account.map_or(true, |account| entry.account == account))
+			.take(limit)
+			.cloned()
+			.collect();
+		result.reverse();
+		result
+	}
+}
+
+fn load_entries(path: &Path, entries: &mut VecDeque<AuditEntry>) {
+	match fs::File::open(path) {
+		Ok(file) => for line in BufReader::new(file).lines() {
+			let entry = line.map_err(|e| e.to_string())
+				.and_then(|line| serde_json::from_str(&line).map_err(|e| e.to_string()));
+			match entry {
+				Ok(entry) => push_bounded(entries, entry),
+				Err(e) => warn!(target: "accounts", "Skipping invalid audit log entry in {:?}: {}", path, e),
+			}
+		},
+		Err(e) => trace!(target: "accounts", "Couldn't open audit log {:?}: {}", path, e),
+	}
+}
+
+fn push_bounded(entries: &mut VecDeque<AuditEntry>, entry: AuditEntry) {
+	if entries.len() == MAX_ENTRIES {
+		entries.pop_front();
+	}
+	entries.push_back(entry);
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use super::{AuditLog, AuditEvent, Address, LOG_FILE, ROTATED_LOG_FILE, MAX_LOG_SIZE};
+	use tempdir::TempDir;
+
+	#[test]
+	fn should_save_and_reload_audit_log() {
+		let tempdir = TempDir::new("").unwrap();
+		let log = AuditLog::new(tempdir.path());
+		log.record(Address::from_low_u64_be(1), AuditEvent::UnlockTimed, Some(60), Some("IPC".into()));
+		log.record(Address::from_low_u64_be(2), AuditEvent::UnlockPermanent, None, None);
+		log.record(Address::from_low_u64_be(1), AuditEvent::Sign, None, None);
+
+		let log = AuditLog::new(tempdir.path());
+		let events: Vec<_> = log.entries(None, 10).into_iter().map(|e| (e.account, e.event, e.duration)).collect();
+		assert_eq!(events, vec![
+			(Address::from_low_u64_be(1), AuditEvent::UnlockTimed, Some(60)),
+			(Address::from_low_u64_be(2), AuditEvent::UnlockPermanent, None),
+			(Address::from_low_u64_be(1), AuditEvent::Sign, None),
+		]);
+
+		let events: Vec<_> = log.entries(Some(Address::from_low_u64_be(1)), 1).into_iter().map(|e| e.event).collect();
+		assert_eq!(events, vec![AuditEvent::Sign]);
+		assert_eq!(log.entries(None, 10)[0].origin, Some("IPC".into()));
+	}
+
+	#[test]
+	fn should_rotate_audit_log() {
+		let tempdir = TempDir::new("").unwrap();
+		let log = AuditLog::new(tempdir.path());
+		log.record(Address::from_low_u64_be(1), AuditEvent::UnlockTimed, Some(60), None);
+		let file = fs::OpenOptions::new().append(true).open(tempdir.path().join(LOG_FILE)).unwrap();
+		file.set_len(MAX_LOG_SIZE).unwrap();
+
+		log.record(Address::from_low_u64_be(1), AuditEvent::Sign, None, None);
+		assert_eq!(fs::metadata(tempdir.path().join(ROTATED_LOG_FILE)).unwrap().len(), MAX_LOG_SIZE);
+		assert!(fs::metadata(tempdir.path().join(LOG_FILE)).unwrap().len() < MAX_LOG_SIZE);
+	}
+}
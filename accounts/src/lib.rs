@@ -19,12 +19,15 @@
 //! Account management.
 
 mod account_data;
+mod audit;
 mod error;
 mod stores;
 
 use self::account_data::{Unlock, AccountData};
+use self::audit::AuditLog;
 use self::stores::AddressBook;
 
+use std::cmp;
 use std::collections::HashMap;
 use std::time::{Instant, Duration};
 
@@ -41,6 +44,7 @@ use parking_lot::RwLock;
 pub use ethstore::{Derivation, IndexDerivation, KeyFile, Error};
 
 pub use self::account_data::AccountMeta;
+pub use self::audit::{AuditEntry, AuditEvent};
 pub use self::error::SignError;
 
 type AccountToken = Password;
@@ -52,6 +56,23 @@ pub struct AccountProviderSettings {
 	pub unlock_keep_secret: bool,
 	/// Disallowed accounts.
 	pub blacklisted_accounts: Vec<Address>,
+	/// Maximum duration of a timed unlock.
+	pub max_unlock_duration: Option<Duration>,
+}
+
+/// Key file change picked up from the keys directory.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccountEvent {
+	/// Key file of a new account was added.
+	Added(Address),
+	/// Key file of an account was removed.
+	Removed(Address),
+}
+
+/// Receives key file changes picked up by `AccountProvider::refresh`.
+pub trait AccountsListener: Send + Sync {
+	/// Called for each account added or removed.
+	fn notify(&self, event: &AccountEvent);
 }
 
 /// Account management.
@@ -72,6 +93,12 @@ pub struct AccountProvider {
 	unlock_keep_secret: bool,
 	/// Disallowed accounts.
 	blacklisted_accounts: Vec<Address>,
+	/// Maximum duration of a timed unlock.
+	max_unlock_duration: Option<Duration>,
+	/// Log of unlocks and operations performed with unlocked accounts.
+	audit_log: AuditLog,
+	/// Listeners notified about key files added or removed externally.
+	listeners: RwLock<Vec<Box<dyn AccountsListener>>>,
 }
 
 fn transient_sstore() -> EthMultiStore {
@@ -94,6 +121,13 @@ impl AccountProvider {
 			address_book.remove(*addr);
 		}
 
+		let local_path = sstore.local_path();
+		let audit_log = if local_path.as_os_str().is_empty() {
+			AuditLog::transient()
+		} else {
+			AuditLog::new(&local_path)
+		};
+
 		AccountProvider {
 			unlocked_secrets: RwLock::new(HashMap::new()),
 			unlocked: RwLock::new(HashMap::new()),
@@ -102,6 +136,9 @@ impl AccountProvider {
 			transient_sstore: transient_sstore(),
 			unlock_keep_secret: settings.unlock_keep_secret,
 			blacklisted_accounts: settings.blacklisted_accounts,
+			max_unlock_duration: settings.max_unlock_duration,
+			audit_log,
+			listeners: RwLock::new(Vec::new()),
 		}
 	}
 
@@ -115,6 +152,9 @@ impl AccountProvider {
 			transient_sstore: transient_sstore(),
 			unlock_keep_secret: false,
 			blacklisted_accounts: vec![],
+			max_unlock_duration: None,
+			audit_log: AuditLog::transient(),
+			listeners: RwLock::new(Vec::new()),
 		}
 	}
 
@@ -189,6 +229,38 @@ impl AccountProvider {
 		)
 	}
 
+	/// Registers a listener notified about key files added or removed externally.
+	pub fn add_listener(&self, listener: Box<dyn AccountsListener>) {
+		self.listeners.write().push(listener);
+	}
+
+	/// Picks up key files added to or removed from the keys directory by other processes
+	/// and notifies the listeners. Accounts whose key files are gone get locked.
+	pub fn refresh(&self) -> Result<Vec<AccountEvent>, Error> {
+		let changes = self.sstore.refresh()?;
+
+		for account in &changes.removed {
+			self.unlocked.write().remove(account);
+			self.unlocked_secrets.write().remove(account);
+		}
+
+		let events = changes.added.into_iter()
+			.map(|a| AccountEvent::Added(a.address))
+			.chain(changes.removed.into_iter().map(|a| AccountEvent::Removed(a.address)))
+			.filter(|event| match *event {
+				AccountEvent::Added(ref address) | AccountEvent::Removed(ref address) => !self.blacklisted_accounts.contains(address),
+			})
+			.collect::<Vec<_>>();
+
+		let listeners = self.listeners.read();
+		for event in &events {
+			for listener in listeners.iter() {
+				listener.notify(event);
+			}
+		}
+		Ok(events)
+	}
+
 	/// Returns the address of default account.
 	pub fn default_account(&self) -> Result<Address, Error> {
 		Ok(self.accounts()?.first().cloned().unwrap_or_default())
@@ -310,29 +382,72 @@ impl AccountProvider {
 		if let Unlock::Timed(ref end) = data.unlock {
 			if Instant::now() > *end {
 				unlocked.remove(account).expect("data exists: so key must exist: qed");
+				self.audit_log.record(account.address, AuditEvent::Relock, None, None);
 				return Err(SignError::NotUnlocked);
 			}
 		}
 		Ok(data.password)
 	}
 
+	/// Locks all accounts which timed unlock has expired.
+	/// Meant to be called periodically, so that the relock is audited when it happens.
+	pub fn relock_expired(&self) {
+		let now = Instant::now();
+		let mut unlocked = self.unlocked.write();
+		let expired: Vec<_> = unlocked.iter()
+			.filter(|&(_, data)| match data.unlock {
+				Unlock::Timed(end) => now > end,
+				_ => false,
+			})
+			.map(|(account, _)| account.clone())
+			.collect();
+		for account in expired {
+			unlocked.remove(&account);
+			self.audit_log.record(account.address, AuditEvent::Relock, None, None);
+		}
+	}
+
 	/// Unlocks account permanently.
-	pub fn unlock_account_permanently(&self, account: Address, password: Password) -> Result<(), Error> {
-		self.unlock_account(account, password, Unlock::Perm)
+	/// Not limited by the maximum unlock duration, meant to be used for node configuration only.
+	/// `origin` describes who requested the unlock and is recorded in the audit log.
+	pub fn unlock_account_permanently(&self, account: Address, password: Password, origin: Option<String>) -> Result<(), Error> {
+		self.unlock_account(account, password, Unlock::Perm)?;
+		self.audit_log.record(account, AuditEvent::UnlockPermanent, None, origin);
+		Ok(())
 	}
 
 	/// Unlocks account temporarily (for one signing).
-	pub fn unlock_account_temporarily(&self, account: Address, password: Password) -> Result<(), Error> {
-		self.unlock_account(account, password, Unlock::OneTime)
+	/// `origin` describes who requested the unlock and is recorded in the audit log.
+	pub fn unlock_account_temporarily(&self, account: Address, password: Password, origin: Option<String>) -> Result<(), Error> {
+		self.unlock_account(account, password, Unlock::OneTime)?;
+		self.audit_log.record(account, AuditEvent::UnlockTemporary, None, origin);
+		Ok(())
 	}
 
 	/// Unlocks account temporarily with a timeout.
-	pub fn unlock_account_timed(&self, account: Address, password: Password, duration: Duration) -> Result<(), Error> {
-		self.unlock_account(account, password, Unlock::Timed(Instant::now() + duration))
+	/// The timeout is capped to the configured maximum unlock duration.
+	/// `origin` describes who requested the unlock and is recorded in the audit log.
+	pub fn unlock_account_timed(&self, account: Address, password: Password, duration: Duration, origin: Option<String>) -> Result<(), Error> {
+		let duration = self.max_unlock_duration.map_or(duration, |max| cmp::min(duration, max));
+		self.unlock_account(account, password, Unlock::Timed(Instant::now() + duration))?;
+		self.audit_log.record(account, AuditEvent::UnlockTimed, Some(duration.as_secs()), origin);
+		Ok(())
+	}
+
+	/// Returns the maximum duration of a timed unlock, if limited.
+	pub fn max_unlock_duration(&self) -> Option<Duration> {
+		self.max_unlock_duration
+	}
+
+	/// Returns up to `limit` most recent audit log entries, optionally only for given account.
+	pub fn audit_log(&self, account: Option<Address>, limit: usize) -> Vec<AuditEntry> {
+		self.relock_expired();
+		self.audit_log.entries(account, limit)
 	}
 
 	/// Checks if given account is unlocked
 	pub fn is_unlocked(&self, address: &Address) -> bool {
+		self.relock_expired();
 		let unlocked = self.unlocked.read();
 		let unlocked_secrets = self.unlocked_secrets.read();
 		self.sstore.account_ref(address)
@@ -351,15 +466,20 @@ impl AccountProvider {
 	/// Signs the message. If password is not provided the account must be unlocked.
 	pub fn sign(&self, address: Address, password: Option<Password>, message: Message) -> Result<Signature, SignError> {
 		let account = self.sstore.account_ref(&address)?;
-		match self.unlocked_secrets.read().get(&account) {
+		let unlocked = password.is_none();
+		let signature = match self.unlocked_secrets.read().get(&account) {
 			Some(secret) => {
-				Ok(self.sstore.sign_with_secret(&secret, &message)?)
+				self.sstore.sign_with_secret(&secret, &message)?
 			},
 			None => {
 				let password = password.map(Ok).unwrap_or_else(|| self.password(&account))?;
-				Ok(self.sstore.sign(&account, &password, &message)?)
+				self.sstore.sign(&account, &password, &message)?
 			}
+		};
+		if unlocked {
+			self.audit_log.record(address, AuditEvent::Sign, None, None);
 		}
+		Ok(signature)
 	}
 
 	/// Signs message using the derived secret. If password is not provided the account must be unlocked.
@@ -367,8 +487,13 @@ impl AccountProvider {
 		-> Result<Signature, SignError>
 	{
 		let account = self.sstore.account_ref(address)?;
+		let unlocked = password.is_none();
 		let password = password.map(Ok).unwrap_or_else(|| self.password(&account))?;
-		Ok(self.sstore.sign_derived(&account, &password, derivation, &message)?)
+		let signature = self.sstore.sign_derived(&account, &password, derivation, &message)?;
+		if unlocked {
+			self.audit_log.record(*address, AuditEvent::SignDerived, None, None);
+		}
+		Ok(signature)
 	}
 
 	/// Signs given message with supplied token. Returns a token to use in next signing within this session.
@@ -418,15 +543,25 @@ impl AccountProvider {
 	/// Decrypts a message. If password is not provided the account must be unlocked.
 	pub fn decrypt(&self, address: Address, password: Option<Password>, shared_mac: &[u8], message: &[u8]) -> Result<Vec<u8>, SignError> {
 		let account = self.sstore.account_ref(&address)?;
+		let unlocked = password.is_none();
 		let password = password.map(Ok).unwrap_or_else(|| self.password(&account))?;
-		Ok(self.sstore.decrypt(&account, &password, shared_mac, message)?)
+		let message = self.sstore.decrypt(&account, &password, shared_mac, message)?;
+		if unlocked {
+			self.audit_log.record(address, AuditEvent::Decrypt, None, None);
+		}
+		Ok(message)
 	}
 
 	/// Agree on shared key.
 	pub fn agree(&self, address: Address, password: Option<Password>, other_public: &Public) -> Result<Secret, SignError> {
 		let account = self.sstore.account_ref(&address)?;
+		let unlocked = password.is_none();
 		let password = password.map(Ok).unwrap_or_else(|| self.password(&account))?;
-		Ok(self.sstore.agree(&account, &password, other_public)?)
+		let secret = self.sstore.agree(&account, &password, other_public)?;
+		if unlocked {
+			self.audit_log.record(address, AuditEvent::Agree, None, None);
+		}
+		Ok(secret)
 	}
 
 	/// Returns the underlying `SecretStore` reference if one exists.
@@ -501,7 +636,7 @@ impl AccountProvider {
 
 #[cfg(test)]
 mod tests {
-	use super::{AccountProvider, Unlock};
+	use super::{AccountProvider, AuditEvent, Unlock};
 	use std::time::{Duration, Instant};
 	use parity_crypto::publickey::{Generator, Random, Address};
 	use ethstore::{StoreAccountRef, Derivation};
@@ -512,8 +647,8 @@ mod tests {
 		let kp = Random.generate().unwrap();
 		let ap = AccountProvider::transient_provider();
 		assert!(ap.insert_account(kp.secret().clone(), &"test".into()).is_ok());
-		assert!(ap.unlock_account_temporarily(kp.address(), "test1".into()).is_err());
-		assert!(ap.unlock_account_temporarily(kp.address(), "test".into()).is_ok());
+		assert!(ap.unlock_account_temporarily(kp.address(), "test1".into(), None).is_err());
+		assert!(ap.unlock_account_temporarily(kp.address(), "test".into(), None).is_ok());
 		assert!(ap.sign(kp.address(), None, Default::default()).is_ok());
 		assert!(ap.sign(kp.address(), None, Default::default()).is_err());
 	}
@@ -523,7 +658,7 @@ mod tests {
 		let kp = Random.generate().unwrap();
 		let ap = AccountProvider::transient_provider();
 		assert!(ap.insert_account(kp.secret().clone(), &"base".into()).is_ok());
-		assert!(ap.unlock_account_permanently(kp.address(), "base".into()).is_ok());
+		assert!(ap.unlock_account_permanently(kp.address(), "base".into(), None).is_ok());
 
 		let derived_addr = ap.derive_account(
 			&kp.address(),
@@ -532,7 +667,7 @@ mod tests {
 			false,
 		).expect("Derivation should not fail");
 
-		assert!(ap.unlock_account_permanently(derived_addr, "base".into()).is_err(),
+		assert!(ap.unlock_account_permanently(derived_addr, "base".into(), None).is_err(),
 			"There should be an error because account is not supposed to be saved");
 	}
 
@@ -541,7 +676,7 @@ mod tests {
 		let kp = Random.generate().unwrap();
 		let ap = AccountProvider::transient_provider();
 		assert!(ap.insert_account(kp.secret().clone(), &"base".into()).is_ok());
-		assert!(ap.unlock_account_permanently(kp.address(), "base".into()).is_ok());
+		assert!(ap.unlock_account_permanently(kp.address(), "base".into(), None).is_ok());
 
 		let derived_addr = ap.derive_account(
 			&kp.address(),
@@ -550,10 +685,10 @@ mod tests {
 			true,
 		).expect("Derivation should not fail");
 
-		assert!(ap.unlock_account_permanently(derived_addr, "base_wrong".into()).is_err(),
+		assert!(ap.unlock_account_permanently(derived_addr, "base_wrong".into(), None).is_err(),
 			"There should be an error because password is invalid");
 
-		assert!(ap.unlock_account_permanently(derived_addr, "base".into()).is_ok(),
+		assert!(ap.unlock_account_permanently(derived_addr, "base".into(), None).is_ok(),
 			"Should be ok because account is saved and password is valid");
 	}
 
@@ -562,7 +697,7 @@ mod tests {
 		let kp = Random.generate().unwrap();
 		let ap = AccountProvider::transient_provider();
 		assert!(ap.insert_account(kp.secret().clone(), &"base".into()).is_ok());
-		assert!(ap.unlock_account_permanently(kp.address(), "base".into()).is_ok());
+		assert!(ap.unlock_account_permanently(kp.address(), "base".into(), None).is_ok());
 
 		let derived_addr = ap.derive_account(
 			&kp.address(),
@@ -570,7 +705,7 @@ mod tests {
 			Derivation::SoftHash(H256::from_low_u64_be(1999)),
 			true,
 		).expect("Derivation should not fail");
-		ap.unlock_account_permanently(derived_addr, "base".into())
+		ap.unlock_account_permanently(derived_addr, "base".into(), None)
 			.expect("Should be ok because account is saved and password is valid");
 
 		let msg = Default::default();
@@ -592,11 +727,11 @@ mod tests {
 		let kp = Random.generate().unwrap();
 		let ap = AccountProvider::transient_provider();
 		assert!(ap.insert_account(kp.secret().clone(), &"test".into()).is_ok());
-		assert!(ap.unlock_account_permanently(kp.address(), "test1".into()).is_err());
-		assert!(ap.unlock_account_permanently(kp.address(), "test".into()).is_ok());
+		assert!(ap.unlock_account_permanently(kp.address(), "test1".into(), None).is_err());
+		assert!(ap.unlock_account_permanently(kp.address(), "test".into(), None).is_ok());
 		assert!(ap.sign(kp.address(), None, Default::default()).is_ok());
 		assert!(ap.sign(kp.address(), None, Default::default()).is_ok());
-		assert!(ap.unlock_account_temporarily(kp.address(), "test".into()).is_ok());
+		assert!(ap.unlock_account_temporarily(kp.address(), "test".into(), None).is_ok());
 		assert!(ap.sign(kp.address(), None, Default::default()).is_ok());
 		assert!(ap.sign(kp.address(), None, Default::default()).is_ok());
 	}
@@ -606,13 +741,35 @@ mod tests {
 		let kp = Random.generate().unwrap();
 		let ap = AccountProvider::transient_provider();
 		assert!(ap.insert_account(kp.secret().clone(), &"test".into()).is_ok());
-		assert!(ap.unlock_account_timed(kp.address(), "test1".into(), Duration::from_secs(60)).is_err());
-		assert!(ap.unlock_account_timed(kp.address(), "test".into(), Duration::from_secs(60)).is_ok());
+		assert!(ap.unlock_account_timed(kp.address(), "test1".into(), Duration::from_secs(60), None).is_err());
+		assert!(ap.unlock_account_timed(kp.address(), "test".into(), Duration::from_secs(60), None).is_ok());
 		assert!(ap.sign(kp.address(), None, Default::default()).is_ok());
 		ap.unlocked.write().get_mut(&StoreAccountRef::root(kp.address())).unwrap().unlock = Unlock::Timed(Instant::now());
 		assert!(ap.sign(kp.address(), None, Default::default()).is_err());
 	}
 
+	#[test]
+	fn unlock_account_timer_capped_and_audited() {
+		let kp = Random.generate().unwrap();
+		let mut ap = AccountProvider::transient_provider();
+		ap.max_unlock_duration = Some(Duration::from_secs(30));
+		assert!(ap.insert_account(kp.secret().clone(), &"test".into()).is_ok());
+		assert!(ap.unlock_account_timed(kp.address(), "test".into(), Duration::from_secs(600), Some("IPC".into())).is_ok());
+		assert!(ap.sign(kp.address(), None, Default::default()).is_ok());
+		assert!(ap.sign(kp.address(), Some("test".into()), Default::default()).is_ok());
+		ap.unlocked.write().get_mut(&StoreAccountRef::root(kp.address())).unwrap().unlock = Unlock::Timed(Instant::now());
+		ap.relock_expired();
+		assert!(!ap.is_unlocked(&kp.address()));
+
+		let events: Vec<_> = ap.audit_log(Some(kp.address()), 10).into_iter().map(|e| (e.event, e.duration, e.origin)).collect();
+		assert_eq!(events, vec![
+			(AuditEvent::UnlockTimed, Some(30), Some("IPC".into())),
+			(AuditEvent::Sign, None, None),
+			(AuditEvent::Relock, None, None),
+		]);
+		assert!(ap.audit_log(Some(Address::from_low_u64_be(1)), 10).is_empty());
+	}
+
 	#[test]
 	fn should_sign_and_return_token() {
 		// given
@@ -640,4 +797,37 @@ mod tests {
 		assert_eq!(ap.accounts_info().unwrap().keys().cloned().collect::<Vec<Address>>(), vec![]);
 		assert_eq!(ap.accounts().unwrap(), vec![]);
 	}
+
+	#[test]
+	fn should_notify_about_key_files_added_externally() {
+		use std::sync::Arc;
+		use parking_lot::Mutex;
+		use tempdir::TempDir;
+		use ethstore::EthStore;
+		use ethstore::accounts_dir::RootDiskDirectory;
+		use super::{AccountEvent, AccountsListener};
+
+		struct Events(Arc<Mutex<Vec<AccountEvent>>>);
+		impl AccountsListener for Events {
+			fn notify(&self, event: &AccountEvent) {
+				self.0.lock().push(event.clone());
+			}
+		}
+
+		// given
+		let tempdir = TempDir::new("").unwrap();
+		let open = || Box::new(EthStore::open(Box::new(RootDiskDirectory::create(tempdir.path()).unwrap())).unwrap());
+		let ap = AccountProvider::new(open(), Default::default());
+		let other = AccountProvider::new(open(), Default::default());
+		let events = Arc::new(Mutex::new(Vec::new()));
+		ap.add_listener(Box::new(Events(events.clone())));
+
+		// when
+		let address = other.new_account(&"test".into()).unwrap();
+
+		// then
+		assert_eq!(ap.refresh().unwrap(), vec![AccountEvent::Added(address)]);
+		assert_eq!(*events.lock(), vec![AccountEvent::Added(address)]);
+		assert_eq!(ap.accounts().unwrap(), vec![address]);
+	}
 }
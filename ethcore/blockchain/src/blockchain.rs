@@ -82,6 +82,19 @@ pub trait BlockChainDB: Send + Sync {
 		self.trace_blooms().reopen()?;
 		Ok(())
 	}
+
+	/// Capture a point-in-time view of the whole database to be backed up. Writes must be paused
+	/// by the caller only until this returns, the backup itself can be written afterwards.
+	fn backup(&self) -> Result<Box<dyn DatabaseBackup + '_>, io::Error> {
+		Err(io::Error::new(io::ErrorKind::Other, "Backups are not supported by this database"))
+	}
+}
+
+/// Consistent view of a `BlockChainDB`, returned by `BlockChainDB::backup`.
+pub trait DatabaseBackup {
+	/// Write the captured database to the given directory, which can later be used in place of
+	/// the original one.
+	fn write(self: Box<Self>, path: &Path) -> Result<(), io::Error>;
 }
 
 /// Generic database handler. This trait contains one function `open`. When called, it opens database with a
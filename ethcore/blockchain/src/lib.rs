@@ -30,7 +30,7 @@ mod update;
 pub mod generator;
 
 pub use crate::{
-	blockchain::{BlockProvider, BlockChain, BlockChainDB, BlockChainDBHandler},
+	blockchain::{BlockProvider, BlockChain, BlockChainDB, BlockChainDBHandler, DatabaseBackup},
 	cache::CacheSize,
 	config::Config,
 	update::ExtrasInsert,
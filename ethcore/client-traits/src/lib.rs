@@ -25,15 +25,17 @@ use bytes::Bytes;
 use call_contract::CallContract;
 use registrar::RegistrarClient;
 use common_types::{
+	backup::BackupStatus,
+	bad_block::BadBlock,
 	basic_account::BasicAccount,
 	block_status::BlockStatus,
 	blockchain_info::BlockChainInfo,
 	BlockNumber,
 	call_analytics::CallAnalytics,
-	chain_notify::{NewBlocks, ChainMessageType},
+	chain_notify::{NewBlocks, ChainMessageType, DeepReorg},
 	client_types::Mode,
 	encoded,
-	engines::{epoch::Transition as EpochTransition, machine::Executed},
+	engines::{epoch::Transition as EpochTransition, fault::ConsensusFault, machine::Executed},
 	errors::{EthcoreError, EthcoreResult},
 	filter::Filter,
 	header::Header,
@@ -54,6 +56,7 @@ use stats;
 use trace::{
 	FlatTrace,
 	localized::LocalizedTrace,
+	StateAccess,
 	VMTrace,
 };
 use common_types::{
@@ -214,15 +217,31 @@ impl Tick for () {}
 
 /// Provides recently seen bad blocks.
 pub trait BadBlocks {
-	/// Returns a list of blocks that were recently not imported because they were invalid.
-	fn bad_blocks(&self) -> Vec<(Unverified, String)>;
+	/// Returns a list of blocks that were recently not imported because they were invalid, oldest first.
+	fn bad_blocks(&self) -> Vec<BadBlock>;
+}
+
+/// Provides the status of the scheduled database backups.
+pub trait DatabaseBackups {
+	/// Returns the status of the scheduled database backups.
+	fn backup_status(&self) -> BackupStatus;
+}
+
+/// Guards against reorgs deeper than the configured limit.
+pub trait ReorgGuard {
+	/// Returns the reorg block import is currently halted on, if any.
+	fn halted_reorg(&self) -> Option<DeepReorg>;
+
+	/// Resume halted block import. The reorg is applied if `accept` is true, otherwise
+	/// the offending block is marked as bad. Returns `false` if import was not halted.
+	fn acknowledge_reorg(&self, accept: bool) -> bool;
 }
 
 
 /// Blockchain database client. Owns and manages a blockchain and a block queue.
 pub trait BlockChainClient:
 	Sync + Send + AccountData + BlockChain + CallContract + RegistrarClient
-	+ ImportBlock + IoClient + BadBlocks
+	+ ImportBlock + IoClient + BadBlocks + ReorgGuard + DatabaseBackups
 {
 	/// Look up the block number for the given block ID.
 	fn block_number(&self, id: BlockId) -> Option<BlockNumber>;
@@ -284,6 +303,21 @@ pub trait BlockChainClient:
 	/// If `after` is set the list starts with the following item.
 	fn list_storage(&self, id: BlockId, account: &Address, after: Option<&H256>, count: Option<u64>) -> Option<Vec<H256>>;
 
+	/// Get a page of at most `limit` storage entries in the block `id`, if fat DB is in operation, otherwise `None`.
+	/// Entries are ordered by the hash of their keys and the page starts with `start` (inclusive).
+	/// Returns `(key, value)` pairs and the key the next page starts with, if there are more entries.
+	fn storage_range(&self, id: BlockId, account: &Address, start: Option<&H256>, limit: u64) -> Option<(Vec<(H256, H256)>, Option<H256>)>;
+
+	/// Keep the state of block `id` from being pruned, until `unpin_state` is called or it isn't accessed
+	/// by hash for a while. Returns the block hash, or `None` if the state is not available.
+	fn pin_state(&self, id: BlockId) -> Option<H256>;
+
+	/// Release a state pinned with `pin_state`. Returns `false` if the state wasn't pinned.
+	fn unpin_state(&self, hash: &H256) -> bool;
+
+	/// Consensus faults of validators recently observed by the engine, oldest first.
+	fn consensus_faults(&self) -> Vec<ConsensusFault>;
+
 	/// Get transaction with given hash.
 	fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction>;
 
@@ -326,6 +360,10 @@ pub trait BlockChainClient:
 	/// Replays all the transactions in a given block for inspection.
 	fn replay_block_transactions(&self, block: BlockId, analytics: CallAnalytics) -> Result<Box<dyn Iterator<Item = (H256, Executed<FlatTrace, VMTrace>)>>, CallError>;
 
+	/// Replays all the transactions in a given block on top of its parent state.
+	/// Returns every account and storage slot read or written by each transaction, in order of access.
+	fn replay_block_with_access_log(&self, block: BlockId) -> Result<Vec<(H256, Vec<StateAccess>)>, CallError>;
+
 	/// Returns traces matching given filter.
 	fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>>;
 
@@ -338,12 +376,18 @@ pub trait BlockChainClient:
 	/// Returns traces created by transaction from block.
 	fn block_traces(&self, trace: BlockId) -> Option<Vec<LocalizedTrace>>;
 
+	/// Returns at most `limit` traces created in block, skipping the first `offset` ones.
+	fn block_traces_page(&self, trace: BlockId, offset: usize, limit: usize) -> Option<Vec<LocalizedTrace>>;
+
 	/// Get last hashes starting from best block.
 	fn last_hashes(&self) -> LastHashes;
 
 	/// List all ready transactions that should be propagated to other peers.
 	fn transactions_to_propagate(&self) -> Vec<Arc<VerifiedTransaction>>;
 
+	/// Get a transaction from the transaction queue by hash.
+	fn pooled_transaction(&self, hash: &H256) -> Option<Arc<VerifiedTransaction>>;
+
 	/// Sorted list of transaction gas prices from at least last sample_size blocks.
 	fn gas_price_corpus(&self, sample_size: usize) -> stats::Corpus<U256> {
 		let mut h = self.chain_info().best_block_hash;
@@ -487,6 +531,12 @@ pub trait StateClient {
 	/// Otherwise, this can fail (but may not) if the DB prunes state or the block
 	/// is unknown.
 	fn state_at(&self, id: BlockId) -> Option<Self::State>;
+
+	/// Attempt to get a copy of the state with the given root.
+	///
+	/// Only archive nodes keep states which are not referenced by recent blocks,
+	/// so this fails if the DB prunes state or the root is unknown.
+	fn state_at_root(&self, root: H256) -> Option<Self::State>;
 }
 
 /// Extended client interface for providing proofs of the state.
@@ -549,6 +599,11 @@ pub trait ChainNotify: Send + Sync {
 	fn transactions_received(&self, _txs: &[UnverifiedTransaction], _peer_id: usize) {
 		// does nothing by default
 	}
+
+	/// fires when block import is halted because of a reorg deeper than allowed
+	fn deep_reorg(&self, _reorg: &DeepReorg) {
+		// does nothing by default
+	}
 }
 
 /// Provides a method for importing/exporting blocks
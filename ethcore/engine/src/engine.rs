@@ -18,6 +18,7 @@
 
 use std::sync::{Weak, Arc};
 use std::collections::BTreeMap;
+use std::time::Duration;
 
 use builtin::Builtin;
 use common_types::{
@@ -27,6 +28,7 @@ use common_types::{
 	engines::{
 		Seal, SealingState, Headers, PendingTransitionStore,
 		params::CommonParams,
+		fault::ConsensusFault,
 		machine as machine_types,
 		machine::{AuxiliaryData, AuxiliaryRequest},
 	},
@@ -320,6 +322,12 @@ pub trait Engine: Sync + Send {
 	/// Trigger next step of the consensus engine.
 	fn step(&self) {}
 
+	/// Set for how long consensus faults observed by the engine are retained.
+	fn set_consensus_faults_window(&self, _window: Duration) {}
+
+	/// Consensus faults attributed to validators within the retention window, oldest first.
+	fn consensus_faults(&self) -> Vec<ConsensusFault> { Vec::new() }
+
 	/// Snapshot mode for the engine: Unsupported, PoW or PoA
 	fn snapshot_mode(&self) -> Snapshotting { Snapshotting::Unsupported }
 
@@ -353,9 +361,21 @@ pub trait Engine: Sync + Send {
 		None
 	}
 
+	/// Gas limit blocks produced on top of `parent` move towards, as fast as the gas limit bound divisor allows.
+	/// Takes precedence over the miner's gas floor and ceiling targets.
+	fn gas_limit_target(&self, _parent: &Header) -> Option<U256> {
+		self.params().gas_limit_target
+	}
+
 	/// Get the general parameters of the chain.
 	fn params(&self) -> &CommonParams;
 
+	/// Blocks at which the chain rules change, unordered and possibly repeated. Used to compute the
+	/// EIP-2124 fork identifier.
+	fn fork_blocks(&self) -> Vec<BlockNumber> {
+		self.params().fork_blocks()
+	}
+
 	/// Get the EVM schedule for the given block number.
 	fn schedule(&self, block_number: BlockNumber) -> Schedule {
 		self.machine().schedule(block_number)
@@ -0,0 +1,121 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Collector of consensus faults reported by engines.
+
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use common_types::{
+	engines::fault::{ConsensusFault, ConsensusFaultKind},
+	header::Header,
+};
+use ethereum_types::Address;
+use parking_lot::Mutex;
+
+/// Default period for which faults are retained.
+pub const DEFAULT_FAULTS_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+/// Maximal number of retained faults, the oldest ones are dropped first.
+const MAX_FAULTS: usize = 10_000;
+
+/// Keeps consensus faults observed within a time window.
+pub struct FaultCollector {
+	window: Mutex<Duration>,
+	faults: Mutex<VecDeque<ConsensusFault>>,
+}
+
+impl Default for FaultCollector {
+	fn default() -> Self {
+		FaultCollector {
+			window: Mutex::new(DEFAULT_FAULTS_WINDOW),
+			faults: Mutex::new(VecDeque::new()),
+		}
+	}
+}
+
+impl FaultCollector {
+	/// Record a fault of `validator` revealed by the block with given header.
+	pub fn report(&self, validator: Address, kind: ConsensusFaultKind, header: &Header) {
+		let now = unix_now();
+		let mut faults = self.faults.lock();
+		if faults.len() == MAX_FAULTS {
+			faults.pop_front();
+		}
+		faults.push_back(ConsensusFault {
+			validator,
+			kind,
+			block_number: header.number(),
+			block_hash: header.hash(),
+			timestamp: now,
+		});
+		self.prune(&mut faults, now);
+	}
+
+	/// Set the period for which faults are retained.
+	pub fn set_window(&self, window: Duration) {
+		*self.window.lock() = window;
+	}
+
+	/// Returns faults observed within the window, oldest first.
+	pub fn faults(&self) -> Vec<ConsensusFault> {
+		let mut faults = self.faults.lock();
+		self.prune(&mut faults, unix_now());
+		faults.iter().cloned().collect()
+	}
+
+	fn prune(&self, faults: &mut VecDeque<ConsensusFault>, now: u64) {
+		let oldest = now.saturating_sub(self.window.lock().as_secs());
+		while faults.front().map_or(false, |fault| fault.timestamp < oldest) {
+			faults.pop_front();
+		}
+	}
+}
+
+fn unix_now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use common_types::{engines::fault::ConsensusFaultKind, header::Header};
+	use ethereum_types::Address;
+	use super::FaultCollector;
+
+	#[test]
+	fn should_keep_faults_within_window() {
+		let collector = FaultCollector::default();
+		let mut header = Header::new();
+		header.set_number(5);
+
+		collector.report(Address::from_low_u64_be(1), ConsensusFaultKind::SkippedStep, &header);
+		collector.report(Address::from_low_u64_be(2), ConsensusFaultKind::Equivocation, &header);
+
+		let faults = collector.faults();
+		assert_eq!(faults.len(), 2);
+		assert_eq!(faults[0].validator, Address::from_low_u64_be(1));
+		assert_eq!(faults[1].kind, ConsensusFaultKind::Equivocation);
+		assert_eq!(faults[1].block_number, 5);
+		assert_eq!(faults[1].block_hash, header.hash());
+
+		// faults reported earlier than the window are dropped
+		for fault in collector.faults.lock().iter_mut() {
+			fault.timestamp -= 120;
+		}
+		collector.set_window(Duration::from_secs(60));
+		assert!(collector.faults().is_empty());
+	}
+}
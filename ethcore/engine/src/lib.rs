@@ -17,6 +17,7 @@
 //! This crate defines the Engine trait and related types.
 
 mod engine;
+pub mod faults;
 pub mod signer;
 
 pub use crate::engine::{
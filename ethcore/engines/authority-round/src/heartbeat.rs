@@ -0,0 +1,120 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Validator liveness heartbeats.
+//!
+//! If a heartbeat contract is configured, a sealing validator periodically calls its `heartbeat()`
+//! function from the sealing account. Governance contracts can then detect validators which stopped
+//! sending heartbeats and remove them from the validator set.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use client_traits::{EngineClient, TransactionRequest};
+use common_types::BlockNumber;
+use ethabi_contract::use_contract;
+use ethereum_types::Address;
+use log::{debug, warn};
+use parking_lot::Mutex;
+
+use_contract!(validator_heartbeat, "../../res/contracts/validator_heartbeat.json");
+
+/// Sends rate-limited heartbeat transactions to the heartbeat contract.
+pub struct Heartbeat {
+	/// Heartbeat contract addresses by the block number they become active at.
+	contracts: BTreeMap<BlockNumber, Address>,
+	/// Minimum time between two heartbeats.
+	interval: Duration,
+	/// Time the last heartbeat was sent at.
+	last_sent: Mutex<Option<Instant>>,
+}
+
+impl Heartbeat {
+	/// Create new heartbeat sender.
+	pub fn new(contracts: BTreeMap<BlockNumber, Address>, interval: Duration) -> Self {
+		Heartbeat {
+			contracts,
+			interval,
+			last_sent: Mutex::new(None),
+		}
+	}
+
+	/// Returns the heartbeat contract active at given block, if any.
+	fn contract_at(&self, block: BlockNumber) -> Option<Address> {
+		self.contracts.range(..=block).last().map(|(_, address)| *address)
+	}
+
+	/// Returns true and records the attempt if a heartbeat is due at `now`.
+	fn take_due(&self, now: Instant) -> bool {
+		let mut last_sent = self.last_sent.lock();
+		match *last_sent {
+			Some(last) if now.duration_since(last) < self.interval => false,
+			_ => {
+				*last_sent = Some(now);
+				true
+			}
+		}
+	}
+
+	/// Send a heartbeat transaction if a contract is active at the best block and the interval elapsed.
+	pub fn maybe_send(&self, client: &dyn EngineClient) {
+		let contract = match self.contract_at(client.chain_info().best_block_number) {
+			Some(contract) => contract,
+			None => return,
+		};
+		let full_client = match client.as_full_client() {
+			Some(full_client) => full_client,
+			None => return,
+		};
+		if !self.take_due(Instant::now()) {
+			return;
+		}
+
+		let data = validator_heartbeat::functions::heartbeat::encode_input();
+		match full_client.transact(TransactionRequest::call(contract, data)) {
+			Ok(()) => debug!(target: "engine", "Sent heartbeat to {}", contract),
+			Err(e) => warn!(target: "engine", "Unable to send heartbeat to {}: {}", contract, e),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+
+	use ethereum_types::Address;
+	use super::Heartbeat;
+
+	#[test]
+	fn should_pick_contract_by_transition() {
+		let contracts = vec![(10, Address::from_low_u64_be(1)), (20, Address::from_low_u64_be(2))];
+		let heartbeat = Heartbeat::new(contracts.into_iter().collect(), Duration::from_secs(60));
+
+		assert_eq!(heartbeat.contract_at(9), None);
+		assert_eq!(heartbeat.contract_at(10), Some(Address::from_low_u64_be(1)));
+		assert_eq!(heartbeat.contract_at(25), Some(Address::from_low_u64_be(2)));
+	}
+
+	#[test]
+	fn should_rate_limit_heartbeats() {
+		let heartbeat = Heartbeat::new(Default::default(), Duration::from_secs(60));
+		let now = Instant::now();
+
+		assert!(heartbeat.take_due(now));
+		assert!(!heartbeat.take_due(now + Duration::from_secs(59)));
+		assert!(heartbeat.take_due(now + Duration::from_secs(60)));
+	}
+}
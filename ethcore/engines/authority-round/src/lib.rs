@@ -42,6 +42,7 @@ use std::u64;
 
 use client_traits::{EngineClient, ForceUpdateSealing, TransactionRequest};
 use engine::{Engine, ConstructedVerifier};
+use engine::faults::FaultCollector;
 use block_gas_limit::block_gas_limit;
 use block_reward::{self, BlockRewardContract, RewardKind};
 use ethjson;
@@ -59,7 +60,7 @@ use io::{IoContext, IoHandler, TimerToken, IoService};
 use itertools::{self, Itertools};
 use rand::rngs::OsRng;
 use rlp::{encode, Decodable, DecoderError, Encodable, RlpStream, Rlp};
-use ethereum_types::{H256, H520, Address, U128, U256};
+use ethereum_types::{BigEndianHash, H256, H520, Address, U128, U256};
 use parking_lot::{Mutex, RwLock};
 use time_utils::CheckedSystemTime;
 use common_types::{
@@ -72,6 +73,7 @@ use common_types::{
 		PendingTransitionStore,
 		Seal,
 		SealingState,
+		fault::{ConsensusFault, ConsensusFaultKind},
 		machine::{Call, AuxiliaryData},
 	},
 	errors::{BlockError, EthcoreError as Error, EngineError},
@@ -83,10 +85,12 @@ use unexpected::{Mismatch, OutOfBounds};
 use validator_set::{ValidatorSet, SimpleList, new_validator_set};
 
 mod finality;
+mod heartbeat;
 mod randomness;
 pub(crate) mod util;
 
 use self::finality::RollingFinality;
+use self::heartbeat::Heartbeat;
 
 /// `AuthorityRound` params.
 pub struct AuthorityRoundParams {
@@ -125,9 +129,18 @@ pub struct AuthorityRoundParams {
 	pub strict_empty_steps_transition: u64,
 	/// If set, enables random number contract integration. It maps the transition block to the contract address.
 	pub randomness_contract_address: BTreeMap<u64, Address>,
+	/// Block from which authors are required to include their due randomness contributions.
+	/// See `AuthorityRound::close_randomness_phase` for the liveness implications.
+	pub randomness_contribution_transition: u64,
+	/// Account and storage slot to which the random seed is copied after every block.
+	pub randomness_beacon: Option<(Address, H256)>,
 	/// The addresses of contracts that determine the block gas limit with their associated block
 	/// numbers.
 	pub block_gas_limit_contract_transitions: BTreeMap<u64, Address>,
+	/// If set, enables heartbeat contract integration. It maps the transition block to the contract address.
+	pub heartbeat_contract_address: BTreeMap<u64, Address>,
+	/// Minimum time between two heartbeat transactions.
+	pub heartbeat_interval: Duration,
 }
 
 const U16_MAX: usize = ::std::u16::MAX as usize;
@@ -135,6 +148,9 @@ const U16_MAX: usize = ::std::u16::MAX as usize;
 /// The number of recent block hashes for which the gas limit override is memoized.
 const GAS_LIMIT_OVERRIDE_CACHE_CAPACITY: usize = 10;
 
+/// Default minimum time between two heartbeat transactions, in seconds.
+const DEFAULT_HEARTBEAT_INTERVAL: u64 = 600;
+
 impl From<ethjson::spec::AuthorityRoundParams> for AuthorityRoundParams {
 	fn from(p: ethjson::spec::AuthorityRoundParams) -> Self {
 		let map_step_duration = |u: ethjson::uint::Uint| {
@@ -193,6 +209,11 @@ impl From<ethjson::spec::AuthorityRoundParams> for AuthorityRoundParams {
 			.into_iter()
 			.map(|(block_num, address)| (block_num.into(), address.into()))
 			.collect();
+		let heartbeat_contract_address: BTreeMap<_, _> = p.heartbeat_contract_address
+			.unwrap_or_default()
+			.into_iter()
+			.map(|(block_num, address)| (block_num.into(), address.into()))
+			.collect();
 		AuthorityRoundParams {
 			step_durations,
 			validators: new_validator_set(p.validators),
@@ -209,7 +230,14 @@ impl From<ethjson::spec::AuthorityRoundParams> for AuthorityRoundParams {
 			two_thirds_majority_transition: p.two_thirds_majority_transition.map_or_else(BlockNumber::max_value, Into::into),
 			strict_empty_steps_transition: p.strict_empty_steps_transition.map_or(0, Into::into),
 			randomness_contract_address,
+			randomness_contribution_transition: p.randomness_contribution_transition.map_or_else(u64::max_value, Into::into),
+			randomness_beacon: p.randomness_beacon.map(|beacon| {
+				let slot: U256 = beacon.slot.into();
+				(beacon.address.into(), BigEndianHash::from_uint(&slot))
+			}),
 			block_gas_limit_contract_transitions,
+			heartbeat_contract_address,
+			heartbeat_interval: Duration::from_secs(p.heartbeat_interval.map_or(DEFAULT_HEARTBEAT_INTERVAL, Into::into)),
 		}
 	}
 }
@@ -559,7 +587,7 @@ pub struct AuthorityRound {
 	transition_service: IoService<()>,
 	step: Arc<PermissionedStep>,
 	client: Arc<RwLock<Option<Weak<dyn EngineClient>>>>,
-	signer: RwLock<Option<Box<dyn EngineSigner>>>,
+	signer: Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
 	validators: Box<dyn ValidatorSet>,
 	validate_score_transition: u64,
 	validate_step_transition: u64,
@@ -579,10 +607,18 @@ pub struct AuthorityRound {
 	received_step_hashes: RwLock<BTreeMap<(u64, Address), H256>>,
 	/// If set, enables random number contract integration. It maps the transition block to the contract address.
 	randomness_contract_address: BTreeMap<u64, Address>,
+	/// Block from which authors are required to include their due randomness contributions.
+	randomness_contribution_transition: u64,
+	/// Account and storage slot to which the random seed is copied after every block.
+	randomness_beacon: Option<(Address, H256)>,
 	/// The addresses of contracts that determine the block gas limit.
 	block_gas_limit_contract_transitions: BTreeMap<u64, Address>,
 	/// Memoized gas limit overrides, by block hash.
 	gas_limit_override_cache: Mutex<LruCache<H256, Option<U256>>>,
+	/// Faults of validators observed while verifying blocks.
+	faults: FaultCollector,
+	/// Sends periodic liveness heartbeats while sealing.
+	heartbeat: Arc<Heartbeat>,
 }
 
 // header-chain validator.
@@ -867,7 +903,7 @@ impl AuthorityRound {
 				transition_service: IoService::<()>::start()?,
 				step: Arc::new(PermissionedStep { inner: step, can_propose: AtomicBool::new(true) }),
 				client: Arc::new(RwLock::new(None)),
-				signer: RwLock::new(None),
+				signer: Arc::new(RwLock::new(None)),
 				validators: our_params.validators,
 				validate_score_transition: our_params.validate_score_transition,
 				validate_step_transition: our_params.validate_step_transition,
@@ -885,8 +921,12 @@ impl AuthorityRound {
 				machine,
 				received_step_hashes: RwLock::new(Default::default()),
 				randomness_contract_address: our_params.randomness_contract_address,
+				randomness_contribution_transition: our_params.randomness_contribution_transition,
+				randomness_beacon: our_params.randomness_beacon,
 				block_gas_limit_contract_transitions: our_params.block_gas_limit_contract_transitions,
 				gas_limit_override_cache: Mutex::new(LruCache::new(GAS_LIMIT_OVERRIDE_CACHE_CAPACITY)),
+				faults: FaultCollector::default(),
+				heartbeat: Arc::new(Heartbeat::new(our_params.heartbeat_contract_address, our_params.heartbeat_interval)),
 			});
 
 		// Do not initialize timeouts for tests.
@@ -894,6 +934,8 @@ impl AuthorityRound {
 			let handler = TransitionHandler {
 				step: engine.step.clone(),
 				client: engine.client.clone(),
+				signer: engine.signer.clone(),
+				heartbeat: engine.heartbeat.clone(),
 			};
 			engine.transition_service.register_handler(Arc::new(handler))?;
 		}
@@ -994,21 +1036,28 @@ impl AuthorityRound {
 			return;
 		}
 
-		if let (true, Some(me)) = (current_step > parent_step + 1, self.address()) {
+		if current_step > parent_step + 1 {
 			debug!(target: "engine", "Author {} built block with step gap. current step: {}, parent step: {}",
 				header.author(), current_step, parent_step);
+			let me = self.address();
 			let mut reported = HashSet::new();
 			for step in parent_step + 1..current_step {
 				let skipped_primary = step_proposer(validators, header.parent_hash(), step);
-				// Do not report this signer.
-				if skipped_primary != me {
-					// Stop reporting once validators start repeating.
-					if !reported.insert(skipped_primary) { break; }
-					trace!(target: "engine", "Reporting benign misbehaviour (cause: skipped step) at block #{}, epoch set number {}, step proposer={:#x}. Own address: {}",
-						header.number(), set_number, skipped_primary, me);
-					self.validators.report_benign(&skipped_primary, set_number, header.number());
-				} else {
-					trace!(target: "engine", "Primary that skipped is self, not self-reporting. Own address: {}", me);
+				// Stop reporting once validators start repeating.
+				if !reported.insert(skipped_primary) { break; }
+				self.faults.report(skipped_primary, ConsensusFaultKind::SkippedStep, header);
+
+				match me {
+					// Do not report this signer.
+					Some(me) if skipped_primary == me => {
+						trace!(target: "engine", "Primary that skipped is self, not self-reporting. Own address: {}", me);
+					},
+					Some(me) => {
+						trace!(target: "engine", "Reporting benign misbehaviour (cause: skipped step) at block #{}, epoch set number {}, step proposer={:#x}. Own address: {}",
+							header.number(), set_number, skipped_primary, me);
+						self.validators.report_benign(&skipped_primary, set_number, header.number());
+					},
+					None => {},
 				}
 			}
 		}
@@ -1116,6 +1165,45 @@ impl AuthorityRound {
 		let tx_request = TransactionRequest::call(contract_addr, data).gas_price(U256::zero()).nonce(nonce);
 		Ok(vec![full_client.create_transaction(tx_request)?])
 	}
+
+	/// Verify the block author's randomness contribution and update the randomness beacon.
+	///
+	/// Enforcing contributions trades liveness for randomness that authors can't bias by withholding
+	/// their numbers: a validator that is unable to include its commit or reveal (e.g. because its
+	/// node lost the encrypted secret, the transaction fails, or its blocks run out of gas) produces
+	/// only invalid blocks until the phase ends, so its steps are skipped. If too many validators are
+	/// affected at once, no blocks are produced for the rest of the phase.
+	fn close_randomness_phase(&self, block: &mut ExecutedBlock, parent: &Header) -> Result<(), Error> {
+		let contract_addr = match self.randomness_contract_address.range(..=block.header.number()).last() {
+			Some((_, &contract_addr)) => contract_addr,
+			None => return Ok(()), // No randomness contract.
+		};
+
+		if block.header.number() >= self.randomness_contribution_transition {
+			let client = self.client.read().as_ref().and_then(|weak| weak.upgrade()).ok_or_else(|| {
+				debug!(target: "engine", "Unable to verify randomness contribution: missing client ref.");
+				EngineError::RequiresClient
+			})?;
+			// The phase is determined by the parent state, the contribution must be visible after this block.
+			let before = util::BoundContract::new(&*client, BlockId::Hash(parent.hash()), contract_addr);
+			let author = *block.header.author();
+			let mut call = engine::default_system_or_code_call(&self.machine, block);
+			randomness::verify_contribution(&before, &mut call, contract_addr, author)
+				.map_err(|err| EngineError::Custom(format!("Randomness error in verify_contribution(): {}", err)))?;
+		}
+
+		if let Some((address, slot)) = self.randomness_beacon {
+			let seed = {
+				let mut call = engine::default_system_or_code_call(&self.machine, block);
+				randomness::current_seed(&mut call, contract_addr)
+					.map_err(|err| EngineError::Custom(format!("Randomness error in current_seed(): {}", err)))?
+			};
+			trace!(target: "engine", "Block {}: randomness beacon set to {}", block.header.number(), seed);
+			block.state.set_storage(&address, slot, BigEndianHash::from_uint(&seed))?;
+		}
+
+		Ok(())
+	}
 }
 
 fn unix_now() -> Duration {
@@ -1125,6 +1213,8 @@ fn unix_now() -> Duration {
 struct TransitionHandler {
 	step: Arc<PermissionedStep>,
 	client: Arc<RwLock<Option<Weak<dyn EngineClient>>>>,
+	signer: Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+	heartbeat: Arc<Heartbeat>,
 }
 
 const ENGINE_TIMEOUT_TOKEN: TimerToken = 23;
@@ -1141,9 +1231,11 @@ impl IoHandler<()> for TransitionHandler {
 			// NOTE we might be lagging by couple of steps in case the timeout
 			// has not been called fast enough.
 			// Make sure to advance up to the actual step.
+			let mut stepped = false;
 			while AsMillis::as_millis(&self.step.inner.duration_remaining()) == 0 {
 				self.step.inner.increment();
 				self.step.can_propose.store(true, AtomicOrdering::SeqCst);
+				stepped = true;
 				if let Some(ref weak) = *self.client.read() {
 					if let Some(c) = weak.upgrade() {
 						c.update_sealing(ForceUpdateSealing::No);
@@ -1151,6 +1243,14 @@ impl IoHandler<()> for TransitionHandler {
 				}
 			}
 
+			// Only validators which are sealing send heartbeats.
+			if stepped && self.signer.read().is_some() {
+				let client = self.client.read().as_ref().and_then(|weak| weak.upgrade());
+				if let Some(c) = client {
+					self.heartbeat.maybe_send(&*c);
+				}
+			}
+
 			let next_run_at = Duration::from_millis(
 				AsMillis::as_millis(&self.step.inner.duration_remaining()) >> 2
 			);
@@ -1171,6 +1271,14 @@ impl Engine for AuthorityRound {
 		header_expected_seal_fields(header, self.empty_steps_transition)
 	}
 
+	fn set_consensus_faults_window(&self, window: Duration) {
+		self.faults.set_window(window);
+	}
+
+	fn consensus_faults(&self) -> Vec<ConsensusFault> {
+		self.faults.faults()
+	}
+
 	fn step(&self) {
 		self.step.inner.increment();
 		self.step.can_propose.store(true, AtomicOrdering::SeqCst);
@@ -1473,6 +1581,8 @@ impl Engine for AuthorityRound {
 			info!(target: "engine", "Block {}: Transitioning to 2/3 quorum.", self.two_thirds_majority_transition);
 		}
 
+		self.close_randomness_phase(block, parent)?;
+
 		if block.header.number() >= self.empty_steps_transition {
 			let empty_steps = if block.header.seal().is_empty() {
 				// this is a new block, calculate rewards based on the empty steps messages we have accumulated
@@ -1522,6 +1632,8 @@ impl Engine for AuthorityRound {
 
 		match verify_timestamp(&self.step.inner, header_step(header, self.empty_steps_transition)?) {
 			Err(BlockError::InvalidSeal) => {
+				self.faults.report(*header.author(), ConsensusFaultKind::InvalidSeal, header);
+
 				// This check runs in Phase 1 where there is no guarantee that the parent block is
 				// already imported, therefore the call to `epoch_set` may fail. In that case we
 				// won't report the misbehavior but this is not a concern because:
@@ -1557,6 +1669,7 @@ impl Engine for AuthorityRound {
 			|| (header.number() >= self.validate_step_transition && step <= parent_step) {
 			warn!(target: "engine", "Multiple blocks proposed for step {}.", parent_step);
 
+			self.faults.report(*header.author(), ConsensusFaultKind::Equivocation, header);
 			self.validators.report_malicious(header.author(), set_number, header.number(), Default::default());
 			Err(EngineError::DoubleVote(*header.author()))?;
 		}
@@ -1566,6 +1679,7 @@ impl Engine for AuthorityRound {
 		let new_hash = header.hash();
 		if self.received_step_hashes.read().get(&received_step_key).map_or(false, |h| *h != new_hash) {
 			trace!(target: "engine", "Validator {} produced sibling blocks in the same step", header.author());
+			self.faults.report(*header.author(), ConsensusFaultKind::Equivocation, header);
 			self.validators.report_malicious(header.author(), set_number, header.number(), Default::default());
 		} else {
 			self.received_step_hashes.write().insert(received_step_key, new_hash);
@@ -1625,6 +1739,7 @@ impl Engine for AuthorityRound {
 			match validate_empty_steps() {
 				Ok(len) => len,
 				Err(err) => {
+					self.faults.report(*header.author(), ConsensusFaultKind::InvalidSeal, header);
 					trace!(target: "engine", "Reporting benign misbehaviour (cause: invalid empty steps) at block #{}, epoch set number {}. Own address: {}",
 						header.number(), set_number, self.address().unwrap_or_default());
 					self.validators.report_benign(header.author(), set_number, header.number());
@@ -1656,6 +1771,7 @@ impl Engine for AuthorityRound {
 		let res = verify_external(header, &*validators, self.empty_steps_transition);
 		match res {
 			Err(Error::Engine(EngineError::NotProposer(_))) => {
+				self.faults.report(*header.author(), ConsensusFaultKind::InvalidSeal, header);
 				trace!(target: "engine", "Reporting benign misbehaviour (cause: block from incorrect proposer) at block #{}, epoch set number {}. Own address: {}",
 					header.number(), set_number, self.address().unwrap_or_default());
 				self.validators.report_benign(header.author(), set_number, header.number());
@@ -1917,7 +2033,7 @@ mod tests {
 	use parity_crypto::publickey::Signature;
 	use common_types::{
 		header::Header,
-		engines::{Seal, params::CommonParams},
+		engines::{Seal, fault::ConsensusFaultKind, params::CommonParams},
 		ids::BlockId,
 		errors::{EthcoreError as Error, EngineError},
 		transaction::{Action, Transaction},
@@ -1963,7 +2079,11 @@ mod tests {
 			strict_empty_steps_transition: 0,
 			two_thirds_majority_transition: 0,
 			randomness_contract_address: BTreeMap::new(),
+			randomness_contribution_transition: u64::max_value(),
+			randomness_beacon: None,
 			block_gas_limit_contract_transitions: BTreeMap::new(),
+			heartbeat_contract_address: BTreeMap::new(),
+			heartbeat_interval: Duration::from_secs(DEFAULT_HEARTBEAT_INTERVAL),
 		};
 
 		// mutate aura params
@@ -2228,6 +2348,12 @@ mod tests {
 		header.set_number(2);
 		assert!(aura.verify_block_family(&header, &parent_header).is_ok());
 		assert_eq!(last_benign.load(AtomicOrdering::SeqCst), 2);
+
+		// Faults are collected even if the node is not a validator.
+		let faults = aura.consensus_faults();
+		assert_eq!(faults.len(), 2);
+		assert!(faults.iter().all(|f| f.validator == validator1 && f.kind == ConsensusFaultKind::SkippedStep));
+		assert_eq!(faults[1].block_number, 2);
 	}
 
 	#[test]
@@ -2721,7 +2847,7 @@ mod tests {
 
 		let addr1 = tap.insert_account(keccak("1").into(), &"1".into()).unwrap();
 		// Unlock account so that the engine can decrypt the secret.
-		tap.unlock_account_permanently(addr1, "1".into()).expect("unlock");
+		tap.unlock_account_permanently(addr1, "1".into(), None).expect("unlock");
 
 		let signer = Box::new((tap.clone(), addr1, "1".into()));
 		client.miner().set_author(Author::Sealer(signer.clone()));
@@ -2754,6 +2880,80 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn randomness_contribution_required() {
+		use_contract!(rand_contract, "../../res/contracts/test_authority_round_random.json");
+
+		let spec = spec::new_test_round_randomness_contribution();
+		let engine = &*spec.engine;
+		let genesis_header = spec.genesis_header();
+		let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+
+		let client = generate_dummy_client_with_spec(spec::new_test_round_randomness_contribution);
+		engine.register_client(Arc::downgrade(&client) as _);
+
+		// A block without the author's due commitment is rejected.
+		let addr1 = Address::from_low_u64_be(1);
+		let b1 = OpenBlock::new(
+			engine,
+			Default::default(),
+			false,
+			db,
+			&genesis_header,
+			last_hashes,
+			addr1,
+			(3141562.into(), 31415620.into()),
+			vec![],
+			false,
+		).unwrap();
+		let err = b1.close_and_lock().map(|_| ()).unwrap_err();
+		assert!(err.to_string().contains("did not make the expected randomness contribution"), "{}", err);
+
+		// Our own blocks include the commitment and pass.
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let addr2 = tap.insert_account(keccak("1").into(), &"1".into()).unwrap();
+		tap.unlock_account_permanently(addr2, "1".into(), None).expect("unlock");
+		let signer = Box::new((tap.clone(), addr2, "1".into()));
+		client.miner().set_author(Author::Sealer(signer.clone()));
+		client.miner().set_gas_range_target((U256::from(1000000), U256::from(1000000)));
+		let engine = client.engine();
+		engine.set_signer(Some(signer));
+		engine.register_client(Arc::downgrade(&client) as _);
+
+		engine.step();
+		let contract_addr = Address::from_str("0000000000000000000000000000000000000042").unwrap();
+		let bc = BoundContract::new(&*client, BlockId::Latest, contract_addr);
+		assert!(bc.call_const(rand_contract::functions::is_committed::call(0, addr2)).unwrap());
+	}
+
+	#[test]
+	fn randomness_beacon() {
+		let spec = spec::new_test_round_randomness_beacon();
+		let engine = &*spec.engine;
+		let genesis_header = spec.genesis_header();
+		let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+
+		// The test contract returns 42 for every call, including `currentSeed()`.
+		let b1 = OpenBlock::new(
+			engine,
+			Default::default(),
+			false,
+			db,
+			&genesis_header,
+			last_hashes,
+			Address::from_low_u64_be(1),
+			(3141562.into(), 31415620.into()),
+			vec![],
+			false,
+		).unwrap();
+		let b1 = b1.close_and_lock().unwrap();
+
+		let beacon = Address::from_str("0000000000000000000000000000000000000043").unwrap();
+		assert_eq!(b1.state.storage_at(&beacon, &H256::from_low_u64_be(1)).unwrap(), H256::from_low_u64_be(42));
+	}
+
 	#[test]
 	fn extra_info_from_seal() {
 		let (spec, tap, accounts) = setup_empty_steps();
@@ -66,6 +66,9 @@
 //! 1. `RandomnessPhase::load()` the phase from the blockchain data.
 //! 2. Call `RandomnessPhase::advance()`.
 //!
+//! When importing blocks, `verify_contribution()` checks that the block's author made the commitment
+//! or revelation that was due, and `current_seed()` reads the accumulated seed for the randomness beacon.
+//!
 //! A production implementation of a randomness contract can be found here:
 //! https://github.com/poanetwork/posdao-contracts/blob/4fddb108993d4962951717b49222327f3d94275b/contracts/RandomAuRa.sol
 
@@ -79,6 +82,7 @@ use parity_crypto::publickey::{ecies, Error as CryptoError};
 use parity_bytes::Bytes;
 use rand::Rng;
 use engine::signer::EngineSigner;
+use engine::{SystemOrCodeCall, SystemOrCodeCallKind};
 
 use crate::util::{BoundContract, CallError};
 
@@ -130,6 +134,9 @@ pub enum PhaseError {
 	/// Failed to get the engine signer's public key.
 	#[display(fmt = "Failed to get the engine signer's public key")]
 	MissingPublicKey,
+	/// The block author did not commit or reveal although it was their turn.
+	#[display(fmt = "Block author {} did not make the expected randomness contribution", _0)]
+	MissingContribution(Address),
 }
 
 impl From<CryptoError> for PhaseError {
@@ -255,3 +262,47 @@ impl RandomnessPhase {
 		}
 	}
 }
+
+/// Verify that the author of a block made the randomness contribution that was due in it.
+///
+/// `before` is bound to the parent block, while `after` runs calls against the state resulting from
+/// the block's transactions. An author who was expected to commit (or reveal) must have done so.
+pub fn verify_contribution(
+	before: &BoundContract,
+	after: &mut SystemOrCodeCall,
+	contract_addr: Address,
+	author: Address,
+) -> Result<(), PhaseError> {
+	let contributed = match RandomnessPhase::load(before, author)? {
+		RandomnessPhase::Waiting | RandomnessPhase::Committed => return Ok(()),
+		RandomnessPhase::BeforeCommit => {
+			let round = before
+				.call_const(aura_random::functions::current_collect_round::call())
+				.map_err(PhaseError::LoadFailed)?;
+			system_call(after, contract_addr, aura_random::functions::is_committed::call(round, author))?
+		}
+		RandomnessPhase::Reveal { round, .. } => {
+			system_call(after, contract_addr, aura_random::functions::sent_reveal::call(round, author))?
+		}
+	};
+
+	if contributed {
+		Ok(())
+	} else {
+		Err(PhaseError::MissingContribution(author))
+	}
+}
+
+/// Read the random seed accumulated by the contract so far.
+pub fn current_seed(call: &mut SystemOrCodeCall, contract_addr: Address) -> Result<U256, PhaseError> {
+	system_call(call, contract_addr, aura_random::functions::current_seed::call())
+}
+
+fn system_call<D>(call: &mut SystemOrCodeCall, contract_addr: Address, (data, decoder): (Bytes, D)) -> Result<D::Output, PhaseError>
+where
+	D: ethabi::FunctionOutputDecoder,
+{
+	let output = call(SystemOrCodeCallKind::Address(contract_addr), data)
+		.map_err(|e| PhaseError::LoadFailed(CallError::CallFailed(e)))?;
+	decoder.decode(&output).map_err(|e| PhaseError::LoadFailed(CallError::DecodeFailed(e)))
+}
@@ -15,8 +15,13 @@
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 //! A blockchain engine that supports a basic, non-BFT proof-of-authority.
+//!
+//! Optionally the seal carries signatures of several authorities, which all have to be
+//! part of the validator set, and at least `signatureThreshold` of them are required.
 
+use std::cmp;
 use std::sync::Weak;
+use std::time::Duration;
 
 use common_types::{
 	header::Header,
@@ -25,16 +30,17 @@ use common_types::{
 		PendingTransitionStore,
 		SealingState,
 		Seal,
+		fault::{ConsensusFault, ConsensusFaultKind},
 		params::CommonParams,
 		machine::{AuxiliaryData, Call},
 	},
 	errors::{EngineError, BlockError, EthcoreError as Error},
 };
 use client_traits::EngineClient;
-use ethereum_types::{H256, H520};
+use ethereum_types::{Address, H256, H520};
 use parking_lot::RwLock;
-use engine::{Engine, ConstructedVerifier, signer::EngineSigner};
-use parity_crypto::publickey::Signature;
+use engine::{Engine, ConstructedVerifier, faults::FaultCollector, signer::EngineSigner};
+use parity_crypto::publickey::{Signature, public_to_address, recover};
 use log::trace;
 use machine::{Machine, executed_block::ExecutedBlock};
 use rlp::Rlp;
@@ -45,36 +51,70 @@ use validator_set::{ValidatorSet, SimpleList, new_validator_set};
 pub struct BasicAuthorityParams {
 	/// Valid signatories.
 	pub validators: ethjson::spec::ValidatorSet,
+	/// Number of distinct authority signatures required in the seal, `None` for a single signature seal.
+	pub signature_threshold: Option<usize>,
 }
 
 impl From<ethjson::spec::BasicAuthorityParams> for BasicAuthorityParams {
 	fn from(p: ethjson::spec::BasicAuthorityParams) -> Self {
 		BasicAuthorityParams {
 			validators: p.validators,
+			signature_threshold: p.signature_threshold.map(|t| cmp::max(1, t.into())),
 		}
 	}
 }
 
 struct EpochVerifier {
 	list: SimpleList,
+	signature_threshold: Option<usize>,
 }
 
 impl engine::EpochVerifier for EpochVerifier {
 	fn verify_light(&self, header: &Header) -> Result<(), Error> {
-		verify_external(header, &self.list)
+		verify_external(header, &self.list, self.signature_threshold)
 	}
 }
 
-fn verify_external(header: &Header, validators: &dyn ValidatorSet) -> Result<(), Error> {
-	// Check if the signature belongs to a validator, can depend on parent state.
-	let sig = Rlp::new(&header.seal()[0]).as_val::<H520>()?;
-	let signer = parity_crypto::publickey::public_to_address(&parity_crypto::publickey::recover(&sig.into(), &header.bare_hash())?);
+/// Recovers the signers of a multi-signature seal.
+/// The signers have to be distinct, include the block author and be at least `threshold` in number.
+fn verify_signatures(header: &Header, threshold: usize) -> Result<Vec<Address>, Error> {
+	let signatures = Rlp::new(&header.seal()[0]).as_list::<H520>()?;
+	let mut signers = Vec::with_capacity(signatures.len());
+	for sig in signatures {
+		let signer = public_to_address(&recover(&sig.into(), &header.bare_hash())?);
+		if signers.contains(&signer) {
+			return Err(BlockError::InvalidSeal.into())
+		}
+		signers.push(signer);
+	}
 
-	if *header.author() != signer {
+	if !signers.contains(header.author()) {
 		return Err(EngineError::NotAuthorized(*header.author()).into())
 	}
+	if signers.len() < threshold {
+		return Err(EngineError::InsufficientProof(
+			format!("{} of {} required authority signatures", signers.len(), threshold)
+		).into())
+	}
+	Ok(signers)
+}
+
+fn verify_external(header: &Header, validators: &dyn ValidatorSet, signature_threshold: Option<usize>) -> Result<(), Error> {
+	let signers = match signature_threshold {
+		Some(threshold) => verify_signatures(header, threshold)?,
+		None => {
+			let sig = Rlp::new(&header.seal()[0]).as_val::<H520>()?;
+			let signer = public_to_address(&recover(&sig.into(), &header.bare_hash())?);
+
+			if *header.author() != signer {
+				return Err(EngineError::NotAuthorized(*header.author()).into())
+			}
+			vec![signer]
+		},
+	};
 
-	match validators.contains(header.parent_hash(), &signer) {
+	// Check if the signatures belong to validators, can depend on parent state.
+	match signers.iter().all(|signer| validators.contains(header.parent_hash(), signer)) {
 		false => Err(BlockError::InvalidSeal.into()),
 		true => Ok(())
 	}
@@ -85,6 +125,8 @@ pub struct BasicAuthority {
 	machine: Machine,
 	signer: RwLock<Option<Box<dyn EngineSigner>>>,
 	validators: Box<dyn ValidatorSet>,
+	signature_threshold: Option<usize>,
+	faults: FaultCollector,
 }
 
 impl BasicAuthority {
@@ -94,6 +136,8 @@ impl BasicAuthority {
 			machine: machine,
 			signer: RwLock::new(None),
 			validators: new_validator_set(our_params.validators),
+			signature_threshold: our_params.signature_threshold,
+			faults: FaultCollector::default(),
 		}
 	}
 }
@@ -103,11 +147,13 @@ impl Engine for BasicAuthority {
 
 	fn machine(&self) -> &Machine { &self.machine }
 
-	// One field - the signature
+	// One field - the signature or the list of signatures
 	fn seal_fields(&self, _header: &Header) -> usize { 1 }
 
 	fn sealing_state(&self) -> SealingState {
-		if self.signer.read().is_some() {
+		// signatures of other authorities aren't collected, so we can only seal alone
+		let can_seal_alone = self.signature_threshold.map_or(true, |threshold| threshold <= 1);
+		if can_seal_alone && self.signer.read().is_some() {
 			SealingState::Ready
 		} else {
 			SealingState::NotReady
@@ -119,11 +165,12 @@ impl Engine for BasicAuthority {
 		let header = &block.header;
 		let author = header.author();
 		if self.validators.contains(header.parent_hash(), author) {
-			// account should be pernamently unlocked, otherwise sealing will fail
-			if let Ok(signature) = self.sign(header.bare_hash()) {
-				return Seal::Regular(vec![rlp::encode(&(H520::from(signature).as_bytes()))]);
-			} else {
-				trace!(target: "basicauthority", "generate_seal: FAIL: accounts secret key unavailable");
+			// account should be pernamently unlocked (or the remote signer reachable), otherwise sealing will fail
+			match (self.sign(header.bare_hash()), self.signature_threshold) {
+				(Ok(signature), None) => return Seal::Regular(vec![rlp::encode(&(H520::from(signature).as_bytes()))]),
+				(Ok(signature), Some(1)) => return Seal::Regular(vec![rlp::encode_list::<H520, _>(&[H520::from(signature)])]),
+				(Ok(_), Some(threshold)) => trace!(target: "basicauthority", "generate_seal: FAIL: {} authority signatures required", threshold),
+				(Err(e), _) => trace!(target: "basicauthority", "generate_seal: FAIL: unable to sign the block: {}", e),
 			}
 		}
 		Seal::None
@@ -133,8 +180,20 @@ impl Engine for BasicAuthority {
 		Ok(())
 	}
 
+	fn verify_block_unordered(&self, header: &Header) -> Result<(), Error> {
+		// validator set membership depends on the parent state and is checked in `verify_block_external`
+		match self.signature_threshold {
+			Some(threshold) => verify_signatures(header, threshold).map(|_| ()),
+			None => Ok(()),
+		}
+	}
+
 	fn verify_block_external(&self, header: &Header) -> Result<(), Error> {
-		verify_external(header, &*self.validators)
+		let result = verify_external(header, &*self.validators, self.signature_threshold);
+		if result.is_err() {
+			self.faults.report(*header.author(), ConsensusFaultKind::InvalidSeal, header);
+		}
+		result
 	}
 
 	fn genesis_epoch_data(&self, header: &Header, call: &Call) -> Result<Vec<u8>, String> {
@@ -181,7 +240,7 @@ impl Engine for BasicAuthority {
 
 		match self.validators.epoch_set(first, &self.machine, header.number(), proof) {
 			Ok((list, finalize)) => {
-				let verifier = Box::new(EpochVerifier { list });
+				let verifier = Box::new(EpochVerifier { list, signature_threshold: self.signature_threshold });
 
 				// our epoch verifier will ensure no unverified verifier is ever verified.
 				match finalize {
@@ -209,6 +268,14 @@ impl Engine for BasicAuthority {
 		self.validators.register_client(client);
 	}
 
+	fn set_consensus_faults_window(&self, window: Duration) {
+		self.faults.set_window(window);
+	}
+
+	fn consensus_faults(&self) -> Vec<ConsensusFault> {
+		self.faults.faults()
+	}
+
 	fn params(&self) -> &CommonParams {
 		self.machine.params()
 	}
@@ -224,6 +291,7 @@ mod tests {
 		test_helpers::get_temp_state_db
 	};
 	use accounts::AccountProvider;
+	use parity_crypto::publickey::{KeyPair, Random, Generator, Secret, sign};
 	use spec::Spec;
 	use common_types::{
 		header::Header,
@@ -238,6 +306,16 @@ mod tests {
 		Spec::load(&tempdir.path(), bytes).expect("invalid chain spec")
 	}
 
+	/// Create a new test chain spec with `BasicAuthority` consensus engine requiring two signatures.
+	fn new_test_multisig_authority() -> Spec {
+		let spec = include_str!("../res/basic_authority.json").replace(
+			r#""durationLimit": "0x0d","#,
+			r#""durationLimit": "0x0d", "signatureThreshold": "0x2","#,
+		);
+		let tempdir = TempDir::new("").unwrap();
+		Spec::load(&tempdir.path(), spec.as_bytes()).expect("invalid chain spec")
+	}
+
 	#[test]
 	fn has_valid_metadata() {
 		let engine = new_test_authority().engine;
@@ -259,6 +337,36 @@ mod tests {
 
 		let verify_result = engine.verify_block_external(&header);
 		assert!(verify_result.is_err());
+		assert_eq!(engine.consensus_faults().len(), 1);
+	}
+
+	#[test]
+	fn can_verify_multi_signature_seal() {
+		let engine = new_test_multisig_authority().engine;
+		let authority = KeyPair::from_secret(Secret::from(keccak(""))).unwrap();
+		let cosigner = Random.generate().unwrap();
+		let mut header: Header = Header::default();
+		header.set_author(authority.address());
+		let bare_hash = header.bare_hash();
+		let seal = |signers: &[&KeyPair]| {
+			let signatures: Vec<H520> = signers.iter().map(|s| H520::from(sign(s.secret(), &bare_hash).unwrap())).collect();
+			vec![rlp::encode_list::<H520, _>(&signatures)]
+		};
+
+		header.set_seal(seal(&[&authority]));
+		assert!(engine.verify_block_unordered(&header).is_err());
+
+		header.set_seal(seal(&[&authority, &authority]));
+		assert!(engine.verify_block_unordered(&header).is_err());
+
+		header.set_seal(seal(&[&cosigner, &Random.generate().unwrap()]));
+		assert!(engine.verify_block_unordered(&header).is_err());
+
+		// the co-signer is not an authority
+		header.set_seal(seal(&[&authority, &cosigner]));
+		assert!(engine.verify_block_unordered(&header).is_ok());
+		assert!(engine.verify_block_external(&header).is_err());
+		assert_eq!(engine.consensus_faults().len(), 1);
 	}
 
 	#[test]
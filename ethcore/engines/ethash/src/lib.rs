@@ -381,6 +381,25 @@ impl Engine for Ethash {
 	}
 
 	fn params(&self) -> &CommonParams { self.machine.params() }
+
+	fn fork_blocks(&self) -> Vec<BlockNumber> {
+		let params = &self.ethash_params;
+		let mut blocks = self.params().fork_blocks();
+		blocks.extend(&[
+			params.homestead_transition,
+			params.difficulty_hardfork_transition,
+			params.bomb_defuse_transition,
+			params.eip100b_transition,
+			params.ecip1010_pause_transition,
+			params.ecip1010_continue_transition,
+			params.expip2_transition,
+			params.block_reward_contract_transition,
+			params.progpow_transition,
+		]);
+		blocks.extend(params.block_reward.keys());
+		blocks.extend(params.difficulty_bomb_delays.keys());
+		blocks
+	}
 }
 
 impl Ethash {
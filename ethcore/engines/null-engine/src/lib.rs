@@ -33,6 +33,11 @@ use common_types::{
 	snapshot::Snapshotting
 };
 
+#[cfg(any(test, feature = "test-helpers"))]
+mod scripted;
+#[cfg(any(test, feature = "test-helpers"))]
+pub use self::scripted::{ScriptedEngine, Verification};
+
 /// Params for a null engine.
 #[derive(Clone, Default)]
 pub struct NullEngineParams {
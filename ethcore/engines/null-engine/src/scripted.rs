@@ -0,0 +1,234 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Engine with scripted seal and verification decisions, for deterministic tests of
+//! subsystems which depend on consensus (miner, sync, RPC).
+
+use std::collections::{HashMap, VecDeque};
+
+use common_types::{
+	BlockNumber,
+	header::Header,
+	engines::{Seal, SealingState, params::CommonParams},
+	errors::{EngineError, EthcoreError as Error},
+	snapshot::Snapshotting,
+};
+use engine::Engine;
+use machine::{ExecutedBlock, Machine};
+use parking_lot::Mutex;
+
+use crate::NullEngine;
+
+/// Verification step a scripted result applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Verification {
+	/// `verify_local_seal`
+	LocalSeal,
+	/// `verify_block_basic`
+	Basic,
+	/// `verify_block_unordered`
+	Unordered,
+	/// `verify_block_family`
+	Family,
+	/// `verify_block_external`
+	External,
+}
+
+struct Script {
+	seals: HashMap<BlockNumber, Seal>,
+	default_seal: Seal,
+	sealing_states: VecDeque<SealingState>,
+	default_sealing_state: SealingState,
+	failures: HashMap<(BlockNumber, Verification), String>,
+	sealed: Vec<BlockNumber>,
+}
+
+/// A `NullEngine` whose `generate_seal`, `sealing_state` and block verification answers are
+/// programmed by the test. Unless scripted otherwise it doesn't seal and accepts every block.
+pub struct ScriptedEngine {
+	inner: NullEngine,
+	script: Mutex<Script>,
+}
+
+impl ScriptedEngine {
+	/// Create a new engine over the given state machine.
+	pub fn new(machine: Machine) -> Self {
+		ScriptedEngine {
+			inner: NullEngine::new(Default::default(), machine),
+			script: Mutex::new(Script {
+				seals: HashMap::new(),
+				default_seal: Seal::None,
+				sealing_states: VecDeque::new(),
+				default_sealing_state: SealingState::External,
+				failures: HashMap::new(),
+				sealed: Vec::new(),
+			}),
+		}
+	}
+
+	/// Seal generated for the block with given number.
+	pub fn set_seal(&self, number: BlockNumber, seal: Seal) {
+		self.script.lock().seals.insert(number, seal);
+	}
+
+	/// Seal generated for blocks without a scripted seal.
+	pub fn set_default_seal(&self, seal: Seal) {
+		self.script.lock().default_seal = seal;
+	}
+
+	/// Queue an answer for the next `sealing_state` query.
+	pub fn push_sealing_state(&self, state: SealingState) {
+		self.script.lock().sealing_states.push_back(state);
+	}
+
+	/// Answer to `sealing_state` queries once the queued answers are exhausted.
+	pub fn set_default_sealing_state(&self, state: SealingState) {
+		self.script.lock().default_sealing_state = state;
+	}
+
+	/// Make given verification step fail for the block with given number.
+	pub fn fail_verification(&self, number: BlockNumber, step: Verification, reason: &str) {
+		self.script.lock().failures.insert((number, step), reason.into());
+	}
+
+	/// Numbers of the blocks a regular seal was generated for, in order.
+	pub fn sealed_blocks(&self) -> Vec<BlockNumber> {
+		self.script.lock().sealed.clone()
+	}
+
+	fn seal(&self, number: BlockNumber) -> Seal {
+		let mut script = self.script.lock();
+		let seal = script.seals.get(&number).unwrap_or(&script.default_seal).clone();
+		if let Seal::Regular(_) = seal {
+			script.sealed.push(number);
+		}
+		seal
+	}
+
+	fn verify(&self, header: &Header, step: Verification) -> Result<(), Error> {
+		match self.script.lock().failures.get(&(header.number(), step)) {
+			Some(reason) => Err(EngineError::Custom(reason.clone()).into()),
+			None => Ok(()),
+		}
+	}
+}
+
+impl Engine for ScriptedEngine {
+	fn name(&self) -> &str { "ScriptedEngine" }
+
+	fn machine(&self) -> &Machine { self.inner.machine() }
+
+	fn maximum_uncle_count(&self, block: BlockNumber) -> usize { self.inner.maximum_uncle_count(block) }
+
+	fn on_close_block(&self, block: &mut ExecutedBlock, parent_header: &Header) -> Result<(), Error> {
+		self.inner.on_close_block(block, parent_header)
+	}
+
+	fn sealing_state(&self) -> SealingState {
+		let mut script = self.script.lock();
+		match script.sealing_states.pop_front() {
+			Some(state) => state,
+			None => script.default_sealing_state.clone(),
+		}
+	}
+
+	fn generate_seal(&self, block: &ExecutedBlock, _parent: &Header) -> Seal {
+		self.seal(block.header.number())
+	}
+
+	fn verify_local_seal(&self, header: &Header) -> Result<(), Error> {
+		self.verify(header, Verification::LocalSeal)
+	}
+
+	fn verify_block_basic(&self, header: &Header) -> Result<(), Error> {
+		self.verify(header, Verification::Basic)
+	}
+
+	fn verify_block_unordered(&self, header: &Header) -> Result<(), Error> {
+		self.verify(header, Verification::Unordered)
+	}
+
+	fn verify_block_family(&self, header: &Header, _parent: &Header) -> Result<(), Error> {
+		self.verify(header, Verification::Family)
+	}
+
+	fn verify_block_external(&self, header: &Header) -> Result<(), Error> {
+		self.verify(header, Verification::External)
+	}
+
+	fn snapshot_mode(&self) -> Snapshotting {
+		self.inner.snapshot_mode()
+	}
+
+	fn params(&self) -> &CommonParams {
+		self.inner.params()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+	use common_types::{
+		header::Header,
+		engines::{Seal, SealingState},
+	};
+	use engine::Engine;
+	use machine::Machine;
+	use super::{ScriptedEngine, Verification};
+
+	fn engine() -> ScriptedEngine {
+		ScriptedEngine::new(Machine::regular(Default::default(), BTreeMap::new()))
+	}
+
+	#[test]
+	fn should_answer_scripted_seals() {
+		let engine = engine();
+		engine.set_seal(2, Seal::Regular(vec![vec![2]]));
+		engine.set_seal(3, Seal::None);
+		engine.set_default_seal(Seal::Regular(Vec::new()));
+
+		assert_eq!(engine.seal(1), Seal::Regular(Vec::new()));
+		assert_eq!(engine.seal(2), Seal::Regular(vec![vec![2]]));
+		assert_eq!(engine.seal(3), Seal::None);
+		assert_eq!(engine.sealed_blocks(), vec![1, 2]);
+	}
+
+	#[test]
+	fn should_answer_scripted_sealing_states() {
+		let engine = engine();
+		engine.push_sealing_state(SealingState::NotReady);
+		engine.set_default_sealing_state(SealingState::Ready);
+
+		assert_eq!(engine.sealing_state(), SealingState::NotReady);
+		assert_eq!(engine.sealing_state(), SealingState::Ready);
+		assert_eq!(engine.sealing_state(), SealingState::Ready);
+	}
+
+	#[test]
+	fn should_fail_scripted_verification() {
+		let engine = engine();
+		engine.fail_verification(5, Verification::Family, "bad parent");
+
+		let mut header = Header::default();
+		header.set_number(5);
+		let parent = Header::default();
+
+		assert!(engine.verify_block_basic(&header).is_ok());
+		assert!(engine.verify_block_family(&header, &parent).is_err());
+		header.set_number(6);
+		assert!(engine.verify_block_family(&header, &parent).is_ok());
+	}
+}
@@ -121,6 +121,7 @@ fn check_first_proof(machine: &Machine, contract_address: Address, old_header: H
 			Arc::new(last_hashes)
 		},
 		gas_used: 0.into(),
+		base_fee: old_header.base_fee(),
 	};
 
 	// check state proof using given machine.
@@ -26,13 +26,14 @@ use super::interpreter::SharedCache;
 #[derive(Clone)]
 pub struct Factory {
 	evm_cache: Arc<SharedCache>,
+	always_u256: bool,
 }
 
 impl Factory {
 	/// Create fresh instance of VM
 	/// Might choose implementation depending on supplied gas.
 	pub fn create(&self, params: ActionParams, schedule: &Schedule, depth: usize) -> Box<dyn Exec> {
-		if Self::can_fit_in_usize(&params.gas) {
+		if !self.always_u256 && Self::can_fit_in_usize(&params.gas) {
 			Box::new(super::interpreter::Interpreter::<usize>::new(params, self.evm_cache.clone(), schedule, depth))
 		} else {
 			Box::new(super::interpreter::Interpreter::<U256>::new(params, self.evm_cache.clone(), schedule, depth))
@@ -44,6 +45,17 @@ impl Factory {
 	pub fn new(cache_size: usize) -> Self {
 		Factory {
 			evm_cache: Arc::new(SharedCache::new(cache_size)),
+			always_u256: false,
+		}
+	}
+
+	/// Create new instance of a factory which always uses 256-bit gas arithmetic and
+	/// doesn't cache jump destinations. Slower than the default one, meant as a reference
+	/// implementation to compare optimized execution against.
+	pub fn new_reference() -> Self {
+		Factory {
+			evm_cache: Arc::new(SharedCache::new(0)),
+			always_u256: true,
 		}
 	}
 
@@ -57,6 +69,7 @@ impl Default for Factory {
 	fn default() -> Factory {
 		Factory {
 			evm_cache: Arc::new(SharedCache::default()),
+			always_u256: false,
 		}
 	}
 }
@@ -29,7 +29,7 @@ use common_types::{
 	transaction::SignedTransaction,
 	receipt::{TransactionOutcome, Receipt},
 };
-use ethereum_types::H256;
+use ethereum_types::{H256, U256};
 use hash_db::AsHashDB;
 use keccak_hasher::KeccakHasher;
 use kvdb::DBValue;
@@ -63,7 +63,9 @@ pub struct ApplyOutcome<T, V> {
 	/// The trace for the applied transaction, empty if tracing was not produced.
 	pub trace: Vec<T>,
 	/// The VM trace for the applied transaction, None if tracing was not produced.
-	pub vm_trace: Option<V>
+	pub vm_trace: Option<V>,
+	/// Gas refunded to the sender on top of the unused gas.
+	pub refunded: U256,
 }
 
 /// Result type for the execution ("application") of a transaction.
@@ -229,6 +231,7 @@ impl<B: Backend> ExecutiveState for State<B> {
 			output,
 			trace: e.trace,
 			vm_trace: e.vm_trace,
+			refunded: e.refunded,
 		})
 	}
 }
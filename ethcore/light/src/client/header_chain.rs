@@ -17,9 +17,10 @@
 //! Light client header chain.
 //!
 //! Unlike a full node's `BlockChain` this doesn't store much in the database.
-//! It stores candidates for the last 2048-4096 blocks as well as CHT roots for
-//! historical blocks all the way to the genesis. If the engine makes use
-//! of epoch transitions, those are stored as well.
+//! It stores candidates for the last `history` to `history + cht::SIZE` blocks
+//! (2048-4096 by default) as well as CHT roots for historical blocks all the way
+//! to the genesis. Headers older than that are dropped once their CHT root has been
+//! computed. If the engine makes use of epoch transitions, those are stored as well.
 //!
 //! This is separate from the `BlockChain` for two reasons:
 //!   - It stores only headers (and a pruned subset of them)
@@ -41,7 +42,7 @@ use common_types::{
 	header::Header,
 	ids::BlockId,
 };
-use spec::{Spec, SpecHardcodedSync};
+use spec::{Spec, SpecCheckpoint, SpecHardcodedSync};
 use ethereum_types::{H256, H264, U256};
 use parity_util_mem::{MallocSizeOf, MallocSizeOfOps};
 use kvdb::{DBTransaction, KeyValueDB};
@@ -50,10 +51,14 @@ use fastmap::H256FastMap;
 use rlp::{Encodable, Decodable, DecoderError, RlpStream, Rlp};
 use smallvec::SmallVec;
 
-/// Store at least this many candidate headers at all times.
+/// Store at least this many candidate headers at all times by default.
 /// Also functions as the delay for computing CHTs as they aren't
 /// relevant to any blocks we've got in memory.
-const HISTORY: u64 = 2048;
+pub const DEFAULT_HISTORY: u64 = 2048;
+
+/// Minimal number of candidate headers kept. Reorganizations deeper than
+/// this can't be handled after the headers are dropped.
+pub const MIN_HISTORY: u64 = 128;
 
 /// The best block key. Maps to an RLP list: [best_era, last_era]
 const CURRENT_KEY: &[u8] = &*b"best_and_latest";
@@ -202,6 +207,8 @@ pub struct PendingChanges {
 }
 
 /// Whether or not the hardcoded sync feature is allowed.
+/// It covers both the hardcoded CHTs and the trusted checkpoint of the spec.
+#[derive(Clone, Copy, PartialEq)]
 pub enum HardcodedSync {
 	Allow,
 	Deny,
@@ -223,17 +230,28 @@ pub struct HeaderChain {
 	col: u32,
 	#[ignore_malloc_size_of = "ignored for performance reason"]
 	cache: Arc<Mutex<Cache>>,
+	history: u64,
 }
 
 impl HeaderChain {
 	/// Create a new header chain given this genesis block and database to read from.
+	///
+	/// Keeps at least `history` recent headers; older ones are compacted into CHTs.
 	pub fn new(
 		db: Arc<dyn KeyValueDB>,
 		col: u32,
 		spec: &Spec,
 		cache: Arc<Mutex<Cache>>,
 		allow_hs: HardcodedSync,
+		history: u64,
 	) -> Result<Self, Error> {
+		let history = if history < MIN_HISTORY {
+			warn!(target: "chain", "Light client header history {} is too low, setting to {}", history, MIN_HISTORY);
+			MIN_HISTORY
+		} else {
+			history
+		};
+
 		let mut live_epoch_proofs = ::std::collections::HashMap::default();
 
 		let genesis = ::rlp::encode(&spec.genesis_header());
@@ -291,6 +309,7 @@ impl HeaderChain {
 				db,
 				col,
 				cache,
+				history,
 			}
 
 		} else {
@@ -306,6 +325,7 @@ impl HeaderChain {
 				db: db.clone(),
 				col,
 				cache,
+				history,
 			};
 
 			// insert the hardcoded sync into the database.
@@ -338,6 +358,13 @@ impl HeaderChain {
 				}
 			}
 
+			// fall back to the trusted checkpoint; headers above it are verified as usual.
+			if let (&Some(ref checkpoint), HardcodedSync::Allow) = (&spec.checkpoint, allow_hs) {
+				if chain.best_block.read().number == 0 {
+					chain.insert_checkpoint(checkpoint)?;
+				}
+			}
+
 			chain
 		};
 
@@ -356,6 +383,29 @@ impl HeaderChain {
 		Ok(chain)
 	}
 
+	/// Start the chain from the trusted checkpoint if its signatures are valid.
+	fn insert_checkpoint(&self, checkpoint: &SpecCheckpoint) -> Result<(), Error> {
+		let header = checkpoint.header.decode()?;
+		let number = header.number();
+
+		// CHTs are only produced from complete sections, so the chain has to start at the beginning of one.
+		if number == 0 || (number - 1) % cht::SIZE != 0 {
+			warn!(target: "chain", "Checkpoint #{} is not the first block of a CHT; falling back to full header sync", number);
+			return Ok(());
+		}
+		if let Err(e) = checkpoint.verify() {
+			warn!(target: "chain", "Untrusted checkpoint #{}: {}; falling back to full header sync", number, e);
+			return Ok(());
+		}
+
+		info!(target: "chain", "Starting header sync from trusted checkpoint #{} ({})", number, header.hash());
+		let mut batch = self.db.transaction();
+		let pending = self.insert_with_td(&mut batch, &header, checkpoint.total_difficulty, None)?;
+		self.db.write_buffered(batch);
+		self.apply_pending(pending);
+		Ok(())
+	}
+
 	/// Insert a pre-verified header.
 	///
 	/// This blindly trusts that the data given to it is sensible.
@@ -484,7 +534,7 @@ impl HeaderChain {
 				entry.candidates.swap(0, canon_pos);
 				entry.canonical_hash = canon_hash;
 
-				// what about reorgs > cht::SIZE + HISTORY?
+				// what about reorgs > cht::SIZE + history?
 				// resetting to the last block of a given CHT should be possible.
 				canon_hash = entry.candidates[0].parent_hash;
 
@@ -502,9 +552,12 @@ impl HeaderChain {
 				total_difficulty,
 			});
 
-			// produce next CHT root if it's time.
-			let earliest_era = *candidates.keys().next().expect("at least one era just created; qed");
-			if earliest_era + HISTORY + cht::SIZE <= number {
+			// produce CHT roots while there are complete CHTs beyond the history.
+			// more than one may be due after the history has been lowered.
+			loop {
+				let earliest_era = *candidates.keys().next().expect("at least one era just created; qed");
+				if earliest_era + self.history + cht::SIZE > number { break }
+
 				let cht_num = cht::block_to_cht_number(earliest_era)
 					.expect("fails only for number == 0; genesis never imported; qed");
 
@@ -872,7 +925,9 @@ impl<'a> Iterator for AncestryIter<'a> {
 
 #[cfg(test)]
 mod tests {
-	use super::{HeaderChain, HardcodedSync};
+	use super::{HeaderChain, HardcodedSync, DEFAULT_HISTORY, MIN_HISTORY};
+	use common_types::encoded;
+	use spec::SpecCheckpoint;
 	use std::sync::Arc;
 
 	use cache::Cache;
@@ -898,7 +953,7 @@ mod tests {
 
 		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
 
-		let chain = HeaderChain::new(db.clone(), 0, &spec, cache, HardcodedSync::Allow).unwrap();
+		let chain = HeaderChain::new(db.clone(), 0, &spec, cache, HardcodedSync::Allow, DEFAULT_HISTORY).unwrap();
 
 		let mut parent_hash = genesis_header.hash();
 		let mut rolling_timestamp = genesis_header.timestamp();
@@ -931,7 +986,7 @@ mod tests {
 		let db = make_db();
 		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
 
-		let chain = HeaderChain::new(db.clone(), 0, &spec, cache, HardcodedSync::Allow).unwrap();
+		let chain = HeaderChain::new(db.clone(), 0, &spec, cache, HardcodedSync::Allow, DEFAULT_HISTORY).unwrap();
 
 		let mut parent_hash = genesis_header.hash();
 		let mut rolling_timestamp = genesis_header.timestamp();
@@ -1013,7 +1068,7 @@ mod tests {
 		let db = make_db();
 		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
 
-		let chain = HeaderChain::new(db.clone(), 0, &spec, cache, HardcodedSync::Allow).unwrap();
+		let chain = HeaderChain::new(db.clone(), 0, &spec, cache, HardcodedSync::Allow, DEFAULT_HISTORY).unwrap();
 
 		assert!(chain.block_header(BlockId::Earliest).is_some());
 		assert!(chain.block_header(BlockId::Latest).is_some());
@@ -1028,7 +1083,7 @@ mod tests {
 
 		{
 			let chain = HeaderChain::new(db.clone(), 0, &spec, cache.clone(),
-										HardcodedSync::Allow).unwrap();
+										HardcodedSync::Allow, DEFAULT_HISTORY).unwrap();
 			let mut parent_hash = genesis_header.hash();
 			let mut rolling_timestamp = genesis_header.timestamp();
 			for i in 1..10000 {
@@ -1049,7 +1104,7 @@ mod tests {
 		}
 
 		let chain = HeaderChain::new(db.clone(), 0, &spec, cache.clone(),
-									HardcodedSync::Allow).unwrap();
+									HardcodedSync::Allow, DEFAULT_HISTORY).unwrap();
 		assert!(chain.block_header(BlockId::Number(10)).is_none());
 		assert!(chain.block_header(BlockId::Number(9000)).is_some());
 		assert!(chain.cht_root(2).is_some());
@@ -1057,6 +1112,59 @@ mod tests {
 		assert_eq!(chain.block_header(BlockId::Latest).unwrap().number(), 9999);
 	}
 
+	#[test]
+	fn compacts_headers_when_history_lowered() {
+		let spec = spec::new_test();
+		let genesis_header = spec.genesis_header();
+		let db = make_db();
+		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
+
+		let mut parent_hash = genesis_header.hash();
+		let mut rolling_timestamp = genesis_header.timestamp();
+		let mut make_header = |i: u64| {
+			let mut header = Header::new();
+			header.set_parent_hash(parent_hash);
+			header.set_number(i);
+			header.set_timestamp(rolling_timestamp);
+			header.set_difficulty(*genesis_header.difficulty() * i as u32);
+			parent_hash = header.hash();
+			rolling_timestamp += 10;
+			header
+		};
+
+		{
+			let chain = HeaderChain::new(db.clone(), 0, &spec, cache.clone(),
+										HardcodedSync::Allow, 4 * DEFAULT_HISTORY).unwrap();
+			for i in 1..12001 {
+				let header = make_header(i);
+				let mut tx = db.transaction();
+				let pending = chain.insert(&mut tx, &header, None).unwrap();
+				db.write(tx).unwrap();
+				chain.apply_pending(pending);
+			}
+
+			assert!(chain.cht_root(0).is_some());
+			assert!(chain.cht_root(1).is_none());
+			assert!(chain.block_header(BlockId::Number(3000)).is_some());
+		}
+
+		let chain = HeaderChain::new(db.clone(), 0, &spec, cache.clone(),
+									HardcodedSync::Allow, MIN_HISTORY).unwrap();
+		let header = make_header(12001);
+		let mut tx = db.transaction();
+		let pending = chain.insert(&mut tx, &header, None).unwrap();
+		db.write(tx).unwrap();
+		chain.apply_pending(pending);
+
+		// all complete CHTs beyond the new history are generated at once.
+		assert!(chain.cht_root(4).is_some());
+		assert!(chain.cht_root(5).is_none());
+		assert!(chain.block_header(BlockId::Number(3000)).is_none());
+		assert!(chain.block_header(BlockId::Number(10000)).is_none());
+		assert!(chain.block_header(BlockId::Number(10300)).is_some());
+		assert_eq!(chain.block_header(BlockId::Latest).unwrap().number(), 12001);
+	}
+
 	#[test]
 	fn restore_higher_non_canonical() {
 		let spec = spec::new_test();
@@ -1066,7 +1174,7 @@ mod tests {
 
 		{
 			let chain = HeaderChain::new(db.clone(), 0, &spec, cache.clone(),
-										HardcodedSync::Allow).unwrap();
+										HardcodedSync::Allow, DEFAULT_HISTORY).unwrap();
 			let mut parent_hash = genesis_header.hash();
 			let mut rolling_timestamp = genesis_header.timestamp();
 
@@ -1109,7 +1217,7 @@ mod tests {
 
 		// after restoration, non-canonical eras should still be loaded.
 		let chain = HeaderChain::new(db.clone(), 0, &spec, cache.clone(),
-									HardcodedSync::Allow).unwrap();
+									HardcodedSync::Allow, DEFAULT_HISTORY).unwrap();
 		assert_eq!(chain.block_header(BlockId::Latest).unwrap().number(), 10);
 		assert!(chain.candidates.read().get(&100).is_some())
 	}
@@ -1122,7 +1230,7 @@ mod tests {
 		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
 
 		let chain = HeaderChain::new(db.clone(), 0, &spec, cache.clone(),
-									HardcodedSync::Allow).unwrap();
+									HardcodedSync::Allow, DEFAULT_HISTORY).unwrap();
 
 		assert!(chain.block_header(BlockId::Earliest).is_some());
 		assert!(chain.block_header(BlockId::Number(0)).is_some());
@@ -1136,7 +1244,7 @@ mod tests {
 		let db = make_db();
 		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
 
-		let chain = HeaderChain::new(db.clone(), 0, &spec, cache, HardcodedSync::Allow).unwrap();
+		let chain = HeaderChain::new(db.clone(), 0, &spec, cache, HardcodedSync::Allow, DEFAULT_HISTORY).unwrap();
 
 		let mut parent_hash = genesis_header.hash();
 		let mut rolling_timestamp = genesis_header.timestamp();
@@ -1203,7 +1311,7 @@ mod tests {
 
 		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
 
-		let chain = HeaderChain::new(db.clone(), 0, &spec, cache, HardcodedSync::Allow).expect("failed to instantiate a new HeaderChain");
+		let chain = HeaderChain::new(db.clone(), 0, &spec, cache, HardcodedSync::Allow, DEFAULT_HISTORY).expect("failed to instantiate a new HeaderChain");
 
 		let mut parent_hash = genesis_header.hash();
 		let mut rolling_timestamp = genesis_header.timestamp();
@@ -1235,4 +1343,40 @@ mod tests {
 		let decoded: Header = hardcoded_sync.header.decode().expect("decoding failed");
 		assert_eq!(decoded.number(), h_num);
 	}
+
+	#[test]
+	fn starts_from_checkpoint() {
+		let mut spec = spec::new_test();
+		let genesis_header = spec.genesis_header();
+		let db = make_db();
+		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
+
+		let mut checkpoint = Header::new();
+		checkpoint.set_number(::cht::SIZE + 1);
+		checkpoint.set_difficulty(*genesis_header.difficulty());
+		let total_difficulty = U256::from(1_000_000);
+		spec.checkpoint = Some(SpecCheckpoint {
+			header: encoded::Header::new(::rlp::encode(&checkpoint)),
+			total_difficulty,
+			signatures: None,
+		});
+
+		let chain = HeaderChain::new(db.clone(), 0, &spec, cache, HardcodedSync::Allow, DEFAULT_HISTORY).unwrap();
+		assert_eq!(chain.best_block().number, ::cht::SIZE + 1);
+		assert_eq!(chain.best_block().total_difficulty, total_difficulty);
+
+		// headers above the checkpoint are imported as usual.
+		let mut header = Header::new();
+		header.set_parent_hash(checkpoint.hash());
+		header.set_number(::cht::SIZE + 2);
+		header.set_difficulty(*genesis_header.difficulty());
+
+		let mut tx = db.transaction();
+		let pending = chain.insert(&mut tx, &header, None).unwrap();
+		db.write(tx).unwrap();
+		chain.apply_pending(pending);
+
+		assert_eq!(chain.best_block().number, ::cht::SIZE + 2);
+		assert_eq!(chain.best_block().total_difficulty, total_difficulty + *genesis_header.difficulty());
+	}
 }
@@ -47,6 +47,7 @@ use self::header_chain::{AncestryIter, HeaderChain, HardcodedSync};
 
 use cache::Cache;
 
+pub use self::header_chain::{DEFAULT_HISTORY as DEFAULT_HEADER_HISTORY, MIN_HISTORY as MIN_HEADER_HISTORY};
 pub use self::service::Service;
 use client_traits::ForceUpdateSealing;
 
@@ -68,6 +69,8 @@ pub struct Config {
 	pub check_seal: bool,
 	/// Disable hardcoded sync.
 	pub no_hardcoded_sync: bool,
+	/// Number of recent headers to keep; older headers are dropped after their CHT is generated.
+	pub header_history: u64,
 }
 
 impl Default for Config {
@@ -78,6 +81,7 @@ impl Default for Config {
 			verify_full: true,
 			check_seal: true,
 			no_hardcoded_sync: false,
+			header_history: DEFAULT_HEADER_HISTORY,
 		}
 	}
 }
@@ -193,7 +197,7 @@ impl<T: ChainDataFetcher> Client<T> {
 			engine: spec.engine.clone(),
 			chain: {
 				let hs_cfg = if config.no_hardcoded_sync { HardcodedSync::Deny } else { HardcodedSync::Allow };
-				HeaderChain::new(db.clone(), chain_col, &spec, cache, hs_cfg)?
+				HeaderChain::new(db.clone(), chain_col, &spec, cache, hs_cfg, config.header_history)?
 			},
 			report: RwLock::new(ClientReport::default()),
 			import_lock: Mutex::new(()),
@@ -406,6 +410,7 @@ impl<T: ChainDataFetcher> Client<T> {
 			last_hashes: self.build_last_hashes(header.parent_hash()),
 			gas_used: Default::default(),
 			gas_limit: header.gas_limit(),
+			base_fee: header.base_fee(),
 		})
 	}
 
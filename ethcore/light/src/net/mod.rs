@@ -93,6 +93,9 @@ pub const PROTOCOL_VERSIONS: &[(u8, u8)] = &[
 /// Max protocol version.
 pub const MAX_PROTOCOL_VERSION: u8 = 1;
 
+/// Number of malformed requests, transaction packets or unknown packets a peer may send before it is disabled.
+pub const MAX_MALFORMED_PACKETS: usize = 8;
+
 // packet ID definitions.
 mod packet {
 	// the status packet.
@@ -405,6 +408,7 @@ pub struct LightProtocol {
 	sample_store: Box<dyn SampleStore>,
 	load_distribution: LoadDistribution,
 	statistics: RwLock<Statistics>,
+	malformed_packets: RwLock<HashMap<PeerId, usize>>,
 }
 
 impl LightProtocol {
@@ -438,6 +442,7 @@ impl LightProtocol {
 			sample_store,
 			load_distribution,
 			statistics: RwLock::new(Statistics::new()),
+			malformed_packets: RwLock::new(HashMap::new()),
 		}
 	}
 
@@ -658,8 +663,29 @@ impl LightProtocol {
 			}
 		};
 
-		if let Err(e) = res {
-			punish(peer, io, &e);
+		// malformed responses to our own requests are punished right away
+		match (packet_id, res) {
+			(_, Ok(())) => {},
+			(packet::REQUEST, Err(e @ Error::Rlp(_)))
+			| (packet::SEND_TRANSACTIONS, Err(e @ Error::Rlp(_)))
+			| (_, Err(e @ Error::UnrecognizedPacket(_))) => self.on_malformed_packet(peer, io, &e),
+			(_, Err(e)) => punish(peer, io, &e),
+		}
+	}
+
+	// note a malformed packet from a peer. the peer is disabled once it has sent
+	// more than `MAX_MALFORMED_PACKETS` of them.
+	fn on_malformed_packet(&self, peer: PeerId, io: &dyn IoContext, e: &Error) {
+		let count = {
+			let mut malformed_packets = self.malformed_packets.write();
+			let count = malformed_packets.entry(peer).or_insert(0);
+			*count += 1;
+			*count
+		};
+		debug!(target: "pip", "Malformed packet from peer {} ({} so far): {}", peer, count, e);
+		if count > MAX_MALFORMED_PACKETS {
+			debug!(target: "pip", "Disabling peer {}: too many malformed packets", peer);
+			io.disable_peer(peer);
 		}
 	}
 
@@ -792,6 +818,7 @@ impl LightProtocol {
 		trace!(target: "pip", "Peer {} disconnecting", peer);
 
 		self.pending_peers.write().remove(&peer);
+		self.malformed_packets.write().remove(&peer);
 		let unfulfilled = match self.peers.write().remove(&peer) {
 			None => return,
 			Some(peer_info) => {
@@ -26,7 +26,7 @@ use ethereum_types::{H256, U256, Address, BigEndianHash};
 use net::context::IoContext;
 use net::load_timer::MOVING_SAMPLE_SIZE;
 use net::status::{Capabilities, Status};
-use net::{LightProtocol, Params, packet, Peer, Statistics};
+use net::{LightProtocol, Params, packet, Peer, Statistics, MAX_MALFORMED_PACKETS};
 use network::{PeerId, NodeId};
 use provider::Provider;
 use request::*;
@@ -782,6 +782,29 @@ fn get_transaction_index() {
 	proto.handle_packet(&expected, 1, packet::REQUEST, &request_body);
 }
 
+#[test]
+fn disable_peer_after_malformed_packets() {
+	let (_, proto) = setup(capabilities());
+
+	// a transaction which isn't even a list
+	let mut stream = RlpStream::new_list(1);
+	stream.append(&vec![0xffu8]);
+	let malformed_transactions = stream.out();
+
+	for i in 0..MAX_MALFORMED_PACKETS {
+		let (packet_id, data) = if i % 2 == 0 {
+			(packet::SEND_TRANSACTIONS, &malformed_transactions[..])
+		} else {
+			(0xff, &[][..])
+		};
+		proto.handle_packet(&Expect::Nothing, 1, packet_id, data);
+	}
+	// other peers are counted separately
+	proto.handle_packet(&Expect::Nothing, 2, 0xff, &[]);
+
+	proto.handle_packet(&Expect::Punish(1), 1, packet::SEND_TRANSACTIONS, &malformed_transactions);
+}
+
 #[test]
 fn sync_statistics() {
 	let mut stats = Statistics::new();
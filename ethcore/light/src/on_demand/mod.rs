@@ -22,14 +22,14 @@ use std::cmp;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures::{Poll, Future, Async};
 use futures::sync::oneshot::{self, Receiver};
 use network::PeerId;
 use parking_lot::{RwLock, Mutex};
 use rand;
-use rand::Rng;
+use rand::seq::SliceRandom;
 
 use net::{
 	Handler, PeerStatus, Status, Capabilities,
@@ -64,6 +64,15 @@ pub const DEFAULT_MAX_REQUEST_BACKOFF_ROUNDS: usize = 10;
 /// The default number failed request to be regarded as failure
 pub const DEFAULT_NUM_CONSECUTIVE_FAILED_REQUESTS: usize = 1;
 
+/// Latency assumed for peers which haven't responded to any request yet
+const DEFAULT_PEER_LATENCY: Duration = Duration::from_millis(500);
+/// Minimum time after which an unanswered request is sent to another peer as well
+const MIN_SOFT_DEADLINE: Duration = Duration::from_secs(2);
+/// Soft deadline of a request as a multiple of the latency of the peer it was sent to
+const SOFT_DEADLINE_LATENCY_FACTOR: u32 = 4;
+/// Weight of the previous average when updating the latency of a peer
+const LATENCY_SMOOTHING: u32 = 4;
+
 /// OnDemand related errors
 pub mod error {
 	use futures::sync::oneshot::Canceled;
@@ -137,6 +146,67 @@ impl Peer {
 	}
 }
 
+// network request sent to a peer on behalf of a pending request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Dispatched {
+	peer: PeerId,
+	sent: Instant,
+	// id of the request this one duplicates after it missed its soft deadline.
+	duplicate_of: Option<ReqId>,
+	// whether a duplicate of this request has been sent to another peer.
+	duplicated: bool,
+}
+
+impl Dispatched {
+	fn new(peer: PeerId, duplicate_of: Option<ReqId>) -> Self {
+		Dispatched {
+			peer,
+			sent: Instant::now(),
+			duplicate_of,
+			duplicated: false,
+		}
+	}
+}
+
+// time after which a request sent to a peer with the given latency is sent to another peer as well.
+fn soft_deadline(latency: Option<&Duration>) -> Duration {
+	latency.map_or(MIN_SOFT_DEADLINE, |latency| cmp::max(MIN_SOFT_DEADLINE, *latency * SOFT_DEADLINE_LATENCY_FACTOR))
+}
+
+// fold the time a peer took to respond, or has kept a request waiting, into its latency estimate.
+fn note_latency(latencies: &mut HashMap<PeerId, Duration>, peer_id: PeerId, elapsed: Duration) {
+	let latency = latencies.entry(peer_id).or_insert(elapsed);
+	*latency = (*latency * (LATENCY_SMOOTHING - 1) + elapsed) / LATENCY_SMOOTHING;
+}
+
+// peers able to serve the given capabilities, preferring the ones expected to respond first
+// given their measured latency and the number of requests they still have to answer.
+// peers with equal expectations are tried in random order.
+fn rank_peers(
+	peers: &HashMap<PeerId, Peer>,
+	capabilities: &Capabilities,
+	load: &HashMap<PeerId, usize>,
+	latencies: &HashMap<PeerId, Duration>,
+) -> Vec<PeerId> {
+	let mut ranked: Vec<PeerId> = peers.iter()
+		.filter(|&(peer_id, peer)| {
+			let can_fulfill = peer.can_fulfill(capabilities);
+			if !can_fulfill {
+				trace!(target: "on_demand", "Peer {} without required capabilities, skipping", peer_id);
+			}
+			can_fulfill
+		})
+		.map(|(peer_id, _)| *peer_id)
+		.collect();
+
+	ranked.shuffle(&mut rand::thread_rng());
+	ranked.sort_by_key(|peer_id| {
+		let latency = latencies.get(peer_id).cloned().unwrap_or(DEFAULT_PEER_LATENCY);
+		latency * (load.get(peer_id).cloned().unwrap_or(0) as u32 + 1)
+	});
+	ranked
+}
+
 /// Either an array of responses or a single error.
 type PendingResponse = self::error::Result<Vec<Response>>;
 
@@ -363,6 +433,8 @@ pub struct OnDemand {
 	pending: RwLock<Vec<Pending>>,
 	peers: RwLock<HashMap<PeerId, Peer>>,
 	in_transit: RwLock<HashMap<ReqId, Pending>>,
+	dispatched: RwLock<HashMap<ReqId, Dispatched>>,
+	latencies: RwLock<HashMap<PeerId, Duration>>,
 	cache: Arc<Mutex<Cache>>,
 	no_immediate_dispatch: bool,
 	response_time_window: Duration,
@@ -456,6 +528,8 @@ impl OnDemand {
 			pending: RwLock::new(Vec::new()),
 			peers: RwLock::new(HashMap::new()),
 			in_transit: RwLock::new(HashMap::new()),
+			dispatched: RwLock::new(HashMap::new()),
+			latencies: RwLock::new(HashMap::new()),
 			cache,
 			no_immediate_dispatch: false,
 			response_time_window: Self::sanitize_circuit_breaker_input(response_time_window, "Response time window"),
@@ -519,33 +593,21 @@ impl OnDemand {
 		let mut pending = self.pending.write();
 
 		// iterate over all pending requests, and check them for hang-up.
-		// then, try and find a peer who can serve it.
+		// then, try and find a peer who can serve it, fastest and least loaded first.
 		let peers = self.peers.read();
+		let mut load = self.peer_load();
+		let latencies = self.latencies.read().clone();
 
 		*pending = ::std::mem::replace(&mut *pending, Vec::new())
 			.into_iter()
 			.filter(|pending| !pending.sender.is_canceled())
 			.filter_map(|mut pending| {
-
-				let num_peers = peers.len();
-				// The first peer to dispatch the request is chosen at random
-				let rand = rand::thread_rng().gen_range(0, cmp::max(1, num_peers));
-
-				for (peer_id, peer) in peers
-					.iter()
-					.cycle()
-					.skip(rand)
-					.take(num_peers)
-				{
-
-					if !peer.can_fulfill(&pending.required_capabilities) {
-						trace!(target: "on_demand", "Peer {} without required capabilities, skipping", peer_id);
-						continue
-					}
-
+				for peer_id in rank_peers(&peers, &pending.required_capabilities, &load, &latencies) {
 					if pending.request_guard.is_call_permitted() {
-						if let Ok(req_id) = ctx.request_from(*peer_id, pending.net_requests.clone()) {
+						if let Ok(req_id) = ctx.request_from(peer_id, pending.net_requests.clone()) {
 							self.in_transit.write().insert(req_id, pending);
+							self.dispatched.write().insert(req_id, Dispatched::new(peer_id, None));
+							*load.entry(peer_id).or_insert(0) += 1;
 							return None;
 						}
 					}
@@ -564,6 +626,81 @@ impl OnDemand {
 		trace!(target: "on_demand", "Was unable to dispatch {} requests.", pending.len());
 	}
 
+	// number of dispatched requests each peer still has to answer.
+	fn peer_load(&self) -> HashMap<PeerId, usize> {
+		let mut load = HashMap::new();
+		for dispatched in self.dispatched.read().values() {
+			*load.entry(dispatched.peer).or_insert(0) += 1;
+		}
+		load
+	}
+
+	// send a copy of each request which missed its soft deadline to another peer.
+	// whichever peer responds first wins, the other copy is cancelled. the late peer is
+	// penalized as if it had responded just now, so it isn't preferred for new requests.
+	fn retry_late_requests(&self, ctx: &dyn BasicContext) {
+		let peers = self.peers.read();
+		let in_transit = self.in_transit.read();
+		let mut dispatched = self.dispatched.write();
+		let mut latencies = self.latencies.write();
+
+		let late: Vec<(ReqId, PeerId, Duration)> = dispatched.iter()
+			.filter(|&(_, d)| d.duplicate_of.is_none() && !d.duplicated)
+			.map(|(req_id, d)| (*req_id, d.peer, d.sent.elapsed()))
+			.filter(|&(_, peer, elapsed)| elapsed > soft_deadline(latencies.get(&peer)))
+			.collect();
+
+		if late.is_empty() {
+			return
+		}
+
+		let mut load = HashMap::new();
+		for d in dispatched.values() {
+			*load.entry(d.peer).or_insert(0) += 1;
+		}
+
+		for (req_id, origin, elapsed) in late {
+			let pending = match in_transit.get(&req_id) {
+				Some(pending) => pending,
+				None => continue,
+			};
+
+			let alternates = rank_peers(&peers, &pending.required_capabilities, &load, &latencies)
+				.into_iter()
+				.filter(|peer_id| *peer_id != origin);
+
+			for peer_id in alternates {
+				if let Ok(duplicate_id) = ctx.request_from(peer_id, pending.net_requests.clone()) {
+					trace!(target: "on_demand", "Request to peer {} missed its deadline, retrying with peer {}", origin, peer_id);
+					dispatched.insert(duplicate_id, Dispatched::new(peer_id, Some(req_id)));
+					if let Some(original) = dispatched.get_mut(&req_id) {
+						original.duplicated = true;
+					}
+					*load.entry(peer_id).or_insert(0) += 1;
+					note_latency(&mut latencies, origin, elapsed);
+					break;
+				}
+			}
+		}
+	}
+
+	// note a response to a dispatched request: update the latency of the peer and cancel the
+	// other copy of a duplicated request. returns the id the pending request is held under in
+	// `in_transit`.
+	fn note_response(&self, peer_id: PeerId, req_id: ReqId) -> ReqId {
+		let mut dispatched = self.dispatched.write();
+		let request = match dispatched.remove(&req_id) {
+			Some(request) => request,
+			None => return req_id,
+		};
+
+		note_latency(&mut self.latencies.write(), peer_id, request.sent.elapsed());
+
+		let original = request.duplicate_of.unwrap_or(req_id);
+		dispatched.retain(|id, d| *id != original && d.duplicate_of != Some(original));
+		original
+	}
+
 	// submit a pending request set. attempts to answer from cache before
 	// going to the network. if complete, sends response and consumes the struct.
 	fn submit_pending(&self, ctx: &dyn BasicContext, mut pending: Pending) {
@@ -599,14 +736,43 @@ impl Handler for OnDemand {
 
 	fn on_disconnect(&self, ctx: &dyn EventContext, unfulfilled: &[ReqId]) {
 		self.peers.write().remove(&ctx.peer());
+		self.latencies.write().remove(&ctx.peer());
 		let ctx = ctx.as_basic();
 
 		{
 			let mut pending = self.pending.write();
+			let mut in_transit = self.in_transit.write();
+			let mut dispatched = self.dispatched.write();
 			for unfulfilled in unfulfilled {
-				if let Some(unfulfilled) = self.in_transit.write().remove(unfulfilled) {
-					trace!(target: "on_demand", "Attempting to reassign dropped request");
-					pending.push(unfulfilled);
+				// the original of a dropped duplicate is still in transit and may be retried again.
+				if let Some(Dispatched { duplicate_of: Some(original), .. }) = dispatched.remove(unfulfilled) {
+					if let Some(original) = dispatched.get_mut(&original) {
+						original.duplicated = false;
+					}
+					continue
+				}
+
+				let request = match in_transit.remove(unfulfilled) {
+					Some(request) => request,
+					None => continue,
+				};
+
+				let duplicate = dispatched.iter()
+					.find(|&(_, d)| d.duplicate_of == Some(*unfulfilled))
+					.map(|(req_id, _)| *req_id);
+
+				match duplicate {
+					Some(duplicate_id) => {
+						trace!(target: "on_demand", "Dropped request is still in transit to another peer");
+						if let Some(d) = dispatched.get_mut(&duplicate_id) {
+							d.duplicate_of = None;
+						}
+						in_transit.insert(duplicate_id, request);
+					}
+					None => {
+						trace!(target: "on_demand", "Attempting to reassign dropped request");
+						pending.push(request);
+					}
 				}
 			}
 		}
@@ -627,6 +793,7 @@ impl Handler for OnDemand {
 	}
 
 	fn on_responses(&self, ctx: &dyn EventContext, req_id: ReqId, responses: &[basic_request::Response]) {
+		let req_id = self.note_response(ctx.peer(), req_id);
 		let mut pending = match self.in_transit.write().remove(&req_id) {
 			Some(req) => req,
 			None => return,
@@ -663,6 +830,7 @@ impl Handler for OnDemand {
 	}
 
 	fn tick(&self, ctx: &dyn BasicContext) {
+		self.retry_late_requests(ctx);
 		self.attempt_dispatch(ctx)
 	}
 }
@@ -596,3 +596,145 @@ fn empty_responses_exceeds_limit_should_be_dropped() {
 	assert!(harness.service.in_transit.read().is_empty());
 	assert!(harness.service.pending.read().is_empty());
 }
+
+#[test]
+fn prefers_fast_and_idle_peers() {
+	let harness = Harness::create();
+	let (slow, fast, busy) = (1, 2, 3);
+
+	for peer_id in &[slow, fast, busy] {
+		harness.inject_peer(*peer_id, Peer {
+			status: dummy_status(),
+			capabilities: dummy_capabilities(),
+		});
+	}
+
+	let mut latencies = ::std::collections::HashMap::new();
+	latencies.insert(slow, Duration::from_secs(2));
+	latencies.insert(fast, Duration::from_millis(300));
+	latencies.insert(busy, Duration::from_millis(200));
+
+	let mut load = ::std::collections::HashMap::new();
+	load.insert(busy, 2);
+
+	let peers = harness.service.peers.read();
+	let ranked = super::rank_peers(&peers, &dummy_capabilities(), &load, &latencies);
+	assert_eq!(ranked, vec![fast, busy, slow]);
+}
+
+#[test]
+fn late_request_is_retried_with_another_peer() {
+	let harness = Harness::create();
+	let peer_ids = (10101, 12345);
+	let req_ids = (ReqId(14426), ReqId(555));
+
+	for peer_id in &[peer_ids.0, peer_ids.1] {
+		harness.inject_peer(*peer_id, Peer {
+			status: dummy_status(),
+			capabilities: dummy_capabilities(),
+		});
+	}
+
+	let header = Header::default();
+	let encoded = header.encoded();
+
+	let recv = harness.service.request_raw(
+		&Context::NoOp,
+		vec![request::HeaderByHash(header.hash().into()).into()]
+	).unwrap();
+
+	harness.service.dispatch_pending(&Context::RequestFrom(peer_ids.0, req_ids.0));
+	assert_eq!(harness.service.in_transit.read().len(), 1);
+
+	// still within the soft deadline
+	harness.service.retry_late_requests(&Context::NoOp);
+	assert_eq!(harness.service.dispatched.read().len(), 1);
+
+	// pretend the request was sent a while ago
+	harness.service.dispatched.write().get_mut(&req_ids.0).unwrap().sent = Instant::now() - Duration::from_secs(10);
+	harness.service.retry_late_requests(&Context::RequestFrom(peer_ids.1, req_ids.1));
+	assert_eq!(harness.service.dispatched.read().len(), 2);
+
+	// the first response wins and the original request is cancelled
+	harness.service.on_responses(
+		&Context::WithPeer(peer_ids.1),
+		req_ids.1,
+		&[Response::Headers(basic_request::HeadersResponse { headers: vec![encoded.clone()] })]
+	);
+	assert!(harness.service.dispatched.read().is_empty());
+	assert!(harness.service.in_transit.read().is_empty());
+	assert!(recv.wait().is_ok());
+
+	// the late response is ignored
+	harness.service.on_responses(
+		&Context::WithPeer(peer_ids.0),
+		req_ids.0,
+		&[Response::Headers(basic_request::HeadersResponse { headers: vec![encoded] })]
+	);
+	assert!(harness.service.pending.read().is_empty());
+}
+
+#[test]
+fn late_peer_latency_is_penalized() {
+	let harness = Harness::create();
+	let peer_ids = (10101, 12345);
+	let req_ids = (ReqId(14426), ReqId(555));
+
+	for peer_id in &[peer_ids.0, peer_ids.1] {
+		harness.inject_peer(*peer_id, Peer {
+			status: dummy_status(),
+			capabilities: dummy_capabilities(),
+		});
+	}
+	harness.service.latencies.write().insert(peer_ids.0, Duration::from_millis(100));
+
+	let _recv = harness.service.request_raw(
+		&Context::NoOp,
+		vec![request::HeaderByHash(Header::default().hash().into()).into()]
+	).unwrap();
+
+	harness.service.dispatch_pending(&Context::RequestFrom(peer_ids.0, req_ids.0));
+	harness.service.dispatched.write().get_mut(&req_ids.0).unwrap().sent = Instant::now() - Duration::from_secs(10);
+	harness.service.retry_late_requests(&Context::RequestFrom(peer_ids.1, req_ids.1));
+
+	let latency = harness.service.latencies.read()[&peer_ids.0];
+	assert!(latency > Duration::from_secs(2), "latency of the late peer is {:?}", latency);
+	// the peer which got the copy is now preferred
+	let peers = harness.service.peers.read();
+	let latencies = harness.service.latencies.read();
+	let ranked = super::rank_peers(&peers, &dummy_capabilities(), &Default::default(), &latencies);
+	assert_eq!(ranked, vec![peer_ids.1, peer_ids.0]);
+}
+
+#[test]
+fn late_request_is_retried_again_after_duplicate_peer_disconnects() {
+	let harness = Harness::create();
+	let peer_ids = (10101, 12345, 54321);
+	let req_ids = (ReqId(14426), ReqId(555), ReqId(556));
+
+	for peer_id in &[peer_ids.0, peer_ids.1, peer_ids.2] {
+		harness.inject_peer(*peer_id, Peer {
+			status: dummy_status(),
+			capabilities: dummy_capabilities(),
+		});
+	}
+
+	let _recv = harness.service.request_raw(
+		&Context::NoOp,
+		vec![request::HeaderByHash(Header::default().hash().into()).into()]
+	).unwrap();
+
+	harness.service.dispatch_pending(&Context::RequestFrom(peer_ids.0, req_ids.0));
+	harness.service.dispatched.write().get_mut(&req_ids.0).unwrap().sent = Instant::now() - Duration::from_secs(10);
+	harness.service.retry_late_requests(&Context::RequestFrom(peer_ids.1, req_ids.1));
+	assert!(harness.service.dispatched.read()[&req_ids.0].duplicated);
+
+	// the copy is lost with its peer, so the original may be copied again
+	harness.service.on_disconnect(&Context::WithPeer(peer_ids.1), &[req_ids.1]);
+	assert!(!harness.service.dispatched.read()[&req_ids.0].duplicated);
+	assert_eq!(harness.service.in_transit.read().len(), 1);
+
+	harness.service.dispatched.write().get_mut(&req_ids.0).unwrap().sent = Instant::now() - Duration::from_secs(60);
+	harness.service.retry_late_requests(&Context::RequestFrom(peer_ids.2, req_ids.2));
+	assert_eq!(harness.service.dispatched.read()[&req_ids.2].duplicate_of, Some(req_ids.0));
+}
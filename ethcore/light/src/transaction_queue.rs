@@ -250,6 +250,13 @@ impl TransactionQueue {
 		self.by_account.get(address).map(AccountTransactions::next_nonce)
 	}
 
+	/// Whether a transaction from `address` with `nonce` is queued.
+	pub fn contains(&self, address: &Address, nonce: &U256) -> bool {
+		self.by_account.get(address).map_or(false, |acct_txs| {
+			acct_txs.current.iter().any(|tx| tx.nonce == *nonce) || acct_txs.future.contains_key(nonce)
+		})
+	}
+
 	/// Get all transactions ready to be propagated.
 	/// `best_block_number` and `best_block_timestamp` are used to filter out conditionally
 	/// propagated transactions.
@@ -587,5 +594,8 @@ mod tests {
 
 		assert_eq!(txq.future_transactions(0, 0).len(), 7);
 		assert_eq!(txq.next_nonce(&sender).unwrap(), 1.into());
+		assert!(txq.contains(&sender, &0.into()));
+		assert!(txq.contains(&sender, &3.into()));
+		assert!(!txq.contains(&sender, &1.into()));
 	}
 }
@@ -86,6 +86,7 @@ impl ExecutedBlock {
 			last_hashes: self.last_hashes.clone(),
 			gas_used: self.receipts.last().map_or(U256::zero(), |r| r.gas_used),
 			gas_limit: self.header.gas_limit().clone(),
+			base_fee: self.header.base_fee(),
 		}
 	}
 
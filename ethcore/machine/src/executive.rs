@@ -862,6 +862,13 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 			return Err(ExecutionError::InvalidNonce { expected: nonce, got: t.nonce });
 		}
 
+		// validate if transaction pays at least the base fee, virtual calls (not checking nonce) are exempt
+		if let Some(base_fee) = self.info.base_fee {
+			if check_nonce && t.gas_price < base_fee {
+				return Err(ExecutionError::GasPriceLowerThanBaseFee { base_fee, got: t.gas_price });
+			}
+		}
+
 		// validate if transaction fits into given block
 		if self.info.gas_used + t.gas > self.info.gas_limit {
 			return Err(ExecutionError::BlockGasLimitReached {
@@ -1155,7 +1162,9 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 
 		let gas_used = t.gas.saturating_sub(gas_left);
 		let (refund_value, overflow_1) = gas_left.overflowing_mul(t.gas_price);
-		let (fees_value, overflow_2) = gas_used.overflowing_mul(t.gas_price);
+		// after EIP-1559 the author only receives the tip above the base fee, the rest is burnt
+		let author_gas_price = self.info.base_fee.map_or(t.gas_price, |base_fee| t.gas_price.saturating_sub(base_fee));
+		let (fees_value, overflow_2) = gas_used.overflowing_mul(author_gas_price);
 		if overflow_1 || overflow_2 {
 			return Err(ExecutionError::TransactionMalformed("U256 Overflow".to_string()));
 		}
@@ -2101,6 +2110,70 @@ mod tests {
 		}
 	}
 
+	evm_test!{test_gas_price_lower_than_base_fee: test_gas_price_lower_than_base_fee_int}
+	fn test_gas_price_lower_than_base_fee(factory: Factory) {
+		let keypair = Random.generate().unwrap();
+		let t = Transaction {
+			action: Action::Call(Address::from_low_u64_be(0xff)),
+			value: U256::zero(),
+			data: vec![],
+			gas: U256::from(21_000),
+			gas_price: U256::from(5),
+			nonce: U256::zero()
+		}.sign(keypair.secret(), None);
+
+		let mut state = get_temp_state_with_factory(factory);
+		state.add_balance(&t.sender(), &U256::from(1_000_000), CleanupMode::NoEmpty).unwrap();
+		let mut info = EnvInfo::default();
+		info.gas_limit = U256::from(100_000);
+		info.base_fee = Some(U256::from(7));
+		let machine = make_frontier_machine(0);
+		let schedule = machine.schedule(info.number);
+
+		let res = {
+			let mut ex = Executive::new(&mut state, &info, &machine, &schedule);
+			ex.transact(&t, TransactOptions::with_no_tracing())
+		};
+
+		match res {
+			Err(ExecutionError::GasPriceLowerThanBaseFee { base_fee, got })
+				if base_fee == U256::from(7) && got == U256::from(5) => (),
+			_ => assert!(false, "Expected gas price lower than base fee error. {:?}", res)
+		}
+	}
+
+	evm_test!{test_base_fee_is_burnt: test_base_fee_is_burnt_int}
+	fn test_base_fee_is_burnt(factory: Factory) {
+		let keypair = Random.generate().unwrap();
+		let t = Transaction {
+			action: Action::Call(Address::from_low_u64_be(0xff)),
+			value: U256::zero(),
+			data: vec![],
+			gas: U256::from(21_000),
+			gas_price: U256::from(10),
+			nonce: U256::zero()
+		}.sign(keypair.secret(), None);
+		let sender = t.sender();
+
+		let mut state = get_temp_state_with_factory(factory);
+		state.add_balance(&sender, &U256::from(1_000_000), CleanupMode::NoEmpty).unwrap();
+		let mut info = EnvInfo::default();
+		info.author = Address::from_low_u64_be(0xaa);
+		info.gas_limit = U256::from(100_000);
+		info.base_fee = Some(U256::from(7));
+		let machine = make_frontier_machine(0);
+		let schedule = machine.schedule(info.number);
+
+		let executed = {
+			let mut ex = Executive::new(&mut state, &info, &machine, &schedule);
+			ex.transact(&t, TransactOptions::with_no_tracing()).unwrap()
+		};
+
+		assert_eq!(executed.gas_used, U256::from(21_000));
+		assert_eq!(state.balance(&sender).unwrap(), U256::from(790_000));
+		assert_eq!(state.balance(&info.author).unwrap(), U256::from(63_000));
+	}
+
 	evm_test!{test_keccak: test_keccak_int}
 	fn test_keccak(factory: Factory) {
 		let code = "6064640fffffffff20600055".from_hex().unwrap();
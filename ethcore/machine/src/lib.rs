@@ -15,8 +15,8 @@
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 //! This crate provides a state machine and the facilities needed to execute transactions and the
-//! code contained therein, as well as contract based transaction permissions. All ethereum
-//! engines embed a `Machine`.
+//! code contained therein, as well as contract based transaction permissions and other
+//! transaction admission rules. All ethereum engines embed a `Machine`.
 
 pub mod executed;
 pub mod executed_block;
@@ -26,6 +26,7 @@ pub mod machine;
 pub mod substate;
 pub mod transaction_ext;
 pub mod tx_filter;
+pub mod tx_validator;
 
 pub use crate::{
 	executed_block::ExecutedBlock,
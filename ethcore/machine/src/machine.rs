@@ -48,6 +48,7 @@ use crate::{
 	executive::Executive,
 	substate::Substate,
 	tx_filter::TransactionFilter,
+	tx_validator::{self, TransactionValidator},
 };
 
 /// Parity tries to round block.gas_limit to multiple of this constant
@@ -61,6 +62,7 @@ pub struct Machine {
 	params: CommonParams,
 	builtins: Arc<BTreeMap<Address, Builtin>>,
 	tx_filter: Option<Arc<TransactionFilter>>,
+	tx_validators: Vec<Arc<dyn TransactionValidator>>,
 	ethash_extensions: Option<EthashExtensions>,
 	schedule_rules: Option<Box<ScheduleCreationRules>>,
 }
@@ -69,10 +71,12 @@ impl Machine {
 	/// Regular ethereum machine.
 	pub fn regular(params: CommonParams, builtins: BTreeMap<Address, Builtin>) -> Machine {
 		let tx_filter = TransactionFilter::from_params(&params).map(Arc::new);
+		let tx_validators = params.transaction_validators.iter().map(tx_validator::from_params).collect();
 		Machine {
 			params,
 			builtins: Arc::new(builtins),
 			tx_filter,
+			tx_validators,
 			ethash_extensions: None,
 			schedule_rules: None,
 		}
@@ -219,6 +223,7 @@ impl Machine {
 	/// The gas floor target must not be lower than the engine's minimum gas limit.
 	pub fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, gas_ceil_target: U256) {
 		header.set_difficulty(parent.difficulty().clone());
+		header.set_base_fee(self.params().next_base_fee(parent));
 		let gas_limit = parent.gas_limit().clone();
 		assert!(!gas_limit.is_zero(), "Gas limit should be > 0");
 
@@ -345,6 +350,12 @@ impl Machine {
 		};
 		t.verify_basic(check_low_s, chain_id)?;
 
+		if let Some(max_gas) = self.params().max_transaction_gas(header.number()) {
+			if t.gas > max_gas {
+				return Err(transaction::Error::GasLimitExceeded { limit: max_gas, got: t.gas });
+			}
+		}
+
 		Ok(())
 	}
 
@@ -361,9 +372,19 @@ impl Machine {
 			}
 		}
 
+		for validator in &self.tx_validators {
+			validator.validate(t, parent, client)?;
+		}
+
 		Ok(())
 	}
 
+	/// Append a validator to the chain of transaction admission rules configured in the spec.
+	/// Validators are consulted in order, the first failing one rejects the transaction.
+	pub fn add_transaction_validator(&mut self, validator: Arc<dyn TransactionValidator>) {
+		self.tx_validators.push(validator);
+	}
+
 	/// Performs pre-validation of RLP decoded transaction before other processing
 	pub fn decode_transaction(&self, transaction: &[u8]) -> Result<UnverifiedTransaction, transaction::Error> {
 		let rlp = Rlp::new(&transaction);
@@ -438,6 +459,35 @@ mod tests {
 		assert_eq!(res, Err(transaction::Error::InvalidSignature("invalid EC signature".into())));
 	}
 
+	#[test]
+	fn should_reject_transactions_above_gas_cap() {
+		use parity_crypto::publickey::{Generator, Random};
+
+		let keypair = Random.generate().unwrap();
+		let transaction: UnverifiedTransaction = transaction::Transaction {
+			action: transaction::Action::Create,
+			value: U256::zero(),
+			data: Vec::new(),
+			gas: U256::from(100_000),
+			gas_price: U256::zero(),
+			nonce: U256::zero(),
+		}.sign(keypair.secret(), None).into();
+		let mut params = spec::new_instant().params().clone();
+		params.max_transaction_gas = Some(transaction.gas - 1);
+		params.max_transaction_gas_transition = 10;
+		let machine = Machine::regular(params, Default::default());
+
+		let mut header = Header::new();
+		header.set_number(9);
+		assert!(machine.verify_transaction_basic(&transaction, &header).is_ok());
+
+		header.set_number(10);
+		assert_eq!(
+			machine.verify_transaction_basic(&transaction, &header),
+			Err(transaction::Error::GasLimitExceeded { limit: transaction.gas - 1, got: transaction.gas })
+		);
+	}
+
 	#[test]
 	fn ethash_gas_limit_is_multiple_of_determinant() {
 		use ethereum_types::U256;
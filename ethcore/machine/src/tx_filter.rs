@@ -60,17 +60,22 @@ impl TransactionFilter {
 	/// Create a new instance if address is specified in params.
 	pub fn from_params(params: &CommonParams) -> Option<TransactionFilter> {
 		params.transaction_permission_contract.map(|address|
-			TransactionFilter {
-				contract_address: address,
-				transition_block: params.transaction_permission_contract_transition,
-				permission_cache: Mutex::new(LruCache::new(MAX_CACHE_SIZE)),
-				contract_version_cache: Mutex::new(LruCache::new(MAX_CACHE_SIZE)),
-			}
+			TransactionFilter::new(address, params.transaction_permission_contract_transition)
 		)
 	}
 
+	/// Create a new instance for the contract at given address, used from given block.
+	pub fn new(contract_address: Address, transition_block: BlockNumber) -> TransactionFilter {
+		TransactionFilter {
+			contract_address,
+			transition_block,
+			permission_cache: Mutex::new(LruCache::new(MAX_CACHE_SIZE)),
+			contract_version_cache: Mutex::new(LruCache::new(MAX_CACHE_SIZE)),
+		}
+	}
+
 	/// Check if transaction is allowed at given block.
-	pub fn transaction_allowed<C: BlockInfo + CallContract>(&self, parent_hash: &H256, block_number: BlockNumber, transaction: &SignedTransaction, client: &C) -> bool {
+	pub fn transaction_allowed<C: BlockInfo + CallContract + ?Sized>(&self, parent_hash: &H256, block_number: BlockNumber, transaction: &SignedTransaction, client: &C) -> bool {
 		if block_number < self.transition_block { return true; }
 
 		let mut permission_cache = self.permission_cache.lock();
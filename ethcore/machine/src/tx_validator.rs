@@ -0,0 +1,144 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pluggable transaction admission rules for permissioned chains.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use ethereum_types::Address;
+
+use client_traits::BlockInfo;
+use common_types::{
+	header::Header,
+	engines::params::TransactionValidatorParams,
+	transaction::{self, SignedTransaction},
+};
+use ethcore_call_contract::CallContract;
+
+use crate::tx_filter::TransactionFilter;
+
+/// Chain access available to transaction validators.
+pub trait ValidatorClient: BlockInfo + CallContract {}
+
+impl<T: BlockInfo + CallContract> ValidatorClient for T {}
+
+/// Transaction admission rule, consulted whenever a transaction is verified against its parent block,
+/// both when it enters the transaction queue and when a block including it is imported.
+pub trait TransactionValidator: Send + Sync {
+	/// Check if the transaction may be included in the block following `parent`.
+	fn validate(&self, transaction: &SignedTransaction, parent: &Header, client: &dyn ValidatorClient) -> Result<(), transaction::Error>;
+}
+
+/// Only allows transactions from given senders.
+pub struct SenderAllowList {
+	senders: HashSet<Address>,
+}
+
+impl SenderAllowList {
+	/// Create a new allow-list.
+	pub fn new<I: IntoIterator<Item = Address>>(senders: I) -> Self {
+		SenderAllowList { senders: senders.into_iter().collect() }
+	}
+}
+
+impl TransactionValidator for SenderAllowList {
+	fn validate(&self, transaction: &SignedTransaction, _parent: &Header, _client: &dyn ValidatorClient) -> Result<(), transaction::Error> {
+		if self.senders.contains(&transaction.sender()) {
+			Ok(())
+		} else {
+			Err(transaction::Error::NotAllowed)
+		}
+	}
+}
+
+/// Rejects transactions with data longer than the limit.
+pub struct MaxPayloadSize {
+	max_size: usize,
+}
+
+impl MaxPayloadSize {
+	/// Create a new payload size policy.
+	pub fn new(max_size: usize) -> Self {
+		MaxPayloadSize { max_size }
+	}
+}
+
+impl TransactionValidator for MaxPayloadSize {
+	fn validate(&self, transaction: &SignedTransaction, _parent: &Header, _client: &dyn ValidatorClient) -> Result<(), transaction::Error> {
+		if transaction.data.len() > self.max_size {
+			Err(transaction::Error::TooBig)
+		} else {
+			Ok(())
+		}
+	}
+}
+
+impl TransactionValidator for TransactionFilter {
+	fn validate(&self, transaction: &SignedTransaction, parent: &Header, client: &dyn ValidatorClient) -> Result<(), transaction::Error> {
+		if self.transaction_allowed(&parent.hash(), parent.number() + 1, transaction, client) {
+			Ok(())
+		} else {
+			Err(transaction::Error::NotAllowed)
+		}
+	}
+}
+
+/// Instantiate the validator configured in the spec.
+pub fn from_params(params: &TransactionValidatorParams) -> Arc<dyn TransactionValidator> {
+	match *params {
+		TransactionValidatorParams::SenderAllowList(ref senders) => Arc::new(SenderAllowList::new(senders.iter().cloned())),
+		TransactionValidatorParams::PermissionContract { address, transition } => Arc::new(TransactionFilter::new(address, transition)),
+		TransactionValidatorParams::MaxPayloadSize(max_size) => Arc::new(MaxPayloadSize::new(max_size)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+	use std::sync::Arc;
+
+	use common_types::{
+		engines::params::{CommonParams, TransactionValidatorParams},
+		header::Header,
+		transaction::{Error, Transaction},
+	};
+	use ethcore::test_helpers::TestBlockChainClient;
+	use parity_crypto::publickey::{Generator, Random};
+
+	use crate::Machine;
+	use super::MaxPayloadSize;
+
+	#[test]
+	fn should_consult_validators_in_order() {
+		let allowed = Random.generate().unwrap();
+		let other = Random.generate().unwrap();
+		let params = CommonParams {
+			transaction_validators: vec![TransactionValidatorParams::SenderAllowList(vec![allowed.address()])],
+			..Default::default()
+		};
+		let mut machine = Machine::regular(params, BTreeMap::new());
+		machine.add_transaction_validator(Arc::new(MaxPayloadSize::new(4)));
+
+		let client = TestBlockChainClient::new();
+		let parent = Header::new();
+		let transaction = |data: Vec<u8>, key: &parity_crypto::publickey::KeyPair| Transaction { data, ..Default::default() }.sign(key.secret(), None);
+
+		assert_eq!(machine.verify_transaction(&transaction(vec![1; 4], &allowed), &parent, &client), Ok(()));
+		assert_eq!(machine.verify_transaction(&transaction(vec![1; 4], &other), &parent, &client), Err(Error::NotAllowed));
+		assert_eq!(machine.verify_transaction(&transaction(vec![1; 5], &allowed), &parent, &client), Err(Error::TooBig));
+	}
+}
@@ -81,6 +81,10 @@ impl pool::ScoredTransaction for VerifiedPrivateTransaction {
 	fn nonce(&self) -> U256 {
 		self.transaction.nonce
 	}
+
+	fn origin(&self) -> pool::TransactionOrigin {
+		pool::TransactionOrigin::Network
+	}
 }
 
 /// Checks readiness of transactions by looking if the transaction from sender already exists.
@@ -133,7 +137,7 @@ impl Default for VerificationStore {
 			verification_pool: RwLock::new(
 				txpool::Pool::new(
 					txpool::NoopListener,
-					pool::scoring::NonceAndGasPrice(pool::PrioritizationStrategy::GasPriceOnly),
+					pool::scoring::NonceAndGasPrice::default(),
 					pool::Options {
 						max_count: MAX_QUEUE_LEN,
 						max_per_sender: MAX_QUEUE_LEN / 10,
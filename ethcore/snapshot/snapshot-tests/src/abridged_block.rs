@@ -41,6 +41,18 @@ fn empty_block_abridging() {
 	assert_eq!(abridged.to_block(H256::zero(), 0, receipts_root).unwrap(), b);
 }
 
+#[test]
+fn block_with_base_fee_abridging() {
+	let mut b = Block::default();
+	b.header.set_base_fee(Some(U256::from(1_000_000_000)));
+	b.header.set_seal(vec![::rlp::encode(&0u64), ::rlp::encode(&H256::zero())]);
+	let receipts_root = b.header.receipts_root().clone();
+	let encoded = encode_block(&b);
+
+	let abridged = AbridgedBlock::from_block_view(&view!(BlockView, &encoded));
+	assert_eq!(abridged.to_block(H256::zero(), 0, receipts_root).unwrap(), b);
+}
+
 #[test]
 #[should_panic]
 fn wrong_number() {
@@ -53,10 +53,12 @@ impl AbridgedBlock {
 	pub fn from_block_view(block_view: &BlockView) -> Self {
 		let header = block_view.header_view();
 		let seal_fields = header.seal();
+		let base_fee = header.base_fee();
 
-		// 10 header fields, unknown number of seal fields, and 2 block fields.
+		// 10 header fields, optional base fee, unknown number of seal fields, and 2 block fields.
 		let mut stream = RlpStream::new_list(
 			HEADER_FIELDS +
+			base_fee.is_some() as usize +
 			seal_fields.len() +
 			BLOCK_FIELDS
 		);
@@ -77,6 +79,11 @@ impl AbridgedBlock {
 			.append_list(&block_view.transactions())
 			.append_list(&block_view.uncles());
 
+		// write base fee wrapped in a list, so it's distinguishable from seal fields.
+		if let Some(base_fee) = base_fee {
+			stream.begin_list(1).append(&base_fee);
+		}
+
 		// write seal fields.
 		for field in seal_fields {
 			stream.append_raw(&field, 1);
@@ -117,8 +124,15 @@ impl AbridgedBlock {
 		uncles_rlp.append_list(&uncles);
 		header.set_uncles_hash(keccak(uncles_rlp.as_raw()));
 
+		let item_count = rlp.item_count()?;
+		let mut seal_start = HEADER_FIELDS + BLOCK_FIELDS;
+		if item_count > seal_start && rlp.at(seal_start)?.is_list() {
+			header.set_base_fee(Some(rlp.at(seal_start)?.val_at(0)?));
+			seal_start += 1;
+		}
+
 		let mut seal_fields = Vec::new();
-		for i in (HEADER_FIELDS + BLOCK_FIELDS)..rlp.item_count()? {
+		for i in seal_start..item_count {
 			let seal_rlp = rlp.at(i)?;
 			seal_fields.push(seal_rlp.as_raw().to_owned());
 		}
@@ -87,7 +87,9 @@ bundle_test_spec! {
 	"authority_round" => new_test_round,
 	"authority_round_block_reward_contract" => new_test_round_block_reward_contract,
 	"authority_round_empty_steps" => new_test_round_empty_steps,
+	"authority_round_randomness_beacon" => new_test_round_randomness_beacon,
 	"authority_round_randomness_contract" => new_test_round_randomness_contract,
+	"authority_round_randomness_contribution" => new_test_round_randomness_contribution,
 	"constructor" => new_test_constructor,
 	"ethereum/byzantium_test" => new_byzantium_test,
 	"ethereum/constantinople_test" => new_constantinople_test,
@@ -110,6 +112,7 @@ bundle_test_spec! {
 	"null_morden" => new_test,
 	"null_morden_with_reward" => new_test_with_reward,
 	"null_morden_with_finality" => new_test_with_finality,
+	"null_morden_with_gas_limit_target" => new_test_with_gas_limit_target,
 	"validator_contract" => new_validator_contract,
 	"validator_multi" => new_validator_multi,
 	"validator_safe_contract" => new_validator_safe_contract
@@ -48,6 +48,7 @@ use keccak_hash::{KECCAK_NULL_RLP, keccak};
 use log::{trace, warn};
 use machine::{executive::Executive, Machine, substate::Substate};
 use null_engine::NullEngine;
+use parity_crypto::publickey::{public_to_address, recover, Signature};
 use pod::PodState;
 use rlp::{Rlp, RlpStream};
 use trace::{NoopTracer, NoopVMTracer};
@@ -145,6 +146,7 @@ fn run_constructors<T: Backend>(
 			last_hashes: Default::default(),
 			gas_used: U256::zero(),
 			gas_limit: U256::max_value(),
+			base_fee: None,
 		};
 
 		let from = Address::zero();
@@ -218,6 +220,9 @@ pub struct Spec {
 	pub seal_rlp: Bytes,
 	/// Hardcoded synchronization. Allows the light client to immediately jump to a specific block.
 	pub hardcoded_sync: Option<SpecHardcodedSync>,
+	/// Trusted checkpoint. Allows the light client to start header synchronization from a specific block
+	/// without CHTs of the preceding blocks.
+	pub checkpoint: Option<SpecCheckpoint>,
 	/// Contract constructors to be executed on genesis.
 	pub constructors: Vec<(Address, Bytes)>,
 	/// May be pre-populated if we know this in advance.
@@ -258,6 +263,70 @@ impl fmt::Display for SpecHardcodedSync {
 	}
 }
 
+/// Part of `Spec`. Describes a trusted checkpoint and the signatures required to trust it.
+pub struct SpecCheckpoint {
+	/// Header of the checkpoint block.
+	pub header: encoded::Header,
+	/// Total difficulty including the checkpoint block.
+	pub total_difficulty: U256,
+	/// Signers allowed to vouch for the checkpoint, the number of distinct signers required
+	/// and the signatures. `None` if the checkpoint is trusted unconditionally.
+	pub signatures: Option<(Vec<Address>, usize, Vec<Bytes>)>,
+}
+
+impl SpecCheckpoint {
+	/// Message signed by checkpoint signers: `keccak(block hash ++ total difficulty)`.
+	pub fn message(&self) -> H256 {
+		let mut message = [0u8; 64];
+		message[..32].copy_from_slice(self.header.hash().as_bytes());
+		self.total_difficulty.to_big_endian(&mut message[32..]);
+		keccak(&message[..])
+	}
+
+	/// Check that enough distinct allowed signers signed the checkpoint.
+	pub fn verify(&self) -> Result<(), String> {
+		let (signers, threshold, signatures) = match self.signatures {
+			Some(ref signatures) => signatures,
+			None => return Ok(()),
+		};
+
+		let message = self.message();
+		let mut signed_by = Vec::new();
+		for signature in signatures {
+			if signature.len() != 65 {
+				return Err(format!("Invalid checkpoint signature length: {}", signature.len()));
+			}
+			let mut data = [0u8; 65];
+			data.copy_from_slice(signature);
+			let signer = recover(&Signature::from(data), &message)
+				.map(|public| public_to_address(&public))
+				.map_err(|e| format!("Invalid checkpoint signature: {}", e))?;
+			if signers.contains(&signer) && !signed_by.contains(&signer) {
+				signed_by.push(signer);
+			}
+		}
+
+		if signed_by.len() < *threshold {
+			return Err(format!("Checkpoint signed by {} of {} required signers", signed_by.len(), threshold));
+		}
+		Ok(())
+	}
+}
+
+impl From<ethjson::spec::Checkpoint> for SpecCheckpoint {
+	fn from(checkpoint: ethjson::spec::Checkpoint) -> Self {
+		SpecCheckpoint {
+			header: encoded::Header::new(checkpoint.header.into()),
+			total_difficulty: checkpoint.total_difficulty.into(),
+			signatures: checkpoint.signatures.map(|s| (
+				s.signers.into_iter().map(Into::into).collect(),
+				s.threshold.into(),
+				s.signatures.into_iter().map(Into::into).collect(),
+			)),
+		}
+	}
+}
+
 fn convert_json_to_spec(
 	(address, builtin): (ethjson::hash::Address, ethjson::spec::builtin::Builtin),
 ) -> Result<(Address, Builtin), Error> {
@@ -279,6 +348,7 @@ fn load_from(spec_params: SpecParams, s: ethjson::spec::Spec) -> Result<Spec, Er
 	let params = CommonParams::from(s.params);
 
 	let hardcoded_sync = s.hardcoded_sync.map(Into::into);
+	let checkpoint = s.checkpoint.map(Into::into);
 
 	let engine = Spec::engine(spec_params, s.engine, params, builtins);
 	let author = g.author;
@@ -318,6 +388,7 @@ fn load_from(spec_params: SpecParams, s: ethjson::spec::Spec) -> Result<Spec, Er
 		extra_data: g.extra_data,
 		seal_rlp,
 		hardcoded_sync,
+		checkpoint,
 		constructors,
 		genesis_state,
 		state_root,
@@ -404,6 +475,9 @@ impl Spec {
 		header.set_gas_used(self.gas_used.clone());
 		header.set_gas_limit(self.gas_limit.clone());
 		header.set_difficulty(self.difficulty.clone());
+		if self.params().eip1559_transition == 0 {
+			header.set_base_fee(Some(self.params().eip1559_base_fee_initial_value));
+		}
 		header.set_seal({
 			let r = Rlp::new(&self.seal_rlp);
 			r.iter().map(|f| f.as_raw().to_vec()).collect()
@@ -529,6 +603,7 @@ impl Spec {
 				gas_limit: U256::max_value(),
 				last_hashes: Arc::new(Vec::new()),
 				gas_used: 0.into(),
+				base_fee: genesis.base_fee(),
 			};
 
 			let from = Address::zero();
@@ -565,7 +640,9 @@ mod tests {
 	use ethcore::test_helpers::get_temp_state_db;
 	use tempdir::TempDir;
 
-	use super::Spec;
+	use parity_crypto::publickey::{sign, Generator, Random};
+
+	use super::{Spec, SpecCheckpoint};
 
 	#[test]
 	fn test_load_empty() {
@@ -573,6 +650,25 @@ mod tests {
 		assert!(Spec::load(&tempdir.path(), &[] as &[u8]).is_err());
 	}
 
+	#[test]
+	fn should_verify_checkpoint_signatures() {
+		let signers: Vec<_> = (0..3).map(|_| Random.generate().unwrap()).collect();
+		let mut checkpoint = SpecCheckpoint {
+			header: crate::new_test().genesis_header().encoded(),
+			total_difficulty: 0x20000.into(),
+			signatures: Some((signers.iter().map(|s| s.address()).collect(), 2, Vec::new())),
+		};
+		let message = checkpoint.message();
+		let signature = |i: usize| sign(signers[i].secret(), &message).unwrap()[..].to_vec();
+
+		assert!(checkpoint.verify().is_err());
+		// the same signer counts once
+		checkpoint.signatures.as_mut().unwrap().2 = vec![signature(0), signature(0)];
+		assert!(checkpoint.verify().is_err());
+		checkpoint.signatures.as_mut().unwrap().2 = vec![signature(0), signature(2)];
+		assert!(checkpoint.verify().is_ok());
+	}
+
 	#[test]
 	fn test_chain() {
 		let test_spec = crate::new_test();
@@ -124,6 +124,8 @@ impl<'x> OpenBlock<'x> {
 		r.block.header.set_timestamp(engine.open_block_header_timestamp(parent.timestamp()));
 		r.block.header.set_extra_data(extra_data);
 
+		// a gas limit target set by the engine takes precedence over the one of the miner.
+		let gas_range_target = engine.gas_limit_target(parent).map_or(gas_range_target, |target| (target, target));
 		let gas_floor_target = cmp::max(gas_range_target.0, engine.params().min_gas_limit);
 		let gas_ceil_target = cmp::max(gas_range_target.1, gas_floor_target);
 
@@ -185,22 +187,48 @@ impl<'x> OpenBlock<'x> {
 		Ok(self.block.receipts.last().expect("receipt just pushed; qed"))
 	}
 
-	/// Push transactions onto the block.
+	/// Check the receipt of the last pushed transaction against the header of the block being imported.
+	/// The cumulative gas used may not exceed the gas used of the block and the log bloom of the block
+	/// must contain the bloom of every receipt, so a block failing either check is rejected without
+	/// executing the rest of its transactions.
+	fn check_last_receipt(&self, expected: &Header, applied: usize, total: usize) -> Result<(), Error> {
+		let receipt = self.block.receipts.last().expect("called after a transaction has been pushed; qed");
+		if receipt.gas_used > *expected.gas_used() {
+			debug!(target: "enact", "Block #{} aborted after {}/{} transactions: gas used exceeded", expected.number(), applied, total);
+			return Err(BlockError::InvalidGasUsed(Mismatch {
+				expected: *expected.gas_used(),
+				found: receipt.gas_used,
+			}).into());
+		}
+		if !expected.log_bloom().contains_bloom(&receipt.log_bloom) {
+			debug!(target: "enact", "Block #{} aborted after {}/{} transactions: log bloom mismatch", expected.number(), applied, total);
+			return Err(BlockError::InvalidLogBloom(Box::new(Mismatch {
+				expected: *expected.log_bloom(),
+				found: receipt.log_bloom,
+			})).into());
+		}
+		Ok(())
+	}
+
+	/// Push transactions of the block with the given `expected` header onto the block.
 	#[cfg(not(feature = "slow-blocks"))]
-	fn push_transactions(&mut self, transactions: Vec<SignedTransaction>) -> Result<(), Error> {
-		for t in transactions {
+	fn push_transactions(&mut self, transactions: Vec<SignedTransaction>, expected: &Header) -> Result<(), Error> {
+		let total = transactions.len();
+		for (i, t) in transactions.into_iter().enumerate() {
 			self.push_transaction(t, None)?;
+			self.check_last_receipt(expected, i + 1, total)?;
 		}
 		Ok(())
 	}
 
-	/// Push transactions onto the block.
+	/// Push transactions of the block with the given `expected` header onto the block.
 	#[cfg(feature = "slow-blocks")]
-	fn push_transactions(&mut self, transactions: Vec<SignedTransaction>) -> Result<(), Error> {
+	fn push_transactions(&mut self, transactions: Vec<SignedTransaction>, expected: &Header) -> Result<(), Error> {
 		use std::time;
 
 		let slow_tx = option_env!("SLOW_TX_DURATION").and_then(|v| v.parse().ok()).unwrap_or(100);
-		for t in transactions {
+		let total = transactions.len();
+		for (i, t) in transactions.into_iter().enumerate() {
 			let hash = t.hash();
 			let start = time::Instant::now();
 			self.push_transaction(t, None)?;
@@ -210,6 +238,7 @@ impl<'x> OpenBlock<'x> {
 				warn!("Heavy ({} ms) transaction in block {:?}: {:?}", took_ms, self.block.header.number(), hash);
 			}
 			debug!(target: "tx", "Transaction {:?} took: {} ms", hash, took_ms);
+			self.check_last_receipt(expected, i + 1, total)?;
 		}
 
 		Ok(())
@@ -219,6 +248,7 @@ impl<'x> OpenBlock<'x> {
 	fn populate_from(&mut self, header: &Header) {
 		self.block.header.set_difficulty(*header.difficulty());
 		self.block.header.set_gas_limit(*header.gas_limit());
+		self.block.header.set_base_fee(header.base_fee());
 		self.block.header.set_timestamp(header.timestamp());
 		self.block.header.set_uncles_hash(*header.uncles_hash());
 		self.block.header.set_transactions_root(*header.transactions_root());
@@ -446,7 +476,7 @@ pub(crate) fn enact(
 	}
 
 	b.populate_from(&header);
-	b.push_transactions(transactions)?;
+	b.push_transactions(transactions, &header)?;
 
 	for u in uncles {
 		b.push_uncle(u)?;
@@ -545,7 +575,7 @@ mod tests {
 		)?;
 
 		b.populate_from(&header);
-		b.push_transactions(transactions)?;
+		b.push_transactions(transactions, &header)?;
 
 		for u in block.uncles {
 			b.push_uncle(u)?;
@@ -580,6 +610,18 @@ mod tests {
 		let _ = b.seal(&*spec.engine, vec![]);
 	}
 
+	#[test]
+	fn open_block_uses_engine_gas_limit_target() {
+		let spec = spec::new_test_with_gas_limit_target();
+		let genesis_header = spec.genesis_header();
+		let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let b = OpenBlock::new(&*spec.engine, Default::default(), false, db, &genesis_header, last_hashes, Address::zero(), (3141562.into(), 31415620.into()), vec![], false).unwrap();
+
+		// the miner's floor would lower the gas limit, the engine target raises it as far as the bound divisor allows
+		assert_eq!(*b.block.header.gas_limit(), 3144658.into());
+	}
+
 	#[test]
 	fn enact_block() {
 		let spec = spec::new_test();
@@ -637,4 +679,32 @@ mod tests {
 		assert!(orig_db.journal_db().keys().iter().filter(|k| orig_db.journal_db().get(k.0, EMPTY_PREFIX)
 			!= db.journal_db().get(k.0, EMPTY_PREFIX)).next() == None);
 	}
+
+	#[test]
+	fn check_receipts_against_expected_header() {
+		let spec = spec::new_test();
+		let engine = &*spec.engine;
+		let genesis_header = spec.genesis_header();
+		let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let mut b = OpenBlock::new(engine, Default::default(), false, db, &genesis_header, last_hashes, Address::zero(), (3141562.into(), 31415620.into()), vec![], false).unwrap();
+		b.block.receipts.push(Receipt::new(TransactionOutcome::Unknown, 21000.into(), vec![]));
+
+		let mut expected = Header::new();
+		expected.set_gas_used(21000.into());
+		assert!(b.check_last_receipt(&expected, 1, 2).is_ok());
+
+		expected.set_gas_used(20999.into());
+		match b.check_last_receipt(&expected, 1, 2) {
+			Err(Error::Block(BlockError::InvalidGasUsed(_))) => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+
+		expected.set_gas_used(21000.into());
+		b.block.receipts.last_mut().unwrap().log_bloom = Bloom::from_low_u64_be(1);
+		match b.check_last_receipt(&expected, 1, 2) {
+			Err(Error::Block(BlockError::InvalidLogBloom(_))) => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+	}
 }
@@ -0,0 +1,224 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Scheduled database backups and their retention.
+
+use std::{fs, io};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ethereum_types::H256;
+use parking_lot::RwLock;
+use types::backup::{BackupInfo, BackupStatus};
+use types::BlockNumber;
+
+use client::BackupConfig;
+
+/// Backups are written to `<path>/backup-<block number>`.
+const BACKUP_PREFIX: &str = "backup-";
+/// Backups in progress are written to `<path>/backup-<block number>.partial` and renamed once
+/// complete, so that a backup interrupted by a crash is neither restored nor retained.
+const PARTIAL_SUFFIX: &str = ".partial";
+
+/// Takes the scheduled backups and keeps track of their status.
+pub struct Backups {
+	config: BackupConfig,
+	status: RwLock<BackupStatus>,
+}
+
+impl Backups {
+	/// Create a new instance, backups are disabled if the config has no path.
+	pub fn new(config: BackupConfig) -> Self {
+		let status = BackupStatus {
+			enabled: config.path.is_some(),
+			..Default::default()
+		};
+
+		Backups {
+			config,
+			status: RwLock::new(status),
+		}
+	}
+
+	/// Whether a backup should be taken after importing the given canonical block.
+	pub fn is_due(&self, number: BlockNumber) -> bool {
+		self.config.is_due(number)
+	}
+
+	/// Status of the backups.
+	pub fn status(&self) -> BackupStatus {
+		self.status.read().clone()
+	}
+
+	/// Take a backup of the database at the given block, `write` copies the database to the given
+	/// directory. Does nothing if backups are disabled or another backup is in progress.
+	/// Old backups are removed afterwards according to the retention policy.
+	pub fn take<F>(&self, number: BlockNumber, hash: H256, write: F) where
+		F: FnOnce(&Path) -> io::Result<()>,
+	{
+		let dir = match self.config.path {
+			Some(ref dir) => dir,
+			None => return,
+		};
+
+		{
+			let mut status = self.status.write();
+			if status.in_progress {
+				return;
+			}
+			status.in_progress = true;
+		}
+
+		let path = dir.join(format!("{}{}", BACKUP_PREFIX, number));
+		let partial_path = dir.join(format!("{}{}{}", BACKUP_PREFIX, number, PARTIAL_SUFFIX));
+		info!(target: "client", "Taking database backup at block #{} to {}", number, path.display());
+
+		// a partial backup of the same block may be left over from an interrupted run.
+		let cleared = if partial_path.exists() { fs::remove_dir_all(&partial_path) } else { Ok(()) };
+		let result = cleared
+			.and_then(|_| write(&partial_path))
+			.and_then(|_| if path.exists() { fs::remove_dir_all(&path) } else { Ok(()) })
+			.and_then(|_| fs::rename(&partial_path, &path));
+
+		let mut status = self.status.write();
+		status.in_progress = false;
+		match result {
+			Ok(()) => {
+				info!(target: "client", "Database backup at block #{} finished", number);
+				let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+				status.last_backup = Some(BackupInfo { number, hash, timestamp, path });
+				status.last_error = None;
+
+				if let Err(e) = prune(dir, self.config.keep) {
+					warn!(target: "client", "Failed to remove old database backups: {}", e);
+				}
+			},
+			Err(e) => {
+				warn!(target: "client", "Database backup at block #{} failed: {}", number, e);
+				let _ = fs::remove_dir_all(&partial_path);
+				status.last_error = Some(e.to_string());
+			},
+		}
+	}
+}
+
+/// Remove all but the `keep` most recent complete backups in `dir`.
+fn prune(dir: &Path, keep: usize) -> io::Result<()> {
+	let mut backups = Vec::new();
+	for entry in fs::read_dir(dir)? {
+		let entry = entry?;
+		let number = entry.file_name().to_str()
+			.filter(|name| name.starts_with(BACKUP_PREFIX))
+			.and_then(|name| name[BACKUP_PREFIX.len()..].parse::<BlockNumber>().ok());
+		if let Some(number) = number {
+			backups.push((number, entry.path()));
+		}
+	}
+
+	backups.sort_by_key(|&(number, _)| number);
+	let excess = backups.len().saturating_sub(keep);
+	for (_, path) in backups.into_iter().take(excess) {
+		fs::remove_dir_all(path)?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempdir::TempDir;
+
+	fn backups(dir: &TempDir, keep: usize) -> Backups {
+		Backups::new(BackupConfig {
+			path: Some(dir.path().to_owned()),
+			interval: 10,
+			keep,
+		})
+	}
+
+	fn write_marker(path: &Path) -> io::Result<()> {
+		fs::create_dir_all(path)?;
+		fs::write(path.join("marker"), b"backup")
+	}
+
+	#[test]
+	fn takes_backups_and_prunes_old_ones() {
+		let dir = TempDir::new("backups").unwrap();
+		let backups = backups(&dir, 2);
+		assert!(backups.is_due(20));
+		assert!(!backups.is_due(21));
+
+		for number in &[10, 20, 30] {
+			backups.take(*number, H256::zero(), write_marker);
+		}
+
+		let status = backups.status();
+		assert!(status.enabled);
+		assert!(!status.in_progress);
+		assert_eq!(status.last_error, None);
+		assert_eq!(status.last_backup.map(|b| b.number), Some(30));
+		assert!(!dir.path().join("backup-10").exists());
+		assert!(dir.path().join("backup-20").join("marker").exists());
+		assert!(dir.path().join("backup-30").join("marker").exists());
+	}
+
+	#[test]
+	fn records_failed_backups() {
+		let dir = TempDir::new("backups").unwrap();
+		let backups = backups(&dir, 2);
+
+		backups.take(10, H256::zero(), write_marker);
+		backups.take(20, H256::zero(), |path| {
+			write_marker(path)?;
+			Err(io::Error::new(io::ErrorKind::Other, "disk full"))
+		});
+
+		let status = backups.status();
+		assert_eq!(status.last_error, Some("disk full".into()));
+		assert_eq!(status.last_backup.map(|b| b.number), Some(10));
+		assert!(!dir.path().join("backup-20").exists());
+		assert!(!dir.path().join("backup-20.partial").exists());
+	}
+
+	#[test]
+	fn ignores_interrupted_backups() {
+		let dir = TempDir::new("backups").unwrap();
+		let backups = backups(&dir, 1);
+
+		// left over by a crash during a backup
+		write_marker(&dir.path().join("backup-30.partial")).unwrap();
+
+		backups.take(20, H256::zero(), |path| {
+			assert_eq!(path, dir.path().join("backup-20.partial"));
+			assert!(!dir.path().join("backup-20").exists());
+			write_marker(path)
+		});
+
+		assert_eq!(backups.status().last_backup.map(|b| b.path), Some(dir.path().join("backup-20")));
+		assert!(dir.path().join("backup-20").join("marker").exists());
+		assert!(!dir.path().join("backup-20.partial").exists());
+		assert!(dir.path().join("backup-30.partial").exists());
+	}
+
+	#[test]
+	fn does_nothing_when_disabled() {
+		let backups = Backups::new(BackupConfig::default());
+		assert!(!backups.is_due(10_000));
+
+		backups.take(10_000, H256::zero(), |_| panic!("backups are disabled"));
+		assert_eq!(backups.status(), BackupStatus::default());
+	}
+}
@@ -16,66 +16,222 @@
 
 //! Stores recently seen bad blocks.
 
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use bytes::{Bytes, ToPretty};
-use ethereum_types::H256;
+use db;
 use itertools::Itertools;
-use memory_cache::MemoryLruCache;
+use kvdb::{DBTransaction, KeyValueDB};
 use parking_lot::RwLock;
+use rlp::{Rlp, RlpStream};
+use types::bad_block::{BadBlock, BadBlockReason};
 use types::verification::Unverified;
 
-/// Recently seen bad blocks.
-pub struct BadBlocks {
-	last_blocks: RwLock<MemoryLruCache<H256, (Unverified, String)>>,
+/// Key of the bad blocks in the extras column.
+const BAD_BLOCKS_KEY: &[u8] = b"bad_blocks";
+
+/// Maximal number of bad blocks kept around.
+const MAX_BAD_BLOCKS: usize = 128;
+/// Maximal total size of the RLP of the bad blocks kept around.
+const MAX_BAD_BLOCKS_SIZE: usize = 8 * 1024 * 1024;
+
+#[derive(Default)]
+struct LastBlocks {
+	/// Oldest first
+	blocks: VecDeque<BadBlock>,
+	/// Total size of the RLP of `blocks`
+	size: usize,
 }
 
-impl Default for BadBlocks {
-	fn default() -> Self {
-		BadBlocks {
-			last_blocks: RwLock::new(MemoryLruCache::new(8 * 1024 * 1024)),
+impl LastBlocks {
+	/// Append a bad block, dropping the oldest ones to stay within the limits.
+	fn push(&mut self, bad: BadBlock) {
+		let size = bad.block.bytes.len();
+		while !self.blocks.is_empty()
+			&& (self.blocks.len() == MAX_BAD_BLOCKS || self.size + size > MAX_BAD_BLOCKS_SIZE)
+		{
+			let oldest = self.blocks.pop_front().expect("blocks are not empty; qed");
+			self.size -= oldest.block.bytes.len();
 		}
+		self.size += size;
+		self.blocks.push_back(bad);
 	}
+
+	fn rlp_bytes(&self) -> Bytes {
+		let mut s = RlpStream::new_list(self.blocks.len());
+		for bad in &self.blocks {
+			s.append(bad);
+		}
+		s.out()
+	}
+}
+
+/// Recently seen bad blocks, oldest first, persisted in the client database so that they survive
+/// a restart.
+pub struct BadBlocks {
+	last_blocks: RwLock<LastBlocks>,
+	db: Arc<dyn KeyValueDB>,
 }
 
 impl BadBlocks {
+	/// Load the bad blocks persisted in `db`.
+	pub fn new(db: Arc<dyn KeyValueDB>) -> Self {
+		let mut last_blocks = LastBlocks::default();
+		let persisted = db.get(db::COL_EXTRA, BAD_BLOCKS_KEY)
+			.expect("Low level database error. Some issue with disk?");
+		if let Some(persisted) = persisted {
+			match Rlp::new(&persisted).as_list::<BadBlock>() {
+				Ok(blocks) => blocks.into_iter().for_each(|bad| last_blocks.push(bad)),
+				Err(err) => warn!(target: "client", "Ignoring undecodable persisted bad blocks: {:?}", err),
+			}
+		}
+
+		BadBlocks {
+			last_blocks: RwLock::new(last_blocks),
+			db,
+		}
+	}
+
 	/// Reports given RLP as invalid block.
-	pub fn report(&self, raw: Bytes, message: String) {
+	pub fn report(&self, raw: Bytes, reason: BadBlockReason, message: String) {
 		match Unverified::from_rlp(raw) {
 			Ok(unverified) => {
-				error!(
-					target: "client",
-					"\nBad block detected: {}\nRLP: {}\nHeader: {:?}\nUncles: {}\nTransactions:{}\n",
+				let hash = unverified.header.hash();
+				let mut last_blocks = self.last_blocks.write();
+				// the same block is usually propagated by many peers, keep the first report only.
+				if last_blocks.blocks.iter().any(|bad| bad.block.header.hash() == hash) {
+					return;
+				}
+
+				// blocks building on a bad block are only rejected, there is nothing to debug
+				if reason == BadBlockReason::Banned {
+					debug!(target: "client", "Bad block #{} ({}) rejected ({}): {}", unverified.header.number(), hash, reason, message);
+				} else {
+					error!(
+						target: "client",
+						"\nBad block detected ({}): {}\nRLP: {}\nHeader: {:?}\nUncles: {}\nTransactions:{}\n",
+						reason,
+						message,
+						unverified.bytes.to_hex(),
+						unverified.header,
+						unverified.uncles
+							.iter()
+							.enumerate()
+							.map(|(index, uncle)| format!("[Uncle {}] {:?}", index, uncle))
+							.join("\n"),
+						unverified.transactions
+							.iter()
+							.enumerate()
+							.map(|(index, tx)| format!("[Tx {}] {:?}", index, tx))
+							.join("\n"),
+					);
+				}
+
+				last_blocks.push(BadBlock {
+					block: unverified,
+					reason,
 					message,
-					unverified.bytes.to_hex(),
-					unverified.header,
-					unverified.uncles
-						.iter()
-						.enumerate()
-						.map(|(index, uncle)| format!("[Uncle {}] {:?}", index, uncle))
-						.join("\n"),
-					unverified.transactions
-						.iter()
-						.enumerate()
-						.map(|(index, tx)| format!("[Tx {}] {:?}", index, tx))
-						.join("\n"),
-				);
-				self.last_blocks.write().insert(unverified.header.hash(), (unverified, message));
+					rejected_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+				});
+
+				let mut batch = DBTransaction::new();
+				batch.put_vec(db::COL_EXTRA, BAD_BLOCKS_KEY, last_blocks.rlp_bytes());
+				if let Err(err) = self.db.write(batch) {
+					warn!(target: "client", "Failed to persist bad blocks: {}", err);
+				}
 			},
 			Err(err) => {
-				error!(target: "client", "Bad undecodable block detected: {}\n{:?}", message, err);
+				error!(target: "client", "Bad undecodable block detected ({}): {}\n{:?}", reason, message, err);
 			},
 		}
 	}
 
-	/// Returns a list of recently detected bad blocks with error descriptions.
-	pub fn bad_blocks(&self) -> Vec<(Unverified, String)> {
-		self.last_blocks.read()
-			.backstore()
-			.iter()
-			.map(|(_k, (unverified, message))| (
-				Unverified::from_rlp(unverified.bytes.clone())
-					.expect("Bytes coming from UnverifiedBlock so decodable; qed"),
-				message.clone(),
-			))
-			.collect()
+	/// Returns a list of recently detected bad blocks with rejection reasons, oldest first.
+	pub fn bad_blocks(&self) -> Vec<BadBlock> {
+		self.last_blocks.read().blocks.iter().cloned().collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use types::header::Header;
+
+	fn bad_blocks() -> BadBlocks {
+		BadBlocks::new(Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS)))
+	}
+
+	fn block(number: u64) -> Bytes {
+		let mut header = Header::new();
+		header.set_number(number);
+		let mut rlp = RlpStream::new_list(3);
+		rlp.append(&header);
+		rlp.append_raw(&::rlp::EMPTY_LIST_RLP, 1);
+		rlp.append_raw(&::rlp::EMPTY_LIST_RLP, 1);
+		rlp.out()
+	}
+
+	#[test]
+	fn should_keep_latest_reports_only() {
+		let bad_blocks = bad_blocks();
+		for number in 0..(MAX_BAD_BLOCKS as u64 + 2) {
+			bad_blocks.report(block(number), BadBlockReason::InvalidRoot, "invalid".into());
+		}
+
+		let reported = bad_blocks.bad_blocks();
+		assert_eq!(reported.len(), MAX_BAD_BLOCKS);
+		assert_eq!(reported[0].block.header.number(), 2);
+		assert_eq!(reported[MAX_BAD_BLOCKS - 1].block.header.number(), MAX_BAD_BLOCKS as u64 + 1);
+	}
+
+	#[test]
+	fn should_bound_size_of_reports() {
+		let bad_blocks = bad_blocks();
+		let mut large = Header::new();
+		large.set_extra_data(vec![0; MAX_BAD_BLOCKS_SIZE / 2]);
+		let large = |number| {
+			let mut header = large.clone();
+			header.set_number(number);
+			let mut rlp = RlpStream::new_list(3);
+			rlp.append(&header);
+			rlp.append_raw(&::rlp::EMPTY_LIST_RLP, 1);
+			rlp.append_raw(&::rlp::EMPTY_LIST_RLP, 1);
+			rlp.out()
+		};
+		bad_blocks.report(block(1), BadBlockReason::InvalidRoot, "invalid".into());
+		bad_blocks.report(large(2), BadBlockReason::InvalidRoot, "invalid".into());
+		bad_blocks.report(large(3), BadBlockReason::InvalidRoot, "invalid".into());
+
+		let reported = bad_blocks.bad_blocks();
+		assert_eq!(reported.len(), 1);
+		assert_eq!(reported[0].block.header.number(), 3);
+	}
+
+	#[test]
+	fn should_not_duplicate_reports() {
+		let bad_blocks = bad_blocks();
+		bad_blocks.report(block(1), BadBlockReason::BadSeal, "bad seal".into());
+		bad_blocks.report(block(1), BadBlockReason::Banned, "known bad".into());
+
+		let reported = bad_blocks.bad_blocks();
+		assert_eq!(reported.len(), 1);
+		assert_eq!(reported[0].reason, BadBlockReason::BadSeal);
+	}
+
+	#[test]
+	fn should_persist_reports() {
+		let db = Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS));
+		let bad_blocks = BadBlocks::new(db.clone());
+		bad_blocks.report(block(1), BadBlockReason::BadSeal, "bad seal".into());
+		bad_blocks.report(block(2), BadBlockReason::TooFarInFuture, "too far".into());
+
+		let reloaded = BadBlocks::new(db).bad_blocks();
+		assert_eq!(reloaded.len(), 2);
+		assert_eq!(reloaded[0].block.header.number(), 1);
+		assert_eq!(reloaded[0].reason, BadBlockReason::BadSeal);
+		assert_eq!(reloaded[1].message, "too far");
+		assert_eq!(reloaded[1].rejected_at, bad_blocks.bad_blocks()[1].rejected_at);
 	}
 }
@@ -15,7 +15,7 @@
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::cmp;
-use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::convert::TryFrom;
 use std::io::{BufRead, BufReader};
 use std::str::from_utf8;
@@ -26,7 +26,7 @@ use std::time::{Duration, Instant};
 use ansi_term::Colour;
 use bytes::Bytes;
 use bytes::ToPretty;
-use ethereum_types::{Address, H256, H264, U256};
+use ethereum_types::{Address, BigEndianHash, H256, H264, U256};
 use hash::keccak;
 use hash_db::EMPTY_PREFIX;
 use itertools::Itertools;
@@ -53,7 +53,7 @@ use blockchain::{
 };
 use call_contract::CallContract;
 use client::{
-	bad_blocks, BlockProducer, BroadcastProposalBlock, Call,
+	backup, bad_blocks, BlockProducer, BroadcastProposalBlock, Call,
 	ClientConfig, EngineInfo, ImportSealedBlock, PrepareOpenBlock,
 	ReopenBlock, SealedBlockImporter,
 };
@@ -62,6 +62,7 @@ use client_traits::{
 	AccountData,
 	BadBlocks,
 	Balance,
+	DatabaseBackups,
 	BlockChain as BlockChainTrait,
 	BlockChainClient,
 	BlockChainReset,
@@ -74,6 +75,7 @@ use client_traits::{
 	IoClient,
 	Nonce,
 	ProvingBlockChainClient,
+	ReorgGuard,
 	ScheduleInfo,
 	StateClient,
 	StateOrBlock,
@@ -86,7 +88,7 @@ use db::{keys::BlockDetails, Readable, Writable};
 use engine::Engine;
 use ethcore_miner::pool::VerifiedTransaction;
 use ethtrie::Layout;
-use evm::Schedule;
+use evm::{Factory as EvmFactory, Schedule};
 use executive_state;
 use io::IoChannel;
 use journaldb;
@@ -104,16 +106,19 @@ use trace::{self, Database as TraceDatabase, ImportRequest as TraceImportRequest
 use trie_vm_factories::{Factories, VmFactory};
 use types::{
 	ancestry_action::AncestryAction,
+	backup::BackupStatus,
+	bad_block::{BadBlock, BadBlockReason},
 	block::PreverifiedBlock,
 	block_status::BlockStatus,
 	blockchain_info::BlockChainInfo,
 	BlockNumber,
 	call_analytics::CallAnalytics,
-	chain_notify::{ChainMessageType, ChainRoute, NewBlocks},
+	chain_notify::{ChainMessageType, ChainRoute, DeepReorg, NewBlocks},
 	client_types::{ClientReport, Mode, StateResult},
 	encoded,
 	engines::{
 		epoch::{PendingTransition, Transition as EpochTransition},
+		fault::ConsensusFault,
 		ForkChoice,
 		machine::{AuxiliaryData, Call as MachineCall},
 		MAX_UNCLE_AGE,
@@ -143,6 +148,15 @@ const MAX_ANCIENT_BLOCKS_QUEUE_SIZE: usize = 4096;
 const MAX_ANCIENT_BLOCKS_TO_IMPORT: usize = 4;
 const MAX_QUEUE_SIZE_TO_SLEEP_ON: usize = 2;
 const MIN_HISTORY_SIZE: u64 = 8;
+// Pinned state is released if it wasn't accessed for this long.
+const STATE_PIN_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// State excluded from pruning for the duration of an enumeration session.
+struct PinnedState {
+	number: BlockNumber,
+	pins: usize,
+	last_access: Instant,
+}
 
 struct SleepState {
 	last_activity: Option<Instant>,
@@ -176,6 +190,18 @@ struct Importer {
 
 	/// A lru cache of recently detected bad blocks
 	pub bad_blocks: bad_blocks::BadBlocks,
+
+	/// Blocks held back because of a reorg deeper than `ClientConfig::max_reorg_depth`
+	halted_import: Mutex<Option<HaltedImport>>,
+}
+
+/// Block import halted on a deep reorg.
+struct HaltedImport {
+	reorg: DeepReorg,
+	/// The block causing the reorg followed by the rest of the drained blocks.
+	blocks: Vec<PreverifiedBlock>,
+	/// Operator decision: `Some(true)` applies the reorg, `Some(false)` rejects it.
+	acknowledged: Option<bool>,
 }
 
 /// Blockchain database client backed by a persistent database. Owns and manages a blockchain and a block queue.
@@ -205,6 +231,15 @@ pub struct Client {
 	/// Don't prune the state we're currently snapshotting
 	snapshotting_at: AtomicU64,
 
+	/// Number of the highest canonical block reported finalized by the engine
+	finalized_frontier: AtomicU64,
+
+	/// Scheduled database backups
+	backups: backup::Backups,
+
+	/// Don't prune states pinned for enumeration, by block hash
+	pinned_states: Mutex<HashMap<H256, PinnedState>>,
+
 	/// Client uses this to store blocks, traces, etc.
 	db: RwLock<Arc<dyn BlockChainDB>>,
 
@@ -237,6 +272,8 @@ pub struct Client {
 
 	last_hashes: RwLock<VecDeque<H256>>,
 	factories: Factories,
+	/// Factories every imported block is executed with again, if shadow execution is enabled
+	shadow_factories: Option<Factories>,
 
 	/// Number of eras kept in a journal before they are pruned
 	history: u64,
@@ -258,6 +295,7 @@ impl Importer {
 		engine: Arc<dyn Engine>,
 		message_channel: IoChannel<ClientIoMessage<Client>>,
 		miner: Arc<Miner>,
+		db: &Arc<dyn BlockChainDB>,
 	) -> Result<Importer, EthcoreError> {
 		let block_queue = BlockQueue::new(
 			config.queue.clone(),
@@ -272,7 +310,8 @@ impl Importer {
 			miner,
 			ancient_verifier: AncientVerifier::new(engine.clone()),
 			engine,
-			bad_blocks: Default::default(),
+			bad_blocks: bad_blocks::BadBlocks::new(db.key_value().clone()),
+			halted_import: Mutex::new(None),
 		})
 	}
 
@@ -291,14 +330,29 @@ impl Importer {
 			let mut import_results = Vec::with_capacity(max_blocks_to_import);
 
 			let _import_lock = self.import_lock.lock();
-			let blocks = self.block_queue.drain(max_blocks_to_import);
+			let mut acknowledged = None;
+			let blocks = {
+				let mut halted_import = self.halted_import.lock();
+				match halted_import.take() {
+					Some(HaltedImport { reorg, blocks, acknowledged: Some(accept) }) => {
+						acknowledged = Some((reorg.hash, accept));
+						blocks
+					},
+					Some(halted) => {
+						*halted_import = Some(halted);
+						return 0;
+					},
+					None => self.block_queue.drain(max_blocks_to_import),
+				}
+			};
 			if blocks.is_empty() {
 				return 0;
 			}
 			trace_time!("import_verified_blocks");
 			let start = Instant::now();
 
-			for block in blocks {
+			let mut blocks = blocks.into_iter();
+			while let Some(block) = blocks.next() {
 				let header = block.header.clone();
 				let bytes = block.bytes.clone();
 				let hash = header.hash();
@@ -309,6 +363,25 @@ impl Importer {
 					continue;
 				}
 
+				match acknowledged {
+					Some((reorg_hash, false)) if reorg_hash == hash => {
+						self.bad_blocks.report(bytes, BadBlockReason::Banned, "Reorg rejected by operator".into());
+						invalid_blocks.insert(hash);
+						continue;
+					},
+					Some((reorg_hash, true)) if reorg_hash == hash => {},
+					_ => {
+						if let Some(reorg) = self.deep_reorg(&header, client) {
+							error!(target: "client", "Block import halted: #{} ({}) would retract {} canonical blocks. Use parity_acknowledgeReorg to resume.", reorg.number, reorg.hash, reorg.depth);
+							client.notify(|notify| notify.deep_reorg(&reorg));
+							let mut held = vec![block];
+							held.extend(blocks.by_ref());
+							*self.halted_import.lock() = Some(HaltedImport { reorg, blocks: held, acknowledged: None });
+							break;
+						}
+					},
+				}
+
 				match self.check_and_lock_block(&bytes, block, client) {
 					Ok((closed_block, pending)) => {
 						imported_blocks.push(hash);
@@ -318,7 +391,7 @@ impl Importer {
 						client.report.write().accrue_block(&header, transactions_len);
 					},
 					Err(err) => {
-						self.bad_blocks.report(bytes, format!("{:?}", err));
+						self.bad_blocks.report(bytes, BadBlockReason::from(&err), format!("{:?}", err));
 						invalid_blocks.insert(hash);
 					},
 				}
@@ -340,6 +413,9 @@ impl Importer {
 
 				if !has_more_blocks_to_import {
 					self.miner.chain_new_blocks(client, &imported_blocks, &invalid_blocks, route.enacted(), route.retracted(), false);
+				} else {
+					// the miner is only notified once the queue is drained, but fee suggestions need every block.
+					self.miner.note_fee_blocks(client, route.enacted().iter().map(|hash| BlockId::Hash(*hash)));
 				}
 
 				client.notify(|notify| {
@@ -363,6 +439,41 @@ impl Importer {
 		imported
 	}
 
+	/// Returns true if block import is halted on a deep reorg awaiting acknowledgement.
+	fn is_halted(&self) -> bool {
+		self.halted_import.lock().as_ref().map_or(false, |halted| halted.acknowledged.is_none())
+	}
+
+	/// Returns the reorg importing given block would cause if it is deeper than `ClientConfig::max_reorg_depth`.
+	fn deep_reorg(&self, header: &Header, client: &Client) -> Option<DeepReorg> {
+		let max_depth = client.config.max_reorg_depth?;
+		let chain = client.chain.read();
+		let best_hash = chain.best_block_hash();
+		if header.parent_hash() == &best_hash {
+			return None;
+		}
+
+		let parent_total_difficulty = chain.block_details(header.parent_hash())?.total_difficulty;
+		let best_total_difficulty = chain.block_details(&best_hash)?.total_difficulty;
+		if parent_total_difficulty + *header.difficulty() <= best_total_difficulty {
+			return None;
+		}
+
+		let route = chain.tree_route(best_hash, *header.parent_hash())?;
+		let depth = route.index as u64;
+		if route.is_from_route_finalized || depth <= max_depth {
+			return None;
+		}
+
+		Some(DeepReorg {
+			hash: header.hash(),
+			number: header.number(),
+			best: best_hash,
+			ancestor: route.ancestor,
+			depth,
+		})
+	}
+
 	fn check_and_lock_block(&self, bytes: &[u8], block: PreverifiedBlock, client: &Client) -> EthcoreResult<(LockedBlock, Option<PendingTransition>)> {
 		let engine = &*self.engine;
 		let header = block.header.clone();
@@ -413,13 +524,20 @@ impl Importer {
 
 		let is_epoch_begin = chain.epoch_transition(parent.number(), *header.parent_hash()).is_some();
 
+		let shadow_block = client.shadow_factories.as_ref().map(|_| PreverifiedBlock {
+			header: block.header.clone(),
+			transactions: block.transactions.clone(),
+			uncles: block.uncles.clone(),
+			bytes: block.bytes.clone(),
+		});
+
 		let enact_result = enact_verified(
 			block,
 			engine,
 			client.tracedb.read().tracing_enabled(),
 			db,
 			&parent,
-			last_hashes,
+			last_hashes.clone(),
 			client.factories.clone(),
 			is_epoch_begin,
 		);
@@ -428,10 +546,18 @@ impl Importer {
 			Ok(b) => b,
 			Err(e) => {
 				warn!(target: "client", "Block import failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
+				// gas used and log bloom are only checked during enactment by the per-transaction receipt checks
+				if let EthcoreError::Block(BlockError::InvalidGasUsed(_)) | EthcoreError::Block(BlockError::InvalidLogBloom(_)) = e {
+					client.report.write().blocks_aborted_early += 1;
+				}
 				return Err(e);
 			}
 		};
 
+		if let Some(shadow_block) = shadow_block {
+			self.check_shadow_execution(shadow_block, &parent, last_hashes, is_epoch_begin, &locked_block, client)?;
+		}
+
 		// Strip receipts for blocks before validate_receipts_transition,
 		// if the expected receipts root header does not match.
 		// (i.e. allow inconsistency in receipts outcome before the transition block)
@@ -458,6 +584,39 @@ impl Importer {
 		Ok((locked_block, pending))
 	}
 
+	/// Re-executes the block with the shadow VM factories and compares the outcome with
+	/// `locked_block`. Block import is halted if the two executions diverge.
+	fn check_shadow_execution(
+		&self,
+		block: PreverifiedBlock,
+		parent: &Header,
+		last_hashes: Arc<LastHashes>,
+		is_epoch_begin: bool,
+		locked_block: &LockedBlock,
+		client: &Client,
+	) -> EthcoreResult<()> {
+		let factories = match client.shadow_factories {
+			Some(ref factories) => factories.clone(),
+			None => return Ok(()),
+		};
+
+		let header = block.header.clone();
+		let db = client.state_db.read().boxed_clone_canon(header.parent_hash());
+		let divergence = match enact_verified(block, &*self.engine, false, db, parent, last_hashes, factories, is_epoch_begin) {
+			Ok(shadow_block) => shadow_divergence(locked_block, &shadow_block),
+			Err(e) => Some(format!("shadow execution failed: {:?}", e)),
+		};
+
+		match divergence {
+			None => Ok(()),
+			Some(divergence) => {
+				error!(target: "client", "Shadow execution diverged for #{} ({}): {}. Block import halted.", header.number(), header.hash(), divergence);
+				client.enabled.store(false, AtomicOrdering::Relaxed);
+				Err(EthcoreError::Msg(format!("Shadow execution diverged: {}", divergence)))
+			}
+		}
+	}
+
 	/// Import a block with transaction receipts.
 	///
 	/// The block is guaranteed to be the next best blocks in the
@@ -583,12 +742,17 @@ impl Importer {
 
 		self.check_epoch_end(&header, &finalized, &chain, client);
 
+		client.note_finalized(&finalized, &chain);
 		client.update_last_hashes(&parent, hash);
 
 		if let Err(e) = client.prune_ancient(state, &chain) {
 			warn!("Failed to prune ancient state data: {}", e);
 		}
 
+		if is_canon && client.backups.is_due(number) {
+			client.schedule_backup();
+		}
+
 		route
 	}
 
@@ -625,6 +789,7 @@ impl Importer {
 							last_hashes: client.build_last_hashes(*header.parent_hash()),
 							gas_used: U256::default(),
 							gas_limit: u64::max_value().into(),
+							base_fee: header.base_fee(),
 						};
 
 						let call = move |addr, data| {
@@ -729,6 +894,16 @@ impl Client {
 			accountdb: Default::default(),
 		};
 
+		let shadow_factories = if config.shadow_execution {
+			info!(target: "client", "Shadow execution enabled: every imported block is executed with the reference EVM too.");
+			Some(Factories {
+				vm: EvmFactory::new_reference().into(),
+				..factories.clone()
+			})
+		} else {
+			None
+		};
+
 		let journal_db = journaldb::new(db.key_value().clone(), config.pruning, ::db::COL_STATE);
 		let mut state_db = StateDB::new(journal_db, config.state_cache_size);
 		if state_db.journal_db().is_empty() {
@@ -762,7 +937,7 @@ impl Client {
 
 		let awake = match config.mode { Mode::Dark(..) | Mode::Off => false, _ => true };
 
-		let importer = Importer::new(&config, engine.clone(), message_channel.clone(), miner)?;
+		let importer = Importer::new(&config, engine.clone(), message_channel.clone(), miner, &db)?;
 
 		let registrar_address = engine.machine().params().registrar;
 		if let Some(ref addr) = registrar_address {
@@ -779,6 +954,9 @@ impl Client {
 			engine,
 			pruning: config.pruning,
 			snapshotting_at: AtomicU64::new(0),
+			finalized_frontier: AtomicU64::new(0),
+			backups: backup::Backups::new(config.backup.clone()),
+			pinned_states: Mutex::new(HashMap::new()),
 			db: RwLock::new(db.clone()),
 			state_db: RwLock::new(state_db),
 			report: RwLock::new(Default::default()),
@@ -791,6 +969,7 @@ impl Client {
 			queue_consensus_message: IoChannelQueue::new(usize::max_value()),
 			last_hashes: RwLock::new(VecDeque::new()),
 			factories,
+			shadow_factories,
 			history,
 			on_user_defaults_change: Mutex::new(None),
 			registrar_address,
@@ -882,7 +1061,7 @@ impl Client {
 	/// Flush the block import queue.
 	pub fn flush_queue(&self) {
 		self.importer.block_queue.flush();
-		while !self.importer.block_queue.is_empty() {
+		while !self.importer.block_queue.is_empty() && !self.importer.is_halted() {
 			self.import_verified_blocks();
 		}
 	}
@@ -904,6 +1083,7 @@ impl Client {
 				last_hashes: self.build_last_hashes(header.parent_hash()),
 				gas_used: U256::default(),
 				gas_limit: header.gas_limit(),
+				base_fee: header.base_fee(),
 			}
 		})
 	}
@@ -950,6 +1130,46 @@ impl Client {
 		with_call(&call)
 	}
 
+	/// Advance the finalized frontier to the highest canonical block among the `finalized` ones.
+	fn note_finalized(&self, finalized: &[H256], chain: &BlockChain) {
+		let highest = finalized.iter()
+			.filter_map(|hash| chain.block_number(hash).filter(|n| chain.block_hash(*n).as_ref() == Some(hash)))
+			.max();
+
+		// blocks are committed one at a time, so there are no concurrent updates.
+		if let Some(number) = highest {
+			if number > self.finalized_frontier.load(Ordering::SeqCst) {
+				self.finalized_frontier.store(number, Ordering::SeqCst);
+			}
+		}
+	}
+
+	/// Queue a database backup, it is taken on the IO thread once the current import round is done.
+	fn schedule_backup(&self) {
+		if let Err(e) = self.io_channel.read().send(ClientIoMessage::execute(|client: &Client| client.backup())) {
+			warn!(target: "client", "Failed to schedule database backup: {:?}", e);
+		}
+	}
+
+	/// Take a backup of the database at the current best block. Block import is paused only
+	/// while a consistent view of the database is captured, it is written out afterwards.
+	pub fn backup(&self) {
+		let db = self.db.read().clone();
+		let (number, hash, backup) = {
+			let _import_lock = self.importer.import_lock.lock();
+			let chain = self.chain.read();
+			let backup = db.key_value().flush().and_then(|_| db.backup());
+			(chain.best_block_number(), chain.best_block_hash(), backup)
+		};
+		self.backups.take(number, hash, |path| backup?.write(path));
+	}
+
+	/// Number of the highest canonical block reported finalized by the engine, `0` if the
+	/// engine has not finalized any block since the client started.
+	pub fn finalized_block_number(&self) -> BlockNumber {
+		self.finalized_frontier.load(Ordering::SeqCst)
+	}
+
 	// prune ancient states until below the memory limit or only the minimum amount remain.
 	fn prune_ancient(&self, mut state_db: StateDB, chain: &BlockChain) -> Result<(), EthcoreError> {
 		if !state_db.journal_db().is_prunable() {
@@ -961,19 +1181,28 @@ impl Client {
 			None => return Ok(()),
 		};
 
+		// With finality-aware pruning, every era below the finalized block can be pruned
+		// regardless of the memory target and `history`, since it can never be reverted.
+		let finalized = if self.config.pruning_finalized {
+			self.finalized_frontier.load(Ordering::SeqCst)
+		} else {
+			0
+		};
+
 		// Prune all ancient eras until we're below the memory target (default: 32Mb),
 		// but have at least the minimum number of states, i.e. `history`.
 		// If a snapshot is under way, no pruning happens and memory consumption is allowed to
 		// increase above the memory target until the snapshot has finished.
 		loop {
-			let needs_pruning = state_db.journal_db().journal_size() >= self.config.history_mem;
+			let below_finalized = state_db.journal_db().earliest_era().map_or(false, |era| era < finalized);
+			let needs_pruning = below_finalized || state_db.journal_db().journal_size() >= self.config.history_mem;
 
 			if !needs_pruning {
 				break
 			}
 
 			match state_db.journal_db().earliest_era() {
-				Some(earliest_era) if earliest_era + self.history <= latest_era => {
+				Some(earliest_era) if earliest_era + self.history <= latest_era || earliest_era < finalized => {
 					let freeze_at = self.snapshotting_at.load(Ordering::SeqCst);
 					if freeze_at > 0 && freeze_at == earliest_era {
 						// Note: journal_db().mem_used() can be used for a more accurate memory
@@ -983,6 +1212,13 @@ impl Client {
 						       freeze_at, earliest_era, latest_era, state_db.journal_db().journal_size());
 						break;
 					}
+					if let Some(pinned_at) = self.earliest_pinned_state() {
+						if earliest_era >= pinned_at {
+							trace!(target: "pruning", "Pruning is paused at era {} (state pinned); earliest era={}, latest era={}, journal_size={} – Not pruning.",
+							       pinned_at, earliest_era, latest_era, state_db.journal_db().journal_size());
+							break;
+						}
+					}
 					trace!(target: "pruning", "Pruning state for ancient era #{}; latest era={}, journal_size={}",
 					       earliest_era, latest_era, state_db.journal_db().journal_size());
 					match chain.block_hash(earliest_era) {
@@ -1003,6 +1239,28 @@ impl Client {
 		Ok(())
 	}
 
+	/// Number of the earliest pinned state, releasing pins which expired.
+	fn earliest_pinned_state(&self) -> Option<BlockNumber> {
+		let mut pinned = self.pinned_states.lock();
+		pinned.retain(|hash, state| {
+			let expired = state.last_access.elapsed() > STATE_PIN_TIMEOUT;
+			if expired {
+				debug!(target: "client", "Releasing expired pin of state at block {} ({})", state.number, hash);
+			}
+			!expired
+		});
+		pinned.values().map(|state| state.number).min()
+	}
+
+	/// Extend the lifetime of a pinned state, if block `id` refers to one.
+	fn touch_pinned_state(&self, id: &BlockId) {
+		if let BlockId::Hash(ref hash) = *id {
+			if let Some(state) = self.pinned_states.lock().get_mut(hash) {
+				state.last_access = Instant::now();
+			}
+		}
+	}
+
 	fn update_last_hashes(&self, parent: &H256, hash: &H256) {
 		let mut hashes = self.last_hashes.write();
 		if hashes.front().map_or(false, |h| h == parent) {
@@ -1079,6 +1337,19 @@ impl Client {
 		})
 	}
 
+	/// Attempt to get a copy of the state with the given root.
+	///
+	/// This fails if the DB prunes state or the root is unknown.
+	pub fn state_at_root(&self, root: H256) -> Option<State<StateDB>> {
+		let db = self.state_db.read().boxed_clone();
+		if db.is_prunable() {
+			return None;
+		}
+
+		let best_block_number = self.chain.read().best_block_number();
+		State::from_existing(db, root, self.engine.account_start_nonce(best_block_number), self.factories.clone()).ok()
+	}
+
 	/// Attempt to get a copy of a specific block's beginning state.
 	///
 	/// This will not fail if given BlockId::Latest.
@@ -1468,13 +1739,17 @@ impl ImportBlock for Client {
 			},
 			// we only care about block errors (not import errors)
 			Err((EthcoreError::Block(e), Some(input))) => {
-				self.importer.bad_blocks.report(input.bytes, e.to_string());
+				self.importer.bad_blocks.report(input.bytes, BadBlockReason::from(&e), e.to_string());
 				Err(EthcoreError::Block(e))
 			},
 			Err((EthcoreError::Block(e), None)) => {
 				error!(target: "client", "BlockError {} detected but it was missing raw_bytes of the block", e);
 				Err(EthcoreError::Block(e))
 			}
+			Err((EthcoreError::Import(ImportError::KnownBad), Some(input))) => {
+				self.importer.bad_blocks.report(input.bytes, BadBlockReason::Banned, "Block or its parent is known to be bad".into());
+				Err(EthcoreError::Import(ImportError::KnownBad))
+			},
 			Err((e, _input)) => Err(e),
 		}
 	}
@@ -1495,6 +1770,10 @@ impl StateClient for Client {
 	fn state_at(&self, id: BlockId) -> Option<Self::State> {
 		Client::state_at(self, id)
 	}
+
+	fn state_at_root(&self, root: H256) -> Option<Self::State> {
+		Client::state_at_root(self, root)
+	}
 }
 
 impl Call for Client {
@@ -1509,6 +1788,7 @@ impl Call for Client {
 			last_hashes: self.build_last_hashes(*header.parent_hash()),
 			gas_used: U256::default(),
 			gas_limit: U256::max_value(),
+			base_fee: header.base_fee(),
 		};
 		let machine = self.engine.machine();
 
@@ -1524,6 +1804,7 @@ impl Call for Client {
 			last_hashes: self.build_last_hashes(*header.parent_hash()),
 			gas_used: U256::default(),
 			gas_limit: U256::max_value(),
+			base_fee: header.base_fee(),
 		};
 
 		let mut results = Vec::with_capacity(transactions.len());
@@ -1551,6 +1832,7 @@ impl Call for Client {
 				last_hashes: self.build_last_hashes(*header.parent_hash()),
 				gas_used: U256::default(),
 				gas_limit: max,
+				base_fee: header.base_fee(),
 			};
 
 			(init, max, env_info)
@@ -1630,11 +1912,39 @@ impl EngineInfo for Client {
 }
 
 impl BadBlocks for Client {
-	fn bad_blocks(&self) -> Vec<(Unverified, String)> {
+	fn bad_blocks(&self) -> Vec<BadBlock> {
 		self.importer.bad_blocks.bad_blocks()
 	}
 }
 
+impl DatabaseBackups for Client {
+	fn backup_status(&self) -> BackupStatus {
+		self.backups.status()
+	}
+}
+
+impl ReorgGuard for Client {
+	fn halted_reorg(&self) -> Option<DeepReorg> {
+		self.importer.halted_import.lock().as_ref().map(|halted| halted.reorg.clone())
+	}
+
+	fn acknowledge_reorg(&self, accept: bool) -> bool {
+		match *self.importer.halted_import.lock() {
+			Some(ref mut halted) if halted.acknowledged.is_none() => {
+				info!(target: "client", "Reorg to #{} ({}) {} by operator, resuming block import.",
+					halted.reorg.number, halted.reorg.hash, if accept { "accepted" } else { "rejected" });
+				halted.acknowledged = Some(accept);
+			},
+			_ => return false,
+		}
+
+		if let Err(e) = self.io_channel.read().send(ClientIoMessage::BlockVerified) {
+			debug!(target: "client", "Error sending BlockVerified message: {:?}", e);
+		}
+		true
+	}
+}
+
 impl BlockChainClient for Client {
 	fn replay(&self, id: TransactionId, analytics: CallAnalytics) -> Result<Executed, CallError> {
 		let address = self.transaction_address(id).ok_or_else(|| CallError::TransactionNotFound)?;
@@ -1665,6 +1975,28 @@ impl BlockChainClient for Client {
 			})))
 	}
 
+	fn replay_block_with_access_log(&self, block: BlockId) -> Result<Vec<(H256, Vec<trace::StateAccess>)>, CallError> {
+		let mut env_info = self.env_info(block).ok_or_else(|| CallError::StatePruned)?;
+		let body = self.block_body(block).ok_or_else(|| CallError::StatePruned)?;
+		let mut state = self.state_at_beginning(block).ok_or_else(|| CallError::StatePruned)?;
+		let machine = self.engine.machine();
+		let schedule = machine.schedule(env_info.number);
+
+		const PROOF: &str = "Transactions fetched from blockchain; blockchain transactions are valid; qed";
+
+		body.transactions().into_iter()
+			.map(|t| -> Result<_, CallError> {
+				let transaction_hash = t.hash();
+				let t = SignedTransaction::new(t).expect(PROOF);
+				let tracer = trace::AccessTracer::default();
+				let options = TransactOptions::new(tracer.clone(), tracer.clone()).dont_check_nonce();
+				let executed = Executive::new(&mut state, &env_info, machine, &schedule).transact_virtual(&t, options)?;
+				env_info.gas_used = env_info.gas_used + executed.gas_used;
+				Ok((transaction_hash, tracer.accesses()))
+			})
+			.collect()
+	}
+
 	fn mode(&self) -> Mode {
 		self.mode.lock().clone()
 	}
@@ -1779,6 +2111,7 @@ impl BlockChainClient for Client {
 			return None;
 		}
 
+		self.touch_pinned_state(&id);
 		let state = match self.state_at(id) {
 			Some(state) => state,
 			_ => return None,
@@ -1802,17 +2135,19 @@ impl BlockChainClient for Client {
 		if let Some(after) = after {
 			if let Err(e) = iter.seek(after.as_bytes()) {
 				trace!(target: "fatdb", "list_accounts: Couldn't seek the DB: {:?}", e);
-			} else {
-				// Position the iterator after the `after` element
-				iter.next();
 			}
 		}
 
-		let accounts = iter.filter_map(|item| {
+		let mut accounts = iter.filter_map(|item| {
 			item.ok().map(|(addr, _)| Address::from_slice(&addr))
-		}).take(count as usize).collect();
+		}).peekable();
 
-		Some(accounts)
+		// Position the iterator after the `after` element, unless it no longer exists
+		if after.is_some() && accounts.peek() == after {
+			accounts.next();
+		}
+
+		Some(accounts.take(count as usize).collect())
 	}
 
 	fn list_storage(&self, id: BlockId, account: &Address, after: Option<&H256>, count: Option<u64>) -> Option<Vec<H256>> {
@@ -1821,6 +2156,7 @@ impl BlockChainClient for Client {
 			return None;
 		}
 
+		self.touch_pinned_state(&id);
 		let state = match self.state_at(id) {
 			Some(state) => state,
 			_ => return None,
@@ -1850,26 +2186,115 @@ impl BlockChainClient for Client {
 		if let Some(after) = after {
 			if let Err(e) = iter.seek(after.as_bytes()) {
 				trace!(target: "fatdb", "list_storage: Couldn't seek the DB: {:?}", e);
-			} else {
-				// Position the iterator after the `after` element
-				iter.next();
 			}
 		}
 
-		let keys = {
-			let f = iter.filter_map(|item| {
-				item.ok().map(|(key, _)| H256::from_slice(&key))
-			});
-			if let Some(count) = count {
-				f.take(count as usize).collect()
-			} else {
-				f.collect()
-			}
+		let mut keys = iter.filter_map(|item| {
+			item.ok().map(|(key, _)| H256::from_slice(&key))
+		}).peekable();
+
+		// Position the iterator after the `after` element, unless it no longer exists
+		if after.is_some() && keys.peek() == after {
+			keys.next();
+		}
+
+		let keys = if let Some(count) = count {
+			keys.take(count as usize).collect()
+		} else {
+			keys.collect()
 		};
 
 		Some(keys)
 	}
 
+	fn storage_range(&self, id: BlockId, account: &Address, start: Option<&H256>, limit: u64) -> Option<(Vec<(H256, H256)>, Option<H256>)> {
+		if !self.factories.trie.is_fat() {
+			trace!(target: "fatdb", "storage_range: Not a fat DB");
+			return None;
+		}
+
+		self.touch_pinned_state(&id);
+		let state = self.state_at(id)?;
+		let root = match state.storage_root(account) {
+			Ok(Some(root)) => root,
+			_ => return None,
+		};
+
+		let (_, db) = state.drop();
+		let account_db = &self.factories.accountdb.readonly(db.as_hash_db(), keccak(account));
+		let account_db = &account_db.as_hash_db();
+		let trie = match self.factories.trie.readonly(account_db, &root) {
+			Ok(trie) => trie,
+			_ => {
+				trace!(target: "fatdb", "storage_range: Couldn't open the DB");
+				return None;
+			}
+		};
+
+		let mut iter = trie.iter().ok()?;
+		if let Some(start) = start {
+			if let Err(e) = iter.seek(start.as_bytes()) {
+				trace!(target: "fatdb", "storage_range: Couldn't seek the DB: {:?}", e);
+				return None;
+			}
+		}
+
+		let mut entries = Vec::new();
+		for item in iter {
+			let (key, value) = match item {
+				Ok(item) => item,
+				Err(e) => {
+					trace!(target: "fatdb", "storage_range: Couldn't read the DB: {:?}", e);
+					return None;
+				}
+			};
+			let key = H256::from_slice(&key);
+			if entries.len() as u64 == limit {
+				return Some((entries, Some(key)));
+			}
+			let value: U256 = ::rlp::decode(&value).ok()?;
+			entries.push((key, H256::from_uint(&value)));
+		}
+
+		Some((entries, None))
+	}
+
+	fn pin_state(&self, id: BlockId) -> Option<H256> {
+		let header = self.block_header_decoded(id)?;
+		let hash = header.hash();
+		// make sure the state wasn't pruned already
+		self.state_at(BlockId::Hash(hash))?;
+
+		let mut pinned = self.pinned_states.lock();
+		let state = pinned.entry(hash).or_insert_with(|| PinnedState {
+			number: header.number(),
+			pins: 0,
+			last_access: Instant::now(),
+		});
+		state.pins += 1;
+		state.last_access = Instant::now();
+		Some(hash)
+	}
+
+	fn unpin_state(&self, hash: &H256) -> bool {
+		let mut pinned = self.pinned_states.lock();
+		let released = match pinned.get_mut(hash) {
+			Some(state) => {
+				state.pins -= 1;
+				state.pins == 0
+			},
+			None => return false,
+		};
+		if released {
+			pinned.remove(hash);
+		}
+		true
+	}
+
+	fn consensus_faults(&self) -> Vec<ConsensusFault> {
+		self.engine.consensus_faults()
+	}
+
 	fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction> {
 		self.transaction_address(id).and_then(|address| self.chain.read().transaction(&address))
 	}
@@ -2106,6 +2531,15 @@ impl BlockChainClient for Client {
 			.and_then(|number| self.tracedb.read().block_traces(number))
 	}
 
+	fn block_traces_page(&self, block: BlockId, offset: usize, limit: usize) -> Option<Vec<LocalizedTrace>> {
+		if !self.tracedb.read().tracing_enabled() {
+			return None;
+		}
+
+		self.block_number(block)
+			.and_then(|number| self.tracedb.read().block_traces_page(number, offset, limit))
+	}
+
 	fn last_hashes(&self) -> LastHashes {
 		self.build_last_hashes(self.chain.read().best_block_hash()).to_vec()
 	}
@@ -2132,6 +2566,10 @@ impl BlockChainClient for Client {
 		self.importer.miner.ready_transactions(self, max_len, PendingOrdering::Priority)
 	}
 
+	fn pooled_transaction(&self, hash: &H256) -> Option<Arc<VerifiedTransaction>> {
+		self.importer.miner.transaction(hash)
+	}
+
 	fn signing_chain_id(&self) -> Option<u64> {
 		self.engine.signing_chain_id(&self.latest_env_info())
 	}
@@ -2390,6 +2828,7 @@ impl ImportSealedBlock for Client {
 			if let Err(e) = self.engine.verify_block_basic(&header) {
 				self.importer.bad_blocks.report(
 					block.rlp_bytes(),
+					BadBlockReason::from(&e),
 					format!("Detected an issue with locally sealed block: {}", e),
 				);
 				return Err(e);
@@ -2744,6 +3183,36 @@ impl ImportExportBlocks for Client {
 	}
 }
 
+/// Describes how the shadow execution of a block differs from the primary one, if it does.
+fn shadow_divergence(primary: &LockedBlock, shadow: &LockedBlock) -> Option<String> {
+	if primary.receipts.len() != shadow.receipts.len() {
+		return Some(format!("{} receipts, shadow {}", primary.receipts.len(), shadow.receipts.len()));
+	}
+
+	let diverged = primary.receipts.iter().zip(shadow.receipts.iter()).position(|(a, b)| a != b);
+	if let Some(index) = diverged {
+		let (a, b) = (&primary.receipts[index], &shadow.receipts[index]);
+		return Some(format!(
+			"transaction {} ({:?}): cumulative gas used {}, shadow {}; {} logs, shadow {}; outcome {:?}, shadow {:?}",
+			index,
+			primary.transactions.get(index).map(|tx| tx.hash()),
+			a.gas_used, b.gas_used,
+			a.logs.len(), b.logs.len(),
+			a.outcome, b.outcome,
+		));
+	}
+
+	if primary.header.state_root() != shadow.header.state_root() {
+		return Some(format!("state root {}, shadow {}", primary.header.state_root(), shadow.header.state_root()));
+	}
+
+	if primary.header.gas_used() != shadow.header.gas_used() {
+		return Some(format!("gas used {}, shadow {}", primary.header.gas_used(), shadow.header.gas_used()));
+	}
+
+	None
+}
+
 /// Returns `LocalizedReceipt` given `LocalizedTransaction`
 /// and a vector of receipts from given block up to transaction index.
 fn transaction_receipt(
@@ -2770,6 +3239,7 @@ fn transaction_receipt(
 		block_number,
 		cumulative_gas_used: receipt.gas_used,
 		gas_used: receipt.gas_used - prior_gas_used,
+		effective_gas_price: tx.gas_price,
 		contract_address: match tx.action {
 			Action::Call(_) => None,
 			Action::Create => Some(contract_address(CreateContractAddress::FromSenderAndNonce, &sender, &tx.nonce, &tx.data).0)
@@ -2962,6 +3432,7 @@ mod tests {
 			block_number: block_number,
 			cumulative_gas_used: gas_used,
 			gas_used: gas_used - 5,
+			effective_gas_price: 0.into(),
 			contract_address: None,
 			logs: vec![LocalizedLogEntry {
 				entry: logs[0].clone(),
@@ -2985,6 +3456,30 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn should_import_blocks_with_shadow_execution() {
+		use client::{Client, ClientConfig};
+		use client_traits::{BlockInfo, ImportBlock};
+		use io::IoChannel;
+		use miner::Miner;
+		use test_helpers::new_db;
+		use types::verification::Unverified;
+
+		let source = generate_dummy_client_with_data(3, 2, &[1.into()]);
+		let blocks: Vec<_> = (1..4).map(|n| source.block(BlockId::Number(n)).unwrap().into_inner()).collect();
+
+		let spec = spec::new_null();
+		let mut config = ClientConfig::default();
+		config.shadow_execution = true;
+		let client = Client::new(config, &spec, new_db(), Arc::new(Miner::new_for_tests(&spec, None)), IoChannel::disconnected()).unwrap();
+		for block in blocks {
+			client.import_block(Unverified::from_rlp(block).unwrap()).unwrap();
+		}
+		client.flush_queue();
+
+		assert_eq!(client.chain_info().best_block_number, 3);
+	}
+
 	#[test]
 	fn should_mark_finalization_correctly_for_parent() {
 		let client = generate_dummy_client_with_spec_and_data(spec::new_test_with_finality, 2, 0, &[], false);
@@ -3002,4 +3497,13 @@ mod tests {
 		assert_eq!(block2_details.children.len(), 0);
 		assert!(!block2_details.is_finalized);
 	}
+
+	#[test]
+	fn should_track_finalized_frontier() {
+		let client = generate_dummy_client_with_spec_and_data(spec::new_test_with_finality, 3, 0, &[], false);
+		assert_eq!(client.finalized_block_number(), 2);
+
+		let client = generate_dummy_client_with_spec_and_data(spec::new_test, 3, 0, &[], false);
+		assert_eq!(client.finalized_block_number(), 0);
+	}
 }
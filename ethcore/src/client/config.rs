@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use blockchain::Config as BlockChainConfig;
@@ -53,6 +55,88 @@ impl FromStr for DatabaseCompactionProfile {
 	}
 }
 
+/// Database tuning profile, deciding the compaction profile and how the memory budget
+/// is spread across the columns.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DatabaseProfile {
+	/// SSDs and fast HDDs, most of the memory budget goes to the state column.
+	SSD,
+	/// Slow HDDs, more memory is spent caching blocks to avoid expensive reads.
+	HDD,
+	/// Archive nodes serving a lot of historical blocks, receipts and traces.
+	Archive,
+}
+
+impl DatabaseProfile {
+	/// Compaction profile used with this tuning profile.
+	pub fn compaction(&self) -> DatabaseCompactionProfile {
+		match *self {
+			DatabaseProfile::SSD => DatabaseCompactionProfile::SSD,
+			DatabaseProfile::HDD => DatabaseCompactionProfile::HDD,
+			DatabaseProfile::Archive => DatabaseCompactionProfile::Auto,
+		}
+	}
+
+	/// Percentage of the memory budget spent on the state column.
+	pub fn state_cache_share(&self) -> usize {
+		match *self {
+			DatabaseProfile::SSD => 90,
+			DatabaseProfile::HDD => 80,
+			DatabaseProfile::Archive => 50,
+		}
+	}
+}
+
+impl FromStr for DatabaseProfile {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"ssd" => Ok(DatabaseProfile::SSD),
+			"hdd" => Ok(DatabaseProfile::HDD),
+			"archive" => Ok(DatabaseProfile::Archive),
+			_ => Err("Invalid database profile given. Expected ssd/hdd/archive.".into()),
+		}
+	}
+}
+
+/// RocksDB tuning.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct DatabaseTuning {
+	/// Tuning profile, `None` to use the default memory split.
+	pub profile: Option<DatabaseProfile>,
+	/// Block cache size in MiB of individual columns, overriding the profile.
+	pub column_cache_size: BTreeMap<u32, usize>,
+}
+
+/// Scheduled database backups.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BackupConfig {
+	/// Directory the backups are written to, backups are disabled if `None`.
+	pub path: Option<PathBuf>,
+	/// Take a backup whenever the number of a new canonical block is a multiple of this.
+	pub interval: u64,
+	/// Number of most recent backups kept, older ones are removed.
+	pub keep: usize,
+}
+
+impl Default for BackupConfig {
+	fn default() -> Self {
+		BackupConfig {
+			path: None,
+			interval: 10_000,
+			keep: 3,
+		}
+	}
+}
+
+impl BackupConfig {
+	/// Whether a backup should be taken after importing the given canonical block.
+	pub fn is_due(&self, number: u64) -> bool {
+		self.path.is_some() && self.interval > 0 && number % self.interval == 0
+	}
+}
+
 /// Client configuration. Includes configs for all sub-systems.
 #[derive(Debug, PartialEq, Clone)]
 pub struct ClientConfig {
@@ -72,6 +156,8 @@ pub struct ClientConfig {
 	pub db_cache_size: Option<usize>,
 	/// State db compaction profile
 	pub db_compaction: DatabaseCompactionProfile,
+	/// RocksDB per-column tuning
+	pub db_tuning: DatabaseTuning,
 	/// Operating mode
 	pub mode: Mode,
 	/// The chain spec name
@@ -86,6 +172,8 @@ pub struct ClientConfig {
 	pub history: u64,
 	/// Ideal memory usage for state pruning history.
 	pub history_mem: usize,
+	/// Prune all states below the block finalized by the engine, regardless of `history`.
+	pub pruning_finalized: bool,
 	/// Check seal validity on block import
 	pub check_seal: bool,
 	/// Maximal number of transactions queued for verification in a separate thread.
@@ -94,6 +182,12 @@ pub struct ClientConfig {
 	pub max_round_blocks_to_import: usize,
 	/// Snapshot configuration
 	pub snapshot: SnapshotConfiguration,
+	/// Maximal number of canonical blocks a reorg may retract before block import is halted.
+	pub max_reorg_depth: Option<u64>,
+	/// Execute every imported block with the reference EVM too and halt import on divergence.
+	pub shadow_execution: bool,
+	/// Scheduled database backups.
+	pub backup: BackupConfig,
 }
 
 impl Default for ClientConfig {
@@ -108,6 +202,7 @@ impl Default for ClientConfig {
 			name: "default".into(),
 			db_cache_size: None,
 			db_compaction: Default::default(),
+			db_tuning: Default::default(),
 			mode: Mode::Active,
 			spec_name: "".into(),
 			verifier_type: VerifierType::Canon,
@@ -115,16 +210,20 @@ impl Default for ClientConfig {
 			jump_table_size: 1 * mb,
 			history: 64,
 			history_mem: 32 * mb,
+			pruning_finalized: false,
 			check_seal: true,
 			transaction_verification_queue_size: 8192,
 			max_round_blocks_to_import: 12,
 			snapshot: Default::default(),
+			max_reorg_depth: None,
+			shadow_execution: false,
+			backup: Default::default(),
 		}
 	}
 }
 #[cfg(test)]
 mod test {
-	use super::DatabaseCompactionProfile;
+	use super::{DatabaseCompactionProfile, DatabaseProfile};
 
 	#[test]
 	fn test_default_compaction_profile() {
@@ -137,4 +236,12 @@ mod test {
 		assert_eq!(DatabaseCompactionProfile::SSD, "ssd".parse().unwrap());
 		assert_eq!(DatabaseCompactionProfile::HDD, "hdd".parse().unwrap());
 	}
+
+	#[test]
+	fn test_parsing_database_profile() {
+		assert_eq!(DatabaseProfile::SSD, "ssd".parse().unwrap());
+		assert_eq!(DatabaseProfile::HDD, "hdd".parse().unwrap());
+		assert_eq!(DatabaseProfile::Archive, "archive".parse().unwrap());
+		assert!("auto".parse::<DatabaseProfile>().is_err());
+	}
 }
@@ -17,13 +17,14 @@
 //! Blockchain database client.
 
 mod ancient_import;
+mod backup;
 mod bad_blocks;
 mod client;
 mod config;
 mod traits;
 
 pub use self::client::Client;
-pub use self::config::{ClientConfig, DatabaseCompactionProfile};
+pub use self::config::{BackupConfig, ClientConfig, DatabaseCompactionProfile, DatabaseProfile, DatabaseTuning};
 pub use self::traits::{
     ReopenBlock, PrepareOpenBlock, ImportSealedBlock, BroadcastProposalBlock,
     Call, EngineInfo, BlockProducer, SealedBlockImporter,
@@ -37,7 +37,6 @@ extern crate journaldb;
 extern crate keccak_hash as hash;
 extern crate kvdb;
 extern crate machine;
-extern crate memory_cache;
 extern crate parity_bytes as bytes;
 extern crate parking_lot;
 extern crate trie_db as trie;
@@ -69,7 +68,7 @@ extern crate stats;
 #[cfg(feature = "stratum")]
 extern crate ethcore_stratum;
 
-#[cfg(feature = "stratum")]
+#[cfg(any(test, feature = "stratum"))]
 extern crate ethash;
 
 #[cfg(any(test, feature = "test-helpers"))]
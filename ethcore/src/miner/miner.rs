@@ -18,10 +18,13 @@ use std::cmp;
 use std::time::{Instant, Duration};
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
 
 use ansi_term::Colour;
 use bytes::Bytes;
 use call_contract::CallContract;
+use ethcore_miner::fee_oracle::{FeeOracle, FeeSuggestions};
+use ethcore_miner::gas_price_floor::{GasPriceFloor, GasPriceFloorOptions};
 use ethcore_miner::gas_pricer::GasPricer;
 use ethcore_miner::local_accounts::LocalAccounts;
 use ethcore_miner::pool::{self, TransactionQueue, VerifiedTransaction, QueueStatus, PrioritizationStrategy, TxStatus};
@@ -44,7 +47,7 @@ use types::{
 	ids::BlockId,
 	io_message::ClientIoMessage,
 	engines::{Seal, SealingState},
-	errors::{EthcoreError as Error, ExecutionError},
+	errors::{BlockError, EthcoreError as Error, ExecutionError},
 	receipt::RichReceipt,
 	transaction::{
 		self,
@@ -139,6 +142,8 @@ pub struct MinerOptions {
 	pub pending_set: PendingSet,
 	/// How many historical work packages can we store before running out?
 	pub work_queue_size: usize,
+	/// For how many of the most recently handed out work packages are solutions accepted?
+	pub stale_work_window: usize,
 	/// Can we submit two different solutions for the same block and expect both to result in an import?
 	pub enable_resubmission: bool,
 	/// Create a pending block with maximal possible gas limit.
@@ -158,6 +163,13 @@ pub struct MinerOptions {
 	pub pool_limits: pool::Options,
 	/// Initial transaction verification options.
 	pub pool_verification_options: pool::verifier::Options,
+	/// Quotas of queued transactions per origin.
+	pub pool_origin_quotas: pool::OriginQuotas,
+	/// Raise the minimal gas price when the queue is close to its limits.
+	pub gas_price_floor: Option<GasPriceFloorOptions>,
+	/// Lowered difficulty advertised to external miners to test their rigs.
+	/// Solutions are still checked against the block difficulty.
+	pub work_test_difficulty: Option<U256>,
 }
 
 impl Default for MinerOptions {
@@ -171,6 +183,7 @@ impl Default for MinerOptions {
 			reseal_max_period: Duration::from_secs(120),
 			pending_set: PendingSet::AlwaysQueue,
 			work_queue_size: 20,
+			stale_work_window: 20,
 			enable_resubmission: true,
 			infinite_pending_block: false,
 			tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
@@ -188,6 +201,9 @@ impl Default for MinerOptions {
 				tx_gas_limit: U256::max_value(),
 				no_early_reject: false,
 			},
+			pool_origin_quotas: Default::default(),
+			gas_price_floor: None,
+			work_test_difficulty: None,
 		}
 	}
 }
@@ -221,6 +237,18 @@ impl Author {
 	}
 }
 
+/// Counters of pending block rebuilds.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PendingRebuildStats {
+	/// Number of pending blocks prepared.
+	pub performed: u64,
+	/// Number of transaction-inspired rebuilds skipped because of `reseal_min_period`.
+	/// Skipped rebuilds are coalesced into the next one.
+	pub skipped: u64,
+	/// Number of skipped rebuilds performed on demand, when the pending block was requested.
+	pub on_demand: u64,
+}
+
 struct SealingWork {
 	queue: UsingQueue<ClosedBlock>,
 	enabled: bool,
@@ -228,6 +256,9 @@ struct SealingWork {
 	next_mandatory_reseal: Instant,
 	// block number when sealing work was last requested
 	last_request: Option<u64>,
+	// transactions were imported since the pending block was last rebuilt
+	rebuild_deferred: bool,
+	rebuild_stats: PendingRebuildStats,
 }
 
 impl SealingWork {
@@ -236,6 +267,23 @@ impl SealingWork {
 		Instant::now() > self.next_allowed_reseal
 	}
 
+	/// Note a transaction-inspired rebuild skipped because it came too early.
+	fn defer_rebuild(&mut self) {
+		self.rebuild_deferred = true;
+		self.rebuild_stats.skipped += 1;
+	}
+
+	/// Returns true if a deferred rebuild should be performed now.
+	fn take_deferred_rebuild(&mut self) -> bool {
+		if self.enabled && self.rebuild_deferred {
+			self.rebuild_deferred = false;
+			self.rebuild_stats.on_demand += 1;
+			true
+		} else {
+			false
+		}
+	}
+
 	fn work_available(&self) -> bool {
 		self.queue.peek_last_ref().is_some()
 	}
@@ -247,10 +295,22 @@ pub struct Miner {
 	// NOTE [ToDr]  When locking always lock in this order!
 	sealing: Mutex<SealingWork>,
 	params: RwLock<AuthoringParams>,
+	/// Lowered difficulty advertised to external miners, if set.
+	work_test_difficulty: RwLock<Option<U256>>,
+	/// Number of solutions which met only the advertised test difficulty.
+	work_test_shares: AtomicU64,
 	#[cfg(feature = "work-notify")]
 	listeners: RwLock<Vec<Box<dyn NotifyWork>>>,
 	nonce_cache: NonceCache,
 	gas_pricer: Mutex<GasPricer>,
+	/// Minimal gas price last set by the gas pricer, before the floor is applied.
+	priced_gas_price: Arc<RwLock<U256>>,
+	/// Minimal gas price following the pressure on the queue, if enabled.
+	gas_price_floor: Option<Mutex<GasPriceFloor>>,
+	/// Base fee of the next block, `None` before EIP-1559 transition.
+	next_base_fee: RwLock<Option<U256>>,
+	/// Effective tips paid in recent blocks.
+	fee_oracle: RwLock<FeeOracle>,
 	options: MinerOptions,
 	// TODO [ToDr] Arc is only required because of price updater
 	transaction_queue: Arc<TransactionQueue>,
@@ -292,9 +352,15 @@ impl Miner {
 		let limits = options.pool_limits.clone();
 		let verifier_options = options.pool_verification_options.clone();
 		let tx_queue_strategy = options.tx_queue_strategy;
+		let transaction_queue = TransactionQueue::new(limits, verifier_options, tx_queue_strategy);
+		transaction_queue.set_origin_quotas(options.pool_origin_quotas.clone());
 		let nonce_cache_size = cmp::max(4096, limits.max_count / 4);
 		let refuse_service_transactions = options.refuse_service_transactions;
 		let engine = spec.engine.clone();
+		let gas_price_floor = options.gas_price_floor.clone().map(|floor| {
+			let initial = gas_pricer.current().unwrap_or(options.pool_verification_options.minimal_gas_price);
+			Mutex::new(GasPriceFloor::new(floor, initial))
+		});
 
 		Miner {
 			sealing: Mutex::new(SealingWork {
@@ -304,14 +370,22 @@ impl Miner {
 				next_allowed_reseal: Instant::now(),
 				next_mandatory_reseal: Instant::now() + options.reseal_max_period,
 				last_request: None,
+				rebuild_deferred: false,
+				rebuild_stats: Default::default(),
 			}),
 			params: RwLock::new(AuthoringParams::default()),
+			work_test_difficulty: RwLock::new(options.work_test_difficulty),
+			work_test_shares: AtomicU64::new(0),
 			#[cfg(feature = "work-notify")]
 			listeners: RwLock::new(vec![]),
 			gas_pricer: Mutex::new(gas_pricer),
+			priced_gas_price: Arc::new(RwLock::new(options.pool_verification_options.minimal_gas_price)),
+			gas_price_floor,
+			next_base_fee: RwLock::new(None),
+			fee_oracle: RwLock::new(FeeOracle::default()),
 			nonce_cache: NonceCache::new(nonce_cache_size),
 			options,
-			transaction_queue: Arc::new(TransactionQueue::new(limits, verifier_options, tx_queue_strategy)),
+			transaction_queue: Arc::new(transaction_queue),
 			accounts: Arc::new(accounts),
 			engine,
 			io_channel: RwLock::new(None),
@@ -366,6 +440,11 @@ impl Miner {
 		});
 	}
 
+	/// Returns counters of pending block rebuilds.
+	pub fn pending_rebuild_stats(&self) -> PendingRebuildStats {
+		self.sealing.lock().rebuild_stats.clone()
+	}
+
 	/// Clear all pending block states
 	pub fn clear(&self) {
 		self.sealing.lock().queue.reset();
@@ -374,18 +453,46 @@ impl Miner {
 	/// Updates transaction queue verification limits.
 	///
 	/// Limits consist of current block gas limit and minimal gas price.
+	/// The gas price floor, if enabled, is adjusted to the current occupancy of the queue
+	/// and applied right away, even if the gas pricer has no new price yet.
 	pub fn update_transaction_queue_limits(&self, block_gas_limit: U256) {
 		trace!(target: "miner", "minimal_gas_price: recalibrating...");
 		let txq = self.transaction_queue.clone();
+		let priced = self.priced_gas_price.clone();
 		let mut options = self.options.pool_verification_options.clone();
-		self.gas_pricer.lock().recalibrate(move |gas_price| {
+		let base_fee = *self.next_base_fee.read();
+		let floor = self.gas_price_floor.as_ref().map_or_else(U256::zero, |floor| {
+			let occupancy = self.queue_occupancy();
+			let floor = floor.lock().update(occupancy);
+			trace!(target: "miner", "minimal_gas_price: queue occupancy {}%, floor {}", occupancy, floor);
+			floor
+		});
+		options.block_gas_limit = block_gas_limit;
+		options.minimal_gas_price = cmp::max(*self.priced_gas_price.read(), floor);
+		self.transaction_queue.set_verifier_options(options.clone());
+
+		self.gas_pricer.lock().recalibrate(base_fee, move |gas_price| {
 			debug!(target: "miner", "minimal_gas_price: Got gas price! {}", gas_price);
-			options.minimal_gas_price = gas_price;
-			options.block_gas_limit = block_gas_limit;
+			*priced.write() = gas_price;
+			options.minimal_gas_price = cmp::max(gas_price, floor);
 			txq.set_verifier_options(options);
 		});
 	}
 
+	/// Occupancy of the transaction queue, in percent of its most constraining limit.
+	fn queue_occupancy(&self) -> u8 {
+		let status = self.transaction_queue.status();
+		let percent = |used: usize, limit: usize| match limit {
+			0 => 100,
+			limit => used.saturating_mul(100) / limit,
+		};
+		let occupancy = cmp::max(
+			percent(status.status.transaction_count, status.limits.max_count),
+			percent(status.status.mem_usage, status.limits.max_mem_usage),
+		);
+		cmp::min(occupancy, 100) as u8
+	}
+
 	/// Returns ServiceTransactionChecker
 	pub fn service_transaction_checker(&self) -> Option<ServiceTransactionChecker> {
 		self.service_transaction_checker.clone()
@@ -410,6 +517,42 @@ impl Miner {
 			})
 	}
 
+	/// Returns true if transactions claimed to be local should be treated as such:
+	/// if the option is enabled, if we have the account, or if the account is specified
+	/// as a Prioritized Local Address.
+	fn treat_as_local(&self, sender: &Address, trusted: bool) -> bool {
+		trusted
+			|| !self.options.tx_queue_no_unfamiliar_locals
+			|| self.accounts.is_local(sender)
+	}
+
+	/// Note the tips paid in the given newly imported blocks in the fee oracle.
+	/// Called by the client for blocks which are imported while the queue is still being drained,
+	/// since `chain_new_blocks` isn't notified about them.
+	pub fn note_fee_blocks<C, I>(&self, chain: &C, blocks: I)
+		where C: BlockChain, I: IntoIterator<Item = BlockId>,
+	{
+		let mut oracle = self.fee_oracle.write();
+		for (number, base_fee, gas_prices) in fee_blocks(chain, blocks) {
+			oracle.note_block(number, base_fee, gas_prices);
+		}
+	}
+
+	/// Note the tips paid in the recent blocks the fee oracle doesn't know about yet, e.g. after a restart.
+	fn backfill_fee_blocks<C>(&self, chain: &C) where C: BlockChain {
+		let best = chain.chain_info().best_block_number;
+		let missing = self.fee_oracle.read().missing_blocks(best);
+		if missing.is_empty() {
+			return;
+		}
+
+		let blocks = fee_blocks(chain, missing.into_iter().map(BlockId::Number));
+		let mut oracle = self.fee_oracle.write();
+		for (number, base_fee, gas_prices) in blocks {
+			oracle.backfill_block(number, base_fee, gas_prices);
+		}
+	}
+
 	fn pool_client<'a, C: 'a>(&'a self, chain: &'a C) -> PoolClient<'a, C> where
 		C: BlockChain + CallContract,
 	{
@@ -422,6 +565,35 @@ impl Miner {
 		)
 	}
 
+	/// Imports transactions which are not treated as local, accounting them to given origin.
+	fn import_unverified_transactions<C: miner::BlockChainClient>(
+		&self,
+		chain: &C,
+		transactions: Vec<UnverifiedTransaction>,
+		origin: pool::TransactionOrigin,
+	) -> Vec<Result<(), transaction::Error>> {
+		let client = self.pool_client(chain);
+		let results = self.transaction_queue.import_with_origin(
+			client,
+			transactions.into_iter().map(pool::verifier::Transaction::Unverified),
+			origin,
+		);
+
+		// --------------------------------------------------------------------------
+		// | NOTE Code below requires sealing locks.                                |
+		// | Make sure to release the locks before calling that method.             |
+		// --------------------------------------------------------------------------
+		if !results.is_empty() && self.options.reseal_on_external_tx {
+			if self.sealing.lock().reseal_allowed() {
+				self.prepare_and_update_sealing(chain);
+			} else {
+				self.sealing.lock().defer_rebuild();
+			}
+		}
+
+		results
+	}
+
 	/// Prepares new block for sealing including top transactions from queue.
 	fn prepare_block<C>(&self, chain: &C) -> Option<(ClosedBlock, Option<H256>)> where
 		C: BlockChain + CallContract + BlockProducer + Nonce + Sync,
@@ -433,6 +605,9 @@ impl Miner {
 		// Some engines add transactions to the block for their own purposes, e.g. AuthorityRound RANDAO.
 		let (mut open_block, original_work_hash, engine_txs) = {
 			let mut sealing = self.sealing.lock();
+			// the new block includes all the transactions imported so far
+			sealing.rebuild_deferred = false;
+			sealing.rebuild_stats.performed += 1;
 			let last_work_hash = sealing.queue.peek_last_ref().map(|pb| pb.header.hash());
 			let best_hash = chain_info.best_block_hash;
 
@@ -489,6 +664,7 @@ impl Miner {
 		let mut senders_to_penalize = HashSet::new();
 		let block_number = open_block.header.number();
 
+		let base_fee = open_block.header.base_fee();
 		let mut tx_count = 0usize;
 		let mut skipped_transactions = 0usize;
 
@@ -525,7 +701,13 @@ impl Miner {
 		let block_start = Instant::now();
 		debug!(target: "miner", "Attempting to push {} transactions.", engine_txs.len() + queue_txs.len());
 
-		for transaction in engine_txs.into_iter().chain(queue_txs.into_iter().map(|tx| tx.signed().clone())) {
+		// Queue is ordered by gas price, which for legacy transactions also orders them by the effective
+		// tip (`gas_price - base_fee`). Transactions not paying the base fee stay in the queue, since it may drop.
+		let queue_txs = queue_txs.into_iter()
+			.filter(|tx| base_fee.map_or(true, |base_fee| tx.signed().gas_price >= base_fee))
+			.map(|tx| tx.signed().clone());
+
+		for transaction in engine_txs.into_iter().chain(queue_txs) {
 			let start = Instant::now();
 
 			let hash = transaction.hash();
@@ -579,6 +761,9 @@ impl Miner {
 				Err(Error::Execution(ExecutionError::InvalidNonce { expected, got })) => {
 					debug!(target: "miner", "Skipping adding transaction to block because of invalid nonce: {:?} (expected: {:?}, got: {:?})", hash, expected, got);
 				},
+				Err(Error::Execution(ExecutionError::GasPriceLowerThanBaseFee { base_fee, got })) => {
+					debug!(target: "miner", "Skipping adding transaction to block because of low gas price: {:?} (base fee: {:?}, got: {:?})", hash, base_fee, got);
+				},
 				// already have transaction - ignore
 				Err(Error::Transaction(transaction::Error::AlreadyImported)) => {},
 				Err(Error::Transaction(transaction::Error::NotAllowed)) => {
@@ -748,6 +933,23 @@ impl Miner {
 		sealing_result
 	}
 
+	/// Difficulty advertised to external miners for work of the given block difficulty.
+	fn advertised_difficulty(&self, difficulty: U256) -> U256 {
+		match *self.work_test_difficulty.read() {
+			Some(test_difficulty) if !test_difficulty.is_zero() => cmp::min(test_difficulty, difficulty),
+			_ => difficulty,
+		}
+	}
+
+	/// Whether `seal` solves the work of `header` at the test difficulty, but not at the block difficulty.
+	fn is_test_share(&self, mut header: Header, seal: &[Bytes], test_difficulty: U256) -> bool {
+		header.set_seal(seal.to_vec());
+		match self.engine.verify_block_unordered(&header) {
+			Err(Error::Block(BlockError::InvalidProofOfWork(ref bounds))) => bounds.found >= test_difficulty,
+			_ => false,
+		}
+	}
+
 	/// Prepares work which has to be done to seal.
 	fn prepare_work(&self, block: ClosedBlock, original_work_hash: Option<H256>) {
 		let (work, is_new) = {
@@ -781,7 +983,7 @@ impl Miner {
 					}
 				}
 
-				(Some((block_hash, *block_header.difficulty(), block_header.number())), is_new)
+				(Some((block_hash, self.advertised_difficulty(*block_header.difficulty()), block_header.number())), is_new)
 			} else {
 				(None, false)
 			};
@@ -949,14 +1151,29 @@ impl miner::MinerService for Miner {
 	}
 
 	fn sensible_gas_price(&self) -> U256 {
-		// 10% above our minimum.
-		self.transaction_queue.current_worst_gas_price() * 110u32 / 100
+		// 10% above our minimum, but never below the base fee of the next block.
+		let gas_price = self.transaction_queue.current_worst_gas_price() * 110u32 / 100;
+		cmp::max(gas_price, self.next_base_fee.read().unwrap_or_default())
 	}
 
 	fn sensible_gas_limit(&self) -> U256 {
 		self.params.read().gas_range_target.0 / 5
 	}
 
+	fn gas_price_floor(&self) -> Option<U256> {
+		self.gas_price_floor.as_ref().map(|floor| floor.lock().current())
+	}
+
+	fn fee_suggestions<C>(&self, chain: &C, percentile: usize) -> FeeSuggestions
+		where C: BlockChain,
+	{
+		self.backfill_fee_blocks(chain);
+
+		let next_base_fee = self.engine.params().next_base_fee(&chain.best_block_header());
+		let fallback_tip = self.sensible_gas_price().saturating_sub(next_base_fee.unwrap_or_default());
+		self.fee_oracle.read().suggestions(percentile, next_base_fee, fallback_tip)
+	}
+
 	fn set_minimal_gas_price(&self, new_price: U256) -> Result<bool, &str> {
 		match *self.gas_pricer.lock() {
 			// Binding the gas pricer to `gp` here to prevent
@@ -966,10 +1183,13 @@ impl miner::MinerService for Miner {
 				*gp = GasPricer::new_fixed(new_price);
 
 				let txq = self.transaction_queue.clone();
+				let priced = self.priced_gas_price.clone();
 				let mut options = self.options.pool_verification_options.clone();
-				gp.recalibrate(move |gas_price| {
+				let floor = self.gas_price_floor().unwrap_or_default();
+				gp.recalibrate(None, move |gas_price| {
 					debug!(target: "miner", "minimal_gas_price: Got gas price! {}", gas_price);
-					options.minimal_gas_price = gas_price;
+					*priced.write() = gas_price;
+					options.minimal_gas_price = cmp::max(gas_price, floor);
 					txq.set_verifier_options(options);
 				});
 
@@ -989,21 +1209,7 @@ impl miner::MinerService for Miner {
 		transactions: Vec<UnverifiedTransaction>
 	) -> Vec<Result<(), transaction::Error>> {
 		trace!(target: "external_tx", "Importing external transactions");
-		let client = self.pool_client(chain);
-		let results = self.transaction_queue.import(
-			client,
-			transactions.into_iter().map(pool::verifier::Transaction::Unverified),
-		);
-
-		// --------------------------------------------------------------------------
-		// | NOTE Code below requires sealing locks.                                |
-		// | Make sure to release the locks before calling that method.             |
-		// --------------------------------------------------------------------------
-		if !results.is_empty() && self.options.reseal_on_external_tx &&	self.sealing.lock().reseal_allowed() {
-			self.prepare_and_update_sealing(chain);
-		}
-
-		results
+		self.import_unverified_transactions(chain, transactions, pool::TransactionOrigin::Network)
 	}
 
 	fn import_own_transaction<C: miner::BlockChainClient>(
@@ -1025,8 +1231,12 @@ impl miner::MinerService for Miner {
 		// | NOTE Code below requires sealing locks.                                |
 		// | Make sure to release the locks before calling that method.             |
 		// --------------------------------------------------------------------------
-		if imported.is_ok() && self.options.reseal_on_own_tx && self.sealing.lock().reseal_allowed() {
-			self.prepare_and_update_sealing(chain);
+		if imported.is_ok() && self.options.reseal_on_own_tx {
+			if self.sealing.lock().reseal_allowed() {
+				self.prepare_and_update_sealing(chain);
+			} else {
+				self.sealing.lock().defer_rebuild();
+			}
 		}
 
 		imported
@@ -1038,23 +1248,36 @@ impl miner::MinerService for Miner {
 		pending: PendingTransaction,
 		trusted: bool
 	) -> Result<(), transaction::Error> {
-		// treat the tx as local if the option is enabled, if we have the account, or if
-		// the account is specified as a Prioritized Local Addresses
-		let sender = pending.sender();
-		let treat_as_local = trusted
-			|| !self.options.tx_queue_no_unfamiliar_locals
-			|| self.accounts.is_local(&sender);
-
-		if treat_as_local {
+		if self.treat_as_local(&pending.sender(), trusted) {
 			self.import_own_transaction(chain, pending)
 		} else {
 			// We want to replicate behaviour for external transactions if we're not going to treat
 			// this as local. This is important with regards to sealing blocks
-			self.import_external_transactions(chain, vec![pending.transaction.into()])
+			self.import_unverified_transactions(chain, vec![pending.transaction.into()], pool::TransactionOrigin::Rpc)
 				.pop().expect("one result per tx, as in `import_own_transaction`")
 		}
 	}
 
+	fn simulate_transaction_import<C: miner::BlockChainClient>(
+		&self,
+		chain: &C,
+		pending: PendingTransaction,
+		trusted: bool
+	) -> Result<pool::ImportSimulation, transaction::Error> {
+		let transaction = if self.treat_as_local(&pending.sender(), trusted) {
+			pool::verifier::Transaction::Local(pending)
+		} else {
+			pool::verifier::Transaction::Unverified(pending.transaction.into())
+		};
+		let chain_info = chain.chain_info();
+
+		self.transaction_queue.simulate_import(
+			self.pool_client(chain),
+			transaction,
+			pool::PendingSettings::all_prioritized(chain_info.best_block_number, chain_info.best_block_timestamp),
+		)
+	}
+
 	fn local_transactions(&self) -> BTreeMap<H256, pool::local_transactions::Status> {
 		self.transaction_queue.local_transactions()
 	}
@@ -1214,6 +1437,7 @@ impl miner::MinerService for Miner {
 						transaction_index: index,
 						cumulative_gas_used: receipt.gas_used,
 						gas_used: receipt.gas_used - prev_gas,
+						effective_gas_price: tx.gas_price,
 						contract_address: match tx.action {
 							Action::Call(_) => None,
 							Action::Create => {
@@ -1286,10 +1510,35 @@ impl miner::MinerService for Miner {
 		};
 	}
 
+	fn set_work_test_difficulty(&self, difficulty: Option<U256>) {
+		*self.work_test_difficulty.write() = difficulty;
+	}
+
+	fn work_test_shares(&self) -> u64 {
+		self.work_test_shares.load(AtomicOrdering::Relaxed)
+	}
+
 	fn is_currently_sealing(&self) -> bool {
 		self.sealing.lock().enabled
 	}
 
+	fn refresh_pending_block<C>(&self, chain: &C) where
+		C: BlockChain + CallContract + BlockProducer + SealedBlockImporter + Nonce + Sync,
+	{
+		if self.engine.sealing_state() != SealingState::External || !self.sealing.lock().take_deferred_rebuild() {
+			return;
+		}
+
+		// --------------------------------------------------------------------------
+		// | NOTE Code below requires sealing locks.                                |
+		// | Make sure to release the locks before calling that method.             |
+		// --------------------------------------------------------------------------
+		trace!(target: "miner", "refresh_pending_block: performing deferred rebuild");
+		if let Some((block, original_work_hash)) = self.prepare_block(chain) {
+			self.prepare_work(block, original_work_hash);
+		}
+	}
+
 	fn work_package<C>(&self, chain: &C) -> Option<(H256, BlockNumber, u64, U256)> where
 		C: BlockChain + CallContract + BlockProducer + SealedBlockImporter + Nonce + Sync,
 	{
@@ -1297,34 +1546,53 @@ impl miner::MinerService for Miner {
 			return None;
 		}
 
+		self.refresh_pending_block(chain);
 		self.prepare_pending_block(chain);
 
 		self.sealing.lock().queue.use_last_ref().map(|b| {
 			let header = &b.header;
-			(header.hash(), header.number(), header.timestamp(), *header.difficulty())
+			(header.hash(), header.number(), header.timestamp(), self.advertised_difficulty(*header.difficulty()))
 		})
 	}
 
 	// Note used for external submission (PoW) and internally by sealing engines.
 	fn submit_seal(&self, block_hash: H256, seal: Vec<Bytes>) -> Result<SealedBlock, Error> {
+		self.submit_work_seal(block_hash, seal).map(|(sealed, _)| sealed)
+	}
+
+	fn submit_work_seal(&self, block_hash: H256, seal: Vec<Bytes>) -> Result<(SealedBlock, bool), Error> {
+		let test_difficulty = self.work_test_difficulty.read().filter(|difficulty| !difficulty.is_zero());
+		if let Some(test_difficulty) = test_difficulty {
+			// check before taking the block, so that a test share doesn't discard the work
+			let header = self.sealing.lock().queue
+				.peek_recent_used_if(self.options.stale_work_window, |b| &b.header.bare_hash() == &block_hash)
+				.map(|b| b.header.clone());
+			if header.map_or(false, |header| self.is_test_share(header, &seal, test_difficulty)) {
+				self.work_test_shares.fetch_add(1, AtomicOrdering::Relaxed);
+				debug!(target: "miner", "Submitted solution for {} accepted as a test share.", block_hash);
+				return Err(Error::PowTestShare);
+			}
+		}
+
 		let action = if self.options.enable_resubmission {
 			GetAction::Clone
 		} else {
 			GetAction::Take
 		};
 
-		let block = self.sealing.lock().queue
-			.get_used_if(action, |b| &b.header.bare_hash() == &block_hash)
+		let (block, age) = self.sealing.lock().queue
+			.get_recent_used_if(action, self.options.stale_work_window, |b| &b.header.bare_hash() == &block_hash)
 			.ok_or_else(|| {
 				warn!(target: "miner", "Submitted solution rejected: Block unknown or out of date.");
 				Error::PowHashInvalid
 			})?;
 
 		trace!(
-			target: "miner", "Submitted block {hash}={bare_hash} with seal {seal:?}",
+			target: "miner", "Submitted block {hash}={bare_hash} with seal {seal:?} for work {age} package(s) old",
 			hash = block_hash,
 			bare_hash = block.header.bare_hash(),
-			seal = seal
+			seal = seal,
+			age = age
 		);
 
 		let sealed = block.lock()
@@ -1343,7 +1611,7 @@ impl miner::MinerService for Miner {
 			hash = Colour::White.bold().paint(format!("{:x}", h))
 		);
 
-		Ok(sealed)
+		Ok((sealed, age > 0))
 	}
 
 	fn chain_new_blocks<C>(&self, chain: &C, imported: &[H256], _invalid: &[H256], enacted: &[H256], retracted: &[H256], is_internal_import: bool)
@@ -1363,8 +1631,12 @@ impl miner::MinerService for Miner {
 		}
 
 		// First update gas limit in transaction queue and minimal gas price.
-		let gas_limit = *chain.best_block_header().gas_limit();
-		self.update_transaction_queue_limits(gas_limit);
+		let best_header = chain.best_block_header();
+		*self.next_base_fee.write() = self.engine.params().next_base_fee(&best_header);
+		self.update_transaction_queue_limits(*best_header.gas_limit());
+		if has_new_best_block {
+			self.note_fee_blocks(chain, enacted.iter().map(|hash| BlockId::Hash(*hash)));
+		}
 
 		// Then import all transactions from retracted blocks.
 		let client = self.pool_client(chain);
@@ -1476,6 +1748,22 @@ impl miner::MinerService for Miner {
 	}
 }
 
+/// Number, base fee and transaction gas prices of the given blocks, in ascending order.
+/// The oracle forgets blocks above a newly noted one, so they have to be noted in order.
+fn fee_blocks<C, I>(chain: &C, blocks: I) -> Vec<(BlockNumber, Option<U256>, Vec<U256>)>
+	where C: BlockChain, I: IntoIterator<Item = BlockId>,
+{
+	let mut blocks: Vec<_> = blocks.into_iter()
+		.filter_map(|id| chain.block(id))
+		.map(|block| {
+			let gas_prices = block.transaction_views().iter().map(|tx| tx.gas_price()).collect();
+			(block.number(), block.base_fee(), gas_prices)
+		})
+		.collect();
+	blocks.sort_by_key(|&(number, _, _)| number);
+	blocks
+}
+
 #[cfg(test)]
 mod tests {
 	use std::iter::FromIterator;
@@ -1531,6 +1819,91 @@ mod tests {
 		assert!(miner.submit_seal(hash, vec![]).is_ok());
 	}
 
+	#[test]
+	fn should_report_stale_work_within_window() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::new_for_tests(&spec::new_test(), None);
+		let old_hash = miner.work_package(&client).unwrap().0;
+		client.add_blocks(1, EachBlockWith::Uncle);
+		let new_hash = miner.work_package(&client).unwrap().0;
+		assert!(old_hash != new_hash);
+
+		// then
+		assert_eq!(miner.submit_work_seal(new_hash, vec![]).unwrap().1, false);
+		assert_eq!(miner.submit_work_seal(old_hash, vec![]).unwrap().1, true);
+	}
+
+	#[test]
+	fn should_reject_work_outside_of_stale_window() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::new(MinerOptions {
+			stale_work_window: 1,
+			reseal_min_period: Duration::from_secs(0),
+			..Default::default()
+		}, GasPricer::new_fixed(0u64.into()), &spec::new_test(), Default::default());
+		let old_hash = miner.work_package(&client).unwrap().0;
+		client.add_blocks(1, EachBlockWith::Uncle);
+		let new_hash = miner.work_package(&client).unwrap().0;
+
+		// then
+		assert!(miner.submit_seal(old_hash, vec![]).is_err());
+		assert!(miner.submit_seal(new_hash, vec![]).is_ok());
+	}
+
+	#[test]
+	fn should_advertise_work_test_difficulty() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::new_for_tests(&spec::new_test(), None);
+		let (hash, _, _, difficulty) = miner.work_package(&client).unwrap();
+
+		// when
+		miner.set_work_test_difficulty(Some(difficulty / 2));
+
+		// then
+		let (advertised_hash, _, _, advertised) = miner.work_package(&client).unwrap();
+		assert_eq!(advertised_hash, hash);
+		assert_eq!(advertised, difficulty / 2);
+		// solutions meeting the block difficulty are accepted as usual
+		assert!(miner.submit_seal(hash, vec![]).is_ok());
+		assert_eq!(miner.work_test_shares(), 0);
+
+		miner.set_work_test_difficulty(None);
+		assert_eq!(miner.work_package(&client).unwrap().3, difficulty);
+	}
+
+	#[test]
+	fn should_count_solution_meeting_only_work_test_difficulty() {
+		use ethash::EthashManager;
+		use ethereum_types::H64;
+		use tempdir::TempDir;
+
+		// given
+		let client = TestBlockChainClient::new_with_spec(spec::new_homestead_test());
+		let miner = Miner::new_for_tests(&spec::new_homestead_test(), None);
+		miner.set_work_test_difficulty(Some(U256::one()));
+		let (hash, number, _, advertised) = miner.work_package(&client).unwrap();
+		assert_eq!(advertised, U256::one());
+
+		// when
+		let cache_dir = TempDir::new("").unwrap();
+		let pow = EthashManager::new(cache_dir.path(), None, u64::max_value());
+		let nonce = 0;
+		let result = pow.compute_light(number, &hash.0, nonce);
+		let seal = vec![rlp::encode(&H256(result.mix_hash)), rlp::encode(&H64::from_low_u64_be(nonce))];
+
+		// then
+		match miner.submit_work_seal(hash, seal) {
+			Err(Error::PowTestShare) => {},
+			other => panic!("Expected a test share, got {:?}", other.map(|_| ())),
+		}
+		assert_eq!(miner.work_test_shares(), 1);
+		// the work package is kept for solutions meeting the block difficulty
+		assert_eq!(miner.work_package(&client).unwrap().0, hash);
+	}
+
 	fn miner() -> Miner {
 		Miner::new(
 			MinerOptions {
@@ -1542,6 +1915,7 @@ mod tests {
 				reseal_max_period: Duration::from_secs(120),
 				pending_set: PendingSet::AlwaysSealing,
 				work_queue_size: 5,
+				stale_work_window: 5,
 				enable_resubmission: true,
 				infinite_pending_block: false,
 				tx_queue_penalization: Penalization::Disabled,
@@ -1555,6 +1929,9 @@ mod tests {
 					tx_gas_limit: U256::max_value(),
 					no_early_reject: false,
 				},
+				pool_origin_quotas: Default::default(),
+				gas_price_floor: None,
+				work_test_difficulty: None,
 			},
 			GasPricer::new_fixed(0u64.into()),
 			&spec::new_test(),
@@ -1599,6 +1976,27 @@ mod tests {
 		assert_eq!(miner.prepare_pending_block(&client), BlockPreparationStatus::NotPrepared);
 	}
 
+	#[test]
+	fn should_coalesce_pending_block_rebuilds() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let best_block = 0;
+
+		// when
+		// the third import comes within `reseal_min_period` from the second one
+		for _ in 0..3 {
+			miner.import_own_transaction(&client, PendingTransaction::new(transaction(), None)).unwrap();
+		}
+		let coalesced = miner.pending_transactions(best_block).unwrap().len();
+		miner.refresh_pending_block(&client);
+
+		// then
+		assert_eq!(coalesced, 2);
+		assert_eq!(miner.pending_transactions(best_block).unwrap().len(), 3);
+		assert_eq!(miner.pending_rebuild_stats(), PendingRebuildStats { performed: 3, skipped: 1, on_demand: 1 });
+	}
+
 	#[test]
 	fn should_not_return_stale_work_packages() {
 		// given
@@ -1888,6 +2286,36 @@ mod tests {
 		assert!(current_minimum_gas_price == expected_minimum_gas_price);
 	}
 
+	#[test]
+	fn should_raise_gas_price_floor_under_queue_pressure() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::new(MinerOptions {
+			pool_limits: pool::Options { max_count: 2, ..Default::default() },
+			gas_price_floor: Some(GasPriceFloorOptions {
+				min: 0.into(),
+				max: 1_000.into(),
+				step: 50,
+				high_watermark: 50,
+				low_watermark: 10,
+			}),
+			..miner().options
+		}, GasPricer::new_fixed(0u64.into()), &spec::new_test(), Default::default());
+		miner.update_transaction_queue_limits(U256::max_value());
+		assert_eq!(miner.gas_price_floor(), Some(0.into()));
+
+		// when
+		miner.import_external_transactions(&client, vec![transaction().into()]).pop().unwrap().unwrap();
+		miner.update_transaction_queue_limits(U256::max_value());
+
+		// then
+		assert_eq!(miner.gas_price_floor(), Some(1.into()));
+		assert_eq!(miner.queue_status().options.minimal_gas_price, 1.into());
+		// manually set price does not go below the floor
+		miner.set_minimal_gas_price(0.into()).unwrap();
+		assert_eq!(miner.queue_status().options.minimal_gas_price, 1.into());
+	}
+
 	#[cfg(feature = "price-info")]
 	fn dynamic_gas_pricer() -> GasPricer {
 		use parity_runtime::Executor;
@@ -1930,6 +2358,34 @@ mod tests {
 		assert!(received_error_msg == expected_error_msg);
 	}
 
+	#[test]
+	#[cfg(feature = "price-info")]
+	fn should_apply_gas_price_floor_between_calibrations() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::new(MinerOptions {
+			pool_limits: pool::Options { max_count: 2, ..Default::default() },
+			gas_price_floor: Some(GasPriceFloorOptions {
+				min: 0.into(),
+				max: 1_000.into(),
+				step: 50,
+				high_watermark: 50,
+				low_watermark: 10,
+			}),
+			..miner().options
+		}, dynamic_gas_pricer(), &spec::new_test(), Default::default());
+		miner.update_transaction_queue_limits(U256::max_value());
+
+		// when
+		miner.import_external_transactions(&client, vec![transaction().into()]).pop().unwrap().unwrap();
+		// the calibrator never gets a price from its fake endpoint
+		miner.update_transaction_queue_limits(U256::max_value());
+
+		// then
+		assert_eq!(miner.gas_price_floor(), Some(1.into()));
+		assert_eq!(miner.queue_status().options.minimal_gas_price, 1.into());
+	}
+
 	fn filter_tester(option: PendingSet) {
 		let client = TestBlockChainClient::default();
 		let mut miner = miner();
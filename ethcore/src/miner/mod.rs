@@ -25,8 +25,9 @@ pub mod pool_client;
 #[cfg(feature = "stratum")]
 pub mod stratum;
 
-pub use self::miner::{Miner, MinerOptions, Penalization, PendingSet, AuthoringParams, Author};
+pub use self::miner::{Miner, MinerOptions, Penalization, PendingSet, PendingRebuildStats, AuthoringParams, Author};
 pub use self::filter_options::FilterOptions;
+pub use ethcore_miner::fee_oracle::{FeeSuggestions, FeeTier};
 pub use ethcore_miner::local_accounts::LocalAccounts;
 pub use ethcore_miner::pool::PendingOrdering;
 
@@ -34,7 +35,7 @@ use std::sync::Arc;
 use std::collections::{BTreeSet, BTreeMap};
 
 use bytes::Bytes;
-use ethcore_miner::pool::{VerifiedTransaction, QueueStatus, local_transactions};
+use ethcore_miner::pool::{VerifiedTransaction, ImportSimulation, QueueStatus, local_transactions};
 use ethereum_types::{H256, U256, Address};
 use types::transaction::{self, UnverifiedTransaction, SignedTransaction, PendingTransaction};
 use types::{
@@ -77,6 +78,17 @@ pub trait MinerService : Send + Sync {
 	/// Will check the seal, but not actually insert the block into the chain.
 	fn submit_seal(&self, pow_hash: H256, seal: Vec<Bytes>) -> Result<SealedBlock, Error>;
 
+	/// Submit `seal` as a valid solution for the header of `pow_hash`, like `submit_seal`.
+	/// Also returns whether the solution was for stale work, i.e. newer work has been handed out since.
+	fn submit_work_seal(&self, pow_hash: H256, seal: Vec<Bytes>) -> Result<(SealedBlock, bool), Error>;
+
+	/// Advertise `difficulty` instead of the block difficulty to external miners, to test their rigs.
+	/// Solutions meeting only the test difficulty are rejected with `PowTestShare` and counted.
+	fn set_work_test_difficulty(&self, difficulty: Option<U256>);
+
+	/// Number of submitted solutions which met only the test difficulty.
+	fn work_test_shares(&self) -> u64;
+
 	/// Is it currently sealing?
 	fn is_currently_sealing(&self) -> bool;
 
@@ -90,6 +102,12 @@ pub trait MinerService : Send + Sync {
 	fn update_sealing<C>(&self, chain: &C, force: ForceUpdateSealing)
 		where C: BlockChain + CallContract + BlockProducer + SealedBlockImporter + Nonce + Sync;
 
+	/// Rebuild the pending block if a rebuild was skipped since transactions were imported
+	/// too early after the previous one. Call before using the pending block.
+	fn refresh_pending_block<C>(&self, _chain: &C)
+		where C: BlockChain + CallContract + BlockProducer + SealedBlockImporter + Nonce + Sync
+	{}
+
 	// Notifications
 
 	/// Called when blocks are imported to chain, updates transactions queue.
@@ -155,6 +173,13 @@ pub trait MinerService : Send + Sync {
 		-> Result<(), transaction::Error>
 		where C: BlockChainClient;
 
+	/// Runs all transaction pool admission checks for given transaction without importing it.
+	///
+	/// The transaction is treated the same way as in `import_claimed_local_transaction`.
+	fn simulate_transaction_import<C>(&self, chain: &C, transaction: PendingTransaction, trusted: bool)
+		-> Result<ImportSimulation, transaction::Error>
+		where C: BlockChainClient;
+
 	/// Removes transaction from the pool.
 	///
 	/// Attempts to "cancel" a transaction. If it was not propagated yet (or not accepted by other peers)
@@ -218,6 +243,14 @@ pub trait MinerService : Send + Sync {
 	/// Suggested gas limit.
 	fn sensible_gas_limit(&self) -> U256;
 
+	/// Current minimal gas price floor, `None` if the floor is not following the queue pressure.
+	fn gas_price_floor(&self) -> Option<U256>;
+
+	/// Fee suggestions for the next block, based on the effective tips paid in recent blocks.
+	/// The standard tier uses the given percentile of the recent tips.
+	fn fee_suggestions<C>(&self, chain: &C, percentile: usize) -> FeeSuggestions
+		where C: BlockChain;
+
 	/// Set a new minimum gas limit.
 	/// Will not work if dynamic gas calibration is set.
 	fn set_minimal_gas_price(&self, gas_price: U256) -> Result<bool, &str>;
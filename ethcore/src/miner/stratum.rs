@@ -16,23 +16,26 @@
 
 //! Client-side stratum job dispatcher and mining notifier handler
 
-use std::sync::{Arc, Weak};
+use std::sync::{Arc, Weak, mpsc};
 use std::net::{SocketAddr, AddrParseError};
-use std::fmt;
+use std::path::Path;
+use std::{fmt, fs, io, thread};
 
 use client::{Client, ImportSealedBlock};
 use ethereum_types::{H64, H256, U256};
-use ethash::{self, SeedHashCompute};
+use ethash::{self, EthashManager, ProofOfWork, SeedHashCompute};
 #[cfg(feature = "work-notify")]
 use ethcore_miner::work_notify::NotifyWork;
 #[cfg(feature = "work-notify")]
 use ethcore_stratum::PushWorkHandler;
 use ethcore_stratum::{
-	JobDispatcher, Stratum as StratumService, Error as StratumServiceError,
+	Job, JobDispatcher, Stratum as StratumService, Error as StratumServiceError, HASHES_PER_DIFFICULTY,
 };
+pub use ethcore_stratum::{TlsConfig, VardiffConfig, WorkerStats};
 use miner::{Miner, MinerService};
 use parking_lot::Mutex;
 use rlp::encode;
+use types::errors::EthcoreError;
 
 /// Configures stratum server options.
 #[derive(Debug, PartialEq, Clone)]
@@ -45,6 +48,12 @@ pub struct Options {
 	pub port: u16,
 	/// Secret for peers
 	pub secret: Option<H256>,
+	/// Expect HAProxy PROXY protocol headers on incoming connections
+	pub proxy_protocol: bool,
+	/// Per-worker difficulty adjustment, disabled if `None`
+	pub vardiff: Option<VardiffConfig>,
+	/// TLS listener running next to the plaintext one, disabled if `None`
+	pub tls: Option<TlsConfig>,
 }
 
 fn clean_0x(s: &str) -> &str {
@@ -58,12 +67,13 @@ fn clean_0x(s: &str) -> &str {
 struct SubmitPayload {
 	nonce: H64,
 	pow_hash: H256,
-	mix_hash: H256,
+	/// Missing from NiceHash style submissions
+	mix_hash: Option<H256>,
 }
 
 impl SubmitPayload {
 	fn from_args(payload: Vec<String>) -> Result<Self, PayloadError> {
-		if payload.len() != 3 {
+		if payload.len() != 2 && payload.len() != 3 {
 			return Err(PayloadError::ArgumentsAmountUnexpected(payload.len()));
 		}
 
@@ -83,9 +93,10 @@ impl SubmitPayload {
 			}
 		};
 
-		let mix_hash = match clean_0x(&payload[2]).parse::<H256>() {
-			Ok(mix_hash) => mix_hash,
-			Err(e) => {
+		let mix_hash = match payload.get(2).map(|mix_hash| clean_0x(mix_hash).parse::<H256>()) {
+			None => None,
+			Some(Ok(mix_hash)) => Some(mix_hash),
+			Some(Err(e)) => {
 				warn!(target: "stratum", "submit_work ({}): invalid mix-hash ({:?})",  &payload[2], e);
 				return Err(PayloadError::InvalidMixHash(payload[2].clone()));
 			}
@@ -105,6 +116,8 @@ enum PayloadError {
 	InvalidNonce(String),
 	InvalidPowHash(String),
 	InvalidMixHash(String),
+	StaleWork(H256),
+	PowWorkerBusy,
 }
 
 impl fmt::Display for PayloadError {
@@ -113,22 +126,73 @@ impl fmt::Display for PayloadError {
 	}
 }
 
+/// Ethash difficulty of a share of stratum `difficulty`, i.e. in units of `HASHES_PER_DIFFICULTY` hashes
+fn share_target(difficulty: f64) -> U256 {
+	U256::from((difficulty * HASHES_PER_DIFFICULTY).max(1.0) as u64)
+}
+
+/// Directory in `Options::io_path` the light caches of the dispatcher are kept in.
+const ETHASH_CACHE_DIR: &str = "stratum_ethash";
+
+/// Number of submissions waiting for their proof of work before new ones are rejected.
+const POW_QUEUE_SIZE: usize = 64;
+
+type PowRequest = (u64, H256, H64, mpsc::Sender<ProofOfWork>);
+
+/// Computes the proof of work of submitted shares on its own thread, one at a time, so that
+/// building the light cache of a new epoch doesn't run on the stratum server threads.
+struct PowWorker {
+	requests: Mutex<mpsc::SyncSender<PowRequest>>,
+}
+
+impl PowWorker {
+	/// Starts the worker, its light caches are kept in `cache_dir`. It stops once dropped.
+	fn start(cache_dir: &Path) -> io::Result<Self> {
+		fs::create_dir_all(cache_dir)?;
+		let pow = EthashManager::new(cache_dir, None, u64::max_value());
+		let (requests, queue) = mpsc::sync_channel::<PowRequest>(POW_QUEUE_SIZE);
+		thread::Builder::new().name("stratum-pow".into()).spawn(move || {
+			for (number, pow_hash, nonce, result) in queue {
+				let _ = result.send(pow.compute_light(number, &pow_hash.0, nonce.to_low_u64_be()));
+			}
+		})?;
+
+		Ok(PowWorker { requests: Mutex::new(requests) })
+	}
+
+	/// Proof of work of `nonce` for the work package `pow_hash` of block `number`
+	fn compute(&self, number: u64, pow_hash: H256, nonce: H64) -> Result<ProofOfWork, PayloadError> {
+		let (result, receiver) = mpsc::channel();
+		self.requests.lock().try_send((number, pow_hash, nonce, result)).map_err(|_| PayloadError::PowWorkerBusy)?;
+		receiver.recv().map_err(|_| PayloadError::PowWorkerBusy)
+	}
+}
+
 /// Job dispatcher for stratum service
 pub struct StratumJobDispatcher {
 	seed_compute: Mutex<SeedHashCompute>,
+	/// Computes the proof of work of submitted shares
+	pow: PowWorker,
 	client: Weak<Client>,
 	miner: Weak<Miner>,
+	/// Parent hash of the block of the last pushed job, `None` if unknown
+	last_job_parent: Mutex<Option<H256>>,
+	/// Block difficulty of the last job sent to the workers
+	last_job_difficulty: Mutex<Option<U256>>,
 }
 
 impl JobDispatcher for StratumJobDispatcher {
 	fn initial(&self) -> Option<String> {
 		// initial payload may contain additional data, not in this case
-		self.job()
+		self.with_core(|client, miner| miner.work_package(&*client).map(|(pow_hash, number, _timestamp, difficulty)| {
+			*self.last_job_difficulty.lock() = Some(difficulty);
+			self.payload(pow_hash, difficulty, number)
+		}))
 	}
 
-	fn job(&self) -> Option<String> {
+	fn job(&self) -> Option<Job> {
 		self.with_core(|client, miner| miner.work_package(&*client).map(|(pow_hash, number, _timestamp, difficulty)| {
-			self.payload(pow_hash, difficulty, number)
+			self.push_job(pow_hash, difficulty, number, Self::job_parent(&*miner, pow_hash, number))
 		}))
 	}
 
@@ -139,35 +203,92 @@ impl JobDispatcher for StratumJobDispatcher {
 
 		trace!(
 			target: "stratum",
-			"submit_work: Decoded: nonce={}, pow_hash={}, mix_hash={}",
+			"submit_work: Decoded: nonce={}, pow_hash={}, mix_hash={:?}",
 			payload.nonce,
 			payload.pow_hash,
 			payload.mix_hash,
 		);
 
 		self.with_core_result(|client, miner| {
-			let seal = vec![encode(&payload.mix_hash), encode(&payload.nonce)];
-
-			let import = miner.submit_seal(payload.pow_hash, seal)
-				.and_then(|block| client.import_sealed_block(block));
-			match import {
-				Ok(_) => Ok(()),
-				Err(e) => {
-					warn!(target: "stratum", "submit_seal error: {:?}", e);
-					Err(StratumServiceError::Dispatch(e.to_string()))
-				}
+			let mix_hash = match payload.mix_hash {
+				Some(mix_hash) => mix_hash,
+				None => self.mix_hash(&*client, &*miner, &payload.pow_hash, &payload.nonce)
+					.map_err(|e| StratumServiceError::Dispatch(e.to_string()))?,
+			};
+			Self::submit_seal(&*client, &*miner, payload.pow_hash, mix_hash, payload.nonce)
+		})
+	}
+
+	fn submit_share(&self, payload: Vec<String>, difficulty: f64) -> Result<(), StratumServiceError> {
+		let payload = SubmitPayload::from_args(payload).map_err(|e|
+			StratumServiceError::Dispatch(e.to_string())
+		)?;
+
+		self.with_core_result(|client, miner| {
+			let (number, job_difficulty) = match miner.work_package(&*client) {
+				Some((current, number, _timestamp, difficulty)) if current == payload.pow_hash => (number, difficulty),
+				_ => return Err(StratumServiceError::Dispatch(PayloadError::StaleWork(payload.pow_hash).to_string())),
+			};
+			let pow = self.pow.compute(number, payload.pow_hash, payload.nonce)
+				.map_err(|e| StratumServiceError::Dispatch(e.to_string()))?;
+			let mix_hash = H256(pow.mix_hash);
+			if payload.mix_hash.map_or(false, |submitted| submitted != mix_hash) {
+				return Err(StratumServiceError::Dispatch(PayloadError::InvalidMixHash(format!("{:x}", mix_hash)).to_string()));
+			}
+
+			let found = ethash::boundary_to_difficulty(&H256(pow.value));
+			if found >= job_difficulty {
+				return Self::submit_seal(&*client, &*miner, payload.pow_hash, mix_hash, payload.nonce);
+			}
+			if found < share_target(difficulty) {
+				trace!(target: "stratum", "submit_share: {} doesn't meet the worker difficulty {}", payload.pow_hash, difficulty);
+				return Err(StratumServiceError::Dispatch(format!("Share doesn't meet the worker difficulty {}", difficulty)));
 			}
+			Ok(())
 		})
 	}
+
+	fn share_difficulty(&self) -> Option<f64> {
+		// jobs are pushed with the boundary of the block difficulty
+		self.last_job_difficulty.lock().map(|difficulty| difficulty.low_u128() as f64 / HASHES_PER_DIFFICULTY)
+	}
 }
 
 impl StratumJobDispatcher {
-	/// New stratum job dispatcher given the miner and client
-	fn new(miner: Weak<Miner>, client: Weak<Client>) -> StratumJobDispatcher {
-		StratumJobDispatcher {
+	/// New stratum job dispatcher given the miner and client, ethash caches are kept in `cache_dir`
+	fn new(miner: Weak<Miner>, client: Weak<Client>, cache_dir: &Path) -> io::Result<StratumJobDispatcher> {
+		Ok(StratumJobDispatcher {
 			seed_compute: Mutex::new(SeedHashCompute::default()),
+			pow: PowWorker::start(cache_dir)?,
 			client: client,
 			miner: miner,
+			last_job_parent: Mutex::new(None),
+			last_job_difficulty: Mutex::new(None),
+		})
+	}
+
+	/// Parent hash of the block sealed by the job `pow_hash`, if the job is still the pending one
+	fn job_parent(miner: &Miner, pow_hash: H256, number: u64) -> Option<H256> {
+		miner.pending_block_header(number)
+			.filter(|header| header.hash() == pow_hash)
+			.map(|header| *header.parent_hash())
+	}
+
+	/// Job to push to the workers, identified by the pow hash.
+	/// Work on a new parent (a new block or a reorg at the same height) makes the previous jobs
+	/// stale, new transactions on the same parent don't. Jobs of an unknown parent are always clean.
+	fn push_job(&self, pow_hash: H256, difficulty: U256, number: u64, parent_hash: Option<H256>) -> Job {
+		let clean_jobs = {
+			let mut last_job_parent = self.last_job_parent.lock();
+			let clean_jobs = parent_hash.is_none() || *last_job_parent != parent_hash;
+			*last_job_parent = parent_hash;
+			clean_jobs
+		};
+		*self.last_job_difficulty.lock() = Some(difficulty);
+		Job {
+			id: format!("0x{:x}", pow_hash),
+			payload: self.payload(pow_hash, difficulty, number),
+			clean_jobs,
 		}
 	}
 
@@ -177,12 +298,40 @@ impl StratumJobDispatcher {
 		let target = ethash::difficulty_to_boundary(&difficulty);
 		let seed_hash = &self.seed_compute.lock().hash_block_number(number);
 		let seed_hash = H256::from_slice(&seed_hash[..]);
+		// the job id comes first
 		format!(
-			r#"["0x", "0x{:x}","0x{:x}","0x{:x}","0x{:x}"]"#,
-			pow_hash, seed_hash, target, number
+			r#"["0x{:x}", "0x{:x}","0x{:x}","0x{:x}","0x{:x}"]"#,
+			pow_hash, pow_hash, seed_hash, target, number
 		)
 	}
 
+	/// Submits a solution of the work package `pow_hash` to the miner
+	fn submit_seal(client: &Client, miner: &Miner, pow_hash: H256, mix_hash: H256, nonce: H64) -> Result<(), StratumServiceError> {
+		let seal = vec![encode(&mix_hash), encode(&nonce)];
+
+		let import = miner.submit_seal(pow_hash, seal)
+			.and_then(|block| client.import_sealed_block(block));
+		match import {
+			Ok(_) => Ok(()),
+			// the miner counts test shares separately, to the worker they are valid shares
+			Err(EthcoreError::PowTestShare) => Ok(()),
+			Err(e) => {
+				warn!(target: "stratum", "submit_seal error: {:?}", e);
+				Err(StratumServiceError::Dispatch(e.to_string()))
+			}
+		}
+	}
+
+	/// Mix hash of the current work package for `nonce`
+	fn mix_hash(&self, client: &Client, miner: &Miner, pow_hash: &H256, nonce: &H64) -> Result<H256, PayloadError> {
+		match miner.work_package(client) {
+			Some((current, number, _timestamp, _difficulty)) if current == *pow_hash => {
+				Ok(H256(self.pow.compute(number, *pow_hash, *nonce)?.mix_hash))
+			},
+			_ => Err(PayloadError::StaleWork(*pow_hash)),
+		}
+	}
+
 	fn with_core<F, R>(&self, f: F) -> Option<R> where F: Fn(Arc<Client>, Arc<Miner>) -> Option<R> {
 		self.client.upgrade().and_then(|client| self.miner.upgrade().and_then(|miner| (f)(client, miner)))
 	}
@@ -196,6 +345,7 @@ impl StratumJobDispatcher {
 }
 
 /// Wrapper for dedicated stratum service
+#[derive(Clone)]
 pub struct Stratum {
 	dispatcher: Arc<StratumJobDispatcher>,
 	service: Arc<StratumService>,
@@ -223,8 +373,9 @@ impl NotifyWork for Stratum {
 	fn notify(&self, pow_hash: H256, difficulty: U256, number: u64) {
 		trace!(target: "stratum", "Notify work");
 
+		let parent_hash = self.dispatcher.with_core(|_client, miner| StratumJobDispatcher::job_parent(&*miner, pow_hash, number));
 		self.service.push_work_all(
-			self.dispatcher.payload(pow_hash, difficulty, number)
+			self.dispatcher.push_job(pow_hash, difficulty, number, parent_hash)
 		);
 	}
 }
@@ -235,12 +386,17 @@ impl Stratum {
 	pub fn start(options: &Options, miner: Weak<Miner>, client: Weak<Client>) -> Result<Stratum, Error> {
 		use std::net::IpAddr;
 
-		let dispatcher = Arc::new(StratumJobDispatcher::new(miner, client));
+		// the engine's light caches aren't shared, so the dispatcher keeps its own ones apart from them
+		let cache_dir = Path::new(&options.io_path).join(ETHASH_CACHE_DIR);
+		let dispatcher = Arc::new(StratumJobDispatcher::new(miner, client, &cache_dir).map_err(StratumServiceError::from)?);
 
 		let service = StratumService::start(
 			&SocketAddr::new(options.listen_addr.parse::<IpAddr>()?, options.port),
 			dispatcher.clone(),
 			options.secret.clone(),
+			options.proxy_protocol,
+			options.vardiff.clone(),
+			options.tls.clone(),
 		)?;
 
 		Ok(Stratum { dispatcher, service })
@@ -248,9 +404,14 @@ impl Stratum {
 
 	/// Start STRATUM job dispatcher and register it in the miner
 	#[cfg(feature = "work-notify")]
-	pub fn register(cfg: &Options, miner: Arc<Miner>, client: Weak<Client>) -> Result<(), Error> {
+	pub fn register(cfg: &Options, miner: Arc<Miner>, client: Weak<Client>) -> Result<Stratum, Error> {
 		let stratum = Stratum::start(cfg, Arc::downgrade(&miner.clone()), client)?;
-		miner.add_work_listener(Box::new(stratum) as Box<dyn NotifyWork>);
-		Ok(())
+		miner.add_work_listener(Box::new(stratum.clone()) as Box<dyn NotifyWork>);
+		Ok(stratum)
+	}
+
+	/// Share statistics of the workers connected to the stratum service
+	pub fn workers(&self) -> Vec<WorkerStats> {
+		self.service.workers()
 	}
 }
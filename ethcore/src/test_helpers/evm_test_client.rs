@@ -38,6 +38,7 @@ use account_state::{CleanupMode, State};
 use machine::{
 	executive,
 	substate::Substate,
+	transaction_ext::Transaction as TransactionExt,
 };
 
 use executive_state::ExecutiveState;
@@ -214,8 +215,14 @@ impl<'a> EvmTestClient<'a> {
 		vm_tracer: &mut V,
 	) -> Result<FinalizationResult, EvmTestError>
 	{
-		let genesis = self.spec.genesis_header();
-		let info = vm::EnvInfo {
+		let info = Self::genesis_env_info(self.spec);
+		self.call_envinfo(params, tracer, vm_tracer, info)
+	}
+
+	/// Returns the `EnvInfo` of the genesis block of given spec, with all last hashes set to zero.
+	pub fn genesis_env_info(spec: &spec::Spec) -> vm::EnvInfo {
+		let genesis = spec.genesis_header();
+		vm::EnvInfo {
 			number: genesis.number(),
 			author: *genesis.author(),
 			timestamp: genesis.timestamp(),
@@ -223,8 +230,8 @@ impl<'a> EvmTestClient<'a> {
 			last_hashes: Arc::new([H256::zero(); 256].to_vec()),
 			gas_used: 0.into(),
 			gas_limit: *genesis.gas_limit(),
-		};
-		self.call_envinfo(params, tracer, vm_tracer, info)
+			base_fee: genesis.base_fee(),
+		}
 	}
 
 	/// Execute the VM given envinfo, ActionParams and tracer.
@@ -270,6 +277,8 @@ impl<'a> EvmTestClient<'a> {
 				});
 		}
 
+		let intrinsic_gas = transaction.gas_required(&self.spec.engine.machine().schedule(env_info.number)).into();
+
 		// Apply transaction
 		let result = self.state.apply_with_tracing(&env_info, self.spec.engine.machine(), &transaction, tracer, vm_tracer);
 		let scheme = CreateContractAddress::FromSenderAndNonce;
@@ -303,6 +312,8 @@ impl<'a> EvmTestClient<'a> {
 				Ok(TransactSuccess {
 					state_root,
 					gas_left: initial_gas - result.receipt.gas_used,
+					intrinsic_gas,
+					refunded: result.refunded,
 					outcome: result.receipt.outcome,
 					output: result.output,
 					trace: result.trace,
@@ -329,6 +340,10 @@ pub struct TransactSuccess<T, V> {
 	pub state_root: H256,
 	/// Amount of gas left
 	pub gas_left: U256,
+	/// Gas charged before the execution
+	pub intrinsic_gas: U256,
+	/// Gas refunded on top of the unused gas
+	pub refunded: U256,
 	/// Output
 	pub output: Vec<u8>,
 	/// Traces
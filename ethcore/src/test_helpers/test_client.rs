@@ -19,7 +19,7 @@
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrder};
 use std::sync::Arc;
-use std::collections::{HashMap, BTreeMap};
+use std::collections::{HashMap, HashSet, BTreeMap};
 use blockchain::BlockProvider;
 use std::mem;
 
@@ -40,14 +40,17 @@ use rustc_hex::FromHex;
 use types::{
 	BlockNumber,
 	encoded,
-	engines::epoch::Transition as EpochTransition,
+	engines::{epoch::Transition as EpochTransition, fault::ConsensusFault},
 	ids::{BlockId, TransactionId, UncleId, TraceId},
+	backup::BackupStatus,
+	bad_block::{BadBlock, BadBlockReason},
 	basic_account::BasicAccount,
 	errors::{EthcoreError as Error, EthcoreResult},
 	transaction::{self, Transaction, LocalizedTransaction, SignedTransaction, Action, CallError},
 	filter::Filter,
 	trace_filter::Filter as TraceFilter,
 	call_analytics::CallAnalytics,
+	chain_notify::DeepReorg,
 	header::Header,
 	log_entry::LocalizedLogEntry,
 	pruning_info::PruningInfo,
@@ -71,7 +74,7 @@ use client::{
 };
 use client_traits::{
 	BlockInfo, Nonce, Balance, ChainInfo, TransactionInfo, BlockChainClient, ImportBlock,
-	AccountData, BlockChain, IoClient, BadBlocks, ScheduleInfo, StateClient, ProvingBlockChainClient,
+	AccountData, BlockChain, IoClient, BadBlocks, DatabaseBackups, ReorgGuard, ScheduleInfo, StateClient, ProvingBlockChainClient,
 	StateOrBlock, ForceUpdateSealing, TransactionRequest
 };
 use engine::Engine;
@@ -81,7 +84,7 @@ use miner::{self, Miner, MinerService};
 use spec::{Spec, self};
 use account_state::state::StateInfo;
 use state_db::StateDB;
-use trace::LocalizedTrace;
+use trace::{LocalizedTrace, StateAccess};
 
 /// Test client.
 pub struct TestBlockChainClient {
@@ -127,10 +130,16 @@ pub struct TestBlockChainClient {
 	pub first_block: RwLock<Option<(H256, u64)>>,
 	/// Traces to return
 	pub traces: RwLock<Option<Vec<LocalizedTrace>>>,
+	/// State accesses of replayed transactions to return
+	pub state_accesses: RwLock<Vec<(H256, Vec<StateAccess>)>>,
 	/// Pruning history size to report.
 	pub history: RwLock<Option<u64>>,
 	/// Is disabled
 	pub disabled: AtomicBool,
+	/// States pinned with `pin_state`.
+	pub pinned_states: RwLock<HashSet<H256>>,
+	/// Consensus faults to report.
+	pub consensus_faults: RwLock<Vec<ConsensusFault>>,
 }
 
 /// Used for generating test client blocks.
@@ -197,9 +206,12 @@ impl TestBlockChainClient {
 			ancient_block: RwLock::new(None),
 			first_block: RwLock::new(None),
 			traces: RwLock::new(None),
+			state_accesses: RwLock::new(Vec::new()),
 			history: RwLock::new(None),
 			disabled: AtomicBool::new(false),
 			error_on_logs: RwLock::new(None),
+			pinned_states: RwLock::new(HashSet::new()),
+			consensus_faults: RwLock::new(Vec::new()),
 		};
 
 		// insert genesis hash.
@@ -651,6 +663,10 @@ impl StateClient for TestBlockChainClient {
 	fn state_at(&self, _id: BlockId) -> Option<Self::State> {
 		Some(TestState)
 	}
+
+	fn state_at_root(&self, _root: H256) -> Option<Self::State> {
+		Some(TestState)
+	}
 }
 
 impl EngineInfo for TestBlockChainClient {
@@ -660,18 +676,39 @@ impl EngineInfo for TestBlockChainClient {
 }
 
 impl BadBlocks for TestBlockChainClient {
-	fn bad_blocks(&self) -> Vec<(Unverified, String)> {
+	fn bad_blocks(&self) -> Vec<BadBlock> {
 		vec![
-			(Unverified {
-				header: Default::default(),
-				transactions: vec![],
-				uncles: vec![],
-				bytes: vec![1, 2, 3],
-			}, "Invalid block".into())
+			BadBlock {
+				block: Unverified {
+					header: Default::default(),
+					transactions: vec![],
+					uncles: vec![],
+					bytes: vec![1, 2, 3],
+				},
+				reason: BadBlockReason::InvalidRoot,
+				message: "Invalid block".into(),
+				rejected_at: 1_500_000_000,
+			}
 		]
 	}
 }
 
+impl DatabaseBackups for TestBlockChainClient {
+	fn backup_status(&self) -> BackupStatus {
+		BackupStatus::default()
+	}
+}
+
+impl ReorgGuard for TestBlockChainClient {
+	fn halted_reorg(&self) -> Option<DeepReorg> {
+		None
+	}
+
+	fn acknowledge_reorg(&self, _accept: bool) -> bool {
+		false
+	}
+}
+
 impl BlockChainClient for TestBlockChainClient {
 	fn replay(&self, _id: TransactionId, _analytics: CallAnalytics) -> Result<Executed, CallError> {
 		self.execution_result.read().clone().unwrap()
@@ -685,6 +722,7 @@ impl BlockChainClient for TestBlockChainClient {
 			max_queue_size: 0,
 			max_mem_use: 0,
 			mem_used: 0,
+			verification_time: Default::default(),
 		}
 	}
 
@@ -700,6 +738,10 @@ impl BlockChainClient for TestBlockChainClient {
 		))
 	}
 
+	fn replay_block_with_access_log(&self, _block: BlockId) -> Result<Vec<(H256, Vec<StateAccess>)>, CallError> {
+		Ok(self.state_accesses.read().clone())
+	}
+
 	fn block_total_difficulty(&self, _id: BlockId) -> Option<U256> {
 		Some(U256::zero())
 	}
@@ -738,6 +780,32 @@ impl BlockChainClient for TestBlockChainClient {
 	fn list_storage(&self, _id: BlockId, _account: &Address, _after: Option<&H256>, _count: Option<u64>) -> Option<Vec<H256>> {
 		None
 	}
+
+	fn storage_range(&self, _id: BlockId, account: &Address, start: Option<&H256>, limit: u64) -> Option<(Vec<(H256, H256)>, Option<H256>)> {
+		let mut entries: Vec<_> = self.storage.read().iter()
+			.filter(|((address, _), _)| address == account)
+			.map(|((_, key), value)| (keccak(key), *key, *value))
+			.filter(|(hash, _, _)| start.map_or(true, |start| *hash >= keccak(start)))
+			.collect();
+		entries.sort();
+		let next = entries.get(limit as usize).map(|(_, key, _)| *key);
+		Some((entries.into_iter().take(limit as usize).map(|(_, key, value)| (key, value)).collect(), next))
+	}
+
+	fn pin_state(&self, id: BlockId) -> Option<H256> {
+		let hash = self.block_hash(id)?;
+		self.pinned_states.write().insert(hash);
+		Some(hash)
+	}
+
+	fn unpin_state(&self, hash: &H256) -> bool {
+		self.pinned_states.write().remove(hash)
+	}
+
+	fn consensus_faults(&self) -> Vec<ConsensusFault> {
+		self.consensus_faults.read().clone()
+	}
+
 	fn transaction(&self, _id: TransactionId) -> Option<LocalizedTransaction> {
 		None	// Simple default.
 	}
@@ -888,10 +956,18 @@ impl BlockChainClient for TestBlockChainClient {
 		self.traces.read().clone()
 	}
 
+	fn block_traces_page(&self, _trace: BlockId, offset: usize, limit: usize) -> Option<Vec<LocalizedTrace>> {
+		self.traces.read().clone().map(|traces| traces.into_iter().skip(offset).take(limit).collect())
+	}
+
 	fn transactions_to_propagate(&self) -> Vec<Arc<VerifiedTransaction>> {
 		self.miner.ready_transactions(self, 4096, miner::PendingOrdering::Priority)
 	}
 
+	fn pooled_transaction(&self, hash: &H256) -> Option<Arc<VerifiedTransaction>> {
+		self.miner.transaction(hash)
+	}
+
 	fn signing_chain_id(&self) -> Option<u64> { None }
 
 	fn mode(&self) -> Mode { Mode::Active }
@@ -18,13 +18,15 @@ use std::str::{FromStr, from_utf8};
 use std::sync::Arc;
 
 use account_state::state::StateInfo;
-use ethereum_types::{U256, Address};
+use ethereum_types::{H256, U256, Address};
 use parity_crypto::publickey::KeyPair;
 use hash::keccak;
 use io::IoChannel;
 use tempdir::TempDir;
 use types::{
+	chain_notify::DeepReorg,
 	data_format::DataFormat,
+	header::Header,
 	ids::BlockId,
 	transaction::{PendingTransaction, Transaction, Action, Condition},
 	filter::Filter,
@@ -36,7 +38,7 @@ use types::{
 use client::{Client, ClientConfig, PrepareOpenBlock, ImportSealedBlock};
 use client_traits::{
 	BlockInfo, BlockChainClient, BlockChainReset, ChainInfo,
-	ImportExportBlocks, Tick, ImportBlock
+	ImportExportBlocks, Tick, ImportBlock, ReorgGuard
 };
 use spec;
 use stats;
@@ -46,7 +48,7 @@ use account_state::{State, CleanupMode, backend};
 use test_helpers::{
 	self,
 	generate_dummy_client, push_blocks_to_client, get_test_client_with_blocks, get_good_dummy_block_seq,
-	generate_dummy_client_with_data, get_good_dummy_block, get_bad_state_dummy_block
+	generate_dummy_client_with_data, get_good_dummy_block, get_bad_state_dummy_block, create_test_block
 };
 use rustc_hex::ToHex;
 use registrar::RegistrarClient;
@@ -299,6 +301,76 @@ fn change_history_size() {
 	assert_eq!(client.state().balance(&address).unwrap(), 100.into());
 }
 
+#[test]
+fn does_not_prune_pinned_state() {
+	let test_spec = spec::new_null();
+	let mut config = ClientConfig::default();
+	config.history = 2;
+	config.history_mem = 0;
+	let client = Client::new(
+		config,
+		&test_spec,
+		test_helpers::new_db(),
+		Arc::new(Miner::new_for_tests(&test_spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let address = Address::random();
+	let import_block = || {
+		let mut b = client.prepare_open_block(Address::zero(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+		b.block_mut().state_mut().add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
+		b.block_mut().state_mut().commit().unwrap();
+		let b = b.close_and_lock().unwrap().seal(&*test_spec.engine, vec![]).unwrap();
+		client.import_sealed_block(b).unwrap();
+	};
+
+	for _ in 0..2 {
+		import_block();
+	}
+	let pinned = client.pin_state(BlockId::Number(2)).unwrap();
+	for _ in 0..10 {
+		import_block();
+	}
+	let state = client.state_at(BlockId::Hash(pinned)).expect("pinned state is not pruned");
+	assert_eq!(state.balance(&address).unwrap(), 10.into());
+
+	// pruning resumes once the state is released
+	assert!(client.unpin_state(&pinned));
+	import_block();
+	assert!(client.state_at(BlockId::Hash(pinned)).is_none());
+}
+
+#[test]
+fn prunes_state_up_to_finalized_block() {
+	let test_spec = spec::new_test_with_finality();
+	let mut config = ClientConfig::default();
+	config.history = 100;
+	config.pruning_finalized = true;
+	let client = Client::new(
+		config,
+		&test_spec,
+		test_helpers::new_db(),
+		Arc::new(Miner::new_for_tests(&test_spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let address = Address::random();
+	for _ in 0..10 {
+		let mut b = client.prepare_open_block(Address::zero(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+		b.block_mut().state_mut().add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
+		b.block_mut().state_mut().commit().unwrap();
+		let b = b.close_and_lock().unwrap().seal(&*test_spec.engine, vec![]).unwrap();
+		client.import_sealed_block(b).unwrap();
+	}
+
+	// every block finalizes its parent, the history of 100 blocks is ignored below it
+	assert_eq!(client.finalized_block_number(), 9);
+	assert_eq!(client.pruning_info().earliest_state, 9);
+	assert!(client.state_at(BlockId::Number(8)).is_none());
+	let state = client.state_at(BlockId::Number(9)).expect("finalized state is not pruned");
+	assert_eq!(state.balance(&address).unwrap(), 45.into());
+}
+
 #[test]
 fn does_not_propagate_delayed_transactions() {
 	let key = KeyPair::from_secret(keccak("test").into()).unwrap();
@@ -373,6 +445,46 @@ fn transaction_proof() {
 	assert_eq!(state.balance(&address).unwrap(), 95.into());
 }
 
+#[test]
+fn replays_block_with_access_log() {
+	use trace::StateAccess;
+
+	let client = generate_dummy_client(0);
+	let test_spec = spec::new_test();
+	let key = KeyPair::from_secret(keccak("test").into()).unwrap();
+	let contract = Address::from_low_u64_be(0xc0de);
+
+	// PUSH1 5 SLOAD PUSH1 7 SSTORE STOP: copies slot 5 into slot 7
+	let mut b = client.prepare_open_block(Address::zero(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+	b.block_mut().state_mut().init_code(&contract, vec![0x60, 0x05, 0x54, 0x60, 0x07, 0x55, 0x00]).unwrap();
+	b.block_mut().state_mut().set_storage(&contract, H256::from_low_u64_be(5), H256::from_low_u64_be(9)).unwrap();
+	b.block_mut().state_mut().commit().unwrap();
+	let b = b.close_and_lock().unwrap().seal(&*test_spec.engine, vec![]).unwrap();
+	client.import_sealed_block(b).unwrap();
+
+	let transaction = Transaction {
+		nonce: 0.into(),
+		gas_price: 0.into(),
+		gas: 100_000.into(),
+		action: Action::Call(contract),
+		value: 0.into(),
+		data: Vec::new(),
+	}.sign(key.secret(), None);
+	let hash = transaction.hash();
+
+	let mut b = client.prepare_open_block(Address::zero(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+	b.push_transaction(transaction, None).unwrap();
+	let b = b.close_and_lock().unwrap().seal(&*test_spec.engine, vec![]).unwrap();
+	client.import_sealed_block(b).unwrap();
+
+	assert_eq!(client.replay_block_with_access_log(BlockId::Number(2)).unwrap(), vec![(hash, vec![
+		StateAccess::Account(key.address()),
+		StateAccess::Account(contract),
+		StateAccess::StorageRead { address: contract, key: H256::from_low_u64_be(5), value: H256::from_low_u64_be(9) },
+		StateAccess::StorageWrite { address: contract, key: H256::from_low_u64_be(7), value: H256::from_low_u64_be(9) },
+	])]);
+}
+
 #[test]
 fn reset_blockchain() {
 	let client = get_test_client_with_blocks(get_good_dummy_block_seq(19));
@@ -468,3 +580,60 @@ fn import_export_binary() {
 	assert!(client.block_header(BlockId::Number(17)).is_some());
 	assert!(client.block_header(BlockId::Number(16)).is_some());
 }
+
+#[test]
+fn halts_import_on_deep_reorg() {
+	let spec = spec::new_test();
+	let genesis = spec.genesis_header();
+	let mut config = ClientConfig::default();
+	config.max_reorg_depth = Some(1);
+	let client = Client::new(
+		config,
+		&spec,
+		test_helpers::new_db(),
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	for block in get_good_dummy_block_seq(3) {
+		client.import_block(Unverified::from_rlp(block).unwrap()).unwrap();
+	}
+	client.flush_queue();
+	let best_hash = client.chain_info().best_block_hash;
+	assert_eq!(client.chain_info().best_block_number, 4);
+
+	// heavier fork from genesis
+	let mut parent = genesis.hash();
+	let mut fork = Vec::new();
+	for number in 1..3u64 {
+		let mut header = Header::new();
+		header.set_gas_limit(*genesis.gas_limit());
+		header.set_difficulty(U256::from(100) * U256([0, 1, 0, 0]));
+		header.set_timestamp(number * 10 + 5);
+		header.set_number(number);
+		header.set_parent_hash(parent);
+		header.set_state_root(*genesis.state_root());
+		parent = header.hash();
+		fork.push((parent, create_test_block(&header)));
+	}
+	for &(_, ref block) in &fork {
+		client.import_block(Unverified::from_rlp(block.clone()).unwrap()).unwrap();
+	}
+	client.flush_queue();
+
+	assert_eq!(client.chain_info().best_block_hash, best_hash);
+	assert_eq!(client.halted_reorg(), Some(DeepReorg {
+		hash: fork[0].0,
+		number: 1,
+		best: best_hash,
+		ancestor: genesis.hash(),
+		depth: 4,
+	}));
+
+	assert!(client.acknowledge_reorg(true));
+	client.import_verified_blocks();
+
+	assert_eq!(client.chain_info().best_block_hash, fork[1].0);
+	assert_eq!(client.halted_reorg(), None);
+	assert!(!client.acknowledge_reorg(false));
+}
@@ -208,4 +208,6 @@ fn can_trace_block_and_uncle_reward() {
 	// Test1. Check block filter
 	let traces = client.block_traces(BlockId::Number(3));
 	assert_eq!(traces.unwrap().len(), 3);
+	let traces = client.block_traces_page(BlockId::Number(3), 1, 5);
+	assert_eq!(traces.unwrap().len(), 2);
 }
@@ -27,10 +27,16 @@ use crate::sync_io::NetSyncIo;
 use crate::light_sync::{self, SyncInfo};
 use crate::private_tx::PrivateTxHandler;
 use crate::chain::{
-	sync_packet::SyncPacket::{PrivateTransactionPacket, SignedPrivateTransactionPacket},
+	sync_packet::{
+		PacketInfo,
+		SyncPacket::{
+			self, PrivateTransactionPacket, SignedPrivateTransactionPacket, TransactionsPacket,
+			NewPooledTransactionHashesPacket, PooledTransactionsPacket,
+		},
+	},
 	ChainSyncApi, SyncState, SyncStatus as EthSyncStatus, ETH_PROTOCOL_VERSION_62,
-	ETH_PROTOCOL_VERSION_63, PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2,
-	PAR_PROTOCOL_VERSION_3, PAR_PROTOCOL_VERSION_4,
+	ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_66, PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2,
+	PAR_PROTOCOL_VERSION_3, PAR_PROTOCOL_VERSION_4, PAR_PROTOCOL_VERSION_5,
 };
 
 use bytes::Bytes;
@@ -52,9 +58,9 @@ use log::{trace, warn};
 use macros::hash_map;
 use network::{
 	client_version::ClientVersion,
-	NetworkProtocolHandler, NetworkContext, PeerId, ProtocolId,
+	NetworkProtocolHandler, NetworkContext, PacketTraffic, PeerId, ProtocolId,
 	NetworkConfiguration as BasicNetworkConfiguration, NonReservedPeerMode, Error,
-	ConnectionFilter, IpFilter, NatType
+	ConnectionFilter, DialPolicy, BandwidthPolicy, IpFilter, NatType
 };
 use snapshot::SnapshotService;
 use parking_lot::{RwLock, Mutex};
@@ -110,8 +116,17 @@ impl WarpSync {
 	}
 }
 
+/// Snapshot warp sync is pinned to by the operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, MallocSizeOf)]
+pub struct WarpPin {
+	/// Hash of the snapshot manifest to restore.
+	pub manifest_hash: H256,
+	/// Node id of the only peer the snapshot is downloaded from, any peer if `None`.
+	pub node: Option<H512>,
+}
+
 /// Sync configuration
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct SyncConfig {
 	/// Max blocks to download ahead
 	pub max_download_ahead_blocks: usize,
@@ -125,6 +140,8 @@ pub struct SyncConfig {
 	pub light_subprotocol_name: [u8; 3],
 	/// Fork block to check
 	pub fork_block: Option<(BlockNumber, H256)>,
+	/// Blocks at which the chain rules change, used to compute the EIP-2124 fork id.
+	pub fork_blocks: Vec<BlockNumber>,
 	/// Enable snapshot sync
 	pub warp_sync: WarpSync,
 	/// Enable light client server.
@@ -140,6 +157,7 @@ impl Default for SyncConfig {
 			subprotocol_name: ETH_PROTOCOL,
 			light_subprotocol_name: LIGHT_PROTOCOL,
 			fork_block: None,
+			fork_blocks: Vec::new(),
 			warp_sync: WarpSync::Disabled,
 			serve_light: false,
 		}
@@ -175,6 +193,12 @@ pub trait SyncProvider: Send + Sync {
 pub struct TransactionStats {
 	/// Block number where this TX was first seen.
 	pub first_seen: u64,
+	/// Unix timestamp (in seconds) of the first propagation.
+	pub first_propagated_at: u64,
+	/// Unix timestamp (in seconds) of the most recent propagation.
+	pub last_propagated_at: u64,
+	/// Number of times this TX was re-sent to a peer that already received it.
+	pub rebroadcasts: usize,
 	/// Peers it was propagated to.
 	pub propagated_to: BTreeMap<H512, usize>,
 }
@@ -207,6 +231,10 @@ pub struct EthProtocolInfo {
 	pub head: H256,
 	/// Peer total difficulty if known
 	pub difficulty: Option<U256>,
+	/// Hash of the manifest of the best snapshot the peer advertises
+	pub snapshot_hash: Option<H256>,
+	/// Block number of the best snapshot the peer advertises
+	pub snapshot_number: Option<BlockNumber>,
 }
 
 /// PIP protocol info.
@@ -351,7 +379,7 @@ impl EthSync {
 
 		let (priority_tasks_tx, priority_tasks_rx) = mpsc::channel();
 		let sync = ChainSyncApi::new(
-			params.config,
+			params.config.clone(),
 			&*params.chain,
 			params.private_tx_handler.as_ref().cloned(),
 			priority_tasks_rx,
@@ -606,10 +634,10 @@ impl ChainNotify for EthSync {
 			_ => {},
 		}
 
-		self.network.register_protocol(self.eth_handler.clone(), self.subprotocol_name, &[ETH_PROTOCOL_VERSION_62, ETH_PROTOCOL_VERSION_63])
+		self.network.register_protocol(self.eth_handler.clone(), self.subprotocol_name, &[ETH_PROTOCOL_VERSION_62, ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_66])
 			.unwrap_or_else(|e| warn!("Error registering ethereum protocol: {:?}", e));
 		// register the warp sync subprotocol
-		self.network.register_protocol(self.eth_handler.clone(), WARP_SYNC_PROTOCOL_ID, &[PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2, PAR_PROTOCOL_VERSION_3, PAR_PROTOCOL_VERSION_4])
+		self.network.register_protocol(self.eth_handler.clone(), WARP_SYNC_PROTOCOL_ID, &[PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2, PAR_PROTOCOL_VERSION_3, PAR_PROTOCOL_VERSION_4, PAR_PROTOCOL_VERSION_5])
 			.unwrap_or_else(|e| warn!("Error registering snapshot sync protocol: {:?}", e));
 
 		// register the light protocol.
@@ -678,6 +706,10 @@ pub trait ManageNetwork: Send + Sync {
 	fn num_peers_range(&self) -> RangeInclusive<u32>;
 	/// Get network context for protocol.
 	fn with_proto_context(&self, proto: ProtocolId, f: &mut dyn FnMut(&dyn NetworkContext));
+	/// Returns traffic statistics by subprotocol and message type.
+	fn traffic_stats(&self) -> Vec<PacketTraffic>;
+	/// Restrict warp sync to the given snapshot (and peer), or lift the restriction with `None`.
+	fn set_warp_pin(&self, pin: Option<WarpPin>) -> Result<(), String>;
 }
 
 impl ManageNetwork for EthSync {
@@ -725,6 +757,15 @@ impl ManageNetwork for EthSync {
 	fn with_proto_context(&self, proto: ProtocolId, f: &mut dyn FnMut(&dyn NetworkContext)) {
 		self.network.with_context_eval(proto, f);
 	}
+
+	fn traffic_stats(&self) -> Vec<PacketTraffic> {
+		self.network.traffic_stats()
+	}
+
+	fn set_warp_pin(&self, pin: Option<WarpPin>) -> Result<(), String> {
+		self.eth_handler.sync.write().set_warp_pin(pin);
+		Ok(())
+	}
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -766,6 +807,12 @@ pub struct NetworkConfiguration {
 	pub ip_filter: IpFilter,
 	/// Client version string
 	pub client_version: String,
+	/// Maximum age of a peer session before it is rotated
+	pub max_session_age: Option<Duration>,
+	/// Outbound dialing policy
+	pub dial_policy: DialPolicy,
+	/// Outbound bandwidth policy
+	pub bandwidth_policy: BandwidthPolicy,
 }
 
 impl NetworkConfiguration {
@@ -800,6 +847,9 @@ impl NetworkConfiguration {
 			ip_filter: self.ip_filter,
 			non_reserved_mode: if self.allow_non_reserved { NonReservedPeerMode::Accept } else { NonReservedPeerMode::Deny },
 			client_version: self.client_version,
+			max_session_age: self.max_session_age,
+			dial_policy: self.dial_policy,
+			bandwidth_policy: self.bandwidth_policy,
 		})
 	}
 }
@@ -825,10 +875,34 @@ impl From<BasicNetworkConfiguration> for NetworkConfiguration {
 			ip_filter: other.ip_filter,
 			allow_non_reserved: match other.non_reserved_mode { NonReservedPeerMode::Accept => true, _ => false } ,
 			client_version: other.client_version,
+			max_session_age: other.max_session_age,
+			dial_policy: other.dial_policy,
+			bandwidth_policy: other.bandwidth_policy,
 		}
 	}
 }
 
+/// Outbound bandwidth policy sharing `max_bytes_per_second` between block sync, transaction
+/// propagation and light client serving according to their weights.
+pub fn bandwidth_policy(max_bytes_per_second: u64, sync_weight: u32, transactions_weight: u32, light_weight: u32) -> BandwidthPolicy {
+	let transaction_packets: [SyncPacket; 5] = [
+		TransactionsPacket,
+		NewPooledTransactionHashesPacket,
+		PooledTransactionsPacket,
+		PrivateTransactionPacket,
+		SignedPrivateTransactionPacket,
+	];
+
+	BandwidthPolicy {
+		max_bytes_per_second,
+		sync_weight,
+		transactions_weight,
+		light_weight,
+		transaction_packets: transaction_packets.iter().map(|p| (p.protocol(), p.id())).collect(),
+		light_protocols: vec![LIGHT_PROTOCOL],
+	}
+}
+
 /// Configuration for IPC service.
 #[derive(Debug, Clone)]
 pub struct ServiceConfiguration {
@@ -1021,6 +1095,14 @@ impl ManageNetwork for LightSync {
 	fn with_proto_context(&self, proto: ProtocolId, f: &mut dyn FnMut(&dyn NetworkContext)) {
 		self.network.with_context_eval(proto, f);
 	}
+
+	fn traffic_stats(&self) -> Vec<PacketTraffic> {
+		self.network.traffic_stats()
+	}
+
+	fn set_warp_pin(&self, _pin: Option<WarpPin>) -> Result<(), String> {
+		Err("Warp sync is not available for the light client".into())
+	}
 }
 
 impl LightSyncProvider for LightSync {
@@ -20,6 +20,7 @@
 
 use std::collections::{HashSet, VecDeque};
 use std::cmp;
+use std::time::{Duration, Instant};
 
 use crate::{
 	blocks::{BlockCollection, SyncBody, SyncHeader},
@@ -47,6 +48,11 @@ const SUBCHAIN_SIZE: u64 = 256;
 const MAX_ROUND_PARENTS: usize = 16;
 const MAX_PARALLEL_SUBCHAIN_DOWNLOAD: usize = 5;
 const MAX_USELESS_HEADERS_PER_ROUND: usize = 3;
+/// Number of peers that must agree on subchain heads before the round starts filling them in.
+const SKELETON_QUORUM: usize = 3;
+/// Time after the first subchain heads response after which a majority of the responses received
+/// so far is enough, so that peers which never answer can't stall the round.
+const SKELETON_QUORUM_TIMEOUT: Duration = Duration::from_secs(10);
 
 // logging macros prepend BlockSet context for log filtering
 macro_rules! trace_sync {
@@ -149,6 +155,14 @@ pub struct BlockDownloader {
 	retract_step: u64,
 	/// consecutive useless headers this round
 	useless_headers_count: usize,
+	/// Number of matching subchain heads responses required to start downloading.
+	skeleton_quorum: usize,
+	/// Subchain heads received this round, one entry per response.
+	skeleton_votes: Vec<Vec<H256>>,
+	/// Time the first subchain heads response was received this round.
+	skeleton_started: Option<Instant>,
+	/// Peers asked for subchain heads this round.
+	skeleton_peers: HashSet<PeerId>,
 }
 
 impl BlockDownloader {
@@ -174,6 +188,10 @@ impl BlockDownloader {
 			target_hash: None,
 			retract_step: 1,
 			useless_headers_count: 0,
+			skeleton_quorum: 1,
+			skeleton_votes: Vec::new(),
+			skeleton_started: None,
+			skeleton_peers: HashSet::new(),
 		}
 	}
 
@@ -181,9 +199,16 @@ impl BlockDownloader {
 	pub fn reset(&mut self) {
 		self.blocks.clear();
 		self.useless_headers_count = 0;
+		self.clear_skeleton();
 		self.state = State::Idle;
 	}
 
+	/// Set the number of peers available for syncing.
+	/// Subchain heads are only trusted once `min(SKELETON_QUORUM, peers)` of them returned the same ones.
+	pub fn set_available_peers(&mut self, peers: usize) {
+		self.skeleton_quorum = cmp::max(1, cmp::min(SKELETON_QUORUM, peers));
+	}
+
 	/// Mark a block as known in the chain
 	pub fn mark_as_known(&mut self, hash: &H256, number: BlockNumber) {
 		if number >= self.last_imported_block + 1 {
@@ -319,10 +344,14 @@ impl BlockDownloader {
 		match self.state {
 			State::ChainHead => {
 				if !headers.is_empty() {
-					trace_sync!(self, "Received {} subchain heads, proceeding to download", headers.len());
-					self.blocks.reset_to(hashes);
-					self.state = State::Blocks;
-					return Ok(DownloadAction::Reset);
+					trace_sync!(self, "Received {} subchain heads ({} of {} responses)", headers.len(), self.skeleton_votes.len() + 1, self.skeleton_quorum);
+					self.skeleton_votes.push(hashes);
+					self.skeleton_started.get_or_insert_with(Instant::now);
+					if let Some(heads) = self.confirmed_skeleton() {
+						trace_sync!(self, "{} subchain heads confirmed, proceeding to download", heads.len());
+						self.start_filling(heads);
+						return Ok(DownloadAction::Reset);
+					}
 				} else {
 					trace_sync!(self, "No useful subchain heads received, expected hash {:?}", expected_hash);
 					let best = io.chain().chain_info().best_block_number;
@@ -422,8 +451,55 @@ impl BlockDownloader {
 		Ok(())
 	}
 
+	/// Returns the longest sequence of subchain heads returned by at least `skeleton_quorum` peers,
+	/// or by a majority of the responses once `SKELETON_QUORUM_TIMEOUT` has passed.
+	/// Headers between the heads are then downloaded from any peer and must link to them.
+	fn confirmed_skeleton(&self) -> Option<Vec<H256>> {
+		let quorum = match self.skeleton_started {
+			Some(started) if started.elapsed() >= SKELETON_QUORUM_TIMEOUT =>
+				cmp::min(self.skeleton_quorum, self.skeleton_votes.len() / 2 + 1),
+			_ => self.skeleton_quorum,
+		};
+		if self.skeleton_votes.len() < quorum {
+			return None;
+		}
+		let mut supporters: Vec<&Vec<H256>> = self.skeleton_votes.iter().collect();
+		let mut heads = Vec::new();
+		loop {
+			let index = heads.len();
+			let best = supporters.iter()
+				.filter_map(|vote| vote.get(index))
+				.max_by_key(|hash| supporters.iter().filter(|vote| vote.get(index) == Some(*hash)).count())
+				.cloned();
+			let hash = match best {
+				Some(hash) => hash,
+				None => break,
+			};
+			let agreeing: Vec<_> = supporters.iter().filter(|vote| vote.get(index) == Some(&hash)).cloned().collect();
+			if agreeing.len() < quorum {
+				break;
+			}
+			heads.push(hash);
+			supporters = agreeing;
+		}
+		if heads.is_empty() { None } else { Some(heads) }
+	}
+
+	fn start_filling(&mut self, heads: Vec<H256>) {
+		self.clear_skeleton();
+		self.blocks.reset_to(heads);
+		self.state = State::Blocks;
+	}
+
+	fn clear_skeleton(&mut self) {
+		self.skeleton_votes.clear();
+		self.skeleton_started = None;
+		self.skeleton_peers.clear();
+	}
+
 	fn start_sync_round(&mut self, io: &mut dyn SyncIo) {
 		self.state = State::ChainHead;
+		self.clear_skeleton();
 		trace_sync!(self, "Starting round (last imported count = {:?}, last started = {}, block = {:?}", self.imported_this_round, self.last_round_start, self.last_imported_block);
 		// Check if need to retract to find the common block. The problem is that the peers still return headers by hash even
 		// from the non-canonical part of the tree. So we also retract if nothing has been imported last round.
@@ -479,7 +555,17 @@ impl BlockDownloader {
 				}
 			},
 			State::ChainHead => {
+				if self.skeleton_peers.contains(&peer_id) {
+					// The quorum might have been lowered since the last response, e.g. if peers disconnected.
+					if let Some(heads) = self.confirmed_skeleton() {
+						trace_sync!(self, "{} subchain heads confirmed, proceeding to download", heads.len());
+						self.start_filling(heads);
+						return self.request_blocks(peer_id, io, num_active_peers);
+					}
+					return None;
+				}
 				if num_active_peers < MAX_PARALLEL_SUBCHAIN_DOWNLOAD {
+					self.skeleton_peers.insert(peer_id);
 					// Request subchain headers
 					trace_sync!(self, "Starting sync with better chain");
 					// Request MAX_HEADERS_TO_REQUEST - 2 headers apart so that
@@ -536,9 +622,20 @@ impl BlockDownloader {
 	pub fn collect_blocks(&mut self, io: &mut dyn SyncIo, allow_out_of_order: bool) -> DownloadAction {
 		let mut download_action = DownloadAction::None;
 		let mut imported = HashSet::new();
-		let blocks = self.blocks.drain();
-		let count = blocks.len();
-		for block_and_receipts in blocks {
+		let mut count = 0;
+		loop {
+			// Downloaded blocks stay in the collection until the verification queue has room for them,
+			// ancient blocks with receipts bypass the queue.
+			if !self.download_receipts && io.chain().queue_info().is_full() {
+				trace_sync!(self, "Block import queue full, keeping downloaded blocks");
+				break;
+			}
+			let block_and_receipts = match self.blocks.pop_ready() {
+				Some(block_and_receipts) => block_and_receipts,
+				None => break,
+			};
+			count += 1;
+
 			let block = block_and_receipts.block;
 			let receipts = block_and_receipts.receipts;
 
@@ -592,8 +689,9 @@ impl BlockDownloader {
 					break;
 				},
 				Err(EthcoreError::FullQueue(limit)) => {
-					debug_sync!(self, "Block import queue full ({}), restarting sync", limit);
-					download_action = DownloadAction::Reset;
+					// The queue is checked before every import, so it only fills up here when blocks are
+					// imported from elsewhere at the same time. Keep the remaining blocks and wait for room.
+					debug_sync!(self, "Block import queue full ({}), pausing import of {:?}", limit, h);
 					break;
 				},
 				Err(e) => {
@@ -642,8 +740,9 @@ fn all_expected<A, B, F>(values: &[A], expected_values: &[B], is_expected: F) ->
 #[cfg(test)]
 mod tests {
 	use super::{
-		BlockSet, BlockDownloader, BlockDownloaderImportError, DownloadAction, SyncIo, H256,
-		MAX_HEADERS_TO_REQUEST, MAX_USELESS_HEADERS_PER_ROUND, SUBCHAIN_SIZE, State, Rlp, VecDeque
+		BlockSet, BlockDownloader, BlockDownloaderImportError, DownloadAction, SyncIo, H256, Instant,
+		MAX_HEADERS_TO_REQUEST, MAX_USELESS_HEADERS_PER_ROUND, SKELETON_QUORUM_TIMEOUT, SUBCHAIN_SIZE,
+		State, Rlp, VecDeque
 	};
 
 	use crate::tests::{helpers::TestIo, snapshot::TestSnapshotService};
@@ -761,6 +860,84 @@ mod tests {
 		};
 	}
 
+	#[test]
+	fn import_headers_in_chain_head_state_waits_for_quorum() {
+		env_logger::try_init().ok();
+
+		let spec = spec::new_test();
+		let genesis_hash = spec.genesis_header().hash();
+
+		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &genesis_hash, 0);
+		downloader.set_available_peers(5);
+		downloader.state = State::ChainHead;
+
+		let mut chain = TestBlockChainClient::new();
+		let snapshot_service = TestSnapshotService::new();
+		let queue = RwLock::new(VecDeque::new());
+		let mut io = TestIo::new(&mut chain, &snapshot_service, &queue, None, None);
+
+		let head_127 = dummy_header(127, H256::random());
+		let head_254 = dummy_header(254, H256::random());
+		let honest = [spec.genesis_header(), head_127.clone(), head_254.clone()];
+		let dishonest = [spec.genesis_header(), head_127.clone(), dummy_header(254, H256::random())];
+
+		match import_headers(&honest, &mut downloader, &mut io) {
+			Ok(DownloadAction::None) => assert_eq!(downloader.state, State::ChainHead),
+			_ => panic!("expected to wait for more subchain heads"),
+		};
+		match import_headers(&dishonest, &mut downloader, &mut io) {
+			Ok(DownloadAction::None) => assert_eq!(downloader.state, State::ChainHead),
+			_ => panic!("expected to wait for more subchain heads"),
+		};
+		match import_headers(&honest, &mut downloader, &mut io) {
+			Ok(DownloadAction::Reset) => assert_eq!(downloader.state, State::Blocks),
+			_ => panic!("expected transition to Blocks state"),
+		};
+
+		// Only heads returned by the quorum are downloaded.
+		assert_eq!(downloader.blocks.heads_len(), 2);
+		assert!(downloader.skeleton_votes.is_empty());
+	}
+
+	#[test]
+	fn chain_head_state_falls_back_to_received_heads_after_timeout() {
+		env_logger::try_init().ok();
+
+		let spec = spec::new_test();
+		let genesis_hash = spec.genesis_header().hash();
+
+		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &genesis_hash, 0);
+		downloader.set_available_peers(5);
+		downloader.state = State::ChainHead;
+
+		let mut chain = TestBlockChainClient::new();
+		let snapshot_service = TestSnapshotService::new();
+		let queue = RwLock::new(VecDeque::new());
+		let mut io = TestIo::new(&mut chain, &snapshot_service, &queue, None, None);
+
+		let head_127 = dummy_header(127, H256::random());
+		let honest = [spec.genesis_header(), head_127.clone(), dummy_header(254, H256::random())];
+		let dishonest = [spec.genesis_header(), head_127.clone(), dummy_header(254, H256::random())];
+		let peer = 1;
+		downloader.skeleton_peers.insert(peer);
+
+		match import_headers(&honest, &mut downloader, &mut io) {
+			Ok(DownloadAction::None) => assert_eq!(downloader.state, State::ChainHead),
+			_ => panic!("expected to wait for more subchain heads"),
+		};
+		match import_headers(&dishonest, &mut downloader, &mut io) {
+			Ok(DownloadAction::None) => assert_eq!(downloader.state, State::ChainHead),
+			_ => panic!("expected to wait for more subchain heads"),
+		};
+		assert!(downloader.request_blocks(peer, &mut io, 0).is_none());
+
+		// The remaining peers never answer, so the heads both responses agree on are used.
+		downloader.skeleton_started = Some(Instant::now() - SKELETON_QUORUM_TIMEOUT);
+		downloader.request_blocks(peer, &mut io, 0);
+		assert_eq!(downloader.state, State::Blocks);
+		assert_eq!(downloader.blocks.heads_len(), 1);
+	}
+
 	#[test]
 	fn import_headers_in_blocks_state() {
 		env_logger::try_init().ok();
@@ -311,45 +311,29 @@ impl BlockCollection {
 		}
 	}
 
-	/// Get a valid chain of blocks ordered in ascending order and ready for importing into blockchain.
-	pub fn drain(&mut self) -> Vec<BlockAndReceipts> {
-		if self.blocks.is_empty() || self.head.is_none() {
-			return Vec::new();
+	/// Take the next block of the valid chain if it is ready for importing into blockchain.
+	pub fn pop_ready(&mut self) -> Option<BlockAndReceipts> {
+		let next = *self.parents.get(&self.head?)?;
+		let is_ready = self.blocks.get(&next)
+			.map_or(false, |block| block.body.is_some() && (!self.need_receipts || block.receipts.is_some()));
+		if !is_ready {
+			return None;
 		}
 
-		let mut drained = Vec::new();
-		let mut hashes = Vec::new();
-		{
-			let mut blocks = Vec::new();
-			let mut head = self.head;
-			while let Some(h) = head {
-				head = self.parents.get(&h).cloned();
-				if let Some(head) = head {
-					match self.blocks.remove(&head) {
-						Some(block) => {
-							if block.body.is_some() && (!self.need_receipts || block.receipts.is_some()) {
-								blocks.push(block);
-								hashes.push(head);
-								self.head = Some(head);
-							} else {
-								self.blocks.insert(head, block);
-								break;
-							}
-						},
-						_ => {
-							break;
-						},
-					}
-				}
-			}
+		let block = self.blocks.remove(&next).expect("checked above; qed");
+		self.head = Some(next);
+		Some(BlockAndReceipts {
+			block: unverified_from_sync(block.header, block.body),
+			receipts: block.receipts,
+		})
+	}
 
-			for block in blocks.into_iter() {
-				let unverified = unverified_from_sync(block.header, block.body);
-				drained.push(BlockAndReceipts {
-					block: unverified,
-					receipts: block.receipts.clone(),
-				});
-			}
+	/// Get a valid chain of blocks ordered in ascending order and ready for importing into blockchain.
+	#[cfg(test)]
+	pub fn drain(&mut self) -> Vec<BlockAndReceipts> {
+		let mut drained = Vec::new();
+		while let Some(block) = self.pop_ready() {
+			drained.push(block);
 		}
 
 		trace!(target: "sync", "Drained {} blocks, new head :{:?}", drained.len(), self.head);
@@ -627,6 +611,29 @@ mod test {
 		assert!(bc.is_empty());
 	}
 
+	#[test]
+	fn pop_ready_keeps_remaining_blocks() {
+		let mut bc = BlockCollection::new(false);
+		let client = TestBlockChainClient::new();
+		let nblocks = 10;
+		client.add_blocks(nblocks, EachBlockWith::Nothing);
+		let blocks: Vec<_> = (0..nblocks)
+			.map(|i| (&client as &dyn BlockChainClient).block(BlockId::Number(i as BlockNumber)).unwrap().into_inner())
+			.collect();
+		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec()).unwrap()).collect();
+		let hashes: Vec<_> = headers.iter().map(|h| h.header.hash()).collect();
+		bc.reset_to(vec![hashes[0]]);
+		bc.insert_headers(headers);
+
+		let first = bc.pop_ready().unwrap();
+		assert_eq!(first.block, Unverified::from_rlp(blocks[0].to_vec()).unwrap());
+		assert!(!bc.contains(&hashes[0]));
+		assert!(bc.contains(&hashes[1]));
+
+		assert_eq!(bc.drain().len(), nblocks - 1);
+		assert!(bc.pop_ready().is_none());
+	}
+
 	#[test]
 	fn insert_headers_with_gap() {
 		let mut bc = BlockCollection::new(false);
@@ -0,0 +1,201 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EIP-2124 fork identifier, exchanged in the Status message from eth/64 on so that peers on
+//! incompatible chains are dropped during the handshake.
+
+use common_types::BlockNumber;
+use ethereum_types::H256;
+use parity_util_mem::MallocSizeOf;
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+
+/// Fork identifier: checksum of the genesis hash and the passed forks, and the next fork.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForkId {
+	/// CRC32 of the genesis hash followed by the numbers of all passed forks.
+	pub hash: u32,
+	/// Number of the next fork, 0 if none is scheduled.
+	pub next: BlockNumber,
+}
+
+impl Encodable for ForkId {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(2);
+		s.append(&self.hash.to_be_bytes().to_vec());
+		s.append(&self.next);
+	}
+}
+
+impl Decodable for ForkId {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		let hash: Vec<u8> = rlp.val_at(0)?;
+		if hash.len() != 4 {
+			return Err(DecoderError::RlpInvalidLength);
+		}
+		Ok(ForkId {
+			hash: u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]),
+			next: rlp.val_at(1)?,
+		})
+	}
+}
+
+/// Computes our fork id and validates the ones announced by peers.
+#[derive(Debug, MallocSizeOf)]
+pub struct ForkFilter {
+	/// Fork blocks, sorted and without duplicates or genesis forks.
+	forks: Vec<BlockNumber>,
+	/// `sums[i]` is the fork hash once the first `i` forks have passed.
+	sums: Vec<u32>,
+}
+
+impl ForkFilter {
+	/// Create a filter for the chain with the given genesis and fork blocks. Forks at genesis
+	/// are ignored, the rest may be given in any order.
+	pub fn new(genesis_hash: H256, fork_blocks: &[BlockNumber]) -> Self {
+		let mut forks: Vec<_> = fork_blocks.iter().cloned().filter(|number| *number != 0).collect();
+		forks.sort();
+		forks.dedup();
+
+		let mut sums = Vec::with_capacity(forks.len() + 1);
+		let mut sum = crc32(0, genesis_hash.as_bytes());
+		sums.push(sum);
+		for fork in &forks {
+			sum = crc32(sum, &fork.to_be_bytes());
+			sums.push(sum);
+		}
+		ForkFilter { forks, sums }
+	}
+
+	/// Number of forks passed at `head`.
+	fn passed(&self, head: BlockNumber) -> usize {
+		self.forks.iter().take_while(|fork| **fork <= head).count()
+	}
+
+	/// Our fork id with the chain at `head`.
+	pub fn current(&self, head: BlockNumber) -> ForkId {
+		let passed = self.passed(head);
+		ForkId {
+			hash: self.sums[passed],
+			next: self.forks.get(passed).cloned().unwrap_or(0),
+		}
+	}
+
+	/// Whether a peer announcing `remote` may be on our chain, following the EIP-2124 rules.
+	pub fn is_compatible(&self, head: BlockNumber, remote: &ForkId) -> bool {
+		let passed = self.passed(head);
+		match self.sums.iter().position(|sum| *sum == remote.hash) {
+			// same forks passed: the peer must not expect a fork we already passed without it
+			Some(i) if i == passed => remote.next == 0 || head < remote.next,
+			// the peer is behind: its next fork must be the one we passed after its last one
+			Some(i) if i < passed => remote.next == self.forks[i],
+			// the peer is ahead and passed forks we know about, but haven't reached yet
+			Some(_) => true,
+			None => false,
+		}
+	}
+}
+
+/// Updates the IEEE CRC32 checksum `crc` with `data`.
+fn crc32(crc: u32, data: &[u8]) -> u32 {
+	let mut crc = !crc;
+	for byte in data {
+		crc ^= u32::from(*byte);
+		for _ in 0..8 {
+			crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+		}
+	}
+	!crc
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ForkFilter, ForkId};
+	use ethereum_types::H256;
+	use rlp::{decode, encode};
+	use rustc_hex::FromHex;
+
+	const MAINNET_GENESIS: &str = "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3";
+	const MAINNET_FORKS: [u64; 6] = [1_150_000, 1_920_000, 2_463_000, 2_675_000, 4_370_000, 7_280_000];
+
+	fn mainnet() -> ForkFilter {
+		ForkFilter::new(MAINNET_GENESIS.parse().unwrap(), &MAINNET_FORKS)
+	}
+
+	#[test]
+	fn computes_mainnet_fork_ids() {
+		let filter = mainnet();
+		let cases = [
+			(0, 0xfc64ec04, 1_150_000),
+			(1_149_999, 0xfc64ec04, 1_150_000),
+			(1_150_000, 0x97c2c34c, 1_920_000),
+			(1_920_000, 0x91d1f948, 2_463_000),
+			(2_463_000, 0x7a64da13, 2_675_000),
+			(2_675_000, 0x3edd5b10, 4_370_000),
+			(4_370_000, 0xa00bc324, 7_280_000),
+			(7_280_000, 0x668db0af, 0),
+			(10_000_000, 0x668db0af, 0),
+		];
+		for &(head, hash, next) in &cases {
+			assert_eq!(filter.current(head), ForkId { hash, next }, "head {}", head);
+		}
+	}
+
+	#[test]
+	fn ignores_genesis_and_repeated_forks() {
+		let genesis: H256 = MAINNET_GENESIS.parse().unwrap();
+		let mut forks = vec![0, 7_280_000, 1_150_000, 0, 2_463_000, 2_675_000, 1_920_000, 4_370_000, 2_675_000];
+		assert_eq!(ForkFilter::new(genesis, &forks).current(0), mainnet().current(0));
+		forks.push(7_280_000);
+		assert_eq!(ForkFilter::new(genesis, &forks).current(7_280_000), mainnet().current(7_280_000));
+	}
+
+	#[test]
+	fn validates_remote_fork_ids() {
+		let filter = mainnet();
+		// Petersburg node, same fork id
+		assert!(filter.is_compatible(7_987_396, &ForkId { hash: 0x668db0af, next: 0 }));
+		// Petersburg node, remote announces a future fork
+		assert!(filter.is_compatible(7_987_396, &ForkId { hash: 0x668db0af, next: u64::max_value() }));
+		// Byzantium node, remote is not aware of Petersburg yet
+		assert!(filter.is_compatible(7_279_999, &ForkId { hash: 0xa00bc324, next: 0 }));
+		// Byzantium node, remote announces Petersburg
+		assert!(filter.is_compatible(7_279_999, &ForkId { hash: 0xa00bc324, next: 7_280_000 }));
+		// Byzantium node, remote is already on Petersburg
+		assert!(filter.is_compatible(7_279_999, &ForkId { hash: 0x668db0af, next: 0 }));
+		// Petersburg node, remote is stale on Byzantium but announces Petersburg
+		assert!(filter.is_compatible(7_987_396, &ForkId { hash: 0xa00bc324, next: 7_280_000 }));
+		// Petersburg node, remote is stale on Spurious Dragon and announces Byzantium
+		assert!(filter.is_compatible(7_987_396, &ForkId { hash: 0x3edd5b10, next: 4_370_000 }));
+
+		// Petersburg node, remote is stale on Byzantium and doesn't know about Petersburg
+		assert!(!filter.is_compatible(7_987_396, &ForkId { hash: 0xa00bc324, next: 0 }));
+		// Petersburg node, remote announces a fork we already passed without it
+		assert!(!filter.is_compatible(7_987_396, &ForkId { hash: 0x668db0af, next: 7_987_396 }));
+		// Spurious Dragon node, remote announces a different next fork than Byzantium
+		assert!(!filter.is_compatible(4_369_999, &ForkId { hash: 0x3edd5b10, next: 4_370_001 }));
+		// unknown fork hash
+		assert!(!filter.is_compatible(7_987_396, &ForkId { hash: 0xafec6b27, next: 0 }));
+	}
+
+	#[test]
+	fn encodes_fork_id() {
+		let fork_id = ForkId { hash: 0x00de_adbe, next: 0xbaddcafe };
+		let encoded = encode(&fork_id);
+		assert_eq!(encoded, "ca8400deadbe84baddcafe".from_hex::<Vec<u8>>().unwrap());
+		assert_eq!(decode::<ForkId>(&encoded).unwrap(), fork_id);
+		assert_eq!(encode(&ForkId { hash: 0, next: 0 }), "c6840000000080".from_hex::<Vec<u8>>().unwrap());
+	}
+}
@@ -23,23 +23,27 @@ use crate::{
 	api::WARP_SYNC_PROTOCOL_ID,
 	block_sync::{BlockDownloaderImportError as DownloaderImportError, DownloadAction},
 	chain::{
+		fork_id::ForkId,
 		sync_packet::{
 			PacketInfo,
 			SyncPacket::{
 				self, BlockBodiesPacket, BlockHeadersPacket, NewBlockHashesPacket, NewBlockPacket,
-				PrivateStatePacket, PrivateTransactionPacket, ReceiptsPacket, SignedPrivateTransactionPacket,
+				NewPooledTransactionHashesPacket, PooledTransactionsPacket, PrivateStatePacket,
+				PrivateTransactionPacket, ReceiptsPacket, SignedPrivateTransactionPacket,
 				SnapshotDataPacket, SnapshotManifestPacket, StatusPacket,
 			}
 		},
 		BlockSet, ChainSync, ForkConfirmation, PacketDecodeError, PeerAsking, PeerInfo, SyncRequester,
-		SyncState, ETH_PROTOCOL_VERSION_62, ETH_PROTOCOL_VERSION_63, MAX_NEW_BLOCK_AGE, MAX_NEW_HASHES,
-		PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_3, PAR_PROTOCOL_VERSION_4,
+		RequestId, SyncState, ETH_PROTOCOL_VERSION_62, ETH_PROTOCOL_VERSION_66, FORK_ID_PROTOCOL_VERSION, MAX_NEW_BLOCK_AGE, MAX_NEW_HASHES,
+		MAX_TRANSACTION_HASHES_TO_ANNOUNCE, MAX_UNFETCHED_POOLED_TRANSACTIONS,
+		PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_3, PAR_PROTOCOL_VERSION_5, uses_request_id,
 	}
 };
 
 use bytes::Bytes;
 use enum_primitive::FromPrimitive;
 use ethereum_types::{H256, U256};
+use fastmap::H256FastSet;
 use keccak_hash::keccak;
 use network::PeerId;
 use network::client_version::ClientVersion;
@@ -63,22 +67,28 @@ impl SyncHandler {
 	pub fn on_packet(sync: &mut ChainSync, io: &mut dyn SyncIo, peer: PeerId, packet_id: u8, data: &[u8]) {
 		let rlp = Rlp::new(data);
 		if let Some(packet_id) = SyncPacket::from_u8(packet_id) {
-			let result = match packet_id {
-				StatusPacket => SyncHandler::on_peer_status(sync, io, peer, &rlp),
-				BlockHeadersPacket => SyncHandler::on_peer_block_headers(sync, io, peer, &rlp),
-				BlockBodiesPacket => SyncHandler::on_peer_block_bodies(sync, io, peer, &rlp),
-				ReceiptsPacket => SyncHandler::on_peer_block_receipts(sync, io, peer, &rlp),
-				NewBlockPacket => SyncHandler::on_peer_new_block(sync, io, peer, &rlp),
-				NewBlockHashesPacket => SyncHandler::on_peer_new_hashes(sync, io, peer, &rlp),
-				SnapshotManifestPacket => SyncHandler::on_snapshot_manifest(sync, io, peer, &rlp),
-				SnapshotDataPacket => SyncHandler::on_snapshot_data(sync, io, peer, &rlp),
-				PrivateTransactionPacket => SyncHandler::on_private_transaction(sync, io, peer, &rlp),
-				SignedPrivateTransactionPacket => SyncHandler::on_signed_private_transaction(sync, io, peer, &rlp),
-				PrivateStatePacket => SyncHandler::on_private_state_data(sync, io, peer, &rlp),
-				_ => {
-					trace!(target: "sync", "{}: Unknown packet {}", peer, packet_id.id());
-					Ok(())
-				}
+			let result = match SyncHandler::strip_request_id(sync, io, peer, packet_id, rlp) {
+				Err(e) => Err(e),
+				Ok(None) => Ok(()),
+				Ok(Some(rlp)) => match packet_id {
+					StatusPacket => SyncHandler::on_peer_status(sync, io, peer, &rlp),
+					BlockHeadersPacket => SyncHandler::on_peer_block_headers(sync, io, peer, &rlp),
+					BlockBodiesPacket => SyncHandler::on_peer_block_bodies(sync, io, peer, &rlp),
+					ReceiptsPacket => SyncHandler::on_peer_block_receipts(sync, io, peer, &rlp),
+					NewBlockPacket => SyncHandler::on_peer_new_block(sync, io, peer, &rlp),
+					NewBlockHashesPacket => SyncHandler::on_peer_new_hashes(sync, io, peer, &rlp),
+					SnapshotManifestPacket => SyncHandler::on_snapshot_manifest(sync, io, peer, &rlp),
+					SnapshotDataPacket => SyncHandler::on_snapshot_data(sync, io, peer, &rlp),
+					PrivateTransactionPacket => SyncHandler::on_private_transaction(sync, io, peer, &rlp),
+					SignedPrivateTransactionPacket => SyncHandler::on_signed_private_transaction(sync, io, peer, &rlp),
+					PrivateStatePacket => SyncHandler::on_private_state_data(sync, io, peer, &rlp),
+					NewPooledTransactionHashesPacket => SyncHandler::on_peer_new_pooled_transaction_hashes(sync, io, peer, &rlp),
+					PooledTransactionsPacket => SyncHandler::on_peer_pooled_transactions(sync, io, peer, &rlp),
+					_ => {
+						trace!(target: "sync", "{}: Unknown packet {}", peer, packet_id.id());
+						Ok(())
+					}
+				},
 			};
 
 			match result {
@@ -100,6 +110,38 @@ impl SyncHandler {
 		}
 	}
 
+	/// Unwraps the payload of an eth/66 response and matches it to the request it answers by id.
+	///
+	/// Responses may come in any order. A response is only processed if the request it answers
+	/// is still the one tracked by `asking`; answers to unknown requests, to requests already
+	/// answered, or to requests replaced by a newer one are ignored.
+	fn strip_request_id<'a>(sync: &mut ChainSync, io: &dyn SyncIo, peer_id: PeerId, packet_id: SyncPacket, r: Rlp<'a>) -> Result<Option<Rlp<'a>>, DownloaderImportError> {
+		if !uses_request_id(io, peer_id, packet_id) {
+			return Ok(Some(r));
+		}
+		let request_id: RequestId = r.val_at(0)?;
+		let payload = r.at(1)?;
+		let peer = match sync.peers.get_mut(&peer_id) {
+			Some(peer) => peer,
+			None => {
+				trace!(target: "sync", "{}: Ignored response {} from unknown peer", peer_id, request_id);
+				return Ok(None);
+			}
+		};
+		let asking = match peer.pending_requests.remove(&request_id) {
+			Some(asking) => asking,
+			None => {
+				trace!(target: "sync", "{}: Ignored response to unknown request {}", peer_id, request_id);
+				return Ok(None);
+			}
+		};
+		if asking != peer.asking {
+			trace!(target: "sync", "{}: Ignored response to superseded request {} ({:?})", peer_id, request_id, asking);
+			return Ok(None);
+		}
+		Ok(Some(payload))
+	}
+
 	/// Called when peer sends us new consensus packet
 	pub fn on_consensus_packet(io: &mut dyn SyncIo, peer_id: PeerId, r: &Rlp) {
 		trace!(target: "sync", "Received consensus packet from {:?}", peer_id);
@@ -110,6 +152,7 @@ impl SyncHandler {
 	pub fn on_peer_aborting(sync: &mut ChainSync, io: &mut dyn SyncIo, peer_id: PeerId) {
 		trace!(target: "sync", "== Disconnecting {}: {}", peer_id, io.peer_version(peer_id));
 		sync.handshaking_peers.remove(&peer_id);
+		sync.malformed_packets.remove(&peer_id);
 		if sync.peers.contains_key(&peer_id) {
 			debug!(target: "sync", "Disconnected {}", peer_id);
 			sync.clear_peer_download(peer_id);
@@ -162,7 +205,7 @@ impl SyncHandler {
 		if number > sync.highest_block.unwrap_or(0) {
 			sync.highest_block = Some(number);
 		}
-		let mut unknown = false;
+		let mut unknown = None;
 
 		if let Some(ref mut peer) = sync.peers.get_mut(&peer_id) {
 			peer.latest_hash = hash;
@@ -186,21 +229,30 @@ impl SyncHandler {
 				sync.new_blocks.mark_as_known(&hash, number);
 				trace!(target: "sync", "New block queued {:?} ({})", hash, number);
 			},
+			Err(EthcoreError::FullQueue(limit)) => {
+				// Not the peer's fault, the block is downloaded again once the queue has room.
+				debug!(target: "sync", "Block import queue full ({}), ignoring new block {:?}", limit, hash);
+			},
 			Err(EthcoreError::Block(BlockError::UnknownParent(p))) => {
-				unknown = true;
 				trace!(target: "sync", "New block with unknown parent ({:?}) {:?}", p, hash);
+				if sync.orphan_blocks.insert(hash, p, number, r.at(0)?.as_raw().to_vec()) {
+					unknown = Some(p);
+				}
 			},
 			Err(e) => {
 				debug!(target: "sync", "Bad new block {:?} : {:?}", hash, e);
 				return Err(DownloaderImportError::Invalid);
 			}
 		};
-		if unknown {
+		if let Some(parent) = unknown {
 			if sync.state != SyncState::Idle {
 				trace!(target: "sync", "NewBlock ignored while seeking");
+			} else if sync.new_blocks.is_downloading(&parent) {
+				trace!(target: "sync", "Parent of orphan block {:?} is already being downloaded", hash);
 			} else {
-				trace!(target: "sync", "New unknown block {:?}", hash);
-				//TODO: handle too many unknown blocks
+				trace!(target: "sync", "New orphan block {:?}, requesting parent {:?}", hash, parent);
+				sync.new_blocks.reset_to(vec![parent]);
+				sync.state = SyncState::NewBlocks;
 				sync.sync_peer(io, peer_id, true);
 			}
 		}
@@ -476,6 +528,11 @@ impl SyncHandler {
 		}
 
 		let manifest_rlp = r.at(0)?;
+		let manifest_hash = keccak(manifest_rlp.as_raw());
+		if !sync.is_warp_pin_allowed(io, peer_id, &manifest_hash) {
+			warn!(target: "snapshot_sync", "{}: Snapshot manifest {:?} is not the pinned one", peer_id, manifest_hash);
+			return Err(DownloaderImportError::Invalid);
+		}
 		let manifest = ManifestData::from_rlp(manifest_rlp.as_raw())?;
 
 		let is_supported_version = io.snapshot_service().supported_versions()
@@ -485,7 +542,7 @@ impl SyncHandler {
 			warn!(target: "snapshot_sync", "{}: Snapshot manifest version not supported: {}", peer_id, manifest.version);
 			return Err(DownloaderImportError::Invalid);
 		}
-		sync.snapshot.reset_to(&manifest, &keccak(manifest_rlp.as_raw()));
+		sync.snapshot.reset_to(&manifest, &manifest_hash);
 		debug!(target: "snapshot_sync", "{}: Peer sent a snapshot manifest we can use. Block number #{}, block chunks: {}, state chunks: {}",
 			peer_id, manifest.block_number, manifest.block_hashes.len(), manifest.state_hashes.len());
 		io.snapshot_service().begin_restore(manifest);
@@ -579,6 +636,12 @@ impl SyncHandler {
 			asking_private_state: None,
 			ask_time: Instant::now(),
 			last_sent_transactions: Default::default(),
+			pooled_transactions: warp_protocol_version >= PAR_PROTOCOL_VERSION_5.0,
+			unfetched_pooled_transactions: Default::default(),
+			pending_requests: Default::default(),
+			request_deferred: false,
+			asking_pooled_transactions: Vec::new(),
+			pooled_transactions_ask_time: Instant::now(),
 			last_sent_private_transactions: Default::default(),
 			expired: false,
 			confirmation: if sync.fork_block.is_none() { ForkConfirmation::Confirmed } else { ForkConfirmation::Unconfirmed },
@@ -629,12 +692,19 @@ impl SyncHandler {
 		}
 
 		if false
-			|| (warp_protocol && (peer.protocol_version < PAR_PROTOCOL_VERSION_1.0 || peer.protocol_version > PAR_PROTOCOL_VERSION_4.0))
-			|| (!warp_protocol && (peer.protocol_version < ETH_PROTOCOL_VERSION_62.0 || peer.protocol_version > ETH_PROTOCOL_VERSION_63.0))
+			|| (warp_protocol && (peer.protocol_version < PAR_PROTOCOL_VERSION_1.0 || peer.protocol_version > PAR_PROTOCOL_VERSION_5.0))
+			|| (!warp_protocol && (peer.protocol_version < ETH_PROTOCOL_VERSION_62.0 || peer.protocol_version > ETH_PROTOCOL_VERSION_66.0))
 		{
 			trace!(target: "sync", "Peer {} unsupported eth protocol ({})", peer_id, peer.protocol_version);
 			return Err(DownloaderImportError::Invalid);
 		}
+		if !warp_protocol && peer.protocol_version >= FORK_ID_PROTOCOL_VERSION {
+			let fork_id: ForkId = r.val_at(5)?;
+			if !sync.fork_filter.is_compatible(chain_info.best_block_number, &fork_id) {
+				trace!(target: "sync", "Peer {} fork id mismatch (ours: {:?}, theirs: {:?})", peer_id, sync.fork_filter.current(chain_info.best_block_number), fork_id);
+				return Err(DownloaderImportError::Invalid);
+			}
+		}
 
 		if sync.sync_start_time.is_none() {
 			sync.sync_start_time = Some(Instant::now());
@@ -677,6 +747,74 @@ impl SyncHandler {
 		Ok(())
 	}
 
+	/// Called when peer announces hashes of transactions in its queue
+	fn on_peer_new_pooled_transaction_hashes(sync: &mut ChainSync, io: &mut dyn SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
+		// Accept transactions only when fully synced
+		if !io.is_chain_queue_empty() || (sync.state != SyncState::Idle && sync.state != SyncState::NewBlocks) {
+			trace!(target: "sync", "{} Ignoring transaction hashes while syncing", peer_id);
+			return Ok(());
+		}
+		let peer = match sync.peers.get_mut(&peer_id) {
+			Some(peer) if peer.can_sync() => peer,
+			_ => {
+				trace!(target: "sync", "{} Ignoring transaction hashes from unconfirmed/unknown peer", peer_id);
+				return Ok(());
+			}
+		};
+
+		let item_count = r.item_count()?;
+		trace!(target: "sync", "{:02} -> NewPooledTransactionHashes ({} entries)", peer_id, item_count);
+		if item_count > MAX_TRANSACTION_HASHES_TO_ANNOUNCE {
+			debug!(target: "sync", "{} Too many transaction hashes announced: {}", peer_id, item_count);
+			return Err(DownloaderImportError::Invalid);
+		}
+		for i in 0..item_count {
+			let hash: H256 = r.val_at(i)?;
+			// the peer knows the transaction, no need to send it back
+			peer.last_sent_transactions.insert(hash);
+			if peer.unfetched_pooled_transactions.len() < MAX_UNFETCHED_POOLED_TRANSACTIONS
+				&& !peer.asking_pooled_transactions.contains(&hash)
+				&& io.chain().pooled_transaction(&hash).is_none()
+			{
+				peer.unfetched_pooled_transactions.insert(hash);
+			}
+		}
+
+		SyncRequester::request_pooled_transactions(sync, io, peer_id);
+		Ok(())
+	}
+
+	/// Called when peer sends us transactions we have asked for
+	fn on_peer_pooled_transactions(sync: &mut ChainSync, io: &mut dyn SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
+		let requested = match sync.peers.get_mut(&peer_id) {
+			Some(peer) if !peer.asking_pooled_transactions.is_empty() => {
+				mem::replace(&mut peer.asking_pooled_transactions, Vec::new()).into_iter().collect::<H256FastSet>()
+			},
+			_ => {
+				trace!(target: "sync", "{} Ignoring unexpected pooled transactions", peer_id);
+				return Ok(());
+			}
+		};
+
+		let item_count = r.item_count()?;
+		trace!(target: "sync", "{:02} -> PooledTransactions ({} entries)", peer_id, item_count);
+		let mut transactions = Vec::with_capacity(item_count);
+		for i in 0..item_count {
+			let tx = r.at(i)?.as_raw().to_vec();
+			if !requested.contains(&keccak(&tx)) {
+				debug!(target: "sync", "{} Sent a transaction we haven't asked for", peer_id);
+				return Err(DownloaderImportError::Invalid);
+			}
+			transactions.push(tx);
+		}
+		if !transactions.is_empty() {
+			io.chain().queue_transactions(transactions, peer_id);
+		}
+
+		SyncRequester::request_pooled_transactions(sync, io, peer_id);
+		Ok(())
+	}
+
 	/// Called when peer sends us signed private transaction packet
 	fn on_signed_private_transaction(sync: &mut ChainSync, _io: &mut dyn SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
 		if !sync.peers.get(&peer_id).map_or(false, |p| p.can_sync()) {
@@ -792,15 +930,103 @@ mod tests {
 
 	use super::{
 		super::tests::{dummy_sync_with_peer, get_dummy_block, get_dummy_blocks, get_dummy_hashes},
-		SyncHandler
+		super::sync_packet::SyncPacket::BlockHeadersPacket,
+		ForkConfirmation, SyncHandler, SyncRequester, ETH_PROTOCOL_VERSION_66,
 	};
 
-	use crate::tests::{helpers::TestIo, snapshot::TestSnapshotService};
+	use crate::tests::{helpers::{Peer, TestIo, TestNet}, snapshot::TestSnapshotService};
 
-	use client_traits::ChainInfo;
+	use client_traits::{BlockChainClient, ChainInfo};
+	use common_types::ids::BlockId;
 	use ethcore::test_helpers::{EachBlockWith, TestBlockChainClient};
+	use ethereum_types::H256;
 	use parking_lot::RwLock;
-	use rlp::Rlp;
+	use rlp::{Rlp, RlpStream};
+
+	#[test]
+	fn matches_eth66_responses_by_request_id() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None, None);
+		io.eth_protocol_version = ETH_PROTOCOL_VERSION_66.0;
+
+		SyncRequester::request_fork_header(&mut sync, &mut io, 0, 5);
+		SyncRequester::request_fork_header(&mut sync, &mut io, 0, 6);
+
+		// requests are prefixed with their ids
+		let request = Rlp::new(&io.packets[1].data);
+		assert_eq!(request.val_at::<u64>(0).unwrap(), 2);
+		assert_eq!(request.at(1).unwrap().val_at::<u64>(0).unwrap(), 6);
+
+		let response = |request_id: u64| {
+			let mut rlp = RlpStream::new_list(2);
+			rlp.append(&request_id);
+			rlp.begin_list(0);
+			rlp.out()
+		};
+
+		// the response to the first request is superseded by the second one
+		let superseded = response(1);
+		assert!(SyncHandler::strip_request_id(&mut sync, &io, 0, BlockHeadersPacket, Rlp::new(&superseded)).unwrap().is_none());
+		let unknown = response(3);
+		assert!(SyncHandler::strip_request_id(&mut sync, &io, 0, BlockHeadersPacket, Rlp::new(&unknown)).unwrap().is_none());
+		let current = response(2);
+		let payload = SyncHandler::strip_request_id(&mut sync, &io, 0, BlockHeadersPacket, Rlp::new(&current)).unwrap().unwrap();
+		assert_eq!(payload.item_count().unwrap(), 0);
+		assert!(sync.peers[&0].pending_requests.is_empty());
+		// a repeated response is ignored
+		assert!(SyncHandler::strip_request_id(&mut sync, &io, 0, BlockHeadersPacket, Rlp::new(&current)).unwrap().is_none());
+	}
+
+	#[test]
+	fn negotiates_eth66_and_matches_out_of_order_responses() {
+		let mut net = TestNet::new(2);
+		for i in 0..2 {
+			let peer = net.peer_mut(i);
+			peer.eth_protocol_version = ETH_PROTOCOL_VERSION_66.0;
+			peer.warp_protocol_version = 0;
+			peer.chain.add_blocks(10, EachBlockWith::Nothing);
+		}
+		let fork_hash = net.peer(0).chain.block_hash(BlockId::Number(5)).unwrap();
+		net.peer(0).sync.write().fork_block = Some((5, fork_hash));
+		net.sync();
+
+		// the Status messages carry a compatible fork id, and the fork header is requested over eth/66
+		assert_eq!(net.peer(0).sync.read().peers[&1].protocol_version, ETH_PROTOCOL_VERSION_66.0);
+		assert_eq!(net.peer(1).sync.read().peers[&0].protocol_version, ETH_PROTOCOL_VERSION_66.0);
+		assert_eq!(net.peer(0).sync.read().peers[&1].confirmation, ForkConfirmation::Confirmed);
+
+		// ask for the fork header twice, the second request replaces the first one
+		net.peer(0).sync.write().peers.get_mut(&1).unwrap().confirmation = ForkConfirmation::Unconfirmed;
+		{
+			let mut io = net.peer(0).io(None);
+			let mut sync = net.peer(0).sync.write();
+			SyncRequester::request_fork_header(&mut sync, &mut io, 1, 4);
+			SyncRequester::request_fork_header(&mut sync, &mut io, 1, 5);
+		}
+		while let Some(request) = net.peer(0).pending_message() {
+			assert!(net.peer(1).receive_message(0, request).is_empty());
+		}
+		let mut responses = Vec::new();
+		while let Some(response) = net.peer(1).pending_message() {
+			responses.push(response);
+		}
+		let request_ids: Vec<u64> = responses.iter().map(|r| Rlp::new(&r.data).val_at(0).unwrap()).collect();
+		assert_eq!(request_ids.len(), 2);
+		assert!(request_ids[0] < request_ids[1]);
+
+		// the answer to the current request arrives first and confirms the peer, the late answer
+		// to the replaced request (a header which doesn't match the fork) is ignored
+		for response in responses.into_iter().rev() {
+			assert!(net.peer(0).receive_message(1, response).is_empty());
+		}
+		let sync = net.peer(0).sync.read();
+		assert_eq!(sync.peers[&1].confirmation, ForkConfirmation::Confirmed);
+		assert!(sync.peers[&1].pending_requests.is_empty());
+	}
 
 	#[test]
 	fn handles_peer_new_hashes() {
@@ -819,6 +1045,35 @@ mod tests {
 		assert!(result.is_ok());
 	}
 
+	#[test]
+	fn requests_announced_transactions() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None, None);
+
+		let hash = H256::from_low_u64_be(1);
+		let mut announcement = RlpStream::new_list(1);
+		announcement.append(&hash);
+		SyncHandler::on_peer_new_pooled_transaction_hashes(&mut sync, &mut io, 0, &Rlp::new(&announcement.out()))
+			.expect("result to be ok");
+
+		// GET_POOLED_TRANSACTIONS_PACKET
+		assert_eq!(1, io.packets.len());
+		assert_eq!(0x1b, io.packets[0].packet_id);
+		assert_eq!(sync.peers[&0].asking_pooled_transactions, vec![hash]);
+		assert!(sync.peers[&0].unfetched_pooled_transactions.is_empty());
+
+		// a transaction we haven't asked for is rejected
+		let mut response = RlpStream::new_list(1);
+		response.append(&vec![1u8, 2, 3]);
+		let result = SyncHandler::on_peer_pooled_transactions(&mut sync, &mut io, 0, &Rlp::new(&response.out()));
+		assert!(result.is_err());
+		assert!(sync.peers[&0].asking_pooled_transactions.is_empty());
+	}
+
 	#[test]
 	fn handles_peer_new_block_malformed() {
 		let mut client = TestBlockChainClient::new();
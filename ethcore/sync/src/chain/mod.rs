@@ -87,6 +87,7 @@
 //!
 //! All other messages are ignored.
 
+mod fork_id;
 mod handler;
 mod propagator;
 mod requester;
@@ -96,15 +97,16 @@ pub mod sync_packet;
 
 use std::sync::{Arc, mpsc};
 use std::collections::{HashSet, HashMap, BTreeMap};
-use std::cmp;
+use std::{cmp, fmt, mem};
 use std::time::{Duration, Instant};
 
 use crate::{
-	EthProtocolInfo as PeerInfoDigest, PriorityTask, SyncConfig, WarpSync, WARP_SYNC_PROTOCOL_ID,
-	api::{Notification, PRIORITY_TIMER_INTERVAL},
+	EthProtocolInfo as PeerInfoDigest, PriorityTask, SyncConfig, WarpPin, WarpSync, WARP_SYNC_PROTOCOL_ID,
+	api::{Notification, ETH_PROTOCOL, PRIORITY_TIMER_INTERVAL},
 	block_sync::{BlockDownloader, DownloadAction},
 	sync_io::SyncIo,
 	snapshot_sync::Snapshot,
+	orphan_blocks::OrphanBlockPool,
 	transactions_stats::{TransactionsStats, Stats as TransactionStats},
 	private_tx::PrivateTxHandler,
 };
@@ -125,13 +127,16 @@ use rlp::{RlpStream, DecoderError};
 use common_types::{
 	BlockNumber,
 	ids::BlockId,
+	errors::{EthcoreError, ImportError},
 	transaction::UnverifiedTransaction,
+	verification::Unverified,
 	verification::VerificationQueueInfo as BlockQueueInfo,
 	blockchain_info::BlockChainInfo,
 	block_status::BlockStatus,
 	snapshot::RestorationStatus,
 };
 
+use self::fork_id::ForkFilter;
 use self::handler::SyncHandler;
 use self::sync_packet::{PacketInfo, SyncPacket};
 use self::sync_packet::SyncPacket::{
@@ -145,12 +150,39 @@ pub(crate) use self::supplier::SyncSupplier;
 
 malloc_size_of_is_0!(PeerInfo);
 
-pub type PacketDecodeError = DecoderError;
+/// Error decoding a packet received from a peer.
+#[derive(Debug, PartialEq)]
+pub enum PacketDecodeError {
+	/// Packet id is not known to the protocol.
+	UnknownPacket(PacketId),
+	/// Packet payload is not valid RLP for the packet.
+	Rlp(DecoderError),
+}
 
+impl From<DecoderError> for PacketDecodeError {
+	fn from(err: DecoderError) -> Self {
+		PacketDecodeError::Rlp(err)
+	}
+}
+
+impl fmt::Display for PacketDecodeError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			PacketDecodeError::UnknownPacket(id) => write!(f, "Unknown packet id {}", id),
+			PacketDecodeError::Rlp(ref err) => write!(f, "Malformed RLP: {}", err),
+		}
+	}
+}
+
+/// 66 version of Ethereum protocol, request-response packets carry a request id.
+/// Its Status message carries the EIP-2124 fork id introduced in eth/64.
+pub const ETH_PROTOCOL_VERSION_66: (u8, u8) = (66, 0x11);
 /// 63 version of Ethereum protocol.
 pub const ETH_PROTOCOL_VERSION_63: (u8, u8) = (63, 0x11);
 /// 62 version of Ethereum protocol.
 pub const ETH_PROTOCOL_VERSION_62: (u8, u8) = (62, 0x11);
+/// First version of Ethereum protocol whose Status message carries the EIP-2124 fork id.
+pub const FORK_ID_PROTOCOL_VERSION: u8 = 64;
 /// 1 version of Parity protocol and the packet count.
 pub const PAR_PROTOCOL_VERSION_1: (u8, u8) = (1, 0x15);
 /// 2 version of Parity protocol (consensus messages added).
@@ -159,6 +191,8 @@ pub const PAR_PROTOCOL_VERSION_2: (u8, u8) = (2, 0x16);
 pub const PAR_PROTOCOL_VERSION_3: (u8, u8) = (3, 0x18);
 /// 4 version of Parity protocol (private state sync added).
 pub const PAR_PROTOCOL_VERSION_4: (u8, u8) = (4, 0x20);
+/// 5 version of Parity protocol (transaction hash announcements added).
+pub const PAR_PROTOCOL_VERSION_5: (u8, u8) = (5, 0x20);
 
 pub const MAX_BODIES_TO_SEND: usize = 256;
 pub const MAX_HEADERS_TO_SEND: usize = 512;
@@ -169,11 +203,21 @@ const MAX_NODE_DATA_TOTAL_DURATION: Duration = Duration::from_secs(2);
 /// Maximum allowed duration for serving a single GetNodeData request.
 const MAX_NODE_DATA_SINGLE_DURATION: Duration = Duration::from_millis(100);
 pub const MAX_RECEIPTS_HEADERS_TO_SEND: usize = 256;
+/// Maximum number of transactions to include in a PooledTransactions response.
+pub const MAX_POOLED_TRANSACTIONS_TO_SEND: usize = 256;
+/// Maximum number of transactions to ask a single peer for at once.
+const MAX_POOLED_TRANSACTIONS_TO_REQUEST: usize = 256;
+/// Maximum number of hashes in a single NewPooledTransactionHashes packet.
+const MAX_TRANSACTION_HASHES_TO_ANNOUNCE: usize = 4096;
+/// Maximum number of announced, not yet requested transaction hashes kept per peer.
+const MAX_UNFETCHED_POOLED_TRANSACTIONS: usize = 4096;
 const MIN_PEERS_PROPAGATION: usize = 4;
 const MAX_PEERS_PROPAGATION: usize = 128;
 const MAX_PEER_LAG_PROPAGATION: BlockNumber = 20;
 const MAX_NEW_HASHES: usize = 64;
 const MAX_NEW_BLOCK_AGE: BlockNumber = 20;
+/// Number of malformed packets a peer may send before it is disconnected.
+pub const MAX_MALFORMED_PACKETS: usize = 8;
 // maximal packet size with transactions (cannot be greater than 16MB - protocol limitation).
 // keep it under 8MB as well, cause it seems that it may result oversized after compression.
 const MAX_TRANSACTION_PACKET_SIZE: usize = 5 * 1024 * 1024;
@@ -203,6 +247,7 @@ const FORK_HEADER_TIMEOUT: Duration = Duration::from_secs(3);
 const SNAPSHOT_MANIFEST_TIMEOUT: Duration = Duration::from_secs(5);
 const SNAPSHOT_DATA_TIMEOUT: Duration = Duration::from_secs(120);
 const PRIVATE_STATE_TIMEOUT: Duration = Duration::from_secs(120);
+const POOLED_TRANSACTIONS_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Defines how much time we have to complete priority transaction or block propagation.
 /// after the deadline is reached the task is considered finished
@@ -349,6 +394,18 @@ pub struct PeerInfo {
 	ask_time: Instant,
 	/// Holds a set of transactions recently sent to this peer to avoid spamming.
 	last_sent_transactions: H256FastSet,
+	/// Peer accepts transaction hash announcements instead of full transactions.
+	pooled_transactions: bool,
+	/// Hashes of transactions announced by the peer which were not requested yet.
+	unfetched_pooled_transactions: H256FastSet,
+	/// Requests sent to an eth/66 peer which were not answered yet, by request id.
+	pending_requests: HashMap<RequestId, PeerAsking>,
+	/// The request tracked by `asking` was dropped by our full outbound queue and must be asked again.
+	request_deferred: bool,
+	/// Hashes of transactions currently being requested from the peer.
+	asking_pooled_transactions: Vec<H256>,
+	/// Pooled transactions request timestamp
+	pooled_transactions_ask_time: Instant,
 	/// Holds a set of private transactions and their signatures recently sent to this peer to avoid spamming.
 	last_sent_private_transactions: H256FastSet,
 	/// Pending request is expired and result should be ignored
@@ -412,6 +469,23 @@ pub mod random {
 pub type RlpResponseResult = Result<Option<(PacketId, RlpStream)>, PacketDecodeError>;
 pub type Peers = HashMap<PeerId, PeerInfo>;
 
+/// Id of a request sent over eth/66, echoed by the peer in its response.
+pub type RequestId = u64;
+
+/// Whether `packet` exchanged with the peer is prefixed with a request id, i.e. it's an eth/66
+/// request or response.
+fn uses_request_id(io: &dyn SyncIo, peer: PeerId, packet: SyncPacket) -> bool {
+	packet.has_request_id() && io.protocol_version(&ETH_PROTOCOL, peer) >= ETH_PROTOCOL_VERSION_66.0
+}
+
+/// Prefixes an eth/66 request or response payload with its request id: `[request_id, payload]`.
+fn with_request_id(request_id: RequestId, payload: &[u8]) -> Bytes {
+	let mut rlp = RlpStream::new_list(2);
+	rlp.append(&request_id);
+	rlp.append_raw(payload, 1);
+	rlp.out()
+}
+
 /// Thread-safe wrapper for `ChainSync`.
 ///
 /// NOTE always lock in order of fields declaration
@@ -662,6 +736,8 @@ pub struct ChainSync {
 	network_id: u64,
 	/// Optional fork block to check
 	fork_block: Option<(BlockNumber, H256)>,
+	/// EIP-2124 fork id of our chain, checked against the one sent by eth/64+ peers.
+	fork_filter: ForkFilter,
 	/// Snapshot downloader.
 	snapshot: Snapshot,
 	/// Connected peers pending Status message.
@@ -671,6 +747,8 @@ pub struct ChainSync {
 	sync_start_time: Option<Instant>,
 	/// Transactions propagation statistics
 	transactions_stats: TransactionsStats,
+	/// New blocks received before their parent
+	orphan_blocks: OrphanBlockPool,
 	/// Enable ancient block downloading
 	download_old_blocks: bool,
 	/// Shared private tx service.
@@ -678,6 +756,12 @@ pub struct ChainSync {
 	private_tx_handler: Option<Arc<dyn PrivateTxHandler>>,
 	/// Enable warp sync.
 	warp_sync: WarpSync,
+	/// Snapshot (and peer) warp sync is restricted to.
+	warp_pin: Option<WarpPin>,
+	/// Number of malformed packets received from each peer.
+	malformed_packets: HashMap<PeerId, usize>,
+	/// Id of the last request sent to an eth/66 peer.
+	last_request_id: RequestId,
 
 	#[ignore_malloc_size_of = "mpsc unmettered, ignoring"]
 	status_sinks: Vec<futures_mpsc::UnboundedSender<SyncState>>
@@ -706,12 +790,17 @@ impl ChainSync {
 			last_sent_block_number: 0,
 			network_id: config.network_id,
 			fork_block: config.fork_block,
+			fork_filter: ForkFilter::new(chain_info.genesis_hash, &config.fork_blocks),
 			download_old_blocks: config.download_old_blocks,
 			snapshot: Snapshot::new(),
 			sync_start_time: None,
 			transactions_stats: TransactionsStats::default(),
+			orphan_blocks: OrphanBlockPool::default(),
 			private_tx_handler,
 			warp_sync: config.warp_sync,
+			warp_pin: None,
+			malformed_packets: HashMap::new(),
+			last_request_id: 0,
 			status_sinks: Vec::new()
 		};
 		sync.update_targets(chain);
@@ -723,7 +812,7 @@ impl ChainSync {
 		let last_imported_number = self.new_blocks.last_imported_block_number();
 		SyncStatus {
 			state: self.state.clone(),
-			protocol_version: ETH_PROTOCOL_VERSION_63.0,
+			protocol_version: ETH_PROTOCOL_VERSION_66.0,
 			network_id: self.network_id,
 			start_block_number: self.starting_block,
 			last_imported_block_number: Some(last_imported_number),
@@ -746,6 +835,8 @@ impl ChainSync {
 				version: peer_data.protocol_version as u32,
 				difficulty: peer_data.difficulty,
 				head: peer_data.latest_hash,
+				snapshot_hash: peer_data.snapshot_hash,
+				snapshot_number: peer_data.snapshot_number,
 			}
 		})
 	}
@@ -835,6 +926,38 @@ impl ChainSync {
 		self.active_peers.remove(&peer_id);
 	}
 
+	/// Note a malformed packet received from a peer. The peer is disabled once it has sent
+	/// more than `MAX_MALFORMED_PACKETS` of them.
+	fn on_malformed_packet(&mut self, io: &mut dyn SyncIo, peer_id: PeerId, packet_id: u8, error: &PacketDecodeError) {
+		let count = {
+			let count = self.malformed_packets.entry(peer_id).or_insert(0);
+			*count += 1;
+			*count
+		};
+		debug!(target: "sync", "{} -> Malformed packet {} ({} so far): {}", peer_id, packet_id, count, error);
+		if count > MAX_MALFORMED_PACKETS {
+			debug!(target: "sync", "{} -> Too many malformed packets, disabling peer", peer_id);
+			io.disable_peer(peer_id);
+			self.deactivate_peer(io, peer_id);
+		}
+	}
+
+	/// Restrict warp sync to the given snapshot (and peer). Takes effect from the next snapshot sync.
+	pub fn set_warp_pin(&mut self, pin: Option<WarpPin>) {
+		debug!(target: "snapshot_sync", "Warp sync pinned to {:?}", pin);
+		self.warp_pin = pin;
+	}
+
+	/// Returns true if the snapshot `hash` may be downloaded from `peer_id`.
+	fn is_warp_pin_allowed(&self, io: &dyn SyncIo, peer_id: PeerId, hash: &H256) -> bool {
+		match self.warp_pin {
+			None => true,
+			Some(ref pin) => pin.manifest_hash == *hash && pin.node.map_or(true, |node| {
+				io.peer_session_info(peer_id).and_then(|info| info.id) == Some(node)
+			}),
+		}
+	}
+
 	/// Decide if we should start downloading a snapshot and from who. Called once per second.
 	fn maybe_start_snapshot_sync(&mut self, io: &mut dyn SyncIo) {
 		if !self.warp_sync.is_enabled() || io.snapshot_service().supported_versions().is_none() {
@@ -863,6 +986,7 @@ impl ChainSync {
 		};
 		// Collect snapshot info from peers and check if we can use their snapshots to sync.
 		let (best_snapshot_block, best_hash, max_peers, snapshot_peers) = {
+			let sync_io: &dyn SyncIo = io;
 			let mut snapshots = self.peers.iter()
 				.filter(|&(_, p)|
 					// filter out expired peers and peers from whom we do not have fork confirmation.
@@ -879,7 +1003,7 @@ impl ChainSync {
 				)
 				.filter_map(|(p, peer)| {
 					peer.snapshot_hash.map(|hash| (p, hash))
-						.filter(|(_, hash)| !self.snapshot.is_known_bad(&hash) )
+						.filter(|(p, hash)| !self.snapshot.is_known_bad(&hash) && self.is_warp_pin_allowed(sync_io, **p, hash))
 						.and_then(|(p, hash)| peer.snapshot_number.map(|n| (*p, n, hash) ) )
 				})
 				.collect::<Vec<(PeerId, BlockNumber, H256)>>();
@@ -917,7 +1041,7 @@ impl ChainSync {
 		) {
 			trace!(target: "snapshot_sync", "We can sync a snapshot at #{:?}/{:?} from {} peer(s): {:?}",
 			       best_snapshot_block, best_hash, max_peers, snapshot_peers.values());
-			if max_peers >= SNAPSHOT_MIN_PEERS {
+			if max_peers >= SNAPSHOT_MIN_PEERS || self.warp_pin.is_some() {
 				debug!(target: "snapshot_sync", "Starting confirmed snapshot sync for a snapshot at #{}/{:?} with peer {:?}", block, hash, peers);
 				self.start_snapshot_sync(io, peers);
 			} else if timeout {
@@ -1057,6 +1181,11 @@ impl ChainSync {
 		let chain_info = io.chain().chain_info();
 		let syncing_difficulty = chain_info.pending_total_difficulty;
 		let num_active_peers = self.peers.values().filter(|p| p.asking != PeerAsking::Nothing).count();
+		let num_syncable_peers = self.peers.iter().filter(|&(id, p)| self.active_peers.contains(id) && p.can_sync()).count();
+		self.new_blocks.set_available_peers(num_syncable_peers);
+		if let Some(ref mut old_blocks) = self.old_blocks {
+			old_blocks.set_available_peers(num_syncable_peers);
+		}
 
 		let higher_difficulty = peer_difficulty.map_or(true, |pd| pd > syncing_difficulty);
 		if force || higher_difficulty || self.old_blocks.is_some() {
@@ -1132,7 +1261,8 @@ impl ChainSync {
 						},
 					}
 
-					if peer_snapshot_hash.is_some() && peer_snapshot_hash == self.snapshot.snapshot_hash() {
+					let is_pin_allowed = peer_snapshot_hash.map_or(false, |hash| self.is_warp_pin_allowed(io, peer_id, &hash));
+					if is_pin_allowed && peer_snapshot_hash == self.snapshot.snapshot_hash() {
 						self.clear_peer_download(peer_id);
 						SyncRequester::request_snapshot_data(self, io, peer_id);
 					}
@@ -1185,6 +1315,11 @@ impl ChainSync {
 	fn collect_blocks(&mut self, io: &mut dyn SyncIo, block_set: BlockSet) {
 		match block_set {
 			BlockSet::NewBlocks => {
+				// Keep downloaded blocks around until the verification queue has room for them.
+				if io.chain().queue_info().is_full() {
+					self.pause_sync();
+					return;
+				}
 				if self.new_blocks.collect_blocks(io, self.state == SyncState::NewBlocks) == DownloadAction::Reset {
 					self.reset_downloads(block_set);
 					self.new_blocks.reset();
@@ -1243,7 +1378,7 @@ impl ChainSync {
 		let warp_protocol_version = io.protocol_version(&WARP_SYNC_PROTOCOL_ID, peer);
 		let warp_protocol = warp_protocol_version != 0;
 		let private_tx_protocol = warp_protocol_version >= PAR_PROTOCOL_VERSION_3.0;
-		let protocol = if warp_protocol { warp_protocol_version } else { ETH_PROTOCOL_VERSION_63.0 };
+		let protocol = if warp_protocol { warp_protocol_version } else { io.protocol_version(&ETH_PROTOCOL, peer) };
 		trace!(target: "sync", "Sending status to {}, protocol version {}", peer, protocol);
 		let mut packet = RlpStream::new();
 		packet.begin_unbounded_list();
@@ -1262,6 +1397,8 @@ impl ChainSync {
 			if private_tx_protocol {
 				packet.append(&self.private_tx_handler.is_some());
 			}
+		} else if protocol >= FORK_ID_PROTOCOL_VERSION {
+			packet.append(&self.fork_filter.current(chain.best_block_number));
 		}
 		packet.finalize_unbounded_list();
 		io.respond(StatusPacket.id(), packet.out())
@@ -1271,6 +1408,8 @@ impl ChainSync {
 	/// Also checks handshaking peers.
 	/// Called every `PEERS_TIMER` (0.7sec).
 	pub fn maintain_peers(&mut self, io: &mut dyn SyncIo) {
+		self.retry_deferred_requests(io);
+
 		let tick = Instant::now();
 		let mut aborting = Vec::new();
 		for (peer_id, peer) in &self.peers {
@@ -1295,6 +1434,14 @@ impl ChainSync {
 			SyncHandler::on_peer_aborting(self, io, p);
 		}
 
+		// Give up on pooled transactions requests which were not answered in time
+		for (peer_id, peer) in &mut self.peers {
+			if !peer.asking_pooled_transactions.is_empty() && tick - peer.pooled_transactions_ask_time > POOLED_TRANSACTIONS_TIMEOUT {
+				debug!(target:"sync", "Peer {} timeout while we were asking them for {} pooled transactions.", peer_id, peer.asking_pooled_transactions.len());
+				peer.asking_pooled_transactions.clear();
+			}
+		}
+
 		// Check for handshake timeouts
 		for (peer, &ask_time) in &self.handshaking_peers {
 			let elapsed = (tick - ask_time) / 1_000_000_000;
@@ -1305,10 +1452,36 @@ impl ChainSync {
 		}
 	}
 
+	/// Give up requests which could not be sent because our outbound queue was full and ask the
+	/// peers again, the peers themselves did nothing wrong.
+	fn retry_deferred_requests(&mut self, io: &mut dyn SyncIo) {
+		let deferred: Vec<_> = self.peers.iter().filter(|(_, peer)| peer.request_deferred).map(|(id, _)| *id).collect();
+		for peer_id in deferred {
+			self.clear_peer_download(peer_id);
+			let (asking, private_state) = {
+				let peer = self.peers.get_mut(&peer_id).expect("peer_id is taken from peers; qed");
+				let asking = mem::replace(&mut peer.asking, PeerAsking::Nothing);
+				peer.pending_requests.retain(|_, pending| *pending != asking);
+				peer.request_deferred = false;
+				peer.asking_blocks.clear();
+				peer.asking_hash = None;
+				peer.asking_snapshot_data = None;
+				(asking, peer.asking_private_state.take())
+			};
+			trace!(target: "sync", "{}: Retrying deferred {:?} request", peer_id, asking);
+			match (asking, self.fork_block, private_state) {
+				(PeerAsking::ForkHeader, Some((fork_block, _)), _) => SyncRequester::request_fork_header(self, io, peer_id, fork_block),
+				(PeerAsking::PrivateState, _, Some(hash)) => SyncRequester::request_private_state(self, io, peer_id, &hash),
+				_ => self.sync_peer(io, peer_id, false),
+			}
+		}
+	}
+
 	fn check_resume(&mut self, io: &mut dyn SyncIo) {
 		match self.state {
 			SyncState::Waiting if !io.chain().queue_info().is_full() => {
 				self.set_state(SyncState::Blocks);
+				self.collect_blocks(io, BlockSet::NewBlocks);
 				self.continue_sync(io);
 			},
 			SyncState::SnapshotData => match io.snapshot_service().status() {
@@ -1407,10 +1580,22 @@ impl ChainSync {
 	pub fn maintain_sync(&mut self, io: &mut dyn SyncIo) {
 		self.maybe_start_snapshot_sync(io);
 		self.check_resume(io);
+		self.request_pooled_transactions(io);
+	}
+
+	/// Request announced transactions from peers which are not busy answering a previous request.
+	fn request_pooled_transactions(&mut self, io: &mut dyn SyncIo) {
+		let peers: Vec<PeerId> = self.peers.iter()
+			.filter(|(_, p)| p.asking_pooled_transactions.is_empty() && !p.unfetched_pooled_transactions.is_empty())
+			.map(|(id, _)| *id)
+			.collect();
+		for peer_id in peers {
+			SyncRequester::request_pooled_transactions(self, io, peer_id);
+		}
 	}
 
 	/// called when block is imported to chain - propagates the blocks and updates transactions sent to peers
-	pub fn chain_new_blocks(&mut self, io: &mut dyn SyncIo, _imported: &[H256], invalid: &[H256], enacted: &[H256], _retracted: &[H256], sealed: &[H256], proposed: &[Bytes]) {
+	pub fn chain_new_blocks(&mut self, io: &mut dyn SyncIo, imported: &[H256], invalid: &[H256], enacted: &[H256], _retracted: &[H256], sealed: &[H256], proposed: &[Bytes]) {
 		let queue_info = io.chain().queue_info();
 		let is_syncing = self.status().is_syncing(queue_info);
 
@@ -1424,6 +1609,8 @@ impl ChainSync {
 			self.restart(io);
 		}
 
+		self.import_orphans(io, imported);
+
 		if !is_syncing && !enacted.is_empty() && !self.peers.is_empty() {
 			// Select random peer to re-broadcast transactions to.
 			let peer = random::new().gen_range(0, self.peers.len());
@@ -1436,6 +1623,41 @@ impl ChainSync {
 		}
 	}
 
+	/// Queue orphan blocks whose parent has just been imported.
+	fn import_orphans(&mut self, io: &mut dyn SyncIo, imported: &[H256]) {
+		if self.orphan_blocks.is_empty() {
+			return;
+		}
+		let best_block = io.chain().chain_info().best_block_number;
+		self.orphan_blocks.prune(best_block.saturating_sub(MAX_NEW_BLOCK_AGE));
+
+		for parent in imported {
+			for (hash, bytes) in self.orphan_blocks.take_children(parent) {
+				let block = match Unverified::from_rlp(bytes) {
+					Ok(block) => block,
+					Err(e) => {
+						debug!(target: "sync", "Bad orphan block {:?} : {:?}", hash, e);
+						continue;
+					}
+				};
+				let number = block.header.number();
+				match io.chain().import_block(block) {
+					Ok(_) => {
+						trace!(target: "sync", "Orphan block queued {:?} ({})", hash, number);
+						self.new_blocks.mark_as_known(&hash, number);
+					},
+					Err(EthcoreError::Import(ImportError::AlreadyInChain)) |
+					Err(EthcoreError::Import(ImportError::AlreadyQueued)) => {
+						trace!(target: "sync", "Orphan block already known {:?}", hash);
+					},
+					Err(e) => {
+						debug!(target: "sync", "Bad orphan block {:?} : {:?}", hash, e);
+					}
+				}
+			}
+		}
+	}
+
 	pub fn on_packet(&mut self, io: &mut dyn SyncIo, peer: PeerId, packet_id: u8, data: &[u8]) {
 		SyncHandler::on_packet(self, io, peer, packet_id, data);
 	}
@@ -1494,11 +1716,11 @@ pub mod tests {
 
 	use super::{
 		BlockId, BlockQueueInfo, ChainSync, ClientVersion, PeerInfo, PeerAsking,
-		SyncHandler, SyncState, SyncStatus, SyncPropagator, UnverifiedTransaction
+		SyncHandler, SyncRequester, SyncState, SyncStatus, SyncPropagator, UnverifiedTransaction
 	};
 
 	use crate::{
-		api::SyncConfig,
+		api::{SyncConfig, WarpPin},
 		tests::{helpers::TestIo, snapshot::TestSnapshotService},
 	};
 
@@ -1506,7 +1728,7 @@ pub mod tests {
 	use client_traits::{BlockInfo, BlockChainClient, ChainInfo};
 	use ethcore::test_helpers::{EachBlockWith, TestBlockChainClient};
 	use ethcore::miner::{MinerService, PendingOrdering};
-	use ethereum_types::{H256, U256, Address};
+	use ethereum_types::{H256, H512, U256, Address};
 	use network::PeerId;
 	use parking_lot::RwLock;
 	use rlp::{Rlp, RlpStream};
@@ -1558,7 +1780,8 @@ pub mod tests {
 			verifying_queue_size: 0,
 			max_queue_size: 1000,
 			max_mem_use: 1000,
-			mem_used: 500
+			mem_used: 500,
+			verification_time: Default::default(),
 		}
 	}
 
@@ -1614,6 +1837,12 @@ pub mod tests {
 				asking_private_state: None,
 				ask_time: Instant::now(),
 				last_sent_transactions: Default::default(),
+				pooled_transactions: false,
+				unfetched_pooled_transactions: Default::default(),
+				pending_requests: Default::default(),
+				request_deferred: false,
+				asking_pooled_transactions: Vec::new(),
+				pooled_transactions_ask_time: Instant::now(),
 				last_sent_private_transactions: Default::default(),
 				expired: false,
 				private_tx_enabled: false,
@@ -1675,6 +1904,27 @@ pub mod tests {
 		assert!(result.is_ok());
 	}
 
+	#[test]
+	fn warp_pin_restricts_snapshots() {
+		let mut client = TestBlockChainClient::new();
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(H256::zero(), &client);
+		let ss = TestSnapshotService::new();
+		let io = TestIo::new(&mut client, &ss, &queue, None, None);
+		let pinned = H256::from_low_u64_be(1);
+		let other = H256::from_low_u64_be(2);
+
+		assert!(sync.is_warp_pin_allowed(&io, 0, &other));
+
+		sync.set_warp_pin(Some(WarpPin { manifest_hash: pinned, node: None }));
+		assert!(sync.is_warp_pin_allowed(&io, 0, &pinned));
+		assert!(!sync.is_warp_pin_allowed(&io, 0, &other));
+
+		// the session of the peer is unknown, so it can't be the pinned node
+		sync.set_warp_pin(Some(WarpPin { manifest_hash: pinned, node: Some(H512::from_low_u64_be(3)) }));
+		assert!(!sync.is_warp_pin_allowed(&io, 0, &pinned));
+	}
+
 	// idea is that what we produce when propagading latest block should be accepted in
 	// on_peer_new_block  in our code as well
 	#[test]
@@ -1745,6 +1995,32 @@ pub mod tests {
 		assert_eq!(client.miner.ready_transactions(&client, 10, PendingOrdering::Priority).len(), 1);
 	}
 
+	#[test]
+	fn defers_requests_dropped_by_full_outbound_queue() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+		let fork_hash = client.block_hash(BlockId::Number(5)).unwrap();
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		sync.fork_block = Some((5, fork_hash));
+		let mut io = TestIo::new(&mut client, &ss, &queue, None, None);
+
+		io.outbound_queue_full = true;
+		SyncRequester::request_fork_header(&mut sync, &mut io, 0, 5);
+		assert!(io.packets.is_empty());
+		assert!(io.to_disconnect.is_empty());
+		assert!(sync.peers[&0].request_deferred);
+
+		// the peer is asked again once the queue drained
+		io.outbound_queue_full = false;
+		sync.maintain_peers(&mut io);
+		assert!(io.to_disconnect.is_empty());
+		assert!(!sync.peers[&0].request_deferred);
+		assert_eq!(sync.peers[&0].asking, PeerAsking::ForkHeader);
+		assert_eq!(io.packets.len(), 1);
+	}
+
 	#[test]
 	fn should_not_add_transactions_to_queue_if_not_synced() {
 		// given
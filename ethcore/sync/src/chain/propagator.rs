@@ -16,6 +16,7 @@
 
 use std::cmp;
 use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::{sync_io::SyncIo, chain::sync_packet::SyncPacket};
 
@@ -34,12 +35,14 @@ use super::sync_packet::SyncPacket::{
 	TransactionsPacket,
 	NewBlockPacket,
 	ConsensusDataPacket,
+	NewPooledTransactionHashesPacket,
 };
 
 use super::{
 	random,
 	ChainSync,
 	MAX_TRANSACTION_PACKET_SIZE,
+	MAX_TRANSACTION_HASHES_TO_ANNOUNCE,
 	MAX_PEER_LAG_PROPAGATION,
 	MAX_PEERS_PROPAGATION,
 	MIN_PEERS_PROPAGATION,
@@ -164,6 +167,7 @@ impl SyncPropagator {
 		};
 
 		let block_number = io.chain().chain_info().best_block_number;
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
 		let mut sent_to_peers = HashSet::new();
 		let mut max_sent = 0;
 
@@ -178,12 +182,41 @@ impl SyncPropagator {
 			let peer_info = sync.peers.get_mut(&peer_id)
 				.expect("peer_id is form peers; peers is result of select_peers_for_transactions; select_peers_for_transactions selects peers from self.peers; qed");
 
+			// Announce only hashes to peers which can request the transactions they don't know
+			if peer_info.pooled_transactions {
+				let to_announce = all_transactions_hashes.difference(&peer_info.last_sent_transactions)
+					.take(MAX_TRANSACTION_HASHES_TO_ANNOUNCE)
+					.cloned()
+					.collect::<H256FastSet>();
+				if to_announce.is_empty() {
+					continue;
+				}
+
+				let id = io.peer_session_info(peer_id).and_then(|info| info.id);
+				let mut packet = RlpStream::new_list(to_announce.len());
+				for hash in &to_announce {
+					stats.propagated(hash, id, block_number, now);
+					packet.append(hash);
+				}
+				peer_info.last_sent_transactions = all_transactions_hashes
+					.intersection(&peer_info.last_sent_transactions)
+					.chain(&to_announce)
+					.cloned()
+					.collect();
+
+				SyncPropagator::send_packet(io, peer_id, NewPooledTransactionHashesPacket, packet.out());
+				trace!(target: "sync", "{:02} <- NewPooledTransactionHashes ({} entries)", peer_id, to_announce.len());
+				sent_to_peers.insert(peer_id);
+				max_sent = cmp::max(max_sent, to_announce.len());
+				continue;
+			}
+
 			// Send all transactions, if the peer doesn't know about anything
 			if peer_info.last_sent_transactions.is_empty() {
 				// update stats
 				for hash in &all_transactions_hashes {
 					let id = io.peer_session_info(peer_id).and_then(|info| info.id);
-					stats.propagated(hash, id, block_number);
+					stats.propagated(hash, id, block_number, now);
 				}
 				peer_info.last_sent_transactions = all_transactions_hashes.clone();
 
@@ -230,7 +263,7 @@ impl SyncPropagator {
 			let id = io.peer_session_info(peer_id).and_then(|info| info.id);
 			for hash in &to_send {
 				// update stats
-				stats.propagated(hash, id, block_number);
+				stats.propagated(hash, id, block_number, now);
 			}
 
 			peer_info.last_sent_transactions = all_transactions_hashes
@@ -318,7 +351,8 @@ impl SyncPropagator {
 		sync.peers.keys()
 			.cloned()
 			.filter(filter)
-			.filter(|_| small || random.next_u32() < fraction)
+			// announcements are cheap, so peers accepting them always get the hashes
+			.filter(|peer_id| small || sync.peers[peer_id].pooled_transactions || random.next_u32() < fraction)
 			.take(MAX_PEERS_PROPAGATION)
 			.collect()
 	}
@@ -438,6 +472,12 @@ mod tests {
 				asking_private_state: None,
 				ask_time: Instant::now(),
 				last_sent_transactions: Default::default(),
+				pooled_transactions: false,
+				unfetched_pooled_transactions: Default::default(),
+				pending_requests: Default::default(),
+				request_deferred: false,
+				asking_pooled_transactions: Vec::new(),
+				pooled_transactions_ask_time: Instant::now(),
 				last_sent_private_transactions: Default::default(),
 				expired: false,
 				private_tx_enabled: false,
@@ -485,6 +525,28 @@ mod tests {
 		assert_eq!(0x02, io.packets[0].packet_id);
 	}
 
+	#[test]
+	fn announces_transaction_hashes_to_capable_peers() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let hash = client.insert_transaction_to_queue();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		sync.peers.get_mut(&0).unwrap().pooled_transactions = true;
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None, None);
+		let peer_count = SyncPropagator::propagate_new_transactions(&mut sync, &mut io, || true);
+		// Already announced transactions are not announced again
+		let peer_count2 = SyncPropagator::propagate_new_transactions(&mut sync, &mut io, || true);
+
+		assert_eq!(1, peer_count);
+		assert_eq!(0, peer_count2);
+		assert_eq!(1, io.packets.len());
+		// NEW_POOLED_TRANSACTION_HASHES_PACKET
+		assert_eq!(0x1a, io.packets[0].packet_id);
+		assert_eq!(Rlp::new(&io.packets[0].data).as_list::<H256>().unwrap(), vec![hash]);
+	}
+
 	#[test]
 	fn does_not_propagate_new_transactions_after_new_block() {
 		let mut client = TestBlockChainClient::new();
@@ -23,8 +23,9 @@ use crate::{
 
 use bytes::Bytes;
 use ethereum_types::H256;
+use fastmap::H256FastSet;
 use log::{debug, trace, warn};
-use network::{PeerId};
+use network::{self, PeerId};
 use rlp::RlpStream;
 use common_types::BlockNumber;
 
@@ -36,12 +37,16 @@ use super::sync_packet::SyncPacket::{
 	GetSnapshotManifestPacket,
 	GetSnapshotDataPacket,
 	GetPrivateStatePacket,
+	GetPooledTransactionsPacket,
 };
 
 use super::{
 	BlockSet,
 	ChainSync,
 	PeerAsking,
+	MAX_POOLED_TRANSACTIONS_TO_REQUEST,
+	uses_request_id,
+	with_request_id,
 };
 
 /// The Chain Sync Requester: requesting data to other peers
@@ -113,6 +118,49 @@ impl SyncRequester {
 		peer.asking_private_state = Some(hash.clone());
 	}
 
+	/// Request transactions announced by a peer which are neither known nor being requested from other peers.
+	pub fn request_pooled_transactions(sync: &mut ChainSync, io: &mut dyn SyncIo, peer_id: PeerId) {
+		let in_flight = sync.peers.values()
+			.flat_map(|p| p.asking_pooled_transactions.iter().cloned())
+			.collect::<H256FastSet>();
+		let peer = match sync.peers.get_mut(&peer_id) {
+			Some(peer) if peer.asking_pooled_transactions.is_empty() => peer,
+			_ => return,
+		};
+
+		peer.unfetched_pooled_transactions.retain(|hash| io.chain().pooled_transaction(hash).is_none());
+		let hashes = peer.unfetched_pooled_transactions.iter()
+			.filter(|hash| !in_flight.contains(hash))
+			.take(MAX_POOLED_TRANSACTIONS_TO_REQUEST)
+			.cloned()
+			.collect::<Vec<_>>();
+		if hashes.is_empty() {
+			return;
+		}
+
+		trace!(target: "sync", "{} <- GetPooledTransactions: {} entries", peer_id, hashes.len());
+		let mut rlp = RlpStream::new_list(hashes.len());
+		for hash in &hashes {
+			peer.unfetched_pooled_transactions.remove(hash);
+			rlp.append(hash);
+		}
+		peer.asking_pooled_transactions = hashes;
+		peer.pooled_transactions_ask_time = Instant::now();
+
+		match io.send(peer_id, GetPooledTransactionsPacket, rlp.out()) {
+			Ok(()) => {},
+			Err(network::Error::OutboundQueueFull) => {
+				// our own outbound queue is backed up, ask for the transactions later
+				trace!(target: "sync", "{}: Outbound queue is full, deferring GetPooledTransactions", peer_id);
+				peer.unfetched_pooled_transactions.extend(peer.asking_pooled_transactions.drain(..));
+			},
+			Err(e) => {
+				debug!(target:"sync", "Error sending request: {:?}", e);
+				io.disconnect_peer(peer_id);
+			},
+		}
+	}
+
 	/// Request headers from a peer by block hash
 	fn request_headers_by_hash(sync: &mut ChainSync, io: &mut dyn SyncIo, peer_id: PeerId, h: &H256, count: u64, skip: u64, reverse: bool, set: BlockSet) {
 		trace!(target: "sync", "{} <- GetBlockHeaders: {} entries starting from {}, set = {:?}", peer_id, count, h, set);
@@ -154,14 +202,29 @@ impl SyncRequester {
 			if peer.asking != PeerAsking::Nothing {
 				warn!(target:"sync", "Asking {:?} while requesting {:?}", peer.asking, asking);
 			}
+			let packet = if uses_request_id(io, peer_id, packet_id) {
+				sync.last_request_id += 1;
+				// a newer request of the same kind replaces the previous one
+				peer.pending_requests.retain(|_, pending| *pending != asking);
+				peer.pending_requests.insert(sync.last_request_id, asking.clone());
+				with_request_id(sync.last_request_id, &packet)
+			} else {
+				packet
+			};
 			peer.asking = asking;
 			peer.ask_time = Instant::now();
 
-			let result = io.send(peer_id, packet_id, packet);
-
-			if let Err(e) = result {
-				debug!(target:"sync", "Error sending request: {:?}", e);
-				io.disconnect_peer(peer_id);
+			match io.send(peer_id, packet_id, packet) {
+				Ok(()) => {},
+				Err(network::Error::OutboundQueueFull) => {
+					// the peer did nothing wrong, the request is asked again on the next `maintain_peers`
+					trace!(target: "sync", "{}: Outbound queue is full, deferring {:?} request", peer_id, peer.asking);
+					peer.request_deferred = true;
+				},
+				Err(e) => {
+					debug!(target:"sync", "Error sending request: {:?}", e);
+					io.disconnect_peer(peer_id);
+				},
 			}
 		}
 	}
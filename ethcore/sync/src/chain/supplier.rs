@@ -47,11 +47,14 @@ use super::sync_packet::SyncPacket::{
 	ConsensusDataPacket,
 	GetPrivateStatePacket,
 	PrivateStatePacket,
+	GetPooledTransactionsPacket,
+	PooledTransactionsPacket,
 };
 
 use super::{
 	ChainSync,
 	SyncHandler,
+	RequestId,
 	RlpResponseResult,
 	PacketDecodeError,
 	MAX_BODIES_TO_SEND,
@@ -59,7 +62,10 @@ use super::{
 	MAX_NODE_DATA_TO_SEND,
 	MAX_NODE_DATA_TOTAL_DURATION,
 	MAX_NODE_DATA_SINGLE_DURATION,
+	MAX_POOLED_TRANSACTIONS_TO_SEND,
 	MAX_RECEIPTS_HEADERS_TO_SEND,
+	uses_request_id,
+	with_request_id,
 };
 
 /// The Chain Sync Supplier: answers requests from peers with available data
@@ -75,40 +81,45 @@ impl SyncSupplier {
 		if let Some(id) = SyncPacket::from_u8(packet_id) {
 			let result = match id {
 				GetBlockBodiesPacket => SyncSupplier::return_rlp(
-					io, &rlp, peer,
+					io, &rlp, peer, id,
 					SyncSupplier::return_block_bodies,
 					|e| format!("Error sending block bodies: {:?}", e)),
 
 				GetBlockHeadersPacket => SyncSupplier::return_rlp(
-					io, &rlp, peer,
+					io, &rlp, peer, id,
 					SyncSupplier::return_block_headers,
 					|e| format!("Error sending block headers: {:?}", e)),
 
 				GetReceiptsPacket => SyncSupplier::return_rlp(
-					io, &rlp, peer,
+					io, &rlp, peer, id,
 					SyncSupplier::return_receipts,
 					|e| format!("Error sending receipts: {:?}", e)),
 
 				GetNodeDataPacket => SyncSupplier::return_rlp(
-					io, &rlp, peer,
+					io, &rlp, peer, id,
 					SyncSupplier::return_node_data,
 					|e| format!("Error sending nodes: {:?}", e)),
 
 				GetSnapshotManifestPacket => SyncSupplier::return_rlp(
-					io, &rlp, peer,
+					io, &rlp, peer, id,
 					SyncSupplier::return_snapshot_manifest,
 					|e| format!("Error sending snapshot manifest: {:?}", e)),
 
 				GetSnapshotDataPacket => SyncSupplier::return_rlp(
-					io, &rlp, peer,
+					io, &rlp, peer, id,
 					SyncSupplier::return_snapshot_data,
 					|e| format!("Error sending snapshot data: {:?}", e)),
 
 				GetPrivateStatePacket => SyncSupplier::return_rlp(
-					io, &rlp, peer,
+					io, &rlp, peer, id,
 					SyncSupplier::return_private_state,
 					|e| format!("Error sending private state data: {:?}", e)),
 
+				GetPooledTransactionsPacket => SyncSupplier::return_rlp(
+					io, &rlp, peer, id,
+					SyncSupplier::return_pooled_transactions,
+					|e| format!("Error sending pooled transactions: {:?}", e)),
+
 				StatusPacket => {
 					sync.write().on_packet(io, peer, packet_id, data);
 					Ok(())
@@ -130,10 +141,8 @@ impl SyncSupplier {
 								let sync_ro = sync.read();
 								SyncHandler::on_peer_transactions(&*sync_ro, io, peer, &rlp)
 							};
-							if res.is_err() {
-								// peer sent invalid data, disconnect.
-								io.disable_peer(peer);
-								sync.write().deactivate_peer(io, peer);
+							if let Err(e) = res {
+								sync.write().on_malformed_packet(io, peer, packet_id, &e);
 							}
 						},
 						_ => {
@@ -145,9 +154,11 @@ impl SyncSupplier {
 				}
 			};
 
-			result.unwrap_or_else(|e| {
-				debug!(target:"sync", "{} -> Malformed packet {} : {}", peer, packet_id, e);
-			})
+			if let Err(e) = result {
+				sync.write().on_malformed_packet(io, peer, packet_id, &e);
+			}
+		} else {
+			sync.write().on_malformed_packet(io, peer, packet_id, &PacketDecodeError::UnknownPacket(packet_id));
 		}
 	}
 
@@ -235,7 +246,7 @@ impl SyncSupplier {
 	/// Respond to GetBlockBodies request
 	fn return_block_bodies(io: &dyn SyncIo, r: &Rlp, peer_id: PeerId) -> RlpResponseResult {
 		let payload_soft_limit = io.payload_soft_limit();
-		let mut count = r.item_count().unwrap_or(0);
+		let mut count = r.item_count()?;
 		if count == 0 {
 			debug!(target: "sync", "Empty GetBlockBodies request, ignoring.");
 			return Ok(None);
@@ -262,7 +273,7 @@ impl SyncSupplier {
 	/// Respond to GetNodeData request
 	fn return_node_data(io: &dyn SyncIo, r: &Rlp, peer_id: PeerId) -> RlpResponseResult {
 		let payload_soft_limit = io.payload_soft_limit(); // 4Mb
-		let mut count = r.item_count().unwrap_or(0);
+		let mut count = r.item_count()?;
 		trace!(target: "sync", "{} -> GetNodeData: {} entries requested", peer_id, count);
 		if count == 0 {
 			debug!(target: "sync", "Empty GetNodeData request, ignoring.");
@@ -304,7 +315,7 @@ impl SyncSupplier {
 
 	fn return_receipts(io: &dyn SyncIo, rlp: &Rlp, peer_id: PeerId) -> RlpResponseResult {
 		let payload_soft_limit = io.payload_soft_limit();
-		let mut count = rlp.item_count().unwrap_or(0);
+		let mut count = rlp.item_count()?;
 		trace!(target: "sync", "{} -> GetReceipts: {} entries", peer_id, count);
 		if count == 0 {
 			debug!(target: "sync", "Empty GetReceipts request, ignoring.");
@@ -330,7 +341,7 @@ impl SyncSupplier {
 
 	/// Respond to GetSnapshotManifest request
 	fn return_snapshot_manifest(io: &dyn SyncIo, r: &Rlp, peer_id: PeerId) -> RlpResponseResult {
-		let count = r.item_count().unwrap_or(0);
+		let count = r.item_count()?;
 		trace!(target: "warp", "{} -> GetSnapshotManifest", peer_id);
 		if count != 0 {
 			debug!(target: "warp", "Invalid GetSnapshotManifest request, ignoring.");
@@ -390,15 +401,45 @@ impl SyncSupplier {
 		})
 	}
 
-	fn return_rlp<FRlp, FError>(io: &mut dyn SyncIo, rlp: &Rlp, peer: PeerId, rlp_func: FRlp, error_func: FError) -> Result<(), PacketDecodeError>
+	/// Respond to GetPooledTransactions request
+	fn return_pooled_transactions(io: &dyn SyncIo, rlp: &Rlp, peer_id: PeerId) -> RlpResponseResult {
+		let payload_soft_limit = io.payload_soft_limit();
+		let count = cmp::min(rlp.item_count()?, MAX_POOLED_TRANSACTIONS_TO_SEND);
+		trace!(target: "sync", "{} -> GetPooledTransactions: {} entries", peer_id, count);
+		let mut added = 0usize;
+		let mut data = Bytes::new();
+		for i in 0..count {
+			if let Some(tx) = io.chain().pooled_transaction(&rlp.val_at::<H256>(i)?) {
+				let mut tx_bytes = ::rlp::encode(tx.signed());
+				if data.len() + tx_bytes.len() > payload_soft_limit { break; }
+				data.append(&mut tx_bytes);
+				added += 1;
+			}
+		}
+		let mut rlp_result = RlpStream::new_list(added);
+		rlp_result.append_raw(&data, added);
+		Ok(Some((PooledTransactionsPacket.id(), rlp_result)))
+	}
+
+	fn return_rlp<FRlp, FError>(io: &mut dyn SyncIo, rlp: &Rlp, peer: PeerId, request: SyncPacket, rlp_func: FRlp, error_func: FError) -> Result<(), PacketDecodeError>
 		where FRlp : Fn(&dyn SyncIo, &Rlp, PeerId) -> RlpResponseResult,
 			FError : FnOnce(network::Error) -> String
 	{
-		let response = rlp_func(io, rlp, peer);
+		// eth/66 requests are answered with the request id they came with
+		let (request_id, rlp) = if uses_request_id(io, peer, request) {
+			(Some(rlp.val_at::<RequestId>(0)?), rlp.at(1)?)
+		} else {
+			(None, rlp.clone())
+		};
+		let response = rlp_func(io, &rlp, peer);
 		match response {
 			Err(e) => Err(e),
 			Ok(Some((packet_id, rlp_stream))) => {
-				io.respond(packet_id, rlp_stream.out()).unwrap_or_else(
+				let data = match request_id {
+					Some(request_id) => with_request_id(request_id, &rlp_stream.out()),
+					None => rlp_stream.out(),
+				};
+				io.respond(packet_id, data).unwrap_or_else(
 					|e| debug!(target: "sync", "{:?}", error_func(e)));
 				Ok(())
 			}
@@ -418,19 +459,43 @@ mod test {
 	};
 
 	use super::{
-		SyncPacket::{GetReceiptsPacket, GetNodeDataPacket},
+		SyncPacket::{GetBlockBodiesPacket, GetReceiptsPacket, GetNodeDataPacket, PooledTransactionsPacket},
 		BlockNumber, BlockId, SyncSupplier, PacketInfo
 	};
 
-	use super::super::tests::dummy_sync_with_peer;
+	use super::super::{MAX_MALFORMED_PACKETS, ETH_PROTOCOL_VERSION_66, tests::dummy_sync_with_peer};
 
 	use bytes::Bytes;
 	use client_traits::BlockChainClient;
 	use ethcore::test_helpers::{EachBlockWith, TestBlockChainClient};
 	use ethereum_types::H256;
+	use keccak_hash::keccak;
 	use parking_lot::RwLock;
 	use rlp::{Rlp, RlpStream};
 
+	#[test]
+	fn return_pooled_transactions() {
+		let mut client = TestBlockChainClient::new();
+		let hash = client.insert_transaction_to_queue();
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let io = TestIo::new(&mut client, &ss, &queue, None, None);
+
+		let mut request = RlpStream::new_list(2);
+		request.append(&hash);
+		request.append(&H256::zero());
+		let (packet_id, response) = SyncSupplier::return_pooled_transactions(&io, &Rlp::new(&request.out()), 0)
+			.unwrap()
+			.unwrap();
+		let response = response.out();
+		let response = Rlp::new(&response);
+
+		// only known transactions are returned
+		assert_eq!(PooledTransactionsPacket.id(), packet_id);
+		assert_eq!(1, response.item_count().unwrap());
+		assert_eq!(hash, keccak(response.at(0).unwrap().as_raw()));
+	}
+
 	#[test]
 	fn return_block_headers() {
 		fn make_hash_req(h: &H256, count: usize, skip: usize, reverse: bool) -> Bytes {
@@ -606,4 +671,46 @@ mod test {
 		SyncSupplier::dispatch_packet(&RwLock::new(sync), &mut io, 0usize, GetReceiptsPacket.id(), &receipts_request);
 		assert_eq!(1, io.packets.len());
 	}
+
+	#[test]
+	fn answers_eth66_requests_with_request_id() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let hash = client.block_hash(BlockId::Number(1)).unwrap();
+		let queue = RwLock::new(VecDeque::new());
+		let sync = dummy_sync_with_peer(H256::zero(), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, Some(0), None);
+		io.eth_protocol_version = ETH_PROTOCOL_VERSION_66.0;
+
+		let mut request = RlpStream::new_list(2);
+		request.append(&42u64);
+		request.begin_list(1).append(&hash);
+		SyncSupplier::dispatch_packet(&RwLock::new(sync), &mut io, 0usize, GetBlockBodiesPacket.id(), &request.out());
+
+		assert_eq!(1, io.packets.len());
+		let response = Rlp::new(&io.packets[0].data);
+		assert_eq!(response.val_at::<u64>(0), Ok(42));
+		assert_eq!(response.at(1).and_then(|bodies| bodies.item_count()), Ok(1));
+	}
+
+	#[test]
+	fn disable_peer_after_malformed_packets() {
+		let mut client = TestBlockChainClient::new();
+		let queue = RwLock::new(VecDeque::new());
+		let sync = RwLock::new(dummy_sync_with_peer(H256::zero(), &client));
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None, None);
+
+		// not a list of hashes
+		let request = [0x80];
+		for _ in 0..MAX_MALFORMED_PACKETS {
+			SyncSupplier::dispatch_packet(&sync, &mut io, 0usize, GetBlockBodiesPacket.id(), &request);
+		}
+		assert!(io.packets.is_empty());
+		assert!(io.to_disconnect.is_empty());
+
+		SyncSupplier::dispatch_packet(&sync, &mut io, 0usize, GetBlockBodiesPacket.id(), &request);
+		assert!(io.to_disconnect.contains(&0));
+	}
 }
@@ -60,9 +60,28 @@ enum_from_primitive! {
 		SignedPrivateTransactionPacket = 0x17,
 		GetPrivateStatePacket = 0x18,
 		PrivateStatePacket = 0x19,
+		NewPooledTransactionHashesPacket = 0x1a,
+		GetPooledTransactionsPacket = 0x1b,
+		PooledTransactionsPacket = 0x1c,
 	}
 }
 
+impl SyncPacket {
+	/// Whether the packet is a request or a response which eth/66 prefixes with a request id.
+	pub fn has_request_id(&self) -> bool {
+		match self {
+			GetBlockHeadersPacket |
+			BlockHeadersPacket |
+			GetBlockBodiesPacket |
+			BlockBodiesPacket |
+			GetNodeDataPacket |
+			NodeDataPacket |
+			GetReceiptsPacket |
+			ReceiptsPacket => true,
+			_ => false,
+		}
+	}
+}
 
 /// Provide both subprotocol and packet id information within the
 /// same object.
@@ -103,7 +122,10 @@ impl PacketInfo for SyncPacket {
 			PrivateTransactionPacket |
 			SignedPrivateTransactionPacket |
 			GetPrivateStatePacket |
-			PrivateStatePacket
+			PrivateStatePacket |
+			NewPooledTransactionHashesPacket |
+			GetPooledTransactionsPacket |
+			PooledTransactionsPacket
 
 				=> WARP_SYNC_PROTOCOL_ID,
 		}
@@ -141,6 +163,14 @@ mod tests {
 		assert_eq!(StatusPacket.protocol(), ETH_PROTOCOL);
 	}
 
+	#[test]
+	fn only_eth_requests_and_responses_have_request_id() {
+		assert!(GetBlockHeadersPacket.has_request_id());
+		assert!(ReceiptsPacket.has_request_id());
+		assert!(!NewBlockPacket.has_request_id());
+		assert!(!GetSnapshotDataPacket.has_request_id());
+	}
+
 	#[test]
 	fn when_consensus_data_packet_then_id_and_protocol_match() {
 		assert_eq!(ConsensusDataPacket.id(), ConsensusDataPacket as PacketId);
@@ -30,6 +30,7 @@ mod blocks;
 mod block_sync;
 mod sync_io;
 mod private_tx;
+mod orphan_blocks;
 mod snapshot_sync;
 mod transactions_stats;
 
@@ -41,5 +42,5 @@ mod tests;
 pub use api::*;
 pub use chain::{SyncStatus, SyncState};
 pub use devp2p::validate_node_url;
-pub use network::{NonReservedPeerMode, Error, ConnectionFilter, ConnectionDirection};
+pub use network::{NonReservedPeerMode, Error, ConnectionFilter, ConnectionDirection, DialPolicy, BandwidthPolicy, PacketTraffic, TrafficSample};
 pub use private_tx::{PrivateTxHandler, NoopPrivateTxHandler, SimplePrivateTxHandler};
@@ -0,0 +1,225 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pool of recently announced blocks whose parent is not known yet.
+//!
+//! Blocks received via `NewBlock` before their parent are kept here (up to a bounded
+//! count) instead of being dropped. Once the parent gets imported the orphans are
+//! handed back to the caller to be queued for import.
+
+use std::collections::VecDeque;
+
+use bytes::Bytes;
+use ethereum_types::H256;
+use fastmap::H256FastMap;
+use common_types::BlockNumber;
+
+/// Maximum number of orphan blocks kept in the pool.
+pub const MAX_ORPHAN_BLOCKS: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, MallocSizeOf)]
+struct OrphanBlock {
+	parent: H256,
+	number: BlockNumber,
+	bytes: Bytes,
+}
+
+/// Bounded set of blocks waiting for their parent.
+#[derive(Debug, MallocSizeOf)]
+pub struct OrphanBlockPool {
+	/// Orphan blocks by their hash.
+	blocks: H256FastMap<OrphanBlock>,
+	/// Orphan block hashes by parent hash.
+	by_parent: H256FastMap<Vec<H256>>,
+	/// Insertion order, used for eviction.
+	order: VecDeque<H256>,
+	/// Maximum number of blocks to keep.
+	limit: usize,
+}
+
+impl Default for OrphanBlockPool {
+	fn default() -> Self {
+		OrphanBlockPool::new(MAX_ORPHAN_BLOCKS)
+	}
+}
+
+impl OrphanBlockPool {
+	/// Create a new pool holding at most `limit` blocks.
+	pub fn new(limit: usize) -> Self {
+		OrphanBlockPool {
+			blocks: Default::default(),
+			by_parent: Default::default(),
+			order: VecDeque::new(),
+			limit,
+		}
+	}
+
+	/// Number of blocks in the pool.
+	#[cfg(test)]
+	pub fn len(&self) -> usize {
+		self.blocks.len()
+	}
+
+	/// Returns true if there are no orphans.
+	pub fn is_empty(&self) -> bool {
+		self.blocks.is_empty()
+	}
+
+	/// Returns true if the block with given hash is kept in the pool.
+	#[cfg(test)]
+	pub fn contains(&self, hash: &H256) -> bool {
+		self.blocks.contains_key(hash)
+	}
+
+	/// Insert an orphan block. Evicts the oldest orphan if the pool is full.
+	/// Returns `false` if the block was already known.
+	pub fn insert(&mut self, hash: H256, parent: H256, number: BlockNumber, bytes: Bytes) -> bool {
+		if self.limit == 0 || self.blocks.contains_key(&hash) {
+			return false;
+		}
+		while self.blocks.len() >= self.limit {
+			match self.order.pop_front() {
+				Some(oldest) => { self.remove(&oldest); },
+				None => break,
+			}
+		}
+		self.by_parent.entry(parent).or_insert_with(Vec::new).push(hash);
+		self.blocks.insert(hash, OrphanBlock { parent, number, bytes });
+		self.order.push_back(hash);
+		true
+	}
+
+	/// Remove and return all orphans that are direct children of `parent`, ordered by number.
+	pub fn take_children(&mut self, parent: &H256) -> Vec<(H256, Bytes)> {
+		let hashes = match self.by_parent.remove(parent) {
+			Some(hashes) => hashes,
+			None => return Vec::new(),
+		};
+		self.order.retain(|h| !hashes.contains(h));
+		let mut children: Vec<_> = hashes.into_iter()
+			.filter_map(|hash| self.blocks.remove(&hash).map(|block| (block.number, hash, block.bytes)))
+			.collect();
+		children.sort_by_key(|&(number, _, _)| number);
+		children.into_iter().map(|(_, hash, bytes)| (hash, bytes)).collect()
+	}
+
+	/// Returns the unknown ancestor hashes that orphans are waiting for.
+	#[cfg(test)]
+	pub fn missing_parents(&self) -> Vec<H256> {
+		self.by_parent.keys()
+			.filter(|parent| !self.blocks.contains_key(parent))
+			.cloned()
+			.collect()
+	}
+
+	/// Drop all orphans with number lower or equal to `number`.
+	pub fn prune(&mut self, number: BlockNumber) {
+		let stale: Vec<_> = self.blocks.iter()
+			.filter(|&(_, block)| block.number <= number)
+			.map(|(hash, _)| *hash)
+			.collect();
+		for hash in stale {
+			self.remove(&hash);
+		}
+	}
+
+	/// Remove all orphans.
+	#[cfg(test)]
+	pub fn clear(&mut self) {
+		self.blocks.clear();
+		self.by_parent.clear();
+		self.order.clear();
+	}
+
+	fn remove(&mut self, hash: &H256) {
+		if let Some(block) = self.blocks.remove(hash) {
+			let now_empty = match self.by_parent.get_mut(&block.parent) {
+				Some(siblings) => {
+					siblings.retain(|h| h != hash);
+					siblings.is_empty()
+				},
+				None => false,
+			};
+			if now_empty {
+				self.by_parent.remove(&block.parent);
+			}
+			self.order.retain(|h| h != hash);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{OrphanBlockPool, H256};
+
+	fn hash(n: u64) -> H256 {
+		H256::from_low_u64_be(n)
+	}
+
+	#[test]
+	fn should_return_children_of_imported_parent() {
+		// given
+		let mut pool = OrphanBlockPool::new(10);
+		pool.insert(hash(3), hash(2), 3, vec![3]);
+		pool.insert(hash(4), hash(3), 4, vec![4]);
+		pool.insert(hash(5), hash(2), 3, vec![5]);
+
+		// when
+		let children = pool.take_children(&hash(2));
+
+		// then
+		assert_eq!(children.len(), 2);
+		assert_eq!(pool.len(), 1);
+		assert!(pool.contains(&hash(4)));
+		assert_eq!(pool.take_children(&hash(3)), vec![(hash(4), vec![4])]);
+		assert!(pool.is_empty());
+	}
+
+	#[test]
+	fn should_evict_oldest_when_full() {
+		// given
+		let mut pool = OrphanBlockPool::new(2);
+		pool.insert(hash(3), hash(2), 3, vec![]);
+		pool.insert(hash(4), hash(3), 4, vec![]);
+
+		// when
+		pool.insert(hash(5), hash(4), 5, vec![]);
+
+		// then
+		assert_eq!(pool.len(), 2);
+		assert!(!pool.contains(&hash(3)));
+		assert_eq!(pool.missing_parents(), vec![hash(3)]);
+	}
+
+	#[test]
+	fn should_prune_stale_orphans() {
+		// given
+		let mut pool = OrphanBlockPool::new(10);
+		pool.insert(hash(3), hash(2), 3, vec![]);
+		pool.insert(hash(10), hash(9), 10, vec![]);
+
+		// when
+		pool.prune(5);
+
+		// then
+		assert_eq!(pool.len(), 1);
+		assert!(pool.take_children(&hash(2)).is_empty());
+
+		pool.clear();
+		assert!(pool.is_empty());
+		assert!(pool.missing_parents().is_empty());
+	}
+}
@@ -73,6 +73,9 @@ pub struct TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 	pub to_disconnect: HashSet<PeerId>,
 	pub packets: Vec<TestPacket>,
 	pub peers_info: HashMap<PeerId, String>,
+	pub eth_protocol_version: u8,
+	pub warp_protocol_version: u8,
+	pub outbound_queue_full: bool,
 	pub private_state_db: Option<Arc<PrivateStateDB>>,
 	overlay: RwLock<HashMap<BlockNumber, Bytes>>,
 }
@@ -93,6 +96,9 @@ impl<'p, C> TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 			to_disconnect: HashSet::new(),
 			packets: Vec::new(),
 			peers_info: HashMap::new(),
+			eth_protocol_version: ETH_PROTOCOL_VERSION_63.0,
+			warp_protocol_version: PAR_PROTOCOL_VERSION_4.0,
+			outbound_queue_full: false,
 			private_state_db,
 			overlay: RwLock::new(HashMap::new()),
 		}
@@ -122,6 +128,9 @@ impl<'p, C> SyncIo for TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 	}
 
 	fn send(&mut self,peer_id: PeerId, packet_id: SyncPacket, data: Vec<u8>) -> Result<(), network::Error> {
+		if self.outbound_queue_full {
+			return Err(network::Error::OutboundQueueFull);
+		}
 		self.packets.push(
 			TestPacket { data, packet_id: packet_id.id(), recipient: peer_id }
 		);
@@ -156,11 +165,11 @@ impl<'p, C> SyncIo for TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 	}
 
 	fn eth_protocol_version(&self, _peer: PeerId) -> u8 {
-		ETH_PROTOCOL_VERSION_63.0
+		self.eth_protocol_version
 	}
 
 	fn protocol_version(&self, protocol: &ProtocolId, peer_id: PeerId) -> u8 {
-		if protocol == &WARP_SYNC_PROTOCOL_ID { PAR_PROTOCOL_VERSION_4.0 } else { self.eth_protocol_version(peer_id) }
+		if protocol == &WARP_SYNC_PROTOCOL_ID { self.warp_protocol_version } else { self.eth_protocol_version(peer_id) }
 	}
 
 	fn is_expired(&self) -> bool {
@@ -243,11 +252,20 @@ pub struct EthPeer<C> where C: FlushingBlockChainClient {
 	pub queue: RwLock<VecDeque<TestPacket>>,
 	pub private_tx_handler: Arc<SimplePrivateTxHandler>,
 	pub io_queue: RwLock<VecDeque<ChainMessageType>>,
+	pub eth_protocol_version: u8,
+	pub warp_protocol_version: u8,
 	new_blocks_queue: RwLock<VecDeque<NewBlockMessage>>,
 	private_state_db: RwLock<Option<Arc<PrivateStateDB>>>,
 }
 
 impl<C> EthPeer<C> where C: FlushingBlockChainClient {
+	pub fn io(&self, sender: Option<PeerId>) -> TestIo<C> {
+		let mut io = TestIo::new(&*self.chain, &self.snapshot_service, &self.queue, sender, self.private_state_db());
+		io.eth_protocol_version = self.eth_protocol_version;
+		io.warp_protocol_version = self.warp_protocol_version;
+		io
+	}
+
 	fn is_io_queue_empty(&self) -> bool {
 		self.io_queue.read().is_empty()
 	}
@@ -257,7 +275,7 @@ impl<C> EthPeer<C> where C: FlushingBlockChainClient {
 	}
 
 	fn process_io_message(&self, message: ChainMessageType) {
-		let mut io = TestIo::new(&*self.chain, &self.snapshot_service, &self.queue, None, self.private_state_db());
+		let mut io = self.io(None);
 		match message {
 			ChainMessageType::Consensus(data) => self.sync.write().propagate_consensus_packet(&mut io, data),
 			ChainMessageType::PrivateTransaction(transaction_hash, data) =>
@@ -270,7 +288,7 @@ impl<C> EthPeer<C> where C: FlushingBlockChainClient {
 	}
 
 	fn process_new_block_message(&self, message: NewBlockMessage) {
-		let mut io = TestIo::new(&*self.chain, &self.snapshot_service, &self.queue, None, self.private_state_db());
+		let mut io = self.io(None);
 		self.sync.write().chain_new_blocks(
 			&mut io,
 			&message.imported,
@@ -297,22 +315,16 @@ impl<C: FlushingBlockChainClient> Peer for EthPeer<C> {
 
 	fn on_connect(&self, other: PeerId) {
 		self.sync.write().update_targets(&*self.chain);
-		self.sync.write().on_peer_connected(&mut TestIo::new(
-			&*self.chain,
-			&self.snapshot_service,
-			&self.queue,
-			Some(other),
-			self.private_state_db()),
-			other);
+		self.sync.write().on_peer_connected(&mut self.io(Some(other)), other);
 	}
 
 	fn on_disconnect(&self, other: PeerId) {
-		let mut io = TestIo::new(&*self.chain, &self.snapshot_service, &self.queue, Some(other), self.private_state_db());
+		let mut io = self.io(Some(other));
 		self.sync.write().on_peer_aborting(&mut io, other);
 	}
 
 	fn receive_message(&self, from: PeerId, msg: TestPacket) -> HashSet<PeerId> {
-		let mut io = TestIo::new(&*self.chain, &self.snapshot_service, &self.queue, Some(from), self.private_state_db());
+		let mut io = self.io(Some(from));
 		SyncSupplier::dispatch_packet(&self.sync, &mut io, from, msg.packet_id, &msg.data);
 		self.chain.flush();
 		io.to_disconnect.clone()
@@ -328,7 +340,7 @@ impl<C: FlushingBlockChainClient> Peer for EthPeer<C> {
 	}
 
 	fn sync_step(&self) {
-		let mut io = TestIo::new(&*self.chain, &self.snapshot_service, &self.queue, None, self.private_state_db());
+		let mut io = self.io(None);
 		self.chain.flush();
 		self.sync.write().maintain_peers(&mut io);
 		self.sync.write().maintain_sync(&mut io);
@@ -337,7 +349,7 @@ impl<C: FlushingBlockChainClient> Peer for EthPeer<C> {
 	}
 
 	fn restart_sync(&self) {
-		self.sync.write().restart(&mut TestIo::new(&*self.chain, &self.snapshot_service, &self.queue, None, self.private_state_db()));
+		self.sync.write().restart(&mut self.io(None));
 	}
 
 	fn process_all_io_messages(&self) {
@@ -393,6 +405,8 @@ impl TestNet<EthPeer<TestBlockChainClient>> {
 				queue: RwLock::new(VecDeque::new()),
 				private_tx_handler,
 				io_queue: RwLock::new(VecDeque::new()),
+				eth_protocol_version: ETH_PROTOCOL_VERSION_63.0,
+				warp_protocol_version: PAR_PROTOCOL_VERSION_4.0,
 				new_blocks_queue: RwLock::new(VecDeque::new()),
 				private_state_db: RwLock::new(None),
 			}));
@@ -447,6 +461,8 @@ impl TestNet<EthPeer<EthcoreClient>> {
 			queue: RwLock::new(VecDeque::new()),
 			private_tx_handler,
 			io_queue: RwLock::new(VecDeque::new()),
+			eth_protocol_version: ETH_PROTOCOL_VERSION_63.0,
+			warp_protocol_version: PAR_PROTOCOL_VERSION_4.0,
 			new_blocks_queue: RwLock::new(VecDeque::new()),
 			private_state_db: RwLock::new(None),
 		});
@@ -28,13 +28,17 @@ type NodeId = H512;
 #[derive(Debug, PartialEq, Clone, MallocSizeOf)]
 pub struct Stats {
 	first_seen: BlockNumber,
+	first_propagated_at: u64,
+	last_propagated_at: u64,
 	propagated_to: HashMap<NodeId, usize>,
 }
 
 impl Stats {
-	pub fn new(number: BlockNumber) -> Self {
+	pub fn new(number: BlockNumber, now: u64) -> Self {
 		Stats {
 			first_seen: number,
+			first_propagated_at: now,
+			last_propagated_at: now,
 			propagated_to: Default::default(),
 		}
 	}
@@ -44,6 +48,12 @@ impl<'a> From<&'a Stats> for TransactionStats {
 	fn from(other: &'a Stats) -> Self {
 		TransactionStats {
 			first_seen: other.first_seen,
+			first_propagated_at: other.first_propagated_at,
+			last_propagated_at: other.last_propagated_at,
+			rebroadcasts: other.propagated_to
+				.values()
+				.map(|count| count.saturating_sub(1))
+				.sum(),
 			propagated_to: other.propagated_to
 				.iter()
 				.map(|(hash, size)| (*hash, *size))
@@ -59,9 +69,11 @@ pub struct TransactionsStats {
 
 impl TransactionsStats {
 	/// Increases number of propagations to given `enodeid`.
-	pub fn propagated(&mut self, hash: &H256, enode_id: Option<NodeId>, current_block_num: BlockNumber) {
+	/// `now` is the current unix timestamp in seconds.
+	pub fn propagated(&mut self, hash: &H256, enode_id: Option<NodeId>, current_block_num: BlockNumber, now: u64) {
 		let enode_id = enode_id.unwrap_or_default();
-		let stats = self.pending_transactions.entry(*hash).or_insert_with(|| Stats::new(current_block_num));
+		let stats = self.pending_transactions.entry(*hash).or_insert_with(|| Stats::new(current_block_num, now));
+		stats.last_propagated_at = now;
 		let count = stats.propagated_to.entry(enode_id).or_insert(0);
 		*count = count.saturating_add(1);
 	}
@@ -104,14 +116,16 @@ mod tests {
 		let enodeid2 = NodeId::from_low_u64_be(5);
 
 		// when
-		stats.propagated(&hash, Some(enodeid1), 5);
-		stats.propagated(&hash, Some(enodeid1), 10);
-		stats.propagated(&hash, Some(enodeid2), 15);
+		stats.propagated(&hash, Some(enodeid1), 5, 100);
+		stats.propagated(&hash, Some(enodeid1), 10, 110);
+		stats.propagated(&hash, Some(enodeid2), 15, 120);
 
 		// then
 		let stats = stats.get(&hash);
 		assert_eq!(stats, Some(&Stats {
 			first_seen: 5,
+			first_propagated_at: 100,
+			last_propagated_at: 120,
 			propagated_to: hash_map![
 				enodeid1 => 2,
 				enodeid2 => 1
@@ -125,7 +139,7 @@ mod tests {
 		let mut stats = TransactionsStats::default();
 		let hash = H256::from_low_u64_be(5);
 		let enodeid1 = NodeId::from_low_u64_be(5);
-		stats.propagated(&hash, Some(enodeid1), 10);
+		stats.propagated(&hash, Some(enodeid1), 10, 100);
 
 		// when
 		stats.retain(&HashSet::new());
@@ -0,0 +1,225 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tracer logging the state accessed during execution.
+
+use std::sync::Arc;
+use ethereum_types::{U256, H256, Address, BigEndianHash};
+use evm::Instruction;
+use parking_lot::Mutex;
+use vm::{Error as VmError, ActionParams};
+use crate::{Tracer, VMTracer, RewardType};
+
+/// State read or written during execution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateAccess {
+	/// Account sent from, called, created or queried for its balance or code.
+	Account(Address),
+	/// Storage slot read.
+	StorageRead {
+		/// Account owning the storage.
+		address: Address,
+		/// Storage key.
+		key: H256,
+		/// Value read.
+		value: H256,
+	},
+	/// Storage slot written.
+	StorageWrite {
+		/// Account owning the storage.
+		address: Address,
+		/// Storage key.
+		key: H256,
+		/// Value written.
+		value: H256,
+	},
+}
+
+/// Execution frame of a call or create.
+#[derive(Default)]
+struct Frame {
+	stack: Vec<U256>,
+	instruction: u8,
+	/// Storage key read by the instruction being executed.
+	reading: Option<H256>,
+}
+
+#[derive(Default)]
+struct AccessLog {
+	accesses: Vec<StateAccess>,
+	/// Accounts whose storage is used by the calls being executed.
+	addresses: Vec<Address>,
+	frames: Vec<Frame>,
+}
+
+/// Logs every account and storage slot accessed during execution, in order of access.
+///
+/// Used both as the tracer and as the VM tracer of an execution: the VM tracer hooks
+/// see the storage accesses, the tracer hooks tell which account they belong to.
+/// Clones share the same log.
+#[derive(Default, Clone)]
+pub struct AccessTracer {
+	log: Arc<Mutex<AccessLog>>,
+}
+
+impl AccessTracer {
+	/// Returns the accesses logged so far.
+	pub fn accesses(&self) -> Vec<StateAccess> {
+		self.log.lock().accesses.clone()
+	}
+
+	fn enter(&mut self, params: &ActionParams, account: Address) {
+		let mut log = self.log.lock();
+		if log.addresses.is_empty() {
+			log.accesses.push(StateAccess::Account(params.sender));
+		}
+		log.accesses.push(StateAccess::Account(account));
+		log.addresses.push(params.address);
+	}
+
+	fn leave(&mut self) {
+		self.log.lock().addresses.pop();
+	}
+}
+
+impl Tracer for AccessTracer {
+	type Output = StateAccess;
+
+	fn prepare_trace_call(&mut self, params: &ActionParams, _depth: usize, _is_builtin: bool) {
+		self.enter(params, params.code_address);
+	}
+
+	fn prepare_trace_create(&mut self, params: &ActionParams) {
+		self.enter(params, params.address);
+	}
+
+	fn done_trace_call(&mut self, _gas_used: U256, _output: &[u8]) {
+		self.leave();
+	}
+
+	fn done_trace_create(&mut self, _gas_used: U256, _code: &[u8], _address: Address) {
+		self.leave();
+	}
+
+	fn done_trace_failed(&mut self, _error: &VmError) {
+		self.leave();
+	}
+
+	fn trace_suicide(&mut self, _address: Address, _balance: U256, refund_address: Address) {
+		self.log.lock().accesses.push(StateAccess::Account(refund_address));
+	}
+
+	fn trace_reward(&mut self, _author: Address, _value: U256, _reward_type: RewardType) {}
+
+	fn drain(self) -> Vec<StateAccess> {
+		self.accesses()
+	}
+}
+
+impl VMTracer for AccessTracer {
+	type Output = Vec<StateAccess>;
+
+	fn trace_next_instruction(&mut self, _pc: usize, _instruction: u8, _current_gas: U256) -> bool { true }
+
+	fn trace_prepare_execute(&mut self, _pc: usize, instruction: u8, _gas_cost: U256, _mem_written: Option<(usize, usize)>, store_written: Option<(U256, U256)>) {
+		let mut log = self.log.lock();
+		let log = &mut *log;
+		let address = *log.addresses.last().expect("instructions are executed within a call; qed");
+		let frame = log.frames.last_mut().expect("instructions are executed within a subtrace; qed");
+		frame.instruction = instruction;
+		let top = frame.stack.last().map(H256::from_uint);
+
+		match Instruction::from_u8(instruction) {
+			Some(Instruction::SLOAD) => frame.reading = top,
+			Some(Instruction::SSTORE) => if let Some((key, value)) = store_written {
+				log.accesses.push(StateAccess::StorageWrite {
+					address,
+					key: H256::from_uint(&key),
+					value: H256::from_uint(&value),
+				});
+			},
+			Some(Instruction::BALANCE) | Some(Instruction::EXTCODESIZE) |
+			Some(Instruction::EXTCODECOPY) | Some(Instruction::EXTCODEHASH) => if let Some(word) = top {
+				log.accesses.push(StateAccess::Account(Address::from(word)));
+			},
+			Some(Instruction::SELFBALANCE) => log.accesses.push(StateAccess::Account(address)),
+			_ => {},
+		}
+	}
+
+	fn trace_executed(&mut self, _gas_used: U256, stack_push: &[U256], _mem: &[u8]) {
+		let mut log = self.log.lock();
+		let log = &mut *log;
+		let address = log.addresses.last().cloned();
+		let frame = log.frames.last_mut().expect("instructions are executed within a subtrace; qed");
+
+		if let (Some(key), Some(address), Some(value)) = (frame.reading.take(), address, stack_push.first()) {
+			log.accesses.push(StateAccess::StorageRead { address, key, value: H256::from_uint(value) });
+		}
+
+		let args = Instruction::from_u8(frame.instruction).map_or(0, |i| i.info().args);
+		let len = frame.stack.len();
+		frame.stack.truncate(len.saturating_sub(args));
+		frame.stack.extend_from_slice(stack_push);
+	}
+
+	fn prepare_subtrace(&mut self, _code: &[u8]) {
+		self.log.lock().frames.push(Frame::default());
+	}
+
+	fn done_subtrace(&mut self) {
+		self.log.lock().frames.pop();
+	}
+
+	fn drain(self) -> Option<Vec<StateAccess>> {
+		Some(self.accesses())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_log_accesses_of_the_executing_account() {
+		let caller = Address::from_low_u64_be(1);
+		let callee = Address::from_low_u64_be(2);
+		let mut tracer = AccessTracer::default();
+		let mut vm_tracer = tracer.clone();
+		let params = ActionParams { sender: caller, address: callee, code_address: callee, ..Default::default() };
+
+		tracer.prepare_trace_call(&params, 0, false);
+		vm_tracer.prepare_subtrace(&[]);
+		// PUSH1 5, SLOAD, PUSH1 7, SSTORE
+		vm_tracer.trace_prepare_execute(0, Instruction::PUSH1 as u8, 3.into(), None, None);
+		vm_tracer.trace_executed(3.into(), &[5.into()], &[]);
+		vm_tracer.trace_prepare_execute(2, Instruction::SLOAD as u8, 800.into(), None, None);
+		vm_tracer.trace_executed(800.into(), &[9.into()], &[]);
+		vm_tracer.trace_prepare_execute(3, Instruction::PUSH1 as u8, 3.into(), None, None);
+		vm_tracer.trace_executed(3.into(), &[7.into()], &[]);
+		vm_tracer.trace_prepare_execute(5, Instruction::SSTORE as u8, 5000.into(), None, Some((7.into(), 9.into())));
+		vm_tracer.trace_executed(5000.into(), &[], &[]);
+		vm_tracer.done_subtrace();
+		tracer.done_trace_call(0.into(), &[]);
+
+		assert_eq!(tracer.drain(), vec![
+			StateAccess::Account(caller),
+			StateAccess::Account(callee),
+			StateAccess::StorageRead { address: callee, key: H256::from_low_u64_be(5), value: H256::from_low_u64_be(9) },
+			StateAccess::StorageWrite { address: callee, key: H256::from_low_u64_be(7), value: H256::from_low_u64_be(9) },
+		]);
+	}
+}
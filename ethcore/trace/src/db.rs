@@ -28,6 +28,7 @@ use ethereum_types::{H256, H264};
 use kvdb::DBTransaction;
 use parity_util_mem::MallocSizeOfExt;
 use parking_lot::RwLock;
+use rlp::Rlp;
 
 use crate::{
 	BlockNumber,
@@ -157,6 +158,44 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 		result
 	}
 
+	/// Returns up to `limit` traces of the block following the first `offset` ones, along with the
+	/// position of their transaction. Traces of blocks which aren't cached are read straight from
+	/// the database and only the ones within the page are decoded.
+	fn flat_traces_page(&self, block_hash: &H256, offset: usize, limit: usize) -> Option<Vec<(usize, FlatTrace)>> {
+		if let Some(traces) = self.traces.read().get(block_hash) {
+			self.note_trace_used(*block_hash);
+			return Some(traces.0.iter()
+				.enumerate()
+				.flat_map(|(tx_position, traces)| traces.0.iter().map(move |trace| (tx_position, trace)))
+				.skip(offset)
+				.take(limit)
+				.map(|(tx_position, trace)| (tx_position, trace.clone()))
+				.collect());
+		}
+
+		let key = Key::<FlatBlockTraces>::key(block_hash);
+		let bytes = self.db.key_value().get(db::COL_TRACE, key.as_bytes())
+			.expect("Low level database error. Some issue with disk?")?;
+		let mut skip = offset;
+		let mut page = Vec::new();
+		for (tx_position, traces) in Rlp::new(&bytes).iter().enumerate() {
+			if page.len() == limit {
+				break;
+			}
+			// whole transactions before the page are skipped without decoding their traces
+			let count = traces.item_count().expect("decode db value failed");
+			if skip >= count {
+				skip -= count;
+				continue;
+			}
+			for trace in traces.iter().skip(skip).take(limit - page.len()) {
+				page.push((tx_position, trace.as_val().expect("decode db value failed")));
+			}
+			skip = 0;
+		}
+		Some(page)
+	}
+
 	/// Returns vector of transaction traces for given block.
 	fn transactions_traces(&self, block_hash: &H256) -> Option<Vec<FlatTransactionTraces>> {
 		self.traces(block_hash).map(Into::into)
@@ -314,35 +353,30 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 			)
 	}
 
-	fn block_traces(&self, block_number: BlockNumber) -> Option<Vec<LocalizedTrace>> {
+	fn block_traces_page(&self, block_number: BlockNumber, offset: usize, limit: usize) -> Option<Vec<LocalizedTrace>> {
 		self.extras.block_hash(block_number)
-			.and_then(|block_hash| self.transactions_traces(&block_hash)
-				.map(|traces| {
-					traces.into_iter()
-						.map(Into::<Vec<FlatTrace>>::into)
-						.enumerate()
-						.flat_map(|(tx_position, traces)| {
-							let (trace_tx_number, trace_tx_hash) = match self.extras.transaction_hash(block_number, tx_position) {
-								Some(hash) => (Some(tx_position), Some(hash.clone())),
-								//None means trace without transaction (reward)
-								None => (None, None),
-							};
-
-							traces.into_iter()
-								.map(|trace| LocalizedTrace {
-									action: trace.action,
-									result: trace.result,
-									subtraces: trace.subtraces,
-									trace_address: trace.trace_address.into_iter().collect(),
-									transaction_number: trace_tx_number,
-									transaction_hash: trace_tx_hash,
-									block_number,
-									block_hash,
-								})
-								.collect::<Vec<LocalizedTrace>>()
-						})
-						.collect::<Vec<LocalizedTrace>>()
-				})
+			.and_then(|block_hash| self.flat_traces_page(&block_hash, offset, limit)
+				.map(|traces| traces.into_iter()
+					.map(|(tx_position, trace)| {
+						let (trace_tx_number, trace_tx_hash) = match self.extras.transaction_hash(block_number, tx_position) {
+							Some(hash) => (Some(tx_position), Some(hash)),
+							//None means trace without transaction (reward)
+							None => (None, None),
+						};
+
+						LocalizedTrace {
+							action: trace.action,
+							result: trace.result,
+							subtraces: trace.subtraces,
+							trace_address: trace.trace_address.into_iter().collect(),
+							transaction_number: trace_tx_number,
+							transaction_hash: trace_tx_hash,
+							block_number,
+							block_hash,
+						}
+					})
+					.collect()
+				)
 			)
 	}
 
@@ -609,6 +643,17 @@ mod tests {
 
 		assert_eq!(None, tracedb.block_traces(3));
 
+		assert_eq!(tracedb.block_traces_page(1, 0, 1).unwrap(), vec![create_simple_localized_trace(1, block_1.clone(), tx_1.clone())]);
+		assert!(tracedb.block_traces_page(1, 1, 10).unwrap().is_empty());
+		assert!(tracedb.block_traces_page(1, 0, 0).unwrap().is_empty());
+		assert_eq!(None, tracedb.block_traces_page(3, 0, 1));
+
+		// pages of blocks which aren't cached are decoded from the database
+		tracedb.traces.write().clear();
+		assert_eq!(tracedb.block_traces_page(2, 0, 1).unwrap(), vec![create_simple_localized_trace(2, block_2.clone(), tx_2.clone())]);
+		assert!(tracedb.block_traces_page(2, 1, 10).unwrap().is_empty());
+		assert!(tracedb.block_traces_page(2, 0, 0).unwrap().is_empty());
+
 		let traces = tracedb.transaction_traces(1, 0).unwrap();
 		assert_eq!(traces.len(), 1);
 		assert_eq!(traces[0], create_simple_localized_trace(1, block_1.clone(), tx_1.clone()));
@@ -22,6 +22,7 @@ use vm::{Error as VmError, ActionParams};
 // The MallocSizeOf derive looks for this in the root
 use parity_util_mem as malloc_size_of;
 
+mod access_tracer;
 mod config;
 mod db;
 mod executive_tracer;
@@ -30,6 +31,7 @@ mod noop_tracer;
 mod types;
 
 pub use crate::{
+	access_tracer::{AccessTracer, StateAccess},
 	config::Config,
 	db::{TraceDB, DatabaseExtras},
 	localized::LocalizedTrace,
@@ -125,7 +127,12 @@ pub trait Database {
 	fn transaction_traces(&self, block_number: BlockNumber, tx_position: usize) -> Option<Vec<LocalizedTrace>>;
 
 	/// Returns localized traces created in given block.
-	fn block_traces(&self, block_number: BlockNumber) -> Option<Vec<LocalizedTrace>>;
+	fn block_traces(&self, block_number: BlockNumber) -> Option<Vec<LocalizedTrace>> {
+		self.block_traces_page(block_number, 0, usize::max_value())
+	}
+
+	/// Returns at most `limit` localized traces created in given block, skipping the first `offset` ones.
+	fn block_traces_page(&self, block_number: BlockNumber, offset: usize, limit: usize) -> Option<Vec<LocalizedTrace>>;
 
 	/// Filter traces matching given filter.
 	fn filter(&self, filter: &Filter) -> Vec<LocalizedTrace>;
@@ -0,0 +1,48 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Status of the scheduled database backups.
+
+use std::path::PathBuf;
+
+use ethereum_types::H256;
+use BlockNumber;
+
+/// A database backup which was successfully taken.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupInfo {
+	/// Number of the best block at the time of the backup.
+	pub number: BlockNumber,
+	/// Hash of the best block at the time of the backup.
+	pub hash: H256,
+	/// UNIX timestamp (in seconds) at which the backup finished.
+	pub timestamp: u64,
+	/// Directory the backup was written to.
+	pub path: PathBuf,
+}
+
+/// Status of the scheduled database backups.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BackupStatus {
+	/// Whether backups are enabled.
+	pub enabled: bool,
+	/// Whether a backup is being taken at the moment.
+	pub in_progress: bool,
+	/// The most recent successful backup.
+	pub last_backup: Option<BackupInfo>,
+	/// Error of the most recent backup attempt, if it failed.
+	pub last_error: Option<String>,
+}
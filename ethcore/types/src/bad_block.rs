@@ -0,0 +1,145 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Blocks rejected by the client and the reasons they were rejected for.
+
+use std::fmt;
+
+use errors::{BlockError, EthcoreError, ImportError};
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+use verification::Unverified;
+
+/// Coarse reason a block was rejected for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BadBlockReason {
+	/// Seal (proof of work or engine signature) is invalid.
+	BadSeal,
+	/// State, transactions, receipts or uncles root doesn't match the block contents.
+	InvalidRoot,
+	/// Block is known to be bad, descends from a bad block or was rejected by the operator.
+	Banned,
+	/// Block timestamp is too far in the future.
+	TooFarInFuture,
+	/// Any other verification failure.
+	Other,
+}
+
+impl BadBlockReason {
+	/// Short, stable identifier of the reason.
+	pub fn code(&self) -> &'static str {
+		match *self {
+			BadBlockReason::BadSeal => "badSeal",
+			BadBlockReason::InvalidRoot => "invalidRoot",
+			BadBlockReason::Banned => "banned",
+			BadBlockReason::TooFarInFuture => "tooFarInFuture",
+			BadBlockReason::Other => "other",
+		}
+	}
+}
+
+impl Encodable for BadBlockReason {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		let id: u8 = match *self {
+			BadBlockReason::BadSeal => 0,
+			BadBlockReason::InvalidRoot => 1,
+			BadBlockReason::Banned => 2,
+			BadBlockReason::TooFarInFuture => 3,
+			BadBlockReason::Other => 4,
+		};
+		s.append(&id);
+	}
+}
+
+impl Decodable for BadBlockReason {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		match rlp.as_val::<u8>()? {
+			0 => Ok(BadBlockReason::BadSeal),
+			1 => Ok(BadBlockReason::InvalidRoot),
+			2 => Ok(BadBlockReason::Banned),
+			3 => Ok(BadBlockReason::TooFarInFuture),
+			4 => Ok(BadBlockReason::Other),
+			_ => Err(DecoderError::Custom("Unknown bad block reason")),
+		}
+	}
+}
+
+impl fmt::Display for BadBlockReason {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(self.code())
+	}
+}
+
+impl<'a> From<&'a EthcoreError> for BadBlockReason {
+	fn from(err: &'a EthcoreError) -> Self {
+		match *err {
+			EthcoreError::Block(ref e) => e.into(),
+			EthcoreError::Import(ImportError::KnownBad) => BadBlockReason::Banned,
+			EthcoreError::Engine(_) | EthcoreError::PowHashInvalid | EthcoreError::PowInvalid => BadBlockReason::BadSeal,
+			_ => BadBlockReason::Other,
+		}
+	}
+}
+
+impl<'a> From<&'a BlockError> for BadBlockReason {
+	fn from(err: &'a BlockError) -> Self {
+		match *err {
+			BlockError::InvalidSeal
+				| BlockError::InvalidSealArity(_)
+				| BlockError::MismatchedH256SealElement(_)
+				| BlockError::InvalidProofOfWork(_) => BadBlockReason::BadSeal,
+			BlockError::InvalidStateRoot(_)
+				| BlockError::InvalidTransactionsRoot(_)
+				| BlockError::InvalidReceiptsRoot(_)
+				| BlockError::InvalidUnclesHash(_) => BadBlockReason::InvalidRoot,
+			BlockError::TemporarilyInvalid(_) => BadBlockReason::TooFarInFuture,
+			_ => BadBlockReason::Other,
+		}
+	}
+}
+
+/// Recently rejected block.
+#[derive(Debug, Clone)]
+pub struct BadBlock {
+	/// The rejected block.
+	pub block: Unverified,
+	/// Why the block was rejected.
+	pub reason: BadBlockReason,
+	/// Detailed description of the error.
+	pub message: String,
+	/// Unix timestamp (in seconds) of when the block was rejected.
+	pub rejected_at: u64,
+}
+
+impl Encodable for BadBlock {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(4);
+		s.append(&self.block.bytes);
+		s.append(&self.reason);
+		s.append(&self.message);
+		s.append(&self.rejected_at);
+	}
+}
+
+impl Decodable for BadBlock {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		Ok(BadBlock {
+			block: Unverified::from_rlp(rlp.val_at(0)?)?,
+			reason: rlp.val_at(1)?,
+			message: rlp.val_at(2)?,
+			rejected_at: rlp.val_at(3)?,
+		})
+	}
+}
@@ -20,6 +20,7 @@
 use bytes::Bytes;
 use ethereum_types::H256;
 use crate::{
+	BlockNumber,
 	import_route::ImportRoute,
 };
 use std::time::Duration;
@@ -161,3 +162,18 @@ impl NewBlocks {
 		}
 	}
 }
+
+/// Reorg deeper than the configured limit. Block import is halted until the reorg is acknowledged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeepReorg {
+	/// Hash of the block which would cause the reorg.
+	pub hash: H256,
+	/// Number of the block which would cause the reorg.
+	pub number: BlockNumber,
+	/// Best block at the time the reorg was detected.
+	pub best: H256,
+	/// Common ancestor of the canonical and the new chain.
+	pub ancestor: H256,
+	/// Number of canonical blocks which would be retracted.
+	pub depth: u64,
+}
@@ -63,6 +63,8 @@ pub struct ClientReport {
 	pub gas_processed: U256,
 	/// Memory used by state DB
 	pub state_db_mem: usize,
+	/// How many blocks have been rejected before all of their transactions were executed.
+	pub blocks_aborted_early: usize,
 }
 
 impl ClientReport {
@@ -84,6 +86,7 @@ impl<'a> ops::Sub<&'a ClientReport> for ClientReport {
 		self.blocks_imported -= other.blocks_imported;
 		self.transactions_applied -= other.transactions_applied;
 		self.gas_processed = self.gas_processed - other.gas_processed;
+		self.blocks_aborted_early -= other.blocks_aborted_early;
 		self.state_db_mem = higher_mem - lower_mem;
 
 		self
@@ -113,6 +113,9 @@ impl Header {
 	/// Block extra data.
 	pub fn extra_data(&self) -> Vec<u8> { self.view().extra_data() }
 
+	/// Base fee per gas, `None` before EIP-1559 transition.
+	pub fn base_fee(&self) -> Option<U256> { self.view().base_fee() }
+
 	/// Engine-specific seal fields.
 	pub fn seal(&self) -> Vec<Vec<u8>> { self.view().seal() }
 }
@@ -271,6 +274,9 @@ impl Block {
 	/// Block extra data.
 	pub fn extra_data(&self) -> Vec<u8> { self.header_view().extra_data() }
 
+	/// Base fee per gas, `None` before EIP-1559 transition.
+	pub fn base_fee(&self) -> Option<U256> { self.header_view().base_fee() }
+
 	/// Engine-specific seal fields.
 	pub fn seal(&self) -> Vec<Vec<u8>> { self.header_view().seal() }
 }
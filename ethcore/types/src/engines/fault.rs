@@ -0,0 +1,47 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Consensus faults attributed to validators.
+
+use ethereum_types::{Address, H256};
+
+use BlockNumber;
+
+/// Kind of a consensus fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsensusFaultKind {
+	/// Block with an invalid seal: wrong proposer, a step from the future or invalid empty steps.
+	InvalidSeal,
+	/// More than one block proposed by the validator for the same step.
+	Equivocation,
+	/// No block produced by the validator in its step.
+	SkippedStep,
+}
+
+/// Consensus fault observed while verifying a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsensusFault {
+	/// Validator the fault is attributed to.
+	pub validator: Address,
+	/// Kind of the fault.
+	pub kind: ConsensusFaultKind,
+	/// Number of the block revealing the fault.
+	pub block_number: BlockNumber,
+	/// Hash of the block revealing the fault.
+	pub block_hash: H256,
+	/// UNIX timestamp (in seconds) at which the fault was observed.
+	pub timestamp: u64,
+}
@@ -25,6 +25,7 @@ use unexpected::Mismatch;
 use crate::{BlockNumber, errors::{BlockError, EthcoreError}};
 
 pub mod epoch;
+pub mod fault;
 pub mod params;
 pub mod machine;
 
@@ -72,7 +73,7 @@ impl EthashSeal {
 
 
 /// Seal type.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Seal {
 	/// Regular block seal; should be part of the blockchain.
 	Regular(Vec<Bytes>),
@@ -81,7 +82,7 @@ pub enum Seal {
 }
 
 /// The type of sealing the engine is currently able to perform.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SealingState {
 	/// The engine is ready to seal a block.
 	Ready,
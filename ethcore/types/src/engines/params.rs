@@ -22,8 +22,15 @@ use ethjson;
 
 use BlockNumber;
 use engines::DEFAULT_BLOCKHASH_CONTRACT;
+use header::Header;
 
 const MAX_TRANSACTION_SIZE: usize = 300 * 1024;
+/// Default bound divisor of the base fee change between blocks.
+const EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+/// Default ratio of the block gas limit to the gas target.
+const EIP1559_ELASTICITY_MULTIPLIER: u64 = 2;
+/// Default base fee of the first EIP-1559 block (1 gwei).
+const EIP1559_BASE_FEE_INITIAL_VALUE: u64 = 1_000_000_000;
 
 /// Parameters common to ethereum-like blockchains.
 /// NOTE: when adding bugfix hard-fork parameters,
@@ -104,6 +111,14 @@ pub struct CommonParams {
 	pub eip2200_advance_transition: BlockNumber,
 	/// Number of first block where dust cleanup rules (EIP-168 and EIP169) begin.
 	pub dust_protection_transition: BlockNumber,
+	/// Number of first block where EIP-1559 base fee rules begin.
+	pub eip1559_transition: BlockNumber,
+	/// Bound divisor of the base fee change between two consecutive blocks.
+	pub eip1559_base_fee_max_change_denominator: U256,
+	/// Ratio of the block gas limit to the gas target the base fee adjusts towards.
+	pub eip1559_elasticity_multiplier: U256,
+	/// Base fee of the block at `eip1559_transition`.
+	pub eip1559_base_fee_initial_value: U256,
 	/// Nonce cap increase per block. Nonce cap is only checked if dust protection is enabled.
 	pub nonce_cap_increment: u64,
 	/// Enable dust cleanup for contracts.
@@ -119,6 +134,8 @@ pub struct CommonParams {
 	pub kip6_transition: BlockNumber,
 	/// Gas limit bound divisor (how much gas limit can change per block)
 	pub gas_limit_bound_divisor: U256,
+	/// Gas limit produced blocks gradually converge to, overriding the miner's gas floor and ceiling targets.
+	pub gas_limit_target: Option<U256>,
 	/// Registrar contract address.
 	pub registrar: Option<Address>,
 	/// Node permission managing contract address.
@@ -131,8 +148,44 @@ pub struct CommonParams {
 	pub transaction_permission_contract: Option<Address>,
 	/// Block at which the transaction permission contract should start being used.
 	pub transaction_permission_contract_transition: BlockNumber,
+	/// Ordered chain of transaction admission rules.
+	pub transaction_validators: Vec<TransactionValidatorParams>,
 	/// Maximum size of transaction's RLP payload
 	pub max_transaction_size: usize,
+	/// Maximum gas a single transaction may use, regardless of the block gas limit.
+	pub max_transaction_gas: Option<U256>,
+	/// Number of first block where the transaction gas cap is enforced.
+	pub max_transaction_gas_transition: BlockNumber,
+}
+
+/// Transaction admission rule, see `Machine::verify_transaction`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TransactionValidatorParams {
+	/// Only transactions from listed senders are allowed.
+	SenderAllowList(Vec<Address>),
+	/// Transactions are checked by the permissioning contract, starting at the transition block.
+	PermissionContract {
+		/// Contract address.
+		address: Address,
+		/// Block at which the contract should start being used.
+		transition: BlockNumber,
+	},
+	/// Transactions with data longer than given number of bytes are rejected.
+	MaxPayloadSize(usize),
+}
+
+impl From<ethjson::spec::TransactionValidator> for TransactionValidatorParams {
+	fn from(v: ethjson::spec::TransactionValidator) -> Self {
+		use ethjson::spec::TransactionValidator::*;
+		match v {
+			SenderAllowList(senders) => TransactionValidatorParams::SenderAllowList(senders.into_iter().map(Into::into).collect()),
+			PermissionContract(contract) => TransactionValidatorParams::PermissionContract {
+				address: contract.address.into(),
+				transition: contract.transition.map_or(0, Into::into),
+			},
+			MaxPayloadSize(size) => TransactionValidatorParams::MaxPayloadSize(size.into()),
+		}
+	}
 }
 
 impl CommonParams {
@@ -154,6 +207,15 @@ impl CommonParams {
 		}
 	}
 
+	/// Returns the maximal gas of a single transaction at given block, if capped.
+	pub fn max_transaction_gas(&self, block_number: BlockNumber) -> Option<U256> {
+		if block_number >= self.max_transaction_gas_transition {
+			self.max_transaction_gas
+		} else {
+			None
+		}
+	}
+
 	/// Returns max code size at given block.
 	pub fn max_code_size(&self, block_number: u64) -> u64 {
 		if block_number >= self.max_code_size_transition {
@@ -214,6 +276,71 @@ impl CommonParams {
 		}
 	}
 
+	/// Expected base fee of the child of given header, `None` if the child precedes EIP-1559 transition.
+	pub fn next_base_fee(&self, parent: &Header) -> Option<U256> {
+		let number = parent.number() + 1;
+		if number < self.eip1559_transition {
+			return None;
+		}
+		let parent_base_fee = match parent.base_fee() {
+			Some(base_fee) if number > self.eip1559_transition => base_fee,
+			_ => return Some(self.eip1559_base_fee_initial_value),
+		};
+
+		let gas_target = *parent.gas_limit() / self.eip1559_elasticity_multiplier;
+		let gas_used = *parent.gas_used();
+		if gas_target.is_zero() || gas_used == gas_target {
+			return Some(parent_base_fee);
+		}
+
+		let base_fee = if gas_used > gas_target {
+			let delta = parent_base_fee.saturating_mul(gas_used - gas_target) / gas_target / self.eip1559_base_fee_max_change_denominator;
+			parent_base_fee.saturating_add(::std::cmp::max(delta, U256::one()))
+		} else {
+			let delta = parent_base_fee.saturating_mul(gas_target - gas_used) / gas_target / self.eip1559_base_fee_max_change_denominator;
+			parent_base_fee.saturating_sub(delta)
+		};
+		Some(base_fee)
+	}
+
+	/// Blocks at which the rules set by these parameters change, unordered and possibly repeated.
+	/// Includes transitions active from genesis and ones which are disabled.
+	pub fn fork_blocks(&self) -> Vec<BlockNumber> {
+		let mut blocks = vec![
+			self.eip150_transition,
+			self.eip160_transition,
+			self.eip161abc_transition,
+			self.eip161d_transition,
+			self.eip98_transition,
+			self.eip658_transition,
+			self.eip155_transition,
+			self.eip140_transition,
+			self.eip210_transition,
+			self.eip211_transition,
+			self.eip214_transition,
+			self.eip145_transition,
+			self.eip1052_transition,
+			self.eip1283_transition,
+			self.eip1283_disable_transition,
+			self.eip1283_reenable_transition,
+			self.eip1014_transition,
+			self.eip1706_transition,
+			self.eip1344_transition,
+			self.eip1884_transition,
+			self.eip2028_transition,
+			self.eip2200_advance_transition,
+			self.eip1559_transition,
+			self.dust_protection_transition,
+			self.wasm_activation_transition,
+			self.kip4_transition,
+			self.kip6_transition,
+			self.max_code_size_transition,
+			self.max_transaction_gas_transition,
+		];
+		blocks.extend(self.fork_block.map(|(number, _)| number));
+		blocks
+	}
+
 	/// Return Some if the current parameters contain a bugfix hard fork not on block 0.
 	pub fn nonzero_bugfix_hard_fork(&self) -> Option<&str> {
 		if self.eip155_transition != 0 {
@@ -335,17 +462,33 @@ impl From<ethjson::spec::Params> for CommonParams {
 				BlockNumber::max_value,
 				Into::into,
 			),
+			eip1559_transition: p.eip1559_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip1559_base_fee_max_change_denominator: p.eip1559_base_fee_max_change_denominator
+				.map_or_else(|| EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR.into(), Into::into),
+			eip1559_elasticity_multiplier: p.eip1559_elasticity_multiplier
+				.map_or_else(|| EIP1559_ELASTICITY_MULTIPLIER.into(), Into::into),
+			eip1559_base_fee_initial_value: p.eip1559_base_fee_initial_value
+				.map_or_else(|| EIP1559_BASE_FEE_INITIAL_VALUE.into(), Into::into),
 			nonce_cap_increment: p.nonce_cap_increment.map_or(64, Into::into),
 			remove_dust_contracts: p.remove_dust_contracts.unwrap_or(false),
 			gas_limit_bound_divisor: p.gas_limit_bound_divisor.into(),
+			gas_limit_target: p.gas_limit_target.map(Into::into),
 			registrar: p.registrar.map(Into::into),
 			node_permission_contract: p.node_permission_contract.map(Into::into),
 			max_code_size: p.max_code_size.map_or(u64::max_value(), Into::into),
 			max_transaction_size: p.max_transaction_size.map_or(MAX_TRANSACTION_SIZE, Into::into),
 			max_code_size_transition: p.max_code_size_transition.map_or(0, Into::into),
+			max_transaction_gas: p.max_transaction_gas.map(Into::into),
+			max_transaction_gas_transition: p.max_transaction_gas_transition.map_or(0, Into::into),
 			transaction_permission_contract: p.transaction_permission_contract.map(Into::into),
 			transaction_permission_contract_transition:
 			p.transaction_permission_contract_transition.map_or(0, Into::into),
+			transaction_validators: p.transaction_validators.map_or_else(Vec::new, |validators|
+				validators.into_iter().map(Into::into).collect()
+			),
 			wasm_activation_transition: p.wasm_activation_transition.map_or_else(
 				BlockNumber::max_value,
 				Into::into
@@ -362,3 +505,62 @@ impl From<ethjson::spec::Params> for CommonParams {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::U256;
+	use header::Header;
+	use super::CommonParams;
+
+	fn eip1559_params() -> CommonParams {
+		CommonParams {
+			eip1559_transition: 10,
+			eip1559_base_fee_max_change_denominator: 8.into(),
+			eip1559_elasticity_multiplier: 2.into(),
+			eip1559_base_fee_initial_value: 1_000_000_000.into(),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn max_transaction_gas_starts_at_transition() {
+		let params = CommonParams {
+			max_transaction_gas: Some(500_000.into()),
+			max_transaction_gas_transition: 10,
+			..Default::default()
+		};
+		assert_eq!(params.max_transaction_gas(9), None);
+		assert_eq!(params.max_transaction_gas(10), Some(500_000.into()));
+		assert_eq!(CommonParams::default().max_transaction_gas(10), None);
+	}
+
+	#[test]
+	fn base_fee_starts_at_transition() {
+		let params = eip1559_params();
+		let mut parent = Header::new();
+		parent.set_number(8);
+		assert_eq!(params.next_base_fee(&parent), None);
+		parent.set_number(9);
+		assert_eq!(params.next_base_fee(&parent), Some(1_000_000_000.into()));
+	}
+
+	#[test]
+	fn base_fee_follows_gas_used() {
+		let params = eip1559_params();
+		let mut parent = Header::new();
+		parent.set_number(10);
+		parent.set_gas_limit(20_000_000.into());
+		parent.set_base_fee(Some(1_000_000_000.into()));
+
+		parent.set_gas_used(10_000_000.into());
+		assert_eq!(params.next_base_fee(&parent), Some(1_000_000_000.into()));
+		parent.set_gas_used(20_000_000.into());
+		assert_eq!(params.next_base_fee(&parent), Some(1_125_000_000.into()));
+		parent.set_gas_used(0.into());
+		assert_eq!(params.next_base_fee(&parent), Some(875_000_000.into()));
+
+		parent.set_base_fee(Some(U256::from(7)));
+		parent.set_gas_used(10_000_001.into());
+		assert_eq!(params.next_base_fee(&parent), Some(8.into()));
+	}
+}
@@ -98,6 +98,9 @@ pub enum BlockError {
 	/// Log bloom header field is invalid.
 	#[display(fmt = "Invalid log bloom in header: {}", _0)]
 	InvalidLogBloom(Box<Mismatch<Bloom>>),
+	/// Base fee header field is invalid. Missing base fee is reported as zero.
+	#[display(fmt = "Invalid base fee in header: {}", _0)]
+	InvalidBaseFee(Mismatch<U256>),
 	/// Number field of header is invalid.
 	#[display(fmt = "Invalid number in header: {}", _0)]
 	InvalidNumber(Mismatch<BlockNumber>),
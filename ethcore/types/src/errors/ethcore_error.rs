@@ -77,6 +77,9 @@ pub enum EthcoreError {
 	/// The value of the nonce or mishash is invalid.
 	#[display(fmt = "The value of the nonce or mishash is invalid.")]
 	PowInvalid,
+	/// The solution only meets the lowered difficulty advertised for testing mining rigs.
+	#[display(fmt = "The solution is a test share, it does not meet the block difficulty.")]
+	PowTestShare,
 	/// A convenient variant for String.
 	#[display(fmt = "{}", _0)]
 	Msg(String),
@@ -152,6 +155,13 @@ pub enum ExecutionError {
 		/// Actual balance.
 		got: U512
 	},
+	/// Returned when transaction gas price is lower than the base fee of the block.
+	GasPriceLowerThanBaseFee {
+		/// Base fee of the block.
+		base_fee: U256,
+		/// Gas price of the transaction.
+		got: U256
+	},
 	/// When execution tries to modify the state in static context
 	MutableCallInStaticContext,
 	/// Returned when transacting from a non-existing account with dust protection enabled.
@@ -194,6 +204,8 @@ impl fmt::Display for ExecutionError {
 			NotEnoughCash { ref required, ref got } =>
 				format!("Cost of transaction exceeds sender balance. {} is required \
 					but the sender only has {}", required, got),
+			GasPriceLowerThanBaseFee { ref base_fee, ref got } =>
+				format!("Gas price {} is lower than the block base fee {}", got, base_fee),
 			MutableCallInStaticContext => "Mutable Call in static context".to_owned(),
 			SenderMustExist => "Transacting from an empty account".to_owned(),
 			Internal(ref msg) => msg.clone(),
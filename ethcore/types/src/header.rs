@@ -80,6 +80,8 @@ pub struct Header {
 
 	/// Block difficulty.
 	difficulty: U256,
+	/// Base fee per gas, only present after EIP-1559 transition.
+	base_fee: Option<U256>,
 	/// Vector of post-RLP-encoded fields.
 	seal: Vec<Bytes>,
 
@@ -108,6 +110,7 @@ impl PartialEq for Header {
 		self.gas_used == c.gas_used &&
 		self.gas_limit == c.gas_limit &&
 		self.difficulty == c.difficulty &&
+		self.base_fee == c.base_fee &&
 		self.seal == c.seal
 	}
 }
@@ -131,6 +134,7 @@ impl Default for Header {
 			gas_limit: U256::default(),
 
 			difficulty: U256::default(),
+			base_fee: None,
 			seal: vec![],
 			hash: None,
 		}
@@ -180,6 +184,9 @@ impl Header {
 	/// Get the difficulty field of the header.
 	pub fn difficulty(&self) -> &U256 { &self.difficulty }
 
+	/// Get the base fee field of the header, `None` before EIP-1559 transition.
+	pub fn base_fee(&self) -> Option<U256> { self.base_fee }
+
 	/// Get the seal field of the header.
 	pub fn seal(&self) -> &[Bytes] { &self.seal }
 
@@ -255,6 +262,11 @@ impl Header {
 		change_field(&mut self.hash, &mut self.difficulty, a);
 	}
 
+	/// Set the base fee field of the header.
+	pub fn set_base_fee(&mut self, a: Option<U256>) {
+		change_field(&mut self.hash, &mut self.base_fee, a);
+	}
+
 	/// Set the seal field of the header.
 	pub fn set_seal(&mut self, a: Vec<Bytes>) {
 		change_field(&mut self.hash, &mut self.seal, a)
@@ -291,10 +303,11 @@ impl Header {
 
 	/// Place this header into an RLP stream `s`, optionally `with_seal`.
 	fn stream_rlp(&self, s: &mut RlpStream, with_seal: Seal) {
+		let fields = 13 + self.base_fee.is_some() as usize;
 		if let Seal::With = with_seal {
-			s.begin_list(13 + self.seal.len());
+			s.begin_list(fields + self.seal.len());
 		} else {
-			s.begin_list(13);
+			s.begin_list(fields);
 		}
 
 		s.append(&self.parent_hash);
@@ -310,6 +323,10 @@ impl Header {
 		s.append(&self.gas_used);
 		s.append(&self.timestamp);
 		s.append(&self.extra_data);
+		// Base fee is wrapped in a list, so it can't be mistaken for the first seal field.
+		if let Some(ref base_fee) = self.base_fee {
+			s.begin_list(1).append(base_fee);
+		}
 
 		if let Seal::With = with_seal {
 			for b in &self.seal {
@@ -343,11 +360,19 @@ impl Decodable for Header {
 			gas_used: r.val_at(10)?,
 			timestamp: r.val_at(11)?,
 			extra_data: r.val_at(12)?,
+			base_fee: None,
 			seal: vec![],
 			hash: keccak(r.as_raw()).into(),
 		};
 
-		for i in 13..r.item_count()? {
+		let item_count = r.item_count()?;
+		let mut seal_start = 13;
+		if item_count > 13 && r.at(13)?.is_list() {
+			blockheader.base_fee = Some(r.at(13)?.val_at(0)?);
+			seal_start = 14;
+		}
+
+		for i in seal_start..item_count {
 			blockheader.seal.push(r.at(i)?.as_raw().to_vec())
 		}
 
@@ -416,4 +441,21 @@ mod tests {
 		let header: Result<Header, _> = rlp::decode(&header_rlp);
 		assert_eq!(header.unwrap_err(), rlp::DecoderError::RlpIsTooBig);
 	}
+
+	#[test]
+	fn decode_and_encode_header_with_base_fee() {
+		let mut header = Header::default();
+		header.set_number(10);
+		header.set_base_fee(Some(1_000_000_000.into()));
+		header.set_seal(vec![rlp::encode(&1u64), rlp::encode(&vec![0u8; 65])]);
+		let bare_hash = header.bare_hash();
+
+		let decoded: Header = rlp::decode(&rlp::encode(&header)).expect("error decoding header");
+		assert_eq!(decoded, header);
+		assert_eq!(decoded.base_fee(), Some(1_000_000_000.into()));
+		assert_eq!(decoded.seal().len(), 2);
+
+		header.set_base_fee(None);
+		assert!(header.bare_hash() != bare_hash);
+	}
 }
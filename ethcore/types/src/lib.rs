@@ -59,6 +59,8 @@ pub mod views;
 
 pub mod account_diff;
 pub mod ancestry_action;
+pub mod backup;
+pub mod bad_block;
 pub mod basic_account;
 pub mod block;
 pub mod block_status;
@@ -121,6 +121,8 @@ pub struct RichReceipt {
 	pub cumulative_gas_used: U256,
 	/// The gas used in the execution of the transaction. Note the difference of meaning to `Receipt::gas_used`.
 	pub gas_used: U256,
+	/// Price paid per unit of gas.
+	pub effective_gas_price: U256,
 	/// Contract address.
 	/// NOTE: It is an Option because only `Action::Create` transactions has a contract address
 	pub contract_address: Option<Address>,
@@ -152,6 +154,8 @@ pub struct LocalizedReceipt {
 	pub cumulative_gas_used: U256,
 	/// The gas used in the execution of the transaction. Note the difference of meaning to `Receipt::gas_used`.
 	pub gas_used: U256,
+	/// Price paid per unit of gas.
+	pub effective_gas_price: U256,
 	/// Contract address.
 	/// NOTE: It is an Option because only `Action::Create` transactions has a contract address
 	pub contract_address: Option<Address>,
@@ -20,6 +20,8 @@ use crate::{
 	header::Header,
 	transaction::UnverifiedTransaction,
 };
+use std::time::Duration;
+
 use bytes::Bytes;
 use parity_util_mem::MallocSizeOf;
 
@@ -38,6 +40,8 @@ pub struct VerificationQueueInfo {
 	pub max_mem_use: usize,
 	/// Heap memory used in bytes
 	pub mem_used: usize,
+	/// Average time it takes to verify a single item
+	pub verification_time: Duration,
 }
 
 impl VerificationQueueInfo {
@@ -98,10 +98,19 @@ impl<'a> HeaderView<'a> {
 	/// Returns block extra data.
 	pub fn extra_data(&self) -> Bytes { self.rlp.val_at(12) }
 
+	/// Returns base fee per gas, if the header was created after EIP-1559 transition.
+	pub fn base_fee(&self) -> Option<U256> {
+		match self.rlp.item_count() > 13 && self.rlp.at(13).rlp.is_list() {
+			true => Some(self.rlp.at(13).val_at(0)),
+			false => None,
+		}
+	}
+
 	/// Returns a vector of post-RLP-encoded seal fields.
 	pub fn seal(&self) -> Vec<Bytes> {
 		let mut seal = vec![];
-		for i in 13..self.rlp.item_count() {
+		let seal_start = 13 + self.base_fee().is_some() as usize;
+		for i in seal_start..self.rlp.item_count() {
 			seal.push(self.rlp.at(i).as_raw().to_vec());
 		}
 		seal
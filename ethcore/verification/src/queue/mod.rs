@@ -22,6 +22,7 @@ use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
 use std::sync::Arc;
 use std::cmp;
 use std::collections::{VecDeque, HashSet, HashMap};
+use std::time::{Duration, Instant};
 use common_types::{
 	block_status::BlockStatus,
 	io_message::ClientIoMessage,
@@ -56,7 +57,7 @@ pub struct Config {
 	/// When the limit is reached, is_full returns true.
 	pub max_queue_size: usize,
 	/// Maximum heap memory to use.
-	/// When the limit is reached, is_full returns true.
+	/// When the limit is reached, is_full returns true and further imports are rejected.
 	pub max_mem_use: usize,
 	/// Settings for the number of verifiers and adaptation strategy.
 	pub verifier_settings: VerifierSettings,
@@ -132,6 +133,32 @@ struct Sizes {
 	verified: AtomicUsize,
 }
 
+/// Weight of the latest sample in the average verification time, as `1 / VERIFICATION_TIME_WEIGHT`.
+const VERIFICATION_TIME_WEIGHT: u64 = 8;
+
+// moving average of item verification time.
+#[derive(Default)]
+struct VerificationTime {
+	micros: AtomicUsize,
+}
+
+impl VerificationTime {
+	fn record(&self, elapsed: Duration) {
+		let sample = elapsed.as_micros() as u64;
+		let average = self.micros.load(AtomicOrdering::Relaxed) as u64;
+		let average = if average == 0 {
+			sample
+		} else {
+			(average * (VERIFICATION_TIME_WEIGHT - 1) + sample) / VERIFICATION_TIME_WEIGHT
+		};
+		self.micros.store(average as usize, AtomicOrdering::Relaxed);
+	}
+
+	fn average(&self) -> Duration {
+		Duration::from_micros(self.micros.load(AtomicOrdering::Relaxed) as u64)
+	}
+}
+
 /// A queue of items to be verified. Sits between network or other I/O and the `BlockChain`.
 /// Keeps them in the same order as inserted, minus invalid items.
 pub struct VerificationQueue<K: Kind, C: 'static> {
@@ -198,6 +225,7 @@ struct Verification<K: Kind> {
 	verified: LenCachingMutex<VecDeque<K::Verified>>,
 	bad: Mutex<HashSet<H256>>,
 	sizes: Sizes,
+	verification_time: VerificationTime,
 	check_seal: bool,
 }
 
@@ -214,6 +242,7 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 				verifying: AtomicUsize::new(0),
 				verified: AtomicUsize::new(0),
 			},
+			verification_time: VerificationTime::default(),
 			check_seal,
 		});
 		let more_to_verify = Arc::new(Condvar::new());
@@ -356,7 +385,11 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 			};
 
 			let hash = item.hash();
-			let is_ready = match K::verify(item, &*engine, verification.check_seal) {
+			let started = Instant::now();
+			let result = K::verify(item, &*engine, verification.check_seal);
+			verification.verification_time.record(started.elapsed());
+
+			let is_ready = match result {
 				Ok(verified) => {
 					let mut verifying = verification.verifying.lock();
 					let mut idx = None;
@@ -467,6 +500,8 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 	}
 
 	/// Add a block to the queue.
+	/// Fails with `FullQueue` once the queue uses more than `max_mem_use` bytes, so that the caller
+	/// can stop feeding it until some items are imported.
 	//
 	// TODO: #11403 - rework `EthcoreError::Block` to include raw bytes of the error cause
 	pub fn import(&self, input: K::Input) -> Result<H256, (Error, Option<K::Input>)> {
@@ -488,6 +523,13 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 			}
 		}
 
+		// Always accept at least one item, so that a single block larger than the limit can't stall the import.
+		let queue_info = self.queue_info();
+		if !queue_info.is_empty() && queue_info.mem_used > self.max_mem_use {
+			trace!(target: "verification", "Queue is full ({} of {} bytes used), rejecting {}", queue_info.mem_used, self.max_mem_use, hash);
+			return Err((Error::FullQueue(self.max_mem_use), Some(input)));
+		}
+
 		match K::create(input, &*self.engine, self.verification.check_seal) {
 			Ok(item) => {
 				if self.processing.write().insert(hash, item.difficulty()).is_some() {
@@ -633,7 +675,8 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 			max_mem_use: self.max_mem_use,
 			mem_used: unverified_bytes
 					   + verifying_bytes
-					   + verified_bytes
+					   + verified_bytes,
+			verification_time: self.verification.verification_time.average(),
 		}
 	}
 
@@ -872,9 +915,18 @@ mod tests {
 		let queue = BlockQueue::<Client>::new(config, engine, IoChannel::disconnected(), true);
 		assert!(!queue.queue_info().is_full());
 		let mut blocks = get_good_dummy_block_seq(50);
+		let mut rejected = 0;
 		for b in blocks.drain(..) {
-			queue.import(new_unverified(b)).unwrap();
+			match queue.import(new_unverified(b)) {
+				Ok(_) => {},
+				Err((EthcoreError::FullQueue(limit), Some(_))) => {
+					assert_eq!(limit, super::MIN_MEM_LIMIT);
+					rejected += 1;
+				},
+				Err((e, _)) => panic!("unexpected import error: {:?}", e),
+			}
 		}
+		assert!(rejected > 0);
 		assert!(queue.queue_info().is_full());
 	}
 
@@ -404,6 +404,14 @@ fn verify_parent(header: &Header, parent: &Header, engine: &dyn Engine) -> Resul
 		}
 	}
 
+	let expected_base_fee = engine.params().next_base_fee(parent);
+	if header.base_fee() != expected_base_fee {
+		return Err(From::from(BlockError::InvalidBaseFee(Mismatch {
+			expected: expected_base_fee.unwrap_or_default(),
+			found: header.base_fee().unwrap_or_default(),
+		})));
+	}
+
 	Ok(())
 }
 
@@ -0,0 +1,214 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Comparison of a produced standard JSON trace against a reference trace (`--expect`).
+
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use ethereum_types::U256;
+use serde_json::Value;
+
+use crate::display::std_json::Writer;
+
+/// Trace sink keeping the produced trace in memory.
+#[derive(Debug, Clone, Default)]
+pub struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl Capture {
+	/// Returns everything written so far.
+	pub fn contents(&self) -> String {
+		String::from_utf8_lossy(&self.0.lock().expect("Capture lock is never poisoned; qed")).into_owned()
+	}
+}
+
+impl Writer for Capture {
+	fn clone(&self) -> Self { Clone::clone(self) }
+	fn default() -> Self { Default::default() }
+}
+
+impl io::Write for Capture {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0.lock().expect("Capture lock is never poisoned; qed").write(buf)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+/// Single step of a standard JSON trace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+	pc: u64,
+	op: u8,
+	gas: U256,
+	stack: Vec<U256>,
+	depth: u64,
+}
+
+impl fmt::Display for Step {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let name = evm::Instruction::from_u8(self.op).map_or("UNKNOWN", |i| i.info().name);
+		write!(f, "pc={} op={:#04x} ({}) gas={:#x} depth={} stack top=", self.pc, self.op, name, self.gas, self.depth)?;
+		match self.stack.last() {
+			Some(top) => write!(f, "{:#x}", top),
+			None => write!(f, "<empty>"),
+		}
+	}
+}
+
+/// First step at which two traces differ.
+#[derive(Debug, PartialEq)]
+pub struct Divergence {
+	/// Index of the divergent step.
+	pub index: usize,
+	/// Name of the first differing field.
+	pub field: &'static str,
+	/// Last step both traces agree on.
+	pub previous: Option<Step>,
+	/// Produced step, `None` if the produced trace ended early.
+	pub actual: Option<Step>,
+	/// Reference step, `None` if the reference trace ended early.
+	pub expected: Option<Step>,
+}
+
+impl fmt::Display for Divergence {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let step = |step: &Option<Step>| step.as_ref().map_or_else(|| "<end of trace>".to_owned(), Step::to_string);
+		writeln!(f, "Traces diverge at step {} ({} differs)", self.index, self.field)?;
+		writeln!(f, "  previous: {}", step(&self.previous))?;
+		writeln!(f, "  expected: {}", step(&self.expected))?;
+		write!(f, "  actual:   {}", step(&self.actual))
+	}
+}
+
+/// Parse execution steps out of a standard JSON trace. Lines which aren't steps (e.g. state roots) are ignored.
+pub fn parse_trace(trace: &str) -> Result<Vec<Step>, String> {
+	let mut steps = Vec::new();
+	for (number, line) in trace.lines().map(str::trim).enumerate().filter(|(_, line)| !line.is_empty()) {
+		let value: Value = serde_json::from_str(line).map_err(|e| format!("Invalid trace at line {}: {}", number + 1, e))?;
+		if value.get("pc").is_none() || value.get("op").is_none() {
+			continue;
+		}
+		steps.push(parse_step(&value).map_err(|e| format!("Invalid trace at line {}: {}", number + 1, e))?);
+	}
+	Ok(steps)
+}
+
+fn parse_step(value: &Value) -> Result<Step, String> {
+	let stack = match value.get("stack") {
+		Some(Value::Array(items)) => items.iter().map(parse_u256).collect::<Result<_, _>>()?,
+		_ => Vec::new(),
+	};
+	Ok(Step {
+		pc: value["pc"].as_u64().ok_or("pc is not a number")?,
+		op: value["op"].as_u64().filter(|op| *op <= 0xff).ok_or("op is not a byte")? as u8,
+		gas: value.get("gas").map_or(Ok(U256::zero()), parse_u256)?,
+		stack,
+		depth: value.get("depth").and_then(Value::as_u64).unwrap_or(0),
+	})
+}
+
+fn parse_u256(value: &Value) -> Result<U256, String> {
+	match value {
+		Value::Number(n) => n.as_u64().map(U256::from).ok_or_else(|| format!("Invalid number: {}", n)),
+		Value::String(s) => match s.trim_start_matches("0x") {
+			"" => Ok(U256::zero()),
+			hex => hex.parse().map_err(|_| format!("Invalid hex value: {}", s)),
+		},
+		other => Err(format!("Expected a number, got: {}", other)),
+	}
+}
+
+/// Returns the first step at which the produced trace differs from the reference one.
+pub fn first_divergence(actual: &[Step], expected: &[Step]) -> Option<Divergence> {
+	let differing_field = |a: &Step, e: &Step| {
+		if a.depth != e.depth {
+			Some("depth")
+		} else if a.pc != e.pc {
+			Some("pc")
+		} else if a.op != e.op {
+			Some("op")
+		} else if a.gas != e.gas {
+			Some("gas")
+		} else if a.stack != e.stack {
+			Some("stack")
+		} else {
+			None
+		}
+	};
+
+	let index = actual.iter().zip(expected)
+		.position(|(a, e)| differing_field(a, e).is_some())
+		.or_else(|| if actual.len() == expected.len() { None } else { Some(actual.len().min(expected.len())) })?;
+
+	let actual_step = actual.get(index).cloned();
+	let expected_step = expected.get(index).cloned();
+	let field = match (&actual_step, &expected_step) {
+		(Some(a), Some(e)) => differing_field(a, e).expect("index points to the differing step; qed"),
+		_ => "length",
+	};
+
+	Some(Divergence {
+		index,
+		field,
+		previous: index.checked_sub(1).and_then(|i| expected.get(i)).cloned(),
+		actual: actual_step,
+		expected: expected_step,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{first_divergence, parse_trace};
+
+	const TRACE: &str = r#"{"pc":0,"op":96,"opName":"PUSH1","gas":"0xffff","stack":[],"storage":{},"depth":1}
+{"pc":2,"op":96,"opName":"PUSH1","gas":"0xfffc","stack":["0xf8"],"storage":{},"depth":1}
+{"pc":4,"op":1,"opName":"ADD","gas":"0xfff9","stack":["0xf8","0x1"],"storage":{},"depth":1}
+{"stateRoot":"0x0000000000000000000000000000000000000000000000000000000000000000"}
+"#;
+
+	#[test]
+	fn should_match_identical_traces() {
+		let trace = parse_trace(TRACE).unwrap();
+		assert_eq!(trace.len(), 3);
+
+		// reference clients may use numeric gas and leading zeros
+		let reference = TRACE.replace(r#""gas":"0xffff""#, r#""gas":65535"#).replace("0xf8", "0x00f8");
+		assert_eq!(first_divergence(&trace, &parse_trace(&reference).unwrap()), None);
+	}
+
+	#[test]
+	fn should_report_first_divergent_step() {
+		let trace = parse_trace(TRACE).unwrap();
+		let reference = parse_trace(&TRACE.replace(r#"["0xf8","0x1"]"#, r#"["0xf8","0x2"]"#)).unwrap();
+
+		let divergence = first_divergence(&trace, &reference).unwrap();
+		assert_eq!(divergence.index, 2);
+		assert_eq!(divergence.field, "stack");
+		assert_eq!(divergence.to_string(), "Traces diverge at step 2 (stack differs)
+  previous: pc=2 op=0x60 (PUSH1) gas=0xfffc depth=1 stack top=0xf8
+  expected: pc=4 op=0x01 (ADD) gas=0xfff9 depth=1 stack top=0x2
+  actual:   pc=4 op=0x01 (ADD) gas=0xfff9 depth=1 stack top=0x1");
+
+		let divergence = first_divergence(&trace[..2], &trace).unwrap();
+		assert_eq!(divergence.index, 2);
+		assert_eq!(divergence.field, "length");
+		assert_eq!(divergence.actual, None);
+	}
+}
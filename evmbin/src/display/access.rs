@@ -0,0 +1,279 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Report of the accounts and storage slots accessed during execution.
+
+use std::collections::BTreeMap;
+
+use ethereum_types::{Address, H256, U256, BigEndianHash};
+use evm::Instruction;
+use parity_bytes::ToPretty;
+use serde::Serialize;
+use trace;
+
+use crate::{
+	display,
+	info as vm,
+};
+
+/// Number of accesses to an account and to each of its storage slots.
+/// The first access is cold, all the following ones are warm.
+#[derive(Debug, Default, PartialEq)]
+pub struct AccountAccess {
+	/// Number of accesses to the account itself.
+	pub accesses: usize,
+	/// Number of accesses to each storage slot of the account.
+	pub storage: BTreeMap<H256, usize>,
+}
+
+/// Accounts accessed during execution.
+pub type AccessReport = BTreeMap<Address, AccountAccess>;
+
+/// EIP-2930 access list entry.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItem<'a> {
+	address: &'a Address,
+	storage_keys: Vec<&'a H256>,
+}
+
+/// Returns the report as an EIP-2930 access list.
+pub fn access_list(report: &AccessReport) -> Vec<AccessListItem> {
+	report.iter()
+		.map(|(address, access)| AccessListItem {
+			address,
+			storage_keys: access.storage.keys().collect(),
+		})
+		.collect()
+}
+
+/// Execution frame of a call or create.
+#[derive(Default)]
+struct Frame {
+	/// Address whose storage is accessed, unknown for contract creation until it returns.
+	address: Option<Address>,
+	instruction: u8,
+	stack: Vec<U256>,
+	/// Storage accessed while the address is still unknown.
+	pending_storage: BTreeMap<H256, usize>,
+	/// Storage accessed by the contract creation which has just returned to this frame.
+	created_storage: Option<BTreeMap<H256, usize>>,
+}
+
+/// Informant recording every account and storage slot touched during execution.
+pub struct Informant {
+	report: AccessReport,
+	frames: Vec<Frame>,
+	next_address: Option<Address>,
+	json: bool,
+}
+
+impl Informant {
+	/// Create new informant for the execution starting at `address`.
+	/// With `json` the report is displayed as an EIP-2930 access list.
+	pub fn new(address: Address, json: bool) -> Self {
+		Informant {
+			report: Default::default(),
+			frames: Default::default(),
+			next_address: Some(address),
+			json,
+		}
+	}
+
+	fn access_account(&mut self, address: Address) {
+		self.report.entry(address).or_default().accesses += 1;
+	}
+
+	fn access_storage(&mut self, key: H256) {
+		let frame = self.frames.last_mut().expect("instructions are traced within a subtrace; qed");
+		match frame.address {
+			Some(address) => *self.report.entry(address).or_default().storage.entry(key).or_insert(0) += 1,
+			None => *frame.pending_storage.entry(key).or_insert(0) += 1,
+		}
+	}
+
+	fn display(report: &AccessReport) {
+		println!("Accessed accounts: {}", report.len());
+		for (address, access) in report {
+			println!("  {:?}: {} access(es)", address, access.accesses);
+			for (key, accesses) in &access.storage {
+				println!("    {:?}: {} access(es)", key, accesses);
+			}
+		}
+	}
+}
+
+impl vm::Informant for Informant {
+	type Sink = bool;
+
+	fn before_test(&mut self, name: &str, action: &str) {
+		println!("Test: {} ({})", name, action);
+	}
+
+	fn clone_sink(&self) -> Self::Sink { self.json }
+
+	fn finish(result: vm::RunResult<Self::Output>, json: &mut Self::Sink) {
+		let traces = match result {
+			Ok(success) => {
+				if !*json {
+					println!("Output: 0x{}", success.output.to_hex());
+					println!("Gas used: {:x}", success.gas_used);
+					display::print_gas_breakdown(&success.gas_breakdown);
+					println!("Time: {}", display::format_time(&success.time));
+				}
+				success.traces
+			},
+			Err(failure) => {
+				if *json {
+					eprintln!("Error: {}", failure.error);
+				} else {
+					println!("Error: {}", failure.error);
+					println!("Time: {}", display::format_time(&failure.time));
+				}
+				failure.traces
+			},
+		};
+
+		let report = traces.unwrap_or_default();
+		if *json {
+			let s = serde_json::to_string(&access_list(&report)).expect("Serialization cannot fail; qed");
+			println!("{}", s);
+		} else {
+			Self::display(&report);
+		}
+	}
+}
+
+impl trace::VMTracer for Informant {
+	type Output = AccessReport;
+
+	fn trace_next_instruction(&mut self, _pc: usize, instruction: u8, _current_gas: U256) -> bool {
+		let (address, top, second) = {
+			let frame = self.frames.last_mut().expect("instructions are traced within a subtrace; qed");
+			frame.instruction = instruction;
+			let len = frame.stack.len();
+			let top = if len > 0 { Some(frame.stack[len - 1]) } else { None };
+			let second = if len > 1 { Some(frame.stack[len - 2]) } else { None };
+			(frame.address, top, second)
+		};
+		let to_address = |word: U256| Address::from(H256::from_uint(&word));
+
+		match Instruction::from_u8(instruction) {
+			Some(Instruction::SLOAD) | Some(Instruction::SSTORE) => {
+				if let Some(key) = top {
+					self.access_storage(H256::from_uint(&key));
+				}
+			},
+			Some(Instruction::BALANCE) | Some(Instruction::EXTCODESIZE) | Some(Instruction::EXTCODECOPY) |
+			Some(Instruction::EXTCODEHASH) | Some(Instruction::SUICIDE) => {
+				if let Some(account) = top {
+					self.access_account(to_address(account));
+				}
+			},
+			Some(Instruction::CALL) | Some(Instruction::STATICCALL) => {
+				if let Some(account) = second {
+					self.access_account(to_address(account));
+					self.next_address = Some(to_address(account));
+				}
+			},
+			Some(Instruction::CALLCODE) | Some(Instruction::DELEGATECALL) => {
+				if let Some(account) = second {
+					self.access_account(to_address(account));
+					self.next_address = address;
+				}
+			},
+			Some(Instruction::CREATE) | Some(Instruction::CREATE2) => {
+				self.next_address = None;
+			},
+			_ => {},
+		}
+		true
+	}
+
+	fn trace_executed(&mut self, _gas_used: U256, stack_push: &[U256], _mem: &[u8]) {
+		let created = {
+			let frame = self.frames.last_mut().expect("instructions are traced within a subtrace; qed");
+			let info = Instruction::from_u8(frame.instruction).map(|i| i.info());
+			let len = frame.stack.len();
+			let info_args = info.map(|i| i.args).unwrap_or(0);
+			frame.stack.truncate(if len > info_args { len - info_args } else { 0 });
+			frame.stack.extend_from_slice(stack_push);
+			frame.created_storage.take()
+		};
+
+		// the address of the created contract is pushed onto the stack, zero if creation failed
+		if let (Some(storage), Some(address)) = (created, stack_push.first()) {
+			if !address.is_zero() {
+				let address = Address::from(H256::from_uint(address));
+				self.access_account(address);
+				let account = self.report.entry(address).or_default();
+				for (key, accesses) in storage {
+					*account.storage.entry(key).or_insert(0) += accesses;
+				}
+			}
+		}
+	}
+
+	fn prepare_subtrace(&mut self, _code: &[u8]) {
+		let address = self.next_address.take();
+		if let (true, Some(address)) = (self.frames.is_empty(), address) {
+			self.access_account(address);
+		}
+		self.frames.push(Frame {
+			address,
+			..Default::default()
+		});
+	}
+
+	fn done_subtrace(&mut self) {
+		let frame = self.frames.pop().expect("prepare/done_subtrace are not balanced");
+		if frame.address.is_none() {
+			if let Some(parent) = self.frames.last_mut() {
+				parent.created_storage = Some(frame.pending_storage);
+			}
+		}
+	}
+
+	fn drain(self) -> Option<Self::Output> { Some(self.report) }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::info::tests::run_test;
+
+	fn compare(report: Option<AccessReport>, expected: &str) {
+		let report = report.expect("report is always drained; qed");
+		assert_eq!(serde_json::to_string(&access_list(&report)).unwrap(), expected);
+	}
+
+	#[test]
+	fn should_report_accessed_accounts_and_storage() {
+		// SSTORE 2, SLOAD 3, BALANCE 4
+		run_test(
+			Informant::new(Address::zero(), true),
+			&compare,
+			"6001600255600354600431",
+			0xffffff,
+			concat!(
+				r#"[{"address":"0x0000000000000000000000000000000000000000","storageKeys":["#,
+				r#""0x0000000000000000000000000000000000000000000000000000000000000002","#,
+				r#""0x0000000000000000000000000000000000000000000000000000000000000003"]},"#,
+				r#"{"address":"0x0000000000000000000000000000000000000004","storageKeys":[]}]"#,
+			),
+		);
+	}
+}
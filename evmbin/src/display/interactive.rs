@@ -0,0 +1,362 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Interactive debugger pausing the execution at breakpoints.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use ethereum_types::{H256, U256, BigEndianHash};
+use evm::Instruction;
+use parity_bytes::ToPretty;
+use trace;
+
+use crate::{
+	display,
+	info as vm,
+};
+
+const HELP: &str = "\
+Commands:
+    c, continue            Run until the next breakpoint.
+    s, step [N]            Execute N instructions (defaults to 1).
+    stack                  Print the stack, top first.
+    memory OFFSET LEN      Print LEN bytes of memory starting at OFFSET.
+    storage                Dump the storage read or written by the current call.
+    break PC|OPCODE        Add a breakpoint at a program counter or an opcode.
+    q, quit                Abort the execution.
+    h, help                Display this message.";
+
+fn parse_number(s: &str) -> Result<usize, String> {
+	if s.starts_with("0x") {
+		usize::from_str_radix(&s[2..], 16).map_err(|e| format!("Invalid number {}: {}", s, e))
+	} else {
+		s.parse().map_err(|e| format!("Invalid number {}: {}", s, e))
+	}
+}
+
+/// Location the execution is paused at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Breakpoint {
+	/// Program counter of the instruction.
+	Pc(usize),
+	/// Opcode of the instruction.
+	Op(u8),
+}
+
+impl Breakpoint {
+	fn matches(&self, pc: usize, instruction: u8) -> bool {
+		match *self {
+			Breakpoint::Pc(at) => at == pc,
+			Breakpoint::Op(op) => op == instruction,
+		}
+	}
+}
+
+impl FromStr for Breakpoint {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.chars().next().map_or(false, |c| c.is_ascii_digit()) {
+			return parse_number(s).map(Breakpoint::Pc);
+		}
+
+		(0..=255u8)
+			.find(|op| Instruction::from_u8(*op).map_or(false, |i| i.info().name.eq_ignore_ascii_case(s)))
+			.map(Breakpoint::Op)
+			.ok_or_else(|| format!("Unknown opcode: {}", s))
+	}
+}
+
+/// Command accepted at the debugger prompt.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+	/// Run until the next breakpoint.
+	Continue,
+	/// Execute given number of instructions.
+	Step(usize),
+	/// Print the stack.
+	Stack,
+	/// Print a slice of memory.
+	Memory(usize, usize),
+	/// Dump the storage.
+	Storage,
+	/// Add a breakpoint.
+	Break(Breakpoint),
+	/// Abort the execution.
+	Quit,
+	/// Print available commands.
+	Help,
+}
+
+impl FromStr for Command {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let words = s.split_whitespace().collect::<Vec<_>>();
+		let arg = |index: usize, name: &str| words.get(index).cloned().ok_or_else(|| format!("Missing {}", name));
+
+		Ok(match words.first().cloned().unwrap_or("step") {
+			"c" | "continue" => Command::Continue,
+			"s" | "step" => match words.get(1) {
+				Some(steps) => Command::Step(parse_number(steps)?.max(1)),
+				None => Command::Step(1),
+			},
+			"stack" => Command::Stack,
+			"memory" => Command::Memory(parse_number(arg(1, "OFFSET")?)?, parse_number(arg(2, "LEN")?)?),
+			"storage" => Command::Storage,
+			"break" => Command::Break(arg(1, "PC or OPCODE")?.parse()?),
+			"q" | "quit" => Command::Quit,
+			"h" | "help" => Command::Help,
+			other => return Err(format!("Unknown command: {}. Type `help` for the list of commands.", other)),
+		})
+	}
+}
+
+/// Machine state of a single call.
+#[derive(Default)]
+struct Frame {
+	instruction: u8,
+	mem_written: Option<(usize, usize)>,
+	store_written: Option<(U256, U256)>,
+	stack: Vec<U256>,
+	memory: Vec<u8>,
+	storage: BTreeMap<H256, H256>,
+}
+
+/// Informant pausing the execution at breakpoints and reading debugger commands from `input`.
+pub struct Informant<R, W> {
+	input: R,
+	output: W,
+	breakpoints: Vec<Breakpoint>,
+	/// Number of instructions to execute before pausing, `None` to run until a breakpoint.
+	steps: Option<usize>,
+	frames: Vec<Frame>,
+}
+
+impl Informant<io::BufReader<io::Stdin>, io::Stdout> {
+	/// Create new debugger reading commands from the standard input.
+	/// Without breakpoints the execution is paused before the first instruction.
+	pub fn new(breakpoints: Vec<Breakpoint>) -> Self {
+		Informant::with_io(io::BufReader::new(io::stdin()), io::stdout(), breakpoints)
+	}
+}
+
+impl<R: BufRead, W: Write> Informant<R, W> {
+	/// Create new debugger reading commands from `input` and writing the machine state to `output`.
+	pub fn with_io(input: R, output: W, breakpoints: Vec<Breakpoint>) -> Self {
+		let steps = if breakpoints.is_empty() { Some(0) } else { None };
+		Informant {
+			input,
+			output,
+			breakpoints,
+			steps,
+			frames: vec![Frame::default()],
+		}
+	}
+
+	fn frame(&mut self) -> &mut Frame {
+		self.frames.last_mut().expect("there is always at least one frame; qed")
+	}
+
+	/// Displays the current instruction and handles commands until the execution is resumed.
+	fn pause(&mut self, pc: usize, instruction: u8, gas: U256) -> io::Result<()> {
+		let name = Instruction::from_u8(instruction).map_or("UNKNOWN", |i| i.info().name);
+		writeln!(self.output, "[depth {}] pc {:#x}: {} (gas {:#x})", self.frames.len() - 1, pc, name, gas)?;
+
+		loop {
+			write!(self.output, "> ")?;
+			self.output.flush()?;
+
+			let mut line = String::new();
+			if self.input.read_line(&mut line)? == 0 {
+				// no more commands, run to completion.
+				self.breakpoints.clear();
+				self.steps = None;
+				return Ok(());
+			}
+
+			match line.parse() {
+				Ok(Command::Continue) => {
+					self.steps = None;
+					return Ok(());
+				},
+				Ok(Command::Step(steps)) => {
+					self.steps = Some(steps);
+					return Ok(());
+				},
+				Ok(Command::Stack) => {
+					let frame = self.frames.last().expect("there is always at least one frame; qed");
+					for (index, item) in frame.stack.iter().rev().enumerate() {
+						writeln!(self.output, "{}: {:#x}", index, item)?;
+					}
+				},
+				Ok(Command::Memory(offset, len)) => {
+					let memory = &self.frames.last().expect("there is always at least one frame; qed").memory;
+					let slice = (offset..offset.saturating_add(len))
+						.map(|i| memory.get(i).cloned().unwrap_or(0))
+						.collect::<Vec<u8>>();
+					writeln!(self.output, "0x{}", slice.to_hex())?;
+				},
+				Ok(Command::Storage) => {
+					let frame = self.frames.last().expect("there is always at least one frame; qed");
+					for (key, value) in &frame.storage {
+						writeln!(self.output, "{:?}: {:?}", key, value)?;
+					}
+				},
+				Ok(Command::Break(breakpoint)) => {
+					writeln!(self.output, "Breakpoint {} set.", self.breakpoints.len())?;
+					self.breakpoints.push(breakpoint);
+				},
+				Ok(Command::Quit) => ::std::process::exit(1),
+				Ok(Command::Help) => writeln!(self.output, "{}", HELP)?,
+				Err(err) => writeln!(self.output, "{}", err)?,
+			}
+		}
+	}
+}
+
+impl<R: BufRead, W: Write> vm::Informant for Informant<R, W> {
+	type Sink = ();
+
+	fn before_test(&mut self, name: &str, action: &str) {
+		let _ = writeln!(self.output, "Test: {} ({})", name, action);
+	}
+
+	fn clone_sink(&self) -> Self::Sink { () }
+
+	fn finish(result: vm::RunResult<Self::Output>, sink: &mut Self::Sink) {
+		<display::simple::Informant as vm::Informant>::finish(result, sink)
+	}
+}
+
+impl<R: BufRead, W: Write> trace::VMTracer for Informant<R, W> {
+	type Output = ();
+
+	fn trace_next_instruction(&mut self, pc: usize, instruction: u8, current_gas: U256) -> bool {
+		let pause = self.steps == Some(0) || self.breakpoints.iter().any(|b| b.matches(pc, instruction));
+		if pause {
+			if let Err(err) = self.pause(pc, instruction, current_gas) {
+				eprintln!("Debugger I/O error: {}", err);
+				self.breakpoints.clear();
+				self.steps = None;
+			}
+		}
+		if let Some(ref mut steps) = self.steps {
+			*steps = steps.saturating_sub(1);
+		}
+		true
+	}
+
+	fn trace_prepare_execute(&mut self, _pc: usize, instruction: u8, _gas_cost: U256, mem_written: Option<(usize, usize)>, store_written: Option<(U256, U256)>) {
+		let frame = self.frame();
+		frame.instruction = instruction;
+		frame.mem_written = mem_written;
+		frame.store_written = store_written;
+	}
+
+	fn trace_executed(&mut self, _gas_used: U256, stack_push: &[U256], mem: &[u8]) {
+		let frame = self.frame();
+		let info = Instruction::from_u8(frame.instruction).map(|i| i.info());
+
+		// SLOAD pops the key and pushes the value it reads.
+		if frame.instruction == Instruction::SLOAD as u8 {
+			if let (Some(key), Some(value)) = (frame.stack.last(), stack_push.first()) {
+				frame.storage.insert(BigEndianHash::from_uint(key), BigEndianHash::from_uint(value));
+			}
+		}
+		if let Some((key, value)) = frame.store_written.take() {
+			frame.storage.insert(BigEndianHash::from_uint(&key), BigEndianHash::from_uint(&value));
+		}
+
+		let len = frame.stack.len();
+		let args = info.map_or(0, |i| i.args);
+		frame.stack.truncate(len.saturating_sub(args));
+		frame.stack.extend_from_slice(stack_push);
+
+		if let Some((pos, size)) = frame.mem_written.take() {
+			if frame.memory.len() < pos + size {
+				frame.memory.resize(pos + size, 0);
+			}
+			frame.memory[pos..(pos + size)].copy_from_slice(&mem[pos..(pos + size)]);
+		}
+	}
+
+	fn prepare_subtrace(&mut self, _code: &[u8]) {
+		self.frames.push(Frame::default());
+	}
+
+	fn done_subtrace(&mut self) {
+		self.frames.pop();
+	}
+
+	fn drain(self) -> Option<()> { None }
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+
+	use super::*;
+	use crate::{compare::Capture, info::tests::run_test};
+
+	fn debug(code: &str, commands: &str, breakpoints: Vec<Breakpoint>) -> String {
+		let output = Capture::default();
+		let informant = Informant::with_io(Cursor::new(commands.as_bytes().to_vec()), output.clone(), breakpoints);
+		run_test(informant, |_, _| {}, code, 0xffff, "");
+		output.contents()
+	}
+
+	#[test]
+	fn should_parse_commands() {
+		assert_eq!("c".parse::<Command>(), Ok(Command::Continue));
+		assert_eq!("".parse::<Command>(), Ok(Command::Step(1)));
+		assert_eq!("step 0x10".parse::<Command>(), Ok(Command::Step(16)));
+		assert_eq!("memory 32 0x20".parse::<Command>(), Ok(Command::Memory(32, 32)));
+		assert_eq!("break sstore".parse::<Command>(), Ok(Command::Break(Breakpoint::Op(0x55))));
+		assert_eq!("break 0x1a".parse::<Command>(), Ok(Command::Break(Breakpoint::Pc(26))));
+		assert!("memory 32".parse::<Command>().is_err());
+		assert!("break FOO".parse::<Command>().is_err());
+		assert!("jump".parse::<Command>().is_err());
+	}
+
+	#[test]
+	fn should_step_and_print_stack() {
+		// PUSH1 0x01 PUSH1 0x02 ADD
+		let output = debug("6001600201", "step 2\nstack\ncontinue\n", vec![]);
+
+		assert_eq!(
+			output,
+			"[depth 0] pc 0x0: PUSH1 (gas 0xffff)\n> \
+			[depth 0] pc 0x4: ADD (gas 0xfff9)\n> \
+			0: 0x2\n\
+			1: 0x1\n> "
+		);
+	}
+
+	#[test]
+	fn should_stop_at_breakpoints_and_dump_storage() {
+		// PUSH1 0x05 PUSH1 0x01 SSTORE PUSH1 0x2a PUSH1 0x00 MSTORE PUSH1 0x20 PUSH1 0x00 RETURN
+		let output = debug("6005600155602a60005260206000f3", "storage\nmemory 0 32\n", vec![Breakpoint::Op(0xf3)]);
+
+		assert!(output.starts_with("[depth 0] pc 0xe: RETURN (gas "));
+		assert!(output.ends_with(")\n> \
+			0x0000000000000000000000000000000000000000000000000000000000000001: 0x0000000000000000000000000000000000000000000000000000000000000005\n> \
+			0x000000000000000000000000000000000000000000000000000000000000002a\n> "
+		));
+	}
+}
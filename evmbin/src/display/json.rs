@@ -26,7 +26,7 @@ use trace;
 
 use crate::{
 	display,
-	info as vm,
+	info::{self as vm, GasBreakdown},
 };
 
 /// JSON formatting informant.
@@ -76,6 +76,8 @@ pub struct MessageSuccess<'a> {
 	output: &'a str,
 	gas_used: &'a str,
 	time: &'a u64,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	gas_breakdown: Option<&'a GasBreakdown>,
 }
 
 #[derive(Serialize, Debug)]
@@ -84,6 +86,8 @@ pub struct MessageFailure<'a> {
 	error: &'a str,
 	gas_used: &'a str,
 	time: &'a u64,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	gas_breakdown: Option<&'a GasBreakdown>,
 }
 
 impl Informant {
@@ -149,6 +153,7 @@ impl vm::Informant for Informant {
 						output: &format!("0x{}", success.output.to_hex()),
 						gas_used: &format!("{:#x}", success.gas_used),
 						time: &display::as_micros(&success.time),
+						gas_breakdown: success.gas_breakdown.as_ref(),
 					}
 				;
 
@@ -165,6 +170,7 @@ impl vm::Informant for Informant {
 						error: &failure.error.to_string(),
 						gas_used: &format!("{:#x}", failure.gas_used),
 						time: &display::as_micros(&failure.time),
+						gas_breakdown: failure.gas_breakdown.as_ref(),
 					}
 				;
 
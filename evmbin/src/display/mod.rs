@@ -18,15 +18,29 @@
 
 use std::time::Duration;
 
+use crate::info::GasBreakdown;
+
 pub mod json;
 pub mod std_json;
 pub mod simple;
+pub mod access;
+pub mod interactive;
 
 /// Formats duration into human readable format.
 pub fn format_time(time: &Duration) -> String {
 	format!("{}.{:09}s", time.as_secs(), time.subsec_nanos())
 }
 
+/// Prints the gas accounting of a transaction, if any.
+pub fn print_gas_breakdown(breakdown: &Option<GasBreakdown>) {
+	if let Some(ref breakdown) = *breakdown {
+		println!("Intrinsic gas: {:x}", breakdown.intrinsic);
+		println!("Execution gas: {:x}", breakdown.execution);
+		println!("Refunded gas: {:x} (cap: {:x})", breakdown.refunded, breakdown.refund_cap);
+		println!("Returned gas: {:x}", breakdown.returned);
+	}
+}
+
 /// Formats the time as microseconds.
 pub fn as_micros(time: &Duration) -> u64 {
 	time.as_secs() * 1_000_000 + time.subsec_nanos() as u64 / 1_000
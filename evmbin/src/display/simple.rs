@@ -43,10 +43,12 @@ impl vm::Informant for Informant {
 			Ok(success) => {
 				println!("Output: 0x{}", success.output.to_hex());
 				println!("Gas used: {:x}", success.gas_used);
+				display::print_gas_breakdown(&success.gas_breakdown);
 				println!("Time: {}", display::format_time(&success.time));
 			},
 			Err(failure) => {
 				println!("Error: {}", failure.error);
+				display::print_gas_breakdown(&failure.gas_breakdown);
 				println!("Time: {}", display::format_time(&failure.time));
 			},
 		}
@@ -27,7 +27,7 @@ use trace;
 
 use crate::{
 	display,
-	info as vm,
+	info::{self as vm, GasBreakdown},
 };
 
 pub trait Writer: io::Write + Send + Sized {
@@ -92,6 +92,8 @@ pub struct MessageSuccess<'a> {
 	output: &'a str,
 	gas_used: &'a str,
 	time: &'a u64,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	gas_breakdown: Option<&'a GasBreakdown>,
 }
 
 #[derive(Serialize, Debug)]
@@ -100,6 +102,8 @@ pub struct MessageFailure<'a> {
 	error: &'a str,
 	gas_used: &'a str,
 	time: &'a u64,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	gas_breakdown: Option<&'a GasBreakdown>,
 }
 
 #[derive(Serialize, Debug)]
@@ -216,6 +220,7 @@ impl<Trace: Writer, Out: Writer> vm::Informant for Informant<Trace, Out> {
 						output: &format!("0x{}", success.output.to_hex()),
 						gas_used: &format!("{:#x}", success.gas_used),
 						time: &display::as_micros(&success.time),
+						gas_breakdown: success.gas_breakdown.as_ref(),
 					}
 				;
 
@@ -228,6 +233,7 @@ impl<Trace: Writer, Out: Writer> vm::Informant for Informant<Trace, Out> {
 						error: &failure.error.to_string(),
 						gas_used: &format!("{:#x}", failure.gas_used),
 						time: &display::as_micros(&failure.time),
+						gas_breakdown: failure.gas_breakdown.as_ref(),
 					}
 				;
 
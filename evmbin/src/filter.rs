@@ -0,0 +1,206 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State test selection: name patterns, skip-lists and fork ranges.
+
+use std::fs;
+use std::path::Path;
+
+use ethjson::spec::ForkSpec;
+
+/// Forks in activation order. Transition test-nets are ranked by the fork they transition to.
+const FORK_ORDER: &[&str] = &[
+	"frontier",
+	"homestead",
+	"eip150",
+	"eip158",
+	"byzantium",
+	"constantinople",
+	"constantinoplefix",
+	"istanbul",
+];
+
+/// Returns the position of a fork in `FORK_ORDER`.
+fn fork_rank(name: &str) -> Option<usize> {
+	let name = match name.to_lowercase().as_str() {
+		"frontiertohomesteadat5" | "homesteadtodaoat5" => "homestead".to_owned(),
+		"homesteadtoeip150at5" => "eip150".to_owned(),
+		"eip158tobyzantiumat5" => "byzantium".to_owned(),
+		other => other.to_owned(),
+	};
+	FORK_ORDER.iter().position(|fork| *fork == name)
+}
+
+/// Case-insensitive glob match supporting `*` (any sequence) and `?` (any single character).
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+	let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+	let name: Vec<char> = name.to_lowercase().chars().collect();
+
+	let (mut p, mut n) = (0, 0);
+	// position of the last `*` in pattern and the name position it matched up to
+	let mut backtrack = None;
+	while n < name.len() {
+		if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+			p += 1;
+			n += 1;
+		} else if p < pattern.len() && pattern[p] == '*' {
+			backtrack = Some((p, n));
+			p += 1;
+		} else if let Some((star, matched)) = backtrack {
+			p = star + 1;
+			n = matched + 1;
+			backtrack = Some((star, n));
+		} else {
+			return false;
+		}
+	}
+	pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Selects which state tests and forks should be executed.
+#[derive(Debug, Default, PartialEq)]
+pub struct TestFilter {
+	/// Glob pattern the test name must match (`--only`).
+	only: Option<String>,
+	/// Exact fork name (`--chain`).
+	chain: Option<String>,
+	/// Lowest fork rank to run (`--from-fork`).
+	from_fork: Option<usize>,
+	/// Highest fork rank to run (`--to-fork`).
+	to_fork: Option<usize>,
+	/// Known-bad test name patterns with the reason they are skipped (`--skip`).
+	skip: Vec<(String, String)>,
+}
+
+impl TestFilter {
+	/// Create a new filter from CLI options.
+	pub fn new(only: Option<&str>, chain: Option<&str>, from_fork: Option<&str>, to_fork: Option<&str>) -> Result<Self, String> {
+		let rank = |fork: &str| fork_rank(fork).ok_or_else(|| format!("Unknown fork: {}", fork));
+		let from_fork = from_fork.map(rank).transpose()?;
+		let to_fork = to_fork.map(rank).transpose()?;
+		if let (Some(from), Some(to)) = (from_fork, to_fork) {
+			if from > to {
+				return Err("--from-fork must not be later than --to-fork".into());
+			}
+		}
+
+		Ok(TestFilter {
+			only: only.map(str::to_owned),
+			chain: chain.map(str::to_lowercase),
+			from_fork,
+			to_fork,
+			skip: Vec::new(),
+		})
+	}
+
+	/// Read a skip-list file.
+	///
+	/// Each line contains a test name (or glob pattern) optionally followed by the reason it is skipped.
+	/// Empty lines and lines starting with `#` are ignored.
+	pub fn load_skip_list(&mut self, path: &Path) -> Result<(), String> {
+		let content = fs::read_to_string(path).map_err(|e| format!("Unable to read skip-list {:?}: {}", path, e))?;
+		self.skip.extend(parse_skip_list(&content));
+		Ok(())
+	}
+
+	/// Returns true if the test with given name was selected.
+	pub fn matches_test(&self, name: &str) -> bool {
+		self.only.as_ref().map_or(true, |only| glob_match(only, name))
+	}
+
+	/// Returns the reason why test with given name should be skipped, if any.
+	pub fn skip_reason(&self, name: &str) -> Option<&str> {
+		self.skip.iter()
+			.find(|(pattern, _)| glob_match(pattern, name))
+			.map(|(_, reason)| reason.as_str())
+	}
+
+	/// Returns true if the tests for given fork should be run.
+	pub fn matches_fork(&self, fork: &ForkSpec) -> bool {
+		let name = format!("{:?}", fork).to_lowercase();
+		if self.chain.as_ref().map_or(false, |chain| *chain != name) {
+			return false;
+		}
+		if self.from_fork.is_none() && self.to_fork.is_none() {
+			return true;
+		}
+		match fork_rank(&name) {
+			Some(rank) => self.from_fork.map_or(true, |from| rank >= from) && self.to_fork.map_or(true, |to| rank <= to),
+			None => false,
+		}
+	}
+}
+
+fn parse_skip_list(content: &str) -> Vec<(String, String)> {
+	content.lines()
+		.map(str::trim)
+		.filter(|line| !line.is_empty() && !line.starts_with('#'))
+		.map(|line| {
+			let mut parts = line.splitn(2, char::is_whitespace);
+			let name = parts.next().expect("splitn always yields at least one item; qed").to_owned();
+			let reason = parts.next().map(str::trim).filter(|r| !r.is_empty()).unwrap_or("known failure").to_owned();
+			(name, reason)
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use ethjson::spec::ForkSpec;
+	use super::{TestFilter, glob_match, parse_skip_list};
+
+	#[test]
+	fn should_match_globs() {
+		assert!(glob_match("add11", "add11"));
+		assert!(glob_match("ADD*", "add11"));
+		assert!(glob_match("add1?", "add12"));
+		assert!(glob_match("*Precompiles", "create2callPrecompiles"));
+		assert!(glob_match("c*call*s", "create2callPrecompiles"));
+		assert!(!glob_match("add1?", "add123"));
+		assert!(!glob_match("add", "add11"));
+	}
+
+	#[test]
+	fn should_parse_skip_list() {
+		let skip = parse_skip_list("# comment\n\nadd11   broken since forever\ncreate2*\n");
+		assert_eq!(skip, vec![
+			("add11".to_owned(), "broken since forever".to_owned()),
+			("create2*".to_owned(), "known failure".to_owned()),
+		]);
+	}
+
+	#[test]
+	fn should_filter_fork_range() {
+		let filter = TestFilter::new(None, None, Some("Homestead"), Some("Byzantium")).unwrap();
+		assert!(!filter.matches_fork(&ForkSpec::Frontier));
+		assert!(filter.matches_fork(&ForkSpec::Homestead));
+		assert!(filter.matches_fork(&ForkSpec::EIP158));
+		assert!(filter.matches_fork(&ForkSpec::EIP158ToByzantiumAt5));
+		assert!(!filter.matches_fork(&ForkSpec::Constantinople));
+		assert!(TestFilter::new(None, None, Some("Byzantium"), Some("Frontier")).is_err());
+		assert!(TestFilter::new(None, None, Some("Unknown"), None).is_err());
+	}
+
+	#[test]
+	fn should_report_skip_reason() {
+		let mut filter = TestFilter::new(Some("add*"), None, None, None).unwrap();
+		filter.skip = parse_skip_list("add12 consensus bug #123");
+		assert!(filter.matches_test("add11"));
+		assert!(!filter.matches_test("create2callPrecompiles"));
+		assert_eq!(filter.skip_reason("add12"), Some("consensus bug #123"));
+		assert_eq!(filter.skip_reason("add11"), None);
+	}
+}
@@ -0,0 +1,209 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pre-population of the state from a live node.
+//!
+//! Accounts and storage slots are fetched over JSON-RPC the first time the execution accesses them.
+//! Since the state has to be known before the execution starts, the execution is repeated
+//! with the fetched state until it doesn't access anything new.
+
+use std::collections::BTreeMap;
+
+use ethereum_types::{Address, H256, U256};
+use fetch::{self, Fetch};
+use futures::Future;
+use parity_bytes::Bytes;
+use pod::{PodAccount, PodState};
+use rustc_hex::FromHex;
+use serde::de::DeserializeOwned;
+use serde_json::{self, json, Value};
+
+use crate::display::access::AccessReport;
+
+/// Maximal number of executions while looking for the accessed state.
+pub const MAX_ROUNDS: usize = 64;
+
+/// State of the forked chain.
+pub trait StateSource {
+	/// Fetch balance, nonce and code of given account.
+	fn account(&mut self, address: &Address) -> Result<PodAccount, String>;
+
+	/// Fetch the value of given storage slot.
+	fn storage(&mut self, address: &Address, key: &H256) -> Result<H256, String>;
+}
+
+/// State fetched from a remote node over JSON-RPC.
+pub struct RpcSource {
+	client: fetch::Client,
+	url: fetch::Url,
+	block: String,
+	id: u64,
+}
+
+impl RpcSource {
+	/// Create a source of the state at `block` (latest if `None`) of the node at `url`.
+	pub fn new(url: &str, block: Option<u64>) -> Result<Self, String> {
+		Ok(RpcSource {
+			client: fetch::Client::new(1).map_err(|e| format!("Unable to create HTTP client: {:?}", e))?,
+			url: url.parse().map_err(|e| format!("Invalid URL {}: {}", url, e))?,
+			block: block.map_or_else(|| "latest".into(), |number| format!("0x{:x}", number)),
+			id: 0,
+		})
+	}
+
+	fn call<T: DeserializeOwned>(&mut self, method: &str, mut params: Vec<Value>) -> Result<T, String> {
+		self.id += 1;
+		params.push(self.block.clone().into());
+		let body = json!({ "jsonrpc": "2.0", "id": self.id, "method": method, "params": params });
+		let request = fetch::Request::post(self.url.clone())
+			.with_header(fetch::header::CONTENT_TYPE, fetch::header::HeaderValue::from_static("application/json"))
+			.with_body(body.to_string());
+
+		let response = self.client.fetch(request, Default::default())
+			.wait()
+			.map_err(|e| format!("{} request failed: {:?}", method, e))?;
+		if !response.is_success() {
+			return Err(format!("{} request failed: HTTP {}", method, response.status()));
+		}
+		let mut response: Value = serde_json::from_reader(fetch::BodyReader::new(response))
+			.map_err(|e| format!("Invalid {} response: {}", method, e))?;
+		if let Some(error) = response.get("error") {
+			return Err(format!("{} failed: {}", method, error));
+		}
+		serde_json::from_value(response["result"].take())
+			.map_err(|e| format!("Invalid {} result: {}", method, e))
+	}
+}
+
+impl StateSource for RpcSource {
+	fn account(&mut self, address: &Address) -> Result<PodAccount, String> {
+		let code: String = self.call("eth_getCode", vec![json!(address)])?;
+		let code: Bytes = code.trim_start_matches("0x").from_hex().map_err(|e| format!("Invalid code: {}", e))?;
+		Ok(PodAccount {
+			balance: self.call("eth_getBalance", vec![json!(address)])?,
+			nonce: self.call("eth_getTransactionCount", vec![json!(address)])?,
+			code: Some(code),
+			storage: BTreeMap::new(),
+			version: U256::zero(),
+		})
+	}
+
+	fn storage(&mut self, address: &Address, key: &H256) -> Result<H256, String> {
+		self.call("eth_getStorageAt", vec![json!(address), json!(key)])
+	}
+}
+
+/// Accounts and storage slots fetched so far.
+pub struct ForkState<S> {
+	source: S,
+	accounts: BTreeMap<Address, PodAccount>,
+}
+
+impl<S: StateSource> ForkState<S> {
+	/// Create an empty state fetched from given source.
+	pub fn new(source: S) -> Self {
+		ForkState {
+			source,
+			accounts: BTreeMap::new(),
+		}
+	}
+
+	/// Fetch given account unless it's been fetched already. Returns true if the account was fetched.
+	pub fn fetch_account(&mut self, address: &Address) -> Result<bool, String> {
+		if self.accounts.contains_key(address) {
+			return Ok(false);
+		}
+		let account = self.source.account(address)?;
+		self.accounts.insert(*address, account);
+		Ok(true)
+	}
+
+	/// Fetch the accounts and storage slots of the report which haven't been fetched yet.
+	/// Returns the number of newly fetched entries.
+	pub fn fetch(&mut self, report: &AccessReport) -> Result<usize, String> {
+		let mut fetched = 0;
+		for (address, access) in report {
+			if self.fetch_account(address)? {
+				fetched += 1;
+			}
+			for key in access.storage.keys() {
+				if self.accounts[address].storage.contains_key(key) {
+					continue;
+				}
+				let value = self.source.storage(address, key)?;
+				self.accounts.get_mut(address).expect("account has been fetched above; qed").storage.insert(*key, value);
+				fetched += 1;
+			}
+		}
+		Ok(fetched)
+	}
+
+	/// Returns given state with the fetched accounts put on top of it.
+	pub fn pod_state(&self, base: &PodState) -> PodState {
+		let mut accounts = base.get().clone();
+		accounts.extend(self.accounts.iter().map(|(address, account)| (*address, account.clone())));
+		accounts.into()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::display::access::AccountAccess;
+
+	#[derive(Default)]
+	struct TestSource {
+		requests: usize,
+	}
+
+	impl StateSource for TestSource {
+		fn account(&mut self, _address: &Address) -> Result<PodAccount, String> {
+			self.requests += 1;
+			Ok(PodAccount {
+				balance: 10.into(),
+				nonce: 1.into(),
+				code: Some(vec![0x00]),
+				storage: BTreeMap::new(),
+				version: U256::zero(),
+			})
+		}
+
+		fn storage(&mut self, _address: &Address, key: &H256) -> Result<H256, String> {
+			self.requests += 1;
+			Ok(*key)
+		}
+	}
+
+	#[test]
+	fn should_fetch_accessed_state_once() {
+		let mut state = ForkState::new(TestSource::default());
+		let address = Address::from_low_u64_be(1);
+		let mut report = AccessReport::new();
+		report.insert(address, AccountAccess {
+			accesses: 1,
+			storage: vec![(H256::from_low_u64_be(2), 1)].into_iter().collect(),
+		});
+
+		assert_eq!(state.fetch(&report), Ok(2));
+		assert_eq!(state.fetch(&report), Ok(0));
+		assert_eq!(state.source.requests, 2);
+
+		let pod = state.pod_state(&PodState::default());
+		let account = &pod.get()[&address];
+		assert_eq!(account.balance, 10.into());
+		assert_eq!(account.storage[&H256::from_low_u64_be(2)], H256::from_low_u64_be(2));
+	}
+}
@@ -16,6 +16,7 @@
 
 //! EVM runner.
 
+use std::collections::HashMap;
 use std::time::{Instant, Duration};
 
 use common_types::transaction;
@@ -23,9 +24,10 @@ use ethcore::test_helpers::{EvmTestClient, EvmTestError, TransactErr, TransactSu
 use ethereum_types::{H256, U256};
 use ethjson;
 use pod::PodState;
+use serde::Serialize;
 use spec;
 use trace;
-use vm::ActionParams;
+use vm::{ActionParams, EnvInfo};
 
 /// EVM execution informant.
 pub trait Informant: trace::VMTracer {
@@ -41,6 +43,36 @@ pub trait Informant: trace::VMTracer {
 	fn finish(result: RunResult<Self::Output>, _: &mut Self::Sink);
 }
 
+/// Breakdown of the gas paid for by a transaction.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasBreakdown {
+	/// Gas charged before the execution (base cost and payload).
+	pub intrinsic: U256,
+	/// Gas used by the execution, before refunds.
+	pub execution: U256,
+	/// Refund applied.
+	pub refunded: U256,
+	/// Maximal refund, half of the gas used before refunds.
+	pub refund_cap: U256,
+	/// Gas returned to the sender, unused gas included.
+	pub returned: U256,
+}
+
+impl GasBreakdown {
+	/// Compute the breakdown of a transaction with given gas limit.
+	pub fn new(gas: U256, gas_left: U256, intrinsic: U256, refunded: U256) -> Self {
+		let used_before_refund = gas - gas_left + refunded;
+		GasBreakdown {
+			intrinsic,
+			execution: used_before_refund.saturating_sub(intrinsic),
+			refunded,
+			refund_cap: used_before_refund / 2,
+			returned: gas_left,
+		}
+	}
+}
+
 /// Execution finished correctly.
 #[derive(Debug)]
 pub struct Success<T> {
@@ -56,6 +88,8 @@ pub struct Success<T> {
 	pub traces: Option<T>,
 	/// Optional end state dump
 	pub end_state: Option<PodState>,
+	/// Gas accounting, available for transactions only.
+	pub gas_breakdown: Option<GasBreakdown>,
 }
 
 /// Execution failed.
@@ -73,29 +107,44 @@ pub struct Failure<T> {
 	pub traces: Option<T>,
 	/// Optional end state dump
 	pub end_state: Option<PodState>,
+	/// Gas accounting, available for transactions which were executed.
+	pub gas_breakdown: Option<GasBreakdown>,
 }
 
 /// EVM execution result.
 pub type RunResult<T> = Result<Success<T>, Failure<T>>;
 
-/// Execute given `ActionParams` and return the result.
+/// Execute given `ActionParams` in given environment and return the result.
 pub fn run_action<T: Informant>(
 	spec: &spec::Spec,
+	params: ActionParams,
+	informant: T,
+	trie_spec: TrieSpec,
+	env_info: EnvInfo,
+) -> RunResult<T::Output> {
+	run_action_with_state(spec, &spec.genesis_state, params, informant, trie_spec, env_info)
+}
+
+/// Execute given `ActionParams` against given pre-state instead of the genesis state of the spec.
+pub fn run_action_with_state<T: Informant>(
+	spec: &spec::Spec,
+	pre_state: &PodState,
 	mut params: ActionParams,
 	mut informant: T,
 	trie_spec: TrieSpec,
+	env_info: EnvInfo,
 ) -> RunResult<T::Output> {
 	informant.set_gas(params.gas);
 
-	// if the code is not overwritten from CLI, use code from spec file.
+	// if the code is not overwritten from CLI, use code from the pre-state.
 	if params.code.is_none() {
-		if let Some(acc) = spec.genesis_state.get().get(&params.code_address) {
+		if let Some(acc) = pre_state.get().get(&params.code_address) {
 			params.code = acc.code.clone().map(::std::sync::Arc::new);
 			params.code_hash = None;
 		}
 	}
-	run(spec, trie_spec, params.gas, &spec.genesis_state, |mut client| {
-		let result = match client.call(params, &mut trace::NoopTracer, &mut informant) {
+	run(spec, trie_spec, params.gas, pre_state, |mut client| {
+		let result = match client.call_envinfo(params, &mut trace::NoopTracer, &mut informant, env_info) {
 			Ok(r) => (Ok(r.return_data.to_vec()), Some(r.gas_left)),
 			Err(err) => (Err(err), None),
 		};
@@ -103,6 +152,16 @@ pub fn run_action<T: Informant>(
 	})
 }
 
+/// Returns the 256 most recent block hashes as seen by block `number`, most recent first.
+/// Hashes missing from `hashes` are set to zero.
+pub fn last_hashes(number: u64, hashes: &HashMap<u64, H256>) -> Vec<H256> {
+	(1..=256)
+		.map(|distance| number.checked_sub(distance)
+			.and_then(|n| hashes.get(&n).cloned())
+			.unwrap_or_default())
+		.collect()
+}
+
 /// Input data to run transaction.
 #[derive(Debug)]
 pub struct TxInput<'a, T> {
@@ -157,10 +216,13 @@ pub fn run_transaction<T: Informant>(
 	informant.set_gas(env_info.gas_limit);
 
 	let mut sink = informant.clone_sink();
-	let result = run(&fork_spec, trie_spec, transaction.gas, &pre_state, |mut client| {
+	let gas = transaction.gas;
+	let mut gas_breakdown = None;
+	let mut result = run(&fork_spec, trie_spec, gas, &pre_state, |mut client| {
 		let result = client.transact(&env_info, transaction, trace::NoopTracer, informant);
 		match result {
-			Ok(TransactSuccess { state_root, gas_left, output, vm_trace, end_state, .. }) => {
+			Ok(TransactSuccess { state_root, gas_left, intrinsic_gas, refunded, output, vm_trace, end_state, .. }) => {
+				gas_breakdown = Some(GasBreakdown::new(gas, gas_left, intrinsic_gas, refunded));
 				if state_root != post_root {
 					(Err(EvmTestError::PostCondition(format!(
 						"State root mismatch (got: {:#x}, expected: {:#x})",
@@ -179,6 +241,11 @@ pub fn run_transaction<T: Informant>(
 		}
 	});
 
+	match result {
+		Ok(ref mut success) => success.gas_breakdown = gas_breakdown,
+		Err(ref mut failure) => failure.gas_breakdown = gas_breakdown,
+	}
+
 	let ok = result.is_ok();
 	T::finish(result, &mut sink);
 	ok
@@ -204,6 +271,7 @@ pub fn run<'a, F, X>(
 			traces: None,
 			state_root: H256::zero(),
 			end_state: None,
+			gas_breakdown: None,
 		})?;
 
 	if do_dump {
@@ -222,6 +290,7 @@ pub fn run<'a, F, X>(
 			time,
 			traces,
 			end_state,
+			gas_breakdown: None,
 		}),
 		(Err(error), state_root, end_state, gas_left, traces) => Err(Failure {
 			gas_used: gas_left.map(|gas_left| initial_gas - gas_left).unwrap_or(initial_gas),
@@ -230,6 +299,7 @@ pub fn run<'a, F, X>(
 			traces,
 			state_root,
 			end_state,
+			gas_breakdown: None,
 		}),
 	}
 }
@@ -260,7 +330,8 @@ pub mod tests {
 
 		let tempdir = TempDir::new("").unwrap();
 		let spec = spec::new_foundation(&tempdir.path());
-		let result = run_action(&spec, params, informant, TrieSpec::Secure);
+		let env_info = EvmTestClient::genesis_env_info(&spec);
+		let result = run_action(&spec, params, informant, TrieSpec::Secure, env_info);
 		match result {
 			Ok(Success { traces, .. }) => {
 				compare(traces, expected)
@@ -271,6 +342,34 @@ pub mod tests {
 		}
 	}
 
+	#[test]
+	fn should_compute_gas_breakdown() {
+		// 21_000 intrinsic, 40_000 used by execution, 10_000 refunded
+		let breakdown = GasBreakdown::new(100_000.into(), 49_000.into(), 21_000.into(), 10_000.into());
+		assert_eq!(breakdown, GasBreakdown {
+			intrinsic: 21_000.into(),
+			execution: 40_000.into(),
+			refunded: 10_000.into(),
+			refund_cap: 30_500.into(),
+			returned: 49_000.into(),
+		});
+	}
+
+	#[test]
+	fn should_build_last_hashes() {
+		let hashes = vec![(1, H256::from_low_u64_be(1)), (3, H256::from_low_u64_be(3))].into_iter().collect();
+		let last_hashes = last_hashes(4, &hashes);
+
+		assert_eq!(last_hashes.len(), 256);
+		assert_eq!(&last_hashes[..5], &[
+			H256::from_low_u64_be(3),
+			H256::zero(),
+			H256::from_low_u64_be(1),
+			H256::zero(),
+			H256::zero(),
+		]);
+	}
+
 	#[test]
 	fn should_call_account_from_spec() {
 		use crate::display::std_json::tests::informant;
@@ -282,7 +381,8 @@ pub mod tests {
 
 		let tempdir = TempDir::new("").unwrap();
 		let spec = Spec::load(&tempdir.path(), include_bytes!("../res/testchain.json") as &[u8]).unwrap();
-		let _result = run_action(&spec, params, inf, TrieSpec::Secure);
+		let env_info = EvmTestClient::genesis_env_info(&spec);
+		let _result = run_action(&spec, params, inf, TrieSpec::Secure, env_info);
 
 		assert_eq!(
 			&String::from_utf8_lossy(&**res.0.lock().unwrap()),
@@ -34,22 +34,28 @@
 
 #![warn(missing_docs)]
 
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::{fmt, fs};
+use std::{fmt, fs, io};
 use std::path::PathBuf;
 
 use parity_bytes::Bytes;
 use docopt::Docopt;
 use rustc_hex::FromHex;
-use ethereum_types::{U256, Address};
-use ethcore::{json_tests, test_helpers::TrieSpec};
+use ethereum_types::{H256, U256, Address};
+use ethcore::{json_tests, test_helpers::{EvmTestClient, TrieSpec}};
 use spec;
 use serde::Deserialize;
-use vm::{ActionParams, ActionType};
+use vm::{ActionParams, ActionType, EnvInfo};
+use pod::PodState;
 
 mod info;
 mod display;
+mod filter;
+mod compare;
+mod fork;
 
+use crate::filter::TestFilter;
 use crate::info::{Informant, TxInput};
 
 const USAGE: &'static str = r#"
@@ -57,7 +63,7 @@ EVM implementation for Parity.
   Copyright 2015-2020 Parity Technologies (UK) Ltd.
 
 Usage:
-    parity-evm state-test <file> [--chain CHAIN --only NAME --json --std-json --std-dump-json --std-out-only --std-err-only]
+    parity-evm state-test <file> [--chain CHAIN --only NAME --skip FILE --from-fork FORK --to-fork FORK --json --std-json --std-dump-json --std-out-only --std-err-only]
     parity-evm stats [options]
     parity-evm stats-jsontests-vm <file>
     parity-evm [options]
@@ -78,12 +84,31 @@ Transaction options:
     --gas GAS          Supplied gas as hex (without 0x).
     --gas-price WEI    Supplied gas price as hex (without 0x).
 
+Environment options:
+    --env-block-number NUM    Number of the block the code is executed in.
+                              Defaults to the genesis block number.
+    --env-timestamp TIME      Timestamp of the block as UNIX seconds.
+    --env-author ADDRESS      Block author address (without 0x).
+    --blockhashes FILE        Read hashes returned by BLOCKHASH from a JSON file
+                              mapping block numbers to hashes,
+                              e.g. {"1": "0x..."}. Missing hashes are zero.
+    --fork-url URL            Fetch the accounts and storage accessed during
+                              execution from the JSON-RPC endpoint at URL.
+    --fork-block NUM          Number of the block whose state is fetched with
+                              --fork-url. Defaults to the latest block.
+
 State test options:
     --chain CHAIN      Run only from specific chain name (i.e. one of EIP150, EIP158,
                        Frontier, Homestead, Byzantium, Constantinople,
                        ConstantinopleFix, Istanbul, EIP158ToByzantiumAt5, FrontierToHomesteadAt5,
                        HomesteadToDaoAt5, HomesteadToEIP150At5).
-    --only NAME        Runs only a single test matching the name.
+    --only NAME        Runs only tests matching the name. NAME may be a glob
+                       pattern using `*` and `?`.
+    --skip FILE        Read a list of known-bad tests from FILE. Each line holds
+                       a test name (or glob pattern) followed by the reason.
+                       Matching tests are reported as skipped.
+    --from-fork FORK   Run only forks activated at or after FORK.
+    --to-fork FORK     Run only forks activated at or before FORK.
 
 General options:
     --chain PATH       Path to chain spec file.
@@ -93,6 +118,22 @@ General options:
                        with additional state dump.
     --std-err-only     With --std-json redirect to err output only.
     --std-out-only     With --std-json redirect to out output only.
+    --expect FILE      Compare the standardized JSON trace with a reference
+                       trace from FILE (e.g. produced by another client) and
+                       report the first divergent step.
+    --access-report    Report every account and storage slot accessed during
+                       execution together with the number of accesses. The
+                       first access is cold, the following ones are warm.
+                       With --json output the report as EIP-2930 access list.
+    --interactive      Debug the execution interactively. The execution is
+                       paused before the first instruction, or at the
+                       breakpoints given with --break, and commands to step,
+                       continue or inspect the stack, memory and storage are
+                       read from the standard input. Type `help` at the prompt
+                       for the list of commands.
+    --break LIST       Comma separated list of breakpoints for --interactive.
+                       A breakpoint is either a program counter (decimal or
+                       0x-prefixed hex) or an opcode name, e.g. 0x1a,SSTORE.
     -h, --help         Display this message and exit.
 "#;
 
@@ -106,6 +147,15 @@ fn main() {
 		run_state_test(args)
 	} else if args.cmd_stats_jsontests_vm {
 		run_stats_jsontests_vm(args)
+	} else if let Some(expected) = args.flag_expect.clone() {
+		run_compare(args, expected)
+	} else if args.flag_interactive {
+		let breakpoints = arg(args.breakpoints(), "--break");
+		run_call(args, display::interactive::Informant::new(breakpoints))
+	} else if args.flag_access_report {
+		let address = arg(args.to(), "--to");
+		let json = args.flag_json;
+		run_call(args, display::access::Informant::new(address, json))
 	} else if args.flag_json {
 		run_call(args, display::json::Informant::default())
 	} else if args.flag_std_dump_json || args.flag_std_json {
@@ -125,8 +175,8 @@ fn run_state_test(args: Args) {
 	use ethjson::test_helpers::state::Test;
 
 	// Parse the specified state test JSON file provided to the command `state-test <file>`.
-	let file = args.arg_file.expect("PATH to a state test JSON file is required");
-	let mut file = match fs::File::open(&file) {
+	let file = args.arg_file.as_ref().expect("PATH to a state test JSON file is required");
+	let mut file = match fs::File::open(file) {
 		Err(err) => die(format!("Unable to open path: {:?}: {}", file, err)),
 		Ok(file) => file,
 	};
@@ -134,18 +184,34 @@ fn run_state_test(args: Args) {
 		Err(err) => die(format!("Unable to load the test file: {}", err)),
 		Ok(test) => test,
 	};
-	// Parse the name CLI option `--only NAME`.
-	let only_test = args.flag_only.map(|s| s.to_lowercase());
-	// Parse the chain `--chain CHAIN`
-	let only_chain = args.flag_chain.map(|s| s.to_lowercase());
+	// Parse the name CLI option `--only NAME`, the chain `--chain CHAIN` and the fork range
+	// `--from-fork FORK`/`--to-fork FORK`.
+	let mut filter = match TestFilter::new(
+		args.flag_only.as_ref().map(String::as_str),
+		args.flag_chain.as_ref().map(String::as_str),
+		args.flag_from_fork.as_ref().map(String::as_str),
+		args.flag_to_fork.as_ref().map(String::as_str),
+	) {
+		Err(err) => die(err),
+		Ok(filter) => filter,
+	};
+	// Parse the skip-list `--skip FILE`.
+	if let Some(ref skip) = args.flag_skip {
+		if let Err(err) = filter.load_skip_list(skip) {
+			die(err);
+		}
+	}
 
 	// Iterate over 1st level (outer) key-value pair of the state test JSON file.
-	// Skip to next iteration if CLI option `--only NAME` was parsed into `only_test` and does not match
+	// Skip to next iteration if CLI option `--only NAME` was parsed and does not match
 	// the current key `state_test_name` (i.e. add11, create2callPrecompiles).
 	for (state_test_name, test) in state_test {
-		if let Some(false) = only_test.as_ref().map(|only_test| {
-			&state_test_name.to_lowercase() == only_test
-		}) {
+		if !filter.matches_test(&state_test_name) {
+			continue;
+		}
+		// Report tests listed in the skip-list instead of running them.
+		if let Some(reason) = filter.skip_reason(&state_test_name) {
+			report_skipped(&args, &state_test_name, reason);
 			continue;
 		}
 
@@ -155,12 +221,10 @@ fn run_state_test(args: Args) {
 		let pre = test.pre_state.into();
 
 		// Iterate over remaining "post" key of the 2nd level key-value pairs in the state test JSON file.
-		// Skip to next iteration if CLI option `--chain CHAIN` was parsed into `only_chain` and does not match
-		// the current key `fork_spec_name` (i.e. Constantinople, EIP150, EIP158).
+		// Skip to next iteration if CLI options `--chain CHAIN` or `--from-fork`/`--to-fork` were parsed
+		// and do not match the current key `fork_spec_name` (i.e. Constantinople, EIP150, EIP158).
 		for (fork_spec_name, states) in test.post_states {
-			if let Some(false) = only_chain.as_ref().map(|only_chain| {
-				&format!("{:?}", fork_spec_name).to_lowercase() == only_chain
-			}) {
+			if !filter.matches_fork(&fork_spec_name) {
 				continue;
 			}
 
@@ -265,6 +329,24 @@ fn run_state_test(args: Args) {
 	}
 }
 
+/// Report a state test that was skipped using the informant selected by CLI options.
+fn report_skipped(args: &Args, state_test_name: &str, reason: &str) {
+	let action = format!("skipped: {}", reason);
+	if args.flag_std_dump_json || args.flag_std_json {
+		if args.flag_std_err_only {
+			display::std_json::Informant::err_only().before_test(state_test_name, &action);
+		} else if args.flag_std_out_only {
+			display::std_json::Informant::out_only().before_test(state_test_name, &action);
+		} else {
+			display::std_json::Informant::default().before_test(state_test_name, &action);
+		}
+	} else if args.flag_json {
+		display::json::Informant::default().before_test(state_test_name, &action);
+	} else {
+		display::simple::Informant::default().before_test(state_test_name, &action);
+	}
+}
+
 fn run_stats_jsontests_vm(args: Args) {
 	use crate::json_tests::HookType;
 	use std::collections::HashMap;
@@ -299,6 +381,26 @@ fn run_stats_jsontests_vm(args: Args) {
 	}
 }
 
+// CLI option `--expect`
+fn run_compare(args: Args, expected: PathBuf) {
+	let expected = fs::read_to_string(&expected)
+		.map_err(|e| format!("Unable to read {:?}: {}", expected, e))
+		.and_then(|trace| compare::parse_trace(&trace))
+		.unwrap_or_else(|e| die(e));
+
+	let capture = compare::Capture::default();
+	run_call(args, display::std_json::Informant::new(capture.clone(), io::stdout()));
+
+	let actual = compare::parse_trace(&capture.contents()).unwrap_or_else(|e| die(e));
+	match compare::first_divergence(&actual, &expected) {
+		None => println!("Traces match ({} steps).", actual.len()),
+		Some(divergence) => {
+			println!("{}", divergence);
+			::std::process::exit(1);
+		},
+	}
+}
+
 // CLI command `stats`
 fn run_call<T: Informant>(args: Args, informant: T) {
 	let code = arg(args.code(), "--code");
@@ -324,15 +426,62 @@ fn run_call<T: Informant>(args: Args, informant: T) {
 	params.gas = gas;
 	params.gas_price = gas_price;
 
-	let mut sink = informant.clone_sink();
-	let result = if args.flag_std_dump_json {
-		info::run_action(&spec, params, informant, TrieSpec::Fat)
-	} else {
-		info::run_action(&spec, params, informant, TrieSpec::Secure)
+	let mut env_info = EvmTestClient::genesis_env_info(&spec);
+	if let Some(number) = arg(args.env_block_number(), "--env-block-number") {
+		env_info.number = number;
+	}
+	if let Some(timestamp) = arg(args.env_timestamp(), "--env-timestamp") {
+		env_info.timestamp = timestamp;
+	}
+	if let Some(author) = arg(args.env_author(), "--env-author") {
+		env_info.author = author;
+	}
+	if let Some(hashes) = arg(args.blockhashes(), "--blockhashes") {
+		env_info.last_hashes = Arc::new(info::last_hashes(env_info.number, &hashes));
+	}
+
+	let pre_state = match args.flag_fork_url {
+		Some(ref url) => {
+			let source = arg(fork::RpcSource::new(url, arg(args.fork_block(), "--fork-block")), "--fork-url");
+			fork_state(&spec, &params, &env_info, fork::ForkState::new(source), &[from, to])
+		},
+		None => spec.genesis_state.clone(),
 	};
+
+	let mut sink = informant.clone_sink();
+	let trie_spec = if args.flag_std_dump_json { TrieSpec::Fat } else { TrieSpec::Secure };
+	let result = info::run_action_with_state(&spec, &pre_state, params, informant, trie_spec, env_info);
 	T::finish(result, &mut sink);
 }
 
+/// Repeats the execution recording the accessed state until all of it has been fetched.
+fn fork_state<S: fork::StateSource>(
+	spec: &spec::Spec,
+	params: &ActionParams,
+	env_info: &EnvInfo,
+	mut state: fork::ForkState<S>,
+	accounts: &[Address],
+) -> PodState {
+	for address in accounts {
+		arg(state.fetch_account(address), "--fork-url");
+	}
+
+	for _ in 0..fork::MAX_ROUNDS {
+		let pre_state = state.pod_state(&spec.genesis_state);
+		let informant = display::access::Informant::new(params.address, false);
+		let result = info::run_action_with_state(spec, &pre_state, params.clone(), informant, TrieSpec::Secure, env_info.clone());
+		let report = match result {
+			Ok(success) => success.traces,
+			Err(failure) => failure.traces,
+		}.unwrap_or_default();
+
+		if arg(state.fetch(&report), "--fork-url") == 0 {
+			return pre_state;
+		}
+	}
+	die(format!("Accessed state not fetched in {} executions.", fork::MAX_ROUNDS))
+}
+
 #[derive(Debug, Deserialize)]
 struct Args {
 	cmd_stats: bool,
@@ -345,13 +494,26 @@ struct Args {
 	flag_input: Option<String>,
 	flag_gas: Option<String>,
 	flag_gas_price: Option<String>,
+	flag_env_block_number: Option<String>,
+	flag_env_timestamp: Option<String>,
+	flag_env_author: Option<String>,
+	flag_blockhashes: Option<PathBuf>,
+	flag_fork_url: Option<String>,
+	flag_fork_block: Option<String>,
 	flag_only: Option<String>,
+	flag_skip: Option<PathBuf>,
+	flag_from_fork: Option<String>,
+	flag_to_fork: Option<String>,
 	flag_chain: Option<String>,
 	flag_json: bool,
 	flag_std_json: bool,
 	flag_std_dump_json: bool,
 	flag_std_err_only: bool,
 	flag_std_out_only: bool,
+	flag_expect: Option<PathBuf>,
+	flag_access_report: bool,
+	flag_interactive: bool,
+	flag_break: Option<String>,
 }
 
 impl Args {
@@ -411,6 +573,68 @@ impl Args {
 		}
 	}
 
+	// CLI option `--env-block-number NUM`
+	/// Set the number of the block the code is executed in.
+	pub fn env_block_number(&self) -> Result<Option<u64>, String> {
+		match self.flag_env_block_number {
+			Some(ref number) => number.parse().map(Some).map_err(to_string),
+			None => Ok(None),
+		}
+	}
+
+	// CLI option `--env-timestamp TIME`
+	/// Set the timestamp of the block the code is executed in.
+	pub fn env_timestamp(&self) -> Result<Option<u64>, String> {
+		match self.flag_env_timestamp {
+			Some(ref timestamp) => timestamp.parse().map(Some).map_err(to_string),
+			None => Ok(None),
+		}
+	}
+
+	// CLI option `--env-author ADDRESS`
+	/// Set the author of the block the code is executed in.
+	pub fn env_author(&self) -> Result<Option<Address>, String> {
+		match self.flag_env_author {
+			Some(ref author) => author.parse().map(Some).map_err(to_string),
+			None => Ok(None),
+		}
+	}
+
+	// CLI option `--fork-block NUM`
+	/// Set the number of the block whose state is fetched with `--fork-url`.
+	pub fn fork_block(&self) -> Result<Option<u64>, String> {
+		match self.flag_fork_block {
+			Some(ref number) => number.parse().map(Some).map_err(to_string),
+			None => Ok(None),
+		}
+	}
+
+	// CLI option `--blockhashes FILE`
+	/// Read block hashes available to the BLOCKHASH opcode, keyed by block number.
+	pub fn blockhashes(&self) -> Result<Option<HashMap<u64, H256>>, String> {
+		let path = match self.flag_blockhashes {
+			Some(ref path) => path,
+			None => return Ok(None),
+		};
+		let file = fs::File::open(path).map_err(to_string)?;
+		let hashes: HashMap<String, H256> = serde_json::from_reader(file).map_err(to_string)?;
+		hashes.into_iter()
+			.map(|(number, hash)| number.parse()
+				.map(|number| (number, hash))
+				.map_err(|_| format!("Invalid block number: {}", number)))
+			.collect::<Result<_, _>>()
+			.map(Some)
+	}
+
+	// CLI option `--break LIST`
+	/// Set the breakpoints of the interactive debugger.
+	pub fn breakpoints(&self) -> Result<Vec<display::interactive::Breakpoint>, String> {
+		match self.flag_break {
+			Some(ref breakpoints) => breakpoints.split(',').map(|b| b.trim().parse()).collect(),
+			None => Ok(Vec::new()),
+		}
+	}
+
 	// CLI option `--chain PATH`
 	/// Set the path of the chain specification JSON file.
 	pub fn spec(&self) -> Result<spec::Spec, String> {
@@ -449,7 +673,7 @@ mod tests {
 
 	use super::{Args, USAGE, Address, run_call};
 	use crate::{
-		display::std_json::tests::informant,
+		display::{interactive::Breakpoint, std_json::tests::informant},
 		info::{self, TxInput}
 	};
 
@@ -479,12 +703,22 @@ mod tests {
 			"--input", "06",
 			"--gas", "1",
 			"--gas-price", "2",
+			"--env-block-number", "10",
+			"--env-timestamp", "1500000000",
+			"--env-author", "0000000000000000000000000000000000000005",
+			"--blockhashes", "./hashes.json",
+			"--fork-url", "http://localhost:8545",
+			"--fork-block", "9000000",
 			"--chain", "./testfile.json",
 			"--json",
 			"--std-json",
 			"--std-dump-json",
 			"--std-err-only",
 			"--std-out-only",
+			"--expect", "./trace.jsonl",
+			"--access-report",
+			"--interactive",
+			"--break", "0x1a,SSTORE",
 		]);
 
 		assert_eq!(args.code(), Ok(Some(vec![05])));
@@ -493,12 +727,22 @@ mod tests {
 		assert_eq!(args.data(), Ok(Some(vec![06]))); // input data
 		assert_eq!(args.gas(), Ok(1.into()));
 		assert_eq!(args.gas_price(), Ok(2.into()));
+		assert_eq!(args.env_block_number(), Ok(Some(10)));
+		assert_eq!(args.env_timestamp(), Ok(Some(1_500_000_000)));
+		assert_eq!(args.env_author(), Ok(Some(Address::from_low_u64_be(5))));
+		assert_eq!(args.flag_blockhashes, Some("./hashes.json".into()));
+		assert_eq!(args.flag_fork_url, Some("http://localhost:8545".to_owned()));
+		assert_eq!(args.fork_block(), Ok(Some(9000000)));
 		assert_eq!(args.flag_chain, Some("./testfile.json".to_owned()));
 		assert_eq!(args.flag_json, true);
 		assert_eq!(args.flag_std_json, true);
 		assert_eq!(args.flag_std_dump_json, true);
 		assert_eq!(args.flag_std_err_only, true);
 		assert_eq!(args.flag_std_out_only, true);
+		assert_eq!(args.flag_expect, Some("./trace.jsonl".into()));
+		assert_eq!(args.flag_access_report, true);
+		assert_eq!(args.flag_interactive, true);
+		assert_eq!(args.breakpoints(), Ok(vec![Breakpoint::Pc(0x1a), Breakpoint::Op(0x55)]));
 	}
 
 	#[test]
@@ -509,6 +753,9 @@ mod tests {
 			"./file.json",
 			"--chain", "homestead",
 			"--only=add11",
+			"--skip", "./skip.txt",
+			"--from-fork", "frontier",
+			"--to-fork", "byzantium",
 			"--json",
 			"--std-json",
 			"--std-dump-json",
@@ -520,6 +767,9 @@ mod tests {
 		assert!(args.arg_file.is_some());
 		assert_eq!(args.flag_chain, Some("homestead".to_owned()));
 		assert_eq!(args.flag_only, Some("add11".to_owned()));
+		assert_eq!(args.flag_skip, Some("./skip.txt".into()));
+		assert_eq!(args.flag_from_fork, Some("frontier".to_owned()));
+		assert_eq!(args.flag_to_fork, Some("byzantium".to_owned()));
 		assert_eq!(args.flag_json, true);
 		assert_eq!(args.flag_std_json, true);
 		assert_eq!(args.flag_std_dump_json, true);
@@ -97,9 +97,31 @@ pub struct AuthorityRoundParams {
 	pub two_thirds_majority_transition: Option<Uint>,
 	/// The random number contract's address, or a map of contract transitions.
 	pub randomness_contract_address: Option<BTreeMap<Uint, Address>>,
+	/// Block from which every block author has to include their pending randomness commitment
+	/// or revelation in the block. A validator that cannot do so produces invalid blocks until
+	/// the phase ends, so its steps are skipped.
+	pub randomness_contribution_transition: Option<Uint>,
+	/// Storage slot to which the random seed is copied after every block.
+	pub randomness_beacon: Option<RandomnessBeacon>,
 	/// The addresses of contracts that determine the block gas limit starting from the block number
 	/// associated with each of those contracts.
 	pub block_gas_limit_contract_transitions: Option<BTreeMap<Uint, Address>>,
+	/// The heartbeat contract's address transitions. Sealing validators periodically call its
+	/// `heartbeat()` function so that offline validators can be detected on-chain.
+	pub heartbeat_contract_address: Option<BTreeMap<Uint, Address>>,
+	/// Minimum number of seconds between two heartbeat transactions. Defaults to 600.
+	pub heartbeat_interval: Option<Uint>,
+}
+
+/// Location of the randomness beacon in the state.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RandomnessBeacon {
+	/// Account holding the beacon value.
+	pub address: Address,
+	/// Storage slot of the beacon value.
+	pub slot: Uint,
 }
 
 /// Authority engine deserialization.
@@ -117,7 +139,7 @@ mod tests {
 	use ethereum_types::{U256, H160};
 	use serde_json;
 
-	use super::{Address, Uint, StepDuration};
+	use super::{Address, Uint, StepDuration, RandomnessBeacon};
 	use crate::{spec::{validator_set::ValidatorSet, authority_round::AuthorityRound}};
 
 	#[test]
@@ -137,10 +159,19 @@ mod tests {
 					"10": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
 					"20": "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"
 				},
+				"randomnessContributionTransition": 15,
+				"randomnessBeacon": {
+					"address": "0xdddddddddddddddddddddddddddddddddddddddd",
+					"slot": "0x01"
+				},
 				"blockGasLimitContractTransitions": {
 					"10": "0x1000000000000000000000000000000000000001",
 					"20": "0x2000000000000000000000000000000000000002"
-				}
+				},
+				"heartbeatContractAddress": {
+					"5": "0xcccccccccccccccccccccccccccccccccccccccc"
+				},
+				"heartbeatInterval": 300
 			}
 		}"#;
 
@@ -159,10 +190,18 @@ mod tests {
 				(Uint(10.into()), Address(H160::from_str("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa").unwrap())),
 				(Uint(20.into()), Address(H160::from_str("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb").unwrap())),
 			].into_iter().collect());
+		assert_eq!(deserialized.params.randomness_contribution_transition, Some(Uint(15.into())));
+		assert_eq!(deserialized.params.randomness_beacon, Some(RandomnessBeacon {
+			address: Address(H160::from_str("dddddddddddddddddddddddddddddddddddddddd").unwrap()),
+			slot: Uint(1.into()),
+		}));
 		let expected_bglc =
 			[(Uint(10.into()), Address(H160::from_str("1000000000000000000000000000000000000001").unwrap())),
 			 (Uint(20.into()), Address(H160::from_str("2000000000000000000000000000000000000002").unwrap()))];
 		assert_eq!(deserialized.params.block_gas_limit_contract_transitions,
 				   Some(expected_bglc.to_vec().into_iter().collect()));
+		assert_eq!(deserialized.params.heartbeat_contract_address,
+				   Some(vec![(Uint(5.into()), Address(H160::from_str("cccccccccccccccccccccccccccccccccccccccc").unwrap()))].into_iter().collect()));
+		assert_eq!(deserialized.params.heartbeat_interval, Some(Uint(300.into())));
 	}
 }
@@ -29,6 +29,9 @@ pub struct BasicAuthorityParams {
 	pub duration_limit: Uint,
 	/// Valid authorities
 	pub validators: ValidatorSet,
+	/// Number of distinct authority signatures required in the seal.
+	/// If present, the seal carries a list of signatures instead of a single one.
+	pub signature_threshold: Option<Uint>,
 }
 
 /// Authority engine deserialization.
@@ -63,4 +66,24 @@ mod tests {
 		let vs = ValidatorSet::List(vec![Address(H160::from_str("c6d9d2cd449a754c494264e1809c50e34d64562b").unwrap())]);
 		assert_eq!(deserialized.params.validators, vs);
 	}
+
+	#[test]
+	fn basic_authority_signature_threshold_deserialization() {
+		let s = r#"{
+			"params": {
+				"durationLimit": "0x0d",
+				"validators" : {
+					"list": [
+						"0xc6d9d2cd449a754c494264e1809c50e34d64562b",
+						"0x7d577a597b2742b498cb5cf0c26cdcd726d39e6e"
+					]
+				},
+				"signatureThreshold": "0x2"
+			}
+		}"#;
+
+		let deserialized: BasicAuthority = serde_json::from_str(s).unwrap();
+
+		assert_eq!(deserialized.params.signature_threshold, Some(Uint(U256::from(2))));
+	}
 }
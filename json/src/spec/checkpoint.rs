@@ -0,0 +1,76 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Spec trusted checkpoint deserialization for the light client.
+
+use crate::{bytes::Bytes, hash::Address, uint::Uint};
+use serde::Deserialize;
+
+/// Spec trusted checkpoint header sync can start from.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+	/// Hexadecimal of the RLP encoding of the header of the checkpoint block.
+	pub header: Bytes,
+	/// Total difficulty including the checkpoint block.
+	pub total_difficulty: Uint,
+	/// Signatures required to trust the checkpoint.
+	pub signatures: Option<CheckpointSignatures>,
+}
+
+/// Signature set of a trusted checkpoint.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointSignatures {
+	/// Addresses allowed to sign the checkpoint.
+	pub signers: Vec<Address>,
+	/// Number of distinct signers required.
+	pub threshold: Uint,
+	/// 65-byte signatures of `keccak(block hash ++ total difficulty)`.
+	pub signatures: Vec<Bytes>,
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+	use super::{Address, Checkpoint, CheckpointSignatures, Uint};
+	use ethereum_types::{H160, U256};
+
+	#[test]
+	fn checkpoint_deserialization() {
+		let s = r#"{
+			"header": "f901f9a0d405da4e66f1445d455195229624e133f5baafe72b5cf7b3c36c12c8146e98b7",
+			"totalDifficulty": "0x400000000",
+			"signatures": {
+				"signers": ["0x7d577a597b2742b498cb5cf0c26cdcd726d39e6e"],
+				"threshold": "1",
+				"signatures": ["0x01"]
+			}
+		}"#;
+		let deserialized: Checkpoint = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized, Checkpoint {
+			header: "f901f9a0d405da4e66f1445d455195229624e133f5baafe72b5cf7b3c36c12c8146e98b7".parse().unwrap(),
+			total_difficulty: Uint(U256::from(0x400000000u64)),
+			signatures: Some(CheckpointSignatures {
+				signers: vec![Address(H160::from_str("7d577a597b2742b498cb5cf0c26cdcd726d39e6e").unwrap())],
+				threshold: Uint(U256::one()),
+				signatures: vec!["01".parse().unwrap()],
+			}),
+		});
+	}
+}
@@ -31,8 +31,10 @@ pub mod authority_round;
 pub mod null_engine;
 pub mod instant_seal;
 pub mod hardcoded_sync;
+pub mod checkpoint;
 pub mod clique;
 pub mod step_duration;
+pub mod transaction_validator;
 
 pub use self::account::Account;
 pub use self::builtin::{Builtin, Pricing, Linear};
@@ -45,9 +47,11 @@ pub use self::state::{State, HashOrMap};
 pub use self::ethash::{Ethash, EthashParams, BlockReward};
 pub use self::validator_set::ValidatorSet;
 pub use self::basic_authority::{BasicAuthority, BasicAuthorityParams};
-pub use self::authority_round::{AuthorityRound, AuthorityRoundParams};
+pub use self::authority_round::{AuthorityRound, AuthorityRoundParams, RandomnessBeacon};
 pub use self::clique::{Clique, CliqueParams};
 pub use self::null_engine::{NullEngine, NullEngineParams};
 pub use self::instant_seal::{InstantSeal, InstantSealParams};
 pub use self::hardcoded_sync::HardcodedSync;
+pub use self::checkpoint::{Checkpoint, CheckpointSignatures};
 pub use self::step_duration::StepDuration;
+pub use self::transaction_validator::{TransactionValidator, PermissionContract};
@@ -19,7 +19,8 @@
 use crate::{
 	bytes::Bytes,
 	hash::{H256, Address},
-	uint::{self, Uint}
+	uint::{self, Uint},
+	spec::TransactionValidator,
 };
 use serde::Deserialize;
 
@@ -111,6 +112,16 @@ pub struct Params {
 	/// See `CommonParams` docs.
 	pub dust_protection_transition: Option<Uint>,
 	/// See `CommonParams` docs.
+	pub eip1559_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(default, deserialize_with="uint::validate_optional_non_zero")]
+	pub eip1559_base_fee_max_change_denominator: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(default, deserialize_with="uint::validate_optional_non_zero")]
+	pub eip1559_elasticity_multiplier: Option<Uint>,
+	/// See `CommonParams` docs.
+	pub eip1559_base_fee_initial_value: Option<Uint>,
+	/// See `CommonParams` docs.
 	pub nonce_cap_increment: Option<Uint>,
 	/// See `CommonParams` docs.
 	pub remove_dust_contracts : Option<bool>,
@@ -118,6 +129,9 @@ pub struct Params {
 	#[serde(deserialize_with="uint::validate_non_zero")]
 	pub gas_limit_bound_divisor: Uint,
 	/// See `CommonParams` docs.
+	#[serde(default, deserialize_with="uint::validate_optional_non_zero")]
+	pub gas_limit_target: Option<Uint>,
+	/// See `CommonParams` docs.
 	pub registrar: Option<Address>,
 	/// Apply reward flag
 	pub apply_reward: Option<bool>,
@@ -127,12 +141,18 @@ pub struct Params {
 	pub max_code_size: Option<Uint>,
 	/// Maximum size of transaction RLP payload.
 	pub max_transaction_size: Option<Uint>,
+	/// See `CommonParams` docs.
+	pub max_transaction_gas: Option<Uint>,
+	/// See `CommonParams` docs.
+	pub max_transaction_gas_transition: Option<Uint>,
 	/// See main EthashParams docs.
 	pub max_code_size_transition: Option<Uint>,
 	/// Transaction permission contract address.
 	pub transaction_permission_contract: Option<Address>,
 	/// Block at which the transaction permission contract should start being used.
 	pub transaction_permission_contract_transition: Option<Uint>,
+	/// Ordered chain of transaction admission rules.
+	pub transaction_validators: Option<Vec<TransactionValidator>>,
 	/// Wasm activation block height, if not activated from start.
 	pub wasm_activation_transition: Option<Uint>,
 	/// Define a separate wasm version instead of using the prefix.
@@ -159,7 +179,11 @@ mod tests {
 			"accountStartNonce": "0x01",
 			"gasLimitBoundDivisor": "0x20",
 			"maxCodeSize": "0x1000",
-			"wasmActivationTransition": "0x1010"
+			"wasmActivationTransition": "0x1010",
+			"eip1559Transition": "0x10",
+			"eip1559ElasticityMultiplier": "0x4",
+			"maxTransactionGas": "0x7a120",
+			"maxTransactionGasTransition": "0x64"
 		}"#;
 
 		let deserialized: Params = serde_json::from_str(s).unwrap();
@@ -172,6 +196,25 @@ mod tests {
 		assert_eq!(deserialized.gas_limit_bound_divisor, Uint(U256::from(0x20)));
 		assert_eq!(deserialized.max_code_size, Some(Uint(U256::from(0x1000))));
 		assert_eq!(deserialized.wasm_activation_transition, Some(Uint(U256::from(0x1010))));
+		assert_eq!(deserialized.eip1559_transition, Some(Uint(U256::from(0x10))));
+		assert_eq!(deserialized.eip1559_elasticity_multiplier, Some(Uint(U256::from(0x4))));
+		assert_eq!(deserialized.eip1559_base_fee_max_change_denominator, None);
+		assert_eq!(deserialized.max_transaction_gas, Some(Uint(U256::from(500_000))));
+		assert_eq!(deserialized.max_transaction_gas_transition, Some(Uint(U256::from(100))));
+	}
+
+	#[test]
+	fn gas_limit_target_deserialization() {
+		let s = r#"{
+			"maximumExtraDataSize": "0x20",
+			"networkID": "0x1",
+			"minGasLimit": "0x1388",
+			"gasLimitBoundDivisor": "0x20",
+			"gasLimitTarget": "0x989680"
+		}"#;
+
+		let deserialized: Params = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.gas_limit_target, Some(Uint(U256::from(10_000_000))));
 	}
 
 	#[test]
@@ -17,7 +17,7 @@
 //! Spec deserialization.
 
 use std::io::Read;
-use crate::spec::{Params, Genesis, Engine, State, HardcodedSync};
+use crate::spec::{Params, Genesis, Engine, State, HardcodedSync, Checkpoint};
 use serde::Deserialize;
 use serde_json::Error;
 
@@ -71,6 +71,8 @@ pub struct Spec {
 	pub nodes: Option<Vec<String>>,
 	/// Hardcoded synchronization for the light client.
 	pub hardcoded_sync: Option<HardcodedSync>,
+	/// Trusted checkpoint the light client can start header synchronization from.
+	pub checkpoint: Option<Checkpoint>,
 }
 
 impl Spec {
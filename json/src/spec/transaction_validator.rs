@@ -0,0 +1,73 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction validator deserialization.
+
+use crate::{hash::Address, uint::Uint};
+use serde::Deserialize;
+
+/// Transaction admission rule, checked when a transaction is verified against its parent block.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionValidator {
+	/// Only transactions from listed senders are allowed.
+	SenderAllowList(Vec<Address>),
+	/// Transactions are checked by the permissioning contract.
+	PermissionContract(PermissionContract),
+	/// Transactions with data longer than given number of bytes are rejected.
+	MaxPayloadSize(Uint),
+}
+
+/// Permissioning contract validator.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionContract {
+	/// Contract address.
+	pub address: Address,
+	/// Block at which the contract should start being used.
+	pub transition: Option<Uint>,
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+	use super::{Address, PermissionContract, TransactionValidator, Uint};
+	use ethereum_types::{H160, U256};
+
+	#[test]
+	fn transaction_validator_deserialization() {
+		let s = r#"[{
+			"senderAllowList": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
+		}, {
+			"permissionContract": { "address": "0xc6d9d2cd449a754c494264e1809c50e34d64562b", "transition": "0x10" }
+		}, {
+			"maxPayloadSize": 1024
+		}]"#;
+
+		let deserialized: Vec<TransactionValidator> = serde_json::from_str(s).unwrap();
+		let address = Address(H160::from_str("c6d9d2cd449a754c494264e1809c50e34d64562b").unwrap());
+		assert_eq!(deserialized, vec![
+			TransactionValidator::SenderAllowList(vec![address.clone()]),
+			TransactionValidator::PermissionContract(PermissionContract {
+				address,
+				transition: Some(Uint(U256::from(0x10))),
+			}),
+			TransactionValidator::MaxPayloadSize(Uint(U256::from(1024))),
+		]);
+	}
+}
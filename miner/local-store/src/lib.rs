@@ -14,9 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Manages local node data: pending local transactions, sync security level
+//! Manages local node data: pending local transactions, nonces signed locally, sync security level
 
-use std::io;
+use std::{fs, io};
+use std::collections::BTreeMap;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -28,13 +30,19 @@ use common_types::{
 	}
 };
 use ethcore_io::{IoHandler, TimerToken, IoContext};
+use ethereum_types::{Address, U256};
 use kvdb::KeyValueDB;
 use log::{debug, trace, warn};
+use parking_lot::{Mutex, RwLock};
 use rlp::Rlp;
 use serde_derive::{Serialize, Deserialize};
 use serde_json;
 
 const LOCAL_TRANSACTIONS_KEY: &'static [u8] = &*b"LOCAL_TXS";
+const LOCAL_NONCES_KEY: &'static [u8] = &*b"LOCAL_NONCES";
+
+/// Version of the portable local data file format.
+const LOCAL_DATA_FILE_VERSION: u32 = 1;
 
 const UPDATE_TIMER: TimerToken = 0;
 const UPDATE_TIMEOUT: Duration = Duration::from_secs(15 * 60); // once every 15 minutes.
@@ -69,6 +77,16 @@ struct TransactionEntry {
 	condition: Option<Condition>,
 }
 
+/// Portable file with local node data, independent of the chain database.
+#[derive(Serialize, Deserialize)]
+struct LocalDataFile {
+	version: u32,
+	transactions: Vec<TransactionEntry>,
+	/// Highest nonce signed locally by each account.
+	#[serde(default)]
+	nonces: BTreeMap<Address, U256>,
+}
+
 impl TransactionEntry {
 	fn into_pending(self) -> Option<PendingTransaction> {
 		let tx: UnverifiedTransaction = match Rlp::new(&self.rlp_bytes).as_val() {
@@ -99,52 +117,111 @@ impl From<PendingTransaction> for TransactionEntry {
 	}
 }
 
+/// Entries written to the local store.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoreUpdate {
+	/// Local transactions were persisted.
+	Transactions {
+		/// Number of persisted transactions.
+		count: usize,
+		/// Number of previously persisted transactions which are gone.
+		pruned: usize,
+		/// Number of bytes written.
+		bytes: usize,
+	},
+	/// Signed nonces were persisted.
+	Nonces {
+		/// Number of accounts with a persisted nonce.
+		count: usize,
+		/// Number of bytes written.
+		bytes: usize,
+	},
+}
+
+/// Listener notified about every write to the local store.
+pub type StoreListener = Box<dyn Fn(&StoreUpdate) + Send + Sync>;
+
 /// Something which can provide information about the local node.
 pub trait NodeInfo: Send + Sync {
 	/// Get all pending transactions of local origin.
 	fn pending_transactions(&self) -> Vec<PendingTransaction>;
 }
 
+/// Node which is not running. See `open`.
+pub struct NoNode;
+
+impl NodeInfo for NoNode {
+	fn pending_transactions(&self) -> Vec<PendingTransaction> {
+		Vec::new()
+	}
+}
+
 /// Create a new local data store, given a database, a column to write to, and a node.
 /// Attempts to read data out of the store, and move it into the node.
 pub fn create<T: NodeInfo>(db: Arc<dyn KeyValueDB>, col: u32, node: T) -> LocalDataStore<T> {
 	LocalDataStore {
 		db,
 		col,
-		node,
+		node: Some(node),
+		nonces_lock: Mutex::new(()),
+		listeners: RwLock::new(Vec::new()),
+	}
+}
+
+/// Open the local data store while the node is not running, e.g. to export or import its data.
+/// Persisted entries are never overwritten by this store on its own.
+pub fn open(db: Arc<dyn KeyValueDB>, col: u32) -> LocalDataStore<NoNode> {
+	LocalDataStore {
+		db,
+		col,
+		node: None,
+		nonces_lock: Mutex::new(()),
+		listeners: RwLock::new(Vec::new()),
 	}
 }
 
 /// Manages local node data.
 ///
-/// In specific, this will be used to store things like unpropagated local transactions
-/// and the node security level.
+/// In specific, this will be used to store things like unpropagated local transactions,
+/// the highest nonce signed locally by each account and the node security level.
 pub struct LocalDataStore<T: NodeInfo> {
 	db: Arc<dyn KeyValueDB>,
 	col: u32,
-	node: T,
+	node: Option<T>,
+	// serializes read-modify-write cycles of the nonce entries.
+	nonces_lock: Mutex<()>,
+	listeners: RwLock<Vec<StoreListener>>,
 }
 
 impl<T: NodeInfo> LocalDataStore<T> {
+	/// Add a listener notified whenever entries are persisted or pruned.
+	pub fn add_listener(&self, f: StoreListener) {
+		self.listeners.write().push(f);
+	}
+
+	fn notify(&self, update: StoreUpdate) {
+		for listener in self.listeners.read().iter() {
+			listener(&update);
+		}
+	}
+
 	/// Attempt to read pending transactions out of the local store.
 	pub fn pending_transactions(&self) -> io::Result<Vec<PendingTransaction>> {
-		if let Some(val) = self.db.get(self.col, LOCAL_TRANSACTIONS_KEY)? {
-			let local_txs: Vec<_> = serde_json::from_slice::<Vec<TransactionEntry>>(&val)?
-				.into_iter()
-				.filter_map(TransactionEntry::into_pending)
-				.collect();
-
-			Ok(local_txs)
-		} else {
-			Ok(Vec::new())
-		}
+		Ok(self.read_txs()?
+			.into_iter()
+			.filter_map(TransactionEntry::into_pending)
+			.collect())
 	}
 
 	/// Update the entries in the database.
 	pub fn update(&self) -> io::Result<()> {
+		let node = match self.node {
+			Some(ref node) => node,
+			None => return Ok(()),
+		};
 		trace!(target: "local_store", "Updating local store entries.");
 
-		let local_entries: Vec<TransactionEntry> = self.node.pending_transactions()
+		let local_entries: Vec<TransactionEntry> = node.pending_transactions()
 			.into_iter()
 			.map(Into::into)
 			.collect();
@@ -152,22 +229,135 @@ impl<T: NodeInfo> LocalDataStore<T> {
 		self.write_txs(&local_entries)
 	}
 
+	/// Lowest nonce `sender` may sign without reusing a nonce which has been signed by this node before.
+	/// Returns `None` if `sender` never signed a transaction locally.
+	pub fn nonce_floor(&self, sender: &Address) -> io::Result<Option<U256>> {
+		Ok(self.read_nonces()?.get(sender).map(|nonce| nonce.saturating_add(U256::one())))
+	}
+
+	/// Persist that `sender` signed a transaction with `nonce`.
+	/// The floor only ever moves up, noting a lower nonce is a no-op.
+	/// Returns the highest nonce `sender` signed before.
+	pub fn note_signed_nonce(&self, sender: Address, nonce: U256) -> io::Result<Option<U256>> {
+		let _lock = self.nonces_lock.lock();
+		let mut nonces = self.read_nonces()?;
+		let previous = nonces.get(&sender).cloned();
+		if previous.map_or(false, |highest| highest >= nonce) {
+			return Ok(previous);
+		}
+		trace!(target: "local_store", "Noting signed nonce {} of {:?}.", nonce, sender);
+		nonces.insert(sender, nonce);
+		self.write_nonces(&nonces)?;
+		Ok(previous)
+	}
+
+	/// Restore the `previous` highest nonce of `sender` after the transaction signed with `nonce`
+	/// failed to be sent. A no-op if `sender` signed a higher nonce in the meantime.
+	pub fn rollback_signed_nonce(&self, sender: Address, nonce: U256, previous: Option<U256>) -> io::Result<()> {
+		let _lock = self.nonces_lock.lock();
+		let mut nonces = self.read_nonces()?;
+		if nonces.get(&sender) != Some(&nonce) || previous == Some(nonce) {
+			return Ok(());
+		}
+		trace!(target: "local_store", "Rolling signed nonce {} of {:?} back to {:?}.", nonce, sender, previous);
+		match previous {
+			Some(previous) => nonces.insert(sender, previous),
+			None => nonces.remove(&sender),
+		};
+		self.write_nonces(&nonces)
+	}
+
+	/// Export persisted local data into a portable file.
+	/// Returns the number of exported transactions.
+	pub fn export_to(&self, path: &Path) -> io::Result<usize> {
+		let file = LocalDataFile {
+			version: LOCAL_DATA_FILE_VERSION,
+			transactions: self.read_txs()?,
+			nonces: self.read_nonces()?,
+		};
+		let count = file.transactions.len();
+		fs::write(path, serde_json::to_vec(&file)?)?;
+		Ok(count)
+	}
+
+	/// Import local data from a file created with `export_to`, merging it with the persisted entries.
+	/// Returns the number of newly imported transactions.
+	///
+	/// Imported transactions are picked up by the node on its next start.
+	pub fn import_from(&self, path: &Path) -> io::Result<usize> {
+		let file: LocalDataFile = serde_json::from_slice(&fs::read(path)?)?;
+		if file.version != LOCAL_DATA_FILE_VERSION {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Unsupported local data file version: {}", file.version)));
+		}
+
+		for (sender, nonce) in file.nonces {
+			self.note_signed_nonce(sender, nonce)?;
+		}
+
+		let mut entries = self.read_txs()?;
+		let known = entries.len();
+		for entry in file.transactions {
+			if entries.iter().all(|e| e.rlp_bytes != entry.rlp_bytes) {
+				entries.push(entry);
+			}
+		}
+		self.write_txs(&entries)?;
+		Ok(entries.len() - known)
+	}
+
 	/// Clear data in this column.
+	/// Signed nonces are kept, they must outlive the transactions they guard.
 	pub fn clear(&self) -> io::Result<()> {
 		trace!(target: "local_store", "Clearing local store entries.");
 
 		self.write_txs(&[])
 	}
 
+	// helper for reading transaction entries from disk.
+	fn read_txs(&self) -> io::Result<Vec<TransactionEntry>> {
+		match self.db.get(self.col, LOCAL_TRANSACTIONS_KEY)? {
+			Some(val) => Ok(serde_json::from_slice(&val)?),
+			None => Ok(Vec::new()),
+		}
+	}
+
 	// helper for writing a vector of transaction entries to disk.
 	fn write_txs(&self, txs: &[TransactionEntry]) -> io::Result<()> {
+		let pruned = match self.listeners.read().is_empty() {
+			true => 0,
+			false => self.read_txs()?.iter()
+				.filter(|old| txs.iter().all(|tx| tx.rlp_bytes != old.rlp_bytes))
+				.count(),
+		};
 		let mut batch = self.db.transaction();
 
 		let local_json = serde_json::to_value(txs)?;
 		let json_str = format!("{}", local_json);
+		let bytes = json_str.len();
 
 		batch.put_vec(self.col, LOCAL_TRANSACTIONS_KEY, json_str.into_bytes());
-		self.db.write(batch)
+		self.db.write(batch)?;
+		self.notify(StoreUpdate::Transactions { count: txs.len(), pruned, bytes });
+		Ok(())
+	}
+
+	// helper for reading the highest signed nonce of each account from disk.
+	fn read_nonces(&self) -> io::Result<BTreeMap<Address, U256>> {
+		match self.db.get(self.col, LOCAL_NONCES_KEY)? {
+			Some(val) => Ok(serde_json::from_slice(&val)?),
+			None => Ok(BTreeMap::new()),
+		}
+	}
+
+	// helper for writing the highest signed nonce of each account to disk.
+	fn write_nonces(&self, nonces: &BTreeMap<Address, U256>) -> io::Result<()> {
+		let mut batch = self.db.transaction();
+		let value = serde_json::to_vec(nonces)?;
+		let bytes = value.len();
+		batch.put_vec(self.col, LOCAL_NONCES_KEY, value);
+		self.db.write(batch)?;
+		self.notify(StoreUpdate::Nonces { count: nonces.len(), bytes });
+		Ok(())
 	}
 }
 
@@ -197,12 +387,15 @@ impl<T: NodeInfo> Drop for LocalDataStore<T> {
 
 #[cfg(test)]
 mod tests {
-	use super::NodeInfo;
+	use super::{NodeInfo, StoreUpdate};
 
 	use std::sync::Arc;
 	use common_types::transaction::{Transaction, Condition, PendingTransaction};
+	use ethereum_types::Address;
 	use ethkey::Brain;
+	use parking_lot::Mutex;
 	use parity_crypto::publickey::Generator;
+	use tempdir::TempDir;
 
 	// we want to test: round-trip of good transactions.
 	// failure to roundtrip bad transactions (but that it doesn't panic)
@@ -296,4 +489,94 @@ mod tests {
 			assert_eq!(loaded, transactions);
 		}
 	}
+
+	#[test]
+	fn export_and_import() {
+		let keypair = Brain::new("abcd".into()).generate().unwrap();
+		let transactions: Vec<_> = (0..3u64).map(|nonce| {
+			let mut tx = Transaction::default();
+			tx.nonce = nonce.into();
+			PendingTransaction::new(tx.sign(keypair.secret(), None), None)
+		}).collect();
+
+		let tempdir = TempDir::new("").unwrap();
+		let path = tempdir.path().join("local.json");
+
+		let db = Arc::new(::kvdb_memorydb::create(1));
+		drop(super::create(db.clone(), 0, Dummy(transactions.clone())));
+		assert_eq!(super::open(db, 0).export_to(&path).unwrap(), 3);
+
+		let other_db = Arc::new(::kvdb_memorydb::create(1));
+		drop(super::create(other_db.clone(), 0, Dummy(transactions[..1].to_vec())));
+		{
+			let store = super::open(other_db.clone(), 0);
+			assert_eq!(store.import_from(&path).unwrap(), 2);
+			assert_eq!(store.import_from(&path).unwrap(), 0);
+		}
+		// store opened without a node does not overwrite the imported entries.
+		assert_eq!(super::open(other_db, 0).pending_transactions().unwrap(), transactions);
+	}
+
+	#[test]
+	fn listeners_are_notified_of_writes() {
+		let keypair = Brain::new("abcd".into()).generate().unwrap();
+		let transactions: Vec<_> = (0..3u64).map(|nonce| {
+			let mut tx = Transaction::default();
+			tx.nonce = nonce.into();
+			PendingTransaction::new(tx.sign(keypair.secret(), None), None)
+		}).collect();
+
+		let db = Arc::new(::kvdb_memorydb::create(1));
+		let updates = Arc::new(Mutex::new(Vec::new()));
+		let store = super::create(db, 0, Dummy(transactions[..1].to_vec()));
+		let u = updates.clone();
+		store.add_listener(Box::new(move |update| u.lock().push(update.clone())));
+
+		drop(super::create(store.db.clone(), 0, Dummy(transactions)));
+		store.update().unwrap();
+		store.note_signed_nonce(Address::from_low_u64_be(1), 5.into()).unwrap();
+
+		let updates = updates.lock();
+		assert_eq!(updates.len(), 2);
+		match updates[0] {
+			StoreUpdate::Transactions { count: 1, pruned: 2, bytes } => assert!(bytes > 0),
+			ref other => panic!("unexpected update: {:?}", other),
+		}
+		match updates[1] {
+			StoreUpdate::Nonces { count: 1, bytes } => assert!(bytes > 0),
+			ref other => panic!("unexpected update: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn nonce_floor_survives_restart_and_export() {
+		let sender = Address::from_low_u64_be(1);
+		let tempdir = TempDir::new("").unwrap();
+		let path = tempdir.path().join("local.json");
+
+		let db = Arc::new(::kvdb_memorydb::create(1));
+		{
+			let store = super::create(db.clone(), 0, Dummy(vec![]));
+			assert_eq!(store.nonce_floor(&sender).unwrap(), None);
+			assert_eq!(store.note_signed_nonce(sender, 5.into()).unwrap(), None);
+			assert_eq!(store.note_signed_nonce(sender, 3.into()).unwrap(), Some(5.into()));
+			assert_eq!(store.nonce_floor(&sender).unwrap(), Some(6.into()));
+
+			// a transaction which failed to be sent does not move the floor.
+			let previous = store.note_signed_nonce(sender, 9.into()).unwrap();
+			store.rollback_signed_nonce(sender, 9.into(), previous).unwrap();
+			assert_eq!(store.nonce_floor(&sender).unwrap(), Some(6.into()));
+			store.clear().unwrap();
+		}
+
+		let store = super::open(db, 0);
+		assert_eq!(store.nonce_floor(&sender).unwrap(), Some(6.into()));
+		store.export_to(&path).unwrap();
+
+		// a node restored from an older backup picks the floor up on import.
+		let restored = super::open(Arc::new(::kvdb_memorydb::create(1)), 0);
+		restored.note_signed_nonce(sender, 2.into()).unwrap();
+		restored.import_from(&path).unwrap();
+		assert_eq!(restored.nonce_floor(&sender).unwrap(), Some(6.into()));
+	}
 }
@@ -0,0 +1,253 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fee suggestions based on the effective tips paid in recent blocks.
+
+use std::collections::BTreeMap;
+
+use ethereum_types::U256;
+use types::BlockNumber;
+
+/// Default number of recent blocks the oracle keeps track of.
+pub const DEFAULT_TRACKED_BLOCKS: usize = 100;
+
+/// Effective tips paid by the transactions of a single block.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct BlockTips {
+	/// Tips sorted in ascending order.
+	tips: Vec<U256>,
+}
+
+/// Suggested fees for a single inclusion speed.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FeeTier {
+	/// Suggested tip paid to the block author per unit of gas.
+	pub max_priority_fee_per_gas: U256,
+	/// Suggested gas price: base fee of the next block plus the tip.
+	pub gas_price: U256,
+	/// Fraction of recent blocks (`0.0` to `1.0`) which included a transaction paying this tip or less.
+	pub confidence: f64,
+}
+
+/// Fee suggestions for slow, standard and fast inclusion.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FeeSuggestions {
+	/// Base fee of the next block, if the chain has one.
+	pub base_fee: Option<U256>,
+	/// Tier for transactions which can wait a few blocks.
+	pub slow: FeeTier,
+	/// Tier for regular transactions.
+	pub standard: FeeTier,
+	/// Tier for transactions which should be included in the next block.
+	pub fast: FeeTier,
+}
+
+/// Keeps track of the effective tips (gas price above the base fee) paid in recent blocks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeOracle {
+	blocks: BTreeMap<BlockNumber, BlockTips>,
+	max_blocks: usize,
+}
+
+impl Default for FeeOracle {
+	fn default() -> Self {
+		FeeOracle::new(DEFAULT_TRACKED_BLOCKS)
+	}
+}
+
+impl FeeOracle {
+	/// Create a new oracle tracking at most `max_blocks` recent blocks.
+	pub fn new(max_blocks: usize) -> Self {
+		FeeOracle {
+			blocks: BTreeMap::new(),
+			max_blocks: ::std::cmp::max(max_blocks, 1),
+		}
+	}
+
+	/// Returns true if no block has been noted yet.
+	pub fn is_empty(&self) -> bool {
+		self.blocks.is_empty()
+	}
+
+	/// Returns the numbers of the blocks in the window ending at `best` which haven't been noted yet.
+	pub fn missing_blocks(&self, best: BlockNumber) -> Vec<BlockNumber> {
+		let first = best.saturating_sub(self.max_blocks as BlockNumber - 1);
+		(first..=best).filter(|number| !self.blocks.contains_key(number)).collect()
+	}
+
+	/// Note the gas prices of the transactions included in block `number`.
+	/// Blocks above `number` are forgotten, since they have been retracted.
+	pub fn note_block<I>(&mut self, number: BlockNumber, base_fee: Option<U256>, gas_prices: I)
+		where I: IntoIterator<Item = U256>,
+	{
+		let _ = self.blocks.split_off(&number);
+		self.insert(number, base_fee, gas_prices);
+	}
+
+	/// Note the gas prices of an older block, e.g. after a restart. Unlike `note_block` this keeps
+	/// the blocks above `number` and doesn't replace a block which has already been noted.
+	pub fn backfill_block<I>(&mut self, number: BlockNumber, base_fee: Option<U256>, gas_prices: I)
+		where I: IntoIterator<Item = U256>,
+	{
+		if !self.blocks.contains_key(&number) {
+			self.insert(number, base_fee, gas_prices);
+		}
+	}
+
+	fn insert<I>(&mut self, number: BlockNumber, base_fee: Option<U256>, gas_prices: I)
+		where I: IntoIterator<Item = U256>,
+	{
+		let base_fee = base_fee.unwrap_or_default();
+		let mut tips: Vec<U256> = gas_prices.into_iter()
+			.map(|gas_price| gas_price.saturating_sub(base_fee))
+			.collect();
+		tips.sort();
+
+		self.blocks.insert(number, BlockTips { tips });
+
+		while self.blocks.len() > self.max_blocks {
+			let oldest = *self.blocks.keys().next().expect("more than `max_blocks` blocks are tracked; qed");
+			self.blocks.remove(&oldest);
+		}
+	}
+
+	/// Returns the given percentile of all the tips paid in the tracked blocks.
+	pub fn tip_percentile(&self, percentile: usize) -> Option<U256> {
+		let mut tips: Vec<U256> = self.blocks.values()
+			.flat_map(|block| block.tips.iter().cloned())
+			.collect();
+		if tips.is_empty() {
+			return None;
+		}
+		tips.sort();
+		let percentile = ::std::cmp::min(percentile, 100);
+		let index = (tips.len() - 1) * percentile / 100;
+		Some(tips[index])
+	}
+
+	/// Fraction of the tracked non-empty blocks whose cheapest transaction paid at most `tip`.
+	fn confidence(&self, tip: U256) -> f64 {
+		let (total, included) = self.blocks.values()
+			.filter_map(|block| block.tips.first())
+			.fold((0usize, 0usize), |(total, included), min| {
+				(total + 1, if *min <= tip { included + 1 } else { included })
+			});
+		if total == 0 {
+			return 0.0;
+		}
+		included as f64 / total as f64
+	}
+
+	/// Suggested fees for the next block. The standard tier uses the given percentile of recent tips,
+	/// the slow and fast tiers are halfway towards the lowest and highest tip respectively.
+	/// `fallback_tip` is used if no transaction has been included recently.
+	pub fn suggestions(&self, percentile: usize, next_base_fee: Option<U256>, fallback_tip: U256) -> FeeSuggestions {
+		let percentile = ::std::cmp::min(percentile, 100);
+		let base_fee = next_base_fee.unwrap_or_default();
+		let tier = |percentile: usize| {
+			let tip = self.tip_percentile(percentile).unwrap_or(fallback_tip);
+			FeeTier {
+				max_priority_fee_per_gas: tip,
+				gas_price: base_fee.saturating_add(tip),
+				confidence: self.confidence(tip),
+			}
+		};
+
+		FeeSuggestions {
+			base_fee: next_base_fee,
+			slow: tier(percentile / 2),
+			standard: tier(percentile),
+			fast: tier((percentile + 100) / 2),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn prices(prices: &[u64]) -> Vec<U256> {
+		prices.iter().map(|p| U256::from(*p)).collect()
+	}
+
+	#[test]
+	fn should_track_effective_tips() {
+		let mut oracle = FeeOracle::new(2);
+		assert!(oracle.is_empty());
+		assert_eq!(oracle.tip_percentile(50), None);
+
+		oracle.note_block(1, None, prices(&[10, 20, 30]));
+		oracle.note_block(2, Some(10.into()), prices(&[15, 25]));
+		assert_eq!(oracle.tip_percentile(0), Some(5.into()));
+		assert_eq!(oracle.tip_percentile(100), Some(30.into()));
+
+		// oldest block is dropped
+		oracle.note_block(3, Some(10.into()), prices(&[5, 110]));
+		assert_eq!(oracle.tip_percentile(0), Some(0.into()));
+		assert_eq!(oracle.tip_percentile(100), Some(100.into()));
+	}
+
+	#[test]
+	fn should_forget_retracted_blocks() {
+		let mut oracle = FeeOracle::new(10);
+		oracle.note_block(1, None, prices(&[10]));
+		oracle.note_block(2, None, prices(&[1000]));
+		oracle.note_block(3, None, prices(&[1000]));
+
+		// re-org to a different block 2
+		oracle.note_block(2, None, prices(&[20]));
+		assert_eq!(oracle.tip_percentile(100), Some(20.into()));
+	}
+
+	#[test]
+	fn should_backfill_missing_blocks() {
+		let mut oracle = FeeOracle::new(3);
+		oracle.note_block(5, None, prices(&[50]));
+		assert_eq!(oracle.missing_blocks(5), vec![3, 4]);
+
+		oracle.backfill_block(3, None, prices(&[30]));
+		oracle.backfill_block(4, None, prices(&[40]));
+		oracle.backfill_block(5, None, prices(&[1000]));
+		assert!(oracle.missing_blocks(5).is_empty());
+		assert_eq!(oracle.tip_percentile(0), Some(30.into()));
+		assert_eq!(oracle.tip_percentile(100), Some(50.into()));
+	}
+
+	#[test]
+	fn should_suggest_fee_tiers() {
+		let mut oracle = FeeOracle::default();
+		oracle.note_block(1, Some(100.into()), prices(&[101, 102, 103, 104, 105]));
+		oracle.note_block(2, Some(100.into()), prices(&[110]));
+		oracle.note_block(3, Some(100.into()), vec![]);
+
+		let suggestions = oracle.suggestions(50, Some(120.into()), 7.into());
+		assert_eq!(suggestions.base_fee, Some(120.into()));
+		assert_eq!(suggestions.slow.max_priority_fee_per_gas, 2.into());
+		assert_eq!(suggestions.standard.max_priority_fee_per_gas, 3.into());
+		assert_eq!(suggestions.standard.gas_price, 123.into());
+		assert_eq!(suggestions.fast.max_priority_fee_per_gas, 4.into());
+		assert_eq!(suggestions.standard.confidence, 0.5);
+	}
+
+	#[test]
+	fn should_fall_back_without_transactions() {
+		let oracle = FeeOracle::default();
+		let suggestions = oracle.suggestions(50, None, 7.into());
+		assert_eq!(suggestions.standard.max_priority_fee_per_gas, 7.into());
+		assert_eq!(suggestions.fast.gas_price, 7.into());
+		assert_eq!(suggestions.standard.confidence, 0.0);
+	}
+}
@@ -16,10 +16,12 @@
 
 //! Auto-updates minimal gas price requirement from a price-info source.
 
+use std::sync::Arc;
 use std::time::{Instant, Duration};
 
 use ansi_term::Colour;
 use ethereum_types::U256;
+use parking_lot::Mutex;
 use parity_runtime::Executor;
 use price_info::{Client as PriceInfoClient, PriceInfo};
 use price_info::fetch::Client as FetchClient;
@@ -34,11 +36,16 @@ pub struct GasPriceCalibratorOptions {
 }
 
 /// The gas price validator variant for a `GasPricer`.
-#[derive(Debug, PartialEq)]
+///
+/// Once blocks have a base fee (EIP-1559) `usd_per_tx` is the price of the priority fee,
+/// the base fee of the next block is paid on top of it.
+#[derive(Debug)]
 pub struct GasPriceCalibrator {
 	options: GasPriceCalibratorOptions,
 	next_calibration: Instant,
 	price_info: PriceInfoClient,
+	/// Wei per gas matching `usd_per_tx`, known once the first price info arrives.
+	calibrated_price: Arc<Mutex<Option<U256>>>,
 }
 
 impl GasPriceCalibrator {
@@ -48,13 +55,17 @@ impl GasPriceCalibrator {
 			options: options,
 			next_calibration: Instant::now(),
 			price_info: PriceInfoClient::new(fetch, p, api_endpoint),
+			calibrated_price: Default::default(),
 		}
 	}
 
-	pub(crate) fn recalibrate<F: FnOnce(U256) + Sync + Send + 'static>(&mut self, set_price: F) {
+	/// Updates the minimal gas price. `base_fee` is the base fee of the next block, if the chain has one.
+	/// Between calibrations the last calibrated price is re-applied on top of the new base fee.
+	pub(crate) fn recalibrate<F: FnOnce(U256) + Sync + Send + 'static>(&mut self, base_fee: Option<U256>, set_price: F) {
 		trace!(target: "miner", "Recalibrating {:?} versus {:?}", Instant::now(), self.next_calibration);
 		if Instant::now() >= self.next_calibration {
 			let usd_per_tx = self.options.usd_per_tx;
+			let calibrated_price = self.calibrated_price.clone();
 			trace!(target: "miner", "Getting price info");
 
 			self.price_info.get(move |price: PriceInfo| {
@@ -64,10 +75,53 @@ impl GasPriceCalibrator {
 				let gas_per_tx: f32 = 21000.0;
 				let wei_per_gas: f32 = wei_per_usd * usd_per_tx / gas_per_tx;
 				info!(target: "miner", "Updated conversion rate to Ξ1 = {} ({} wei/gas)", Colour::White.bold().paint(format!("US${:.2}", usd_per_eth)), Colour::Yellow.bold().paint(format!("{}", wei_per_gas)));
-				set_price(U256::from(wei_per_gas as u64));
+				let wei_per_gas = U256::from(wei_per_gas as u64);
+				*calibrated_price.lock() = Some(wei_per_gas);
+				set_price(Self::gas_price(base_fee, wei_per_gas));
 			});
 
 			self.next_calibration = Instant::now() + self.options.recalibration_period;
+		} else if let (Some(_), Some(wei_per_gas)) = (base_fee, *self.calibrated_price.lock()) {
+			set_price(Self::gas_price(base_fee, wei_per_gas));
 		}
 	}
+
+	/// Wei per gas of the last calibration, if any.
+	pub(crate) fn calibrated_price(&self) -> Option<U256> {
+		*self.calibrated_price.lock()
+	}
+
+	/// Minimal gas price for the calibrated price, which is the priority fee if there is a base fee.
+	fn gas_price(base_fee: Option<U256>, calibrated_price: U256) -> U256 {
+		base_fee.map_or(calibrated_price, |base_fee| base_fee.saturating_add(calibrated_price))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::mpsc;
+
+	#[test]
+	fn should_target_priority_fee_with_base_fee() {
+		let mut calibrator = GasPriceCalibrator::new(
+			GasPriceCalibratorOptions {
+				usd_per_tx: 0.0,
+				recalibration_period: Duration::from_secs(3600),
+			},
+			FetchClient::new(1).unwrap(),
+			Executor::new_sync(),
+			"fake_endpoint".to_owned(),
+		);
+		calibrator.next_calibration = Instant::now() + Duration::from_secs(3600);
+		*calibrator.calibrated_price.lock() = Some(5.into());
+
+		let (tx, rx) = mpsc::channel();
+		let tx2 = tx.clone();
+		// no base fee yet, the calibrated price stays in force until the next calibration
+		calibrator.recalibrate(None, move |price| tx2.send(price).unwrap());
+		calibrator.recalibrate(Some(100.into()), move |price| tx.send(price).unwrap());
+
+		assert_eq!(rx.try_iter().collect::<Vec<U256>>(), vec![105.into()]);
+	}
 }
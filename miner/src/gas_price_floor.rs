@@ -0,0 +1,140 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Minimal gas price floor following the pressure on the transaction queue.
+
+use std::cmp;
+
+use ethereum_types::U256;
+
+/// Bounds and adjustment rules of the gas price floor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasPriceFloorOptions {
+	/// Lowest floor, used while the queue is not under pressure.
+	pub min: U256,
+	/// Highest floor.
+	pub max: U256,
+	/// Percentage by which the floor is raised or lowered at each adjustment.
+	pub step: u32,
+	/// Queue occupancy (percent of its limits) above which the floor is raised.
+	pub high_watermark: u8,
+	/// Queue occupancy (percent of its limits) below which the floor is lowered.
+	pub low_watermark: u8,
+}
+
+impl Default for GasPriceFloorOptions {
+	fn default() -> Self {
+		GasPriceFloorOptions {
+			min: 0.into(),
+			max: U256::max_value(),
+			step: 12,
+			high_watermark: 90,
+			low_watermark: 50,
+		}
+	}
+}
+
+/// Raises the minimal accepted gas price when the queue approaches capacity
+/// and lowers it back as the pressure subsides.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasPriceFloor {
+	options: GasPriceFloorOptions,
+	current: U256,
+}
+
+impl GasPriceFloor {
+	/// Create a new floor starting at `initial`, usually the current minimal gas price, within the bounds.
+	/// Starting at the lower bound would take a long time to catch up with the pressure on the queue.
+	pub fn new(options: GasPriceFloorOptions, initial: U256) -> Self {
+		GasPriceFloor {
+			current: cmp::min(cmp::max(initial, options.min), options.max),
+			options,
+		}
+	}
+
+	/// Current floor.
+	pub fn current(&self) -> U256 {
+		self.current
+	}
+
+	/// Adjust the floor to the current queue occupancy, in percent of its limits. Returns the new floor.
+	pub fn update(&mut self, occupancy: u8) -> U256 {
+		let percent = U256::from(self.options.step);
+		let step = |price: U256| cmp::max(price.saturating_mul(percent) / 100, 1.into());
+
+		if occupancy >= self.options.high_watermark {
+			self.current = cmp::min(self.current.saturating_add(step(self.current)), self.options.max);
+		} else if occupancy < self.options.low_watermark {
+			self.current = cmp::max(self.current.saturating_sub(step(self.current)), self.options.min);
+		}
+		self.current
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn floor() -> GasPriceFloor {
+		GasPriceFloor::new(GasPriceFloorOptions {
+			min: 100.into(),
+			max: 150.into(),
+			step: 20,
+			high_watermark: 90,
+			low_watermark: 50,
+		}, 0.into())
+	}
+
+	#[test]
+	fn should_raise_floor_under_pressure_up_to_max() {
+		let mut floor = floor();
+		assert_eq!(floor.update(95), 120.into());
+		assert_eq!(floor.update(100), 144.into());
+		assert_eq!(floor.update(90), 150.into());
+	}
+
+	#[test]
+	fn should_keep_floor_between_watermarks() {
+		let mut floor = floor();
+		floor.update(95);
+		assert_eq!(floor.update(70), 120.into());
+		assert_eq!(floor.current(), 120.into());
+	}
+
+	#[test]
+	fn should_lower_floor_down_to_min() {
+		let mut floor = floor();
+		floor.update(95);
+		floor.update(95);
+		assert_eq!(floor.update(10), 116.into());
+		assert_eq!(floor.update(10), 100.into());
+	}
+
+	#[test]
+	fn should_start_at_initial_price_within_bounds() {
+		let options = floor().options;
+		assert_eq!(GasPriceFloor::new(options.clone(), 120.into()).current(), 120.into());
+		assert_eq!(GasPriceFloor::new(options.clone(), 10.into()).current(), 100.into());
+		assert_eq!(GasPriceFloor::new(options, 1000.into()).current(), 150.into());
+	}
+
+	#[test]
+	fn should_raise_zero_floor() {
+		let mut floor = GasPriceFloor::new(GasPriceFloorOptions::default(), 0.into());
+		assert_eq!(floor.update(100), 1.into());
+		assert_eq!(floor.update(100), 2.into());
+	}
+}
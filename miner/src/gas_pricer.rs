@@ -21,7 +21,7 @@ use ethereum_types::U256;
 use gas_price_calibrator::GasPriceCalibrator;
 
 /// Struct to look after updating the acceptable gas price of a miner.
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 pub enum GasPricer {
 	/// A fixed gas price in terms of Wei - always the argument given.
 	Fixed(U256),
@@ -42,12 +42,21 @@ impl GasPricer {
 		GasPricer::Fixed(gas_price)
 	}
 
-	/// Recalibrate current gas price.
-	pub fn recalibrate<F: FnOnce(U256) + Sync + Send + 'static>(&mut self, set_price: F) {
+	/// Current gas price, if known already.
+	pub fn current(&self) -> Option<U256> {
+		match *self {
+			GasPricer::Fixed(ref curr) => Some(*curr),
+			#[cfg(feature = "price-info")]
+			GasPricer::Calibrated(ref cal) => cal.calibrated_price(),
+		}
+	}
+
+	/// Recalibrate current gas price. `base_fee` is the base fee of the next block, if the chain has one.
+	pub fn recalibrate<F: FnOnce(U256) + Sync + Send + 'static>(&mut self, base_fee: Option<U256>, set_price: F) {
 		match *self {
 			GasPricer::Fixed(ref curr) => set_price(curr.clone()),
 			#[cfg(feature = "price-info")]
-			GasPricer::Calibrated(ref mut cal) => cal.recalibrate(set_price),
+			GasPricer::Calibrated(ref mut cal) => cal.recalibrate(base_fee, set_price),
 		}
 	}
 }
@@ -55,8 +55,10 @@ extern crate parity_crypto;
 extern crate env_logger;
 
 pub mod external;
+pub mod fee_oracle;
 #[cfg(feature = "price-info")]
 pub mod gas_price_calibrator;
+pub mod gas_price_floor;
 pub mod gas_pricer;
 pub mod local_accounts;
 pub mod pool;
@@ -24,7 +24,7 @@ use futures::sync::mpsc;
 use txpool::{self, VerifiedTransaction};
 
 use pool::VerifiedTransaction as Transaction;
-use pool::TxStatus;
+use pool::{ScoredTransaction, TxStatus};
 
 /// Transaction pool logger.
 #[derive(Default, Debug)]
@@ -129,8 +129,15 @@ impl fmt::Debug for TransactionsPoolNotifier {
 }
 
 impl txpool::Listener<Transaction> for TransactionsPoolNotifier {
-	fn added(&mut self, tx: &Arc<Transaction>, _old: Option<&Arc<Transaction>>) {
+	fn added(&mut self, tx: &Arc<Transaction>, old: Option<&Arc<Transaction>>) {
 		self.tx_statuses.push((tx.hash.clone(), TxStatus::Added));
+
+		if let Some(old) = old.filter(|old| old.priority().is_local()) {
+			self.tx_statuses.push((old.hash.clone(), TxStatus::Replaced {
+				by: tx.hash.clone(),
+				gas_price_delta: tx.gas_price().saturating_sub(*old.gas_price()),
+			}));
+		}
 	}
 
 	fn rejected<H: fmt::Debug + fmt::LowerHex>(&mut self, tx: &Arc<Transaction>, _reason: &txpool::Error<H>) {
@@ -161,6 +168,7 @@ mod tests {
 	use txpool::Listener;
 	use futures::{Stream, Future};
 	use ethereum_types::Address;
+	use pool::Priority;
 
 	#[test]
 	fn should_notify_listeners() {
@@ -190,13 +198,45 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn should_notify_about_replaced_local_transaction() {
+		// given
+		let (full_sender, full_receiver) = mpsc::unbounded();
+		let mut tx_listener = TransactionsPoolNotifier::default();
+		tx_listener.add_full_listener(full_sender);
+
+		let mut old = Transaction::from_pending_block_transaction(new_tx_with_gas_price(5).signed().clone());
+		old.priority = Priority::Local;
+		let old = Arc::new(old);
+		let new = new_tx_with_gas_price(12);
+
+		// when
+		tx_listener.added(&new, Some(&old));
+		tx_listener.added(&new_tx_with_gas_price(20), Some(&new));
+
+		// then
+		tx_listener.notify();
+		let (full_res, _full_receiver) = full_receiver.into_future().wait().unwrap();
+		let statuses = full_res.unwrap();
+		assert_eq!(statuses.len(), 3);
+		assert_eq!(statuses[1], (old.hash, TxStatus::Replaced { by: new.hash, gas_price_delta: 7.into() }));
+		assert_eq!(
+			serde_json::to_string(&statuses[1].1).unwrap(),
+			format!(r#"{{"replaced":{{"by":"{:?}","gasPriceDelta":"0x7"}}}}"#, new.hash)
+		);
+	}
+
 	fn new_tx() -> Arc<Transaction> {
+		new_tx_with_gas_price(5)
+	}
+
+	fn new_tx_with_gas_price(gas_price: u64) -> Arc<Transaction> {
 		let signed = transaction::Transaction {
 			action: transaction::Action::Create,
 			data: vec![1, 2, 3],
 			nonce: 5.into(),
 			gas: 21_000.into(),
-			gas_price: 5.into(),
+			gas_price: gas_price.into(),
 			value: 0.into(),
 		}.fake_sign(Address::from_low_u64_be(5));
 
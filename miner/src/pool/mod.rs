@@ -34,16 +34,24 @@ pub mod verifier;
 #[cfg(test)]
 mod tests;
 
-pub use self::queue::{TransactionQueue, Status as QueueStatus};
+pub use self::queue::{TransactionQueue, ImportSimulation, Status as QueueStatus};
 pub use self::txpool::{VerifiedTransaction as PoolVerifiedTransaction, Options};
 
 /// How to prioritize transactions in the pool
 ///
-/// TODO [ToDr] Implement more strategies.
+/// Transactions from the same sender are always ordered by nonce, the strategy decides
+/// how transactions of different senders are interleaved. Ties are broken by arrival order.
+/// Selects one of the built-in `scoring::Prioritization`s, other ones can be plugged in through
+/// `TransactionQueue::with_scoring`.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum PrioritizationStrategy {
 	/// Simple gas-price based prioritization.
 	GasPriceOnly,
+	/// Transactions are included in the order they arrived, regardless of the gas price.
+	Fifo,
+	/// Senders take turns: the first transaction of every sender is included before
+	/// the second transaction of any sender and so on.
+	SenderRoundRobin,
 }
 
 /// Transaction ordering when requesting pending set.
@@ -109,6 +117,77 @@ impl Priority {
 	}
 }
 
+/// Where a transaction has been submitted from.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum TransactionOrigin {
+	/// Transactions from local accounts or submitted over RPC and treated as local.
+	Local,
+	/// Transactions submitted over RPC which are not treated as local.
+	Rpc,
+	/// Transactions received from the network, including the ones from retracted blocks.
+	Network,
+}
+
+/// Maximal number of queued transactions per origin. `None` leaves the origin bounded by the pool limits only.
+///
+/// If any quota is set, transactions from one origin never push out transactions from another one.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OriginQuotas {
+	/// Quota of local transactions.
+	pub local: Option<usize>,
+	/// Quota of RPC transactions.
+	pub rpc: Option<usize>,
+	/// Quota of network transactions.
+	pub network: Option<usize>,
+}
+
+impl OriginQuotas {
+	/// Returns the quota of given origin.
+	pub fn get(&self, origin: TransactionOrigin) -> Option<usize> {
+		match origin {
+			TransactionOrigin::Local => self.local,
+			TransactionOrigin::Rpc => self.rpc,
+			TransactionOrigin::Network => self.network,
+		}
+	}
+
+	/// Returns true if no quota is set.
+	pub fn is_empty(&self) -> bool {
+		self.local.is_none() && self.rpc.is_none() && self.network.is_none()
+	}
+}
+
+/// Number of queued transactions per origin.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OriginCounts {
+	/// Number of local transactions.
+	pub local: usize,
+	/// Number of RPC transactions.
+	pub rpc: usize,
+	/// Number of network transactions.
+	pub network: usize,
+}
+
+impl OriginCounts {
+	/// Returns the number of transactions from given origin.
+	pub fn get(&self, origin: TransactionOrigin) -> usize {
+		match origin {
+			TransactionOrigin::Local => self.local,
+			TransactionOrigin::Rpc => self.rpc,
+			TransactionOrigin::Network => self.network,
+		}
+	}
+
+	/// Counts one more transaction from given origin.
+	pub fn note(&mut self, origin: TransactionOrigin) {
+		match origin {
+			TransactionOrigin::Local => self.local += 1,
+			TransactionOrigin::Rpc => self.rpc += 1,
+			TransactionOrigin::Network => self.network += 1,
+		}
+	}
+}
+
 /// Scoring properties for verified transaction.
 pub trait ScoredTransaction {
 	/// Gets transaction priority.
@@ -119,6 +198,9 @@ pub trait ScoredTransaction {
 
 	/// Gets transaction nonce.
 	fn nonce(&self) -> U256;
+
+	/// Gets transaction origin.
+	fn origin(&self) -> TransactionOrigin;
 }
 
 /// Verified transaction stored in the pool.
@@ -129,6 +211,7 @@ pub struct VerifiedTransaction {
 	hash: H256,
 	sender: Address,
 	priority: Priority,
+	origin: TransactionOrigin,
 	insertion_id: usize,
 }
 
@@ -146,6 +229,7 @@ impl VerifiedTransaction {
 			hash,
 			sender,
 			priority: Priority::Retracted,
+			origin: TransactionOrigin::Network,
 			insertion_id: 0,
 		}
 	}
@@ -198,6 +282,10 @@ impl ScoredTransaction for VerifiedTransaction {
 	fn nonce(&self) -> U256 {
 		self.transaction.nonce
 	}
+
+	fn origin(&self) -> TransactionOrigin {
+		self.origin
+	}
 }
 
 /// Pool transactions status
@@ -216,4 +304,12 @@ pub enum TxStatus {
 	Canceled,
 	/// Culled transaction
 	Culled,
+	/// Local transaction replaced by another one with the same sender and nonce
+	#[serde(rename_all = "camelCase")]
+	Replaced {
+		/// Hash of the replacing transaction
+		by: H256,
+		/// Gas price increase of the replacing transaction
+		gas_price_delta: U256,
+	},
 }
@@ -29,7 +29,8 @@ use types::transaction;
 
 use pool::{
 	self, replace, scoring, verifier, client, ready, listener,
-	PrioritizationStrategy, PendingOrdering, PendingSettings, TxStatus
+	PrioritizationStrategy, PendingOrdering, PendingSettings, TxStatus,
+	TransactionOrigin, OriginQuotas, OriginCounts,
 };
 use pool::local_transactions::LocalTransactionsList;
 
@@ -61,6 +62,10 @@ pub struct Status {
 	pub status: txpool::LightStatus,
 	/// Current limits of the transaction pool.
 	pub limits: txpool::Options,
+	/// Number of queued transactions per origin.
+	pub origins: OriginCounts,
+	/// Quotas of queued transactions per origin.
+	pub origin_quotas: OriginQuotas,
 }
 
 impl fmt::Display for Status {
@@ -79,6 +84,16 @@ impl fmt::Display for Status {
 	}
 }
 
+/// Predicted outcome of importing a transaction to the queue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportSimulation {
+	/// Hash of the queued transaction (same sender and nonce) that would be replaced.
+	pub replaces: Option<H256>,
+	/// Predicted position in the pending set (`0` is the first transaction to be included)
+	/// or `None` if the transaction would be queued as a future one.
+	pub pending_position: Option<usize>,
+}
+
 #[derive(Debug)]
 struct CachedPending {
 	block_number: u64,
@@ -204,6 +219,7 @@ pub struct TransactionQueue {
 	options: RwLock<verifier::Options>,
 	cached_pending: RwLock<CachedPending>,
 	recently_rejected: RecentlyRejected,
+	origin_quotas: RwLock<OriginQuotas>,
 }
 
 impl TransactionQueue {
@@ -212,17 +228,35 @@ impl TransactionQueue {
 		limits: txpool::Options,
 		verification_options: verifier::Options,
 		strategy: PrioritizationStrategy,
+	) -> Self {
+		Self::with_scoring(limits, verification_options, strategy.into())
+	}
+
+	/// Create new queue with given pool limits and initial verification options, prioritizing
+	/// transactions between senders with the given scoring.
+	pub fn with_scoring(
+		limits: txpool::Options,
+		verification_options: verifier::Options,
+		scoring: scoring::NonceAndGasPrice,
 	) -> Self {
 		let max_count = limits.max_count;
 		TransactionQueue {
 			insertion_id: Default::default(),
-			pool: RwLock::new(txpool::Pool::new(Default::default(), scoring::NonceAndGasPrice(strategy), limits)),
+			pool: RwLock::new(txpool::Pool::new(Default::default(), scoring, limits)),
 			options: RwLock::new(verification_options),
 			cached_pending: RwLock::new(CachedPending::none()),
 			recently_rejected: RecentlyRejected::new(cmp::max(MIN_REJECTED_CACHE_SIZE, max_count / 4)),
+			origin_quotas: RwLock::new(OriginQuotas::default()),
 		}
 	}
 
+	/// Update quotas of queued transactions per origin.
+	///
+	/// Transactions already in the pool are kept, the quotas apply to new imports only.
+	pub fn set_origin_quotas(&self, quotas: OriginQuotas) {
+		*self.origin_quotas.write() = quotas;
+	}
+
 	/// Update verification options
 	///
 	/// Some parameters of verification may vary in time (like block gas limit or minimal gas price).
@@ -245,23 +279,32 @@ impl TransactionQueue {
 		&self,
 		client: C,
 		transactions: T,
+	) -> Vec<Result<(), transaction::Error>> {
+		self.import_internal(client, transactions, None)
+	}
+
+	/// Import a set of transactions to the pool, accounting them to given origin.
+	///
+	/// Same as `import`, but overrides the origin derived from the kind of the transaction.
+	pub fn import_with_origin<T: IntoIterator<Item = verifier::Transaction>, C: client::Client + client::NonceClient + Clone>(
+		&self,
+		client: C,
+		transactions: T,
+		origin: TransactionOrigin,
+	) -> Vec<Result<(), transaction::Error>> {
+		self.import_internal(client, transactions, Some(origin))
+	}
+
+	fn import_internal<T: IntoIterator<Item = verifier::Transaction>, C: client::Client + client::NonceClient + Clone>(
+		&self,
+		client: C,
+		transactions: T,
+		origin: Option<TransactionOrigin>,
 	) -> Vec<Result<(), transaction::Error>> {
 		// Run verification
 		trace_time!("pool::verify_and_import");
 		let options = self.options.read().clone();
-
-		let transaction_to_replace = {
-			if options.no_early_reject {
-				None
-			} else {
-				let pool = self.pool.read();
-				if pool.is_full() {
-					pool.worst_transaction().map(|worst| (pool.scoring().clone(), worst))
-				} else {
-					None
-				}
-			}
-		};
+		let transaction_to_replace = self.transaction_to_replace(&options);
 
 		let verifier = verifier::Verifier::new(
 			client.clone(),
@@ -270,7 +313,13 @@ impl TransactionQueue {
 			transaction_to_replace,
 		);
 
-		let mut replace = replace::ReplaceByScoreAndReadiness::new(self.pool.read().scoring().clone(), client);
+		let quotas = self.origin_quotas.read().clone();
+		let mut origins = if quotas.is_empty() { OriginCounts::default() } else { self.origin_counts() };
+		let scoring = self.pool.read().scoring().clone();
+		let arrival_order = scoring.arrival_order();
+		let mut replace = replace::ReplaceByScoreAndReadiness::new(scoring, client)
+			.separate_origins(!quotas.is_empty())
+			.arrival_order(arrival_order);
 
 		let results = transactions
 			.into_iter()
@@ -288,12 +337,27 @@ impl TransactionQueue {
 
 				let imported = verifier
 					.verify_transaction(transaction)
-					.and_then(|verified| {
-						self.pool.write().import(verified, &mut replace).map_err(convert_error)
+					.and_then(|mut verified| {
+						if let Some(origin) = origin {
+							verified.origin = origin;
+						}
+						let origin = verified.origin;
+						let at_quota = quotas.get(origin).map_or(false, |quota| origins.get(origin) >= quota);
+						// replacing a queued transaction doesn't take any additional space
+						let replaces = at_quota && self.has_queued(&verified.sender, &verified.transaction.nonce);
+						if at_quota && !replaces {
+							trace!(target: "txqueue", "[{:?}] Rejecting, quota of {:?} transactions reached", hash, origin);
+							return Err(transaction::Error::LimitReached);
+						}
+						self.pool.write().import(verified, &mut replace).map_err(convert_error)?;
+						if !replaces {
+							origins.note(origin);
+						}
+						Ok(())
 					});
 
 				match imported {
-					Ok(_) => Ok(()),
+					Ok(()) => Ok(()),
 					Err(err) => {
 						self.recently_rejected.insert(hash, &err);
 						Err(err)
@@ -312,6 +376,113 @@ impl TransactionQueue {
 		results
 	}
 
+	/// Run all admission checks for given transaction without importing it.
+	///
+	/// Returns the error `import` would fail with or the predicted position of the transaction
+	/// within the pending set described by `settings`.
+	pub fn simulate_import<C: client::Client + client::NonceClient + Clone>(
+		&self,
+		client: C,
+		transaction: verifier::Transaction,
+		settings: PendingSettings,
+	) -> Result<ImportSimulation, transaction::Error> {
+		let hash = transaction.hash();
+		if self.pool.read().find(&hash).is_some() {
+			return Err(transaction::Error::AlreadyImported);
+		}
+		if let Some(err) = self.recently_rejected.get(&hash) {
+			return Err(err);
+		}
+
+		let options = self.options.read().clone();
+		let transaction_to_replace = self.transaction_to_replace(&options);
+		// Use a separate counter so the simulation doesn't consume insertion ids.
+		let verifier = verifier::Verifier::new(client.clone(), options, Default::default(), transaction_to_replace);
+		let verified = verifier.verify_transaction(transaction)?;
+
+		let (sender, nonce, gas_price) = (verified.sender, verified.transaction.nonce, verified.transaction.gas_price);
+		let next_nonce = self.next_nonce(client.clone(), &sender).unwrap_or_else(|| client.account_nonce(&sender));
+		let pending = self.pending(client, settings);
+
+		let replaced = {
+			let current = self.pool.read();
+			let all = |_tx: &pool::VerifiedTransaction| txpool::Readiness::Ready;
+			let from_sender: Vec<_> = current.unordered_pending(all).filter(|tx| tx.sender == sender).collect();
+			let replaced = from_sender.iter().find(|tx| tx.transaction.nonce == nonce).cloned();
+
+			match replaced {
+				Some(ref old) => {
+					if let txpool::scoring::Choice::RejectNew = txpool::Scoring::choose(current.scoring(), &**old, &verified) {
+						return Err(transaction::Error::TooCheapToReplace {
+							prev: Some(old.transaction.gas_price),
+							new: Some(gas_price),
+						});
+					}
+				},
+				None if from_sender.len() >= current.options().max_per_sender => {
+					return Err(transaction::Error::TooCheapToReplace { prev: None, new: Some(gas_price) });
+				},
+				None if current.is_full() && !verified.priority.is_local() => {
+					if let Some(worst) = current.worst_transaction() {
+						if worst.transaction.gas_price >= gas_price {
+							return Err(transaction::Error::TooCheapToReplace {
+								prev: Some(worst.transaction.gas_price),
+								new: Some(gas_price),
+							});
+						}
+					}
+				},
+				None => {},
+			}
+
+			replaced.map(|tx| tx.hash)
+		};
+
+		let pending_position = if nonce > next_nonce {
+			None
+		} else {
+			let pending: Vec<_> = pending.iter().filter(|tx| Some(tx.hash) != replaced).collect();
+			// the transaction has to go after all transactions from the same sender with lower nonces
+			let start = pending.iter()
+				.rposition(|tx| tx.sender == sender && tx.transaction.nonce < nonce)
+				.map_or(0, |p| p + 1);
+			let score = scoring::score(&verified);
+			let position = pending[start..].iter()
+				.position(|tx| tx.sender == sender || scoring::score(&***tx) < score)
+				.map_or(pending.len(), |p| p + start);
+			Some(position)
+		};
+
+		Ok(ImportSimulation { replaces: replaced, pending_position })
+	}
+
+	fn transaction_to_replace(&self, options: &verifier::Options) -> Option<(scoring::NonceAndGasPrice, Arc<pool::VerifiedTransaction>)> {
+		if options.no_early_reject {
+			return None;
+		}
+
+		let pool = self.pool.read();
+		if pool.is_full() {
+			pool.worst_transaction().map(|worst| (pool.scoring().clone(), worst))
+		} else {
+			None
+		}
+	}
+
+	fn has_queued(&self, sender: &Address, nonce: &U256) -> bool {
+		let ready = |_tx: &pool::VerifiedTransaction| txpool::Readiness::Ready;
+		self.pool.read().unordered_pending(ready).any(|tx| tx.sender == *sender && tx.transaction.nonce == *nonce)
+	}
+
+	/// Returns the number of queued transactions per origin.
+	pub fn origin_counts(&self) -> OriginCounts {
+		let ready = |_tx: &pool::VerifiedTransaction| txpool::Readiness::Ready;
+		self.pool.read().unordered_pending(ready).fold(OriginCounts::default(), |mut counts, tx| {
+			counts.note(tx.origin);
+			counts
+		})
+	}
+
 	/// Returns all transactions in the queue without explicit ordering.
 	pub fn all_transactions(&self) -> Vec<Arc<pool::VerifiedTransaction>> {
 		let ready = |_tx: &pool::VerifiedTransaction| txpool::Readiness::Ready;
@@ -541,6 +712,7 @@ impl TransactionQueue {
 
 	/// Returns a status of the queue.
 	pub fn status(&self) -> Status {
+		let origins = self.origin_counts();
 		let pool = self.pool.read();
 		let status = pool.light_status();
 		let limits = pool.options();
@@ -550,6 +722,8 @@ impl TransactionQueue {
 			options,
 			status,
 			limits,
+			origins,
+			origin_quotas: self.origin_quotas.read().clone(),
 		}
 	}
 
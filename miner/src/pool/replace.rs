@@ -36,12 +36,41 @@ use super::{client, ScoredTransaction};
 pub struct ReplaceByScoreAndReadiness<S, C> {
 	scoring: S,
 	client: C,
+	separate_origins: bool,
+	arrival_order: bool,
 }
 
 impl<S, C> ReplaceByScoreAndReadiness<S, C> {
 	/// Create a new `ReplaceByScoreAndReadiness`
 	pub fn new(scoring: S, client: C) -> Self {
-		ReplaceByScoreAndReadiness { scoring, client }
+		ReplaceByScoreAndReadiness { scoring, client, separate_origins: false, arrival_order: false }
+	}
+
+	/// Never push out a transaction from a different origin.
+	pub fn separate_origins(mut self, separate_origins: bool) -> Self {
+		self.separate_origins = separate_origins;
+		self
+	}
+
+	/// Ignore the gas price, so a transaction of another sender is pushed out only
+	/// by one of higher priority.
+	pub fn arrival_order(mut self, arrival_order: bool) -> Self {
+		self.arrival_order = arrival_order;
+		self
+	}
+
+	/// Choice for `new` if it replaces a pooled transaction of its sender, which keeps
+	/// the pool within its limits without pushing out `old`.
+	fn replacement<T>(&self, new: &ReplaceTransaction<T>) -> Option<Choice> where
+		T: VerifiedTransaction,
+		S: Scoring<T>,
+	{
+		let txs = new.pooled_by_sender?;
+		let index = txs.binary_search_by(|old| self.scoring.compare(old, new)).ok()?;
+		Some(match self.scoring.choose(&txs[index], new) {
+			Choice::ReplaceOld => Choice::InsertNew,
+			choice => choice,
+		})
 	}
 }
 
@@ -68,21 +97,28 @@ where
 		} else if both_local {
 			Choice::InsertNew
 		} else {
-			let old_score = (old.priority(), old.gas_price());
-			let new_score = (new.priority(), new.gas_price());
+			if self.arrival_order {
+				// replacements do not depend on the gas price of `old`
+				if let Some(choice) = self.replacement(new) {
+					return choice;
+				}
+			}
+
+			let fee = |tx: &ReplaceTransaction<T>| if self.arrival_order { U256::zero() } else { *tx.gas_price() };
+			let old_score = (old.priority(), fee(old));
+			let new_score = (new.priority(), fee(new));
 			if new_score > old_score {
 				// Check if this is a replacement transaction.
 				//
 				// With replacement transactions we can safely return `InsertNew` here, because
 				// we don't need to remove `old` (worst transaction in the pool) since `new` will replace
 			    // some other transaction in the pool so we will never go above limit anyway.
-				if let Some(txs) = new.pooled_by_sender {
-					if let Ok(index) = txs.binary_search_by(|old| self.scoring.compare(old, new)) {
-						return match self.scoring.choose(&txs[index], new) {
-							Choice::ReplaceOld => Choice::InsertNew,
-							choice => choice,
-						}
-					}
+				if let Some(choice) = self.replacement(new) {
+					return choice;
+				}
+
+				if self.separate_origins && old.origin() != new.origin() {
+					return Choice::RejectNew;
 				}
 
 				let state = &self.client;
@@ -143,7 +179,7 @@ mod tests {
 
 	#[test]
 	fn should_always_accept_local_transactions_unless_same_sender_and_nonce() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::default();
 		let client = TestClient::new().with_nonce(1);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -196,7 +232,7 @@ mod tests {
 
 	#[test]
 	fn should_replace_same_sender_by_nonce() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::default();
 		let client = TestClient::new().with_nonce(1);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -239,7 +275,7 @@ mod tests {
 	#[test]
 	fn should_replace_different_sender_by_priority_and_gas_price() {
 		// given
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::default();
 		let client = TestClient::new().with_nonce(0);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -292,7 +328,7 @@ mod tests {
 
 	#[test]
 	fn should_not_replace_ready_transaction_with_future_transaction() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::default();
 		let client = TestClient::new().with_nonce(1);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -318,7 +354,7 @@ mod tests {
 
 	#[test]
 	fn should_compute_readiness_with_pooled_transactions_from_the_same_sender_as_the_existing_transaction() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::default();
 		let client = TestClient::new().with_nonce(1);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -373,7 +409,7 @@ mod tests {
 
 	#[test]
 	fn should_compute_readiness_with_pooled_transactions_from_the_same_sender_as_the_new_transaction() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::default();
 		let client = TestClient::new().with_nonce(1);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -429,7 +465,7 @@ mod tests {
 
 	#[test]
 	fn should_accept_local_tx_with_same_sender_and_nonce_with_better_gas_price() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::default();
 		let client = TestClient::new().with_nonce(1);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -471,7 +507,7 @@ mod tests {
 
 	#[test]
 	fn should_reject_local_tx_with_same_sender_and_nonce_with_worse_gas_price() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::default();
 		let client = TestClient::new().with_nonce(1);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -510,4 +546,42 @@ mod tests {
 
 		assert_eq!(replace.should_replace(&old, &new), RejectNew);
 	}
+
+	#[test]
+	fn should_not_replace_transactions_from_different_origin() {
+		let scoring = NonceAndGasPrice::default();
+		let client = TestClient::new().with_nonce(123);
+		let replace = ReplaceByScoreAndReadiness::new(scoring, client).separate_origins(true);
+
+		let old = Tx::gas_price(1).signed().verified();
+		let mut new = Tx::gas_price(2).signed().verified();
+		assert_eq!(should_replace(&replace, old.clone(), new.clone()), ReplaceOld);
+
+		new.origin = ::pool::TransactionOrigin::Rpc;
+		assert_eq!(should_replace(&replace, old, new), RejectNew);
+	}
+
+	#[test]
+	fn should_only_accept_replacements_in_arrival_order() {
+		let scoring = NonceAndGasPrice::from(PrioritizationStrategy::Fifo);
+		let client = TestClient::new().with_nonce(1);
+		let replace = ReplaceByScoreAndReadiness::new(scoring, client).arrival_order(true);
+
+		let old_tx = txpool::Transaction { insertion_id: 0, transaction: Arc::new(Tx::gas_price(1).signed().verified()) };
+		let (pooled, replacement) = Tx::gas_price(16).signed_replacement();
+		let pooled_txs = [txpool::Transaction { insertion_id: 1, transaction: Arc::new(pooled.verified()) }];
+		let replacement = txpool::Transaction { insertion_id: 2, transaction: Arc::new(replacement.verified()) };
+		let other = txpool::Transaction { insertion_id: 3, transaction: Arc::new(Tx::gas_price(100).signed().verified()) };
+
+		let old = ReplaceTransaction::new(&old_tx, None);
+		// a higher gas price does not push out an earlier transaction
+		assert_eq!(replace.should_replace(&old, &ReplaceTransaction::new(&other, None)), RejectNew);
+		// the replacement is judged against the transaction it replaces
+		assert_eq!(replace.should_replace(&old, &ReplaceTransaction::new(&replacement, Some(&pooled_txs))), RejectNew);
+
+		let (pooled, replacement) = Tx::gas_price(1).signed_replacement();
+		let pooled_txs = [txpool::Transaction { insertion_id: 1, transaction: Arc::new(pooled.verified()) }];
+		let replacement = txpool::Transaction { insertion_id: 2, transaction: Arc::new(replacement.verified()) };
+		assert_eq!(replace.should_replace(&old, &ReplaceTransaction::new(&replacement, Some(&pooled_txs))), InsertNew);
+	}
 }
@@ -23,15 +23,21 @@
 //! is high enough to prevent attacking miners by requiring them to reshuffle/reexecute
 //! the queue too often.
 //!
-//! Transactions between senders are prioritized using `gas price`. Higher `gas price`
+//! Transactions between senders are prioritized using `gas price` by default. Higher `gas price`
 //! yields more profits for miners. Additionally we prioritize transactions that originate
 //! from our local node (own transactions).
+//!
+//! Private networks may prefer not to order by fee at all, the ordering between senders is
+//! pluggable through the `Prioritization` trait, see `PrioritizationStrategy` for the built-in
+//! ones. Transactions with equal scores are ordered by their insertion id, so the resulting
+//! order is deterministic.
 
-use std::cmp;
+use std::{cmp, fmt};
+use std::sync::Arc;
 
 use ethereum_types::U256;
 use txpool::{self, scoring};
-use super::{verifier, PrioritizationStrategy, VerifiedTransaction, ScoredTransaction};
+use super::{verifier, Priority, PrioritizationStrategy, VerifiedTransaction, ScoredTransaction};
 
 /// Transaction with the same (sender, nonce) can be replaced only if
 /// `new_gas_price >= old_gas_price + old_gas_price >> SHIFT`
@@ -39,16 +45,116 @@ const GAS_PRICE_BUMP_SHIFT: usize = 3; // 2 = 25%, 3 = 12.5%, 4 = 6.25%
 
 /// Calculate minimal gas price requirement.
 #[inline]
-fn bump_gas_price(old_gp: U256) -> U256 {
+pub fn bump_gas_price(old_gp: U256) -> U256 {
 	old_gp.saturating_add(old_gp >> GAS_PRICE_BUMP_SHIFT)
 }
 
-/// Simple, gas-price based scoring for transactions.
+/// Calculate the score of a transaction: gas price boosted according to its priority.
+pub(crate) fn score<P: ScoredTransaction>(tx: &P) -> U256 {
+	GasPriceOnly.score(tx.priority(), tx.gas_price(), 0)
+}
+
+/// Lower the score of a penalized transaction.
+fn penalize(score: U256) -> U256 {
+	score >> 3
+}
+
+/// Rank of the transaction priority, used by the prioritizations that ignore the gas price.
+fn priority_rank(priority: Priority) -> u64 {
+	match priority {
+		Priority::Local => 2,
+		Priority::Retracted => 1,
+		Priority::Regular => 0,
+	}
+}
+
+/// Prioritization of transactions between senders, transactions of the same sender are always
+/// ordered by nonce. Higher scores are included first.
+pub trait Prioritization: fmt::Debug + Send + Sync {
+	/// Score of a transaction with given `priority` and `gas_price` at `index` in its sender's queue.
+	fn score(&self, priority: Priority, gas_price: &U256, index: usize) -> U256;
+
+	/// Whether the score depends on the position in the sender's queue, the scores of a sender
+	/// are recomputed whenever its queue shifts if so.
+	fn depends_on_position(&self) -> bool { false }
+
+	/// Whether the gas price is ignored when a full pool decides which transaction to push out.
+	fn arrival_order(&self) -> bool { false }
+
+	/// Whether a non-local `new` transaction can't push `old`, the worst transaction of a full pool, out.
+	/// Used to reject transactions early, before their sender is known.
+	fn should_reject_early(&self, _old: &VerifiedTransaction, _new: &verifier::Transaction) -> bool { false }
+}
+
+/// Simple gas-price based prioritization.
+#[derive(Debug, Clone, Copy)]
+pub struct GasPriceOnly;
+
+impl Prioritization for GasPriceOnly {
+	fn score(&self, priority: Priority, gas_price: &U256, _index: usize) -> U256 {
+		let boost = match priority {
+			Priority::Local => 15,
+			Priority::Retracted => 10,
+			Priority::Regular => 0,
+		};
+		*gas_price << boost
+	}
+
+	fn should_reject_early(&self, old: &VerifiedTransaction, new: &verifier::Transaction) -> bool {
+		&old.transaction.gas_price > new.gas_price()
+	}
+}
+
+/// Transactions are included in the order they arrived, regardless of the gas price.
+#[derive(Debug, Clone, Copy)]
+pub struct Fifo;
+
+impl Prioritization for Fifo {
+	fn score(&self, priority: Priority, _gas_price: &U256, _index: usize) -> U256 {
+		priority_rank(priority).into()
+	}
+
+	// Only replacements of pooled transactions can enter a full pool, which can't be told
+	// before verification, so nothing is rejected early.
+	fn arrival_order(&self) -> bool { true }
+}
+
+/// Senders take turns: the first transaction of every sender is included before the second
+/// transaction of any sender and so on.
+#[derive(Debug, Clone, Copy)]
+pub struct SenderRoundRobin;
+
+impl Prioritization for SenderRoundRobin {
+	fn score(&self, priority: Priority, _gas_price: &U256, index: usize) -> U256 {
+		(U256::from(priority_rank(priority)) << 64) + U256::from(u64::max_value() - index as u64)
+	}
+
+	// Position in the sender queue is not known before verification, so nothing is rejected early.
+	fn depends_on_position(&self) -> bool { true }
+}
+
+/// Nonce-ordered scoring for transactions, prioritized between senders by a `Prioritization`.
 ///
 /// NOTE: Currently penalization does not apply to new transactions that enter the pool.
 /// We might want to store penalization status in some persistent state.
 #[derive(Debug, Clone)]
-pub struct NonceAndGasPrice(pub PrioritizationStrategy);
+pub struct NonceAndGasPrice(pub Arc<dyn Prioritization>);
+
+impl Default for NonceAndGasPrice {
+	fn default() -> Self {
+		NonceAndGasPrice(Arc::new(GasPriceOnly))
+	}
+}
+
+impl From<PrioritizationStrategy> for NonceAndGasPrice {
+	fn from(strategy: PrioritizationStrategy) -> Self {
+		NonceAndGasPrice(match strategy {
+			PrioritizationStrategy::GasPriceOnly => Arc::new(GasPriceOnly),
+			PrioritizationStrategy::Fifo => Arc::new(Fifo),
+			PrioritizationStrategy::SenderRoundRobin => Arc::new(SenderRoundRobin),
+		})
+	}
+}
 
 impl NonceAndGasPrice {
 	/// Decide if the transaction should even be considered into the pool (if the pool is full).
@@ -63,7 +169,29 @@ impl NonceAndGasPrice {
 			return true
 		}
 
-		&old.transaction.gas_price > new.gas_price()
+		self.0.should_reject_early(old, new)
+	}
+
+	/// Whether the gas price is ignored when a full pool decides which transaction to push out.
+	pub fn arrival_order(&self) -> bool {
+		self.0.arrival_order()
+	}
+
+	/// Compute the score of the transaction at given position in the sender's queue.
+	fn score_at<P: ScoredTransaction>(&self, index: usize, tx: &P) -> U256 {
+		self.0.score(tx.priority(), tx.gas_price(), index)
+	}
+
+	/// Recompute `score` of the transaction that moved from `previous` to `index` in the sender's
+	/// queue, the score keeps the penalties of the transaction.
+	fn rescore_at<P: ScoredTransaction>(&self, previous: usize, index: usize, score: U256, tx: &P) -> U256 {
+		let mut unpenalized = self.score_at(previous, tx);
+		let mut new_score = self.score_at(index, tx);
+		while unpenalized > score {
+			unpenalized = penalize(unpenalized);
+			new_score = penalize(new_score);
+		}
+		new_score
 	}
 }
 
@@ -95,19 +223,30 @@ impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txp
 		use self::scoring::Change;
 
 		match change {
+			// Scores depending on the position in the sender queue have to be recomputed
+			// whenever the queue shifts.
+			Change::Culled(_) | Change::RemovedAt(_) | Change::InsertedAt(_) if self.0.depends_on_position() => {
+				for (i, (score, tx)) in scores.iter_mut().zip(txs).enumerate() {
+					let previous = match change {
+						Change::Culled(culled) => Some(i + culled),
+						Change::RemovedAt(removed) if i >= removed => Some(i + 1),
+						Change::InsertedAt(inserted) if i == inserted => None,
+						Change::InsertedAt(inserted) if i > inserted => Some(i - 1),
+						_ => Some(i),
+					};
+					*score = match previous {
+						Some(previous) => self.rescore_at(previous, i, *score, &*tx.transaction),
+						None => self.score_at(i, &*tx.transaction),
+					};
+				}
+			},
 			Change::Culled(_) => {},
 			Change::RemovedAt(_) => {}
 			Change::InsertedAt(i) | Change::ReplacedAt(i) => {
 				assert!(i < txs.len());
 				assert!(i < scores.len());
 
-				scores[i] = *txs[i].transaction.gas_price();
-				let boost = match txs[i].priority() {
-					super::Priority::Local => 15,
-					super::Priority::Retracted => 10,
-					super::Priority::Regular => 0,
-				};
-				scores[i] = scores[i] << boost;
+				scores[i] = self.score_at(i, &*txs[i].transaction);
 			},
 			// We are only sending an event in case of penalization.
 			// So just lower the priority of all non-local transactions.
@@ -115,7 +254,7 @@ impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txp
 				for (score, tx) in scores.iter_mut().zip(txs) {
 					// Never penalize local transactions.
 					if !tx.priority().is_local() {
-						*score = *score >> 3;
+						*score = penalize(*score);
 					}
 				}
 			},
@@ -138,7 +277,7 @@ mod tests {
 	#[test]
 	fn should_calculate_score_correctly() {
 		// given
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::default();
 		let (tx1, tx2, tx3) = Tx::default().signed_triple();
 		let transactions = vec![tx1, tx2, tx3].into_iter().enumerate().map(|(i, tx)| {
 			let mut verified = tx.verified();
@@ -187,4 +326,33 @@ mod tests {
 		scoring.update_scores(&transactions, &mut *scores, scoring::Change::Event(()));
 		assert_eq!(scores, vec![32768.into(), 128.into(), 0.into()]);
 	}
+
+	#[test]
+	fn should_keep_penalty_when_recomputing_round_robin_scores() {
+		// given
+		let scoring = NonceAndGasPrice::from(PrioritizationStrategy::SenderRoundRobin);
+		let (tx1, tx2, tx3) = Tx::default().signed_triple();
+		let mut transactions = Vec::new();
+		let mut scores = Vec::new();
+		for tx in vec![tx1, tx2, tx3] {
+			transactions.push(txpool::Transaction {
+				insertion_id: 0,
+				transaction: Arc::new(tx.verified()),
+			});
+			scores.push(U256::zero());
+			scoring.update_scores(&transactions, &mut *scores, scoring::Change::InsertedAt(transactions.len() - 1));
+		}
+		let unpenalized = scores.clone();
+
+		// when
+		scoring.update_scores(&transactions, &mut *scores, scoring::Change::Event(()));
+		let penalized = scores.clone();
+		assert!(penalized.iter().zip(&unpenalized).all(|(penalized, score)| *penalized == *score >> 3));
+		transactions.remove(0);
+		scores.remove(0);
+		scoring.update_scores(&transactions, &mut *scores, scoring::Change::Culled(1));
+
+		// then
+		assert_eq!(scores, vec![penalized[0], penalized[1]]);
+	}
 }
@@ -88,6 +88,7 @@ impl TestClient {
 			hash: tx.hash(),
 			sender: tx.sender(),
 			priority: pool::Priority::Regular,
+			origin: pool::TransactionOrigin::Network,
 			transaction: tx,
 			insertion_id: 1,
 		}
@@ -14,11 +14,15 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::sync::Arc;
 use ethereum_types::U256;
 use types::transaction::{self, PendingTransaction};
 use txpool;
 
-use pool::{verifier, TransactionQueue, PrioritizationStrategy, PendingSettings, PendingOrdering};
+use pool::{
+	scoring, verifier, TransactionQueue, ImportSimulation, Priority, PrioritizationStrategy, PendingSettings,
+	PendingOrdering, TransactionOrigin, OriginQuotas, OriginCounts,
+};
 
 pub mod tx;
 pub mod client;
@@ -33,7 +37,15 @@ use self::client::TestClient;
 const TEST_QUEUE_MAX_MEM: usize = 100;
 
 fn new_queue() -> TransactionQueue {
-	TransactionQueue::new(
+	new_queue_with_strategy(PrioritizationStrategy::GasPriceOnly)
+}
+
+fn new_queue_with_strategy(strategy: PrioritizationStrategy) -> TransactionQueue {
+	new_queue_with_scoring(strategy.into())
+}
+
+fn new_queue_with_scoring(scoring: scoring::NonceAndGasPrice) -> TransactionQueue {
+	TransactionQueue::with_scoring(
 		txpool::Options {
 			max_count: 3,
 			max_per_sender: 3,
@@ -45,9 +57,87 @@ fn new_queue() -> TransactionQueue {
 			tx_gas_limit: 1_000_000.into(),
 			no_early_reject: false,
 		},
-		PrioritizationStrategy::GasPriceOnly,
+		scoring,
 	)
 }
+
+#[derive(Debug)]
+struct LowestGasPriceFirst;
+
+impl scoring::Prioritization for LowestGasPriceFirst {
+	fn score(&self, _priority: Priority, gas_price: &U256, _index: usize) -> U256 {
+		U256::max_value() - *gas_price
+	}
+}
+
+#[test]
+fn should_order_with_custom_prioritization() {
+	// given
+	let txq = new_queue_with_scoring(scoring::NonceAndGasPrice(Arc::new(LowestGasPriceFirst)));
+	let tx1 = Tx::gas_price(10).signed();
+	let tx2 = Tx::gas_price(1).signed();
+	let (hash1, hash2) = (tx1.hash(), tx2.hash());
+
+	// when
+	let res = txq.import(TestClient::new(), vec![tx1, tx2].retracted());
+	assert_eq!(res, vec![Ok(()), Ok(())]);
+
+	// then
+	let top = txq.pending(TestClient::new(), PendingSettings::all_prioritized(0, 0));
+	assert_eq!(top.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![hash2, hash1]);
+}
+
+#[test]
+fn should_order_by_arrival_with_fifo_strategy() {
+	// given
+	let txq = new_queue_with_strategy(PrioritizationStrategy::Fifo);
+	let tx1 = Tx::gas_price(1).signed();
+	let tx2 = Tx::gas_price(10).signed();
+	let (hash1, hash2) = (tx1.hash(), tx2.hash());
+
+	// when
+	let res = txq.import(TestClient::new(), vec![tx1, tx2].retracted());
+	assert_eq!(res, vec![Ok(()), Ok(())]);
+
+	// then
+	let top = txq.pending(TestClient::new(), PendingSettings::all_prioritized(0, 0));
+	assert_eq!(top.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![hash1, hash2]);
+}
+
+#[test]
+fn should_only_accept_replacements_when_full_with_fifo_strategy() {
+	// given
+	let txq = new_queue_with_strategy(PrioritizationStrategy::Fifo);
+	let (tx1, tx2) = Tx::gas_price(1).signed_replacement();
+	let (tx3, tx4) = Tx::gas_price(1).signed_pair();
+	let res = txq.import(TestClient::new(), vec![tx1, tx3, tx4].retracted());
+	assert_eq!(res, vec![Ok(()), Ok(()), Ok(())]);
+
+	// when
+	let res = txq.import(TestClient::new(), vec![Tx::gas_price(100).signed(), tx2].retracted());
+
+	// then
+	assert_eq!(res, vec![Err(transaction::Error::LimitReached), Ok(())]);
+	assert_eq!(txq.status().status.transaction_count, 3);
+}
+
+#[test]
+fn should_interleave_senders_with_round_robin_strategy() {
+	// given
+	let txq = new_queue_with_strategy(PrioritizationStrategy::SenderRoundRobin);
+	let (tx1, tx2) = Tx::gas_price(10).signed_pair();
+	let tx3 = Tx::gas_price(1).signed();
+	let (hash1, hash2, hash3) = (tx1.hash(), tx2.hash(), tx3.hash());
+
+	// when
+	let res = txq.import(TestClient::new(), vec![tx1, tx2, tx3].retracted());
+	assert_eq!(res, vec![Ok(()), Ok(()), Ok(())]);
+
+	// then
+	let top = txq.pending(TestClient::new(), PendingSettings::all_prioritized(0, 0));
+	assert_eq!(top.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![hash1, hash3, hash2]);
+}
+
 #[test]
 fn should_return_correct_nonces_when_dropped_because_of_limit() {
 	// given
@@ -1074,3 +1164,90 @@ fn should_not_reject_early_in_case_gas_price_is_less_than_min_effective() {
 	assert_eq!(txq.status().status.transaction_count, 2);
 	assert!(client.was_verification_triggered());
 }
+
+#[test]
+fn should_simulate_import_without_inserting() {
+	// given
+	let txq = new_queue();
+	let client = TestClient::new().with_balance(1_000_000);
+	let (tx1, tx2) = Tx::gas_price(2).signed_pair();
+	let tx3 = Tx::gas_price(5).signed();
+	let res = txq.import(client.clone(), vec![tx1.clone().unverified(), tx3.unverified()]);
+	assert_eq!(res, vec![Ok(()), Ok(())]);
+	let settings = || PendingSettings::all_prioritized(0, 0);
+
+	// when
+	let next = txq.simulate_import(client.clone(), tx2.unverified(), settings());
+	let cheaper = txq.simulate_import(client.clone(), Tx::gas_price(3).signed().unverified(), settings());
+	let future = txq.simulate_import(client.clone(), Tx { nonce: 124, ..Default::default() }.signed().unverified(), settings());
+	let imported = txq.simulate_import(client.clone(), tx1.unverified(), settings());
+	let no_balance = txq.simulate_import(TestClient::new(), Tx::gas_price(5).signed().unverified(), settings());
+
+	// then
+	assert_eq!(next, Ok(ImportSimulation { replaces: None, pending_position: Some(2) }));
+	assert_eq!(cheaper, Ok(ImportSimulation { replaces: None, pending_position: Some(1) }));
+	assert_eq!(future, Ok(ImportSimulation { replaces: None, pending_position: None }));
+	assert_eq!(imported, Err(transaction::Error::AlreadyImported));
+	assert_eq!(no_balance, Err(transaction::Error::InsufficientBalance { cost: 105_100.into(), balance: 63_100.into() }));
+	assert_eq!(txq.status().status.transaction_count, 2);
+}
+
+#[test]
+fn should_simulate_replacement() {
+	// given
+	let txq = new_queue();
+	let client = TestClient::new().with_balance(1_000_000);
+	let (tx1, tx2) = Tx::gas_price(16).signed_replacement();
+	let (tx3, tx4) = Tx::gas_price(2).signed_replacement();
+	let hash3 = tx3.hash();
+	let res = txq.import(client.clone(), vec![tx1.unverified(), tx3.unverified()]);
+	assert_eq!(res, vec![Ok(()), Ok(())]);
+
+	// when
+	let too_cheap = txq.simulate_import(client.clone(), tx2.unverified(), PendingSettings::all_prioritized(0, 0));
+	let replacement = txq.simulate_import(client.clone(), tx4.unverified(), PendingSettings::all_prioritized(0, 0));
+
+	// then
+	assert_eq!(too_cheap, Err(transaction::Error::TooCheapToReplace { prev: Some(16.into()), new: Some(17.into()) }));
+	assert_eq!(replacement, Ok(ImportSimulation { replaces: Some(hash3), pending_position: Some(1) }));
+}
+
+#[test]
+fn should_enforce_origin_quotas() {
+	// given
+	let txq = TransactionQueue::new(
+		txpool::Options {
+			max_count: 2,
+			max_per_sender: 1,
+			max_mem_usage: usize::max_value(),
+		},
+		verifier::Options {
+			minimal_gas_price: 1.into(),
+			block_gas_limit: 1_000_000.into(),
+			tx_gas_limit: 1_000_000.into(),
+			no_early_reject: false,
+		},
+		PrioritizationStrategy::GasPriceOnly,
+	);
+	txq.set_origin_quotas(OriginQuotas { network: Some(1), ..Default::default() });
+	let client = TestClient::new().with_balance(1_000_000);
+
+	// when
+	let rpc = txq.import_with_origin(client.clone(), vec![Tx::gas_price(1).signed().unverified()], TransactionOrigin::Rpc);
+	let network = txq.import(client.clone(), vec![Tx::gas_price(2).signed().unverified()]);
+	let over_quota = txq.import(client.clone(), vec![Tx::gas_price(3).signed().unverified()]);
+
+	// then
+	assert_eq!(rpc, vec![Ok(())]);
+	assert_eq!(network, vec![Ok(())]);
+	assert_eq!(over_quota, vec![Err(transaction::Error::LimitReached)]);
+	assert_eq!(txq.status().origins, OriginCounts { local: 0, rpc: 1, network: 1 });
+
+	// when
+	txq.set_origin_quotas(OriginQuotas { network: Some(2), ..Default::default() });
+	let evicting = txq.import(client.clone(), vec![Tx::gas_price(10).signed().unverified()]);
+
+	// then
+	assert_eq!(evicting, vec![Err(transaction::Error::LimitReached)]);
+	assert_eq!(txq.origin_counts(), OriginCounts { local: 0, rpc: 1, network: 1 });
+}
@@ -335,6 +335,7 @@ impl<C: Client> txpool::Verifier<Transaction> for Verifier<C, ::pool::scoring::N
 		Ok(VerifiedTransaction {
 			transaction,
 			priority,
+			origin: if is_own { super::TransactionOrigin::Local } else { super::TransactionOrigin::Network },
 			hash,
 			sender,
 			insertion_id: self.id.fetch_add(1, atomic::Ordering::AcqRel),
@@ -15,6 +15,9 @@
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Sends HTTP notifications to a list of URLs every time new work is available.
+//!
+//! Failed deliveries are retried with exponential backoff for as long as the
+//! notified work is still the latest one.
 
 extern crate ethash;
 extern crate fetch;
@@ -22,8 +25,12 @@ extern crate parity_runtime;
 extern crate url;
 extern crate hyper;
 
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
 use self::fetch::{Fetch, Request, Client as FetchClient, Method};
-use self::parity_runtime::Executor;
+use self::parity_runtime::{Executor, Delay};
 use self::ethash::SeedHashCompute;
 use self::url::Url;
 use self::hyper::header::{self, HeaderValue};
@@ -31,7 +38,13 @@ use self::hyper::header::{self, HeaderValue};
 use ethereum_types::{H256, U256};
 use parking_lot::Mutex;
 
-use futures::Future;
+use futures::{future, Future};
+
+/// Default number of times a failed notification is retried.
+pub const DEFAULT_NOTIFY_RETRIES: usize = 3;
+
+/// Delay before the first retry, doubled after every failed attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
 
 /// Trait for notifying about new mining work
 pub trait NotifyWork : Send + Sync {
@@ -39,12 +52,37 @@ pub trait NotifyWork : Send + Sync {
 	fn notify(&self, pow_hash: H256, difficulty: U256, number: u64);
 }
 
+/// Delivery statistics of work notifications.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DeliveryStats {
+	/// Number of notifications successfully delivered.
+	pub delivered: usize,
+	/// Number of retried delivery attempts.
+	pub retried: usize,
+	/// Number of notifications dropped after exhausting retries.
+	pub failed: usize,
+	/// Number of pending retries abandoned because newer work was available.
+	pub superseded: usize,
+}
+
+#[derive(Default)]
+struct Metrics {
+	delivered: AtomicUsize,
+	retried: AtomicUsize,
+	failed: AtomicUsize,
+	superseded: AtomicUsize,
+}
+
 /// POSTs info about new work to given urls.
 pub struct WorkPoster {
 	urls: Vec<Url>,
 	client: FetchClient,
 	executor: Executor,
 	seed_compute: Mutex<SeedHashCompute>,
+	retries: usize,
+	/// Incremented on every new work package; stale retries are abandoned.
+	generation: Arc<AtomicUsize>,
+	metrics: Arc<Metrics>,
 }
 
 impl WorkPoster {
@@ -64,10 +102,76 @@ impl WorkPoster {
 			executor: executor,
 			urls: urls,
 			seed_compute: Mutex::new(SeedHashCompute::default()),
+			retries: DEFAULT_NOTIFY_RETRIES,
+			generation: Default::default(),
+			metrics: Default::default(),
+		}
+	}
+
+	/// Set the number of times a failed notification is retried.
+	pub fn with_retries(mut self, retries: usize) -> Self {
+		self.retries = retries;
+		self
+	}
+
+	/// Returns delivery statistics.
+	pub fn stats(&self) -> DeliveryStats {
+		DeliveryStats {
+			delivered: self.metrics.delivered.load(Ordering::Relaxed),
+			retried: self.metrics.retried.load(Ordering::Relaxed),
+			failed: self.metrics.failed.load(Ordering::Relaxed),
+			superseded: self.metrics.superseded.load(Ordering::Relaxed),
 		}
 	}
 }
 
+/// A single notification being delivered to one endpoint.
+struct Delivery {
+	url: Url,
+	body: String,
+	client: FetchClient,
+	generation: usize,
+	current_generation: Arc<AtomicUsize>,
+	metrics: Arc<Metrics>,
+}
+
+impl Delivery {
+	fn send(self, retries_left: usize, backoff: Duration) -> Box<dyn Future<Item = (), Error = ()> + Send> {
+		let request = Request::new(self.url.clone(), Method::POST)
+			.with_header(header::CONTENT_TYPE, HeaderValue::from_static("application/json"))
+			.with_body(self.body.clone());
+
+		Box::new(self.client.fetch(request, Default::default()).then(move |result| {
+			let error = match result {
+				Ok(ref response) if response.status().is_success() => {
+					self.metrics.delivered.fetch_add(1, Ordering::Relaxed);
+					return Box::new(future::ok(())) as Box<dyn Future<Item = (), Error = ()> + Send>;
+				},
+				Ok(response) => format!("unexpected status {}", response.status()),
+				Err(e) => format!("{}", e),
+			};
+
+			if self.current_generation.load(Ordering::SeqCst) != self.generation {
+				self.metrics.superseded.fetch_add(1, Ordering::Relaxed);
+				debug!(target: "miner", "Dropping HTTP notification to {}: newer work available", self.url);
+				return Box::new(future::ok(()));
+			}
+
+			if retries_left == 0 {
+				self.metrics.failed.fetch_add(1, Ordering::Relaxed);
+				warn!("Error sending HTTP notification to {} : {}", self.url, error);
+				return Box::new(future::ok(()));
+			}
+
+			debug!(target: "miner", "Error sending HTTP notification to {} : {}, retrying in {:?}", self.url, error, backoff);
+			self.metrics.retried.fetch_add(1, Ordering::Relaxed);
+			Box::new(Delay::new(Instant::now() + backoff)
+				.map_err(|e| warn!("Notification retry timer failed: {}", e))
+				.and_then(move |_| self.send(retries_left - 1, backoff * 2)))
+		}))
+	}
+}
+
 impl NotifyWork for WorkPoster {
 	fn notify(&self, pow_hash: H256, difficulty: U256, number: u64) {
 		// TODO: move this to engine
@@ -78,16 +182,19 @@ impl NotifyWork for WorkPoster {
 			r#"{{ "result": ["0x{:x}","0x{:x}","0x{:x}","0x{:x}"] }}"#,
 			pow_hash, seed_hash, target, number
 		);
+		let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+		debug!(target: "miner", "Pushing work package #{} to {} endpoints ({:?})", number, self.urls.len(), self.stats());
 
 		for u in &self.urls {
-			let u = u.clone();
-			self.executor.spawn(self.client.fetch(
-				Request::new(u.clone(), Method::POST)
-					.with_header(header::CONTENT_TYPE, HeaderValue::from_static("application/json"))
-					.with_body(body.clone()), Default::default()
-			).map_err(move |e| {
-				warn!("Error sending HTTP notification to {} : {}, retrying", u, e);
-			}).map(|_| ()));
+			let delivery = Delivery {
+				url: u.clone(),
+				body: body.clone(),
+				client: self.client.clone(),
+				generation,
+				current_generation: self.generation.clone(),
+				metrics: self.metrics.clone(),
+			};
+			self.executor.spawn(delivery.send(self.retries, INITIAL_BACKOFF));
 		}
 	}
 }
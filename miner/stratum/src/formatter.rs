@@ -0,0 +1,140 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Formatting of the jobs pushed to workers, one formatter per stratum dialect.
+
+use std::str::FromStr;
+
+use jsonrpc_core::Value;
+use traits::{Job, JobFormatter};
+
+/// Dialect announced in `mining.subscribe` by NiceHash compatible miners.
+pub const NICEHASH_DIALECT: &str = "EthereumStratum/1.0.0";
+/// Leading nonce bytes assigned to each NiceHash connection, the miner searches the remaining ones.
+pub const NICEHASH_EXTRANONCE_SIZE: usize = 2;
+
+/// Pushes the job payload as is, e.g. the `eth_getWork` style `["0x", pow_hash, seed_hash, target, number]`.
+pub struct GetWorkFormatter;
+
+impl JobFormatter for GetWorkFormatter {
+	fn notify(&self, id: u32, job: &Job) -> String {
+		format!("{{ \"id\": {}, \"method\": \"mining.notify\", \"params\": {} }}", id, job.payload)
+	}
+}
+
+/// NiceHash `EthereumStratum/1.0.0` style job: `[job_id, seed_hash, header_hash, clean_jobs]`, without `0x` prefixes.
+///
+/// Expects an `eth_getWork` style payload and the pow hash as the job id, any other payload is pushed as is.
+pub struct NiceHashFormatter;
+
+impl JobFormatter for NiceHashFormatter {
+	fn notify(&self, id: u32, job: &Job) -> String {
+		let work: Vec<String> = match Value::from_str(&job.payload) {
+			Ok(Value::Array(values)) => values.into_iter()
+				.filter_map(|value| match value {
+					Value::String(s) => Some(s.trim_start_matches("0x").to_owned()),
+					_ => None,
+				})
+				.collect(),
+			_ => Vec::new(),
+		};
+		if work.len() < 3 {
+			trace!(target: "stratum", "Pushing unrecognized job payload as is: {}", job.payload);
+			return GetWorkFormatter.notify(id, job);
+		}
+
+		format!(
+			"{{ \"id\": {}, \"method\": \"mining.notify\", \"params\": [\"{}\", \"{}\", \"{}\", {}] }}",
+			id, job.id.trim_start_matches("0x"), work[2], work[1], job.clean_jobs
+		)
+	}
+
+	fn set_difficulty(&self, difficulty: f64) -> Option<String> {
+		Some(format!("{{ \"id\": null, \"method\": \"mining.set_difficulty\", \"params\": [{}] }}", difficulty))
+	}
+
+	fn extranonce_size(&self) -> usize {
+		NICEHASH_EXTRANONCE_SIZE
+	}
+
+	/// `[["mining.notify", session_id, "EthereumStratum/1.0.0"], extranonce]`
+	fn subscribe_result(&self, session_id: &str, extranonce: &str) -> Option<Value> {
+		Some(Value::Array(vec![
+			Value::Array(vec!["mining.notify".into(), session_id.into(), NICEHASH_DIALECT.into()]),
+			extranonce.into(),
+		]))
+	}
+
+	/// `[worker_id, job_id, nonce]` where the nonce lacks the extranonce, becomes `[nonce, pow_hash]`
+	fn submit_payload(&self, extranonce: &str, params: &[Value]) -> Vec<String> {
+		match (params.get(1), params.get(2)) {
+			(Some(Value::String(job_id)), Some(Value::String(nonce))) => vec![
+				format!("0x{}{}", extranonce, nonce.trim_start_matches("0x")),
+				format!("0x{}", job_id.trim_start_matches("0x")),
+			],
+			_ => {
+				trace!(target: "stratum", "Invalid NiceHash submission: {:?}", params);
+				Vec::new()
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn job(id: &str, payload: &str, clean_jobs: bool) -> Job {
+		Job { id: id.into(), payload: payload.into(), clean_jobs }
+	}
+
+	#[test]
+	fn formats_get_work_job() {
+		assert_eq!(
+			GetWorkFormatter.notify(17, &job("01", r#"["0x", "0x01", "0x02"]"#, true)),
+			r#"{ "id": 17, "method": "mining.notify", "params": ["0x", "0x01", "0x02"] }"#
+		);
+	}
+
+	#[test]
+	fn completes_nicehash_submission() {
+		let params: Vec<Value> = vec!["miner1".into(), "01".into(), "0203040506".into()];
+		assert_eq!(
+			NiceHashFormatter.submit_payload("ab00", &params),
+			vec!["0xab000203040506".to_owned(), "0x01".to_owned()]
+		);
+		assert_eq!(
+			GetWorkFormatter.submit_payload("", &params),
+			vec!["0203040506".to_owned()]
+		);
+	}
+
+	#[test]
+	fn formats_nicehash_job() {
+		assert_eq!(
+			NiceHashFormatter.notify(17, &job("01", r#"["0x", "0x01", "0x02", "0x03", "0x4"]"#, true)),
+			r#"{ "id": 17, "method": "mining.notify", "params": ["01", "02", "01", true] }"#
+		);
+		assert_eq!(
+			NiceHashFormatter.notify(17, &job("0x01", r#"["0x01", "0x01", "0x02", "0x03", "0x4"]"#, false)),
+			r#"{ "id": 17, "method": "mining.notify", "params": ["01", "02", "01", false] }"#
+		);
+		assert_eq!(
+			NiceHashFormatter.notify(17, &job("01", r#"{ "00040008", "100500" }"#, true)),
+			r#"{ "id": 17, "method": "mining.notify", "params": { "00040008", "100500" } }"#
+		);
+	}
+}
@@ -21,6 +21,9 @@ extern crate jsonrpc_core;
 extern crate ethereum_types;
 extern crate keccak_hash as hash;
 extern crate parking_lot;
+extern crate parity_runtime;
+extern crate rustls_pem;
+extern crate tokio_rustls;
 
 #[macro_use] extern crate log;
 
@@ -28,28 +31,44 @@ extern crate parking_lot;
 #[cfg(test)] extern crate tokio_io;
 #[cfg(test)] extern crate env_logger;
 
+mod formatter;
+mod proxy_protocol;
+mod stats;
+mod tls;
 mod traits;
+mod vardiff;
 
+pub use formatter::{GetWorkFormatter, NiceHashFormatter, NICEHASH_DIALECT, NICEHASH_EXTRANONCE_SIZE};
+pub use stats::{WorkerStats, HASHES_PER_DIFFICULTY};
+pub use tls::TlsConfig;
+pub use vardiff::VardiffConfig;
 pub use traits::{
-	JobDispatcher, PushWorkHandler, Error, ServiceConfiguration,
+	Job, JobDispatcher, JobFormatter, PushWorkHandler, Error, ServiceConfiguration,
 };
 
 use jsonrpc_tcp_server::{
 	Server as JsonRpcServer, ServerBuilder as JsonRpcServerBuilder,
 	RequestContext, MetaExtractor, Dispatcher, PushMessageError,
 };
-use jsonrpc_core::{MetaIoHandler, Params, to_value, Value, Metadata, Compatibility, IoDelegate};
+use jsonrpc_core::{MetaIoHandler, Params, to_value, Value, Metadata, Compatibility, IoDelegate, ErrorCode};
 use std::sync::Arc;
+use std::time::Instant;
 
 use std::net::SocketAddr;
-use std::collections::{HashSet, HashMap};
+use std::collections::{HashSet, HashMap, VecDeque};
 use hash::keccak;
 use ethereum_types::H256;
 use parking_lot::RwLock;
+use stats::Stats;
+use vardiff::Vardiff;
 
 type RpcResult = Result<jsonrpc_core::Value, jsonrpc_core::Error>;
 
 const NOTIFY_COUNTER_INITIAL: u32 = 16;
+/// Number of jobs pushed since the last clean job which shares are accepted for.
+const MAX_ACTIVE_JOBS: usize = 32;
+/// Stratum error code of shares for unknown or stale jobs.
+const JOB_NOT_FOUND: i64 = 21;
 
 /// Container which owns rpc server and stratum implementation
 pub struct Stratum {
@@ -65,6 +84,10 @@ pub struct Stratum {
 	///
 	/// Used to push messages to peers
 	tcp_dispatcher: Dispatcher,
+	/// PROXY protocol relay listening on the public address, if enabled
+	_relay: Option<proxy_protocol::Relay>,
+	/// TLS relay listening on its own address, if enabled
+	_tls_relay: Option<tls::Relay>,
 }
 
 impl Stratum {
@@ -72,42 +95,84 @@ impl Stratum {
 		addr: &SocketAddr,
 		dispatcher: Arc<dyn JobDispatcher>,
 		secret: Option<H256>,
+		accept_proxy_protocol: bool,
+		vardiff: Option<VardiffConfig>,
+		tls: Option<TlsConfig>,
 	) -> Result<Arc<Stratum>, Error> {
 
+		let mut formatters: HashMap<String, Arc<dyn JobFormatter>> = HashMap::new();
+		formatters.insert(NICEHASH_DIALECT.to_lowercase(), Arc::new(NiceHashFormatter));
+
 		let implementation = Arc::new(StratumImpl {
 			subscribers: RwLock::default(),
 			job_queue: RwLock::default(),
+			jobs: RwLock::default(),
 			dispatcher,
 			workers: Arc::new(RwLock::default()),
 			secret,
 			notify_counter: RwLock::new(NOTIFY_COUNTER_INITIAL),
+			formatters: RwLock::new(formatters),
+			peer_formatters: RwLock::default(),
+			extranonces: RwLock::default(),
+			next_extranonce: RwLock::new(0),
+			job_difficulty: RwLock::new(None),
+			vardiff: vardiff.map(Vardiff::new),
+			stats: Stats::default(),
 		});
 
 		let mut delegate = IoDelegate::<StratumImpl, SocketMetadata>::new(implementation.clone());
 		delegate.add_method_with_meta("mining.subscribe", StratumImpl::subscribe);
 		delegate.add_method_with_meta("mining.authorize", StratumImpl::authorize);
 		delegate.add_method_with_meta("mining.submit", StratumImpl::submit);
+		delegate.add_method_with_meta("mining.extranonce.subscribe", StratumImpl::extranonce_subscribe);
 		let mut handler = MetaIoHandler::<SocketMetadata>::with_compatibility(Compatibility::Both);
 		handler.extend_with(delegate);
 
 		let server_builder = JsonRpcServerBuilder::new(handler);
 		let tcp_dispatcher = server_builder.dispatcher();
-		let server_builder = server_builder.session_meta_extractor(PeerMetaExtractor::new(tcp_dispatcher.clone()));
-		let server = server_builder.start(addr)?;
+		let sources = proxy_protocol::SourceAddresses::default();
+		let server_builder = server_builder.session_meta_extractor(PeerMetaExtractor::new(tcp_dispatcher.clone(), sources.clone()));
+
+		let (server, backend, relay) = if accept_proxy_protocol {
+			let backend = proxy_protocol::loopback_address()?;
+			let relay = proxy_protocol::Relay::start(addr, backend, sources.clone())?;
+			(server_builder.start(&backend)?, backend, Some(relay))
+		} else {
+			(server_builder.start(addr)?, tls::connectable_address(addr), None)
+		};
+
+		// TLS connections don't carry PROXY protocol headers, they are relayed to the server directly
+		let tls_relay = match tls {
+			Some(ref config) => Some(tls::Relay::start(config, backend, sources)?),
+			None => None,
+		};
 
 		let stratum = Arc::new(Stratum {
 			rpc_server: Some(server),
 			implementation,
 			tcp_dispatcher,
+			_relay: relay,
+			_tls_relay: tls_relay,
 		});
 
 		Ok(stratum)
 	}
+
+	/// Format the jobs of workers announcing `dialect` in `mining.subscribe` with `formatter`.
+	/// Workers which announce no dialect, or an unknown one, receive `eth_getWork` style jobs.
+	pub fn register_formatter(&self, dialect: &str, formatter: Arc<dyn JobFormatter>) {
+		self.implementation.formatters.write().insert(dialect.to_lowercase(), formatter);
+	}
+
+	/// Share statistics of the authorized workers.
+	pub fn workers(&self) -> Vec<WorkerStats> {
+		self.implementation.stats.workers(Instant::now())
+	}
 }
 
 impl PushWorkHandler for Stratum {
-	fn push_work_all(&self, payload: String) {
-		self.implementation.push_work_all(payload, &self.tcp_dispatcher)
+	fn push_work_all(&self, job: Job) {
+		self.implementation.push_work_all(job, &self.tcp_dispatcher)
 	}
 }
 
@@ -123,6 +188,8 @@ struct StratumImpl {
 	subscribers: RwLock<Vec<SocketAddr>>,
 	/// List of workers supposed to receive job update
 	job_queue: RwLock<HashSet<SocketAddr>>,
+	/// Ids of the jobs shares are accepted for, oldest first. Empty until the first job is pushed.
+	jobs: RwLock<VecDeque<String>>,
 	/// Payload manager
 	dispatcher: Arc<dyn JobDispatcher>,
 	/// Authorized workers (socket - worker_id)
@@ -131,17 +198,55 @@ struct StratumImpl {
 	secret: Option<H256>,
 	/// Dispatch notify counter
 	notify_counter: RwLock<u32>,
+	/// Job formatters by dialect (lowercase)
+	formatters: RwLock<HashMap<String, Arc<dyn JobFormatter>>>,
+	/// Job formatters of the subscribers which announced a known dialect
+	peer_formatters: RwLock<HashMap<SocketAddr, Arc<dyn JobFormatter>>>,
+	/// Extranonces (hex) assigned to the subscribers whose dialect uses one
+	extranonces: RwLock<HashMap<SocketAddr, String>>,
+	/// Number of extranonces assigned so far, also used as the session id
+	next_extranonce: RwLock<u64>,
+	/// Share difficulty of the last pushed job, announced to the workers without their own difficulty
+	job_difficulty: RwLock<Option<f64>>,
+	/// Per-worker difficulty adjustment, if enabled
+	vardiff: Option<Vardiff>,
+	/// Share statistics of the authorized workers
+	stats: Stats,
 }
 
 impl StratumImpl {
 	/// rpc method `mining.subscribe`
-	fn subscribe(&self, _params: Params, meta: SocketMetadata) -> RpcResult {
+	fn subscribe(&self, params: Params, meta: SocketMetadata) -> RpcResult {
 		use std::str::FromStr;
 
-		self.subscribers.write().push(meta.addr().clone());
-		self.job_queue.write().insert(meta.addr().clone());
+		self.subscribers.write().push(meta.peer_addr);
+		self.job_queue.write().insert(meta.peer_addr);
 		trace!(target: "stratum", "Subscription request from {:?}", meta.addr());
 
+		// `[agent, dialect]`, e.g. `["ethminer/0.18.0", "EthereumStratum/1.0.0"]`
+		let dialect = match params {
+			Params::Array(ref vals) => match vals.get(1) {
+				Some(Value::String(ref dialect)) => Some(dialect.to_lowercase()),
+				_ => None,
+			},
+			_ => None,
+		};
+		self.extranonces.write().remove(&meta.peer_addr);
+		match dialect.and_then(|dialect| self.formatters.read().get(&dialect).cloned()) {
+			Some(formatter) => {
+				self.peer_formatters.write().insert(meta.peer_addr, formatter.clone());
+				let extranonce_size = formatter.extranonce_size();
+				if extranonce_size > 0 {
+					let (session_id, extranonce) = self.assign_extranonce(meta.peer_addr, extranonce_size);
+					trace!(target: "stratum", "Assigned extranonce {} to {:?}", extranonce, meta.addr());
+					if let Some(result) = formatter.subscribe_result(&session_id, &extranonce) {
+						return Ok(result);
+					}
+				}
+			},
+			None => { self.peer_formatters.write().remove(&meta.peer_addr); },
+		}
+
 		Ok(match self.dispatcher.initial() {
 			Some(initial) => match jsonrpc_core::Value::from_str(&initial) {
 				Ok(val) => Ok(val),
@@ -154,6 +259,32 @@ impl StratumImpl {
 		}.expect("Empty slices are serializable; qed"))
 	}
 
+	/// rpc method `mining.extranonce.subscribe`
+	///
+	/// The extranonce of a connection never changes, so `mining.set_extranonce` is never sent.
+	fn extranonce_subscribe(&self, _params: Params, meta: SocketMetadata) -> RpcResult {
+		trace!(target: "stratum", "Extranonce subscription from {:?}", meta.addr());
+		Ok(to_value(true).expect("Only true/false is returned and it's always serializable; qed"))
+	}
+
+	/// Assigns an extranonce of `size` bytes not used by any other connection, returns the session id and the extranonce
+	fn assign_extranonce(&self, addr: SocketAddr, size: usize) -> (String, String) {
+		let mut extranonces = self.extranonces.write();
+		let mut next = self.next_extranonce.write();
+		let space = 1u64.checked_shl(8 * size as u32).unwrap_or(0).wrapping_sub(1);
+		let mut extranonce = String::new();
+		// at most as many attempts as there are extranonces in use
+		for _ in 0..=extranonces.len() {
+			extranonce = format!("{:0width$x}", *next & space, width = size * 2);
+			*next += 1;
+			if !extranonces.values().any(|used| *used == extranonce) {
+				break;
+			}
+		}
+		extranonces.insert(addr, extranonce.clone());
+		(format!("{:016x}", *next - 1), extranonce)
+	}
+
 	/// rpc method `mining.authorize`
 	fn authorize(&self, params: Params, meta: SocketMetadata) -> RpcResult {
 		params.parse::<(String, String)>().map(|(worker_id, secret)| {
@@ -163,8 +294,20 @@ impl StratumImpl {
 					return to_value(&false);
 				}
 			}
-			trace!(target: "stratum", "New worker #{} registered", worker_id);
-			self.workers.write().insert(meta.addr().clone(), worker_id);
+			trace!(target: "stratum", "New worker #{} registered from {:?}", worker_id, meta.addr());
+			// workers whose dialect can be told their difficulty get their own one, or the one of the jobs
+			let own_difficulty = self.vardiff.as_ref().map(Vardiff::initial_difficulty);
+			let difficulty = own_difficulty.or_else(|| self.dispatcher.share_difficulty());
+			if let Some(message) = difficulty.and_then(|difficulty| self.set_difficulty_message(&meta.peer_addr, difficulty)) {
+				if let Some(ref vardiff) = self.vardiff {
+					vardiff.register(meta.peer_addr, worker_id.clone(), Instant::now());
+				}
+				if let Some(ref tcp_dispatcher) = meta.tcp_dispatcher {
+					Self::push_difficulty_message(&meta.peer_addr, message, tcp_dispatcher);
+				}
+			}
+			self.stats.register(meta.peer_addr, meta.addr(), worker_id.clone(), Instant::now());
+			self.workers.write().insert(meta.peer_addr, worker_id);
 			to_value(true)
 		}).map(|v| v.expect("Only true/false is returned and it's always serializable; qed"))
 	}
@@ -173,19 +316,47 @@ impl StratumImpl {
 	fn submit(&self, params: Params, meta: SocketMetadata) -> RpcResult {
 		Ok(match params {
 			Params::Array(vals) => {
+				let tcp_dispatcher = meta.tcp_dispatcher.as_ref().expect("tcp_dispatcher is always initialized; qed");
+				let worker_difficulty = self.vardiff.as_ref().and_then(|vardiff| vardiff.difficulty(&meta.peer_addr));
+				let share_difficulty = worker_difficulty.or_else(|| self.dispatcher.share_difficulty());
+				if let Some(job_id) = vals.get(1).and_then(Value::as_str) {
+					if self.is_stale(job_id) {
+						trace!(target: "stratum", "Share for stale job {} from {:?}", job_id, meta.addr());
+						self.stats.record_share(&meta.peer_addr, false, share_difficulty, Instant::now());
+						return Err(jsonrpc_core::Error {
+							code: ErrorCode::ServerError(JOB_NOT_FOUND),
+							message: "Job not found".into(),
+							data: None,
+						});
+					}
+				}
 				// first two elements are service messages (worker_id & job_id)
-				match self.dispatcher.submit(vals.iter().skip(2)
-					.filter_map(|val| match *val {
-						Value::String(ref s) => Some(s.to_owned()),
-						_ => None
-					})
-					.collect::<Vec<String>>()) {
+				let payload = match self.peer_formatters.read().get(&meta.peer_addr) {
+					Some(formatter) => {
+						let extranonce = self.extranonces.read().get(&meta.peer_addr).cloned().unwrap_or_default();
+						formatter.submit_payload(&extranonce, &vals)
+					},
+					None => GetWorkFormatter.submit_payload("", &vals),
+				};
+				let result = match worker_difficulty {
+					Some(difficulty) => self.dispatcher.submit_share(payload, difficulty),
+					None => self.dispatcher.submit(payload),
+				};
+				match result {
 					Ok(()) => {
-						self.update_peers(&meta.tcp_dispatcher.expect("tcp_dispatcher is always initialized; qed"));
+						if let Some(ref vardiff) = self.vardiff {
+							vardiff.record_share(&meta.peer_addr);
+							if let Some(difficulty) = vardiff.retarget(&meta.peer_addr, Instant::now()) {
+								self.push_difficulty(&meta.peer_addr, difficulty, tcp_dispatcher);
+							}
+						}
+						self.stats.record_share(&meta.peer_addr, true, share_difficulty, Instant::now());
+						self.update_peers(tcp_dispatcher);
 						to_value(true)
 					},
 					Err(submit_err) => {
 						warn!("Error while submitting share: {:?}", submit_err);
+						self.stats.record_share(&meta.peer_addr, false, share_difficulty, Instant::now());
 						to_value(false)
 					}
 				}
@@ -197,6 +368,28 @@ impl StratumImpl {
 		}.expect("Only true/false is returned and it's always serializable; qed"))
 	}
 
+	/// Whether shares for the job are no longer accepted, shares for any job are accepted until the first one is pushed
+	fn is_stale(&self, job_id: &str) -> bool {
+		let job_id = job_id.trim_start_matches("0x").to_lowercase();
+		let jobs = self.jobs.read();
+		!jobs.is_empty() && !jobs.contains(&job_id)
+	}
+
+	/// Tracks a pushed job, all previous ones become stale if it's a clean one
+	fn note_job(&self, job: &Job) {
+		let job_id = job.id.trim_start_matches("0x").to_lowercase();
+		let mut jobs = self.jobs.write();
+		if job.clean_jobs {
+			jobs.clear();
+		}
+		if !jobs.contains(&job_id) {
+			jobs.push_back(job_id);
+		}
+		while jobs.len() > MAX_ACTIVE_JOBS {
+			jobs.pop_front();
+		}
+	}
+
 	/// Helper method
 	fn update_peers(&self, tcp_dispatcher: &Dispatcher) {
 		if let Some(job) = self.dispatcher.job() {
@@ -204,7 +397,37 @@ impl StratumImpl {
 		}
 	}
 
-	fn push_work_all(&self, payload: String, tcp_dispatcher: &Dispatcher) {
+	/// `mining.set_difficulty` notification for a worker in its dialect, `None` if the dialect has none
+	fn set_difficulty_message(&self, addr: &SocketAddr, difficulty: f64) -> Option<String> {
+		match self.peer_formatters.read().get(addr) {
+			Some(formatter) => formatter.set_difficulty(difficulty),
+			None => GetWorkFormatter.set_difficulty(difficulty),
+		}
+	}
+
+	/// Announces the share difficulty of a worker, disconnected workers are cleaned up by the next job push
+	fn push_difficulty(&self, addr: &SocketAddr, difficulty: f64, tcp_dispatcher: &Dispatcher) {
+		if let Some(message) = self.set_difficulty_message(addr, difficulty) {
+			Self::push_difficulty_message(addr, message, tcp_dispatcher)
+		}
+	}
+
+	fn push_difficulty_message(addr: &SocketAddr, message: String, tcp_dispatcher: &Dispatcher) {
+		trace!(target: "stratum", "pushing difficulty to {} (message: '{}')", addr, &message);
+		if let Err(e) = tcp_dispatcher.push_message(addr, message) {
+			trace!(target: "stratum", "Failed to push difficulty to {}: {:?}", addr, e);
+		}
+	}
+
+	fn push_work_all(&self, job: Job, tcp_dispatcher: &Dispatcher) {
+		self.note_job(&job);
+		let job_difficulty = self.dispatcher.share_difficulty();
+		let job_difficulty_changed = {
+			let mut last = self.job_difficulty.write();
+			let changed = *last != job_difficulty;
+			*last = job_difficulty;
+			changed
+		};
 		let hup_peers = {
 			let workers = self.workers.read();
 			let next_request_id = {
@@ -218,11 +441,26 @@ impl StratumImpl {
 			};
 
 			let mut hup_peers = HashSet::new();
-			let workers_msg = format!("{{ \"id\": {}, \"method\": \"mining.notify\", \"params\": {} }}", next_request_id, payload);
-			trace!(target: "stratum", "pushing work for {} workers (payload: '{}')", workers.len(), &workers_msg);
+			let peer_formatters = self.peer_formatters.read();
+			let now = Instant::now();
+			trace!(target: "stratum", "pushing job {} for {} workers (payload: '{}', clean: {})", job.id, workers.len(), &job.payload, job.clean_jobs);
 			for (addr, _) in workers.iter() {
-				trace!(target: "stratum", "pushing work to {}", addr);
-				match tcp_dispatcher.push_message(addr, workers_msg.clone()) {
+				let formatter: &dyn JobFormatter = match peer_formatters.get(addr) {
+					Some(formatter) => &**formatter,
+					None => &GetWorkFormatter,
+				};
+				// workers which stopped submitting shares are only retargeted here
+				let difficulty = match self.vardiff {
+					Some(ref vardiff) if vardiff.difficulty(addr).is_some() => vardiff.retarget(addr, now),
+					_ if job_difficulty_changed => job_difficulty,
+					_ => None,
+				};
+				if let Some(message) = difficulty.and_then(|difficulty| formatter.set_difficulty(difficulty)) {
+					Self::push_difficulty_message(addr, message, tcp_dispatcher);
+				}
+				let workers_msg = formatter.notify(next_request_id, &job);
+				trace!(target: "stratum", "pushing work to {} (message: '{}')", addr, &workers_msg);
+				match tcp_dispatcher.push_message(addr, workers_msg) {
 					Err(PushMessageError::NoSuchPeer) => {
 						trace!(target: "stratum", "Worker no longer connected: {}", addr);
 						hup_peers.insert(addr.clone());
@@ -238,8 +476,15 @@ impl StratumImpl {
 
 		if !hup_peers.is_empty() {
 			let mut workers = self.workers.write();
+			let mut peer_formatters = self.peer_formatters.write();
 			for hup_peer in hup_peers {
 				workers.remove(&hup_peer);
+				peer_formatters.remove(&hup_peer);
+				self.extranonces.write().remove(&hup_peer);
+				self.stats.remove(&hup_peer);
+				if let Some(ref vardiff) = self.vardiff {
+					vardiff.remove(&hup_peer);
+				}
 			}
 		}
 	}
@@ -247,7 +492,10 @@ impl StratumImpl {
 
 #[derive(Clone)]
 pub struct SocketMetadata {
-	addr: SocketAddr,
+	/// Address of the connection, used to push messages to the peer
+	peer_addr: SocketAddr,
+	/// Source addresses reported by the PROXY protocol
+	sources: proxy_protocol::SourceAddresses,
 	// with the new version of jsonrpc-core, SocketMetadata
 	// won't have to implement default, so this field will not
 	// have to be an Option
@@ -257,15 +505,17 @@ pub struct SocketMetadata {
 impl Default for SocketMetadata {
 	fn default() -> Self {
 		SocketMetadata {
-			addr: "0.0.0.0:0".parse().unwrap(),
+			peer_addr: "0.0.0.0:0".parse().unwrap(),
+			sources: Default::default(),
 			tcp_dispatcher: None,
 		}
 	}
 }
 
 impl SocketMetadata {
-	pub fn addr(&self) -> &SocketAddr {
-		&self.addr
+	/// Address of the miner. If PROXY protocol is enabled it's the source address reported by the proxy.
+	pub fn addr(&self) -> SocketAddr {
+		self.sources.read().get(&self.peer_addr).cloned().unwrap_or(self.peer_addr)
 	}
 }
 
@@ -273,20 +523,24 @@ impl Metadata for SocketMetadata { }
 
 pub struct PeerMetaExtractor {
 	tcp_dispatcher: Dispatcher,
+	sources: proxy_protocol::SourceAddresses,
 }
 
 impl PeerMetaExtractor {
-	fn new(tcp_dispatcher: Dispatcher) -> Self {
+	fn new(tcp_dispatcher: Dispatcher, sources: proxy_protocol::SourceAddresses) -> Self {
 		PeerMetaExtractor {
 			tcp_dispatcher,
+			sources,
 		}
 	}
 }
 
 impl MetaExtractor<SocketMetadata> for PeerMetaExtractor {
 	fn extract(&self, context: &RequestContext) -> SocketMetadata {
+		// the relay may not have recorded the source address yet, so it's resolved lazily
 		SocketMetadata {
-			addr: context.peer_addr,
+			peer_addr: context.peer_addr,
+			sources: self.sources.clone(),
 			tcp_dispatcher: Some(self.tcp_dispatcher.clone()),
 		}
 	}
@@ -333,7 +587,7 @@ mod tests {
 
 	#[test]
 	fn can_be_started() {
-		let stratum = Stratum::start(&"127.0.0.1:19980".parse().unwrap(), Arc::new(VoidManager), None);
+		let stratum = Stratum::start(&"127.0.0.1:19980".parse().unwrap(), Arc::new(VoidManager), None, false, None, None);
 		assert!(stratum.is_ok());
 	}
 
@@ -342,12 +596,46 @@ mod tests {
 		let _ = ::env_logger::try_init();
 
 		let addr = "127.0.0.1:19985".parse().unwrap();
-		let stratum = Stratum::start(&addr, Arc::new(VoidManager), None).unwrap();
+		let stratum = Stratum::start(&addr, Arc::new(VoidManager), None, false, None, None).unwrap();
 		let request = r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": [], "id": 1}"#;
 		dummy_request(&addr, request);
 		assert_eq!(1, stratum.implementation.subscribers.read().len());
 	}
 
+	#[test]
+	fn records_subscriber_dialect() {
+		let addr = "127.0.0.1:19960".parse().unwrap();
+		let stratum = Stratum::start(&addr, Arc::new(VoidManager), None, false, None, None).unwrap();
+		let request = r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": ["miner/1.0", "EthereumStratum/1.0.0"], "id": 1}"#;
+		dummy_request(&addr, request);
+		assert_eq!(1, stratum.implementation.peer_formatters.read().len());
+
+		let request = r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": ["miner/1.0", "unknown/1.0"], "id": 1}"#;
+		dummy_request(&addr, request);
+		assert_eq!(1, stratum.implementation.peer_formatters.read().len());
+	}
+
+	#[test]
+	fn assigns_nicehash_extranonce() {
+		let addr = "127.0.0.1:19950".parse().unwrap();
+		let stratum = Stratum::start(&addr, DummyManager::new(), None, false, None, None).unwrap();
+
+		let request = r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": ["miner/1.0", "EthereumStratum/1.0.0"], "id": 1}"#;
+		let response = String::from_utf8(dummy_request(&addr, request)).unwrap();
+		assert_eq!(
+			terminated_str(r#"{"jsonrpc":"2.0","result":[["mining.notify","0000000000000000","EthereumStratum/1.0.0"],"0000"],"id":1}"#),
+			response
+		);
+
+		let response = String::from_utf8(dummy_request(&addr, request)).unwrap();
+		assert!(response.contains(r#""0001"]"#));
+		assert_eq!(2, stratum.implementation.extranonces.read().len());
+
+		let request = r#"{"jsonrpc": "2.0", "method": "mining.extranonce.subscribe", "params": [], "id": 2}"#;
+		let response = String::from_utf8(dummy_request(&addr, request)).unwrap();
+		assert_eq!(terminated_str(r#"{"jsonrpc":"2.0","result":true,"id":2}"#), response);
+	}
+
 	struct DummyManager {
 		initial_payload: String
 	}
@@ -387,7 +675,7 @@ mod tests {
 	#[test]
 	fn receives_initial_payload() {
 		let addr = "127.0.0.1:19975".parse().unwrap();
-		let _stratum = Stratum::start(&addr, DummyManager::new(), None).expect("There should be no error starting stratum");
+		let _stratum = Stratum::start(&addr, DummyManager::new(), None, false, None, None).expect("There should be no error starting stratum");
 		let request = r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": [], "id": 2}"#;
 
 		let response = String::from_utf8(dummy_request(&addr, request)).unwrap();
@@ -395,13 +683,63 @@ mod tests {
 		assert_eq!(terminated_str(r#"{"jsonrpc":"2.0","result":["dummy payload"],"id":2}"#), response);
 	}
 
+	#[test]
+	fn records_worker_stats() {
+		let addr = "127.0.0.1:19935".parse().unwrap();
+		let stratum = Stratum::start(&addr, DummyManager::new(), None, false, None, None)
+			.expect("There should be no error starting stratum");
+
+		let request = concat!(
+			r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1", ""], "id": 1}"#, "\n",
+			r#"{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1", "job1", "0x1", "0x2", "0x3"], "id": 2}"#,
+		);
+		dummy_request(&addr, request);
+
+		let workers = stratum.workers();
+		assert_eq!(workers.len(), 1);
+		assert_eq!(workers[0].worker_id, "miner1");
+		assert_eq!(workers[0].accepted_shares, 1);
+		assert_eq!(workers[0].rejected_shares, 0);
+		assert!(workers[0].last_share.is_some());
+		// neither vardiff nor the dispatcher know the share difficulty
+		assert_eq!(workers[0].hashrate, None);
+	}
+
+	#[test]
+	fn rejects_stale_shares() {
+		let addr = "127.0.0.1:19930".parse().unwrap();
+		let stratum = Stratum::start(&addr, DummyManager::new(), None, false, None, None)
+			.expect("There should be no error starting stratum");
+		let job = |id: &str, clean_jobs| Job { id: id.into(), payload: "[]".into(), clean_jobs };
+		let submit = |job_id: &str| String::from_utf8(dummy_request(&addr, &format!(
+			r#"{{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1", "{}", "0x1", "0x2", "0x3"], "id": 2}}"#,
+			job_id
+		))).unwrap();
+		let accepted = terminated_str(r#"{"jsonrpc":"2.0","result":true,"id":2}"#);
+		let stale = terminated_str(r#"{"jsonrpc":"2.0","error":{"code":21,"message":"Job not found"},"id":2}"#);
+
+		stratum.push_work_all(job("0x01", true));
+		stratum.push_work_all(job("0x02", false));
+		assert_eq!(submit("0x01"), accepted);
+		assert_eq!(submit("02"), accepted);
+		assert_eq!(submit("0x00"), stale);
+
+		// the new block makes the previous jobs stale
+		stratum.push_work_all(job("0x03", true));
+		assert_eq!(submit("0x02"), stale);
+		assert_eq!(submit("0x03"), accepted);
+	}
+
 	#[test]
 	fn can_authorize() {
 		let addr = "127.0.0.1:19970".parse().unwrap();
 		let stratum = Stratum::start(
 			&addr,
 			Arc::new(DummyManager::build().of_initial(r#"["dummy autorize payload"]"#)),
-			None
+			None,
+			false,
+			None,
+			None,
 		).expect("There should be no error starting stratum");
 
 		let request = r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1", ""], "id": 1}"#;
@@ -411,6 +749,213 @@ mod tests {
 		assert_eq!(1, stratum.implementation.workers.read().len());
 	}
 
+	#[test]
+	fn pushes_worker_difficulty() {
+		let addr = "127.0.0.1:19955".parse().unwrap();
+		let stratum = Stratum::start(
+			&addr,
+			DummyManager::new(),
+			None,
+			false,
+			Some(VardiffConfig { initial_difficulty: 2.0, ..Default::default() }),
+			None,
+		).expect("There should be no error starting stratum");
+
+		let request = concat!(
+			r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": ["miner/1.0", "EthereumStratum/1.0.0"], "id": 1}"#, "\n",
+			r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1", ""], "id": 2}"#,
+		);
+		let response = String::from_utf8(dummy_request(&addr, request)).unwrap();
+
+		assert_eq!(
+			concat!(
+				r#"{"jsonrpc":"2.0","result":[["mining.notify","0000000000000000","EthereumStratum/1.0.0"],"0000"],"id":1}"#, "\n",
+				"{ \"id\": null, \"method\": \"mining.set_difficulty\", \"params\": [2] }\n",
+				"{\"jsonrpc\":\"2.0\",\"result\":true,\"id\":2}\n",
+			),
+			response
+		);
+		assert_eq!(1, stratum.implementation.workers.read().len());
+
+		// `eth_getWork` style workers can't be told their difficulty, they keep mining at the job's one
+		let request = r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner2", ""], "id": 1}"#;
+		let response = String::from_utf8(dummy_request(&addr, request)).unwrap();
+
+		assert_eq!(terminated_str(r#"{"jsonrpc":"2.0","result":true,"id":1}"#), response);
+		assert_eq!(2, stratum.implementation.workers.read().len());
+	}
+
+	#[derive(Default)]
+	struct ShareManager {
+		share_difficulties: ::parking_lot::Mutex<Vec<f64>>,
+	}
+
+	impl JobDispatcher for ShareManager {
+		fn submit(&self, _payload: Vec<String>) -> Result<(), Error> {
+			Ok(())
+		}
+
+		fn submit_share(&self, _payload: Vec<String>, difficulty: f64) -> Result<(), Error> {
+			self.share_difficulties.lock().push(difficulty);
+			Err(Error::Dispatch("Share doesn't meet the worker difficulty".into()))
+		}
+	}
+
+	#[test]
+	fn validates_shares_against_worker_difficulty() {
+		let addr = "127.0.0.1:19925".parse().unwrap();
+		let manager = Arc::new(ShareManager::default());
+		let stratum = Stratum::start(
+			&addr,
+			manager.clone(),
+			None,
+			false,
+			Some(VardiffConfig { initial_difficulty: 2.0, ..Default::default() }),
+			None,
+		).expect("There should be no error starting stratum");
+
+		let request = concat!(
+			r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": ["miner/1.0", "EthereumStratum/1.0.0"], "id": 1}"#, "\n",
+			r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1", ""], "id": 2}"#, "\n",
+			r#"{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1", "01", "0203040506"], "id": 3}"#,
+		);
+		let response = String::from_utf8(dummy_request(&addr, request)).unwrap();
+
+		assert!(response.ends_with("{\"jsonrpc\":\"2.0\",\"result\":false,\"id\":3}\n"));
+		assert_eq!(*manager.share_difficulties.lock(), vec![2.0]);
+		let workers = stratum.workers();
+		assert_eq!(workers[0].accepted_shares, 0);
+		assert_eq!(workers[0].rejected_shares, 1);
+	}
+
+	struct JobDifficultyManager;
+
+	impl JobDispatcher for JobDifficultyManager {
+		fn submit(&self, _payload: Vec<String>) -> Result<(), Error> {
+			Ok(())
+		}
+
+		fn share_difficulty(&self) -> Option<f64> {
+			Some(4.0)
+		}
+	}
+
+	#[test]
+	fn pushes_job_difficulty_to_nicehash_workers() {
+		let addr = "127.0.0.1:19920".parse().unwrap();
+		let _stratum = Stratum::start(&addr, Arc::new(JobDifficultyManager), None, false, None, None)
+			.expect("There should be no error starting stratum");
+
+		let request = concat!(
+			r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": ["miner/1.0", "EthereumStratum/1.0.0"], "id": 1}"#, "\n",
+			r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1", ""], "id": 2}"#,
+		);
+		let response = String::from_utf8(dummy_request(&addr, request)).unwrap();
+
+		assert!(response.ends_with(concat!(
+			"{ \"id\": null, \"method\": \"mining.set_difficulty\", \"params\": [4] }\n",
+			"{\"jsonrpc\":\"2.0\",\"result\":true,\"id\":2}\n",
+		)));
+	}
+
+	#[test]
+	fn accepts_proxy_protocol_header() {
+		let addr = "127.0.0.1:19965".parse().unwrap();
+		let stratum = Stratum::start(&addr, DummyManager::new(), None, true, None, None)
+			.expect("There should be no error starting stratum");
+
+		let request = concat!(
+			"PROXY TCP4 192.168.0.1 192.168.0.11 56324 8008\r\n",
+			r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1", ""], "id": 1}"#,
+		);
+		let response = String::from_utf8(dummy_request(&addr, request)).unwrap();
+
+		assert_eq!(terminated_str(r#"{"jsonrpc":"2.0","result":true,"id":1}"#), response);
+		assert_eq!(1, stratum.implementation.workers.read().len());
+	}
+
+	fn tls_config(addr: SocketAddr) -> TlsConfig {
+		let res = concat!(env!("CARGO_MANIFEST_DIR"), "/res/tls/");
+		TlsConfig {
+			addr,
+			certificate_path: format!("{}cert.pem", res),
+			private_key_path: format!("{}key.pem", res),
+		}
+	}
+
+	/// Sends `data` over a TLS connection to `addr` and reads a response of `response_len` bytes.
+	fn dummy_tls_request(addr: &SocketAddr, data: &str, response_len: usize) -> Vec<u8> {
+		use std::fs::File;
+		use std::io::BufReader;
+		use tokio_rustls::TlsConnector;
+		use tokio_rustls::rustls::ClientConfig;
+		use tokio_rustls::webpki::DNSNameRef;
+
+		let mut client_config = ClientConfig::new();
+		client_config.root_store
+			.add_pem_file(&mut BufReader::new(File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/res/tls/ca.pem")).unwrap()))
+			.unwrap();
+		let connector = TlsConnector::from(Arc::new(client_config));
+
+		let mut runtime = Runtime::new().expect("Tokio Runtime should be created with no errors");
+		let data_vec = format!("{}\n", data).into_bytes();
+		let (_stream, response) = runtime.block_on(TcpStream::connect(addr)
+			.and_then(move |stream| connector.connect(DNSNameRef::try_from_ascii_str("localhost").unwrap(), stream))
+			.and_then(move |stream| io::write_all(stream, data_vec))
+			.and_then(move |(stream, _)| io::read_exact(stream, vec![0u8; response_len]))
+		).expect("TLS connection should be accepted");
+		response
+	}
+
+	#[test]
+	fn accepts_tls_connections() {
+		let addr = "127.0.0.1:19945".parse().unwrap();
+		let tls_addr = "127.0.0.1:19940".parse().unwrap();
+		let stratum = Stratum::start(&addr, DummyManager::new(), None, false, None, Some(tls_config(tls_addr)))
+			.expect("There should be no error starting stratum");
+
+		let request = r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1", ""], "id": 1}"#;
+		let response = terminated_str(r#"{"jsonrpc":"2.0","result":true,"id":1}"#);
+		assert_eq!(response, String::from_utf8(dummy_tls_request(&tls_addr, request, response.len())).unwrap());
+
+		// the plaintext listener keeps serving workers next to the TLS one
+		assert_eq!(response, String::from_utf8(dummy_request(&addr, request)).unwrap());
+		assert_eq!(2, stratum.implementation.workers.read().len());
+	}
+
+	#[test]
+	fn subscribes_over_tls() {
+		let addr = "127.0.0.1:19915".parse().unwrap();
+		let tls_addr = "127.0.0.1:19910".parse().unwrap();
+		let stratum = Stratum::start(&addr, DummyManager::new(), None, false, None, Some(tls_config(tls_addr)))
+			.expect("There should be no error starting stratum");
+
+		let request = r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": [], "id": 2}"#;
+		let response = terminated_str(r#"{"jsonrpc":"2.0","result":["dummy payload"],"id":2}"#);
+		assert_eq!(response, String::from_utf8(dummy_tls_request(&tls_addr, request, response.len())).unwrap());
+		assert_eq!(1, stratum.implementation.subscribers.read().len());
+	}
+
+	#[test]
+	fn drops_tls_connections_stalling_the_handshake() {
+		use std::time::{Duration, Instant};
+
+		let addr = "127.0.0.1:19905".parse().unwrap();
+		let tls_addr = "127.0.0.1:19900".parse().unwrap();
+		let _stratum = Stratum::start(&addr, DummyManager::new(), None, false, None, Some(tls_config(tls_addr)))
+			.expect("There should be no error starting stratum");
+
+		// connect without ever starting the handshake, the relay closes the connection once it times out
+		let started = Instant::now();
+		let mut runtime = Runtime::new().expect("Tokio Runtime should be created with no errors");
+		let (_stream, read_buf) = runtime.block_on(Timeout::new(
+			TcpStream::connect(&tls_addr).and_then(|stream| io::read_to_end(stream, Vec::new())),
+			Duration::from_secs(5),
+		)).expect("Stalled TLS connection should be closed");
+		assert!(read_buf.is_empty());
+		assert!(started.elapsed() >= Duration::from_millis(500));
+	}
+
 	#[test]
 	fn can_push_work() {
 		let _ = ::env_logger::try_init();
@@ -419,7 +964,10 @@ mod tests {
 		let stratum = Stratum::start(
 			&addr,
 			Arc::new(DummyManager::build().of_initial(r#"["dummy autorize payload"]"#)),
-			None
+			None,
+			false,
+			None,
+			None,
 		).expect("There should be no error starting stratum");
 
 		let mut auth_request =
@@ -449,7 +997,11 @@ mod tests {
 			.map_err(|err: timeout::Error<()>| panic!("Timeout: {:?}", err))
 			.and_then(move |stream| {
 				trace!(target: "stratum", "Pusing work to peers");
-				stratum.push_work_all(r#"{ "00040008", "100500" }"#.to_owned());
+				stratum.push_work_all(Job {
+					id: "01".into(),
+					payload: r#"{ "00040008", "100500" }"#.into(),
+					clean_jobs: true,
+				});
 				Timeout::new(future::ok(stream), ::std::time::Duration::from_millis(100))
 			})
 			.map_err(|err: timeout::Error<()>| panic!("Timeout: {:?}", err))
@@ -0,0 +1,299 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! HAProxy PROXY protocol (v1 and v2) support.
+//!
+//! `jsonrpc-tcp-server` does not give access to the raw connection, so when the protocol is
+//! enabled the public port is served by a relay instead. The relay strips the PROXY header,
+//! forwards the connection to the stratum server bound on loopback and records the original
+//! source address, keyed by the address the stratum server sees as the peer.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener as StdTcpListener};
+use std::str;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use parity_runtime::Runtime;
+use parity_runtime::futures::{Async, Future, Poll, Stream};
+use parity_runtime::tokio::io::{copy, shutdown, write_all, AsyncRead, AsyncWrite};
+use parity_runtime::tokio::net::{TcpListener, TcpStream};
+
+/// Original source addresses of relayed connections, keyed by the relay side of the connection.
+pub type SourceAddresses = Arc<RwLock<HashMap<SocketAddr, SocketAddr>>>;
+
+const V1_PREFIX: &[u8] = b"PROXY ";
+/// Maximal length of a v1 header, including the trailing CRLF.
+const V1_MAX_LENGTH: usize = 107;
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+/// Length of the fixed part of a v2 header (signature, version, family and length).
+const V2_HEADER_LENGTH: usize = 16;
+
+/// PROXY protocol header.
+#[derive(Debug, PartialEq)]
+pub struct Header {
+	/// Source address of the proxied connection, `None` for connections made by the proxy itself.
+	pub source: Option<SocketAddr>,
+	/// Length of the header in bytes.
+	pub length: usize,
+}
+
+/// Parse a v1 or v2 header at the beginning of `buf`. Returns `None` if more data is needed.
+pub fn parse_header(buf: &[u8]) -> Result<Option<Header>, String> {
+	if is_prefix(buf, V1_PREFIX) {
+		parse_v1(buf)
+	} else if is_prefix(buf, V2_SIGNATURE) {
+		parse_v2(buf)
+	} else {
+		Err("Missing PROXY protocol header".into())
+	}
+}
+
+/// Returns true if `buf` and `signature` agree on their common part.
+fn is_prefix(buf: &[u8], signature: &[u8]) -> bool {
+	let len = buf.len().min(signature.len());
+	buf[..len] == signature[..len]
+}
+
+fn parse_v1(buf: &[u8]) -> Result<Option<Header>, String> {
+	let end = match buf.windows(2).position(|w| w == b"\r\n") {
+		Some(end) if end + 2 <= V1_MAX_LENGTH => end,
+		None if buf.len() < V1_MAX_LENGTH => return Ok(None),
+		_ => return Err("PROXY v1 header is too long".into()),
+	};
+	let line = str::from_utf8(&buf[..end]).map_err(|_| "PROXY v1 header is not valid ASCII".to_owned())?;
+	let invalid = || format!("Invalid PROXY v1 header: {}", line);
+
+	let parts: Vec<&str> = line.split(' ').collect();
+	let source = match parts.get(1) {
+		Some(&"UNKNOWN") => None,
+		Some(&protocol) if parts.len() == 6 => {
+			let ip: IpAddr = parts[2].parse().map_err(|_| invalid())?;
+			let port: u16 = parts[4].parse().map_err(|_| invalid())?;
+			match (protocol, ip) {
+				("TCP4", IpAddr::V4(_)) | ("TCP6", IpAddr::V6(_)) => Some(SocketAddr::new(ip, port)),
+				_ => return Err(invalid()),
+			}
+		},
+		_ => return Err(invalid()),
+	};
+
+	Ok(Some(Header { source, length: end + 2 }))
+}
+
+fn parse_v2(buf: &[u8]) -> Result<Option<Header>, String> {
+	if buf.len() < V2_HEADER_LENGTH {
+		return Ok(None);
+	}
+	if buf[12] >> 4 != 2 {
+		return Err(format!("Unsupported PROXY protocol version: {}", buf[12] >> 4));
+	}
+	let length = V2_HEADER_LENGTH + u16::from_be_bytes([buf[14], buf[15]]) as usize;
+	if buf.len() < length {
+		return Ok(None);
+	}
+
+	let addresses = &buf[V2_HEADER_LENGTH..length];
+	let source = match (buf[12] & 0x0f, buf[13] >> 4) {
+		// LOCAL command, e.g. health checks of the proxy itself
+		(0x0, _) => None,
+		(0x1, 0x1) if addresses.len() >= 12 => {
+			let ip = Ipv4Addr::new(addresses[0], addresses[1], addresses[2], addresses[3]);
+			Some(SocketAddr::new(ip.into(), u16::from_be_bytes([addresses[8], addresses[9]])))
+		},
+		(0x1, 0x2) if addresses.len() >= 36 => {
+			let mut octets = [0u8; 16];
+			octets.copy_from_slice(&addresses[..16]);
+			Some(SocketAddr::new(Ipv6Addr::from(octets).into(), u16::from_be_bytes([addresses[32], addresses[33]])))
+		},
+		// unspecified or unix socket family
+		(0x1, 0x0) | (0x1, 0x3) => None,
+		_ => return Err("Invalid PROXY v2 header".into()),
+	};
+
+	Ok(Some(Header { source, length }))
+}
+
+/// Returns a free loopback address for the stratum server hidden behind the relay.
+pub fn loopback_address() -> io::Result<SocketAddr> {
+	StdTcpListener::bind((Ipv4Addr::LOCALHOST, 0))?.local_addr()
+}
+
+/// Accepts connections with PROXY protocol headers and relays them to the stratum server.
+pub struct Relay {
+	_runtime: Runtime,
+}
+
+impl Relay {
+	/// Start listening on `addr`, forwarding connections to `backend`.
+	pub fn start(addr: &SocketAddr, backend: SocketAddr, sources: SourceAddresses) -> io::Result<Self> {
+		let listener = TcpListener::bind(addr)?;
+		let runtime = Runtime::with_thread_count(1);
+		let executor = runtime.executor();
+
+		runtime.executor().spawn(listener.incoming()
+			.then(|client| Ok::<_, ()>(client.map_err(|e| warn!(target: "stratum", "Unable to accept connection: {}", e)).ok()))
+			.filter_map(|client| client)
+			.for_each(move |client| {
+				executor.spawn(relay(client, backend, sources.clone()));
+				Ok(())
+			})
+		);
+
+		Ok(Relay { _runtime: runtime })
+	}
+}
+
+fn relay(client: TcpStream, backend: SocketAddr, sources: SourceAddresses) -> impl Future<Item = (), Error = ()> {
+	let peer = client.peer_addr().ok();
+	let cleanup = sources.clone();
+
+	ReadHeader { socket: Some(client), buf: Vec::new() }
+		.and_then(move |(client, source, rest)| {
+			TcpStream::connect(&backend).map(move |server| (client, server, source, rest))
+		})
+		.and_then(move |(client, server, source, rest)| {
+			let local = server.local_addr()?;
+			trace!(target: "stratum", "Relaying connection from {:?} (source {:?})", peer, source);
+			if let Some(source) = source {
+				sources.write().insert(local, source);
+			}
+
+			let client = SharedStream(Arc::new(client));
+			let server = SharedStream(Arc::new(server));
+			let upstream = write_all(server.clone(), rest)
+				.and_then({
+					let client = client.clone();
+					move |(server, _)| copy(client, server)
+				})
+				.and_then(|(_, _, server)| shutdown(server));
+			let downstream = copy(server, client)
+				.and_then(|(_, _, client)| shutdown(client));
+
+			Ok(upstream.join(downstream).then(move |result| {
+				cleanup.write().remove(&local);
+				result.map(|_| ())
+			}))
+		})
+		.flatten()
+		.map_err(move |e| debug!(target: "stratum", "Relayed connection from {:?} closed: {}", peer, e))
+}
+
+/// Reads the PROXY protocol header of a new connection.
+///
+/// Resolves to the connection, the source address and the data received after the header.
+struct ReadHeader {
+	socket: Option<TcpStream>,
+	buf: Vec<u8>,
+}
+
+impl Future for ReadHeader {
+	type Item = (TcpStream, Option<SocketAddr>, Vec<u8>);
+	type Error = io::Error;
+
+	fn poll(&mut self) -> Poll<Self::Item, io::Error> {
+		loop {
+			let header = parse_header(&self.buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+			if let Some(header) = header {
+				let socket = self.socket.take().expect("ReadHeader is not polled after completion; qed");
+				let rest = self.buf.split_off(header.length);
+				return Ok(Async::Ready((socket, header.source, rest)));
+			}
+
+			let mut chunk = [0u8; 256];
+			let socket = self.socket.as_mut().expect("ReadHeader is not polled after completion; qed");
+			match socket.poll_read(&mut chunk)? {
+				Async::Ready(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+				Async::Ready(read) => self.buf.extend_from_slice(&chunk[..read]),
+				Async::NotReady => return Ok(Async::NotReady),
+			}
+		}
+	}
+}
+
+/// `TcpStream` shared by both directions of the relay, which half-closes the connection on shutdown.
+#[derive(Clone)]
+pub struct SharedStream(pub Arc<TcpStream>);
+
+impl Read for SharedStream {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		(&*self.0).read(buf)
+	}
+}
+
+impl Write for SharedStream {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		(&*self.0).write(buf)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+impl AsyncRead for SharedStream {}
+
+impl AsyncWrite for SharedStream {
+	fn shutdown(&mut self) -> Poll<(), io::Error> {
+		self.0.shutdown(Shutdown::Write)?;
+		Ok(Async::Ready(()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{parse_header, Header};
+
+	#[test]
+	fn parses_v1_header() {
+		let buf = b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 8008\r\n{\"id\":1}";
+		assert_eq!(parse_header(buf), Ok(Some(Header {
+			source: Some("192.168.0.1:56324".parse().unwrap()),
+			length: 48,
+		})));
+
+		let buf = b"PROXY TCP6 ::1 ::2 4000 8008\r\n";
+		assert_eq!(parse_header(buf).unwrap().unwrap().source, Some("[::1]:4000".parse().unwrap()));
+		assert_eq!(parse_header(b"PROXY UNKNOWN\r\n"), Ok(Some(Header { source: None, length: 15 })));
+		assert_eq!(parse_header(b"PROXY TCP4 192.168"), Ok(None));
+		assert!(parse_header(b"PROXY TCP6 192.168.0.1 192.168.0.11 56324 8008\r\n").is_err());
+		assert!(parse_header(b"{\"id\":1}\n").is_err());
+	}
+
+	#[test]
+	fn parses_v2_header() {
+		let mut buf = b"\r\n\r\n\0\r\nQUIT\n".to_vec();
+		// PROXY command, TCP over IPv4, 12 bytes of addresses
+		buf.extend_from_slice(&[0x21, 0x11, 0x00, 0x0c]);
+		assert_eq!(parse_header(&buf), Ok(None));
+
+		buf.extend_from_slice(&[10, 0, 0, 1, 10, 0, 0, 2, 0xdc, 0x04, 0x1f, 0x48]);
+		buf.extend_from_slice(b"{\"id\":1}");
+		assert_eq!(parse_header(&buf), Ok(Some(Header {
+			source: Some("10.0.0.1:56324".parse().unwrap()),
+			length: 28,
+		})));
+
+		// LOCAL command
+		buf[12] = 0x20;
+		assert_eq!(parse_header(&buf).unwrap().unwrap().source, None);
+
+		// version 1 is not binary
+		buf[12] = 0x11;
+		assert!(parse_header(&buf).is_err());
+	}
+}
@@ -0,0 +1,241 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Share statistics of the authorized workers.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant, SystemTime};
+
+use parking_lot::RwLock;
+
+/// Average number of hashes computed to find a share of difficulty 1.
+pub const HASHES_PER_DIFFICULTY: f64 = 4_294_967_296.0;
+/// Shares accepted within this window are used to estimate the hashrate of a worker.
+const HASHRATE_WINDOW: Duration = Duration::from_secs(600);
+
+/// Statistics of an authorized worker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkerStats {
+	/// Worker id given in `mining.authorize`
+	pub worker_id: String,
+	/// Address of the worker, the source address for relayed connections
+	pub addr: SocketAddr,
+	/// Shares accepted by the job dispatcher
+	pub accepted_shares: u64,
+	/// Shares rejected by the job dispatcher
+	pub rejected_shares: u64,
+	/// Time of the last submitted share
+	pub last_share: Option<SystemTime>,
+	/// Hashes per second estimated from the accepted shares, `None` if their difficulty is unknown
+	pub hashrate: Option<f64>,
+}
+
+struct WorkerState {
+	stats: WorkerStats,
+	/// Time the worker was authorized
+	since: Instant,
+	/// Time and difficulty of the shares accepted within the hashrate window
+	recent: VecDeque<(Instant, f64)>,
+	/// Whether the difficulty of some accepted share was unknown
+	unknown_difficulty: bool,
+}
+
+/// Statistics of the authorized workers, keyed by their connection.
+#[derive(Default)]
+pub struct Stats {
+	workers: RwLock<HashMap<SocketAddr, WorkerState>>,
+}
+
+impl Stats {
+	/// Starts tracking a newly authorized worker, `addr` is the source address of the connection `peer`.
+	/// A worker authorizing again on the same connection keeps its counters.
+	pub fn register(&self, peer: SocketAddr, addr: SocketAddr, worker_id: String, now: Instant) {
+		let mut workers = self.workers.write();
+		if let Some(state) = workers.get_mut(&peer) {
+			state.stats.worker_id = worker_id;
+			state.stats.addr = addr;
+			return;
+		}
+
+		workers.insert(peer, WorkerState {
+			stats: WorkerStats {
+				worker_id,
+				addr,
+				accepted_shares: 0,
+				rejected_shares: 0,
+				last_share: None,
+				hashrate: None,
+			},
+			since: now,
+			recent: VecDeque::new(),
+			unknown_difficulty: false,
+		});
+	}
+
+	/// Stops tracking a disconnected worker.
+	pub fn remove(&self, peer: &SocketAddr) {
+		self.workers.write().remove(peer);
+	}
+
+	/// Counts a share submitted by a worker, `difficulty` is the share difficulty if known.
+	pub fn record_share(&self, peer: &SocketAddr, accepted: bool, difficulty: Option<f64>, now: Instant) {
+		let mut workers = self.workers.write();
+		let state = match workers.get_mut(peer) {
+			Some(state) => state,
+			None => return,
+		};
+
+		state.stats.last_share = Some(SystemTime::now());
+		if !accepted {
+			state.stats.rejected_shares += 1;
+			return;
+		}
+
+		state.stats.accepted_shares += 1;
+		match difficulty {
+			Some(difficulty) => state.recent.push_back((now, difficulty)),
+			None => state.unknown_difficulty = true,
+		}
+		trim_recent(state, now);
+	}
+
+	/// Statistics of all authorized workers.
+	pub fn workers(&self, now: Instant) -> Vec<WorkerStats> {
+		let mut workers = self.workers.write();
+		workers.values_mut().map(|state| {
+			trim_recent(state, now);
+			let mut stats = state.stats.clone();
+			stats.hashrate = hashrate(state, now);
+			stats
+		}).collect()
+	}
+}
+
+/// Drops the shares which left the hashrate window.
+fn trim_recent(state: &mut WorkerState, now: Instant) {
+	while state.recent.front().map_or(false, |&(time, _)| time + HASHRATE_WINDOW < now) {
+		state.recent.pop_front();
+	}
+}
+
+fn hashrate(state: &WorkerState, now: Instant) -> Option<f64> {
+	if state.unknown_difficulty && state.recent.is_empty() {
+		return None;
+	}
+
+	let elapsed = if now > state.since { now - state.since } else { Duration::from_secs(0) };
+	let elapsed = elapsed.min(HASHRATE_WINDOW);
+	let secs = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9;
+	if secs == 0.0 {
+		return Some(0.0);
+	}
+
+	let difficulty: f64 = state.recent.iter().map(|&(_, difficulty)| difficulty).sum();
+	Some(difficulty * HASHES_PER_DIFFICULTY / secs)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+	use super::{Stats, HASHES_PER_DIFFICULTY, HASHRATE_WINDOW};
+
+	#[test]
+	fn counts_shares() {
+		let stats = Stats::default();
+		let peer = "127.0.0.1:30000".parse().unwrap();
+		let source = "10.0.0.1:56324".parse().unwrap();
+		let now = Instant::now();
+		stats.register(peer, source, "miner1".into(), now);
+
+		stats.record_share(&peer, true, Some(1.0), now);
+		stats.record_share(&peer, true, Some(2.0), now);
+		stats.record_share(&peer, false, Some(2.0), now);
+		stats.record_share(&"127.0.0.1:30001".parse().unwrap(), true, Some(1.0), now);
+
+		let workers = stats.workers(now + Duration::from_secs(60));
+		assert_eq!(workers.len(), 1);
+		assert_eq!(workers[0].worker_id, "miner1");
+		assert_eq!(workers[0].addr, source);
+		assert_eq!(workers[0].accepted_shares, 2);
+		assert_eq!(workers[0].rejected_shares, 1);
+		assert!(workers[0].last_share.is_some());
+		assert_eq!(workers[0].hashrate, Some(3.0 * HASHES_PER_DIFFICULTY / 60.0));
+
+		stats.remove(&peer);
+		assert!(stats.workers(now).is_empty());
+	}
+
+	#[test]
+	fn keeps_counters_when_authorizing_again() {
+		let stats = Stats::default();
+		let peer = "127.0.0.1:30000".parse().unwrap();
+		let now = Instant::now();
+		stats.register(peer, peer, "miner1".into(), now);
+		stats.record_share(&peer, true, Some(1.0), now);
+		stats.record_share(&peer, false, Some(1.0), now);
+
+		stats.register(peer, peer, "miner2".into(), now + Duration::from_secs(30));
+		let workers = stats.workers(now + Duration::from_secs(60));
+		assert_eq!(workers[0].worker_id, "miner2");
+		assert_eq!(workers[0].accepted_shares, 1);
+		assert_eq!(workers[0].rejected_shares, 1);
+		assert_eq!(workers[0].hashrate, Some(HASHES_PER_DIFFICULTY / 60.0));
+	}
+
+	#[test]
+	fn estimates_hashrate_over_window() {
+		let stats = Stats::default();
+		let peer = "127.0.0.1:30000".parse().unwrap();
+		let now = Instant::now();
+		stats.register(peer, peer, "miner1".into(), now);
+
+		stats.record_share(&peer, true, Some(4.0), now + Duration::from_secs(10));
+		stats.record_share(&peer, true, Some(1.0), now + HASHRATE_WINDOW);
+
+		// the first share left the window
+		let later = now + HASHRATE_WINDOW + Duration::from_secs(20);
+		let window = HASHRATE_WINDOW.as_secs() as f64;
+		assert_eq!(stats.workers(later)[0].hashrate, Some(HASHES_PER_DIFFICULTY / window));
+	}
+
+	#[test]
+	fn drops_old_shares_when_recording() {
+		let stats = Stats::default();
+		let peer = "127.0.0.1:30000".parse().unwrap();
+		let now = Instant::now();
+		stats.register(peer, peer, "miner1".into(), now);
+
+		for i in 0..100 {
+			stats.record_share(&peer, true, Some(1.0), now + Duration::from_secs(i * 60));
+		}
+		// only the shares of the last window are kept, even if nobody asks for the statistics
+		let recent = stats.workers.read()[&peer].recent.len() as u64;
+		assert_eq!(recent, HASHRATE_WINDOW.as_secs() / 60 + 1);
+	}
+
+	#[test]
+	fn unknown_difficulty_has_no_hashrate() {
+		let stats = Stats::default();
+		let peer = "127.0.0.1:30000".parse().unwrap();
+		let now = Instant::now();
+		stats.register(peer, peer, "miner1".into(), now);
+		assert_eq!(stats.workers(now + Duration::from_secs(1))[0].hashrate, Some(0.0));
+
+		stats.record_share(&peer, true, None, now);
+		assert_eq!(stats.workers(now + Duration::from_secs(1))[0].hashrate, None);
+	}
+}
@@ -0,0 +1,169 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! TLS encrypted stratum connections.
+//!
+//! Like the PROXY protocol relay, the TLS listener is a relay in front of the stratum server:
+//! it terminates TLS, forwards the decrypted stream to the stratum server and records the
+//! address of the worker, keyed by the address the stratum server sees as the peer.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parity_runtime::Runtime;
+use parity_runtime::futures::{Future, Stream};
+use parity_runtime::tokio::io::{copy, shutdown, AsyncRead};
+use parity_runtime::tokio::net::{TcpListener, TcpStream};
+use parity_runtime::tokio::timer::Timeout;
+use rustls_pem::{read_certificates, read_private_key};
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::{NoClientAuth, ServerConfig};
+
+use proxy_protocol::{SharedStream, SourceAddresses};
+use traits::Error;
+
+/// Time a worker has to complete the TLS handshake before it is disconnected.
+#[cfg(not(test))]
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+#[cfg(test)]
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// TLS listener configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsConfig {
+	/// Address of the TLS listener, it runs next to the plaintext one
+	pub addr: SocketAddr,
+	/// PEM file with the certificate chain presented to workers
+	pub certificate_path: String,
+	/// PEM file with the private key of the certificate (PKCS8 or RSA)
+	pub private_key_path: String,
+}
+
+/// Accepts TLS connections and relays them to the stratum server.
+pub struct Relay {
+	_runtime: Runtime,
+}
+
+impl Relay {
+	/// Start listening on the configured address, forwarding connections to `backend`.
+	pub fn start(config: &TlsConfig, backend: SocketAddr, sources: SourceAddresses) -> Result<Self, Error> {
+		let acceptor = acceptor(config)?;
+		let listener = TcpListener::bind(&config.addr)?;
+		let runtime = Runtime::with_thread_count(1);
+		let executor = runtime.executor();
+
+		runtime.executor().spawn(listener.incoming()
+			.then(|client| Ok::<_, ()>(client.map_err(|e| warn!(target: "stratum", "Unable to accept TLS connection: {}", e)).ok()))
+			.filter_map(|client| client)
+			.for_each(move |client| {
+				executor.spawn(relay(acceptor.clone(), client, backend, sources.clone()));
+				Ok(())
+			})
+		);
+
+		Ok(Relay { _runtime: runtime })
+	}
+}
+
+/// Address the relay can connect to when the stratum server listens on `addr`.
+pub fn connectable_address(addr: &SocketAddr) -> SocketAddr {
+	match addr.ip() {
+		IpAddr::V4(ip) if ip.is_unspecified() => SocketAddr::new(Ipv4Addr::LOCALHOST.into(), addr.port()),
+		IpAddr::V6(ip) if ip.is_unspecified() => SocketAddr::new(Ipv6Addr::LOCALHOST.into(), addr.port()),
+		_ => *addr,
+	}
+}
+
+fn acceptor(config: &TlsConfig) -> Result<TlsAcceptor, Error> {
+	let certificates = read_certificates(&config.certificate_path).map_err(|e| Error::Tls(e.to_string()))?;
+	let private_key = read_private_key(&config.private_key_path).map_err(|e| Error::Tls(e.to_string()))?;
+
+	let mut server_config = ServerConfig::new(NoClientAuth::new());
+	server_config.set_single_cert(certificates, private_key)
+		.map_err(|e| Error::Tls(format!("Invalid TLS certificate or private key: {}", e)))?;
+
+	Ok(Arc::new(server_config).into())
+}
+
+fn relay(acceptor: TlsAcceptor, client: TcpStream, backend: SocketAddr, sources: SourceAddresses) -> impl Future<Item = (), Error = ()> {
+	let peer = client.peer_addr().ok();
+	let cleanup = sources.clone();
+
+	// a worker stalling the handshake would otherwise hold its connection open forever
+	Timeout::new(acceptor.accept(client), HANDSHAKE_TIMEOUT)
+		.map_err(|e| e.into_inner().unwrap_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "TLS handshake timed out")))
+		.and_then(move |client| {
+			TcpStream::connect(&backend).map(move |server| (client, server))
+		})
+		.and_then(move |(client, server)| {
+			let local = server.local_addr()?;
+			trace!(target: "stratum", "Relaying TLS connection from {:?}", peer);
+			if let Some(peer) = peer {
+				sources.write().insert(local, peer);
+			}
+
+			let (client_reader, client_writer) = client.split();
+			let server = SharedStream(Arc::new(server));
+			let upstream = copy(client_reader, server.clone())
+				.and_then(|(_, _, server)| shutdown(server));
+			let downstream = copy(server, client_writer)
+				.and_then(|(_, _, client)| shutdown(client));
+
+			Ok(upstream.join(downstream).then(move |result| {
+				cleanup.write().remove(&local);
+				result.map(|_| ())
+			}))
+		})
+		.flatten()
+		.map_err(move |e| debug!(target: "stratum", "TLS connection from {:?} closed: {}", peer, e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{acceptor, connectable_address, TlsConfig};
+	use traits::Error;
+
+	fn config(certificate: &str, key: &str) -> TlsConfig {
+		TlsConfig {
+			addr: "127.0.0.1:0".parse().unwrap(),
+			certificate_path: format!("{}/res/tls/{}", env!("CARGO_MANIFEST_DIR"), certificate),
+			private_key_path: format!("{}/res/tls/{}", env!("CARGO_MANIFEST_DIR"), key),
+		}
+	}
+
+	#[test]
+	fn loads_certificate_and_key() {
+		assert!(acceptor(&config("cert.pem", "key.pem")).is_ok());
+
+		match acceptor(&config("missing.pem", "key.pem")) {
+			Err(Error::Tls(_)) => {},
+			_ => panic!("missing certificate should be rejected"),
+		}
+		match acceptor(&config("cert.pem", "cert.pem")) {
+			Err(Error::Tls(_)) => {},
+			_ => panic!("certificate is not a private key"),
+		}
+	}
+
+	#[test]
+	fn connects_to_loopback_for_unspecified_address() {
+		assert_eq!(connectable_address(&"0.0.0.0:8008".parse().unwrap()), "127.0.0.1:8008".parse().unwrap());
+		assert_eq!(connectable_address(&"[::]:8008".parse().unwrap()), "[::1]:8008".parse().unwrap());
+		assert_eq!(connectable_address(&"10.0.0.1:8008".parse().unwrap()), "10.0.0.1:8008".parse().unwrap());
+	}
+}
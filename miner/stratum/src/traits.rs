@@ -15,7 +15,9 @@
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use ethereum_types::H256;
+use jsonrpc_core::Value;
 use jsonrpc_tcp_server::PushMessageError;
+use vardiff::VardiffConfig;
 
 #[derive(Debug, Clone)]
 pub enum Error {
@@ -24,6 +26,7 @@ pub enum Error {
 	Io(String),
 	Tcp(String),
 	Dispatch(String),
+	Tls(String),
 }
 
 impl From<std::io::Error> for Error {
@@ -38,6 +41,17 @@ impl From<PushMessageError> for Error {
 	}
 }
 
+/// Job pushed to the workers
+#[derive(Debug, Clone, PartialEq)]
+pub struct Job {
+	/// Id the workers submit their shares for the job with
+	pub id: String,
+	/// Json of the pow-specific work specification
+	pub payload: String,
+	/// Whether the previous jobs are stale, e.g. because the job builds on a new block
+	pub clean_jobs: bool,
+}
+
 /// Interface that can provide pow/blockchain-specific responses for the clients
 pub trait JobDispatcher: Send + Sync {
 	// json for initial client handshake
@@ -45,15 +59,45 @@ pub trait JobDispatcher: Send + Sync {
 	// json for difficulty dispatch
 	fn difficulty(&self) -> Option<String> { None }
 	// json for job update given worker_id (payload manager should split job!)
-	fn job(&self) -> Option<String> { None }
+	fn job(&self) -> Option<Job> { None }
 	// miner job result
 	fn submit(&self, payload: Vec<String>) -> Result<(), Error>;
+	// miner job result of a worker with its own share difficulty, solutions meeting only that difficulty are valid shares
+	fn submit_share(&self, payload: Vec<String>, _difficulty: f64) -> Result<(), Error> { self.submit(payload) }
+	// difficulty of the shares of workers without their own difficulty, used to estimate their hashrate
+	fn share_difficulty(&self) -> Option<f64> { None }
+}
+
+/// Formats the jobs pushed to the workers speaking one stratum dialect
+pub trait JobFormatter: Send + Sync {
+	/// `mining.notify` message with request `id` for `job`
+	fn notify(&self, id: u32, job: &Job) -> String;
+
+	/// `mining.set_difficulty` notification announcing the share `difficulty` of the worker, `None` if the dialect has none
+	fn set_difficulty(&self, _difficulty: f64) -> Option<String> { None }
+
+	/// Number of leading nonce bytes assigned to each connection (extranonce), the dialect uses none if 0
+	fn extranonce_size(&self) -> usize { 0 }
+
+	/// `mining.subscribe` result for a connection assigned `extranonce`, the dispatcher's initial payload is returned if `None`
+	fn subscribe_result(&self, _session_id: &str, _extranonce: &str) -> Option<Value> { None }
+
+	/// Dispatcher payload of the `mining.submit` `params`, by default all but the leading worker and job ids
+	fn submit_payload(&self, _extranonce: &str, params: &[Value]) -> Vec<String> {
+		params.iter()
+			.skip(2)
+			.filter_map(|val| match *val {
+				Value::String(ref s) => Some(s.to_owned()),
+				_ => None
+			})
+			.collect()
+	}
 }
 
 /// Interface that can handle requests to push job for workers
 pub trait PushWorkHandler: Send + Sync {
-	/// push the same work package for all workers (`payload`: json of pow-specific set of work specification)
-	fn push_work_all(&self, payload: String);
+	/// push the same job for all workers, shares for stale jobs are rejected from now on
+	fn push_work_all(&self, job: Job);
 }
 
 pub struct ServiceConfiguration {
@@ -61,4 +105,6 @@ pub struct ServiceConfiguration {
 	pub listen_addr: String,
 	pub port: u16,
 	pub secret: Option<H256>,
+	/// Per-worker difficulty adjustment, all workers get the same difficulty if `None`
+	pub vardiff: Option<VardiffConfig>,
 }
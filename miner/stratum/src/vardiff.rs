@@ -0,0 +1,264 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-worker difficulty adjustment (vardiff).
+//!
+//! Every worker starts at the configured difficulty, which is then retargeted once per window so that
+//! the worker submits a share about every `target_share_interval`.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use parking_lot::RwLock;
+
+/// Largest factor the difficulty of a worker is changed by in one retarget.
+const MAX_ADJUSTMENT: f64 = 4.0;
+/// Retargets which change the difficulty by less than this fraction aren't announced.
+const MIN_ADJUSTMENT: f64 = 0.05;
+
+/// Configures the per-worker difficulty adjustment.
+#[derive(Debug, PartialEq, Clone)]
+pub struct VardiffConfig {
+	/// Desired time between two shares of a worker
+	pub target_share_interval: Duration,
+	/// Time the shares of a worker are counted for before its difficulty is retargeted
+	pub retarget_window: Duration,
+	/// Difficulty of newly authorized workers
+	pub initial_difficulty: f64,
+	/// Lowest difficulty a worker is retargeted to
+	pub min_difficulty: f64,
+	/// Highest difficulty a worker is retargeted to
+	pub max_difficulty: f64,
+}
+
+impl Default for VardiffConfig {
+	fn default() -> Self {
+		VardiffConfig {
+			target_share_interval: Duration::from_secs(10),
+			retarget_window: Duration::from_secs(60),
+			initial_difficulty: 1.0,
+			min_difficulty: 0.001,
+			max_difficulty: 1_000_000.0,
+		}
+	}
+}
+
+struct WorkerState {
+	/// Worker id given in `mining.authorize`
+	worker_id: String,
+	/// Current share difficulty
+	difficulty: f64,
+	/// Start of the current retarget window
+	window_start: Instant,
+	/// Shares submitted in the current retarget window
+	shares: u32,
+}
+
+/// Share rate and difficulty of the authorized workers, keyed by their connection.
+pub struct Vardiff {
+	config: VardiffConfig,
+	workers: RwLock<HashMap<SocketAddr, WorkerState>>,
+}
+
+impl Vardiff {
+	pub fn new(config: VardiffConfig) -> Self {
+		Vardiff {
+			config,
+			workers: RwLock::default(),
+		}
+	}
+
+	/// Difficulty of newly authorized workers.
+	pub fn initial_difficulty(&self) -> f64 {
+		self.clamp(self.config.initial_difficulty)
+	}
+
+	/// Starts tracking a newly authorized worker, returns its initial difficulty.
+	/// A worker authorizing again on the same connection keeps its current difficulty.
+	pub fn register(&self, addr: SocketAddr, worker_id: String, now: Instant) -> f64 {
+		let mut workers = self.workers.write();
+		if let Some(state) = workers.get_mut(&addr) {
+			state.worker_id = worker_id;
+			return state.difficulty;
+		}
+
+		let difficulty = self.initial_difficulty();
+		workers.insert(addr, WorkerState {
+			worker_id,
+			difficulty,
+			window_start: now,
+			shares: 0,
+		});
+		difficulty
+	}
+
+	/// Stops tracking a disconnected worker.
+	pub fn remove(&self, addr: &SocketAddr) {
+		self.workers.write().remove(addr);
+	}
+
+	/// Current difficulty of a worker.
+	pub fn difficulty(&self, addr: &SocketAddr) -> Option<f64> {
+		self.workers.read().get(addr).map(|state| state.difficulty)
+	}
+
+	/// Counts a share submitted by a worker.
+	pub fn record_share(&self, addr: &SocketAddr) {
+		if let Some(state) = self.workers.write().get_mut(addr) {
+			state.shares = state.shares.saturating_add(1);
+		}
+	}
+
+	/// Retargets the difficulty of a worker if its retarget window is over.
+	///
+	/// Returns the new difficulty if it should be announced to the worker.
+	pub fn retarget(&self, addr: &SocketAddr, now: Instant) -> Option<f64> {
+		let mut workers = self.workers.write();
+		let state = workers.get_mut(addr)?;
+		if now < state.window_start + self.config.retarget_window {
+			return None;
+		}
+
+		let elapsed = now - state.window_start;
+		let difficulty = self.clamp(next_difficulty(&self.config, state.difficulty, state.shares, elapsed));
+		let previous = state.difficulty;
+		state.window_start = now;
+		state.shares = 0;
+
+		if (difficulty - previous).abs() <= previous * MIN_ADJUSTMENT {
+			return None;
+		}
+		trace!(target: "stratum", "Retargeting worker #{} ({}) from difficulty {} to {}", state.worker_id, addr, previous, difficulty);
+		state.difficulty = difficulty;
+		Some(difficulty)
+	}
+
+	fn clamp(&self, difficulty: f64) -> f64 {
+		difficulty.max(self.config.min_difficulty).min(self.config.max_difficulty)
+	}
+}
+
+/// Difficulty at which `shares` submitted over `elapsed` would have arrived at the target interval.
+fn next_difficulty(config: &VardiffConfig, difficulty: f64, shares: u32, elapsed: Duration) -> f64 {
+	if shares == 0 {
+		return difficulty / MAX_ADJUSTMENT;
+	}
+
+	let target = duration_secs(config.target_share_interval);
+	let actual = duration_secs(elapsed) / f64::from(shares);
+	if actual <= 0.0 {
+		return difficulty * MAX_ADJUSTMENT;
+	}
+	let factor = (target / actual).max(1.0 / MAX_ADJUSTMENT).min(MAX_ADJUSTMENT);
+	difficulty * factor
+}
+
+fn duration_secs(duration: Duration) -> f64 {
+	duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1_000_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn vardiff() -> Vardiff {
+		Vardiff::new(VardiffConfig {
+			target_share_interval: Duration::from_secs(10),
+			retarget_window: Duration::from_secs(60),
+			initial_difficulty: 8.0,
+			min_difficulty: 1.0,
+			max_difficulty: 64.0,
+		})
+	}
+
+	#[test]
+	fn keeps_difficulty_within_window() {
+		let vardiff = vardiff();
+		let addr = "127.0.0.1:30303".parse().unwrap();
+		let start = Instant::now();
+		assert_eq!(vardiff.register(addr, "miner1".into(), start), 8.0);
+
+		for _ in 0..20 {
+			vardiff.record_share(&addr);
+		}
+		assert_eq!(vardiff.retarget(&addr, start + Duration::from_secs(30)), None);
+		assert_eq!(vardiff.difficulty(&addr), Some(8.0));
+	}
+
+	#[test]
+	fn raises_difficulty_of_fast_workers() {
+		let vardiff = vardiff();
+		let addr = "127.0.0.1:30303".parse().unwrap();
+		let start = Instant::now();
+		vardiff.register(addr, "miner1".into(), start);
+
+		// 12 shares a minute, twice the target rate
+		for _ in 0..12 {
+			vardiff.record_share(&addr);
+		}
+		assert_eq!(vardiff.retarget(&addr, start + Duration::from_secs(60)), Some(16.0));
+
+		// 600 shares a minute, capped by the maximal adjustment and difficulty
+		for _ in 0..600 {
+			vardiff.record_share(&addr);
+		}
+		assert_eq!(vardiff.retarget(&addr, start + Duration::from_secs(120)), Some(64.0));
+	}
+
+	#[test]
+	fn lowers_difficulty_of_slow_workers() {
+		let vardiff = vardiff();
+		let addr = "127.0.0.1:30303".parse().unwrap();
+		let start = Instant::now();
+		vardiff.register(addr, "miner1".into(), start);
+
+		// 3 shares a minute, half the target rate
+		for _ in 0..3 {
+			vardiff.record_share(&addr);
+		}
+		assert_eq!(vardiff.retarget(&addr, start + Duration::from_secs(60)), Some(4.0));
+
+		// no shares at all, capped by the minimal difficulty
+		assert_eq!(vardiff.retarget(&addr, start + Duration::from_secs(120)), Some(1.0));
+		assert_eq!(vardiff.retarget(&addr, start + Duration::from_secs(180)), None);
+	}
+
+	#[test]
+	fn keeps_difficulty_when_authorizing_again() {
+		let vardiff = vardiff();
+		let addr = "127.0.0.1:30303".parse().unwrap();
+		let start = Instant::now();
+		vardiff.register(addr, "miner1".into(), start);
+		for _ in 0..12 {
+			vardiff.record_share(&addr);
+		}
+		assert_eq!(vardiff.retarget(&addr, start + Duration::from_secs(60)), Some(16.0));
+
+		assert_eq!(vardiff.register(addr, "miner2".into(), start + Duration::from_secs(90)), 16.0);
+		assert_eq!(vardiff.difficulty(&addr), Some(16.0));
+	}
+
+	#[test]
+	fn forgets_removed_workers() {
+		let vardiff = vardiff();
+		let addr = "127.0.0.1:30303".parse().unwrap();
+		vardiff.register(addr, "miner1".into(), Instant::now());
+		vardiff.remove(&addr);
+		assert_eq!(vardiff.difficulty(&addr), None);
+		assert_eq!(vardiff.retarget(&addr, Instant::now() + Duration::from_secs(60)), None);
+	}
+}
@@ -100,6 +100,24 @@ impl<T> UsingQueue<T> {
 		}
 	}
 
+	/// Returns `Some` item among the `window` most recently used ones which `predicate` returns `true` for,
+	/// together with the number of items which were used after it (`0` for the most recently used one).
+	pub fn get_recent_used_if<P>(&mut self, action: GetAction, window: usize, predicate: P) -> Option<(T, usize)> where P: Fn(&T) -> bool, T: Clone {
+		let age = self.in_use.iter().rev().take(window).position(|r| predicate(r))?;
+		let index = self.in_use.len() - 1 - age;
+		let item = match action {
+			GetAction::Take => self.in_use.remove(index),
+			GetAction::Clone => self.in_use[index].clone(),
+		};
+		Some((item, age))
+	}
+
+	/// Returns a reference to the item among the `window` most recently used ones which `predicate`
+	/// returns `true` for, leaving the queue untouched.
+	pub fn peek_recent_used_if<P>(&self, window: usize, predicate: P) -> Option<&T> where P: Fn(&T) -> bool {
+		self.in_use.iter().rev().take(window).find(|r| predicate(r))
+	}
+
 	/// Returns a clone of the pending block if `f` returns `true` with a reference to it as
 	/// a parameter, otherwise `None`.
 	///
@@ -274,3 +292,19 @@ fn should_not_remove_used_popped() {
 	assert_eq!(q.get_pending_if(|i| i == &1), Some(1));
 	assert_eq!(q.get_pending_if(|i| i == &1), Some(1));
 }
+
+#[test]
+fn should_find_only_recently_used_with_age() {
+	let mut q = UsingQueue::new(3);
+	for i in 1..=3 {
+		q.set_pending(i);
+		q.use_last_ref();
+	}
+	assert_eq!(q.get_recent_used_if(GetAction::Clone, 2, |i| i == &3), Some((3, 0)));
+	assert_eq!(q.get_recent_used_if(GetAction::Clone, 2, |i| i == &2), Some((2, 1)));
+	assert_eq!(q.get_recent_used_if(GetAction::Clone, 2, |i| i == &1), None);
+	assert_eq!(q.get_recent_used_if(GetAction::Take, 3, |i| i == &1), Some((1, 2)));
+	assert_eq!(q.get_recent_used_if(GetAction::Clone, 3, |i| i == &1), None);
+	assert_eq!(q.peek_recent_used_if(2, |i| i == &2), Some(&2));
+	assert_eq!(q.peek_recent_used_if(1, |i| i == &2), None);
+}
@@ -55,6 +55,10 @@ mod accounts {
 	pub fn accounts_list(_account_provider: Arc<AccountProvider>) -> Arc<Fn() -> Vec<Address> + Send + Sync> {
 		Arc::new(|| vec![])
 	}
+
+	pub fn watch_keys(_account_provider: &Arc<AccountProvider>, _refresh_time: u64) {}
+
+	pub fn relock_expired_accounts(_account_provider: &Arc<AccountProvider>) {}
 }
 
 #[cfg(feature = "accounts")]
@@ -80,6 +84,7 @@ mod accounts {
 		let dir = Box::new(RootDiskDirectory::create(&path).map_err(|e| format!("Could not open keys directory: {}", e))?);
 		let account_settings = AccountProviderSettings {
 			unlock_keep_secret: cfg.enable_fast_unlock,
+			max_unlock_duration: cfg.max_unlock_duration.map(::std::time::Duration::from_secs),
 			blacklisted_accounts: 	match *spec {
 				SpecType::Morden | SpecType::Mordor | SpecType::Ropsten | SpecType::Kovan | SpecType::Goerli | SpecType::Kotti | SpecType::Sokol | SpecType::Dev => vec![],
 				_ => vec![
@@ -88,10 +93,8 @@ mod accounts {
 			},
 		};
 
+		// key files added by other processes are picked up by `watch_keys`
 		let ethstore = EthStore::open_with_iterations(dir, cfg.iterations).map_err(|e| format!("Could not open keys directory: {}", e))?;
-		if cfg.refresh_time > 0 {
-			ethstore.set_refresh_time(::std::time::Duration::from_secs(cfg.refresh_time));
-		}
 		let account_provider = AccountProvider::new(
 			Box::new(ethstore),
 			account_settings,
@@ -113,7 +116,7 @@ mod accounts {
 				return Err(format!("No password found to unlock account {}. {}", a, VERIFY_PASSWORD_HINT));
 			}
 
-			if !passwords.iter().any(|p| account_provider.unlock_account_permanently(a, (*p).clone()).is_ok()) {
+			if !passwords.iter().any(|p| account_provider.unlock_account_permanently(a, (*p).clone(), Some("--unlock".into())).is_ok()) {
 				return Err(format!("No valid password to unlock account {}. {}", a, VERIFY_PASSWORD_HINT));
 			}
 		}
@@ -210,6 +213,60 @@ mod accounts {
 		Arc::new(move || account_provider.accounts().unwrap_or_default())
 	}
 
+	/// Re-read the keys directory every `refresh_time` seconds, so that key files added or removed
+	/// by other processes are picked up without a restart. Stops with the account provider.
+	pub fn watch_keys(account_provider: &Arc<AccountProvider>, refresh_time: u64) {
+		use std::{thread, time::Duration};
+		use accounts::AccountEvent;
+
+		if refresh_time == 0 {
+			return;
+		}
+
+		let account_provider = Arc::downgrade(account_provider);
+		let interval = Duration::from_secs(refresh_time);
+		let watcher = thread::Builder::new().name("keys-watcher".into()).spawn(move || loop {
+			thread::sleep(interval);
+			let account_provider = match account_provider.upgrade() {
+				Some(account_provider) => account_provider,
+				None => break,
+			};
+
+			match account_provider.refresh() {
+				Ok(events) => for event in events {
+					match event {
+						AccountEvent::Added(address) => info!("Account {:?} added to the keys directory", address),
+						AccountEvent::Removed(address) => info!("Account {:?} removed from the keys directory", address),
+					}
+				},
+				Err(e) => warn!("Unable to read the keys directory: {}", e),
+			}
+		});
+
+		if let Err(e) = watcher {
+			warn!("Unable to start watching the keys directory: {}", e);
+		}
+	}
+
+	/// Lock accounts as soon as their timed unlock expires, so that the relock shows up
+	/// in the audit log when it happens. Stops with the account provider.
+	pub fn relock_expired_accounts(account_provider: &Arc<AccountProvider>) {
+		use std::{thread, time::Duration};
+
+		let account_provider = Arc::downgrade(account_provider);
+		let relocker = thread::Builder::new().name("accounts-relock".into()).spawn(move || loop {
+			thread::sleep(Duration::from_secs(1));
+			match account_provider.upgrade() {
+				Some(account_provider) => account_provider.relock_expired(),
+				None => break,
+			}
+		});
+
+		if let Err(e) = relocker {
+			warn!("Unable to start relocking expired accounts: {}", e);
+		}
+	}
+
 	fn insert_dev_account(account_provider: &AccountProvider) {
 		let secret = parity_crypto::publickey::Secret::from_str("4d5db4107d237df6a3d58ee5f70ae63d73d7658d4026f2eefd2f204c81682cb7".into()).expect("Valid account;qed");
 		let dev_account = parity_crypto::publickey::KeyPair::from_secret(secret.clone()).expect("Valid secret produces valid key;qed");
@@ -240,4 +297,6 @@ pub use self::accounts::{
 	miner_author,
 	private_tx_signer,
 	accounts_list,
+	watch_keys,
+	relock_expired_accounts,
 };
@@ -17,6 +17,7 @@
 use std::str::from_utf8;
 use std::{io, fs};
 use std::io::{BufReader, BufRead};
+use std::path::Path;
 use std::time::{Instant, Duration};
 use std::thread::sleep;
 use std::sync::Arc;
@@ -40,6 +41,7 @@ use dir::Directories;
 use user_defaults::UserDefaults;
 use ethcore_private_tx;
 use db;
+use local_store;
 use ansi_term::Colour;
 use types::{
 	ids::BlockId,
@@ -55,7 +57,9 @@ pub enum BlockchainCmd {
 	Import(ImportBlockchain),
 	Export(ExportBlockchain),
 	ExportState(ExportState),
-	Reset(ResetBlockchain)
+	Reset(ResetBlockchain),
+	ExportLocalStore(LocalStoreFile),
+	ImportLocalStore(LocalStoreFile),
 }
 
 #[derive(Debug, PartialEq)]
@@ -79,6 +83,15 @@ pub struct KillBlockchain {
 	pub pruning: Pruning,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct LocalStoreFile {
+	pub spec: SpecType,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub compaction: DatabaseCompactionProfile,
+	pub file_path: String,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct ImportBlockchain {
 	pub spec: SpecType,
@@ -152,6 +165,8 @@ pub fn execute(cmd: BlockchainCmd) -> Result<(), String> {
 		BlockchainCmd::Export(export_cmd) => execute_export(export_cmd),
 		BlockchainCmd::ExportState(export_cmd) => execute_export_state(export_cmd),
 		BlockchainCmd::Reset(reset_cmd) => execute_reset(reset_cmd),
+		BlockchainCmd::ExportLocalStore(cmd) => execute_export_local_store(cmd),
+		BlockchainCmd::ImportLocalStore(cmd) => execute_import_local_store(cmd),
 	}
 }
 
@@ -199,6 +214,7 @@ fn execute_import_light(cmd: ImportBlockchain) -> Result<(), String> {
 		verify_full: true,
 		check_seal: cmd.check_seal,
 		no_hardcoded_sync: true,
+		header_history: light::client::DEFAULT_HEADER_HISTORY,
 	};
 
 	config.queue.max_mem_use = cmd.cache_config.queue() as usize * 1024 * 1024;
@@ -674,6 +690,38 @@ pub fn kill_db(cmd: KillBlockchain) -> Result<(), String> {
 	Ok(())
 }
 
+fn open_local_store(cmd: &LocalStoreFile) -> Result<local_store::LocalDataStore<local_store::NoNode>, String> {
+	let spec = cmd.spec.spec(&cmd.dirs.cache)?;
+	let genesis_hash = spec.genesis_header().hash();
+	let db_dirs = cmd.dirs.database(genesis_hash, None, spec.data_dir);
+	let user_defaults = UserDefaults::load(&db_dirs.user_defaults_path())?;
+	let algorithm = cmd.pruning.to_algorithm(&user_defaults);
+	let client_path = db_dirs.client_path(algorithm);
+	if !client_path.exists() {
+		return Err(format!("Database {} does not exist", client_path.display()));
+	}
+
+	let db = db::open_db_offline(&client_path.to_string_lossy(), &cmd.compaction)
+		.map_err(|e| format!("Failed to open database: {}", e))?;
+	Ok(local_store::open(db.key_value().clone(), ::ethcore_db::COL_NODE_INFO))
+}
+
+fn execute_export_local_store(cmd: LocalStoreFile) -> Result<(), String> {
+	let store = open_local_store(&cmd)?;
+	let count = store.export_to(Path::new(&cmd.file_path))
+		.map_err(|e| format!("Unable to export local data: {}", e))?;
+	info!("Exported {} local transactions to {}", count, cmd.file_path);
+	Ok(())
+}
+
+fn execute_import_local_store(cmd: LocalStoreFile) -> Result<(), String> {
+	let store = open_local_store(&cmd)?;
+	let count = store.import_from(Path::new(&cmd.file_path))
+		.map_err(|e| format!("Unable to import local data: {}", e))?;
+	info!("Imported {} local transactions from {}", count, cmd.file_path);
+	Ok(())
+}
+
 #[cfg(test)]
 mod test {
 	use super::DataFormat;
@@ -211,6 +211,11 @@ usage! {
 				"<FILE>",
 				"File",
 			}
+
+			CMD cmd_tools_rotate_node_key
+			{
+				"Replace the devp2p node key with a new random key, encrypted if --node-key-password is given. Takes effect on the next start.",
+			}
 		}
 
 		CMD cmd_db
@@ -229,6 +234,22 @@ usage! {
 				"Number of blocks to revert",
 			}
 
+			CMD cmd_db_export_local_store {
+				"Export the persisted local data (e.g. pending local transactions) of the given --chain (default: mainnet) into a portable file",
+
+				ARG arg_db_export_local_store_file: (Option<String>) = None,
+				"<FILE>",
+				"Path to the exported file",
+			}
+
+			CMD cmd_db_import_local_store {
+				"Import local data exported with export-local-store into the database of the given --chain (default: mainnet). The node must not be running.",
+
+				ARG arg_db_import_local_store_file: (Option<String>) = None,
+				"<FILE>",
+				"Path to the file to import",
+			}
+
 		}
 
 		CMD cmd_export_hardcoded_sync
@@ -342,7 +363,7 @@ usage! {
 
 			ARG arg_accounts_refresh: (u64) = 5u64, or |c: &Config| c.account.as_ref()?.refresh_time.clone(),
 			"--accounts-refresh=[TIME]",
-			"Specify the cache time of accounts read from disk. If you manage thousands of accounts set this to 0 to disable refresh.",
+			"Specify how often, in seconds, the keys directory is checked for key files added or removed by other processes. If you manage thousands of accounts set this to 0 to disable refresh.",
 
 			ARG arg_unlock: (Option<String>) = None, or |c: &Config| c.account.as_ref()?.unlock.as_ref().map(|vec| vec.join(",")),
 			"--unlock=[ACCOUNTS]",
@@ -356,6 +377,10 @@ usage! {
 			"--password=[FILE]...",
 			"Provide a file containing a password for unlocking an account. Leading and trailing whitespace is trimmed.",
 
+			ARG arg_max_unlock_duration: (Option<u64>) = None, or |c: &Config| c.account.as_ref()?.max_unlock_duration,
+			"--max-unlock-duration=[SECONDS]",
+			"Maximum duration of account unlocks requested via RPC. When set, accounts can't be unlocked permanently via RPC.",
+
 		["Private Transactions Options"]
 			FLAG flag_private_enabled: (bool) = false, or |c: &Config| c.private_tx.as_ref()?.enabled,
 			"--private-tx-enabled",
@@ -394,6 +419,14 @@ usage! {
 			"--ui-path=[PATH]",
 			"Specify directory where Trusted UIs tokens should be stored.",
 
+			ARG arg_signer_webhooks: (Option<String>) = None, or |c: &Config| c.ui.as_ref()?.webhooks.as_ref().map(|vec| vec.join(",")),
+			"--signer-webhooks=[URLS]",
+			"Comma-separated list of URLs notified about requests awaiting confirmation. Requires --signer-webhook-secret.",
+
+			ARG arg_signer_webhook_secret: (Option<String>) = None, or |c: &Config| c.ui.as_ref()?.webhook_secret.clone(),
+			"--signer-webhook-secret=[FILE]",
+			"Provide a file containing the secret used to sign webhook notifications and to authenticate confirmation callbacks.",
+
 		["Networking Options"]
 			FLAG flag_no_warp: (bool) = false, or |c: &Config| c.network.as_ref()?.warp.clone().map(|w| !w),
 			"--no-warp",
@@ -451,6 +484,34 @@ usage! {
 			"--max-pending-peers=[NUM]",
 			"Allow up to NUM pending connections.",
 
+			ARG arg_max_session_age: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.max_session_age.clone(),
+			"--max-session-age=[SECS]",
+			"Gracefully reconnect peers after their session is older than SECS seconds, renewing the session encryption keys. Reconnections are staggered and postponed while below --min-peers.",
+
+			ARG arg_max_dials_per_sec: (u32) = 0u32, or |c: &Config| c.network.as_ref()?.max_dials_per_sec.clone(),
+			"--max-dials-per-sec=[NUM]",
+			"Make at most NUM outbound connection attempts per second. 0 means unlimited.",
+
+			ARG arg_max_peers_per_subnet: (u32) = 0u32, or |c: &Config| c.network.as_ref()?.max_peers_per_subnet.clone(),
+			"--max-peers-per-subnet=[NUM]",
+			"Dial at most NUM peers within a single /24 IPv4 (/64 IPv6) subnet. Reserved peers are exempt. 0 means unlimited.",
+
+			ARG arg_max_peers_per_id_bucket: (u32) = 0u32, or |c: &Config| c.network.as_ref()?.max_peers_per_id_bucket.clone(),
+			"--max-peers-per-id-bucket=[NUM]",
+			"Dial at most NUM peers sharing the first byte of their node id. Reserved peers are exempt. 0 means unlimited.",
+
+			ARG arg_reserved_dial_weight: (u8) = 100u8, or |c: &Config| c.network.as_ref()?.reserved_dial_weight.clone(),
+			"--reserved-dial-weight=[PERCENT]",
+			"Give PERCENT of outbound connection attempts to reserved peers and the rest to discovered peers.",
+
+			ARG arg_max_upload_bandwidth: (u64) = 0u64, or |c: &Config| c.network.as_ref()?.max_upload_bandwidth.clone(),
+			"--max-upload-bandwidth=[KBPS]",
+			"Send at most KBPS kilobytes per second to peers, shared between block sync, transaction propagation and light client serving. 0 means unlimited.",
+
+			ARG arg_bandwidth_weights: (String) = "4,2,1", or |c: &Config| c.network.as_ref()?.bandwidth_weights.clone(),
+			"--bandwidth-weights=[SYNC,TXS,LIGHT]",
+			"Relative shares of --max-upload-bandwidth given to block sync, transaction propagation and light client serving. Bandwidth left unused by one is lent to the others.",
+
 			ARG arg_network_id: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.id.clone(),
 			"--network-id=[INDEX]",
 			"Override the network identifier from the chain we are on.",
@@ -463,6 +524,10 @@ usage! {
 			"--node-key=[KEY]",
 			"Specify node secret key, either as 64-character hex string or input to SHA3 operation.",
 
+			ARG arg_node_key_password: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.node_key_password.clone(),
+			"--node-key-password=[FILE]",
+			"Store the node key encrypted, like account keys, with the password in FILE. An existing plain node key is encrypted on start.",
+
 			ARG arg_reserved_peers: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.reserved_peers.clone(),
 			"--reserved-peers=[FILE]",
 			"Provide a file containing enodes, one per line. These nodes will always have a reserved slot on top of the normal maximum peers.",
@@ -530,6 +595,34 @@ usage! {
 			"--poll-lifetime=[S]",
 			"Set the RPC filter lifetime to S seconds. The filter has to be polled at least every S seconds , otherwise it is removed.",
 
+			ARG arg_jsonrpc_slow_query_threshold: (Option<u64>) = None, or |c: &Config| c.rpc.as_ref()?.slow_query_threshold,
+			"--jsonrpc-slow-query-threshold=[MS]",
+			"Log RPC requests taking longer than MS milliseconds together with their method, params size and timing breakdown.",
+
+			ARG arg_jsonrpc_max_batch_size: (Option<usize>) = None, or |c: &Config| c.rpc.as_ref()?.max_batch_size,
+			"--jsonrpc-max-batch-size=[N]",
+			"Reject JSON-RPC batch requests with more than N calls, none of their calls is executed.",
+
+			ARG arg_jsonrpc_batch_time_budget: (Option<u64>) = None, or |c: &Config| c.rpc.as_ref()?.batch_time_budget,
+			"--jsonrpc-batch-time-budget=[MS]",
+			"Do not execute the remaining calls of a JSON-RPC batch request once it has been running for MS milliseconds.",
+
+			FLAG flag_jsonrpc_batch_abort_on_error: (bool) = false, or |c: &Config| c.rpc.as_ref()?.batch_abort_on_error,
+			"--jsonrpc-batch-abort-on-error",
+			"Do not execute the remaining calls of a JSON-RPC batch request after a call fails with an internal error.",
+
+			ARG arg_jsonrpc_latest_confirmations: (u64) = 0u64, or |c: &Config| c.rpc.as_ref()?.latest_confirmations,
+			"--jsonrpc-latest-confirmations=[N]",
+			"Make eth_ read calls treat 'latest' as the block N confirmations below the chain head. A single call can pick its own depth by passing {\"confirmations\": \"0x..\"} as the block.",
+
+			ARG arg_jsonrpc_page_size: (usize) = 10000usize, or |c: &Config| c.rpc.as_ref()?.page_size,
+			"--jsonrpc-page-size=[ITEMS]",
+			"Maximal number of results returned in a page by eth_getLogsPage and trace_filterPage. Larger results are continued from the returned cursor.",
+
+			FLAG flag_jsonrpc_deprecation_warnings: (bool) = false, or |c: &Config| c.rpc.as_ref()?.deprecation_warnings,
+			"--jsonrpc-deprecation-warnings",
+			"Add a warning to the errors returned by deprecated RPC methods. Usage of every method is reported by parity_rpcStats.",
+
 		["API and Console Options – WebSockets"]
 			FLAG flag_no_ws: (bool) = false, or |c: &Config| c.websockets.as_ref()?.disable.clone(),
 			"--no-ws",
@@ -559,6 +652,10 @@ usage! {
 			"--ws-max-connections=[CONN]",
 			"Maximum number of allowed concurrent WebSockets JSON-RPC connections.",
 
+			ARG arg_ws_max_payload: (Option<usize>) = None, or |c: &Config| c.websockets.as_ref()?.max_payload,
+			"--ws-max-payload=[MB]",
+			"Specify maximum size for WebSockets JSON-RPC messages in megabytes.",
+
 		["API and Console Options – IPC"]
 			FLAG flag_no_ipc: (bool) = false, or |c: &Config| c.ipc.as_ref()?.disable.clone(),
 			"--no-ipc",
@@ -618,6 +715,10 @@ usage! {
 			"--on-demand-consecutive-failures=[TIMES]",
 			"Specify light client the number of failures for a request until it gets exponentially backed off",
 
+			ARG arg_light_header_history: (u64) = 2048u64, or |c: &Config| c.light.as_ref()?.header_history,
+			"--light-header-history=[BLOCKS]",
+			"Specify the number of recent headers kept by the light client. Older headers are dropped once their Canonical Hash Trie has been generated.",
+
 		["Secret Store Options"]
 			FLAG flag_no_secretstore: (bool) = false, or |c: &Config| c.secretstore.as_ref()?.disable.clone(),
 			"--no-secretstore",
@@ -695,6 +796,26 @@ usage! {
 			"--secretstore-admin=[PUBLIC]",
 			"Hex-encoded public key of secret store administrator.",
 
+			ARG arg_secretstore_tls_cert: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.tls_cert.clone(),
+			"--secretstore-tls-cert=[FILE]",
+			"Path to PEM-encoded certificate chain of this key server. Enables mutual TLS between key servers, requires --secretstore-tls-key and --secretstore-tls-ca.",
+
+			ARG arg_secretstore_tls_key: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.tls_key.clone(),
+			"--secretstore-tls-key=[FILE]",
+			"Path to PEM-encoded private key of this key server certificate.",
+
+			ARG arg_secretstore_tls_ca: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.tls_ca.clone(),
+			"--secretstore-tls-ca=[FILE]",
+			"Path to PEM-encoded CA certificates used to verify certificates of other key servers.",
+
+			ARG arg_secretstore_tls_name: (String) = "secretstore", or |c: &Config| c.secretstore.as_ref()?.tls_name.clone(),
+			"--secretstore-tls-name=[NAME]",
+			"DNS name that certificates of all key servers are issued for.",
+
+			ARG arg_secretstore_tls_pins: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.tls_pins.as_ref().map(|vec| vec.join(",")),
+			"--secretstore-tls-pins=[FINGERPRINTS]",
+			"Comma-separated list of hex-encoded SHA-256 fingerprints of key servers certificates. When set, only key servers presenting one of these certificates are accepted.",
+
 		["Sealing/Mining Options"]
 			FLAG flag_force_sealing: (bool) = false, or |c: &Config| c.mining.as_ref()?.force_sealing.clone(),
 			"--force-sealing",
@@ -734,6 +855,10 @@ usage! {
 			"--stratum",
 			"Run Stratum server for miner push notification.",
 
+			FLAG flag_stratum_proxy_protocol: (bool) = false, or |c: &Config| c.stratum.as_ref()?.proxy_protocol.clone(),
+			"--stratum-proxy-protocol",
+			"Expect HAProxy PROXY protocol (v1 or v2) headers on Stratum connections, so miners behind a TCP load balancer are reported with their own addresses.",
+
 			ARG arg_reseal_on_txs: (String) = "own", or |c: &Config| c.mining.as_ref()?.reseal_on_txs.clone(),
 			"--reseal-on-txs=[SET]",
 			"Specify which transactions should force the node to reseal a block. SET is one of: none - never reseal on new transactions; own - reseal only on a new local transaction; ext - reseal only on a new external transaction; all - reseal on all new transactions.",
@@ -750,6 +875,14 @@ usage! {
 			"--work-queue-size=[ITEMS]",
 			"Specify the number of historical work packages which are kept cached lest a solution is found for them later. High values take more memory but result in fewer unusable solutions.",
 
+			ARG arg_stale_work_window: (usize) = 20usize, or |c: &Config| c.mining.as_ref()?.stale_work_window.clone(),
+			"--stale-work-window=[ITEMS]",
+			"Specify for how many of the most recently handed out work packages solutions are accepted. Solutions for older packages are rejected even if they are still cached.",
+
+			ARG arg_work_test_difficulty: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.work_test_difficulty.clone(),
+			"--work-test-difficulty=[DIFF]",
+			"Advertise DIFF instead of the block difficulty in eth_getWork and stratum jobs, to validate mining rigs against a live node. Solutions are still checked against the block difficulty, the ones meeting only DIFF are accepted and counted as test shares.",
+
 			ARG arg_relay_set: (String) = "cheap", or |c: &Config| c.mining.as_ref()?.relay_set.clone(),
 			"--relay-set=[SET]",
 			"Set of transactions to relay. SET may be: cheap - Relay any transaction in the queue (this may include invalid transactions); strict - Relay only executed transactions (this guarantees we don't relay invalid transactions, but means we relay nothing if not mining); lenient - Same as strict when mining, and cheap when not.",
@@ -786,13 +919,37 @@ usage! {
 			"--tx-queue-per-sender=[LIMIT]",
 			"Maximum number of transactions per sender in the queue. By default it's 1% of the entire queue, but not less than 16.",
 
+			ARG arg_tx_queue_local_quota: (Option<usize>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_local_quota.clone(),
+			"--tx-queue-local-quota=[LIMIT]",
+			"Maximum number of local transactions in the queue. Setting any of the quotas prevents transactions from one origin pushing out transactions from another one.",
+
+			ARG arg_tx_queue_rpc_quota: (Option<usize>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_rpc_quota.clone(),
+			"--tx-queue-rpc-quota=[LIMIT]",
+			"Maximum number of transactions submitted over RPC which are not treated as local in the queue.",
+
+			ARG arg_tx_queue_network_quota: (Option<usize>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_network_quota.clone(),
+			"--tx-queue-network-quota=[LIMIT]",
+			"Maximum number of transactions received from the network in the queue.",
+
+			ARG arg_tx_queue_max_gas_price_floor: (Option<u64>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_max_gas_price_floor.clone(),
+			"--tx-queue-max-gas-price-floor=[WEI]",
+			"Raise the minimal gas price when the queue is close to its limits and lower it back as the pressure subsides, up to WEI per gas. Disabled by default.",
+
+			ARG arg_tx_queue_min_gas_price_floor: (u64) = 0u64, or |c: &Config| c.mining.as_ref()?.tx_queue_min_gas_price_floor.clone(),
+			"--tx-queue-min-gas-price-floor=[WEI]",
+			"Lowest minimal gas price floor used by --tx-queue-max-gas-price-floor.",
+
+			ARG arg_tx_queue_gas_price_floor_step: (u32) = 12u32, or |c: &Config| c.mining.as_ref()?.tx_queue_gas_price_floor_step.clone(),
+			"--tx-queue-gas-price-floor-step=[PCT]",
+			"Percentage by which the minimal gas price floor is raised or lowered after each block.",
+
 			ARG arg_tx_queue_locals: (Option<String>) = None, or |c: &Config| helpers::join_set(c.mining.as_ref()?.tx_queue_locals.as_ref()),
 			"--tx-queue-locals=[ACCOUNTS]",
 			"Specify local accounts for which transactions are prioritized in the queue. ACCOUNTS is a comma-delimited list of addresses.",
 
 			ARG arg_tx_queue_strategy: (String) = "gas_price", or |c: &Config| c.mining.as_ref()?.tx_queue_strategy.clone(),
 			"--tx-queue-strategy=[S]",
-			"Prioritization strategy used to order transactions in the queue. S may be: gas_price - Prioritize txs with high gas price",
+			"Prioritization strategy used to order transactions in the queue. S may be: gas_price - Prioritize txs with high gas price; fifo - Include txs in the order they arrived; round_robin - Take turns between senders, one tx each.",
 
 			ARG arg_stratum_interface: (String) = "local", or |c: &Config| c.stratum.as_ref()?.interface.clone(),
 			"--stratum-interface=[IP]",
@@ -802,6 +959,18 @@ usage! {
 			"--stratum-port=[PORT]",
 			"Port for Stratum server to listen on.",
 
+			ARG arg_stratum_tls_port: (u16) = 8010u16, or |c: &Config| c.stratum.as_ref()?.tls_port.clone(),
+			"--stratum-tls-port=[PORT]",
+			"Port for the TLS encrypted Stratum listener, which runs next to the plaintext one.",
+
+			ARG arg_stratum_tls_cert: (Option<String>) = None, or |c: &Config| c.stratum.as_ref()?.tls_cert.clone(),
+			"--stratum-tls-cert=[FILE]",
+			"Path to PEM-encoded certificate chain presented to Stratum workers. Enables the TLS encrypted Stratum listener, requires --stratum-tls-key.",
+
+			ARG arg_stratum_tls_key: (Option<String>) = None, or |c: &Config| c.stratum.as_ref()?.tls_key.clone(),
+			"--stratum-tls-key=[FILE]",
+			"Path to PEM-encoded private key of the Stratum TLS certificate.",
+
 			ARG arg_min_gas_price: (Option<u64>) = None, or |c: &Config| c.mining.as_ref()?.min_gas_price.clone(),
 			"--min-gas-price=[STRING]",
 			"Minimum amount of Wei per GAS to be paid for a transaction to be accepted for mining. Overrides --usd-per-tx.",
@@ -818,6 +987,14 @@ usage! {
 			"--engine-signer=[ADDRESS]",
 			"Specify the address which should be used to sign consensus messages and issue blocks. Relevant only to non-PoW chains.",
 
+			ARG arg_engine_signer_remote: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.engine_signer_remote.clone(),
+			"--engine-signer-remote=[URL]",
+			"Delegate consensus signatures of --engine-signer to an external signing service. URL is either an HTTP endpoint (http://host:port/path) or a path to an IPC socket. A local account for --engine-signer, if available, is used as a fallback.",
+
+			ARG arg_engine_signer_remote_timeout: (u64) = 2000u64, or |c: &Config| c.mining.as_ref()?.engine_signer_remote_timeout.clone(),
+			"--engine-signer-remote-timeout=[MS]",
+			"Maximal time to wait for a signature from the --engine-signer-remote service.",
+
 			ARG arg_tx_gas_limit: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.tx_gas_limit.clone(),
 			"--tx-gas-limit=[GAS]",
 			"Apply a limit of GAS as the maximum amount of gas a single transaction may have for it to be mined.",
@@ -832,16 +1009,40 @@ usage! {
 
 			ARG arg_notify_work: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.notify_work.as_ref().map(|vec| vec.join(",")),
 			"--notify-work=[URLS]",
-			"URLs to which work package notifications are pushed. URLS should be a comma-delimited list of HTTP URLs.",
+			"URLs to which work package notifications are pushed. URLS should be a comma-delimited list of HTTP URLs. New work packages are also available to WebSocket clients via eth_subscribe(\"newWork\").",
+
+			ARG arg_notify_work_retries: (usize) = 3usize, or |c: &Config| c.mining.as_ref()?.notify_work_retries.clone(),
+			"--notify-work-retries=[NUM]",
+			"Number of times a failed work package notification is retried (with exponential backoff) while the package is still current.",
 
 			ARG arg_stratum_secret: (Option<String>) = None, or |c: &Config| c.stratum.as_ref()?.secret.clone(),
 			"--stratum-secret=[STRING]",
 			"Secret for authorizing Stratum server for peers.",
 
+			ARG arg_stratum_share_interval: (Option<u64>) = None, or |c: &Config| c.stratum.as_ref()?.share_interval.clone(),
+			"--stratum-share-interval=[SECS]",
+			"Adjust the difficulty of every Stratum worker so that it submits a share about every SECS seconds. All workers get the same difficulty if not given.",
+
+			ARG arg_stratum_retarget_window: (u64) = 60u64, or |c: &Config| c.stratum.as_ref()?.retarget_window.clone(),
+			"--stratum-retarget-window=[SECS]",
+			"Number of seconds the shares of a Stratum worker are counted for before its difficulty is adjusted. Only used together with --stratum-share-interval.",
+
 			ARG arg_max_round_blocks_to_import: (usize) = 12usize, or |c: &Config| c.mining.as_ref()?.max_round_blocks_to_import.clone(),
 			"--max-round-blocks-to-import=[S]",
 			"Maximal number of blocks to import for each import round.",
 
+			ARG arg_max_reorg_depth: (Option<u64>) = None, or |c: &Config| c.mining.as_ref()?.max_reorg_depth.clone(),
+			"--max-reorg-depth=[BLOCKS]",
+			"Halt block import when a reorg would retract more than BLOCKS canonical blocks. Import resumes once the reorg is acknowledged with parity_acknowledgeReorg.",
+
+			FLAG flag_shadow_execution: (bool) = false, or |c: &Config| c.mining.as_ref()?.shadow_execution.clone(),
+			"--shadow-execution",
+			"Execute every imported block a second time with the reference (unoptimized) EVM, comparing receipts, gas used and state roots. Block import is halted on divergence. Slows down import considerably.",
+
+			ARG arg_consensus_faults_window: (u64) = 86400u64, or |c: &Config| c.mining.as_ref()?.consensus_faults_window.clone(),
+			"--consensus-faults-window=[SECS]",
+			"Keep consensus faults of validators observed within the last SECS seconds, reported via parity_consensusFaults. Relevant only to non-PoW chains.",
+
 		["Internal Options"]
 			FLAG flag_can_restart: (bool) = false, or |_| None,
 			"--can-restart",
@@ -868,6 +1069,10 @@ usage! {
 			"--log-file=[FILENAME]",
 			"Specify a filename into which logging should be appended.",
 
+			ARG arg_log_format: (String) = "text", or |c: &Config| c.misc.as_ref()?.log_format.clone(),
+			"--log-format=[FORMAT]",
+			"Specify the format of log output. FORMAT may be either text or json. With json every record is written as a single JSON object with timestamp, target, level and fields.",
+
 		["Footprint Options"]
 			FLAG flag_scale_verifiers: (bool) = false, or |c: &Config| c.footprint.as_ref()?.scale_verifiers.clone(),
 			"--scale-verifiers",
@@ -889,6 +1094,10 @@ usage! {
 			"--pruning-memory=[MB]",
 			"The ideal amount of memory in megabytes to use to store recent states. As many states as possible will be kept within this limit, and at least --pruning-history states will always be kept.",
 
+			FLAG flag_pruning_finalized: (bool) = false, or |c: &Config| c.footprint.as_ref()?.pruning_finalized.clone(),
+			"--pruning-finalized",
+			"Prune all states below the latest block finalized by the consensus engine (e.g. AuRa), ignoring --pruning-history and --pruning-memory. Has no effect with engines which don't finalize blocks.",
+
 			ARG arg_cache_size_db: (u32) = 128u32, or |c: &Config| c.footprint.as_ref()?.cache_size_db.clone(),
 			"--cache-size-db=[MB]",
 			"Override database cache size.",
@@ -909,6 +1118,26 @@ usage! {
 			"--db-compaction=[TYPE]",
 			"Database compaction type. TYPE may be one of: ssd - suitable for SSDs and fast HDDs; hdd - suitable for slow HDDs; auto - determine automatically.",
 
+			ARG arg_db_profile: (Option<String>) = None, or |c: &Config| c.footprint.as_ref()?.db_profile.clone(),
+			"--db-profile=[PROFILE]",
+			"Database tuning profile, deciding the compaction type (unless --db-compaction is given) and how --cache-size-db is spread across the database columns. PROFILE may be one of: ssd - suitable for SSDs and fast HDDs; hdd - caches more blocks for slow HDDs; archive - caches more blocks, receipts and traces for archive nodes.",
+
+			ARG arg_db_column_cache: (Option<String>) = None, or |c: &Config| c.footprint.as_ref()?.db_column_cache.clone(),
+			"--db-column-cache=[SPEC]",
+			"Override the cache size of individual database columns. SPEC is a comma-separated list of COLUMN:MB pairs, where COLUMN may be one of: state, headers, bodies, extra, trace, account_bloom, node_info, light_chain, private_transactions_state.",
+
+			ARG arg_db_backup_path: (Option<String>) = None, or |c: &Config| c.footprint.as_ref()?.db_backup_path.clone(),
+			"--db-backup-path=[PATH]",
+			"Periodically back up the database into the given directory. Block import is paused only while a consistent view of the database is captured, the copy is then written in the background to its own backup-<BLOCK> subdirectory.",
+
+			ARG arg_db_backup_interval: (u64) = 10000u64, or |c: &Config| c.footprint.as_ref()?.db_backup_interval.clone(),
+			"--db-backup-interval=[BLOCKS]",
+			"Take a database backup every BLOCKS imported blocks. Only used together with --db-backup-path.",
+
+			ARG arg_db_backup_keep: (usize) = 3usize, or |c: &Config| c.footprint.as_ref()?.db_backup_keep.clone(),
+			"--db-backup-keep=[NUM]",
+			"Number of most recent database backups to keep. Older backups are removed after a new one has been taken.",
+
 			ARG arg_fat_db: (String) = "auto", or |c: &Config| c.footprint.as_ref()?.fat_db.clone(),
 			"--fat-db=[BOOL]",
 			"Build appropriate information to allow enumeration of all accounts and storage keys. Doubles the size of the state database. BOOL may be one of on, off or auto.",
@@ -1207,6 +1436,7 @@ struct Account {
 	keys_iterations: Option<u32>,
 	refresh_time: Option<u64>,
 	fast_unlock: Option<bool>,
+	max_unlock_duration: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1226,6 +1456,8 @@ struct PrivateTransactions {
 #[serde(deny_unknown_fields)]
 struct Ui {
 	path: Option<String>,
+	webhooks: Option<Vec<String>>,
+	webhook_secret: Option<String>,
 
 	#[serde(rename = "force")]
 	_legacy_force: Option<bool>,
@@ -1250,12 +1482,20 @@ struct Network {
 	max_peers: Option<u16>,
 	snapshot_peers: Option<u16>,
 	max_pending_peers: Option<u16>,
+	max_session_age: Option<u64>,
+	max_dials_per_sec: Option<u32>,
+	max_peers_per_subnet: Option<u32>,
+	max_peers_per_id_bucket: Option<u32>,
+	reserved_dial_weight: Option<u8>,
+	max_upload_bandwidth: Option<u64>,
+	bandwidth_weights: Option<String>,
 	nat: Option<String>,
 	allow_ips: Option<String>,
 	id: Option<u64>,
 	bootnodes: Option<Vec<String>>,
 	discovery: Option<bool>,
 	node_key: Option<String>,
+	node_key_password: Option<String>,
 	reserved_peers: Option<String>,
 	reserved_only: Option<bool>,
 	no_serve_light: Option<bool>,
@@ -1275,7 +1515,14 @@ struct Rpc {
 	keep_alive: Option<bool>,
 	experimental_rpcs: Option<bool>,
 	poll_lifetime: Option<u32>,
+	slow_query_threshold: Option<u64>,
+	max_batch_size: Option<usize>,
+	batch_time_budget: Option<u64>,
+	batch_abort_on_error: Option<bool>,
+	deprecation_warnings: Option<bool>,
 	allow_missing_blocks: Option<bool>,
+	latest_confirmations: Option<u64>,
+	page_size: Option<usize>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1288,6 +1535,7 @@ struct Ws {
 	origins: Option<Vec<String>>,
 	hosts: Option<Vec<String>>,
 	max_connections: Option<usize>,
+	max_payload: Option<usize>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1341,7 +1589,12 @@ struct SecretStore {
 	http_interface: Option<String>,
 	http_port: Option<u16>,
 	path: Option<String>,
-	cors: Option<Vec<String>>
+	cors: Option<Vec<String>>,
+	tls_cert: Option<String>,
+	tls_key: Option<String>,
+	tls_ca: Option<String>,
+	tls_name: Option<String>,
+	tls_pins: Option<Vec<String>>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1359,12 +1612,16 @@ struct Ipfs {
 struct Mining {
 	author: Option<String>,
 	engine_signer: Option<String>,
+	engine_signer_remote: Option<String>,
+	engine_signer_remote_timeout: Option<u64>,
 	force_sealing: Option<bool>,
 	reseal_on_uncle: Option<bool>,
 	reseal_on_txs: Option<String>,
 	reseal_min_period: Option<u64>,
 	reseal_max_period: Option<u64>,
 	work_queue_size: Option<usize>,
+	stale_work_window: Option<usize>,
+	work_test_difficulty: Option<String>,
 	tx_gas_limit: Option<String>,
 	tx_time_limit: Option<u64>,
 	relay_set: Option<String>,
@@ -1378,6 +1635,12 @@ struct Mining {
 	extra_data: Option<String>,
 	tx_queue_size: Option<usize>,
 	tx_queue_per_sender: Option<usize>,
+	tx_queue_local_quota: Option<usize>,
+	tx_queue_rpc_quota: Option<usize>,
+	tx_queue_network_quota: Option<usize>,
+	tx_queue_max_gas_price_floor: Option<u64>,
+	tx_queue_min_gas_price_floor: Option<u64>,
+	tx_queue_gas_price_floor_step: Option<u32>,
 	tx_queue_mem_limit: Option<u32>,
 	tx_queue_locals: Option<HashSet<String>>,
 	tx_queue_strategy: Option<String>,
@@ -1387,9 +1650,13 @@ struct Mining {
 	tx_queue_no_early_reject: Option<bool>,
 	remove_solved: Option<bool>,
 	notify_work: Option<Vec<String>>,
+	notify_work_retries: Option<usize>,
 	refuse_service_transactions: Option<bool>,
 	infinite_pending_block: Option<bool>,
 	max_round_blocks_to_import: Option<usize>,
+	max_reorg_depth: Option<u64>,
+	shadow_execution: Option<bool>,
+	consensus_faults_window: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1399,6 +1666,12 @@ struct Stratum {
 	interface: Option<String>,
 	port: Option<u16>,
 	secret: Option<String>,
+	proxy_protocol: Option<bool>,
+	share_interval: Option<u64>,
+	retarget_window: Option<u64>,
+	tls_port: Option<u16>,
+	tls_cert: Option<String>,
+	tls_key: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1408,6 +1681,7 @@ struct Footprint {
 	pruning: Option<String>,
 	pruning_history: Option<u64>,
 	pruning_memory: Option<usize>,
+	pruning_finalized: Option<bool>,
 	fast_and_loose: Option<bool>,
 	cache_size: Option<u32>,
 	cache_size_db: Option<u32>,
@@ -1415,6 +1689,11 @@ struct Footprint {
 	cache_size_queue: Option<u32>,
 	cache_size_state: Option<u32>,
 	db_compaction: Option<String>,
+	db_profile: Option<String>,
+	db_column_cache: Option<String>,
+	db_backup_path: Option<String>,
+	db_backup_interval: Option<u64>,
+	db_backup_keep: Option<usize>,
 	fat_db: Option<String>,
 	scale_verifiers: Option<bool>,
 	num_verifiers: Option<usize>,
@@ -1432,6 +1711,7 @@ struct Snapshots {
 struct Misc {
 	logging: Option<String>,
 	log_file: Option<String>,
+	log_format: Option<String>,
 	color: Option<bool>,
 	ports_shift: Option<u16>,
 	unsafe_expose: Option<bool>,
@@ -1452,6 +1732,7 @@ struct Light {
 	on_demand_request_backoff_max: Option<u64>,
 	on_demand_request_backoff_rounds_max: Option<usize>,
 	on_demand_request_consecutive_failures: Option<usize>,
+	header_history: Option<u64>,
 }
 
 #[cfg(test)]
@@ -1721,9 +2002,12 @@ mod tests {
 			cmd_restore: false,
 			cmd_tools: false,
 			cmd_tools_hash: false,
+			cmd_tools_rotate_node_key: false,
 			cmd_db: false,
 			cmd_db_kill: false,
 			cmd_db_reset: false,
+			cmd_db_export_local_store: false,
+			cmd_db_import_local_store: false,
 			cmd_export_hardcoded_sync: false,
 
 			// Arguments
@@ -1745,6 +2029,8 @@ mod tests {
 			arg_account_import_path: None,
 			arg_wallet_import_path: None,
 			arg_db_reset_num: 10,
+			arg_db_export_local_store_file: None,
+			arg_db_import_local_store_file: None,
 
 			// -- Operating Options
 			arg_mode: "last".into(),
@@ -1778,6 +2064,7 @@ mod tests {
 			arg_keys_iterations: 10240u32,
 			arg_accounts_refresh: 5u64,
 			flag_fast_unlock: false,
+			arg_max_unlock_duration: None,
 
 			// -- Private Transactions Options
 			flag_private_enabled: true,
@@ -1795,6 +2082,8 @@ mod tests {
 			arg_ui_interface: None,
 			arg_ui_hosts: None,
 			arg_ui_path: "$HOME/.parity/signer".into(),
+			arg_signer_webhooks: None,
+			arg_signer_webhook_secret: None,
 			flag_ui_no_validation: false,
 
 			// -- Networking Options
@@ -1804,6 +2093,13 @@ mod tests {
 			arg_min_peers: Some(25u16),
 			arg_max_peers: Some(50u16),
 			arg_max_pending_peers: 64u16,
+			arg_max_session_age: None,
+			arg_max_dials_per_sec: 0u32,
+			arg_max_peers_per_subnet: 0u32,
+			arg_max_peers_per_id_bucket: 0u32,
+			arg_reserved_dial_weight: 100u8,
+			arg_max_upload_bandwidth: 0u64,
+			arg_bandwidth_weights: "4,2,1".into(),
 			arg_snapshot_peers: 0u16,
 			arg_allow_ips: "all".into(),
 			arg_nat: "any".into(),
@@ -1811,6 +2107,7 @@ mod tests {
 			arg_bootnodes: Some("".into()),
 			flag_no_discovery: false,
 			arg_node_key: None,
+			arg_node_key_password: None,
 			arg_reserved_peers: Some("./path_to_file".into()),
 			flag_reserved_only: false,
 			flag_no_ancient_blocks: false,
@@ -1830,6 +2127,13 @@ mod tests {
 			arg_jsonrpc_threads: None, // DEPRECATED, does nothing
 			arg_jsonrpc_max_payload: None,
 			arg_poll_lifetime: 60u32,
+			arg_jsonrpc_slow_query_threshold: None,
+			arg_jsonrpc_max_batch_size: None,
+			arg_jsonrpc_batch_time_budget: None,
+			flag_jsonrpc_batch_abort_on_error: false,
+			arg_jsonrpc_latest_confirmations: 0u64,
+			arg_jsonrpc_page_size: 10000usize,
+			flag_jsonrpc_deprecation_warnings: false,
 			flag_jsonrpc_allow_missing_blocks: false,
 
 			// WS
@@ -1840,6 +2144,7 @@ mod tests {
 			arg_ws_origins: "none".into(),
 			arg_ws_hosts: "none".into(),
 			arg_ws_max_connections: 100,
+			arg_ws_max_payload: None,
 
 			// IPC
 			flag_no_ipc: false,
@@ -1862,6 +2167,11 @@ mod tests {
 			arg_secretstore_doc_sretr_contract: Some("none".into()),
 			arg_secretstore_secret: None,
 			arg_secretstore_admin_public: None,
+			arg_secretstore_tls_cert: None,
+			arg_secretstore_tls_key: None,
+			arg_secretstore_tls_ca: None,
+			arg_secretstore_tls_name: "secretstore".into(),
+			arg_secretstore_tls_pins: None,
 			arg_secretstore_nodes: "".into(),
 			arg_secretstore_server_set_contract: Some("registry".into()),
 			arg_secretstore_interface: "local".into(),
@@ -1881,12 +2191,16 @@ mod tests {
 			// -- Sealing/Mining Options
 			arg_author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 			arg_engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
+			arg_engine_signer_remote: None,
+			arg_engine_signer_remote_timeout: 2000u64,
 			flag_force_sealing: true,
 			arg_reseal_on_txs: "all".into(),
 			arg_reseal_min_period: 4000u64,
 			arg_reseal_max_period: 60000u64,
 			flag_reseal_on_uncle: false,
 			arg_work_queue_size: 20usize,
+			arg_stale_work_window: 20usize,
+			arg_work_test_difficulty: None,
 			arg_tx_gas_limit: Some("10000000".into()),
 			arg_tx_time_limit: Some(100u64),
 			arg_relay_set: "cheap".into(),
@@ -1902,6 +2216,12 @@ mod tests {
 			flag_tx_queue_no_early_reject: false,
 			arg_tx_queue_size: 8192usize,
 			arg_tx_queue_per_sender: None,
+			arg_tx_queue_local_quota: None,
+			arg_tx_queue_rpc_quota: None,
+			arg_tx_queue_network_quota: None,
+			arg_tx_queue_max_gas_price_floor: None,
+			arg_tx_queue_min_gas_price_floor: 0u64,
+			arg_tx_queue_gas_price_floor_step: 12u32,
 			arg_tx_queue_mem_limit: 4u32,
 			arg_tx_queue_locals: Some("0xdeadbeefcafe0000000000000000000000000000".into()),
 			arg_tx_queue_strategy: "gas_factor".into(),
@@ -1909,14 +2229,24 @@ mod tests {
 			arg_tx_queue_ban_time: Some(180u16),
 			flag_remove_solved: false,
 			arg_notify_work: Some("http://localhost:3001".into()),
+			arg_notify_work_retries: 3usize,
 			flag_refuse_service_transactions: false,
 			flag_infinite_pending_block: false,
 			arg_max_round_blocks_to_import: 12usize,
+			arg_max_reorg_depth: None,
+			flag_shadow_execution: false,
+			arg_consensus_faults_window: 86400u64,
 
 			flag_stratum: false,
 			arg_stratum_interface: "local".to_owned(),
 			arg_stratum_port: 8008u16,
+			arg_stratum_tls_port: 8010u16,
+			arg_stratum_tls_cert: None,
+			arg_stratum_tls_key: None,
 			arg_stratum_secret: None,
+			arg_stratum_share_interval: None,
+			arg_stratum_retarget_window: 60u64,
+			flag_stratum_proxy_protocol: false,
 
 			// -- Footprint Options
 			arg_tracing: "auto".into(),
@@ -1928,8 +2258,14 @@ mod tests {
 			arg_cache_size_queue: 50u32,
 			arg_cache_size_state: 25u32,
 			arg_cache_size: Some(128),
+			flag_pruning_finalized: false,
 			flag_fast_and_loose: false,
 			arg_db_compaction: "ssd".into(),
+			arg_db_profile: None,
+			arg_db_column_cache: None,
+			arg_db_backup_path: None,
+			arg_db_backup_interval: 10000u64,
+			arg_db_backup_keep: 3usize,
 			arg_fat_db: "auto".into(),
 			flag_scale_verifiers: true,
 			arg_num_verifiers: Some(6),
@@ -1955,6 +2291,7 @@ mod tests {
 			arg_on_demand_request_backoff_max: Some(15),
 			arg_on_demand_request_backoff_rounds_max: Some(100),
 			arg_on_demand_request_consecutive_failures: Some(1),
+			arg_light_header_history: 2048u64,
 
 			// -- Whisper options.
 			flag_whisper: false,
@@ -2005,6 +2342,7 @@ mod tests {
 			flag_version: false,
 			arg_logging: Some("own_tx=trace".into()),
 			arg_log_file: Some("/var/log/parity.log".into()),
+			arg_log_format: "text".into(),
 			flag_no_color: false,
 			flag_no_config: false,
 		});
@@ -2062,9 +2400,12 @@ mod tests {
 				keys_iterations: None,
 				refresh_time: None,
 				fast_unlock: None,
+				max_unlock_duration: None,
 			}),
 			ui: Some(Ui {
 				path: None,
+				webhooks: None,
+				webhook_secret: None,
 				_legacy_force: None,
 				_legacy_disable: Some(true),
 				_legacy_port: None,
@@ -2079,6 +2420,13 @@ mod tests {
 				min_peers: Some(10),
 				max_peers: Some(20),
 				max_pending_peers: Some(30),
+				max_session_age: None,
+				max_dials_per_sec: None,
+				max_peers_per_subnet: None,
+				max_peers_per_id_bucket: None,
+				reserved_dial_weight: None,
+				max_upload_bandwidth: None,
+				bandwidth_weights: None,
 				snapshot_peers: Some(40),
 				allow_ips: Some("public".into()),
 				nat: Some("any".into()),
@@ -2086,6 +2434,7 @@ mod tests {
 				bootnodes: None,
 				discovery: Some(true),
 				node_key: None,
+				node_key_password: None,
 				reserved_peers: Some("./path/to/reserved_peers".into()),
 				reserved_only: Some(true),
 				no_serve_light: None,
@@ -2098,6 +2447,7 @@ mod tests {
 				origins: Some(vec!["none".into()]),
 				hosts: None,
 				max_connections: None,
+				max_payload: None,
 			}),
 			rpc: Some(Rpc {
 				disable: Some(true),
@@ -2111,7 +2461,14 @@ mod tests {
 				keep_alive: None,
 				experimental_rpcs: None,
 				poll_lifetime: None,
-				allow_missing_blocks: None
+				slow_query_threshold: None,
+				max_batch_size: None,
+				batch_time_budget: None,
+				batch_abort_on_error: None,
+				deprecation_warnings: None,
+				allow_missing_blocks: None,
+				latest_confirmations: None,
+				page_size: None,
 			}),
 			ipc: Some(Ipc {
 				disable: None,
@@ -2149,6 +2506,11 @@ mod tests {
 				http_port: Some(8082),
 				path: None,
 				cors: None,
+				tls_cert: None,
+				tls_key: None,
+				tls_ca: None,
+				tls_name: None,
+				tls_pins: None,
 			}),
 			private_tx: None,
 			ipfs: Some(Ipfs {
@@ -2161,12 +2523,16 @@ mod tests {
 			mining: Some(Mining {
 				author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 				engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
+				engine_signer_remote: None,
+				engine_signer_remote_timeout: None,
 				force_sealing: Some(true),
 				reseal_on_txs: Some("all".into()),
 				reseal_on_uncle: None,
 				reseal_min_period: Some(4000),
 				reseal_max_period: Some(60000),
 				work_queue_size: None,
+				stale_work_window: None,
+				work_test_difficulty: None,
 				relay_set: None,
 				min_gas_price: None,
 				gas_price_percentile: None,
@@ -2177,6 +2543,12 @@ mod tests {
 				gas_cap: None,
 				tx_queue_size: Some(8192),
 				tx_queue_per_sender: None,
+				tx_queue_local_quota: None,
+				tx_queue_rpc_quota: None,
+				tx_queue_network_quota: None,
+				tx_queue_max_gas_price_floor: None,
+				tx_queue_min_gas_price_floor: None,
+				tx_queue_gas_price_floor_step: None,
 				tx_queue_mem_limit: None,
 				tx_queue_locals: None,
 				tx_queue_strategy: None,
@@ -2189,15 +2561,20 @@ mod tests {
 				extra_data: None,
 				remove_solved: None,
 				notify_work: None,
+				notify_work_retries: None,
 				refuse_service_transactions: None,
 				infinite_pending_block: None,
 				max_round_blocks_to_import: None,
+				max_reorg_depth: None,
+				shadow_execution: None,
+				consensus_faults_window: None,
 			}),
 			footprint: Some(Footprint {
 				tracing: Some("on".into()),
 				pruning: Some("fast".into()),
 				pruning_history: Some(64),
 				pruning_memory: None,
+				pruning_finalized: None,
 				fast_and_loose: None,
 				cache_size: None,
 				cache_size_db: Some(256),
@@ -2205,6 +2582,11 @@ mod tests {
 				cache_size_queue: Some(100),
 				cache_size_state: Some(25),
 				db_compaction: Some("ssd".into()),
+				db_profile: None,
+				db_column_cache: None,
+				db_backup_path: None,
+				db_backup_interval: None,
+				db_backup_keep: None,
 				fat_db: Some("off".into()),
 				scale_verifiers: Some(false),
 				num_verifiers: None,
@@ -2215,6 +2597,7 @@ mod tests {
 				on_demand_request_backoff_max: Some(15),
 				on_demand_request_backoff_rounds_max: Some(10),
 				on_demand_request_consecutive_failures: Some(1),
+				header_history: None,
 			}),
 			snapshots: Some(Snapshots {
 				disable_periodic: Some(true),
@@ -2223,6 +2606,7 @@ mod tests {
 			misc: Some(Misc {
 				logging: Some("own_tx=trace".into()),
 				log_file: Some("/var/log/parity.log".into()),
+				log_format: None,
 				color: Some(true),
 				ports_shift: Some(0),
 				unsafe_expose: Some(false),
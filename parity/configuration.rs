@@ -16,7 +16,7 @@
 
 use std::time::Duration;
 use std::io::Read;
-use std::net::{SocketAddr, ToSocketAddrs};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use std::path::PathBuf;
 use std::collections::{HashSet, BTreeMap};
 use std::iter::FromIterator;
@@ -29,13 +29,16 @@ use bytes::Bytes;
 use ansi_term::Colour;
 use sync::{NetworkConfiguration, validate_node_url, self};
 use parity_crypto::publickey::{Secret, Public};
+use ethcore::client::{BackupConfig, DatabaseCompactionProfile, DatabaseProfile, DatabaseTuning};
 use ethcore::miner::{stratum, MinerOptions};
 use snapshot::SnapshotConfiguration;
 use miner::pool;
+use miner::gas_price_floor::GasPriceFloorOptions;
 use verification::queue::VerifierSettings;
 
 use rpc::{IpcConfiguration, HttpConfiguration, WsConfiguration};
 use parity_rpc::NetworkSettings;
+use parity_rpc::informant::BatchLimits;
 use cache::CacheConfig;
 use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_queue_strategy, to_queue_penalization};
 use dir::helpers::{replace_home, replace_home_and_local};
@@ -44,16 +47,19 @@ use ethcore_logger::Config as LogConfig;
 use dir::{self, Directories, default_hypervisor_path, default_local_path, default_data_path};
 use ipfs::Configuration as IpfsConfiguration;
 use ethcore_private_tx::{ProviderConfig, EncryptorConfig};
-use secretstore::{NodeSecretKey, Configuration as SecretStoreConfiguration, ContractAddress as SecretStoreContractAddress};
+use secretstore::{NodeSecretKey, Configuration as SecretStoreConfiguration, ContractAddress as SecretStoreContractAddress,
+	TlsConfiguration as SecretStoreTlsConfiguration};
 use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 use run::RunCmd;
+use signer::WebhooksConfiguration;
 use types::data_format::DataFormat;
-use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, ResetBlockchain};
+use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, ResetBlockchain, LocalStoreFile};
 use export_hardcoded_sync::ExportHsyncCmd;
+use node_key::{NodeKeyEncryption, RotateNodeKeyCmd};
 use presale::ImportWallet;
 use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts};
 use snapshot_cmd::{self, SnapshotCommand};
-use network::{IpFilter, NatType};
+use network::{BandwidthPolicy, DialPolicy, IpFilter, NatType};
 
 const DEFAULT_MAX_PEERS: u16 = 50;
 const DEFAULT_MIN_PEERS: u16 = 25;
@@ -84,6 +90,7 @@ pub enum Cmd {
 	},
 	Snapshot(SnapshotCommand),
 	Hash(Option<String>),
+	RotateNodeKey(RotateNodeKeyCmd),
 	ExportHardcodedSync(ExportHsyncCmd),
 }
 
@@ -125,7 +132,7 @@ impl Configuration {
 			mode => Some(to_mode(&mode, self.args.arg_mode_timeout, self.args.arg_mode_alarm)?),
 		};
 		let update_policy = self.update_policy()?;
-		let logger_config = self.logger_config();
+		let logger_config = self.logger_config()?;
 		let ws_conf = self.ws_config()?;
 		let snapshot_conf = self.snapshot_config()?;
 		let http_conf = self.http_config()?;
@@ -135,7 +142,7 @@ impl Configuration {
 		let cache_config = self.cache_config();
 		let tracing = self.args.arg_tracing.parse()?;
 		let fat_db = self.args.arg_fat_db.parse()?;
-		let compaction = self.args.arg_db_compaction.parse()?;
+		let compaction = self.db_compaction()?;
 		let warp_sync = !self.args.flag_no_warp;
 		let geth_compatibility = self.args.flag_geth;
 		let experimental_rpcs = self.args.flag_jsonrpc_experimental;
@@ -181,6 +188,11 @@ impl Configuration {
 			}
 		} else if self.args.cmd_tools && self.args.cmd_tools_hash {
 			Cmd::Hash(self.args.arg_tools_hash_file)
+		} else if self.args.cmd_tools && self.args.cmd_tools_rotate_node_key {
+			Cmd::RotateNodeKey(RotateNodeKeyCmd {
+				path: self.network_path(),
+				encryption: self.node_key_encryption(),
+			})
 		} else if self.args.cmd_db && self.args.cmd_db_reset {
 			Cmd::Blockchain(BlockchainCmd::Reset(ResetBlockchain {
 				dirs,
@@ -200,6 +212,22 @@ impl Configuration {
 				dirs: dirs,
 				pruning: pruning,
 			}))
+		} else if self.args.cmd_db && self.args.cmd_db_export_local_store {
+			Cmd::Blockchain(BlockchainCmd::ExportLocalStore(LocalStoreFile {
+				spec,
+				dirs,
+				pruning,
+				compaction,
+				file_path: self.args.arg_db_export_local_store_file.clone().ok_or("Export file path is required")?,
+			}))
+		} else if self.args.cmd_db && self.args.cmd_db_import_local_store {
+			Cmd::Blockchain(BlockchainCmd::ImportLocalStore(LocalStoreFile {
+				spec,
+				dirs,
+				pruning,
+				compaction,
+				file_path: self.args.arg_db_import_local_store_file.clone().ok_or("Import file path is required")?,
+			}))
 		} else if self.args.cmd_account {
 			let account_cmd = if self.args.cmd_account_new {
 				let new_acc = NewAccount {
@@ -370,11 +398,16 @@ impl Configuration {
 				pruning,
 				pruning_history,
 				pruning_memory: self.args.arg_pruning_memory,
+				pruning_finalized: self.args.flag_pruning_finalized,
+				backup_conf: self.backup_config(),
 				daemon,
 				logger_config: logger_config.clone(),
 				miner_options: self.miner_options()?,
 				gas_price_percentile: self.args.arg_gas_price_percentile,
 				poll_lifetime: self.args.arg_poll_lifetime,
+				rpc_slow_query_threshold: self.args.arg_jsonrpc_slow_query_threshold.map(Duration::from_millis),
+				rpc_batch_limits: self.rpc_batch_limits(),
+				rpc_deprecation_warnings: self.args.flag_jsonrpc_deprecation_warnings,
 				ws_conf,
 				snapshot_conf,
 				http_conf,
@@ -387,10 +420,13 @@ impl Configuration {
 				stratum: self.stratum_options()?,
 				update_policy,
 				allow_missing_blocks: self.args.flag_jsonrpc_allow_missing_blocks,
+				latest_confirmations: self.args.arg_jsonrpc_latest_confirmations,
+				page_size: self.args.arg_jsonrpc_page_size,
 				mode,
 				tracing,
 				fat_db,
 				compaction,
+				db_tuning: self.db_tuning()?,
 				warp_sync,
 				warp_barrier: self.args.arg_warp_barrier,
 				geth_compatibility,
@@ -416,6 +452,12 @@ impl Configuration {
 				on_demand_request_backoff_max: self.args.arg_on_demand_request_backoff_max,
 				on_demand_request_backoff_rounds_max: self.args.arg_on_demand_request_backoff_rounds_max,
 				on_demand_request_consecutive_failures: self.args.arg_on_demand_request_consecutive_failures,
+				light_header_history: self.args.arg_light_header_history,
+				signer_webhooks: self.signer_webhooks()?,
+				max_reorg_depth: self.args.arg_max_reorg_depth,
+				shadow_execution: self.args.flag_shadow_execution,
+				consensus_faults_window: self.args.arg_consensus_faults_window,
+				node_key_encryption: self.node_key_encryption(),
 			};
 			Cmd::Run(run_cmd)
 		};
@@ -434,13 +476,31 @@ impl Configuration {
 			extra_data: self.extra_data()?,
 			gas_range_target: (floor, ceil),
 			engine_signer: self.engine_signer()?,
+			engine_signer_remote: self.args.arg_engine_signer_remote.clone(),
+			engine_signer_remote_timeout: Duration::from_millis(self.args.arg_engine_signer_remote_timeout),
 			work_notify: self.work_notify(),
+			work_notify_retries: self.args.arg_notify_work_retries,
 			local_accounts: HashSet::from_iter(to_addresses(&self.args.arg_tx_queue_locals)?.into_iter()),
 		};
 
 		Ok(extras)
 	}
 
+	fn signer_webhooks(&self) -> Result<WebhooksConfiguration, String> {
+		let urls = match self.args.arg_signer_webhooks {
+			Some(ref urls) => urls.split(',').map(str::trim).filter(|url| !url.is_empty()).map(str::to_owned).collect(),
+			None => Vec::new(),
+		};
+		if !urls.is_empty() && self.args.arg_signer_webhook_secret.is_none() {
+			return Err("--signer-webhooks requires --signer-webhook-secret to be set.".into());
+		}
+
+		Ok(WebhooksConfiguration {
+			urls,
+			secret_path: self.args.arg_signer_webhook_secret.clone(),
+		})
+	}
+
 	fn author(&self) -> Result<Address, String> {
 		to_address(self.args.arg_etherbase.clone().or(self.args.arg_author.clone()))
 	}
@@ -470,13 +530,47 @@ impl Configuration {
 		}
 	}
 
+	fn db_profile(&self) -> Result<Option<DatabaseProfile>, String> {
+		self.args.arg_db_profile.as_ref().map(|profile| profile.parse()).transpose()
+	}
+
+	fn db_compaction(&self) -> Result<DatabaseCompactionProfile, String> {
+		let compaction = self.args.arg_db_compaction.parse()?;
+		match (compaction, self.db_profile()?) {
+			(DatabaseCompactionProfile::Auto, Some(profile)) => Ok(profile.compaction()),
+			(compaction, _) => Ok(compaction),
+		}
+	}
+
+	fn db_tuning(&self) -> Result<DatabaseTuning, String> {
+		let mut column_cache_size = BTreeMap::new();
+		if let Some(ref spec) = self.args.arg_db_column_cache {
+			for entry in spec.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+				let mut parts = entry.splitn(2, ':');
+				let (name, size) = match (parts.next(), parts.next()) {
+					(Some(name), Some(size)) => (name.trim(), size.trim()),
+					_ => return Err(format!("Invalid column cache size: {}. Expected COLUMN:MB.", entry)),
+				};
+				let column = to_db_column(name).ok_or_else(|| format!("Invalid database column: {}", name))?;
+				let size = size.parse().map_err(|_| format!("Invalid cache size of column {}: {}", name, size))?;
+				column_cache_size.insert(column, size);
+			}
+		}
+
+		Ok(DatabaseTuning {
+			profile: self.db_profile()?,
+			column_cache_size,
+		})
+	}
+
 	/// returns logger config
-	pub fn logger_config(&self) -> LogConfig {
-		LogConfig {
+	pub fn logger_config(&self) -> Result<LogConfig, String> {
+		Ok(LogConfig {
 			mode: self.args.arg_logging.clone(),
 			color: !self.args.flag_no_color && !cfg!(windows),
 			file: self.args.arg_log_file.as_ref().map(|log_file| replace_home(&self.directories().base, log_file)),
-		}
+			format: self.args.arg_log_format.parse()?,
+		})
 	}
 
 	fn chain(&self) -> Result<SpecType, String> {
@@ -516,6 +610,32 @@ impl Configuration {
 		self.args.arg_max_pending_peers as u32
 	}
 
+	fn dial_policy(&self) -> Result<DialPolicy, String> {
+		if self.args.arg_reserved_dial_weight > 100 {
+			return Err(format!("Invalid reserved dial weight: {}. Must be a percentage between 0 and 100", self.args.arg_reserved_dial_weight));
+		}
+
+		Ok(DialPolicy {
+			max_dials_per_second: self.args.arg_max_dials_per_sec,
+			max_peers_per_subnet: self.args.arg_max_peers_per_subnet,
+			max_peers_per_id_bucket: self.args.arg_max_peers_per_id_bucket,
+			reserved_weight: self.args.arg_reserved_dial_weight,
+		})
+	}
+
+	fn bandwidth_policy(&self) -> Result<BandwidthPolicy, String> {
+		let weights = self.args.arg_bandwidth_weights.split(',')
+			.map(|w| w.trim().parse::<u32>())
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|e| format!("Invalid bandwidth weights: {}", e))?;
+
+		match *weights.as_slice() {
+			[sync, txs, light] if sync > 0 || txs > 0 || light > 0 =>
+				Ok(sync::bandwidth_policy(self.args.arg_max_upload_bandwidth.saturating_mul(1024), sync, txs, light)),
+			_ => Err(format!("Invalid bandwidth weights: {}. Expected three weights, not all 0", self.args.arg_bandwidth_weights)),
+		}
+	}
+
 	fn snapshot_peers(&self) -> u32 {
 		self.args.arg_snapshot_peers as u32
 	}
@@ -532,6 +652,7 @@ impl Configuration {
 			password_files: self.args.arg_password.iter().map(|s| replace_home(&self.directories().base, s)).collect(),
 			unlocked_accounts: to_addresses(&self.args.arg_unlock)?,
 			enable_fast_unlock: self.args.flag_fast_unlock,
+			max_unlock_duration: self.args.arg_max_unlock_duration,
 		};
 
 		Ok(cfg)
@@ -544,10 +665,43 @@ impl Configuration {
 				listen_addr: self.stratum_interface(),
 				port: self.args.arg_ports_shift + self.args.arg_stratum_port,
 				secret: self.args.arg_stratum_secret.as_ref().map(|s| s.parse::<H256>().unwrap_or_else(|_| keccak(s))),
+				proxy_protocol: self.args.flag_stratum_proxy_protocol,
+				vardiff: self.stratum_vardiff()?,
+				tls: self.stratum_tls()?,
 			}))
 		} else { Ok(None) }
 	}
 
+	fn stratum_vardiff(&self) -> Result<Option<stratum::VardiffConfig>, String> {
+		match self.args.arg_stratum_share_interval {
+			None => Ok(None),
+			Some(0) => Err("--stratum-share-interval must be greater than 0".into()),
+			Some(interval) => Ok(Some(stratum::VardiffConfig {
+				target_share_interval: Duration::from_secs(interval),
+				retarget_window: Duration::from_secs(self.args.arg_stratum_retarget_window),
+				..Default::default()
+			})),
+		}
+	}
+
+	fn stratum_tls(&self) -> Result<Option<stratum::TlsConfig>, String> {
+		let (cert, key) = match (&self.args.arg_stratum_tls_cert, &self.args.arg_stratum_tls_key) {
+			(None, None) => return Ok(None),
+			(Some(cert), Some(key)) => (cert, key),
+			_ => return Err("Stratum TLS requires --stratum-tls-cert and --stratum-tls-key".into()),
+		};
+
+		let interface = self.stratum_interface();
+		let ip = interface.parse::<IpAddr>()
+			.map_err(|e| format!("Invalid stratum interface: {}. Error: {:?}", interface, e))?;
+
+		Ok(Some(stratum::TlsConfig {
+			addr: SocketAddr::new(ip, self.args.arg_ports_shift + self.args.arg_stratum_tls_port),
+			certificate_path: cert.clone(),
+			private_key_path: key.clone(),
+		}))
+	}
+
 	fn miner_options(&self) -> Result<MinerOptions, String> {
 		let is_dev_chain = self.is_dev_chain()?;
 		if is_dev_chain && self.args.flag_force_sealing && self.args.arg_reseal_min_period == 0 {
@@ -566,6 +720,7 @@ impl Configuration {
 
 			pending_set: to_pending_set(&self.args.arg_relay_set)?,
 			work_queue_size: self.args.arg_work_queue_size,
+			stale_work_window: self.args.arg_stale_work_window,
 			enable_resubmission: !self.args.flag_remove_solved,
 			infinite_pending_block: self.args.flag_infinite_pending_block,
 
@@ -576,11 +731,45 @@ impl Configuration {
 
 			pool_limits: self.pool_limits()?,
 			pool_verification_options: self.pool_verification_options()?,
+			pool_origin_quotas: pool::OriginQuotas {
+				local: self.args.arg_tx_queue_local_quota,
+				rpc: self.args.arg_tx_queue_rpc_quota,
+				network: self.args.arg_tx_queue_network_quota,
+			},
+			gas_price_floor: self.gas_price_floor()?,
+			work_test_difficulty: match self.args.arg_work_test_difficulty {
+				Some(ref difficulty) => match to_u256(difficulty)? {
+					ref difficulty if difficulty.is_zero() => return Err("--work-test-difficulty must be greater than zero".into()),
+					difficulty => Some(difficulty),
+				},
+				None => None,
+			},
 		};
 
 		Ok(options)
 	}
 
+	fn gas_price_floor(&self) -> Result<Option<GasPriceFloorOptions>, String> {
+		let max = match self.args.arg_tx_queue_max_gas_price_floor {
+			Some(max) => max,
+			None => return Ok(None),
+		};
+		let min = self.args.arg_tx_queue_min_gas_price_floor;
+		if min > max {
+			return Err(format!("Invalid gas price floor bounds: minimum {} is above maximum {}", min, max));
+		}
+		if self.args.arg_tx_queue_gas_price_floor_step == 0 {
+			return Err("Invalid gas price floor step: must be above 0".into());
+		}
+
+		Ok(Some(GasPriceFloorOptions {
+			min: min.into(),
+			max: max.into(),
+			step: self.args.arg_tx_queue_gas_price_floor_step,
+			..Default::default()
+		}))
+	}
+
 	fn pool_limits(&self) -> Result<pool::Options, String> {
 		let max_count = self.args.arg_tx_queue_size;
 
@@ -628,7 +817,8 @@ impl Configuration {
 			http_port: self.args.arg_ports_shift + self.args.arg_secretstore_http_port,
 			data_path: self.directories().secretstore,
 			admin_public: self.secretstore_admin_public()?,
-			cors: self.secretstore_cors()
+			cors: self.secretstore_cors(),
+			tls: self.secretstore_tls()?,
 		})
 	}
 
@@ -741,6 +931,19 @@ impl Configuration {
 		Ok((listen_address, public_address))
 	}
 
+	fn network_path(&self) -> String {
+		let mut net_path = PathBuf::from(self.directories().base);
+		net_path.push("network");
+		net_path.to_str().unwrap().to_owned()
+	}
+
+	fn node_key_encryption(&self) -> Option<NodeKeyEncryption> {
+		self.args.arg_node_key_password.clone().map(|password_file| NodeKeyEncryption {
+			password_file,
+			iterations: self.args.arg_keys_iterations,
+		})
+	}
+
 	fn net_config(&self) -> Result<NetworkConfiguration, String> {
 		let mut ret = NetworkConfiguration::new();
 		ret.nat_enabled = self.args.arg_nat == "any" || self.args.arg_nat == "upnp" || self.args.arg_nat == "natpmp";
@@ -767,9 +970,10 @@ impl Configuration {
 		ret.snapshot_peers = self.snapshot_peers();
 		ret.ip_filter = self.ip_filter()?;
 		ret.max_pending_peers = self.max_pending_peers();
-		let mut net_path = PathBuf::from(self.directories().base);
-		net_path.push("network");
-		ret.config_path = Some(net_path.to_str().unwrap().to_owned());
+		ret.max_session_age = self.args.arg_max_session_age.map(Duration::from_secs);
+		ret.dial_policy = self.dial_policy()?;
+		ret.bandwidth_policy = self.bandwidth_policy()?;
+		ret.config_path = Some(self.network_path());
 		ret.reserved_nodes = self.init_reserved_nodes()?;
 		ret.allow_non_reserved = !self.args.flag_reserved_only;
 		ret.client_version = {
@@ -883,6 +1087,14 @@ impl Configuration {
 		Ok(conf)
 	}
 
+	fn rpc_batch_limits(&self) -> BatchLimits {
+		BatchLimits {
+			max_size: self.args.arg_jsonrpc_max_batch_size,
+			time_budget: self.args.arg_jsonrpc_batch_time_budget.map(Duration::from_millis),
+			abort_on_error: self.args.flag_jsonrpc_batch_abort_on_error,
+		}
+	}
+
 	fn http_config(&self) -> Result<HttpConfiguration, String> {
 		let mut conf = HttpConfiguration::default();
 		conf.enabled = self.rpc_enabled();
@@ -917,6 +1129,7 @@ impl Configuration {
 			signer_path: self.directories().signer.into(),
 			support_token_api,
 			max_connections: self.args.arg_ws_max_connections,
+			max_payload: self.args.arg_ws_max_payload.map_or(5, |max_payload| cmp::max(1, max_payload)),
 		};
 
 		Ok(conf)
@@ -955,6 +1168,14 @@ impl Configuration {
 		Ok(conf)
 	}
 
+	fn backup_config(&self) -> BackupConfig {
+		BackupConfig {
+			path: self.args.arg_db_backup_path.as_ref().map(|path| replace_home(&self.directories().base, path).into()),
+			interval: self.args.arg_db_backup_interval,
+			keep: self.args.arg_db_backup_keep,
+		}
+	}
+
 	fn network_settings(&self) -> Result<NetworkSettings, String> {
 		let http_conf = self.http_config()?;
 		let net_addresses = self.net_addresses()?;
@@ -1095,6 +1316,28 @@ impl Configuration {
 		}
 	}
 
+	fn secretstore_tls(&self) -> Result<Option<SecretStoreTlsConfiguration>, String> {
+		let (cert, key, ca) = match (&self.args.arg_secretstore_tls_cert, &self.args.arg_secretstore_tls_key, &self.args.arg_secretstore_tls_ca) {
+			(None, None, None) => return Ok(None),
+			(Some(cert), Some(key), Some(ca)) => (cert, key, ca),
+			_ => return Err("Secret store TLS requires --secretstore-tls-cert, --secretstore-tls-key and --secretstore-tls-ca".into()),
+		};
+
+		let mut pinned_certificates = Vec::new();
+		for pin in self.args.arg_secretstore_tls_pins.iter().flat_map(|pins| pins.split(',')).filter(|p| p != &"") {
+			pinned_certificates.push(pin.trim_start_matches("0x").parse()
+				.map_err(|e| format!("Invalid secret store TLS certificate fingerprint: {}. Error: {:?}", pin, e))?);
+		}
+
+		Ok(Some(SecretStoreTlsConfiguration {
+			certificate_path: cert.clone(),
+			private_key_path: key.clone(),
+			ca_certificates_path: ca.clone(),
+			server_name: self.args.arg_secretstore_tls_name.clone(),
+			pinned_certificates,
+		}))
+	}
+
 	fn secretstore_nodes(&self) -> Result<BTreeMap<Public, (String, u16)>, String> {
 		let mut nodes = BTreeMap::new();
 		for node in self.args.arg_secretstore_nodes.split(',').filter(|n| n != &"") {
@@ -1182,6 +1425,21 @@ impl Configuration {
 	}
 }
 
+fn to_db_column(name: &str) -> Option<u32> {
+	match name {
+		"state" => Some(ethcore_db::COL_STATE),
+		"headers" => Some(ethcore_db::COL_HEADERS),
+		"bodies" => Some(ethcore_db::COL_BODIES),
+		"extra" => Some(ethcore_db::COL_EXTRA),
+		"trace" => Some(ethcore_db::COL_TRACE),
+		"account_bloom" => Some(ethcore_db::COL_ACCOUNT_BLOOM),
+		"node_info" => Some(ethcore_db::COL_NODE_INFO),
+		"light_chain" => Some(ethcore_db::COL_LIGHT_CHAIN),
+		"private_transactions_state" => Some(ethcore_db::COL_PRIVATE_TRANSACTIONS_STATE),
+		_ => None,
+	}
+}
+
 fn into_secretstore_service_contract_address(s: Option<&String>) -> Result<Option<SecretStoreContractAddress>, String> {
 	match s.map(String::as_str) {
 		None | Some("none") => Ok(None),
@@ -1195,6 +1453,7 @@ mod tests {
 	use std::io::Write;
 	use std::fs::File;
 	use std::str::FromStr;
+	use ethcore_logger::Format as LogFormat;
 
 	use tempdir::TempDir;
 	use ethcore::miner::MinerOptions;
@@ -1237,6 +1496,19 @@ mod tests {
 		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Version);
 	}
 
+	#[test]
+	fn test_command_rotate_node_key() {
+		let args = vec!["parity", "tools", "rotate-node-key", "--node-key-password", "node.pwd", "--base-path", "base"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::RotateNodeKey(RotateNodeKeyCmd {
+			path: "base/network".into(),
+			encryption: Some(NodeKeyEncryption {
+				password_file: "node.pwd".into(),
+				iterations: 10240,
+			}),
+		}));
+	}
+
 	#[test]
 	fn test_command_account_new() {
 		let args = vec!["parity", "account", "new"];
@@ -1395,10 +1667,12 @@ mod tests {
 			signer_path: expected.into(),
 			support_token_api: false,
 			max_connections: 100,
+			max_payload: 5,
 		}, LogConfig {
 			color: !cfg!(windows),
 			mode: None,
 			file: None,
+			format: LogFormat::Text,
 		} ));
 	}
 
@@ -1419,17 +1693,24 @@ mod tests {
 		let conf = parse(&args);
 		let mut expected = RunCmd {
 			allow_missing_blocks: false,
+			latest_confirmations: 0,
+			page_size: 10000,
 			cache_config: Default::default(),
 			dirs: Default::default(),
 			spec: Default::default(),
 			pruning: Default::default(),
 			pruning_history: 64,
 			pruning_memory: 32,
+			pruning_finalized: false,
+			backup_conf: Default::default(),
 			daemon: None,
 			logger_config: Default::default(),
 			miner_options: Default::default(),
 			gas_price_percentile: 50,
 			poll_lifetime: 60,
+			rpc_slow_query_threshold: None,
+			rpc_batch_limits: Default::default(),
+			rpc_deprecation_warnings: false,
 			ws_conf: Default::default(),
 			http_conf: Default::default(),
 			ipc_conf: Default::default(),
@@ -1453,6 +1734,7 @@ mod tests {
 			mode: Default::default(),
 			tracing: Default::default(),
 			compaction: Default::default(),
+			db_tuning: Default::default(),
 			geth_compatibility: false,
 			experimental_rpcs: false,
 			net_settings: Default::default(),
@@ -1479,6 +1761,12 @@ mod tests {
 			on_demand_request_backoff_max: None,
 			on_demand_request_backoff_rounds_max: None,
 			on_demand_request_consecutive_failures: None,
+			light_header_history: 2048,
+			signer_webhooks: Default::default(),
+			max_reorg_depth: None,
+			shadow_execution: false,
+			consensus_faults_window: 86400,
+			node_key_encryption: None,
 		};
 		expected.secretstore_conf.enabled = cfg!(feature = "secretstore");
 		expected.secretstore_conf.http_enabled = cfg!(feature = "secretstore");
@@ -1493,11 +1781,14 @@ mod tests {
 		// when
 		let conf0 = parse(&["parity"]);
 		let conf2 = parse(&["parity", "--tx-queue-strategy", "gas_price"]);
+		let conf3 = parse(&["parity", "--tx-queue-strategy", "round_robin"]);
 
 		// then
 		assert_eq!(conf0.miner_options().unwrap(), mining_options);
 		mining_options.tx_queue_strategy = PrioritizationStrategy::GasPriceOnly;
 		assert_eq!(conf2.miner_options().unwrap(), mining_options);
+		mining_options.tx_queue_strategy = PrioritizationStrategy::SenderRoundRobin;
+		assert_eq!(conf3.miner_options().unwrap(), mining_options);
 	}
 
 	#[test]
@@ -1507,6 +1798,13 @@ mod tests {
 		assert!(conf.miner_options().is_err());
 	}
 
+	#[test]
+	fn should_fail_on_zero_work_test_difficulty() {
+		let conf = parse(&["parity", "--work-test-difficulty", "0"]);
+
+		assert!(conf.miner_options().is_err());
+	}
+
 	#[test]
 	fn should_parse_updater_options() {
 		// when
@@ -1608,6 +1906,17 @@ mod tests {
 		assert_eq!(http_conf.max_payload, 1);
 	}
 
+	#[test]
+	fn should_parse_ws_max_payload() {
+		let conf = parse(&["parity"]);
+		assert_eq!(conf.ws_config().unwrap().max_payload, 5);
+
+		let conf = parse(&["parity", "--ws-max-payload=20"]);
+		assert_eq!(conf.ws_config().unwrap().max_payload, 20);
+		let conf = parse(&["parity", "--ws-max-payload=0"]);
+		assert_eq!(conf.ws_config().unwrap().max_payload, 1);
+	}
+
 	#[test]
 	fn jsonrpc_threading_defaults() {
 		let conf = parse(&["parity"]);
@@ -1814,6 +2123,35 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn should_parse_stratum_vardiff() {
+		let conf0 = parse(&["parity", "--stratum"]);
+		let conf1 = parse(&["parity", "--stratum", "--stratum-share-interval", "15", "--stratum-retarget-window", "120"]);
+
+		assert_eq!(conf0.stratum_options().unwrap().unwrap().vardiff, None);
+		let vardiff = conf1.stratum_options().unwrap().unwrap().vardiff.unwrap();
+		assert_eq!(vardiff.target_share_interval, Duration::from_secs(15));
+		assert_eq!(vardiff.retarget_window, Duration::from_secs(120));
+
+		let conf2 = parse(&["parity", "--stratum", "--stratum-share-interval", "0"]);
+		assert!(conf2.stratum_options().is_err());
+	}
+
+	#[test]
+	fn should_parse_stratum_tls() {
+		let conf0 = parse(&["parity", "--stratum"]);
+		let conf1 = parse(&["parity", "--stratum", "--stratum-tls-cert", "stratum.pem", "--stratum-tls-key", "stratum.key"]);
+		let conf2 = parse(&["parity", "--stratum", "--stratum-tls-cert", "stratum.pem"]);
+
+		assert_eq!(conf0.stratum_options().unwrap().unwrap().tls, None);
+		assert_eq!(conf1.stratum_options().unwrap().unwrap().tls, Some(stratum::TlsConfig {
+			addr: "127.0.0.1:8010".parse().unwrap(),
+			certificate_path: "stratum.pem".into(),
+			private_key_path: "stratum.key".into(),
+		}));
+		assert!(conf2.stratum_options().is_err());
+	}
+
 	#[test]
 	fn should_apply_ports_shift() {
 		// given
@@ -1975,6 +2313,29 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn should_parse_db_tuning() {
+		use ethcore::client::{DatabaseCompactionProfile, DatabaseProfile};
+
+		let args = vec!["parity", "--db-profile=archive", "--db-column-cache=state:1024, trace:256"];
+		let conf = Configuration::parse_cli(&args).unwrap();
+		match conf.into_command().unwrap().cmd {
+			Cmd::Run(c) => {
+				assert_eq!(c.compaction, DatabaseCompactionProfile::Auto);
+				assert_eq!(c.db_tuning.profile, Some(DatabaseProfile::Archive));
+				assert_eq!(c.db_tuning.column_cache_size, vec![(0, 1024), (4, 256)].into_iter().collect());
+			},
+			_ => panic!("Should be Cmd::Run"),
+		}
+
+		let conf = parse(&["parity", "--db-profile=hdd"]);
+		assert_eq!(conf.db_compaction().unwrap(), DatabaseCompactionProfile::HDD);
+		let conf = parse(&["parity", "--db-profile=hdd", "--db-compaction=ssd"]);
+		assert_eq!(conf.db_compaction().unwrap(), DatabaseCompactionProfile::SSD);
+		let conf = parse(&["parity", "--db-column-cache=unknown:16"]);
+		assert!(conf.db_tuning().is_err());
+	}
+
 	#[test]
 	fn should_respect_only_min_peers_and_default() {
 		let args = vec!["parity", "--min-peers=5"];
@@ -2001,6 +2362,64 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn should_parse_dial_policy() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--max-dials-per-sec", "5", "--max-peers-per-subnet", "2", "--reserved-dial-weight", "30"]);
+		let conf2 = parse(&["parity", "--reserved-dial-weight", "150"]);
+
+		// then
+		assert_eq!(conf0.dial_policy(), Ok(DialPolicy::default()));
+		assert_eq!(conf1.dial_policy(), Ok(DialPolicy {
+			max_dials_per_second: 5,
+			max_peers_per_subnet: 2,
+			max_peers_per_id_bucket: 0,
+			reserved_weight: 30,
+		}));
+		assert!(conf2.dial_policy().is_err());
+	}
+
+	#[test]
+	fn should_parse_gas_price_floor() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--tx-queue-max-gas-price-floor", "5000", "--tx-queue-min-gas-price-floor", "100"]);
+		let conf2 = parse(&["parity", "--tx-queue-max-gas-price-floor", "50", "--tx-queue-min-gas-price-floor", "100"]);
+
+		// then
+		assert_eq!(conf0.gas_price_floor(), Ok(None));
+		assert_eq!(conf1.gas_price_floor(), Ok(Some(GasPriceFloorOptions {
+			min: 100.into(),
+			max: 5000.into(),
+			step: 12,
+			..Default::default()
+		})));
+		assert!(conf2.gas_price_floor().is_err());
+	}
+
+	#[test]
+	fn should_parse_bandwidth_policy() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity", "--max-upload-bandwidth", "100", "--bandwidth-weights", "2,1,0"]);
+		let conf1 = parse(&["parity", "--bandwidth-weights", "0,0,0"]);
+		let conf2 = parse(&["parity", "--bandwidth-weights", "2,1"]);
+
+		// then
+		let policy = conf0.bandwidth_policy().unwrap();
+		assert_eq!(policy.max_bytes_per_second, 102400);
+		assert_eq!((policy.sync_weight, policy.transactions_weight, policy.light_weight), (2, 1, 0));
+		assert_eq!(policy.light_protocols, vec![*b"pip"]);
+		assert!(conf1.bandwidth_policy().is_err());
+		assert!(conf2.bandwidth_policy().is_err());
+	}
+
 	#[test]
 	fn should_parse_secretstore_cors() {
 		// given
@@ -2015,4 +2434,27 @@ mod tests {
 		assert_eq!(conf1.secretstore_cors(), None);
 		assert_eq!(conf2.secretstore_cors(), Some(vec!["http://parity.io".into(),"http://something.io".into()]));
 	}
+
+	#[test]
+	fn should_parse_secretstore_tls() {
+		// given
+		let pin = "0xba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--secretstore-tls-cert", "node.pem", "--secretstore-tls-key", "node.key",
+			"--secretstore-tls-ca", "ca.pem", "--secretstore-tls-pins", pin]);
+		let conf2 = parse(&["parity", "--secretstore-tls-cert", "node.pem"]);
+
+		// then
+		assert_eq!(conf0.secretstore_tls(), Ok(None));
+		assert_eq!(conf1.secretstore_tls(), Ok(Some(SecretStoreTlsConfiguration {
+			certificate_path: "node.pem".into(),
+			private_key_path: "node.key".into(),
+			ca_certificates_path: "ca.pem".into(),
+			server_name: "secretstore".into(),
+			pinned_certificates: vec![pin[2..].parse().unwrap()],
+		})));
+		assert!(conf2.secretstore_tls().is_err());
+	}
 }
@@ -16,7 +16,7 @@
 
 use std::collections::HashMap;
 use std::path::Path;
-use ethcore::client::{ClientConfig, DatabaseCompactionProfile};
+use ethcore::client::{ClientConfig, DatabaseCompactionProfile, DatabaseTuning};
 use super::kvdb_rocksdb::{CompactionProfile, DatabaseConfig};
 
 pub fn compaction_profile(profile: &DatabaseCompactionProfile, db_path: &Path) -> CompactionProfile {
@@ -29,21 +29,23 @@ pub fn compaction_profile(profile: &DatabaseCompactionProfile, db_path: &Path) -
 
 /// Spreads the `total` (in MiB) memory budget across the db columns.
 /// If it's `None`, the default memory budget will be used for each column.
-/// 90% of the memory budget is assigned to the first column, `col0`, which is where we store the
-/// state.
-pub fn memory_per_column(total: Option<usize>) -> HashMap<u32, usize> {
+/// The share of the tuning profile (90% by default) is assigned to the first column, `col0`,
+/// which is where we store the state. Explicitly configured columns override the split.
+pub fn memory_per_column(total: Option<usize>, tuning: &DatabaseTuning) -> HashMap<u32, usize> {
 	let mut memory_per_column = HashMap::new();
 	if let Some(budget) = total {
-		// spend 90% of the memory budget on the state column, but at least 256 MiB
-		memory_per_column.insert(ethcore_db::COL_STATE, std::cmp::max(budget * 9 / 10, 256));
-		// spread the remaining 10% evenly across columns
-		let rest_budget = budget / 10 / (ethcore_db::NUM_COLUMNS as usize - 1);
+		let state_share = tuning.profile.map_or(90, |profile| profile.state_cache_share());
+		// spend the state share of the memory budget on the state column, but at least 256 MiB
+		memory_per_column.insert(ethcore_db::COL_STATE, std::cmp::max(budget * state_share / 100, 256));
+		// spread the remaining budget evenly across columns
+		let rest_budget = budget * (100 - state_share) / 100 / (ethcore_db::NUM_COLUMNS as usize - 1);
 
 		for i in 1..ethcore_db::NUM_COLUMNS {
 			// but at least 16 MiB for each column
 			memory_per_column.insert(i, std::cmp::max(rest_budget, 16));
 		}
 	}
+	memory_per_column.extend(tuning.column_cache_size.iter().map(|(col, size)| (*col, *size)));
 	memory_per_column
 }
 
@@ -66,7 +68,7 @@ pub fn memory_per_column_light(total: usize) -> HashMap<u32, usize> {
 pub fn client_db_config(client_path: &Path, client_config: &ClientConfig) -> DatabaseConfig {
 	let mut client_db_config = DatabaseConfig::with_columns(ethcore_db::NUM_COLUMNS);
 
-	client_db_config.memory_budget = memory_per_column(client_config.db_cache_size);
+	client_db_config.memory_budget = memory_per_column(client_config.db_cache_size, &client_config.db_tuning);
 	client_db_config.compaction = compaction_profile(&client_config.db_compaction, &client_path);
 
 	client_db_config
@@ -23,12 +23,12 @@ extern crate tempdir;
 
 use std::{io, fs};
 use std::sync::Arc;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use blooms_db;
 use ethcore_db::NUM_COLUMNS;
 use ethcore::client::{ClientConfig, DatabaseCompactionProfile};
 use kvdb::KeyValueDB;
-use self::ethcore_blockchain::{BlockChainDBHandler, BlockChainDB};
+use self::ethcore_blockchain::{BlockChainDBHandler, BlockChainDB, DatabaseBackup};
 use self::kvdb_rocksdb::{Database, DatabaseConfig};
 
 use cache::CacheConfig;
@@ -39,10 +39,15 @@ mod helpers;
 
 pub use self::migration::migrate;
 
+/// Number of key-value pairs written at once when taking a backup.
+const BACKUP_BATCH_SIZE: usize = 1024;
+
 struct AppDB {
 	key_value: Arc<dyn KeyValueDB>,
 	blooms: blooms_db::Database,
 	trace_blooms: blooms_db::Database,
+	blooms_path: PathBuf,
+	trace_blooms_path: PathBuf,
 }
 
 impl BlockChainDB for AppDB {
@@ -57,6 +62,60 @@ impl BlockChainDB for AppDB {
 	fn trace_blooms(&self) -> &blooms_db::Database {
 		&self.trace_blooms
 	}
+
+	fn backup(&self) -> io::Result<Box<dyn DatabaseBackup + '_>> {
+		// Checkpoints are not exposed by kvdb-rocksdb, but its iterators are created eagerly and
+		// read from an implicit RocksDB snapshot, so opening one per column captures the database.
+		let columns = (0..NUM_COLUMNS).map(|col| self.key_value.iter(col)).collect();
+		Ok(Box::new(AppDBBackup {
+			columns,
+			blooms_path: &self.blooms_path,
+			trace_blooms_path: &self.trace_blooms_path,
+		}))
+	}
+}
+
+/// Consistent view of an `AppDB`, copied column by column into a new database.
+struct AppDBBackup<'a> {
+	columns: Vec<Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>>,
+	blooms_path: &'a Path,
+	trace_blooms_path: &'a Path,
+}
+
+impl<'a> DatabaseBackup for AppDBBackup<'a> {
+	fn write(self: Box<Self>, path: &Path) -> io::Result<()> {
+		fs::create_dir_all(path)?;
+		let backup = Database::open(&DatabaseConfig::with_columns(NUM_COLUMNS), &path.to_string_lossy())?;
+		let AppDBBackup { columns, blooms_path, trace_blooms_path } = *self;
+		for (col, iter) in columns.into_iter().enumerate() {
+			let mut batch = backup.transaction();
+			for (key, value) in iter {
+				batch.put(col as u32, &key, &value);
+				if batch.ops.len() >= BACKUP_BATCH_SIZE {
+					backup.write(batch)?;
+					batch = backup.transaction();
+				}
+			}
+			backup.write(batch)?;
+		}
+		backup.flush()?;
+
+		// Blooms are indexed by block number, entries for blocks imported since the view was
+		// captured are harmless and overwritten when those blocks are imported again.
+		copy_dir(blooms_path, &path.join("blooms"))?;
+		copy_dir(trace_blooms_path, &path.join("trace_blooms"))
+	}
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+	fs::create_dir_all(to)?;
+	for entry in fs::read_dir(from)? {
+		let entry = entry?;
+		if entry.file_type()?.is_file() {
+			fs::copy(entry.path(), to.join(entry.file_name()))?;
+		}
+	}
+	Ok(())
 }
 
 /// Create a restoration db handler using the config generated by `client_path` and `client_config`.
@@ -95,6 +154,19 @@ pub fn open_db_light(
 	open_database(client_path, &db_config)
 }
 
+/// Open the client DB without starting the client.
+pub fn open_db_offline(
+	client_path: &str,
+	compaction: &DatabaseCompactionProfile
+) -> io::Result<Arc<dyn BlockChainDB>> {
+	let db_config = DatabaseConfig {
+		compaction: helpers::compaction_profile(&compaction, Path::new(client_path)),
+		.. DatabaseConfig::with_columns(NUM_COLUMNS)
+	};
+
+	open_database(client_path, &db_config)
+}
+
 pub fn open_database(client_path: &str, config: &DatabaseConfig) -> io::Result<Arc<dyn BlockChainDB>> {
 	let path = Path::new(client_path);
 
@@ -105,9 +177,38 @@ pub fn open_database(client_path: &str, config: &DatabaseConfig) -> io::Result<A
 
 	let db = AppDB {
 		key_value: Arc::new(Database::open(&config, client_path)?),
-		blooms: blooms_db::Database::open(blooms_path)?,
-		trace_blooms: blooms_db::Database::open(trace_blooms_path)?,
+		blooms: blooms_db::Database::open(&blooms_path)?,
+		trace_blooms: blooms_db::Database::open(&trace_blooms_path)?,
+		blooms_path,
+		trace_blooms_path,
 	};
 
 	Ok(Arc::new(db))
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::tempdir::TempDir;
+
+	#[test]
+	fn should_reopen_backup_taken_before_later_writes() {
+		let tempdir = TempDir::new("").unwrap();
+		let config = DatabaseConfig::with_columns(NUM_COLUMNS);
+		let db = open_database(&tempdir.path().join("db").to_string_lossy(), &config).unwrap();
+		let mut batch = db.key_value().transaction();
+		batch.put(0, b"key", b"value");
+		db.key_value().write(batch).unwrap();
+
+		let backup = db.backup().unwrap();
+		let mut batch = db.key_value().transaction();
+		batch.put(0, b"later", b"value");
+		db.key_value().write(batch).unwrap();
+		let backup_path = tempdir.path().join("backup");
+		backup.write(&backup_path).unwrap();
+
+		let restored = open_database(&backup_path.to_string_lossy(), &config).unwrap();
+		assert_eq!(&*restored.key_value().get(0, b"key").unwrap().unwrap(), &b"value"[..]);
+		assert!(restored.key_value().get(0, b"later").unwrap().is_none());
+	}
+}
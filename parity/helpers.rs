@@ -115,6 +115,8 @@ pub fn to_pending_set(s: &str) -> Result<PendingSet, String> {
 pub fn to_queue_strategy(s: &str) -> Result<PrioritizationStrategy, String> {
 	match s {
 		"gas_price" => Ok(PrioritizationStrategy::GasPriceOnly),
+		"fifo" => Ok(PrioritizationStrategy::Fifo),
+		"round_robin" => Ok(PrioritizationStrategy::SenderRoundRobin),
 		other => Err(format!("Invalid queue strategy: {}", other)),
 	}
 }
@@ -224,6 +226,9 @@ pub fn default_network_config() -> ::sync::NetworkConfiguration {
 		reserved_nodes: Vec::new(),
 		allow_non_reserved: true,
 		client_version: ::parity_version::version(),
+		max_session_age: None,
+		dial_policy: Default::default(),
+		bandwidth_policy: Default::default(),
 	}
 }
 
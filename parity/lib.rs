@@ -112,6 +112,7 @@ mod helpers;
 mod informant;
 mod light_helpers;
 mod modules;
+mod node_key;
 mod params;
 mod presale;
 mod rpc;
@@ -216,6 +217,7 @@ fn execute<Cr, Rr>(
 		},
 		Cmd::Version => Ok(ExecutionAction::Instant(Some(Args::print_version()))),
 		Cmd::Hash(maybe_file) => print_hash_of(maybe_file).map(|s| ExecutionAction::Instant(Some(s))),
+		Cmd::RotateNodeKey(rotate_cmd) => node_key::execute(rotate_cmd).map(|s| ExecutionAction::Instant(Some(s))),
 		Cmd::Account(account_cmd) => account::execute(account_cmd).map(|s| ExecutionAction::Instant(Some(s))),
 		Cmd::ImportPresaleWallet(presale_cmd) => presale::execute(presale_cmd).map(|s| ExecutionAction::Instant(Some(s))),
 		Cmd::Blockchain(blockchain_cmd) => blockchain::execute(blockchain_cmd).map(|_| ExecutionAction::Instant(None)),
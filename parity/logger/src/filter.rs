@@ -0,0 +1,129 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-target log level filter which can be changed at runtime.
+
+use std::fmt;
+use rlog::{Level, LevelFilter};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Directive {
+	target: Option<String>,
+	level: LevelFilter,
+}
+
+/// Log levels by target, in the same format as `RUST_LOG`, e.g. `sync=debug,rpc=trace,info`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+	/// Directives sorted by target length, the most specific one last.
+	directives: Vec<Directive>,
+}
+
+impl LogFilter {
+	/// Create a filter logging everything up to `level`.
+	pub fn new(level: LevelFilter) -> Self {
+		LogFilter {
+			directives: vec![Directive { target: None, level }],
+		}
+	}
+
+	/// Set the level for given target (or the default level if `target` is `None`).
+	pub fn set(&mut self, target: Option<&str>, level: LevelFilter) {
+		let target = target.map(str::to_owned);
+		match self.directives.iter_mut().find(|d| d.target == target) {
+			Some(directive) => directive.level = level,
+			None => {
+				self.directives.push(Directive { target, level });
+				self.directives.sort_by_key(|d| d.target.as_ref().map_or(0, String::len));
+			},
+		}
+	}
+
+	/// Apply a comma-separated list of `target=level`, `target` or `level` directives.
+	pub fn parse(&mut self, spec: &str) -> Result<(), String> {
+		for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+			let mut parts = directive.splitn(2, '=');
+			let first = parts.next().expect("splitn always yields at least one item; qed");
+			match parts.next() {
+				Some(level) => self.set(Some(first), parse_level(level)?),
+				None => match first.parse() {
+					Ok(level) => self.set(None, level),
+					Err(_) => self.set(Some(first), LevelFilter::Trace),
+				},
+			}
+		}
+		Ok(())
+	}
+
+	/// Returns true if a record with given target and level should be logged.
+	pub fn enabled(&self, target: &str, level: Level) -> bool {
+		self.directives.iter().rev()
+			.find(|d| d.target.as_ref().map_or(true, |t| target.starts_with(t.as_str())))
+			.map_or(false, |d| level <= d.level)
+	}
+
+	/// The most verbose level enabled for any target.
+	pub fn max_level(&self) -> LevelFilter {
+		self.directives.iter().map(|d| d.level).max().unwrap_or(LevelFilter::Off)
+	}
+}
+
+impl fmt::Display for LogFilter {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let directives: Vec<_> = self.directives.iter().map(|d| match d.target {
+			Some(ref target) => format!("{}={}", target, d.level.to_string().to_lowercase()),
+			None => d.level.to_string().to_lowercase(),
+		}).collect();
+		write!(f, "{}", directives.join(","))
+	}
+}
+
+/// Parse a level name (`off`, `error`, `warn`, `info`, `debug` or `trace`).
+pub fn parse_level(level: &str) -> Result<LevelFilter, String> {
+	level.trim().parse().map_err(|_| format!("Invalid log level: {}", level))
+}
+
+#[cfg(test)]
+mod tests {
+	use rlog::{Level, LevelFilter};
+	use super::LogFilter;
+
+	#[test]
+	fn should_use_most_specific_directive() {
+		let mut filter = LogFilter::new(LevelFilter::Info);
+		filter.parse("sync=debug,sync::light=warn,rpc").unwrap();
+
+		assert!(filter.enabled("client", Level::Info));
+		assert!(!filter.enabled("client", Level::Debug));
+		assert!(filter.enabled("sync", Level::Debug));
+		assert!(!filter.enabled("sync::light", Level::Info));
+		assert!(filter.enabled("rpc", Level::Trace));
+		assert_eq!(filter.max_level(), LevelFilter::Trace);
+	}
+
+	#[test]
+	fn should_override_existing_directive() {
+		let mut filter = LogFilter::new(LevelFilter::Info);
+		filter.parse("sync=debug").unwrap();
+		filter.set(Some("sync"), LevelFilter::Error);
+		filter.set(None, LevelFilter::Warn);
+
+		assert!(!filter.enabled("sync", Level::Warn));
+		assert!(!filter.enabled("client", Level::Info));
+		assert_eq!(filter.to_string(), "warn,sync=error");
+		assert!(filter.parse("sync=loud").is_err());
+	}
+}
@@ -19,32 +19,58 @@
 extern crate ansi_term;
 extern crate arrayvec;
 extern crate atty;
-extern crate env_logger;
+#[macro_use]
 extern crate log as rlog;
 extern crate parking_lot;
 extern crate regex;
+extern crate serde_json;
 extern crate time;
 
 #[macro_use]
 extern crate lazy_static;
 
+mod filter;
 mod rotating;
 
-use std::{env, thread, fs};
+use std::{env, thread, fs, io};
+use std::str::FromStr;
 use std::sync::{Weak, Arc};
 use std::io::Write;
-use env_logger::{Builder as LogBuilder, Formatter};
 use regex::Regex;
 use ansi_term::Colour;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
+use rlog::{Level, LevelFilter, Log, Metadata, Record};
 
+pub use filter::{LogFilter, parse_level};
 pub use rotating::{RotatingLogger, init_log};
 
+/// Output format of log records.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+	/// Human readable lines.
+	Text,
+	/// One JSON object per record, for log aggregation.
+	Json,
+}
+
+impl FromStr for Format {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"text" => Ok(Format::Text),
+			"json" => Ok(Format::Json),
+			other => Err(format!("Invalid log format: {}. Expected 'text' or 'json'.", other)),
+		}
+	}
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Config {
 	pub mode: Option<String>,
 	pub color: bool,
 	pub file: Option<String>,
+	pub format: Format,
 }
 
 impl Default for Config {
@@ -53,42 +79,126 @@ impl Default for Config {
 			mode: None,
 			color: !cfg!(windows),
 			file: None,
+			format: Format::Text,
 		}
 	}
 }
 
 lazy_static! {
 	static ref ROTATING_LOGGER : Mutex<Weak<RotatingLogger>> = Mutex::new(Default::default());
+	static ref LOG_FILTER: RwLock<LogFilter> = RwLock::new(LogFilter::new(LevelFilter::Info));
+}
+
+struct Logger {
+	format: Format,
+	color: bool,
+	isatty: bool,
+	file: Option<fs::File>,
+	rotating: Arc<RotatingLogger>,
+}
+
+impl Logger {
+	fn text(&self, record: &Record) -> (String, String) {
+		let timestamp = time::strftime("%Y-%m-%d %H:%M:%S %Z", &time::now()).unwrap();
+
+		let with_color = if rlog::max_level() <= LevelFilter::Info {
+			format!("{} {}", Colour::Black.bold().paint(timestamp), record.args())
+		} else {
+			let name = thread::current().name().map_or_else(Default::default, |x| format!("{}", Colour::Blue.bold().paint(x)));
+			format!("{} {} {} {}  {}", Colour::Black.bold().paint(timestamp), name, record.level(), record.target(), record.args())
+		};
+
+		let removed_color = kill_color(with_color.as_ref());
+
+		match self.color {
+			true => (with_color, removed_color),
+			false => (removed_color.clone(), removed_color),
+		}
+	}
+}
+
+impl Log for Logger {
+	fn enabled(&self, metadata: &Metadata) -> bool {
+		LOG_FILTER.read().enabled(metadata.target(), metadata.level())
+	}
+
+	fn log(&self, record: &Record) {
+		if !self.enabled(record.metadata()) {
+			return;
+		}
+
+		let (ret, plain) = match self.format {
+			Format::Text => self.text(record),
+			Format::Json => {
+				let json = json_record(record, &time::now_utc().rfc3339().to_string(), thread::current().name());
+				(json.clone(), json)
+			},
+		};
+
+		if let Some(mut file) = self.file.as_ref() {
+			// ignore errors - there's nothing we can do
+			let _ = file.write_all(plain.as_bytes());
+			let _ = file.write_all(b"\n");
+		}
+		self.rotating.append(plain);
+		if !self.isatty && record.level() <= Level::Info && atty::is(atty::Stream::Stdout) {
+			// duplicate INFO/WARN output to console
+			println!("{}", ret);
+		}
+
+		let _ = writeln!(io::stderr(), "{}", ret);
+	}
+
+	fn flush(&self) {
+		let _ = io::stderr().flush();
+	}
+}
+
+/// Serialize a log record as a single-line JSON object.
+fn json_record(record: &Record, timestamp: &str, thread: Option<&str>) -> String {
+	let mut object = serde_json::Map::new();
+	object.insert("timestamp".into(), timestamp.into());
+	object.insert("level".into(), record.level().to_string().into());
+	object.insert("target".into(), record.target().into());
+	if let Some(thread) = thread {
+		object.insert("threadName".into(), thread.into());
+	}
+	let mut fields = serde_json::Map::new();
+	fields.insert("message".into(), record.args().to_string().into());
+	object.insert("fields".into(), fields.into());
+	serde_json::Value::Object(object).to_string()
 }
 
 /// Sets up the logger
 pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
-	use rlog::*;
-
 	let mut levels = String::new();
-	let mut builder = LogBuilder::new();
+	let mut filter = LogFilter::new(LevelFilter::Info);
 	// Disable info logging by default for some modules:
-	builder.filter(Some("ws"), LevelFilter::Warn);
-	builder.filter(Some("hyper"), LevelFilter::Warn);
-	builder.filter(Some("rustls"), LevelFilter::Error);
-	// Enable info for others.
-	builder.filter(None, LevelFilter::Info);
+	filter.set(Some("ws"), LevelFilter::Warn);
+	filter.set(Some("hyper"), LevelFilter::Warn);
+	filter.set(Some("rustls"), LevelFilter::Error);
 
+	// a malformed RUST_LOG is ignored rather than preventing startup, it's reported once the logger is set up.
+	let mut invalid_env = None;
 	if let Ok(lvl) = env::var("RUST_LOG") {
-		levels.push_str(&lvl);
-		levels.push_str(",");
-		builder.parse(&lvl);
+		let mut env_filter = filter.clone();
+		match env_filter.parse(&lvl) {
+			Ok(()) => {
+				levels.push_str(&lvl);
+				levels.push_str(",");
+				filter = env_filter;
+			},
+			Err(err) => invalid_env = Some(err),
+		}
 	}
 
 	if let Some(ref s) = config.mode {
 		levels.push_str(s);
-		builder.parse(s);
+		filter.parse(s)?;
 	}
 
 	let isatty = atty::is(atty::Stream::Stderr);
-	let enable_color = config.color && isatty;
 	let logs = Arc::new(RotatingLogger::new(levels));
-	let logger = logs.clone();
 	let mut open_options = fs::OpenOptions::new();
 
 	let maybe_file = match config.file.as_ref() {
@@ -98,42 +208,24 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 		None => None,
 	};
 
-	let format = move |buf: &mut Formatter, record: &Record| {
-		let timestamp = time::strftime("%Y-%m-%d %H:%M:%S %Z", &time::now()).unwrap();
-
-		let with_color = if max_level() <= LevelFilter::Info {
-			format!("{} {}", Colour::Black.bold().paint(timestamp), record.args())
-		} else {
-			let name = thread::current().name().map_or_else(Default::default, |x| format!("{}", Colour::Blue.bold().paint(x)));
-			format!("{} {} {} {}  {}", Colour::Black.bold().paint(timestamp), name, record.level(), record.target(), record.args())
-		};
-
-		let removed_color = kill_color(with_color.as_ref());
-
-		let ret = match enable_color {
-			true => with_color,
-			false => removed_color.clone(),
-		};
-
-		if let Some(mut file) = maybe_file.as_ref() {
-			// ignore errors - there's nothing we can do
-			let _ = file.write_all(removed_color.as_bytes());
-			let _ = file.write_all(b"\n");
-		}
-		logger.append(removed_color);
-		if !isatty && record.level() <= Level::Info && atty::is(atty::Stream::Stdout) {
-			// duplicate INFO/WARN output to console
-			println!("{}", ret);
-		}
-
-		writeln!(buf, "{}", ret)
-    };
+	let logger = Logger {
+		format: config.format,
+		color: config.color && isatty,
+		isatty,
+		file: maybe_file,
+		rotating: logs.clone(),
+	};
 
-	builder.format(format);
-	builder.try_init()
-		.and_then(|_| {
+	let max_level = filter.max_level();
+	rlog::set_boxed_logger(Box::new(logger))
+		.map(|_| {
+			*LOG_FILTER.write() = filter;
+			rlog::set_max_level(max_level);
 			*ROTATING_LOGGER.lock() = Arc::downgrade(&logs);
-			Ok(logs)
+			if let Some(err) = invalid_env {
+				warn!(target: "logger", "Ignoring RUST_LOG: {}. Using the default log levels.", err);
+			}
+			logs
 		})
 		// couldn't create new logger - try to fall back on previous logger.
 		.or_else(|err| match ROTATING_LOGGER.lock().upgrade() {
@@ -143,6 +235,18 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 		})
 }
 
+/// Change the log level of given target (or the default level if `target` is `None`) at runtime.
+pub fn set_log_level(target: Option<&str>, level: &str) -> Result<(), String> {
+	let level = parse_level(level)?;
+	let mut filter = LOG_FILTER.write();
+	filter.set(target, level);
+	rlog::set_max_level(filter.max_level());
+	if let Some(logs) = ROTATING_LOGGER.lock().upgrade() {
+		logs.set_levels(filter.to_string());
+	}
+	Ok(())
+}
+
 fn kill_color(s: &str) -> String {
 	lazy_static! {
 		static ref RE: Regex = Regex::new("\x1b\\[[^m]+m").unwrap();
@@ -150,6 +254,23 @@ fn kill_color(s: &str) -> String {
 	RE.replace_all(s, "").to_string()
 }
 
+#[test]
+fn should_format_json_record() {
+	let json = json_record(
+		&Record::builder().args(format_args!("Imported #{}", 1)).level(Level::Info).target("import").build(),
+		"2020-01-01T00:00:00Z",
+		Some("IO Worker #1"),
+	);
+	assert_eq!(json, r#"{"fields":{"message":"Imported #1"},"level":"INFO","target":"import","threadName":"IO Worker #1","timestamp":"2020-01-01T00:00:00Z"}"#);
+}
+
+#[test]
+fn should_parse_format() {
+	assert_eq!("json".parse::<Format>(), Ok(Format::Json));
+	assert_eq!("text".parse::<Format>(), Ok(Format::Text));
+	assert!("xml".parse::<Format>().is_err());
+}
+
 #[test]
 fn should_remove_colour() {
 	let before = "test";
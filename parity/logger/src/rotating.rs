@@ -16,23 +16,13 @@
 
 //! Common log helper functions
 
-use std::env;
-use rlog::LevelFilter;
-use env_logger::Builder as LogBuilder;
 use arrayvec::ArrayVec;
 
 use parking_lot::{RwLock, RwLockReadGuard};
 
 lazy_static! {
 	static ref LOG_DUMMY: () = {
-		let mut builder = LogBuilder::new();
-		builder.filter(None, LevelFilter::Info);
-
-		if let Ok(log) = env::var("RUST_LOG") {
-			builder.parse(&log);
-		}
-
-		if !builder.try_init().is_ok() {
+		if super::setup_log(&Default::default()).is_err() {
 			println!("logger initialization failed!");
 		}
 	};
@@ -48,7 +38,7 @@ const LOG_SIZE : usize = 128;
 /// Logger implementation that keeps up to `LOG_SIZE` log elements.
 pub struct RotatingLogger {
 	/// Defined logger levels
-	levels: String,
+	levels: RwLock<String>,
 	/// Logs array. Latest log is always at index 0
 	logs: RwLock<ArrayVec<[String; LOG_SIZE]>>,
 }
@@ -59,7 +49,7 @@ impl RotatingLogger {
 	/// It does not enforce levels - it's just read only.
 	pub fn new(levels: String) -> Self {
 		RotatingLogger {
-			levels: levels,
+			levels: RwLock::new(levels),
 			logs: RwLock::new(ArrayVec::<[_; LOG_SIZE]>::new()),
 		}
 	}
@@ -74,8 +64,13 @@ impl RotatingLogger {
 	}
 
 	/// Return levels
-	pub fn levels(&self) -> &str {
-		&self.levels
+	pub fn levels(&self) -> String {
+		self.levels.read().clone()
+	}
+
+	/// Update levels after they were changed at runtime
+	pub fn set_levels(&self, levels: String) {
+		*self.levels.write() = levels;
 	}
 
 	/// Return logs
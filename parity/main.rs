@@ -190,7 +190,7 @@ fn main_direct(force_can_restart: bool) -> i32 {
 		parity_ethereum::Configuration::parse_cli(&args).unwrap_or_else(|e| e.exit())
 	};
 
-	let logger = setup_log(&conf.logger_config()).unwrap_or_else(|e| {
+	let logger = conf.logger_config().and_then(|config| setup_log(&config)).unwrap_or_else(|e| {
 		eprintln!("{}", e);
 		process::exit(2)
 	});
@@ -0,0 +1,198 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Devp2p node key storage: optional encryption with the account key file format and key rotation.
+
+use std::{fs, io};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use ethkey::Password;
+use ethstore::Crypto;
+use parity_crypto::publickey::{Generator, Random, Secret};
+#[cfg(not(unix))]
+use path::restrict_permissions_owner;
+use sync::NetworkConfiguration;
+use helpers::password_from_file;
+
+/// Plain node key, as written by the network service.
+const PLAIN_KEY_FILE: &str = "key";
+/// Node key encrypted like an account key file.
+const ENCRYPTED_KEY_FILE: &str = "key.json";
+
+/// Encryption of the node key at rest.
+#[derive(Debug, PartialEq, Clone)]
+pub struct NodeKeyEncryption {
+	/// File with the password the key is encrypted with.
+	pub password_file: String,
+	/// Number of key derivation iterations.
+	pub iterations: u32,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RotateNodeKeyCmd {
+	/// Directory holding the node key.
+	pub path: String,
+	/// Encryption of the new key, it's stored in plain text without it.
+	pub encryption: Option<NodeKeyEncryption>,
+}
+
+/// Replace the node key with a new random key. The node advertises the new id on the same
+/// endpoints after a restart.
+pub fn execute(cmd: RotateNodeKeyCmd) -> Result<String, String> {
+	let dir = Path::new(&cmd.path);
+	let keys = Random.generate().map_err(|e| format!("Error generating node key: {}", e))?;
+
+	match cmd.encryption {
+		Some(encryption) => {
+			let password = password_from_file(encryption.password_file)?;
+			save_encrypted(dir, keys.secret(), &password, encryption.iterations)?;
+		},
+		None => {
+			write_key_file(&dir.join(PLAIN_KEY_FILE), keys.secret().to_hex().as_bytes())?;
+			remove_if_exists(&dir.join(ENCRYPTED_KEY_FILE))?;
+		},
+	}
+
+	Ok(format!("New node id: {:x}", keys.public()))
+}
+
+/// Use the encrypted node key, unless a key is given explicitly.
+pub fn apply(net_conf: &mut NetworkConfiguration, encryption: Option<&NodeKeyEncryption>) -> Result<(), String> {
+	let (encryption, path) = match (encryption, net_conf.config_path.as_ref(), net_conf.use_secret.as_ref()) {
+		(Some(encryption), Some(path), None) => (encryption, PathBuf::from(path)),
+		_ => return Ok(()),
+	};
+	let password = password_from_file(encryption.password_file.clone())?;
+	net_conf.use_secret = Some(load_or_create(&path, &password, encryption.iterations)?);
+	Ok(())
+}
+
+/// Load the encrypted node key from `dir`, creating it if necessary.
+///
+/// A plain key left by the network service is encrypted and removed, so the node keeps its id.
+pub fn load_or_create(dir: &Path, password: &Password, iterations: u32) -> Result<Secret, String> {
+	let encrypted = dir.join(ENCRYPTED_KEY_FILE);
+	if encrypted.exists() {
+		let json = fs::read_to_string(&encrypted).map_err(|e| format!("Error reading node key file {}: {}", encrypted.display(), e))?;
+		let crypto: Crypto = json.parse().map_err(|e| format!("Invalid node key file {}: {:?}", encrypted.display(), e))?;
+		return crypto.secret(password).map_err(|e| format!("Unable to decrypt node key: {}", e));
+	}
+
+	let plain = dir.join(PLAIN_KEY_FILE);
+	let secret = if plain.exists() {
+		let hex = fs::read_to_string(&plain).map_err(|e| format!("Error reading node key file {}: {}", plain.display(), e))?;
+		let secret = hex.trim().parse::<Secret>().map_err(|e| format!("Invalid node key file {}: {:?}", plain.display(), e))?;
+		info!("Encrypting the node key in {}", dir.display());
+		secret
+	} else {
+		Random.generate().map_err(|e| format!("Error generating node key: {}", e))?.secret().clone()
+	};
+
+	save_encrypted(dir, &secret, password, iterations)?;
+	Ok(secret)
+}
+
+fn save_encrypted(dir: &Path, secret: &Secret, password: &Password, iterations: u32) -> Result<(), String> {
+	let crypto = Crypto::with_secret(secret, password, iterations).map_err(|e| format!("Error encrypting node key: {}", e))?;
+	write_key_file(&dir.join(ENCRYPTED_KEY_FILE), String::from(crypto).as_bytes())?;
+	remove_if_exists(&dir.join(PLAIN_KEY_FILE))
+}
+
+fn write_key_file(path: &PathBuf, contents: &[u8]) -> Result<(), String> {
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir).map_err(|e| format!("Error creating key directory {}: {}", dir.display(), e))?;
+	}
+	create_key_file(path)
+		.and_then(|mut file| file.write_all(contents))
+		.map_err(|e| format!("Error writing node key file {}: {}", path.display(), e))
+}
+
+/// Create or truncate the key file, readable by the owner only before anything is written to it.
+#[cfg(unix)]
+fn create_key_file(path: &Path) -> io::Result<fs::File> {
+	use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+	let file = fs::OpenOptions::new()
+		.write(true)
+		.create(true)
+		.truncate(true)
+		.mode(0o600)
+		.open(path)?;
+	// `mode` only applies to new files, a replaced key file could be more permissive
+	file.set_permissions(fs::Permissions::from_mode(0o600))?;
+	Ok(file)
+}
+
+/// Create or truncate the key file, readable by the owner only before anything is written to it.
+#[cfg(not(unix))]
+fn create_key_file(path: &Path) -> io::Result<fs::File> {
+	let file = fs::File::create(path)?;
+	if let Err(e) = restrict_permissions_owner(path, true, false) {
+		warn!("Failed to modify permissions of the node key file ({})", e);
+	}
+	Ok(file)
+}
+
+fn remove_if_exists(path: &PathBuf) -> Result<(), String> {
+	if path.exists() {
+		fs::remove_file(path).map_err(|e| format!("Error removing node key file {}: {}", path.display(), e))?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use tempdir::TempDir;
+	use super::{load_or_create, write_key_file, PLAIN_KEY_FILE, ENCRYPTED_KEY_FILE};
+
+	#[test]
+	fn should_encrypt_existing_plain_key() {
+		let tempdir = TempDir::new("").unwrap();
+		let secret = "6f7b0d801bc7b5ce7bbd930b84fd0369b3eb25d09be58d64ba811091046f3aa2";
+		fs::write(tempdir.path().join(PLAIN_KEY_FILE), secret).unwrap();
+
+		let password = "password".into();
+		let loaded = load_or_create(tempdir.path(), &password, 1024).unwrap();
+		assert_eq!(loaded, secret.parse().unwrap());
+		assert!(!tempdir.path().join(PLAIN_KEY_FILE).exists());
+		assert!(tempdir.path().join(ENCRYPTED_KEY_FILE).exists());
+
+		assert_eq!(load_or_create(tempdir.path(), &password, 1024).unwrap(), loaded);
+		assert!(load_or_create(tempdir.path(), &"wrong".into(), 1024).is_err());
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn should_restrict_key_file_to_owner() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let tempdir = TempDir::new("").unwrap();
+		let path = tempdir.path().join(ENCRYPTED_KEY_FILE);
+		fs::write(&path, "").unwrap();
+		fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+		// an existing key file is restricted before the new key is written to it
+		write_key_file(&path, b"{}").unwrap();
+		assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+
+		let tempdir = TempDir::new("").unwrap();
+		load_or_create(tempdir.path(), &"password".into(), 1024).unwrap();
+		let mode = fs::metadata(tempdir.path().join(ENCRYPTED_KEY_FILE)).unwrap().permissions().mode();
+		assert_eq!(mode & 0o777, 0o600);
+	}
+}
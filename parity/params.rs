@@ -24,8 +24,10 @@ use parity_runtime::Executor;
 use hash_fetch::fetch::Client as FetchClient;
 use journaldb::Algorithm;
 use miner::gas_pricer::GasPricer;
+use miner::work_notify::DEFAULT_NOTIFY_RETRIES;
 use miner::gas_price_calibrator::{GasPriceCalibratorOptions, GasPriceCalibrator};
 use parity_version::version_data;
+use parity_rpc::signer::DEFAULT_REMOTE_SIGNER_TIMEOUT;
 use user_defaults::UserDefaults;
 use types::client_types::Mode;
 
@@ -245,6 +247,7 @@ pub struct AccountsConfig {
 	pub password_files: Vec<String>,
 	pub unlocked_accounts: Vec<Address>,
 	pub enable_fast_unlock: bool,
+	pub max_unlock_duration: Option<u64>,
 }
 
 impl Default for AccountsConfig {
@@ -256,6 +259,7 @@ impl Default for AccountsConfig {
 			password_files: Vec::new(),
 			unlocked_accounts: Vec::new(),
 			enable_fast_unlock: false,
+			max_unlock_duration: None,
 		}
 	}
 }
@@ -305,9 +309,12 @@ impl GasPricerConfig {
 pub struct MinerExtras {
 	pub author: Address,
 	pub engine_signer: Address,
+	pub engine_signer_remote: Option<String>,
+	pub engine_signer_remote_timeout: Duration,
 	pub extra_data: Vec<u8>,
 	pub gas_range_target: (U256, U256),
 	pub work_notify: Vec<String>,
+	pub work_notify_retries: usize,
 	pub local_accounts: HashSet<Address>,
 }
 
@@ -316,9 +323,12 @@ impl Default for MinerExtras {
 		MinerExtras {
 			author: Default::default(),
 			engine_signer: Default::default(),
+			engine_signer_remote: None,
+			engine_signer_remote_timeout: DEFAULT_REMOTE_SIGNER_TIMEOUT,
 			extra_data: version_data(),
 			gas_range_target: (8_000_000.into(), 10_000_000.into()),
 			work_notify: Default::default(),
+			work_notify_retries: DEFAULT_NOTIFY_RETRIES,
 			local_accounts: Default::default(),
 		}
 	}
@@ -17,6 +17,7 @@
 use std::io;
 use std::sync::Arc;
 use std::path::PathBuf;
+use std::time::Duration;
 use std::collections::HashSet;
 
 use dir::default_data_path;
@@ -24,7 +25,7 @@ use dir::helpers::replace_home;
 use helpers::parity_ipc_path;
 use jsonrpc_core::MetaIoHandler;
 use parity_runtime::Executor;
-use parity_rpc::informant::{RpcStats, Middleware};
+use parity_rpc::informant::{BatchLimits, RpcStats, Middleware};
 use parity_rpc::{self as rpc, Metadata, DomainsValidation};
 use rpc_apis::{self, ApiSet};
 
@@ -103,6 +104,8 @@ pub struct WsConfiguration {
 	pub port: u16,
 	pub apis: ApiSet,
 	pub max_connections: usize,
+	/// Sets the maximum size of a message in megabytes (default is 5 MiB).
+	pub max_payload: usize,
 	pub origins: Option<Vec<String>>,
 	pub hosts: Option<Vec<String>>,
 	pub signer_path: PathBuf,
@@ -118,6 +121,7 @@ impl Default for WsConfiguration {
 			port: 8546,
 			apis: ApiSet::UnsafeContext,
 			max_connections: 100,
+			max_payload: 5,
 			origins: Some(vec!["parity://*".into(),"chrome-extension://*".into(), "moz-extension://*".into()]),
 			hosts: Some(Vec::new()),
 			signer_path: replace_home(&data_dir, "$BASE/signer").into(),
@@ -147,6 +151,9 @@ pub struct Dependencies<D: rpc_apis::Dependencies> {
 	pub apis: Arc<D>,
 	pub executor: Executor,
 	pub stats: Arc<RpcStats>,
+	pub slow_query_threshold: Option<Duration>,
+	pub batch_limits: BatchLimits,
+	pub deprecation_warnings: bool,
 }
 
 pub fn new_ws<D: rpc_apis::Dependencies>(
@@ -165,7 +172,13 @@ pub fn new_ws<D: rpc_apis::Dependencies>(
 	let handler = {
 		let mut handler = MetaIoHandler::with_middleware((
 			rpc::WsDispatcher::new(full_handler),
-			Middleware::new(deps.stats.clone(), deps.apis.activity_notifier())
+			Middleware::new(
+				deps.stats.clone(),
+				deps.apis.activity_notifier(),
+				deps.slow_query_threshold,
+				deps.batch_limits.clone(),
+				deps.deprecation_warnings,
+			)
 		));
 		let apis = conf.apis.list_apis();
 		deps.apis.extend_with_set(&mut handler, &apis);
@@ -190,6 +203,7 @@ pub fn new_ws<D: rpc_apis::Dependencies>(
 		allowed_origins,
 		allowed_hosts,
 		conf.max_connections,
+		conf.max_payload,
 		rpc::WsExtractor::new(path.clone()),
 		rpc::WsExtractor::new(path.clone()),
 		rpc::WsStats::new(deps.stats.clone()),
@@ -311,7 +325,13 @@ pub fn setup_apis<D>(apis: ApiSet, deps: &Dependencies<D>) -> MetaIoHandler<Meta
 	where D: rpc_apis::Dependencies
 {
 	let mut handler = MetaIoHandler::with_middleware(
-		Middleware::new(deps.stats.clone(), deps.apis.activity_notifier())
+		Middleware::new(
+			deps.stats.clone(),
+			deps.apis.activity_notifier(),
+			deps.slow_query_threshold,
+			deps.batch_limits.clone(),
+			deps.deprecation_warnings,
+		)
 	);
 	let apis = apis.list_apis();
 	deps.apis.extend_with_set(&mut handler, &apis);
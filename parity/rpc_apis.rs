@@ -35,9 +35,10 @@ use jsonrpc_core::{self as core, MetaIoHandler};
 use light::client::LightChainClient;
 use light::{Cache as LightDataCache, TransactionQueue as LightTransactionQueue};
 use miner::external::ExternalMiner;
-use parity_rpc::dispatch::{FullDispatcher, LightDispatcher};
-use parity_rpc::informant::{ActivityNotifier, ClientNotifier};
+use parity_rpc::dispatch::{FullDispatcher, LightDispatcher, NonceGuard};
+use parity_rpc::informant::{ActivityNotifier, ClientNotifier, RpcStats};
 use parity_rpc::{Host, Metadata, NetworkSettings};
+use parity_rpc::stratum::StratumWorkers;
 use parity_rpc::v1::traits::TransactionsPool;
 use parity_runtime::Executor;
 use parking_lot::{Mutex, RwLock};
@@ -249,12 +250,17 @@ pub struct FullDependencies {
 	pub geth_compatibility: bool,
 	pub experimental_rpcs: bool,
 	pub ws_address: Option<Host>,
+	pub rpc_stats: Arc<RpcStats>,
 	pub fetch: FetchClient,
 	pub executor: Executor,
 	pub gas_price_percentile: usize,
 	pub poll_lifetime: u32,
 	pub allow_missing_blocks: bool,
 	pub no_ancient_blocks: bool,
+	pub latest_confirmations: u64,
+	pub page_size: usize,
+	pub nonce_guard: Option<Arc<dyn NonceGuard>>,
+	pub stratum_workers: Option<Arc<dyn StratumWorkers>>,
 }
 
 impl FullDependencies {
@@ -271,12 +277,15 @@ impl FullDependencies {
 		let nonces = Arc::new(Mutex::new(dispatch::Reservations::new(
 			self.executor.clone(),
 		)));
-		let dispatcher = FullDispatcher::new(
+		let mut dispatcher = FullDispatcher::new(
 			self.client.clone(),
 			self.miner.clone(),
 			nonces.clone(),
 			self.gas_price_percentile,
 		);
+		if let Some(ref nonce_guard) = self.nonce_guard {
+			dispatcher = dispatcher.with_nonce_guard(nonce_guard.clone());
+		}
 		let account_signer = Arc::new(dispatch::Signer::new(self.accounts.clone())) as _;
 		let accounts = account_utils::accounts_list(self.accounts.clone());
 
@@ -306,7 +315,9 @@ impl FullDependencies {
 							gas_price_percentile: self.gas_price_percentile,
 							allow_missing_blocks: self.allow_missing_blocks,
 							allow_experimental_rpcs: self.experimental_rpcs,
-							no_ancient_blocks: self.no_ancient_blocks
+							no_ancient_blocks: self.no_ancient_blocks,
+							latest_confirmations: self.latest_confirmations,
+							page_size: self.page_size,
 						}
 					);
 					handler.extend_with(client.to_delegate());
@@ -316,6 +327,7 @@ impl FullDependencies {
 							self.client.clone(),
 							self.miner.clone(),
 							self.poll_lifetime,
+							self.latest_confirmations,
 						);
 						handler.extend_with(filter_client.to_delegate());
 
@@ -344,6 +356,9 @@ impl FullDependencies {
 						if let Some(h) = client.handler().upgrade() {
 							self.client.add_notify(h);
 						}
+						self.miner.add_work_listener(Box::new(client.work_notifier()));
+						#[cfg(feature = "accounts")]
+						self.accounts.add_listener(Box::new(client.accounts_notifier()));
 						handler.extend_with(client.to_delegate());
 					}
 				}
@@ -391,7 +406,10 @@ impl FullDependencies {
 							self.settings.clone(),
 							signer,
 							self.ws_address.clone(),
+							self.rpc_stats.clone(),
 							self.snapshot.clone().into(),
+							self.stratum_workers.clone(),
+							self.gas_price_percentile,
 						).to_delegate(),
 					);
 					#[cfg(feature = "accounts")]
@@ -408,9 +426,9 @@ impl FullDependencies {
 							.retain(ApiSet::PubSub)
 							.list_apis();
 						self.extend_api(&mut rpc, &apis, true);
-						handler.extend_with(
-							PubSubClient::new(rpc, self.executor.clone()).to_delegate(),
-						);
+						let mut client = PubSubClient::new(rpc, self.executor.clone());
+						self.client.add_notify(client.reorgs_handler(self.client.clone()));
+						handler.extend_with(client.to_delegate());
 					}
 				}
 				Api::ParityAccounts => {
@@ -424,6 +442,7 @@ impl FullDependencies {
 							&self.miner,
 							&self.updater,
 							&self.net_service,
+							&nonces,
 							self.fetch.clone(),
 						).to_delegate(),
 					);
@@ -431,11 +450,12 @@ impl FullDependencies {
 					handler.extend_with(
 						ParitySetAccountsClient::new(
 							&self.accounts,
+							&self.client,
 							&self.miner,
 						).to_delegate(),
 					);
 				}
-				Api::Traces => handler.extend_with(TracesClient::new(&self.client).to_delegate()),
+				Api::Traces => handler.extend_with(TracesClient::new(&self.client, self.page_size).to_delegate()),
 				Api::Rpc => {
 					let modules = to_modules(&apis);
 					handler.extend_with(RpcClient::new(modules).to_delegate());
@@ -493,6 +513,7 @@ pub struct LightDependencies<T> {
 	pub cache: Arc<Mutex<LightDataCache>>,
 	pub transaction_queue: Arc<RwLock<LightTransactionQueue>>,
 	pub ws_address: Option<Host>,
+	pub rpc_stats: Arc<RpcStats>,
 	pub fetch: FetchClient,
 	pub geth_compatibility: bool,
 	pub experimental_rpcs: bool,
@@ -500,6 +521,7 @@ pub struct LightDependencies<T> {
 	pub private_tx_service: Option<Arc<PrivateTransactionManager>>,
 	pub gas_price_percentile: usize,
 	pub poll_lifetime: u32,
+	pub nonce_guard: Option<Arc<dyn NonceGuard>>,
 }
 
 impl<C: LightChainClient + 'static> LightDependencies<C> {
@@ -511,7 +533,7 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 	) {
 		use parity_rpc::v1::*;
 
-		let dispatcher = LightDispatcher::new(
+		let mut dispatcher = LightDispatcher::new(
 			self.sync.clone(),
 			self.client.clone(),
 			self.on_demand.clone(),
@@ -522,6 +544,9 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 			))),
 			self.gas_price_percentile,
 		);
+		if let Some(ref nonce_guard) = self.nonce_guard {
+			dispatcher = dispatcher.with_nonce_guard(nonce_guard.clone());
+		}
 		let account_signer = Arc::new(dispatch::Signer::new(self.accounts.clone())) as _;
 		let accounts = account_utils::accounts_list(self.accounts.clone());
 
@@ -582,6 +607,8 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 					});
 
 					self.client.add_listener(client.handler() as Weak<_>);
+					#[cfg(feature = "accounts")]
+					self.accounts.add_listener(Box::new(client.accounts_notifier()));
 					handler.extend_with(EthPubSub::to_delegate(client));
 				}
 				Api::ParityTransactionsPool => {
@@ -624,6 +651,7 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 							self.settings.clone(),
 							signer,
 							self.ws_address.clone(),
+							self.rpc_stats.clone(),
 							self.gas_price_percentile,
 						).to_delegate(),
 					);
@@ -21,7 +21,7 @@ use std::thread;
 
 use ansi_term::Colour;
 use client_traits::{BlockInfo, BlockChainClient};
-use ethcore::client::{Client, DatabaseCompactionProfile};
+use ethcore::client::{BackupConfig, Client, DatabaseCompactionProfile, DatabaseTuning};
 use ethcore::miner::{self, stratum, Miner, MinerService, MinerOptions};
 use snapshot::{self, SnapshotConfiguration};
 use spec::SpecParams;
@@ -46,6 +46,7 @@ use types::{
 use parity_rpc::{
 	Origin, Metadata, NetworkSettings, informant, PubSubSession, FutureResult, FutureResponse, FutureOutput
 };
+use parity_rpc::signer::{RemoteSigner, RemoteSignerEndpoint};
 use updater::{UpdatePolicy, Updater};
 use parity_version::version;
 use ethcore_private_tx::{ProviderConfig, EncryptorConfig, SecretStoreEncryptor};
@@ -61,6 +62,7 @@ use user_defaults::UserDefaults;
 use ipfs;
 use jsonrpc_core;
 use modules;
+use node_key::{self, NodeKeyEncryption};
 use rpc;
 use rpc_apis;
 use secretstore;
@@ -92,12 +94,17 @@ pub struct RunCmd {
 	pub pruning: Pruning,
 	pub pruning_history: u64,
 	pub pruning_memory: usize,
+	pub pruning_finalized: bool,
+	pub backup_conf: BackupConfig,
 	/// Some if execution should be daemonized. Contains pid_file path.
 	pub daemon: Option<String>,
 	pub logger_config: LogConfig,
 	pub miner_options: MinerOptions,
 	pub gas_price_percentile: usize,
 	pub poll_lifetime: u32,
+	pub rpc_slow_query_threshold: Option<Duration>,
+	pub rpc_batch_limits: informant::BatchLimits,
+	pub rpc_deprecation_warnings: bool,
 	pub ws_conf: rpc::WsConfiguration,
 	pub http_conf: rpc::HttpConfiguration,
 	pub ipc_conf: rpc::IpcConfiguration,
@@ -113,6 +120,7 @@ pub struct RunCmd {
 	pub tracing: Switch,
 	pub fat_db: Switch,
 	pub compaction: DatabaseCompactionProfile,
+	pub db_tuning: DatabaseTuning,
 	pub geth_compatibility: bool,
 	pub experimental_rpcs: bool,
 	pub net_settings: NetworkSettings,
@@ -127,6 +135,8 @@ pub struct RunCmd {
 	pub snapshot_conf: SnapshotConfiguration,
 	pub check_seal: bool,
 	pub allow_missing_blocks: bool,
+	pub latest_confirmations: u64,
+	pub page_size: usize,
 	pub download_old_blocks: bool,
 	pub verifier_settings: VerifierSettings,
 	pub serve_light: bool,
@@ -139,6 +149,12 @@ pub struct RunCmd {
 	pub on_demand_request_backoff_max: Option<u64>,
 	pub on_demand_request_backoff_rounds_max: Option<usize>,
 	pub on_demand_request_consecutive_failures: Option<usize>,
+	pub light_header_history: u64,
+	pub signer_webhooks: signer::WebhooksConfiguration,
+	pub max_reorg_depth: Option<u64>,
+	pub shadow_execution: bool,
+	pub consensus_faults_window: u64,
+	pub node_key_encryption: Option<NodeKeyEncryption>,
 }
 
 // node info fetcher for the local store.
@@ -163,6 +179,58 @@ impl ::local_store::NodeInfo for FullNodeInfo {
 	}
 }
 
+// guard against signing a nonce twice, backed by the local store.
+struct LocalNonceGuard<T: ::local_store::NodeInfo>(Arc<::local_store::LocalDataStore<T>>);
+
+impl<T: ::local_store::NodeInfo> ::std::fmt::Debug for LocalNonceGuard<T> {
+	fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+		write!(f, "LocalNonceGuard")
+	}
+}
+
+impl<T: ::local_store::NodeInfo> ::parity_rpc::dispatch::NonceGuard for LocalNonceGuard<T> {
+	fn nonce_floor(&self, sender: &::ethereum_types::Address) -> Option<::ethereum_types::U256> {
+		self.0.nonce_floor(sender).unwrap_or_else(|e| {
+			warn!("Error reading signed nonces from the local store: {}", e);
+			None
+		})
+	}
+
+	fn note_signed(&self, sender: ::ethereum_types::Address, nonce: ::ethereum_types::U256) -> Option<::ethereum_types::U256> {
+		self.0.note_signed_nonce(sender, nonce).unwrap_or_else(|e| {
+			warn!("Error persisting signed nonce {} of {:?}: {}", nonce, sender, e);
+			None
+		})
+	}
+
+	fn rollback_signed(&self, sender: ::ethereum_types::Address, nonce: ::ethereum_types::U256, previous: Option<::ethereum_types::U256>) {
+		if let Err(e) = self.0.rollback_signed_nonce(sender, nonce, previous) {
+			warn!("Error rolling signed nonce {} of {:?} back: {}", nonce, sender, e);
+		}
+	}
+}
+
+// statistics of the workers connected to the stratum server.
+struct StratumWorkersInfo(stratum::Stratum);
+
+impl ::parity_rpc::stratum::StratumWorkers for StratumWorkersInfo {
+	fn workers(&self) -> Vec<::parity_rpc::stratum::StratumWorker> {
+		self.0.workers()
+			.into_iter()
+			.map(|worker| ::parity_rpc::stratum::StratumWorker {
+				worker_id: worker.worker_id,
+				address: worker.addr.to_string(),
+				accepted_shares: worker.accepted_shares.into(),
+				rejected_shares: worker.rejected_shares.into(),
+				last_share: worker.last_share
+					.and_then(|time| time.duration_since(::std::time::UNIX_EPOCH).ok())
+					.map(|since_epoch| since_epoch.as_secs().into()),
+				hashrate: worker.hashrate.map(|hashrate| (hashrate as u128).into()),
+			})
+			.collect()
+	}
+}
+
 type LightClient = ::light::client::Client<::light_helpers::EpochFetch>;
 
 // helper for light execution.
@@ -213,6 +281,7 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 		verify_full: true,
 		check_seal: cmd.check_seal,
 		no_hardcoded_sync: cmd.no_hardcoded_sync,
+		header_history: cmd.light_header_history,
 	};
 
 	config.queue.max_mem_use = cmd.cache_config.queue() as usize * 1024 * 1024;
@@ -259,9 +328,13 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 		&cmd.compaction,
 	).map_err(|e| format!("Failed to open database {:?}", e))?;
 
+	// light clients only use the local store to guard the nonces they sign.
+	let nonce_guard = Arc::new(LocalNonceGuard(Arc::new(::local_store::open(db.key_value().clone(), ::ethcore_db::COL_NODE_INFO))));
+
 	let service = light_client::Service::start(config, &spec, fetch, db, cache.clone())
 		.map_err(|e| format!("Error starting light client: {}", e))?;
 	let client = service.client().clone();
+	client.engine().set_consensus_faults_window(Duration::from_secs(cmd.consensus_faults_window));
 	let txq = Arc::new(RwLock::new(::light::transaction_queue::TransactionQueue::default()));
 	let provider = ::light::provider::LightProvider::new(client.clone(), txq.clone());
 
@@ -274,6 +347,7 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 
 	// set network path.
 	net_conf.net_config_path = Some(db_dirs.network_path().to_string_lossy().into_owned());
+	node_key::apply(&mut net_conf, cmd.node_key_encryption.as_ref())?;
 	let sync_params = LightSyncParams {
 		network_config: net_conf.into_basic().map_err(|e| format!("Failed to produce network config: {}", e))?,
 		client: Arc::new(provider),
@@ -297,11 +371,14 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 	let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
 
 	// prepare account provider
+	let keys_refresh_time = cmd.acc_conf.refresh_time;
 	let account_provider = Arc::new(account_utils::prepare_account_provider(&cmd.spec, &cmd.dirs, &spec.data_dir, cmd.acc_conf, &passwords)?);
+	account_utils::watch_keys(&account_provider, keys_refresh_time);
+	account_utils::relock_expired_accounts(&account_provider);
 	let rpc_stats = Arc::new(informant::RpcStats::default());
 
 	// the dapps server
-	let signer_service = Arc::new(signer::new_service(&cmd.ws_conf, &cmd.logger_config));
+	let signer_service = Arc::new(signer::new_service(&cmd.ws_conf, &cmd.logger_config, &cmd.signer_webhooks, fetch.clone(), runtime.executor())?);
 
 	// start RPCs
 	let deps_for_rpc_apis = Arc::new(rpc_apis::LightDependencies {
@@ -316,19 +393,24 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 		cache: cache.clone(),
 		transaction_queue: txq,
 		ws_address: cmd.ws_conf.address(),
+		rpc_stats: rpc_stats.clone(),
 		fetch,
 		geth_compatibility: cmd.geth_compatibility,
 		experimental_rpcs: cmd.experimental_rpcs,
 		executor: runtime.executor(),
 		private_tx_service: None, //TODO: add this to client.
 		gas_price_percentile: cmd.gas_price_percentile,
-		poll_lifetime: cmd.poll_lifetime
+		poll_lifetime: cmd.poll_lifetime,
+		nonce_guard: Some(nonce_guard),
 	});
 
 	let dependencies = rpc::Dependencies {
 		apis: deps_for_rpc_apis.clone(),
 		executor: runtime.executor(),
 		stats: rpc_stats.clone(),
+		slow_query_threshold: cmd.rpc_slow_query_threshold,
+		batch_limits: cmd.rpc_batch_limits.clone(),
+		deprecation_warnings: cmd.rpc_deprecation_warnings,
 	};
 
 	// start rpc servers
@@ -450,6 +532,7 @@ fn execute_impl<Cr, Rr>(
 	}
 
 	sync_config.fork_block = spec.fork_block();
+	sync_config.fork_blocks = spec.engine.fork_blocks();
 	let snapshot_supported =
 		if let Snapshotting::Unsupported = spec.engine.snapshot_mode() {
 			false
@@ -482,7 +565,10 @@ fn execute_impl<Cr, Rr>(
 	let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
 
 	// prepare account provider
+	let keys_refresh_time = cmd.acc_conf.refresh_time;
 	let account_provider = Arc::new(account_utils::prepare_account_provider(&cmd.spec, &cmd.dirs, &spec.data_dir, cmd.acc_conf, &passwords)?);
+	account_utils::watch_keys(&account_provider, keys_refresh_time);
+	account_utils::relock_expired_accounts(&account_provider);
 
 	// Spin up the Tokio event loop with core_threads = number of logical cores on the machine.
 	// This runtime is shared among many subsystems: sync, rpc processing, tx broadcasting, price fetcher etc
@@ -509,12 +595,29 @@ fn execute_impl<Cr, Rr>(
 	if !cmd.miner_extras.work_notify.is_empty() {
 		miner.add_work_listener(Box::new(
 			WorkPoster::new(&cmd.miner_extras.work_notify, fetch.clone(), runtime.executor())
+				.with_retries(cmd.miner_extras.work_notify_retries)
 		));
 	}
 
 	let engine_signer = cmd.miner_extras.engine_signer;
 	if engine_signer != Default::default() {
-		if let Some(author) = account_utils::miner_author(&cmd.spec, &cmd.dirs, &account_provider, engine_signer, &passwords)? {
+		let local_author = account_utils::miner_author(&cmd.spec, &cmd.dirs, &account_provider, engine_signer, &passwords);
+		let author = match cmd.miner_extras.engine_signer_remote {
+			Some(ref endpoint) => {
+				let endpoint = RemoteSignerEndpoint::parse(endpoint)?;
+				let fallback = match local_author {
+					Ok(Some(miner::Author::Sealer(signer))) => Some(signer),
+					_ => {
+						info!("Consensus signer {} has no local fallback, relying on the remote signer only.", engine_signer);
+						None
+					}
+				};
+				let signer = RemoteSigner::new(endpoint, engine_signer, cmd.miner_extras.engine_signer_remote_timeout, fallback);
+				Some(miner::Author::Sealer(Box::new(signer)))
+			},
+			None => local_author?,
+		};
+		if let Some(author) = author {
 			miner.set_author(author);
 		}
 	}
@@ -543,6 +646,11 @@ fn execute_impl<Cr, Rr>(
 	client_config.queue.verifier_settings = cmd.verifier_settings;
 	client_config.transaction_verification_queue_size = ::std::cmp::max(2048, txpool_size / 4);
 	client_config.snapshot = cmd.snapshot_conf.clone();
+	client_config.max_reorg_depth = cmd.max_reorg_depth;
+	client_config.shadow_execution = cmd.shadow_execution;
+	client_config.db_tuning = cmd.db_tuning;
+	client_config.pruning_finalized = cmd.pruning_finalized;
+	client_config.backup = cmd.backup_conf.clone();
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
@@ -552,6 +660,7 @@ fn execute_impl<Cr, Rr>(
 
 	// set network path.
 	net_conf.net_config_path = Some(db_dirs.network_path().to_string_lossy().into_owned());
+	node_key::apply(&mut net_conf, cmd.node_key_encryption.as_ref())?;
 
 	let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config);
 	let client_db = restoration_db_handler.open(&client_path)
@@ -580,6 +689,7 @@ fn execute_impl<Cr, Rr>(
 
 	// take handle to client
 	let client = service.client();
+	client.engine().set_consensus_faults_window(Duration::from_secs(cmd.consensus_faults_window));
 	// Update miners block gas limit
 	miner.update_transaction_queue_limits(*client.best_block_header().gas_limit());
 
@@ -602,6 +712,10 @@ fn execute_impl<Cr, Rr>(
 		};
 
 		let store = ::local_store::create(db.key_value().clone(), ::ethcore_db::COL_NODE_INFO, node_info);
+		// counting pruned entries re-reads the store on every write, only do it when it gets logged.
+		if log_enabled!(target: "local_store", ::rlog::Level::Debug) {
+			store.add_listener(Box::new(|update| debug!(target: "local_store", "Persisted local data: {:?}", update)));
+		}
 
 		if cmd.no_persistent_txqueue {
 			info!("Running without a persistent transaction queue.");
@@ -626,6 +740,8 @@ fn execute_impl<Cr, Rr>(
 		Arc::new(store)
 	};
 
+	let nonce_guard = Arc::new(LocalNonceGuard(store.clone()));
+
 	// register it as an IO service to update periodically.
 	service.register_io_handler(store).map_err(|_| "Unable to register local store handler".to_owned())?;
 
@@ -633,10 +749,14 @@ fn execute_impl<Cr, Rr>(
 	let external_miner = Arc::new(ExternalMiner::default());
 
 	// start stratum
-	if let Some(ref stratum_config) = cmd.stratum {
-		stratum::Stratum::register(stratum_config, miner.clone(), Arc::downgrade(&client))
-			.map_err(|e| format!("Stratum start error: {:?}", e))?;
-	}
+	let stratum_workers = match cmd.stratum {
+		Some(ref stratum_config) => {
+			let stratum = stratum::Stratum::register(stratum_config, miner.clone(), Arc::downgrade(&client))
+				.map_err(|e| format!("Stratum start error: {:?}", e))?;
+			Some(Arc::new(StratumWorkersInfo(stratum)) as Arc<dyn ::parity_rpc::stratum::StratumWorkers>)
+		},
+		None => None,
+	};
 
 	let (private_tx_sync, private_state) = match cmd.private_tx_enabled {
 		true => (Some(private_tx_service.clone() as Arc<dyn PrivateTxHandler>), Some(private_tx_provider.private_state_db())),
@@ -708,7 +828,7 @@ fn execute_impl<Cr, Rr>(
 	// set up dependencies for rpc servers
 	let rpc_stats = Arc::new(informant::RpcStats::default());
 	let secret_store = account_provider.clone();
-	let signer_service = Arc::new(signer::new_service(&cmd.ws_conf, &cmd.logger_config));
+	let signer_service = Arc::new(signer::new_service(&cmd.ws_conf, &cmd.logger_config, &cmd.signer_webhooks, fetch.clone(), runtime.executor())?);
 
 	let deps_for_rpc_apis = Arc::new(rpc_apis::FullDependencies {
 		signer_service: signer_service,
@@ -726,6 +846,7 @@ fn execute_impl<Cr, Rr>(
 		geth_compatibility: cmd.geth_compatibility,
 		experimental_rpcs: cmd.experimental_rpcs,
 		ws_address: cmd.ws_conf.address(),
+		rpc_stats: rpc_stats.clone(),
 		fetch: fetch.clone(),
 		executor: runtime.executor(),
 		private_tx_service: Some(private_tx_service.clone()),
@@ -733,12 +854,19 @@ fn execute_impl<Cr, Rr>(
 		poll_lifetime: cmd.poll_lifetime,
 		allow_missing_blocks: cmd.allow_missing_blocks,
 		no_ancient_blocks: !cmd.download_old_blocks,
+		latest_confirmations: cmd.latest_confirmations,
+		page_size: cmd.page_size,
+		nonce_guard: Some(nonce_guard),
+		stratum_workers,
 	});
 
 	let dependencies = rpc::Dependencies {
 		apis: deps_for_rpc_apis.clone(),
 		executor: runtime.executor(),
 		stats: rpc_stats.clone(),
+		slow_query_threshold: cmd.rpc_slow_query_threshold,
+		batch_limits: cmd.rpc_batch_limits.clone(),
+		deprecation_warnings: cmd.rpc_deprecation_warnings,
 	};
 
 	// start rpc servers
@@ -24,7 +24,7 @@ mod blockchain;
 #[cfg(all(feature = "accounts", feature = "secretstore"))]
 mod nodekeypair;
 
-pub use self::server::{Configuration, NodeSecretKey, ContractAddress, Dependencies, start};
+pub use self::server::{Configuration, NodeSecretKey, ContractAddress, TlsConfiguration, Dependencies, start};
 #[cfg(feature = "secretstore")]
 use self::blockchain::TrustedClient;
 #[cfg(all(feature = "accounts", feature = "secretstore"))]
@@ -26,7 +26,7 @@ use ethcore::miner::Miner;
 use ethkey::Password;
 use parity_crypto::publickey::{Secret, Public};
 use sync::SyncProvider;
-use ethereum_types::Address;
+use ethereum_types::{Address, H256};
 use parity_runtime::Executor;
 
 /// This node secret key.
@@ -48,6 +48,21 @@ pub enum ContractAddress {
 	Address(Address),
 }
 
+/// Mutual TLS configuration of connections between key servers.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TlsConfiguration {
+	/// Path to PEM file with certificate chain of this node.
+	pub certificate_path: String,
+	/// Path to PEM file with private key of this node.
+	pub private_key_path: String,
+	/// Path to PEM file with CA certificates.
+	pub ca_certificates_path: String,
+	/// DNS name that certificates of all nodes are issued for.
+	pub server_name: String,
+	/// SHA-256 fingerprints of allowed certificates. If empty, every certificate issued by CA is accepted.
+	pub pinned_certificates: Vec<H256>,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 /// Secret store configuration
 pub struct Configuration {
@@ -89,6 +104,8 @@ pub struct Configuration {
 	pub admin_public: Option<Public>,
 	// Allowed CORS domains
 	pub cors: Option<Vec<String>>,
+	/// Mutual TLS configuration of connections between key servers.
+	pub tls: Option<TlsConfiguration>,
 }
 
 /// Secret store dependencies
@@ -126,7 +143,7 @@ mod server {
 	use ethcore_secretstore;
 	use parity_crypto::publickey::KeyPair;
 	use ansi_term::Colour::{Red, White};
-	use super::{Configuration, Dependencies, NodeSecretKey, ContractAddress, Executor};
+	use super::{Configuration, Dependencies, NodeSecretKey, ContractAddress, TlsConfiguration, Executor};
 	use super::super::TrustedClient;
 	#[cfg(feature = "accounts")]
 	use super::super::KeyStoreNodeKeyPair;
@@ -138,6 +155,16 @@ mod server {
 		}
 	}
 
+	fn into_tls_configuration(tls: TlsConfiguration) -> ethcore_secretstore::TlsConfiguration {
+		ethcore_secretstore::TlsConfiguration {
+			certificate_path: tls.certificate_path,
+			private_key_path: tls.private_key_path,
+			ca_certificates_path: tls.ca_certificates_path,
+			server_name: tls.server_name,
+			pinned_certificates: tls.pinned_certificates.into_iter().collect(),
+		}
+	}
+
 	/// Key server
 	pub struct KeyServer {
 		_key_server: Box<dyn ethcore_secretstore::KeyServer>,
@@ -201,6 +228,7 @@ mod server {
 					allow_connecting_to_higher_nodes: true,
 					admin_public: conf.admin_public,
 					auto_migrate_enabled: conf.auto_migrate_enabled,
+					tls: conf.tls.map(into_tls_configuration),
 				},
 				cors: conf.cors
 			};
@@ -244,6 +272,7 @@ impl Default for Configuration {
 			http_port: 8082,
 			data_path: replace_home(&data_dir, "$BASE/secretstore"),
 			cors: Some(vec![]),
+			tls: None,
 		}
 	}
 }
@@ -14,14 +14,17 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::io;
+use std::{fs, io};
 use std::path::{Path, PathBuf};
 
 use ansi_term::Colour::White;
 use ethcore_logger::Config as LogConfig;
+use hash_fetch::fetch;
+use parity_runtime::Executor;
 use rpc;
 use rpc_apis;
 use parity_rpc;
+use parity_rpc::signer::{SignerWebhooks, WebhookSecret};
 use path::restrict_permissions_owner;
 
 pub const CODES_FILENAME: &'static str = "authcodes";
@@ -31,14 +34,43 @@ pub struct NewToken {
 	pub message: String,
 }
 
-pub fn new_service(ws_conf: &rpc::WsConfiguration, logger_config: &LogConfig) -> rpc_apis::SignerService {
+/// Confirmation queue webhooks configuration.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WebhooksConfiguration {
+	/// URLs notified about confirmation queue events.
+	pub urls: Vec<String>,
+	/// Path to a file containing the secret shared with the approvers.
+	pub secret_path: Option<String>,
+}
+
+pub fn new_service(
+	ws_conf: &rpc::WsConfiguration,
+	logger_config: &LogConfig,
+	webhooks_conf: &WebhooksConfiguration,
+	fetch: fetch::Client,
+	executor: Executor,
+) -> Result<rpc_apis::SignerService, String> {
 	let logger_config_color = logger_config.color;
 	let signer_path = ws_conf.signer_path.clone();
 	let signer_enabled = ws_conf.support_token_api;
 
-	rpc_apis::SignerService::new(move || {
+	let service = rpc_apis::SignerService::new(move || {
 		generate_new_token(&signer_path, logger_config_color).map_err(|e| format!("{:?}", e))
-	}, signer_enabled)
+	}, signer_enabled);
+
+	match webhooks_conf.secret_path {
+		Some(ref path) => {
+			let secret = fs::read_to_string(path)
+				.map_err(|e| format!("Unable to read signer webhook secret from {}: {}", path, e))?;
+			let secret = secret.trim();
+			if secret.is_empty() {
+				return Err(format!("Signer webhook secret file {} is empty", path));
+			}
+			let webhooks = SignerWebhooks::new(&webhooks_conf.urls, WebhookSecret::new(secret.as_bytes().to_vec()), fetch, executor)?;
+			Ok(service.with_webhooks(webhooks))
+		},
+		None => Ok(service),
+	}
 }
 
 pub fn codes_path(path: &Path) -> PathBuf {
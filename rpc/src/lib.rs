@@ -155,7 +155,7 @@ pub use http::{
 	AccessControlAllowOrigin, Host, DomainsValidation, cors::AccessControlAllowHeaders
 };
 
-pub use v1::{NetworkSettings, Metadata, Origin, informant, dispatch, signer};
+pub use v1::{NetworkSettings, Metadata, Origin, informant, dispatch, signer, stratum};
 pub use v1::block_import::{is_major_importing_or_waiting};
 pub use v1::PubSubSyncStatus;
 pub use v1::extractors::{RpcExtractor, WsExtractor, WsStats, WsDispatcher};
@@ -227,11 +227,13 @@ pub fn start_http_with_middleware<M, S, H, T, R>(
 }
 
 /// Start ipc server asynchronously and returns result with `Server` handle on success or an error.
+///
+/// The size of IPC requests is not limited, the server has no setting for it.
 pub fn start_ipc<M, S, H, T>(
 	addr: &str,
 	handler: H,
 	extractor: T,
-	chmod: u16
+	chmod: u16,
 ) -> ::std::io::Result<ipc::Server> where
 	M: jsonrpc_core::Metadata,
 	S: jsonrpc_core::Middleware<M>,
@@ -247,12 +249,16 @@ pub fn start_ipc<M, S, H, T>(
 }
 
 /// Start WS server and return `Server` handle.
+///
+/// `max_payload` limits the size of a single message in megabytes. Messages are not compressed,
+/// the server doesn't negotiate `permessage-deflate`.
 pub fn start_ws<M, S, H, T, U, V>(
 	addr: &SocketAddr,
 	handler: H,
 	allowed_origins: ws::DomainsValidation<ws::Origin>,
 	allowed_hosts: ws::DomainsValidation<ws::Host>,
 	max_connections: usize,
+	max_payload: usize,
 	extractor: T,
 	middleware: V,
 	stats: U,
@@ -269,6 +275,7 @@ pub fn start_ws<M, S, H, T, U, V>(
 		.allowed_origins(allowed_origins)
 		.allowed_hosts(allowed_hosts)
 		.max_connections(max_connections)
+		.max_payload(max_payload * 1024 * 1024)
 		.session_stats(stats)
 		.start(addr)
 }
@@ -32,6 +32,33 @@ pub mod msgs {
 
 type MethodName = &'static str;
 
+/// Deprecated methods with details on their replacement.
+/// Methods which already fail with a deprecation error are not listed.
+pub const METHODS: &[(MethodName, Option<&str>)] = &[
+	("parity_startNetwork", Some("use parity_setMode(\"active\") instead.")),
+	("parity_stopNetwork", Some("use parity_setMode(\"offline\") instead.")),
+	("personal_accounts", msgs::ACCOUNTS),
+	("personal_newAccount", msgs::ACCOUNTS),
+	("personal_unlockAccount", msgs::ACCOUNTS),
+	("personal_sign", msgs::ACCOUNTS),
+	("personal_sign191", msgs::ACCOUNTS),
+	("personal_signTypedData", msgs::ACCOUNTS),
+	("personal_signTransaction", msgs::ACCOUNTS),
+	("personal_sendTransaction", msgs::ACCOUNTS),
+	("personal_signAndSendTransaction", Some("use personal_sendTransaction instead.")),
+];
+
+/// Returns the name of the method and details on its replacement if the method is deprecated.
+pub fn find(method: &str) -> Option<(MethodName, Option<&'static str>)> {
+	METHODS.iter().find(|&&(name, _)| name == method).cloned()
+}
+
+/// Warning describing the deprecation of a method.
+pub fn warning(method: MethodName, details: Option<&str>) -> String {
+	let more = details.map(|x| format!(": {}", x)).unwrap_or_else(|| ".".into());
+	format!("{} is deprecated and will be removed in future versions{}", method, more)
+}
+
 const PRINT_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Displays a deprecation notice without spamming the log.
@@ -44,8 +71,7 @@ pub struct DeprecationNotice<T = fn() -> Instant> {
 impl Default for DeprecationNotice {
 	fn default() -> Self {
 		Self::new(Instant::now, |method, more| {
-			let more = more.map(|x| format!(": {}", x)).unwrap_or_else(|| ".".into());
-			warn!(target: "rpc", "{} is deprecated and will be removed in future versions{}", method, more);
+			warn!(target: "rpc", "{}", warning(method, more));
 		})
 	}
 }
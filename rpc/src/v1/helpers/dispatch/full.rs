@@ -28,7 +28,7 @@ use v1::helpers::{errors, nonce, TransactionRequest, FilledTransactionRequest};
 use v1::types::{RichRawTransaction as RpcRichRawTransaction};
 
 use super::prospective_signer::ProspectiveSigner;
-use super::{Dispatcher, Accounts, SignWith, PostSign, default_gas_price};
+use super::{Dispatcher, Accounts, SignWith, PostSign, NonceGuard, NoteSignedNonce, check_nonce_floor, default_gas_price};
 
 /// A dispatcher which uses references to a client and miner in order to sign
 /// requests locally.
@@ -38,6 +38,7 @@ pub struct FullDispatcher<C, M> {
 	miner: Arc<M>,
 	nonces: Arc<Mutex<nonce::Reservations>>,
 	gas_price_percentile: usize,
+	nonce_guard: Option<Arc<dyn NonceGuard>>,
 }
 
 impl<C, M> FullDispatcher<C, M> {
@@ -53,8 +54,15 @@ impl<C, M> FullDispatcher<C, M> {
 			miner,
 			nonces,
 			gas_price_percentile,
+			nonce_guard: None,
 		}
 	}
+
+	/// Refuse to sign nonces which have already been signed according to the given guard.
+	pub fn with_nonce_guard(mut self, nonce_guard: Arc<dyn NonceGuard>) -> Self {
+		self.nonce_guard = Some(nonce_guard);
+		self
+	}
 }
 
 impl<C, M> Clone for FullDispatcher<C, M> {
@@ -64,13 +72,24 @@ impl<C, M> Clone for FullDispatcher<C, M> {
 			miner: self.miner.clone(),
 			nonces: self.nonces.clone(),
 			gas_price_percentile: self.gas_price_percentile,
+			nonce_guard: self.nonce_guard.clone(),
 		}
 	}
 }
 
 impl<C: miner::BlockChainClient, M: MinerService> FullDispatcher<C, M> {
 	fn state_nonce(&self, from: &Address) -> U256 {
-		self.miner.next_nonce(&*self.client, from)
+		let state = self.miner.next_nonce(&*self.client, from);
+		match self.nonce_guard.as_ref().and_then(|guard| guard.nonce_floor(from)) {
+			Some(floor) if floor > state => floor,
+			_ => state,
+		}
+	}
+
+	// Whether a transaction from `sender` with `nonce` is still queued, so that it can be replaced.
+	fn is_queued(&self, sender: &Address, nonce: U256) -> bool {
+		self.miner.queued_transactions().iter()
+			.any(|tx| tx.signed().sender() == *sender && tx.signed().nonce == nonce)
 	}
 
 	/// Post transaction to the network.
@@ -127,8 +146,13 @@ impl<C: miner::BlockChainClient + BlockChainClient, M: MinerService> Dispatcher
 		    <P::Out as IntoFuture>::Future: Send,
 	{
 		let chain_id = self.client.signing_chain_id();
+		let post_sign = NoteSignedNonce::new(self.nonce_guard.clone(), post_sign);
 
 		if let Some(nonce) = filled.nonce {
+			if let Err(err) = check_nonce_floor(&self.nonce_guard, &filled.from, nonce, || self.is_queued(&filled.from, nonce)) {
+				return Box::new(future::err(err));
+			}
+
 			let future = signer.sign_transaction(filled, chain_id, nonce, password)
 				.into_future()
 				.and_then(move |signed| post_sign.execute(signed));
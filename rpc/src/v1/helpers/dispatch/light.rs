@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp;
 use std::sync::Arc;
 
 use ethereum_types::{H256, Address, U256};
@@ -34,7 +35,7 @@ use jsonrpc_core::futures::future::Either;
 use v1::helpers::{errors, nonce, TransactionRequest, FilledTransactionRequest};
 use v1::types::{RichRawTransaction as RpcRichRawTransaction,};
 
-use super::{Dispatcher, Accounts, SignWith, PostSign};
+use super::{Dispatcher, Accounts, SignWith, PostSign, NonceGuard, NoteSignedNonce, check_nonce_floor};
 
 /// Dispatcher for light clients -- fetches default gas price, next nonce, etc. from network.
 pub struct LightDispatcher<S, OD>
@@ -56,6 +57,8 @@ where
 	pub nonces: Arc<Mutex<nonce::Reservations>>,
 	/// Gas Price percentile value used as default gas price.
 	pub gas_price_percentile: usize,
+	/// Record of the nonces signed by this node.
+	pub nonce_guard: Option<Arc<dyn NonceGuard>>,
 }
 
 impl<S, OD> LightDispatcher<S, OD>
@@ -83,9 +86,16 @@ where
 			transaction_queue,
 			nonces,
 			gas_price_percentile,
+			nonce_guard: None,
 		}
 	}
 
+	/// Refuse to sign nonces which have already been signed according to the given guard.
+	pub fn with_nonce_guard(mut self, nonce_guard: Arc<dyn NonceGuard>) -> Self {
+		self.nonce_guard = Some(nonce_guard);
+		self
+	}
+
 	/// Get a recent gas price corpus.
 	// TODO: this could be `impl Trait`.
 	pub fn gas_price_corpus(&self) -> BoxFuture<Corpus<U256>> {
@@ -135,7 +145,8 @@ where
 			cache: self.cache.clone(),
 			transaction_queue: self.transaction_queue.clone(),
 			nonces: self.nonces.clone(),
-			gas_price_percentile: self.gas_price_percentile
+			gas_price_percentile: self.gas_price_percentile,
+			nonce_guard: self.nonce_guard.clone(),
 		}
 	}
 }
@@ -188,6 +199,7 @@ where
 		};
 
 		let future_account = self.account(from);
+		let nonce_guard = self.nonce_guard.clone();
 
 		Box::new(gas_price.and_then(move |mut filled| {
 			future_account
@@ -202,7 +214,8 @@ where
 						}
 						Some(account) => {
 							if filled.nonce.is_none() {
-								filled.nonce = Some(account.nonce);
+								let floor = nonce_guard.as_ref().and_then(|guard| guard.nonce_floor(&filled.from));
+								filled.nonce = Some(floor.map_or(account.nonce, |floor| cmp::max(floor, account.nonce)));
 							}
 							Ok(filled)
 						}
@@ -225,6 +238,12 @@ where
 	{
 		let chain_id = self.client.signing_chain_id();
 		let nonce = filled.nonce.expect("nonce is always provided; qed");
+		let transaction_queue = &self.transaction_queue;
+		if let Err(err) = check_nonce_floor(&self.nonce_guard, &filled.from, nonce, || transaction_queue.read().contains(&filled.from, &nonce)) {
+			return Box::new(future::err(err));
+		}
+
+		let post_sign = NoteSignedNonce::new(self.nonce_guard.clone(), post_sign);
 		let future = signer.sign_transaction(filled, chain_id, nonce, password)
 			.into_future()
 			.and_then(move |signed| post_sign.execute(signed));
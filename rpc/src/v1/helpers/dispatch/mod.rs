@@ -84,8 +84,8 @@ use hash::keccak;
 use types::transaction::{SignedTransaction, PendingTransaction};
 
 use jsonrpc_core::{BoxFuture, Result, Error};
-use jsonrpc_core::futures::{future, Future, IntoFuture};
-use v1::helpers::{TransactionRequest, FilledTransactionRequest, ConfirmationPayload};
+use jsonrpc_core::futures::{future, Async, Future, IntoFuture, Poll};
+use v1::helpers::{errors, TransactionRequest, FilledTransactionRequest, ConfirmationPayload};
 use v1::types::{
 	Bytes as RpcBytes,
 	RichRawTransaction as RpcRichRawTransaction,
@@ -158,6 +158,95 @@ pub trait Accounts: Send + Sync {
 	fn is_unlocked(&self, address: &Address) -> bool;
 }
 
+/// Persistent record of the nonces signed by this node.
+///
+/// Consulted before signing so that a node restored from a backup, or otherwise rolled back,
+/// never signs a second transaction with a nonce it has already used.
+pub trait NonceGuard: Send + Sync + Debug {
+	/// Lowest nonce `sender` may sign, `None` if it never signed a transaction locally.
+	fn nonce_floor(&self, sender: &Address) -> Option<U256>;
+
+	/// Record that `sender` signed a transaction with `nonce`, before the transaction is sent.
+	/// Returns the highest nonce `sender` signed before, to be restored by `rollback_signed`.
+	fn note_signed(&self, sender: Address, nonce: U256) -> Option<U256>;
+
+	/// Forget that `sender` signed `nonce` after its transaction failed to be sent, restoring the
+	/// `previous` highest nonce. A no-op if `sender` signed a higher nonce in the meantime.
+	fn rollback_signed(&self, sender: Address, nonce: U256, previous: Option<U256>);
+}
+
+/// Rejects signing `nonce` for `sender` if it's below the floor of the guard, unless a transaction
+/// with that nonce is still queued and can therefore be replaced.
+fn check_nonce_floor<F>(nonce_guard: &Option<Arc<dyn NonceGuard>>, sender: &Address, nonce: U256, is_queued: F) -> Result<()> where
+	F: FnOnce() -> bool,
+{
+	match nonce_guard.as_ref().and_then(|guard| guard.nonce_floor(sender)) {
+		Some(floor) if nonce < floor && !is_queued() => Err(errors::nonce_already_signed(nonce, floor)),
+		_ => Ok(()),
+	}
+}
+
+/// Notes the nonce of a signed transaction in the guard before executing the wrapped `PostSign`
+/// and rolls the floor back if it fails, so that a transaction which was never sent does not
+/// leave a gap behind.
+pub(crate) struct NoteSignedNonce<P> {
+	nonce_guard: Option<Arc<dyn NonceGuard>>,
+	post_sign: P,
+}
+
+impl<P> NoteSignedNonce<P> {
+	/// Wrap `post_sign`, noting signed nonces in `nonce_guard` if there is one.
+	pub(crate) fn new(nonce_guard: Option<Arc<dyn NonceGuard>>, post_sign: P) -> Self {
+		NoteSignedNonce { nonce_guard, post_sign }
+	}
+}
+
+impl<P: PostSign> PostSign for NoteSignedNonce<P> where
+	<P::Out as IntoFuture>::Future: Send,
+{
+	type Item = P::Item;
+	type Out = RollbackOnError<<P::Out as IntoFuture>::Future>;
+
+	fn execute(self, signed: WithToken<SignedTransaction>) -> Self::Out {
+		let rollback = self.nonce_guard.map(|guard| {
+			let (sender, nonce) = (signed.sender(), signed.nonce);
+			let previous = guard.note_signed(sender, nonce);
+			(guard, sender, nonce, previous)
+		});
+		RollbackOnError {
+			inner: self.post_sign.execute(signed).into_future(),
+			rollback,
+		}
+	}
+}
+
+/// Future of `NoteSignedNonce`.
+pub(crate) struct RollbackOnError<F> {
+	inner: F,
+	rollback: Option<(Arc<dyn NonceGuard>, Address, U256, Option<U256>)>,
+}
+
+impl<F: Future<Error = Error>> Future for RollbackOnError<F> {
+	type Item = F::Item;
+	type Error = Error;
+
+	fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+		match self.inner.poll() {
+			Ok(Async::NotReady) => Ok(Async::NotReady),
+			Ok(Async::Ready(item)) => {
+				self.rollback = None;
+				Ok(Async::Ready(item))
+			},
+			Err(err) => {
+				if let Some((guard, sender, nonce, previous)) = self.rollback.take() {
+					guard.rollback_signed(sender, nonce, previous);
+				}
+				Err(err)
+			},
+		}
+	}
+}
+
 /// action to execute after signing
 /// e.g importing a transaction into the chain
 pub trait PostSign: Send {
@@ -347,11 +436,12 @@ pub fn eth_data_hash(mut data: Bytes) -> H256 {
 }
 
 /// Extract the default gas price from a client and miner.
+/// Matches the standard tier of the miner's fee suggestions.
 pub fn default_gas_price<C, M>(client: &C, miner: &M, percentile: usize) -> U256 where
 	C: BlockChainClient,
 	M: MinerService,
 {
-	client.gas_price_corpus(100).percentile(percentile).cloned().unwrap_or_else(|| miner.sensible_gas_price())
+	miner.fee_suggestions(client, percentile).standard.gas_price
 }
 
 /// Convert RPC confirmation payload to signer confirmation payload.
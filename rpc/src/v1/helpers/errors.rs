@@ -19,6 +19,7 @@
 use std::fmt;
 
 use jsonrpc_core::{futures, Result as RpcResult, Error, ErrorCode, Value};
+use ethereum_types::U256;
 use rlp::DecoderError;
 use types::transaction::Error as TransactionError;
 use ethcore_private_tx::Error as PrivateTransactionError;
@@ -57,6 +58,7 @@ mod codes {
 	pub const REQUEST_REJECTED: i64 = -32040;
 	pub const REQUEST_REJECTED_LIMIT: i64 = -32041;
 	pub const REQUEST_NOT_FOUND: i64 = -32042;
+	pub const NOT_EXECUTED: i64 = -32043;
 	pub const ENCRYPTION_ERROR: i64 = -32055;
 	pub const ENCODING_ERROR: i64 = -32058;
 	pub const FETCH_ERROR: i64 = -32060;
@@ -115,6 +117,14 @@ pub fn request_rejected_limit() -> Error {
 	}
 }
 
+pub fn not_executed(reason: &str) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::NOT_EXECUTED),
+		message: "Call of the batch has not been executed.".into(),
+		data: Some(Value::String(reason.into())),
+	}
+}
+
 pub fn request_rejected_param_limit(limit: u64, items_desc: &str) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
@@ -131,6 +141,14 @@ pub fn account<T: fmt::Debug>(error: &str, details: T) -> Error {
 	}
 }
 
+pub fn nonce_already_signed(nonce: U256, floor: U256) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::TRANSACTION_ERROR),
+		message: format!("Nonce {} has already been signed by this node, the lowest nonce which can be signed is {}.", nonce, floor),
+		data: None,
+	}
+}
+
 pub fn cannot_restart() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::CANNOT_RESTART),
@@ -174,6 +192,22 @@ pub fn state_pruned() -> Error {
 	}
 }
 
+pub fn state_root_unsupported() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Querying by state root is only supported by methods reading the state of a full node.".into(),
+		data: None,
+	}
+}
+
+pub fn confirmations_unsupported() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Querying by confirmations is only supported by eth_ methods of a full node.".into(),
+		data: None,
+	}
+}
+
 pub fn state_corrupt() -> Error {
 	internal("State corrupt", "")
 }
@@ -323,6 +357,30 @@ pub fn signer_disabled() -> Error {
 	}
 }
 
+pub fn stratum_disabled() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Stratum server is disabled. Run with --stratum to enable it.".into(),
+		data: None,
+	}
+}
+
+pub fn signer_webhooks_disabled() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Signer webhooks are not configured. Run with --signer-webhook-secret to enable authenticated callbacks.".into(),
+		data: None,
+	}
+}
+
+pub fn invalid_callback_mac() -> Error {
+	Error {
+		code: ErrorCode::InvalidParams,
+		message: "Callback MAC does not match the request.".into(),
+		data: None,
+	}
+}
+
 pub fn ws_disabled() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
@@ -556,6 +614,14 @@ pub fn filter_not_found() -> Error {
 	}
 }
 
+pub fn stale_cursor() -> Error {
+	Error {
+		code: ErrorCode::InvalidParams,
+		message: "Block of the cursor is no longer in the canonical chain. Restart the query without a cursor.".into(),
+		data: None,
+	}
+}
+
 pub fn filter_block_not_found(id: BlockId) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST), // Specified in EIP-234.
@@ -19,17 +19,21 @@
 use std::sync::Arc;
 use std::ops::Deref;
 
+use fetch::Fetch;
+
 mod oneshot;
 mod signing_queue;
+mod webhooks;
 
-pub use self::signing_queue::{SigningQueue, ConfirmationsQueue, ConfirmationReceiver, ConfirmationResult};
-#[cfg(test)]
+pub use self::signing_queue::{SigningQueue, ConfirmationsQueue, ConfirmationReceiver, ConfirmationResult, ConfirmationSender};
+pub use self::webhooks::{SignerWebhooks, WebhookSecret, callback_message, request_hash, CONFIRM_ACTION, REJECT_ACTION};
 pub use self::signing_queue::QueueEvent;
 
 /// Manages communication with Signer crate
 pub struct SignerService {
 	is_enabled: bool,
 	queue: Arc<ConfirmationsQueue>,
+	webhook_secret: Option<WebhookSecret>,
 	generate_new_token: Box<dyn Fn() -> Result<String, String> + Send + Sync + 'static>,
 }
 
@@ -39,11 +43,24 @@ impl SignerService {
 		where F: Fn() -> Result<String, String> + Send + Sync + 'static {
 		SignerService {
 			queue: Arc::new(ConfirmationsQueue::default()),
+			webhook_secret: None,
 			generate_new_token: Box::new(new_token),
 			is_enabled,
 		}
 	}
 
+	/// Pushes queue events to given webhooks and accepts callbacks authenticated with their secret.
+	pub fn with_webhooks<F: Fetch>(mut self, webhooks: SignerWebhooks<F>) -> Self {
+		self.webhook_secret = Some(webhooks.secret().clone());
+		webhooks.attach(&self.queue);
+		self
+	}
+
+	/// Returns the secret authenticating webhook callbacks, if webhooks are configured.
+	pub fn webhook_secret(&self) -> Option<&WebhookSecret> {
+		self.webhook_secret.as_ref()
+	}
+
 	/// Generates new signer authorization token.
 	pub fn generate_token(&self) -> Result<String, String> {
 		(self.generate_new_token)()
@@ -0,0 +1,236 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Confirmation queue webhooks.
+//!
+//! Every queue event is `POST`ed as JSON to the configured URLs. The body is signed with
+//! HMAC-SHA256 using a shared secret and the hex-encoded MAC is sent in `X-Parity-Signature`.
+//! The same secret authenticates `signer_confirmRequestWithMac` / `signer_rejectRequestWithMac`
+//! callbacks, where the MAC is computed over `callback_message(action, id, request_hash, session, modification)`.
+//! Binding the request hash and the per-process session keeps a signed callback from being replayed
+//! against a different request that reused its id after a restart. Binding the modification keeps the
+//! approved gas, gas price, sender and condition from being changed on the way.
+
+use std::sync::{Arc, Weak};
+
+use crypto::hmac;
+use ethereum_types::{H256, U256};
+use hash::keccak;
+use fetch::{self, Fetch, Url};
+use futures::Future;
+use hyper::header::{CONTENT_TYPE, HeaderValue};
+use parity_runtime::Executor;
+use rustc_hex::ToHex;
+use serde_json;
+
+use v1::helpers;
+use v1::helpers::external_signer::{ConfirmationsQueue, SigningQueue, QueueEvent};
+use v1::types::{ConfirmationRequest, TransactionModification};
+
+/// Header carrying the hex-encoded HMAC-SHA256 of the notification body.
+pub const SIGNATURE_HEADER: &str = "x-parity-signature";
+
+/// Callback action confirming a request.
+pub const CONFIRM_ACTION: &str = "confirm";
+/// Callback action rejecting a request.
+pub const REJECT_ACTION: &str = "reject";
+
+/// Message that has to be authenticated by the callback for given action and request.
+/// Confirmations also cover the modification of the request, appended as JSON.
+pub fn callback_message(action: &str, id: U256, request_hash: H256, session: H256, modification: Option<&TransactionModification>) -> Vec<u8> {
+	let mut message = format!("{}:{:#x}:{:#x}:{:#x}", action, id, request_hash, session);
+	if let Some(modification) = modification {
+		message.push(':');
+		message.push_str(&serde_json::to_string(modification).expect("Modification serialization is infallible; qed"));
+	}
+	message.into_bytes()
+}
+
+/// Hash of the request as announced in the `newRequest` notification.
+pub fn request_hash(request: &helpers::ConfirmationRequest) -> H256 {
+	let request: ConfirmationRequest = request.clone().into();
+	keccak(serde_json::to_vec(&request).expect("Request serialization is infallible; qed"))
+}
+
+/// Shared secret used to sign notifications and verify callbacks.
+#[derive(Clone)]
+pub struct WebhookSecret {
+	secret: Vec<u8>,
+	session: H256,
+}
+
+impl WebhookSecret {
+	/// Create a new secret from raw bytes, with a random session nonce.
+	pub fn new(secret: Vec<u8>) -> Self {
+		WebhookSecret {
+			secret,
+			session: H256::random(),
+		}
+	}
+
+	/// Nonce identifying this process, announced in every notification.
+	pub fn session(&self) -> H256 {
+		self.session
+	}
+
+	/// Compute HMAC-SHA256 of given data.
+	pub fn sign(&self, data: &[u8]) -> Vec<u8> {
+		let key = hmac::SigKey::sha256(&self.secret);
+		hmac::sign(&key, data).to_vec()
+	}
+
+	/// Verify HMAC-SHA256 of given data in constant time.
+	pub fn verify(&self, data: &[u8], mac: &[u8]) -> bool {
+		let key = hmac::VerifyKey::sha256(&self.secret);
+		hmac::verify(&key, data, mac)
+	}
+}
+
+/// Body of a webhook notification.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Notification {
+	/// Name of the queue event.
+	event: &'static str,
+	/// Id of the confirmation request.
+	id: U256,
+	/// Session nonce the callback MAC has to cover.
+	session: H256,
+	/// Hash of the request the callback MAC has to cover, only sent for new requests.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	request_hash: Option<H256>,
+	/// Request details, only sent for new requests.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	request: Option<ConfirmationRequest>,
+}
+
+/// Pushes confirmation queue events to configured URLs.
+pub struct SignerWebhooks<F = fetch::Client> {
+	urls: Vec<Url>,
+	secret: WebhookSecret,
+	fetch: F,
+	executor: Executor,
+}
+
+impl<F: Fetch> SignerWebhooks<F> {
+	/// Create new webhooks posting to given URLs.
+	pub fn new(urls: &[String], secret: WebhookSecret, fetch: F, executor: Executor) -> Result<Self, String> {
+		let urls = urls.iter()
+			.map(|url| url.parse().map_err(|e| format!("Invalid signer webhook URL {}: {}", url, e)))
+			.collect::<Result<_, _>>()?;
+
+		Ok(SignerWebhooks { urls, secret, fetch, executor })
+	}
+
+	/// Returns the secret used to sign notifications.
+	pub fn secret(&self) -> &WebhookSecret {
+		&self.secret
+	}
+
+	/// Start notifying about events happening in given queue.
+	pub fn attach(self, queue: &Arc<ConfirmationsQueue>) {
+		let weak: Weak<ConfirmationsQueue> = Arc::downgrade(queue);
+		let session = self.secret.session();
+		queue.on_event(move |event| {
+			let notification = match event {
+				QueueEvent::NewRequest(id) => {
+					let request = weak.upgrade().and_then(|queue| queue.requests().into_iter().find(|r| r.id == id));
+					Notification {
+						event: "newRequest",
+						id,
+						session,
+						request_hash: request.as_ref().map(request_hash),
+						request: request.map(Into::into),
+					}
+				},
+				QueueEvent::RequestConfirmed(id) => Notification { event: "requestConfirmed", id, session, request_hash: None, request: None },
+				QueueEvent::RequestRejected(id) => Notification { event: "requestRejected", id, session, request_hash: None, request: None },
+				QueueEvent::Finish => return,
+			};
+			self.notify(&notification);
+		});
+	}
+
+	fn notify(&self, notification: &Notification) {
+		let body = serde_json::to_vec(notification).expect("Notification serialization is infallible; qed");
+		let signature = HeaderValue::from_str(&self.secret.sign(&body).to_hex())
+			.expect("Hex string is a valid header value; qed");
+
+		for url in &self.urls {
+			let request = fetch::Request::post(url.clone())
+				.with_header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+				.with_header(SIGNATURE_HEADER, signature.clone())
+				.with_body(body.clone());
+			let url = url.clone();
+			self.executor.spawn(self.fetch.fetch(request, Default::default())
+				.map(move |response| if !response.is_success() {
+					warn!(target: "signer", "Webhook {} responded with {}", url, response.status());
+				})
+				.map_err(|e| warn!(target: "signer", "Unable to deliver webhook notification: {:?}", e))
+			);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::{H256, U256};
+	use serde_json;
+	use v1::types::TransactionModification;
+	use super::{callback_message, Notification, WebhookSecret, CONFIRM_ACTION};
+
+	#[test]
+	fn should_verify_signed_message() {
+		let secret = WebhookSecret::new(b"secret".to_vec());
+		let hash = H256::from_low_u64_be(1);
+		let session = secret.session();
+		let modification = TransactionModification { gas_price: Some(U256::from(0x1000)), ..Default::default() };
+		let message = callback_message(CONFIRM_ACTION, U256::from(15), hash, session, Some(&modification));
+		assert_eq!(
+			message,
+			format!(r#"confirm:0xf:{:#x}:{:#x}:{{"sender":null,"gasPrice":"0x1000","gas":null,"condition":null}}"#, hash, session).into_bytes()
+		);
+
+		let mac = secret.sign(&message);
+		assert!(secret.verify(&message, &mac));
+		assert!(!secret.verify(&callback_message(CONFIRM_ACTION, U256::from(16), hash, session, Some(&modification)), &mac));
+		assert!(!secret.verify(&callback_message(CONFIRM_ACTION, U256::from(15), H256::from_low_u64_be(2), session, Some(&modification)), &mac));
+		assert!(!secret.verify(&callback_message(CONFIRM_ACTION, U256::from(15), hash, H256::zero(), Some(&modification)), &mac));
+		assert!(!secret.verify(&callback_message(CONFIRM_ACTION, U256::from(15), hash, session, Some(&Default::default())), &mac));
+		assert!(!secret.verify(&callback_message(CONFIRM_ACTION, U256::from(15), hash, session, None), &mac));
+		assert!(!WebhookSecret::new(b"other".to_vec()).verify(&message, &mac));
+	}
+
+	#[test]
+	fn should_use_new_session_per_secret() {
+		assert_ne!(WebhookSecret::new(b"secret".to_vec()).session(), WebhookSecret::new(b"secret".to_vec()).session());
+	}
+
+	#[test]
+	fn should_serialize_notification() {
+		let notification = Notification {
+			event: "requestRejected",
+			id: U256::from(1),
+			session: H256::zero(),
+			request_hash: None,
+			request: None,
+		};
+		assert_eq!(
+			serde_json::to_string(&notification).unwrap(),
+			r#"{"event":"requestRejected","id":"0x1","session":"0x0000000000000000000000000000000000000000000000000000000000000000"}"#
+		);
+	}
+}
@@ -29,8 +29,10 @@ pub mod fake_sign;
 pub mod ipfs;
 pub mod light_fetch;
 pub mod nonce;
+pub mod remote_signer;
 #[cfg(any(test, feature = "accounts"))]
 pub mod secretstore;
+pub mod stratum;
 
 mod network_settings;
 mod poll_filter;
@@ -42,7 +44,9 @@ mod work;
 mod signature;
 
 pub use self::dispatch::{Dispatcher, FullDispatcher, LightDispatcher};
-pub use self::signature::verify_signature;
+pub use self::signature::{
+	verify_signature, recover_typed_data_signer, recover_signer, encode_is_valid_signature, is_eip1271_magic_value,
+};
 pub use self::network_settings::NetworkSettings;
 pub use self::poll_manager::PollManager;
 pub use self::poll_filter::{PollFilter, SyncPollFilter, limit_logs};
@@ -51,7 +55,7 @@ pub use self::requests::{
 };
 pub use self::subscribers::Subscribers;
 pub use self::subscription_manager::GenericPollManager;
-pub use self::work::submit_work_detail;
+pub use self::work::{submit_work_accepted, submit_work_detail, submit_work_status};
 
 pub fn to_url(address: &Option<::Host>) -> Option<String> {
 	address.as_ref().map(|host| (**host).to_owned())
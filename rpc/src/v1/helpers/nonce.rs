@@ -15,7 +15,7 @@
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::{cmp, mem};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::{atomic, Arc};
 use std::sync::atomic::{AtomicBool, AtomicUsize};
 
@@ -30,6 +30,8 @@ use parity_runtime::Executor;
 #[derive(Debug)]
 pub struct Reservations {
 	nonces: HashMap<Address, SenderReservations>,
+	/// Nonces reserved by external signers.
+	external: HashMap<Address, BTreeSet<U256>>,
 	executor: Executor,
 }
 impl Reservations {
@@ -41,23 +43,70 @@ impl Reservations {
 	pub fn new(executor: Executor) -> Self {
 		Reservations {
 			nonces: Default::default(),
+			external: Default::default(),
 			executor,
 		}
 	}
 
 	/// Reserve a nonce for particular address.
 	///
-	/// The reserved nonce cannot be smaller than the minimal nonce.
+	/// The reserved nonce cannot be smaller than the minimal nonce
+	/// and is always higher than nonces reserved by external signers.
 	pub fn reserve(&mut self, sender: Address, minimal: U256) -> Reserved {
 		if self.nonces.len() + 1 > Self::CLEAN_AT {
 			self.nonces.retain(|_, v| !v.is_empty());
 		}
 
+		self.prune_external(&sender, minimal);
+		let minimal = match self.external.get(&sender).and_then(|reserved| reserved.iter().next_back()) {
+			Some(last) => cmp::max(minimal, *last + 1),
+			None => minimal,
+		};
+
 		let executor = &self.executor;
 		self.nonces.entry(sender)
 			.or_insert_with(move || SenderReservations::new(executor.clone()))
 			.reserve_nonce(minimal)
 	}
+
+	/// Reserve a nonce for an external signer.
+	///
+	/// Returns the lowest nonce not smaller than the minimal nonce, which is not prospectively used
+	/// by transactions signed by the node and not reserved by other external signers.
+	/// The nonce stays reserved until it's released or the minimal nonce passes it.
+	pub fn reserve_external(&mut self, sender: Address, minimal: U256) -> U256 {
+		self.prune_external(&sender, minimal);
+		let mut nonce = self.nonces.get(&sender)
+			.map_or(minimal, |reservations| cmp::max(minimal, reservations.prospective_value()));
+
+		let reserved = self.external.entry(sender).or_insert_with(Default::default);
+		while reserved.contains(&nonce) {
+			nonce = nonce + 1;
+		}
+		reserved.insert(nonce);
+		nonce
+	}
+
+	/// Release a nonce reserved by an external signer.
+	///
+	/// Returns `false` if the nonce was not reserved.
+	pub fn release_external(&mut self, sender: &Address, nonce: &U256) -> bool {
+		let released = self.external.get_mut(sender).map_or(false, |reserved| reserved.remove(nonce));
+		if self.external.get(sender).map_or(false, BTreeSet::is_empty) {
+			self.external.remove(sender);
+		}
+		released
+	}
+
+	/// Forget external reservations of nonces that are already used.
+	fn prune_external(&mut self, sender: &Address, minimal: U256) {
+		if let Some(reserved) = self.external.get_mut(sender) {
+			*reserved = reserved.split_off(&minimal);
+			if reserved.is_empty() {
+				self.external.remove(sender);
+			}
+		}
+	}
 }
 
 /// Manages currently reserved and prospective nonces.
@@ -122,6 +171,11 @@ impl SenderReservations {
 		}
 	}
 
+	/// Returns the nonce that would be prospectively reserved next.
+	pub fn prospective_value(&self) -> U256 {
+		self.prospective_value - self.dropped.load(atomic::Ordering::SeqCst)
+	}
+
 	/// Returns true if there are no reserved nonces.
 	pub fn is_empty(&self) -> bool {
 		self.previous_ready.load(atomic::Ordering::SeqCst)
@@ -294,6 +348,30 @@ mod tests {
 		assert!(nonces.is_empty());
 	}
 
+	#[test]
+	fn should_skip_externally_reserved_nonces() {
+		let runtime = Runtime::with_thread_count(1);
+		let mut nonces = Reservations::new(runtime.executor());
+		let sender = Address::from_low_u64_be(1);
+
+		assert_eq!(nonces.reserve_external(sender, 5.into()), U256::from(5));
+		assert_eq!(nonces.reserve_external(sender, 5.into()), U256::from(6));
+
+		// Signing by the node continues after external reservations
+		let n1 = nonces.reserve(sender, 5.into());
+		assert_eq!(n1.prospective_value(), &U256::from(7));
+		assert_eq!(nonces.reserve_external(sender, 5.into()), U256::from(8));
+
+		// Released nonce is reserved again
+		assert!(nonces.release_external(&sender, &8.into()));
+		assert!(!nonces.release_external(&sender, &8.into()));
+		assert_eq!(nonces.reserve_external(sender, 5.into()), U256::from(8));
+
+		// Used nonces are forgotten
+		assert_eq!(nonces.reserve_external(sender, 9.into()), U256::from(9));
+		assert!(!nonces.release_external(&sender, &6.into()));
+	}
+
 	#[test]
 	fn should_return_prospective_nonce() {
 		let runtime = Runtime::with_thread_count(1);
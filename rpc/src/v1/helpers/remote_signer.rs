@@ -0,0 +1,315 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! An `EngineSigner` delegating consensus signatures to an external signing service.
+//!
+//! The service is queried with a JSON-RPC request:
+//! `{"jsonrpc":"2.0","id":1,"method":"signer_signHash","params":["<address>","<hash>"]}`
+//! and must respond with the 65-byte recoverable signature of the (bare) hash.
+//! Over HTTP the request is sent as a `POST` body, over IPC (unix socket) as a single line.
+
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crypto::publickey::{self, Address, Message, Public, Signature, Error};
+use ethereum_types::H520;
+use serde_json::{self, Value};
+
+/// Default timeout of a single remote signing request.
+pub const DEFAULT_REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Upper bound on the size of the signing service response.
+const MAX_RESPONSE_SIZE: u64 = 64 * 1024;
+
+#[derive(Serialize)]
+struct SignHashRequest {
+	jsonrpc: &'static str,
+	id: u64,
+	method: &'static str,
+	params: (Address, Message),
+}
+
+/// Location of the remote signing service.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteSignerEndpoint {
+	/// Plain HTTP endpoint.
+	Http {
+		/// `host:port` to connect to.
+		host: String,
+		/// Request path.
+		path: String,
+	},
+	/// Path to the IPC socket.
+	Ipc(PathBuf),
+}
+
+impl RemoteSignerEndpoint {
+	/// Parses `http://host:port/path` URLs; anything else is treated as an IPC socket path.
+	pub fn parse(endpoint: &str) -> Result<Self, String> {
+		if endpoint.is_empty() {
+			return Err("Remote signer endpoint can't be empty.".into());
+		}
+		if endpoint.starts_with("https://") {
+			return Err(format!("Remote signer endpoint {} uses TLS which is not supported. Expose the signer via plain HTTP on a trusted interface or via IPC.", endpoint));
+		}
+		if !endpoint.starts_with("http://") {
+			return Ok(RemoteSignerEndpoint::Ipc(endpoint.into()));
+		}
+
+		let rest = &endpoint["http://".len()..];
+		let (host, path) = match rest.find('/') {
+			Some(idx) => (&rest[..idx], &rest[idx..]),
+			None => (rest, "/"),
+		};
+		if host.is_empty() {
+			return Err(format!("Invalid remote signer URL: {}", endpoint));
+		}
+		let host = if host.rfind(':').map_or(false, |idx| host[idx + 1..].parse::<u16>().is_ok()) {
+			host.to_owned()
+		} else {
+			format!("{}:80", host)
+		};
+
+		Ok(RemoteSignerEndpoint::Http { host, path: path.to_owned() })
+	}
+}
+
+/// An `EngineSigner` asking an external service for signatures.
+///
+/// Every returned signature is checked to recover to the configured address.
+/// If the service fails (or times out) the optional fallback signer is used instead.
+pub struct RemoteSigner {
+	endpoint: RemoteSignerEndpoint,
+	address: Address,
+	timeout: Duration,
+	fallback: Option<Box<dyn engine::signer::EngineSigner>>,
+}
+
+impl RemoteSigner {
+	/// Creates new `RemoteSigner` for given address.
+	pub fn new(
+		endpoint: RemoteSignerEndpoint,
+		address: Address,
+		timeout: Duration,
+		fallback: Option<Box<dyn engine::signer::EngineSigner>>,
+	) -> Self {
+		RemoteSigner { endpoint, address, timeout, fallback }
+	}
+
+	fn remote_sign(&self, message: &Message) -> Result<Signature, String> {
+		let request = serde_json::to_vec(&SignHashRequest {
+			jsonrpc: "2.0",
+			id: 1,
+			method: "signer_signHash",
+			params: (self.address, *message),
+		}).map_err(|e| e.to_string())?;
+
+		let response = match self.endpoint {
+			RemoteSignerEndpoint::Http { ref host, ref path } => self.http_request(host, path, &request),
+			RemoteSignerEndpoint::Ipc(ref path) => self.ipc_request(path, &request),
+		}.map_err(|e| format!("Remote signer request failed: {}", e))?;
+
+		let response: Value = serde_json::from_slice(&response)
+			.map_err(|e| format!("Invalid remote signer response: {}", e))?;
+		if let Some(error) = response.get("error") {
+			return Err(format!("Remote signer returned an error: {}", error));
+		}
+		let signature: H520 = response.get("result").cloned()
+			.ok_or_else(|| "Remote signer response is missing result".to_owned())
+			.and_then(|result| serde_json::from_value(result).map_err(|e| format!("Invalid remote signature: {}", e)))?;
+		let signature = Signature::from(signature);
+
+		let signer = publickey::recover(&signature, message)
+			.map(|public| publickey::public_to_address(&public))
+			.map_err(|e| format!("Unable to recover remote signature: {}", e))?;
+		if signer != self.address {
+			return Err(format!("Remote signature recovers to {:?} instead of {:?}", signer, self.address));
+		}
+
+		Ok(signature)
+	}
+
+	fn http_request(&self, host: &str, path: &str, body: &[u8]) -> io::Result<Vec<u8>> {
+		let address = host.to_socket_addrs()?.next()
+			.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "unable to resolve remote signer host"))?;
+		let mut stream = TcpStream::connect_timeout(&address, self.timeout)?;
+		stream.set_read_timeout(Some(self.timeout))?;
+		stream.set_write_timeout(Some(self.timeout))?;
+
+		write!(
+			stream,
+			"POST {} HTTP/1.0\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+			path, host, body.len(),
+		)?;
+		stream.write_all(body)?;
+		stream.flush()?;
+
+		let mut response = Vec::new();
+		stream.take(MAX_RESPONSE_SIZE).read_to_end(&mut response)?;
+
+		let header_end = response.windows(4).position(|w| w == b"\r\n\r\n")
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed HTTP response"))?;
+		let status_ok = response.split(|b| *b == b' ').nth(1).map_or(false, |code| code == b"200");
+		if !status_ok {
+			return Err(io::Error::new(io::ErrorKind::Other, "unexpected HTTP status"));
+		}
+
+		Ok(response.split_off(header_end + 4))
+	}
+
+	#[cfg(unix)]
+	fn ipc_request(&self, path: &PathBuf, body: &[u8]) -> io::Result<Vec<u8>> {
+		use std::io::{BufRead, BufReader};
+		use std::os::unix::net::UnixStream;
+
+		let mut stream = UnixStream::connect(path)?;
+		stream.set_read_timeout(Some(self.timeout))?;
+		stream.set_write_timeout(Some(self.timeout))?;
+		stream.write_all(body)?;
+		stream.write_all(b"\n")?;
+		stream.flush()?;
+
+		let mut line = Vec::new();
+		BufReader::new(stream.take(MAX_RESPONSE_SIZE)).read_until(b'\n', &mut line)?;
+		Ok(line)
+	}
+
+	#[cfg(not(unix))]
+	fn ipc_request(&self, _path: &PathBuf, _body: &[u8]) -> io::Result<Vec<u8>> {
+		Err(io::Error::new(io::ErrorKind::Other, "IPC remote signer is only supported on unix platforms"))
+	}
+}
+
+impl engine::signer::EngineSigner for RemoteSigner {
+	fn sign(&self, message: Message) -> Result<Signature, Error> {
+		match self.remote_sign(&message) {
+			Ok(signature) => Ok(signature),
+			Err(e) => match self.fallback {
+				Some(ref fallback) => {
+					warn!("{}. Using local signer instead.", e);
+					fallback.sign(message)
+				},
+				None => {
+					warn!("{}", e);
+					Err(Error::Custom(e))
+				},
+			},
+		}
+	}
+
+	fn decrypt(&self, auth_data: &[u8], cipher: &[u8]) -> Result<Vec<u8>, Error> {
+		match self.fallback {
+			Some(ref fallback) => fallback.decrypt(auth_data, cipher),
+			None => Err(Error::Custom("Decryption is not supported by the remote signer".into())),
+		}
+	}
+
+	fn address(&self) -> Address {
+		self.address
+	}
+
+	fn public(&self) -> Option<Public> {
+		self.fallback.as_ref().and_then(|fallback| fallback.public())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::{BufRead, BufReader, Read, Write};
+	use std::net::TcpListener;
+	use std::thread;
+	use std::time::Duration;
+
+	use crypto::publickey::{Generator, KeyPair, Random, sign};
+	use engine::signer::{EngineSigner, from_keypair};
+	use ethereum_types::{H256, H520};
+	use super::{RemoteSigner, RemoteSignerEndpoint};
+
+	fn serve_once(response: String) -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		thread::spawn(move || {
+			let (stream, _) = listener.accept().unwrap();
+			let mut reader = BufReader::new(stream);
+			let mut content_length = 0;
+			loop {
+				let mut line = String::new();
+				reader.read_line(&mut line).unwrap();
+				if line.to_lowercase().starts_with("content-length:") {
+					content_length = line[15..].trim().parse().unwrap();
+				}
+				if line == "\r\n" {
+					break;
+				}
+			}
+			let mut body = vec![0u8; content_length];
+			reader.read_exact(&mut body).unwrap();
+			let mut stream = reader.into_inner();
+			write!(stream, "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n{}", response).unwrap();
+		});
+		format!("http://{}/", address)
+	}
+
+	#[test]
+	fn should_parse_endpoints() {
+		assert_eq!(RemoteSignerEndpoint::parse("http://localhost:8550/sign"), Ok(RemoteSignerEndpoint::Http {
+			host: "localhost:8550".into(),
+			path: "/sign".into(),
+		}));
+		assert_eq!(RemoteSignerEndpoint::parse("http://signer"), Ok(RemoteSignerEndpoint::Http {
+			host: "signer:80".into(),
+			path: "/".into(),
+		}));
+		assert_eq!(RemoteSignerEndpoint::parse("/tmp/signer.ipc"), Ok(RemoteSignerEndpoint::Ipc("/tmp/signer.ipc".into())));
+		assert!(RemoteSignerEndpoint::parse("https://signer").is_err());
+	}
+
+	#[test]
+	fn should_accept_valid_remote_signature() {
+		let keypair = Random.generate().unwrap();
+		let hash = H256::from_low_u64_be(1);
+		let signature = sign(keypair.secret(), &hash).unwrap();
+		let url = serve_once(format!(r#"{{"jsonrpc":"2.0","id":1,"result":"{:?}"}}"#, H520::from(signature.clone())));
+
+		let signer = RemoteSigner::new(RemoteSignerEndpoint::parse(&url).unwrap(), keypair.address(), Duration::from_secs(5), None);
+
+		assert_eq!(signer.sign(hash).unwrap(), signature);
+	}
+
+	#[test]
+	fn should_fall_back_on_signature_from_wrong_key() {
+		let keypair = Random.generate().unwrap();
+		let other = Random.generate().unwrap();
+		let hash = H256::from_low_u64_be(1);
+		let signature = sign(other.secret(), &hash).unwrap();
+		let url = serve_once(format!(r#"{{"jsonrpc":"2.0","id":1,"result":"{:?}"}}"#, H520::from(signature)));
+
+		let fallback = from_keypair(KeyPair::from_secret(keypair.secret().clone()).unwrap());
+		let signer = RemoteSigner::new(RemoteSignerEndpoint::parse(&url).unwrap(), keypair.address(), Duration::from_secs(5), Some(fallback));
+
+		assert_eq!(signer.sign(hash).unwrap(), sign(keypair.secret(), &hash).unwrap());
+	}
+
+	#[test]
+	fn should_fail_without_fallback_when_unreachable() {
+		let keypair = Random.generate().unwrap();
+		let signer = RemoteSigner::new(RemoteSignerEndpoint::parse("http://127.0.0.1:1/").unwrap(), keypair.address(), Duration::from_millis(100), None);
+
+		assert!(signer.sign(H256::zero()).is_err());
+	}
+}
@@ -15,13 +15,18 @@
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use crypto::publickey::{recover, public_to_address, Signature};
-use ethereum_types::{H256, U64};
+use eip_712::{hash_structured_data, EIP712};
+use ethereum_types::{Address, H256, U64};
 use jsonrpc_core::Result;
 use v1::types::{Bytes, RecoveredAccount};
 use v1::helpers::errors;
 use v1::helpers::dispatch::eth_data_hash;
 use hash::keccak;
 
+/// Value returned by `isValidSignature(bytes32,bytes)` of EIP-1271 contracts for valid signatures.
+/// It is also the function selector.
+pub const EIP1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
 /// helper method for parity_verifySignature
 pub fn verify_signature(
 	is_prefixed: bool,
@@ -36,6 +41,23 @@ pub fn verify_signature(
 	} else {
 		keccak(message.0)
 	};
+	recover_account(hash, r, s, v, chain_id)
+}
+
+/// helper method for parity_recoverTypedDataSigner
+pub fn recover_typed_data_signer(
+	typed_data: EIP712,
+	r: H256,
+	s: H256,
+	v: U64,
+	chain_id: Option<u64>
+) -> Result<RecoveredAccount> {
+	let hash = hash_structured_data(typed_data)
+		.map_err(|err| errors::invalid_call_data(err.kind()))?;
+	recover_account(hash, r, s, v, chain_id)
+}
+
+fn recover_account(hash: H256, r: H256, s: H256, v: U64, chain_id: Option<u64>) -> Result<RecoveredAccount> {
 	let v = v.as_u64();
 	let is_valid_for_current_chain = match (chain_id, v) {
 		(None, v) if v == 0 || v == 1 => true,
@@ -51,6 +73,41 @@ pub fn verify_signature(
 	Ok(RecoveredAccount { address, public_key, is_valid_for_current_chain })
 }
 
+/// Recover the signer of `hash` from a 65 bytes `r || s || v` signature.
+/// `v` may be given either as 0/1 or as 27/28.
+pub fn recover_signer(hash: &H256, signature: &[u8]) -> Option<Address> {
+	if signature.len() != 65 {
+		return None;
+	}
+	let v = match signature[64] {
+		v @ 0..=1 => v,
+		v @ 27..=28 => v - 27,
+		_ => return None,
+	};
+	let signature = Signature::from_rsv(&H256::from_slice(&signature[0..32]), &H256::from_slice(&signature[32..64]), v);
+	recover(&signature, hash).ok().map(|public| public_to_address(&public))
+}
+
+/// Encode the EIP-1271 `isValidSignature(bytes32,bytes)` call.
+pub fn encode_is_valid_signature(hash: &H256, signature: &[u8]) -> Vec<u8> {
+	let padded_len = (signature.len() + 31) / 32 * 32;
+	let mut data = Vec::with_capacity(4 + 32 * 3 + padded_len);
+	data.extend_from_slice(&EIP1271_MAGIC_VALUE);
+	data.extend_from_slice(hash.as_bytes());
+	data.extend_from_slice(H256::from_low_u64_be(64).as_bytes());
+	data.extend_from_slice(H256::from_low_u64_be(signature.len() as u64).as_bytes());
+	data.extend_from_slice(signature);
+	data.resize(4 + 32 * 3 + padded_len, 0);
+	data
+}
+
+/// Check whether the output of an EIP-1271 call signals a valid signature.
+pub fn is_eip1271_magic_value(output: &[u8]) -> bool {
+	// `bytes4` return values are left-aligned in a single 32 bytes word, anything else (e.g. a
+	// fallback echoing the calldata, which starts with the same selector) is no valid answer.
+	output.len() == 32 && output[0..4] == EIP1271_MAGIC_VALUE && output[4..].iter().all(|b| *b == 0)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -164,6 +221,42 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn test_recover_signer() {
+		let account = Random.generate().unwrap();
+		let hash = keccak(b"message");
+		let sig = crypto::publickey::sign(account.secret(), &hash).unwrap();
+		let mut bytes = sig[..].to_vec();
+		assert_eq!(recover_signer(&hash, &bytes), Some(account.address()));
+
+		bytes[64] += 27;
+		assert_eq!(recover_signer(&hash, &bytes), Some(account.address()));
+
+		bytes.pop();
+		assert_eq!(recover_signer(&hash, &bytes), None);
+	}
+
+	#[test]
+	fn test_encode_is_valid_signature() {
+		let data = encode_is_valid_signature(&H256::repeat_byte(0x11), &[0xaa; 65]);
+		assert_eq!(data.len(), 4 + 32 * 3 + 96);
+		assert_eq!(data[0..4], EIP1271_MAGIC_VALUE);
+		assert_eq!(data[36..68], H256::from_low_u64_be(64).as_bytes()[..]);
+		assert_eq!(data[68..100], H256::from_low_u64_be(65).as_bytes()[..]);
+		assert_eq!(data[100..165], [0xaa; 65][..]);
+		assert!(data[165..].iter().all(|b| *b == 0));
+
+		let mut output = vec![0u8; 32];
+		assert!(!is_eip1271_magic_value(&output));
+		output[0..4].copy_from_slice(&EIP1271_MAGIC_VALUE);
+		assert!(is_eip1271_magic_value(&output));
+
+		output[31] = 1;
+		assert!(!is_eip1271_magic_value(&output));
+		assert!(!is_eip1271_magic_value(&EIP1271_MAGIC_VALUE));
+		assert!(!is_eip1271_magic_value(&data));
+	}
+
 	#[test]
 	fn test_verify_signature_no_chain_replay_protection() {
 		run_test(TestCase {
@@ -22,17 +22,41 @@ use rlp;
 use ethcore::miner::{BlockChainClient, MinerService};
 use ethereum_types::{H64, H256};
 use jsonrpc_core::Error;
+use types::errors::EthcoreError;
 use v1::helpers::errors;
+use v1::types::SubmittedWork;
 
 // Submit a POW work and return the block's hash
 pub fn submit_work_detail<C: BlockChainClient, M: MinerService>(client: &Arc<C>, miner: &Arc<M>, nonce: H64, pow_hash: H256, mix_hash: H256) -> Result<H256, Error> {
-	// TODO [ToDr] Should disallow submissions in case of PoA?
-	trace!(target: "miner", "submit_work_detail: Decoded: nonce={}, pow_hash={}, mix_hash={}", nonce, pow_hash, mix_hash);
-	let seal = vec![rlp::encode(&mix_hash), rlp::encode(&nonce)];
-	miner.submit_seal(pow_hash, seal)
-		.and_then(|block| client.import_sealed_block(block))
+	submit_work_status(client, miner, nonce, pow_hash, mix_hash).map(|work| work.block_hash)
+}
+
+// Submit a POW work and return the block's hash together with whether the work was stale
+pub fn submit_work_status<C: BlockChainClient, M: MinerService>(client: &Arc<C>, miner: &Arc<M>, nonce: H64, pow_hash: H256, mix_hash: H256) -> Result<SubmittedWork, Error> {
+	submit_work(client, miner, nonce, pow_hash, mix_hash)
 		.map_err(|e| {
 			warn!(target: "miner", "Cannot submit work - {:?}.", e);
 			errors::cannot_submit_work(e)
 		})
 }
+
+// Submit a POW work and return whether it was accepted, solutions meeting only the test difficulty included
+pub fn submit_work_accepted<C: BlockChainClient, M: MinerService>(client: &Arc<C>, miner: &Arc<M>, nonce: H64, pow_hash: H256, mix_hash: H256) -> bool {
+	match submit_work(client, miner, nonce, pow_hash, mix_hash) {
+		Ok(_) => true,
+		// the miner counts test shares separately, to the rig they are valid solutions
+		Err(EthcoreError::PowTestShare) => true,
+		Err(e) => {
+			warn!(target: "miner", "Cannot submit work - {:?}.", e);
+			false
+		},
+	}
+}
+
+fn submit_work<C: BlockChainClient, M: MinerService>(client: &Arc<C>, miner: &Arc<M>, nonce: H64, pow_hash: H256, mix_hash: H256) -> Result<SubmittedWork, EthcoreError> {
+	// TODO [ToDr] Should disallow submissions in case of PoA?
+	trace!(target: "miner", "submit_work_detail: Decoded: nonce={}, pow_hash={}, mix_hash={}", nonce, pow_hash, mix_hash);
+	let seal = vec![rlp::encode(&mix_hash), rlp::encode(&nonce)];
+	miner.submit_work_seal(pow_hash, seal)
+		.and_then(|(block, stale)| client.import_sealed_block(block).map(|block_hash| SubmittedWork { block_hash, stale }))
+}
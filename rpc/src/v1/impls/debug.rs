@@ -19,12 +19,20 @@
 use std::sync::Arc;
 
 use client_traits::BlockChainClient;
+use ethereum_types::{H160, H256};
 use types::header::Header;
 use types::transaction::LocalizedTransaction;
 
 use jsonrpc_core::Result;
 use v1::traits::Debug;
-use v1::types::{Block, Bytes, RichBlock, BlockTransactions, Transaction};
+use v1::helpers::errors;
+use v1::types::{
+	Block, BlockNumber, Bytes, RichBlock, BlockTransactions, StorageRange, Transaction,
+	block_number_to_id,
+};
+
+/// Maximal number of storage entries returned by `debug_storageRangeAt`.
+const MAX_STORAGE_RANGE: u64 = 1024;
 
 /// Debug rpc implementation.
 pub struct DebugClient<C> {
@@ -46,7 +54,8 @@ impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
 			(*t).into()
 		}
 
-		Ok(self.client.bad_blocks().into_iter().map(|(block, reason)| {
+		Ok(self.client.bad_blocks().into_iter().map(|bad| {
+			let block = bad.block;
 			let number = block.header.number();
 			let hash = block.header.hash();
 			RichBlock {
@@ -65,6 +74,7 @@ impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
 					logs_bloom: Some(cast(block.header.log_bloom())),
 					timestamp: block.header.timestamp().into(),
 					difficulty: cast(block.header.difficulty()),
+					base_fee_per_gas: block.header.base_fee(),
 					total_difficulty: None,
 					seal_fields: block.header.seal().iter().cloned().map(Into::into).collect(),
 					uncles: block.uncles.iter().map(Header::hash).collect(),
@@ -83,13 +93,28 @@ impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
 					extra_data: block.header.extra_data().clone().into(),
 				},
 				extra_info: vec![
-					("reason".to_owned(), reason),
+					("reason".to_owned(), bad.message),
 					("rlp".to_owned(), serialize(&Bytes(block.bytes))),
 					("hash".to_owned(), format!("{:#x}", hash)),
 				].into_iter().collect(),
 			}
 		}).collect())
 	}
+
+	fn storage_range_at(&self, address: H160, start: Option<H256>, limit: u64, block_number: Option<BlockNumber>) -> Result<Option<StorageRange>> {
+		if limit > MAX_STORAGE_RANGE {
+			return Err(errors::invalid_params("limit", format!("At most {} entries can be requested", MAX_STORAGE_RANGE)));
+		}
+
+		let id = match block_number.unwrap_or_default() {
+			BlockNumber::Pending => return Err(errors::invalid_params("block", "Pending block is not supported")),
+			BlockNumber::StateRoot(_) => return Err(errors::state_root_unsupported()),
+			BlockNumber::Confirmed(_) => return Err(errors::confirmations_unsupported()),
+			num => block_number_to_id(num),
+		};
+
+		Ok(self.client.storage_range(id, &address, start.as_ref(), limit).map(Into::into))
+	}
 }
 
 fn serialize<T: ::serde::Serialize>(t: &T) -> String {
@@ -16,6 +16,8 @@
 
 //! Eth rpc implementation.
 
+use std::cmp;
+use std::collections::HashMap;
 use std::thread;
 use std::time::{Instant, Duration, SystemTime, UNIX_EPOCH};
 use std::sync::Arc;
@@ -33,8 +35,11 @@ use snapshot::SnapshotService;
 use hash::keccak;
 use miner::external::ExternalMinerService;
 use sync::SyncProvider;
+use trace::TraceError;
+use trace::trace::Res;
 use types::{
 	BlockNumber as EthBlockNumber,
+	call_analytics::CallAnalytics,
 	client_types::StateResult,
 	encoded,
 	header::Header,
@@ -53,8 +58,8 @@ use v1::helpers::dispatch::{FullDispatcher, default_gas_price};
 use v1::traits::Eth;
 use v1::types::{
 	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
-	Transaction, CallRequest, Index, Filter, Log, Receipt, Work, EthAccount, StorageProof,
-	block_number_to_id
+	SyncDetails, SnapshotRestoration, Transaction, CallRequest, Index, Filter, Log, Receipt, Work, EthAccount, StorageProof,
+	Cursor, Page, DEFAULT_PAGE_SIZE, block_number_to_id
 };
 use v1::metadata::Metadata;
 
@@ -78,6 +83,10 @@ pub struct EthClientOptions {
 	pub allow_experimental_rpcs: bool,
 	/// flag for ancient block sync
 	pub no_ancient_blocks: bool,
+	/// Number of confirmations kept between the chain head and the block read as "latest".
+	pub latest_confirmations: u64,
+	/// Maximal number of logs returned in a page.
+	pub page_size: usize,
 }
 
 impl EthClientOptions {
@@ -100,6 +109,8 @@ impl Default for EthClientOptions {
 			allow_missing_blocks: false,
 			allow_experimental_rpcs: false,
 			no_ancient_blocks: false,
+			latest_confirmations: 0,
+			page_size: DEFAULT_PAGE_SIZE,
 		}
 	}
 }
@@ -183,6 +194,87 @@ pub fn base_logs<C, M, T: StateInfo + 'static> (client: &C, miner: &M, filter: F
 	Box::new(future::ok(logs))
 }
 
+/// Mined logs matching the filter which follow its cursor, at most `page_size` of them.
+pub fn base_logs_page<C: BlockChainClient>(client: &C, mut filter: Filter, page_size: usize) -> Result<Page<Log>> {
+	let cursor = filter.cursor.take();
+	let page_size = filter.limit.map_or(page_size, |limit| cmp::min(limit, page_size));
+	let mut filter: EthcoreFilter = filter.try_into()?;
+	// pages are taken from the start of the range, `limit` would keep its end
+	filter.limit = None;
+	if let Some(cursor) = cursor {
+		if client.block_hash(BlockId::Number(cursor.block)) != Some(cursor.hash) {
+			return Err(errors::stale_cursor());
+		}
+	}
+
+	let from = client.block_number(filter.from_block).ok_or_else(|| errors::filter_block_not_found(filter.from_block))?;
+	let to = client.block_number(filter.to_block).ok_or_else(|| errors::filter_block_not_found(filter.to_block))?;
+	let page = Page::scan(from, to, cursor, page_size, |start, end| {
+		let filter = EthcoreFilter {
+			from_block: BlockId::Number(start),
+			to_block: BlockId::Number(end),
+			..filter.clone()
+		};
+		let logs = client.logs(filter).map_err(errors::filter_block_not_found)?;
+		Ok(logs.into_iter().map(|log| (Cursor {
+			block: log.block_number,
+			hash: log.block_hash,
+			transaction: log.transaction_index as u64,
+			index: log.log_index as u64,
+		}, log)).collect())
+	})?;
+
+	Ok(page.map(Log::from))
+}
+
+/// Fills in the revert reason of a failed transaction, see `with_revert_reasons`.
+pub fn with_revert_reason<C: BlockChainClient>(client: &C, receipt: Receipt) -> Receipt {
+	with_revert_reasons(client, vec![receipt]).pop().expect("One receipt is passed in and returned; qed")
+}
+
+/// Fills in the revert reasons of failed transactions from a single block. Only transactions whose
+/// trace is available and shows they were reverted are considered, so tracing has to be enabled.
+/// The block is replayed once, up to the last reverted transaction. Replayed contract creations
+/// keep their return data, so reverted deployments get a reason as well.
+pub fn with_revert_reasons<C: BlockChainClient>(client: &C, mut receipts: Vec<Receipt>) -> Vec<Receipt> {
+	let mut reverted: HashMap<H256, usize> = receipts.iter()
+		.enumerate()
+		.filter_map(|(index, receipt)| match receipt.transaction_hash {
+			Some(hash) if receipt.status_code == Some(U64::zero()) && is_reverted(client, hash) => Some((hash, index)),
+			_ => None,
+		})
+		.collect();
+
+	let block_hash = match reverted.values().next().and_then(|index| receipts[*index].block_hash) {
+		Some(block_hash) => block_hash,
+		None => return receipts,
+	};
+
+	let replayed = match client.replay_block_transactions(BlockId::Hash(block_hash), CallAnalytics::default()) {
+		Ok(replayed) => replayed,
+		Err(_) => return receipts,
+	};
+
+	for (hash, executed) in replayed {
+		if let Some(index) = reverted.remove(&hash) {
+			receipts[index].revert_reason = Receipt::decode_revert_reason(&executed.output);
+		}
+		if reverted.is_empty() {
+			break;
+		}
+	}
+	receipts
+}
+
+fn is_reverted<C: BlockChainClient>(client: &C, hash: H256) -> bool {
+	client.transaction_traces(TransactionId::Hash(hash))
+		.and_then(|traces| traces.into_iter().next())
+		.map_or(false, |trace| match trace.result {
+			Res::FailedCall(TraceError::Reverted) | Res::FailedCreate(TraceError::Reverted) => true,
+			_ => false,
+		})
+}
+
 impl<C, SN: ?Sized, S: ?Sized, M, EM, T> EthClient<C, SN, S, M, EM> where
 	C: miner::BlockChainClient + BlockChainClient + StateClient<State=T> + Call<State=T> + EngineInfo,
 	SN: SnapshotService,
@@ -254,6 +346,8 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T> EthClient<C, SN, S, M, EM> where
 					BlockNumber::Latest => BlockId::Latest,
 					BlockNumber::Earliest => BlockId::Earliest,
 					BlockNumber::Num(n) => BlockId::Number(n),
+					BlockNumber::StateRoot(_) => return Err(errors::state_root_unsupported()),
+					BlockNumber::Confirmed(_) => unreachable!(), // Resolved by `confirmed`
 					BlockNumber::Pending => unreachable!() // Already covered
 				};
 
@@ -292,6 +386,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T> EthClient<C, SN, S, M, EM> where
 						},
 						timestamp: view.timestamp().into(),
 						difficulty: view.difficulty(),
+						base_fee_per_gas: view.base_fee(),
 						total_difficulty: Some(total_difficulty),
 						seal_fields: view.seal().into_iter().map(Into::into).collect(),
 						uncles: block.uncle_hashes(),
@@ -429,6 +524,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T> EthClient<C, SN, S, M, EM> where
 				logs_bloom: Some(*uncle.log_bloom()),
 				timestamp: uncle.timestamp().into(),
 				difficulty: *uncle.difficulty(),
+				base_fee_per_gas: uncle.base_fee(),
 				total_difficulty: Some(uncle.difficulty() + parent_difficulty),
 				receipts_root: *uncle.receipts_root(),
 				extra_data: uncle.extra_data().clone().into(),
@@ -445,12 +541,17 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T> EthClient<C, SN, S, M, EM> where
 	/// can be retrieved.
 	/// Note: When passing `BlockNumber::Pending` we fall back to the state of the current best block
 	/// if no state found for the best pending block.
-	fn get_state(&self, number: BlockNumber) -> StateOrBlock {
-		match number {
+	fn get_state(&self, number: BlockNumber) -> Result<StateOrBlock> {
+		Ok(match number {
 			BlockNumber::Hash { hash, .. } => BlockId::Hash(hash).into(),
 			BlockNumber::Num(num) => BlockId::Number(num).into(),
 			BlockNumber::Earliest => BlockId::Earliest.into(),
 			BlockNumber::Latest => BlockId::Latest.into(),
+			BlockNumber::Confirmed(_) => unreachable!(), // Resolved by `confirmed`
+			BlockNumber::StateRoot(root) => {
+				let state = self.client.state_at_root(root).ok_or_else(errors::state_pruned)?;
+				(Box::new(state) as Box<dyn StateInfo>).into()
+			},
 			BlockNumber::Pending => {
 				let info = self.client.chain_info();
 
@@ -464,12 +565,26 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T> EthClient<C, SN, S, M, EM> where
 					})
 					.into()
 			}
-		}
+		})
+	}
+
+	/// Resolve "latest" to the configured confirmation depth and explicit `Confirmed`
+	/// depths to a block number relative to the current best block.
+	fn confirmed(&self, number: BlockNumber) -> BlockNumber {
+		let best_block = self.client.chain_info().best_block_number;
+		number.with_confirmations(best_block, self.options.latest_confirmations)
+	}
+
+	/// Resolve the block range of a log filter the same way as `confirmed`.
+	fn confirmed_filter(&self, filter: Filter) -> Filter {
+		let best_block = self.client.chain_info().best_block_number;
+		filter.with_confirmations(best_block, self.options.latest_confirmations)
 	}
 
 	/// Get the state and header of best pending block. On failure, fall back to the best imported
 	/// blocks state&header.
 	fn pending_state_and_header_with_fallback(&self) -> (T, Header) {
+		self.miner.refresh_pending_block(&*self.client);
 		let best_block_number = self.client.chain_info().best_block_number;
 		let (maybe_state, maybe_header) =
 			self.miner.pending_state(best_block_number).map_or_else(|| (None, None),|s| {
@@ -507,10 +622,12 @@ fn check_known<C>(client: &C, number: BlockNumber) -> Result<()> where C: BlockC
 	use types::block_status::BlockStatus;
 
 	let id = match number {
-		BlockNumber::Pending => return Ok(()),
+		// the state is looked up by root, there is no block to check
+		BlockNumber::Pending | BlockNumber::StateRoot(_) => return Ok(()),
 		BlockNumber::Num(n) => BlockId::Number(n),
 		BlockNumber::Latest => BlockId::Latest,
 		BlockNumber::Earliest => BlockId::Earliest,
+		BlockNumber::Confirmed(_) => unreachable!(), // Resolved by `confirmed`
 		BlockNumber::Hash { hash, require_canonical } => {
 			// block check takes precedence over canon check.
 			match client.block_status(BlockId::Hash(hash.clone())) {
@@ -553,16 +670,28 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 		let client = &self.client;
 		let snapshot_status = self.snapshot.status();
 
-		let (warping, warp_chunks_amount, warp_chunks_processed) = match snapshot_status {
-			RestorationStatus::Ongoing { state_chunks, block_chunks, state_chunks_done, block_chunks_done } =>
-				(true, Some(block_chunks + state_chunks), Some(block_chunks_done + state_chunks_done)),
-			_ => (false, None, None),
+		let (warping, warp_chunks_amount, warp_chunks_processed, snapshot) = match snapshot_status {
+			RestorationStatus::Ongoing { state_chunks, block_chunks, state_chunks_done, block_chunks_done } => (
+				true,
+				Some(block_chunks + state_chunks),
+				Some(block_chunks_done + state_chunks_done),
+				Some(SnapshotRestoration {
+					state_chunks_done: state_chunks_done.into(),
+					state_chunks_total: state_chunks.into(),
+					block_chunks_done: block_chunks_done.into(),
+					block_chunks_total: block_chunks.into(),
+				}),
+			),
+			_ => (false, None, None, None),
 		};
 
 		if warping || self.sync.is_major_syncing() {
 			let chain_info = client.chain_info();
 			let current_block = U256::from(chain_info.best_block_number);
 			let highest_block = U256::from(status.highest_block_number.unwrap_or(status.start_block_number));
+			let block_gap = chain_info.ancient_block_number.map(|x| U256::from(x + 1))
+				.and_then(|first| chain_info.first_block_number.map(|last| (first, U256::from(last))));
+			let queue_info = client.queue_info();
 
 			let info = SyncInfo {
 				starting_block: status.start_block_number.into(),
@@ -570,6 +699,15 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 				highest_block,
 				warp_chunks_amount: warp_chunks_amount.map(|x| U256::from(x as u64)).map(Into::into),
 				warp_chunks_processed: warp_chunks_processed.map(|x| U256::from(x as u64)).map(Into::into),
+				parity: Some(SyncDetails {
+					snapshot,
+					block_gap,
+					queued_blocks: (queue_info.unverified_queue_size + queue_info.verifying_queue_size).into(),
+					verified_blocks: queue_info.verified_queue_size.into(),
+					queue_mem_used: queue_info.mem_used.into(),
+					queue_mem_limit: queue_info.max_mem_use.into(),
+					verification_time: (queue_info.verification_time.as_micros() as u64).into(),
+				}),
 			};
 			Ok(SyncStatus::Info(info))
 		} else {
@@ -605,6 +743,11 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 		Box::new(future::ok(default_gas_price(&*self.client, &*self.miner, self.options.gas_price_percentile)))
 	}
 
+	fn max_priority_fee_per_gas(&self) -> BoxFuture<U256> {
+		let suggestions = self.miner.fee_suggestions(&*self.client, self.options.gas_price_percentile);
+		Box::new(future::ok(suggestions.standard.max_priority_fee_per_gas))
+	}
+
 	fn accounts(&self) -> Result<Vec<H160>> {
 		self.deprecation_notice.print("eth_accounts", deprecated::msgs::ACCOUNTS);
 
@@ -617,10 +760,10 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn balance(&self, address: H160, num: Option<BlockNumber>) -> BoxFuture<U256> {
-		let num = num.unwrap_or_default();
+		let num = self.confirmed(num.unwrap_or_default());
 
 		try_bf!(check_known(&*self.client, num.clone()));
-		let res = match self.client.balance(&address, self.get_state(num)) {
+		let res = match self.client.balance(&address, try_bf!(self.get_state(num))) {
 			Some(balance) => Ok(balance),
 			None => Err(errors::state_pruned()),
 		};
@@ -633,16 +776,18 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 
 		let key1 = keccak(address);
 
-		let num = num.unwrap_or_default();
+		let num = self.confirmed(num.unwrap_or_default());
 		let id = match num {
 			BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
 			BlockNumber::Num(n) => BlockId::Number(n),
 			BlockNumber::Earliest => BlockId::Earliest,
 			BlockNumber::Latest => BlockId::Latest,
+			BlockNumber::Confirmed(_) => unreachable!(), // Resolved by `confirmed`
 			BlockNumber::Pending => {
 				self.deprecation_notice.print("`Pending`", Some("falling back to `Latest`"));
 				BlockId::Latest
 			}
+			BlockNumber::StateRoot(_) => return Box::new(future::err(errors::state_root_unsupported())),
 		};
 
 		try_bf!(check_known(&*self.client, num.clone()));
@@ -672,20 +817,20 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn storage_at(&self, address: H160, position: U256, num: Option<BlockNumber>) -> BoxFuture<H256> {
-		let num = num.unwrap_or_default();
+		let num = self.confirmed(num.unwrap_or_default());
 
 		try_bf!(check_known(&*self.client, num.clone()));
 		let storage = self.client.storage_at(
 			&address,
 			&BigEndianHash::from_uint(&position),
-			self.get_state(num)
+			try_bf!(self.get_state(num))
 		).ok_or_else(errors::state_pruned);
 
 		Box::new(future::done(storage))
 	}
 
 	fn transaction_count(&self, address: H160, num: Option<BlockNumber>) -> BoxFuture<U256> {
-		let res = match num.unwrap_or_default() {
+		let res = match self.confirmed(num.unwrap_or_default()) {
 			BlockNumber::Pending if self.options.pending_nonce_from_queue => {
 				Ok(self.miner.next_nonce(&*self.client, &address))
 			}
@@ -704,6 +849,10 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 					None => Err(errors::database("latest nonce missing"))
 				}
 			},
+			BlockNumber::StateRoot(root) => self.client
+				.state_at_root(root)
+				.and_then(|s| s.nonce(&address).ok())
+				.ok_or_else(errors::state_pruned),
 			number => {
 				try_bf!(check_known(&*self.client, number.clone()));
 				match self.client.nonce(&address, block_number_to_id(number)) {
@@ -725,9 +874,11 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn block_transaction_count_by_number(&self, num: BlockNumber) -> BoxFuture<Option<U256>> {
+		let num = self.confirmed(num);
 		Box::new(future::done(match num {
 			BlockNumber::Pending =>
 				Ok(Some(self.miner.pending_transaction_hashes(&*self.client).len().into())),
+			BlockNumber::StateRoot(_) => Err(errors::state_root_unsupported()),
 			_ => {
 				let trx_count = self.client.block(block_number_to_id(num.clone()))
 					.map(|block| block.transactions_count().into());
@@ -750,8 +901,10 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn block_uncles_count_by_number(&self, num: BlockNumber) -> BoxFuture<Option<U256>> {
+		let num = self.confirmed(num);
 		Box::new(future::done(match num {
 			BlockNumber::Pending => Ok(Some(0.into())),
+			BlockNumber::StateRoot(_) => Err(errors::state_root_unsupported()),
 			_ => {
 				let uncles_count = self.client.block(block_number_to_id(num.clone()))
 					.map(|block| block.uncles_count().into());
@@ -768,10 +921,10 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	fn code_at(&self, address: H160, num: Option<BlockNumber>) -> BoxFuture<Bytes> {
 		let address: Address = H160::into(address);
 
-		let num = num.unwrap_or_default();
+		let num = self.confirmed(num.unwrap_or_default());
 		try_bf!(check_known(&*self.client, num.clone()));
 
-		let res = match self.client.code(&address, self.get_state(num)) {
+		let res = match self.client.code(&address, try_bf!(self.get_state(num))) {
 			StateResult::Some(code) => Ok(code.map_or_else(Bytes::default, Bytes::new)),
 			StateResult::Missing => Err(errors::state_pruned()),
 		};
@@ -786,6 +939,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn block_by_number(&self, num: BlockNumber, include_txs: bool) -> BoxFuture<Option<RichBlock>> {
+		let num = self.confirmed(num);
 		let result = self.rich_block(num.clone().into(), include_txs).and_then(
 			errors::check_block_number_existence(&*self.client, num, self.options));
 		Box::new(future::done(result))
@@ -809,12 +963,15 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn transaction_by_block_number_and_index(&self, num: BlockNumber, index: Index) -> BoxFuture<Option<Transaction>> {
+		let num = self.confirmed(num);
 		let block_id = match num {
 			BlockNumber::Hash { hash, .. } => PendingOrBlock::Block(BlockId::Hash(hash)),
 			BlockNumber::Latest => PendingOrBlock::Block(BlockId::Latest),
 			BlockNumber::Earliest => PendingOrBlock::Block(BlockId::Earliest),
 			BlockNumber::Num(num) => PendingOrBlock::Block(BlockId::Number(num)),
 			BlockNumber::Pending => PendingOrBlock::Pending,
+			BlockNumber::StateRoot(_) => return Box::new(future::err(errors::state_root_unsupported())),
+			BlockNumber::Confirmed(_) => unreachable!(), // Resolved by `confirmed`
 		};
 
 		let transaction_id = PendingTransactionId::Location(block_id, index.value());
@@ -832,7 +989,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 		}
 
 		let receipt = self.client.transaction_receipt(TransactionId::Hash(hash));
-		let result = Ok(receipt.map(Into::into))
+		let result = Ok(receipt.map(|receipt| with_revert_reason(&*self.client, receipt.into())))
 			.and_then(errors::check_block_gap(&*self.client, self.options));
 		Box::new(future::done(result))
 	}
@@ -846,6 +1003,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn uncle_by_block_number_and_index(&self, num: BlockNumber, index: Index) -> BoxFuture<Option<RichBlock>> {
+		let num = self.confirmed(num);
 		let id = match num {
 			BlockNumber::Hash { hash, .. } => PendingUncleId { id: PendingOrBlock::Block(BlockId::Hash(hash)), position: index.value() },
 			BlockNumber::Latest => PendingUncleId { id: PendingOrBlock::Block(BlockId::Latest), position: index.value() },
@@ -853,6 +1011,8 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 			BlockNumber::Num(num) => PendingUncleId { id: PendingOrBlock::Block(BlockId::Number(num)), position: index.value() },
 
 			BlockNumber::Pending => PendingUncleId { id: PendingOrBlock::Pending, position: index.value() },
+			BlockNumber::StateRoot(_) => return Box::new(future::err(errors::state_root_unsupported())),
+			BlockNumber::Confirmed(_) => unreachable!(), // Resolved by `confirmed`
 		};
 
 		let result = self.uncle(id)
@@ -870,7 +1030,11 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn logs(&self, filter: Filter) -> BoxFuture<Vec<Log>> {
-		base_logs(&*self.client, &*self.miner, filter)
+		base_logs(&*self.client, &*self.miner, self.confirmed_filter(filter))
+	}
+
+	fn logs_page(&self, filter: Filter) -> Result<Page<Log>> {
+		base_logs_page(&*self.client, self.confirmed_filter(filter), self.options.page_size)
 	}
 
 	fn work(&self, no_new_work_timeout: Option<u64>) -> Result<Work> {
@@ -929,10 +1093,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn submit_work(&self, nonce: H64, pow_hash: H256, mix_hash: H256) -> Result<bool> {
-		match helpers::submit_work_detail(&self.client, &self.miner, nonce, pow_hash, mix_hash) {
-			Ok(_)  => Ok(true),
-			Err(_) => Ok(false),
-		}
+		Ok(helpers::submit_work_accepted(&self.client, &self.miner, nonce, pow_hash, mix_hash))
 	}
 
 	fn submit_hashrate(&self, rate: U256, id: H256) -> Result<bool> {
@@ -963,19 +1124,24 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 		let request = CallRequest::into(request);
 		let signed = try_bf!(fake_sign::sign_call(request));
 
-		let num = num.unwrap_or_default();
+		let num = self.confirmed(num.unwrap_or_default());
 		try_bf!(check_known(&*self.client, num.clone()));
 
 		let (mut state, header) =
 			if num == BlockNumber::Pending {
 				self.pending_state_and_header_with_fallback()
+			} else if let BlockNumber::StateRoot(root) = num {
+				// there is no block for an arbitrary state, execute on top of the best block
+				let state = try_bf!(self.client.state_at_root(root).ok_or_else(errors::state_pruned));
+				(state, self.client.best_block_header())
 			} else {
 				let id = match num {
 					BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
 					BlockNumber::Num(num) => BlockId::Number(num),
 					BlockNumber::Earliest => BlockId::Earliest,
 					BlockNumber::Latest => BlockId::Latest,
-					BlockNumber::Pending => unreachable!(), // Already covered
+					BlockNumber::Confirmed(_) => unreachable!(), // Resolved by `confirmed`
+					BlockNumber::Pending | BlockNumber::StateRoot(_) => unreachable!(), // Already covered
 				};
 
 				let state = try_bf!(self.client.state_at(id).ok_or_else(errors::state_pruned));
@@ -1004,17 +1170,21 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	fn estimate_gas(&self, request: CallRequest, num: Option<BlockNumber>) -> BoxFuture<U256> {
 		let request = CallRequest::into(request);
 		let signed = try_bf!(fake_sign::sign_call(request));
-		let num = num.unwrap_or_default();
+		let num = self.confirmed(num.unwrap_or_default());
 
 		let (state, header) = if num == BlockNumber::Pending {
 			self.pending_state_and_header_with_fallback()
+		} else if let BlockNumber::StateRoot(root) = num {
+			let state = try_bf!(self.client.state_at_root(root).ok_or_else(errors::state_pruned));
+			(state, self.client.best_block_header())
 		} else {
 			let id = match num {
 				BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
 				BlockNumber::Num(num) => BlockId::Number(num),
 				BlockNumber::Earliest => BlockId::Earliest,
 				BlockNumber::Latest => BlockId::Latest,
-				BlockNumber::Pending => unreachable!(), // Already covered
+				BlockNumber::Confirmed(_) => unreachable!(), // Resolved by `confirmed`
+				BlockNumber::Pending | BlockNumber::StateRoot(_) => unreachable!(), // Already covered
 			};
 
 			let state = try_bf!(self.client.state_at(id)
@@ -41,6 +41,10 @@ pub trait Filterable {
 	/// Current best block number.
 	fn best_block_number(&self) -> u64;
 
+	/// Number of confirmations kept between the best block and the block read as "latest",
+	/// `None` if filters can't be given a confirmation depth.
+	fn latest_confirmations(&self) -> Option<u64>;
+
 	/// Get a block hash by block id.
 	fn block_hash(&self, id: BlockId) -> Option<H256>;
 
@@ -65,15 +69,17 @@ pub struct EthFilterClient<C, M> {
 	client: Arc<C>,
 	miner: Arc<M>,
 	polls: Mutex<PollManager<SyncPollFilter>>,
+	latest_confirmations: u64,
 }
 
 impl<C, M> EthFilterClient<C, M> {
-	/// Creates new Eth filter client.
-	pub fn new(client: Arc<C>, miner: Arc<M>, poll_lifetime: u32) -> Self {
+	/// Creates new Eth filter client, reading "latest" `latest_confirmations` blocks below the head.
+	pub fn new(client: Arc<C>, miner: Arc<M>, poll_lifetime: u32, latest_confirmations: u64) -> Self {
 		EthFilterClient {
 			client,
 			miner,
 			polls: Mutex::new(PollManager::new(poll_lifetime)),
+			latest_confirmations,
 		}
 	}
 }
@@ -86,6 +92,10 @@ impl<C, M> Filterable for EthFilterClient<C, M> where
 		self.client.chain_info().best_block_number
 	}
 
+	fn latest_confirmations(&self) -> Option<u64> {
+		Some(self.latest_confirmations)
+	}
+
 	fn block_hash(&self, id: BlockId) -> Option<H256> {
 		self.client.block_hash(id)
 	}
@@ -139,11 +149,36 @@ impl<C, M> Filterable for EthFilterClient<C, M> where
 	}
 }
 
+/// Number of the block read as "latest".
+fn latest_block_number<T: Filterable + ?Sized>(filterable: &T) -> u64 {
+	let best_block = filterable.best_block_number();
+	best_block.saturating_sub(filterable.latest_confirmations().unwrap_or(0))
+}
+
+/// Keep the `Latest` bounds of a filter at the configured confirmation depth.
+fn confirm_latest<T: Filterable + ?Sized>(filterable: &T, filter: &mut EthcoreFilter) {
+	if filterable.latest_confirmations().unwrap_or(0) == 0 {
+		return;
+	}
+	let latest = BlockId::Number(latest_block_number(filterable));
+	if filter.from_block == BlockId::Latest {
+		filter.from_block = latest;
+	}
+	if filter.to_block == BlockId::Latest {
+		filter.to_block = latest;
+	}
+}
+
 impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 	fn new_filter(&self, filter: Filter) -> Result<U256> {
 		let mut polls = self.polls().lock();
-		let block_number = self.best_block_number();
+		let block_number = latest_block_number(self);
 		let include_pending = filter.to_block == Some(BlockNumber::Pending);
+		// explicit depths are fixed now, "latest" is resolved whenever the filter is read
+		let filter = match self.latest_confirmations() {
+			Some(_) => filter.with_confirmations(self.best_block_number(), 0),
+			None => filter,
+		};
 		let filter = filter.try_into()?;
 		let id = polls.create_poll(SyncPollFilter::new(PollFilter::Logs {
 			block_number, filter, include_pending,
@@ -157,7 +192,7 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 		let mut polls = self.polls().lock();
 		// +1, since we don't want to include the current block
 		let id = polls.create_poll(SyncPollFilter::new(PollFilter::Block {
-			last_block_number: self.best_block_number(),
+			last_block_number: latest_block_number(self),
 			recent_reported_hashes: VecDeque::with_capacity(PollFilter::MAX_BLOCK_HISTORY_SIZE),
 		}));
 		Ok(id.into())
@@ -187,7 +222,7 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 					*last_block_number = num - 1;
 					recent_reported_hashes.pop_front();
 				}
-				let current_number = self.best_block_number();
+				let current_number = latest_block_number(self);
 				let mut hashes = Vec::new();
 				for n in (*last_block_number + 1)..=current_number {
 					let block_number = BlockId::Number(n);
@@ -230,7 +265,7 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 				include_pending,
 			} => {
 				// retrive the current block number
-				let current_number = self.best_block_number();
+				let current_number = latest_block_number(self);
 
 				let mut filter = filter.clone();
 
@@ -240,10 +275,11 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 
 				filter.from_block = BlockId::Number(*block_number);
 				filter.to_block = BlockId::Latest;
+				confirm_latest(self, &mut filter);
 
 				// retrieve pending logs
 				let pending = if include_pending {
-					let pending_logs = self.pending_logs(current_number, &filter);
+					let pending_logs = self.pending_logs(self.best_block_number(), &filter);
 
 					// remove logs about which client was already notified about
 					let new_pending_logs: Vec<_> = pending_logs.iter()
@@ -279,7 +315,7 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 	}
 
 	fn filter_logs(&self, index: Index) -> BoxFuture<Vec<Log>> {
-		let (filter, include_pending) = {
+		let (mut filter, include_pending) = {
 			let mut polls = self.polls().lock();
 
 			match polls.poll(&index.value()).and_then(|f| f.modify(|filter| match *filter {
@@ -292,6 +328,8 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 			}
 		};
 
+		confirm_latest(self, &mut filter);
+
 		// fetch pending logs.
 		let pending = if include_pending {
 			let best_block = self.best_block_number();
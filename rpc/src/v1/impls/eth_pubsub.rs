@@ -28,14 +28,16 @@ use v1::helpers::{errors, limit_logs, Subscribers, };
 use v1::helpers::light_fetch::LightFetch;
 use v1::metadata::Metadata;
 use v1::traits::EthPubSub;
-use v1::types::{pubsub, RichHeader, Log};
+use v1::types::{pubsub, RichHeader, Log, Work};
 
 use sync::{SyncState, Notification};
 use client_traits::{BlockChainClient, ChainNotify};
-use ethereum_types::H256;
+use ethash::{self, SeedHashCompute};
+use ethereum_types::{H160, H256, U256};
 use light::cache::Cache;
 use light::client::{LightChainClient, LightChainNotify};
 use light::on_demand::OnDemandRequester;
+use miner::work_notify::NotifyWork;
 use parity_runtime::Executor;
 use parking_lot::{RwLock, Mutex};
 
@@ -57,6 +59,8 @@ pub struct EthPubSubClient<C> {
 	logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
 	transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
 	sync_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	work_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	accounts_subscribers: Arc<RwLock<Subscribers<Client>>>,
 }
 
 impl<C> EthPubSubClient<C>
@@ -94,6 +98,8 @@ impl<C> EthPubSubClient<C>
 		let logs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		let transactions_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		let sync_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let work_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let accounts_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 
 		let handler = Arc::new(ChainNotificationHandler {
 			client,
@@ -121,6 +127,8 @@ impl<C> EthPubSubClient<C>
 			heads_subscribers,
 			logs_subscribers,
 			transactions_subscribers,
+			work_subscribers,
+			accounts_subscribers,
 		}
 	}
 
@@ -128,6 +136,81 @@ impl<C> EthPubSubClient<C>
 	pub fn handler(&self) -> Weak<ChainNotificationHandler<C>> {
 		Arc::downgrade(&self.handler)
 	}
+
+	/// Returns a work notifier pushing new mining work to `newWork` subscribers.
+	pub fn work_notifier(&self) -> WorkNotifier {
+		WorkNotifier {
+			executor: self.handler.executor.clone(),
+			subscribers: self.work_subscribers.clone(),
+			seed_compute: Mutex::new(SeedHashCompute::default()),
+		}
+	}
+
+	/// Returns a notifier pushing key files added or removed to `accounts` subscribers.
+	pub fn accounts_notifier(&self) -> AccountsNotifier {
+		AccountsNotifier {
+			executor: self.handler.executor.clone(),
+			subscribers: self.accounts_subscribers.clone(),
+		}
+	}
+}
+
+/// Pushes accounts added to or removed from the keys directory to `accounts` subscribers.
+pub struct AccountsNotifier {
+	executor: Executor,
+	subscribers: Arc<RwLock<Subscribers<Client>>>,
+}
+
+impl AccountsNotifier {
+	/// Notify subscribers about an account whose key file was added or removed.
+	pub fn notify(&self, address: H160, added: bool) {
+		for subscriber in self.subscribers.read().values() {
+			self.executor.spawn(subscriber
+				.notify(Ok(pubsub::Result::Account(pubsub::AccountChange { address, added })))
+				.map(|_| ())
+				.map_err(|e| warn!(target: "rpc", "Unable to send account notification: {}", e))
+			);
+		}
+	}
+}
+
+#[cfg(any(test, feature = "accounts"))]
+impl accounts::AccountsListener for AccountsNotifier {
+	fn notify(&self, event: &accounts::AccountEvent) {
+		match *event {
+			accounts::AccountEvent::Added(address) => AccountsNotifier::notify(self, address, true),
+			accounts::AccountEvent::Removed(address) => AccountsNotifier::notify(self, address, false),
+		}
+	}
+}
+
+/// Pushes new mining work packages to `newWork` subscribers.
+pub struct WorkNotifier {
+	executor: Executor,
+	subscribers: Arc<RwLock<Subscribers<Client>>>,
+	seed_compute: Mutex<SeedHashCompute>,
+}
+
+impl NotifyWork for WorkNotifier {
+	fn notify(&self, pow_hash: H256, difficulty: U256, number: u64) {
+		let subscribers = self.subscribers.read();
+		if subscribers.is_empty() { return }
+
+		let seed_hash = self.seed_compute.lock().hash_block_number(number);
+		let work = Work {
+			pow_hash,
+			seed_hash: seed_hash.into(),
+			target: ethash::difficulty_to_boundary(&difficulty),
+			number: Some(number),
+		};
+		for subscriber in subscribers.values() {
+			self.executor.spawn(subscriber
+				.notify(Ok(pubsub::Result::Work(work.clone())))
+				.map(|_| ())
+				.map_err(|e| warn!(target: "rpc", "Unable to send work notification: {}", e))
+			);
+		}
+	}
 }
 
 impl<S, OD> EthPubSubClient<LightFetch<S, OD>>
@@ -349,6 +432,20 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 			(pubsub::Kind::NewPendingTransactions, _) => {
 				errors::invalid_params("newPendingTransactions", "Expected no parameters.")
 			},
+			(pubsub::Kind::NewWork, None) => {
+				self.work_subscribers.write().push(subscriber);
+				return;
+			},
+			(pubsub::Kind::NewWork, _) => {
+				errors::invalid_params("newWork", "Expected no parameters.")
+			},
+			(pubsub::Kind::Accounts, None) => {
+				self.accounts_subscribers.write().push(subscriber);
+				return;
+			},
+			(pubsub::Kind::Accounts, _) => {
+				errors::invalid_params("accounts", "Expected no parameters.")
+			},
 			_ => {
 				errors::unimplemented(None)
 			},
@@ -362,7 +459,9 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 		let res2 = self.logs_subscribers.write().remove(&id).is_some();
 		let res3 = self.transactions_subscribers.write().remove(&id).is_some();
 		let res4 = self.sync_subscribers.write().remove(&id).is_some();
+		let res5 = self.work_subscribers.write().remove(&id).is_some();
+		let res6 = self.accounts_subscribers.write().remove(&id).is_some();
 
-		Ok(res || res2 || res3 || res4)
+		Ok(res || res2 || res3 || res4 || res5 || res6)
 	}
 }
@@ -44,7 +44,7 @@ use v1::helpers::light_fetch::{self, LightFetch};
 use v1::traits::Eth;
 use v1::types::{
 	RichBlock, Block, BlockTransactions, BlockNumber, LightBlockNumber, Bytes, SyncStatus as RpcSyncStatus,
-	SyncInfo as RpcSyncInfo, Transaction, CallRequest, Index, Filter, Log, Receipt, Work, EthAccount
+	SyncInfo as RpcSyncInfo, Transaction, CallRequest, Index, Filter, Log, Page, Receipt, Work, EthAccount
 };
 use v1::metadata::Metadata;
 
@@ -158,6 +158,7 @@ where
 					logs_bloom: Some(*header.log_bloom()),
 					timestamp: header.timestamp().into(),
 					difficulty: *header.difficulty(),
+					base_fee_per_gas: header.base_fee(),
 					total_difficulty: score.map(Into::into),
 					seal_fields: header.seal().iter().cloned().map(Into::into).collect(),
 					uncles: block.uncle_hashes().into_iter().map(Into::into).collect(),
@@ -244,6 +245,7 @@ where
 				highest_block,
 				warp_chunks_amount: None,
 				warp_chunks_processed: None,
+				parity: None,
 			}))
 		} else {
 			Ok(RpcSyncStatus::None)
@@ -274,6 +276,10 @@ where
 		Box::new(self.fetcher().gas_price())
 	}
 
+	fn max_priority_fee_per_gas(&self) -> BoxFuture<U256> {
+		Box::new(future::err(errors::light_unimplemented(None)))
+	}
+
 	fn accounts(&self) -> Result<Vec<H160>> {
 		self.deprecation_notice.print("eth_accounts", deprecated::msgs::ACCOUNTS);
 
@@ -288,7 +294,7 @@ where
 	}
 
 	fn balance(&self, address: H160, num: Option<BlockNumber>) -> BoxFuture<U256> {
-		Box::new(self.fetcher().account(address, num.unwrap_or_default().to_block_id(), self.transaction_queue.clone())
+		Box::new(self.fetcher().account(address, try_bf!(num.unwrap_or_default().to_block_id()), self.transaction_queue.clone())
 			.map(|acc| acc.map_or(0.into(), |a| a.balance)))
 	}
 
@@ -301,11 +307,11 @@ where
 	}
 
 	fn block_by_number(&self, num: BlockNumber, include_txs: bool) -> BoxFuture<Option<RichBlock>> {
-		Box::new(self.rich_block(num.to_block_id(), include_txs).map(Some))
+		Box::new(self.rich_block(try_bf!(num.to_block_id()), include_txs).map(Some))
 	}
 
 	fn transaction_count(&self, address: H160, num: Option<BlockNumber>) -> BoxFuture<U256> {
-		Box::new(self.fetcher().account(address, num.unwrap_or_default().to_block_id(), self.transaction_queue.clone())
+		Box::new(self.fetcher().account(address, try_bf!(num.unwrap_or_default().to_block_id()), self.transaction_queue.clone())
 			.map(|acc| acc.map_or(0.into(), |a| a.nonce)))
 	}
 
@@ -328,7 +334,7 @@ where
 	fn block_transaction_count_by_number(&self, num: BlockNumber) -> BoxFuture<Option<U256>> {
 		let (sync, on_demand) = (self.sync.clone(), self.on_demand.clone());
 
-		Box::new(self.fetcher().header(num.to_block_id()).and_then(move |hdr| {
+		Box::new(self.fetcher().header(try_bf!(num.to_block_id())).and_then(move |hdr| {
 			if hdr.transactions_root() == KECCAK_NULL_RLP {
 				Either::A(future::ok(Some(U256::from(0))))
 			} else {
@@ -360,7 +366,7 @@ where
 	fn block_uncles_count_by_number(&self, num: BlockNumber) -> BoxFuture<Option<U256>> {
 		let (sync, on_demand) = (self.sync.clone(), self.on_demand.clone());
 
-		Box::new(self.fetcher().header(num.to_block_id()).and_then(move |hdr| {
+		Box::new(self.fetcher().header(try_bf!(num.to_block_id())).and_then(move |hdr| {
 			if hdr.uncles_hash() == KECCAK_EMPTY_LIST_RLP {
 				Either::B(future::ok(Some(U256::from(0))))
 			} else {
@@ -374,7 +380,7 @@ where
 	}
 
 	fn code_at(&self, address: H160, num: Option<BlockNumber>) -> BoxFuture<Bytes> {
-		Box::new(self.fetcher().code(address, num.unwrap_or_default().to_block_id()).map(Into::into))
+		Box::new(self.fetcher().code(address, try_bf!(num.unwrap_or_default().to_block_id())).map(Into::into))
 	}
 
 	fn send_raw_transaction(&self, raw: Bytes) -> Result<H256> {
@@ -401,7 +407,7 @@ where
 	}
 
 	fn call(&self, req: CallRequest, num: Option<BlockNumber>) -> BoxFuture<Bytes> {
-		Box::new(self.fetcher().proved_read_only_execution(req, num, self.transaction_queue.clone()).and_then(|res| {
+		Box::new(self.fetcher().proved_read_only_execution(req, try_bf!(num.unwrap_or_default().to_block_id()), self.transaction_queue.clone()).and_then(|res| {
 			match res {
 				Ok(exec) => Ok(exec.output.into()),
 				Err(e) => Err(errors::execution(e)),
@@ -411,7 +417,7 @@ where
 
 	fn estimate_gas(&self, req: CallRequest, num: Option<BlockNumber>) -> BoxFuture<U256> {
 		// TODO: binary chop for more accurate estimates.
-		Box::new(self.fetcher().proved_read_only_execution(req, num, self.transaction_queue.clone()).and_then(|res| {
+		Box::new(self.fetcher().proved_read_only_execution(req, try_bf!(num.unwrap_or_default().to_block_id()), self.transaction_queue.clone()).and_then(|res| {
 			match res {
 				Ok(exec) => Ok(exec.refunded + exec.gas_used),
 				Err(e) => Err(errors::execution(e)),
@@ -446,7 +452,7 @@ where
 	}
 
 	fn transaction_by_block_number_and_index(&self, num: BlockNumber, idx: Index) -> BoxFuture<Option<Transaction>> {
-		Box::new(self.fetcher().block(num.to_block_id()).map(move |block| {
+		Box::new(self.fetcher().block(try_bf!(num.to_block_id())).map(move |block| {
 			light_fetch::extract_transaction_at_index(block, idx.value())
 		}))
 	}
@@ -468,6 +474,7 @@ where
 								receipt.transaction_index = Some(index.into());
 								receipt.block_hash = Some(block_hash);
 								receipt.block_number = tx.block_number;
+								receipt.effective_gas_price = Some(tx.gas_price);
 								receipt
 							})
 							.map(Some);
@@ -490,7 +497,7 @@ where
 
 	fn uncle_by_block_number_and_index(&self, num: BlockNumber, idx: Index) -> BoxFuture<Option<RichBlock>> {
 		let client = self.client.clone();
-		Box::new(self.fetcher().block(num.to_block_id()).map(move |block| {
+		Box::new(self.fetcher().block(try_bf!(num.to_block_id())).map(move |block| {
 			extract_uncle_at_index(block, idx, client)
 		}))
 	}
@@ -525,6 +532,10 @@ where
 			}).map(move |logs| limit_logs(logs, limit)))
 	}
 
+	fn logs_page(&self, _filter: Filter) -> Result<Page<Log>> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn work(&self, _timeout: Option<u64>) -> Result<Work> {
 		Err(errors::light_unimplemented(None))
 	}
@@ -547,6 +558,8 @@ where
 {
 	fn best_block_number(&self) -> u64 { self.client.chain_info().best_block_number }
 
+	fn latest_confirmations(&self) -> Option<u64> { None }
+
 	fn block_hash(&self, id: BlockId) -> Option<H256> {
 		self.client.block_hash(id)
 	}
@@ -595,6 +608,7 @@ fn extract_uncle_at_index<T: LightChainClient>(block: encoded::Block, index: Ind
 				logs_bloom: Some(*uncle.log_bloom()),
 				timestamp: uncle.timestamp().into(),
 				difficulty: *uncle.difficulty(),
+				base_fee_per_gas: uncle.base_fee(),
 				total_difficulty: None,
 				receipts_root: *uncle.receipts_root(),
 				extra_data: uncle.extra_data().clone().into(),
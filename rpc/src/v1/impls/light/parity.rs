@@ -26,6 +26,7 @@ use crypto::publickey::{Generator, ecies};
 use ethstore::random_phrase;
 use sync::{LightSyncInfo, LightSyncProvider, LightNetworkDispatcher, ManageNetwork};
 use updater::VersionInfo as UpdaterVersionInfo;
+use eip_712::EIP712;
 use ethereum_types::{H64, H160, H256, H512, U64, U256};
 use ethcore::miner::FilterOptions;
 use ethcore_logger::RotatingLogger;
@@ -33,24 +34,24 @@ use ethcore_logger::RotatingLogger;
 use jsonrpc_core::{Result, BoxFuture};
 use jsonrpc_core::futures::{future, Future};
 use light::on_demand::OnDemandRequester;
-use v1::helpers::{self, errors, ipfs, NetworkSettings, verify_signature};
+use v1::helpers::{self, errors, ipfs, NetworkSettings, verify_signature, recover_typed_data_signer};
 use v1::helpers::external_signer::{SignerService, SigningQueue};
 use v1::helpers::dispatch::LightDispatcher;
 use v1::helpers::light_fetch::{LightFetch, light_all_transactions};
+use v1::informant::RpcStats;
 use v1::metadata::Metadata;
 use v1::traits::Parity;
 use v1::types::{
-	Bytes, CallRequest,
-	Peers, Transaction, RpcSettings, Histogram,
-	TransactionStats, LocalTransactionStatus,
+	Bytes, CallRequest, CallBundleResult,
+	Peers, ProtocolThroughput, Transaction, RpcSettings, RpcMethodStats, Histogram, FeeSuggestions, TransactionQueueStatus,
+	TransactionStats, LocalTransactionStatus, TransactionImportSimulation,
 	LightBlockNumber, ChainStatus, Receipt,
-	BlockNumber, ConsensusCapability, VersionInfo,
+	BackupStatus, BadBlock, BlockNumber, ConsensusCapability, ConsensusFault, VersionInfo,
 	OperationsInfo, Header, RichHeader, RecoveredAccount,
-	Log, Filter,
+	Log, Filter, SubmittedWork, AccountHistoryEntry, StratumWorker,
 };
 use Host;
 use v1::helpers::errors::light_unimplemented;
-use v1::types::block_number_to_id;
 
 /// Parity implementation for light client.
 pub struct ParityClient<S, OD>
@@ -63,6 +64,7 @@ where
 	settings: Arc<NetworkSettings>,
 	signer: Option<Arc<SignerService>>,
 	ws_address: Option<Host>,
+	rpc_stats: Arc<RpcStats>,
 	gas_price_percentile: usize,
 }
 
@@ -78,6 +80,7 @@ where
 		settings: Arc<NetworkSettings>,
 		signer: Option<Arc<SignerService>>,
 		ws_address: Option<Host>,
+		rpc_stats: Arc<RpcStats>,
 		gas_price_percentile: usize,
 	) -> Self {
 		ParityClient {
@@ -86,6 +89,7 @@ where
 			settings,
 			signer,
 			ws_address,
+			rpc_stats,
 			gas_price_percentile,
 		}
 	}
@@ -114,10 +118,18 @@ where
 		Ok(usize::max_value())
 	}
 
+	fn transaction_queue_status(&self) -> Result<TransactionQueueStatus> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn min_gas_price(&self) -> Result<U256> {
 		Ok(U256::default())
 	}
 
+	fn gas_price_floor(&self) -> Result<Option<U256>> {
+		Ok(None)
+	}
+
 	fn extra_data(&self) -> Result<Bytes> {
 		Ok(Bytes::default())
 	}
@@ -136,7 +148,7 @@ where
 	}
 
 	fn dev_logs_levels(&self) -> Result<String> {
-		Ok(self.logger.levels().to_owned())
+		Ok(self.logger.levels())
 	}
 
 	fn net_chain(&self) -> Result<String> {
@@ -159,6 +171,10 @@ where
 		Ok(self.settings.network_port)
 	}
 
+	fn net_throughput(&self) -> Result<BTreeMap<String, ProtocolThroughput>> {
+		Ok(ProtocolThroughput::by_protocol(self.light_dispatch.sync.traffic_stats()))
+	}
+
 	fn node_name(&self) -> Result<String> {
 		Ok(self.settings.name.clone())
 	}
@@ -175,6 +191,12 @@ where
 		})
 	}
 
+	fn rpc_stats(&self) -> Result<BTreeMap<String, RpcMethodStats>> {
+		Ok(self.rpc_stats.methods().iter()
+			.map(|(method, stats)| (method.clone(), RpcMethodStats::new(method, stats)))
+			.collect())
+	}
+
 	fn default_extra_data(&self) -> Result<Bytes> {
 		Ok(Bytes::new(version_data()))
 	}
@@ -185,6 +207,10 @@ where
 			.map(Into::into))
 	}
 
+	fn fee_suggestions(&self) -> Result<FeeSuggestions> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn unsigned_transactions_count(&self) -> Result<usize> {
 		match self.signer {
 			None => Err(errors::signer_disabled()),
@@ -208,13 +234,42 @@ where
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn pin_state(&self, _: Option<BlockNumber>) -> Result<H256> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn unpin_state(&self, _: H256) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn consensus_faults(&self) -> Result<Vec<ConsensusFault>> {
+		Ok(self.light_dispatch.client.engine().consensus_faults().into_iter().map(Into::into).collect())
+	}
+
+	fn backup_status(&self) -> Result<BackupStatus> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn bad_blocks(&self) -> Result<Vec<BadBlock>> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn stratum_workers(&self) -> Result<Vec<StratumWorker>> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn work_test_shares(&self) -> Result<U64> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn encrypt_message(&self, key: H512, phrase: Bytes) -> Result<Bytes> {
 		ecies::encrypt(&key, &DEFAULT_MAC, &phrase.0)
 			.map_err(errors::encryption)
 			.map(Into::into)
 	}
 
-	fn pending_transactions(&self, limit: Option<usize>, _filter: Option<FilterOptions>) -> Result<Vec<Transaction>> {
+	fn pending_transactions(&self, limit: Option<usize>, _filter: Option<FilterOptions>, verbose: Option<bool>) -> Result<Vec<Transaction>> {
+		let verbose = verbose.unwrap_or(false);
 		let txq = self.light_dispatch.transaction_queue.read();
 		let chain_info = self.light_dispatch.client.chain_info();
 		Ok(
@@ -222,6 +277,7 @@ where
 				.into_iter()
 				.take(limit.unwrap_or_else(usize::max_value))
 				.map(Transaction::from_pending)
+				.map(|t| if verbose { t.with_method_details() } else { t })
 				.collect::<Vec<_>>()
 		)
 	}
@@ -253,9 +309,10 @@ where
 		)
 	}
 
-	fn pending_transactions_stats(&self) -> Result<BTreeMap<H256, TransactionStats>> {
+	fn pending_transactions_stats(&self, hash: Option<H256>) -> Result<BTreeMap<H256, TransactionStats>> {
 		let stats = self.light_dispatch.sync.transactions_stats();
 		Ok(stats.into_iter()
+			.filter(|&(ref h, _)| hash.map_or(true, |hash| hash == *h))
 			.map(|(hash, stats)| (hash, stats.into()))
 			.collect()
 		)
@@ -289,6 +346,10 @@ where
 		Box::new(self.light_dispatch.next_nonce(address))
 	}
 
+	fn simulate_transaction_import(&self, _raw: Bytes) -> Result<TransactionImportSimulation> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn mode(&self) -> Result<String> {
 		Err(errors::light_unimplemented(None))
 	}
@@ -321,6 +382,7 @@ where
 
 		Ok(ChainStatus {
 			block_gap: gap,
+			halted_reorg: None,
 		})
 	}
 
@@ -357,18 +419,19 @@ where
 					logs_bloom: *header.log_bloom(),
 					timestamp: header.timestamp().into(),
 					difficulty: *header.difficulty(),
+					base_fee_per_gas: header.base_fee(),
 					seal_fields: header.seal().iter().cloned().map(Into::into).collect(),
 					extra_data: Bytes::new(header.extra_data().clone()),
 				},
 				extra_info,
 			})
 		};
-		let id = number.unwrap_or_default().to_block_id();
+		let id = try_bf!(number.unwrap_or_default().to_block_id());
 		Box::new(self.fetcher().header(id).and_then(from_encoded))
 	}
 
 	fn block_receipts(&self, number: Option<BlockNumber>) -> BoxFuture<Vec<Receipt>> {
-		let id = number.unwrap_or_default().to_block_id();
+		let id = try_bf!(number.unwrap_or_default().to_block_id());
 		Box::new(self.fetcher().receipts(id).and_then(|receipts| Ok(receipts.into_iter().map(Into::into).collect())))
 	}
 
@@ -376,14 +439,26 @@ where
 		ipfs::cid(content)
 	}
 
+	fn account_history(&self, _address: H160, _blocks: Vec<BlockNumber>) -> Result<Vec<AccountHistoryEntry>> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn call(&self, _requests: Vec<CallRequest>, _block: Option<BlockNumber>) -> Result<Vec<Bytes>> {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn call_bundle(&self, _requests: Vec<CallRequest>, _block: Option<BlockNumber>) -> Result<Vec<CallBundleResult>> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn submit_work_detail(&self, _nonce: H64, _pow_hash: H256, _mix_hash: H256) -> Result<H256> {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn submit_work_status(&self, _nonce: H64, _pow_hash: H256, _mix_hash: H256) -> Result<SubmittedWork> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn status(&self) -> Result<()> {
 		let has_peers = self.settings.is_dev_chain || self.light_dispatch.sync.peer_numbers().connected > 0;
 		let is_importing = (*self.light_dispatch.sync).is_major_importing();
@@ -407,10 +482,18 @@ where
 		verify_signature(is_prefixed, message, r, s, v, self.light_dispatch.client.signing_chain_id())
 	}
 
+	fn recover_typed_data_signer(&self, typed_data: EIP712, r: H256, s: H256, v: U64) -> Result<RecoveredAccount> {
+		recover_typed_data_signer(typed_data, r, s, v, self.light_dispatch.client.signing_chain_id())
+	}
+
+	fn is_valid_signature(&self, _: H160, _: H256, _: Bytes, _: Option<BlockNumber>) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn get_raw_block_by_number(&self, block: BlockNumber) -> BoxFuture<Option<Bytes>> {
 		Box::new(
 			self.fetcher()
-				.block(block_number_to_id(block))
+				.block(try_bf!(block.to_block_id()))
 				.map(|block| Some(Bytes::from(block.raw().to_vec())))
 		)
 	}
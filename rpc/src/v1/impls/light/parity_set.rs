@@ -20,7 +20,8 @@
 use std::io;
 use std::sync::Arc;
 
-use ethereum_types::{H160, H256, U256};
+use ethcore_logger;
+use ethereum_types::{H160, H256, H512, U256};
 use fetch::{self, Fetch};
 use hash::keccak_buffer;
 use light::client::LightChainClient;
@@ -67,6 +68,10 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn set_work_test_difficulty(&self, _difficulty: Option<U256>) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn set_author(&self, _author: H160) -> Result<bool> {
 		Err(errors::light_unimplemented(None))
 	}
@@ -153,4 +158,26 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 	fn remove_transaction(&self, _hash: H256) -> Result<Option<Transaction>> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn acknowledge_reorg(&self, _accept: bool) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn set_logging_level(&self, target: String, level: String) -> Result<bool> {
+		let target = if target.is_empty() { None } else { Some(target.as_str()) };
+		ethcore_logger::set_log_level(target, &level).map_err(|e| errors::invalid_params("level", e))?;
+		Ok(true)
+	}
+
+	fn reserve_nonce(&self, _address: H160) -> Result<U256> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn release_nonce(&self, _address: H160, _nonce: U256) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn set_warp_pin(&self, _manifest_hash: Option<H256>, _node: Option<H512>) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
 }
@@ -22,7 +22,7 @@ use v1::Metadata;
 use v1::traits::Traces;
 use v1::helpers::errors;
 use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults,
-	TraceResultsWithTransactionHash, TraceOptions};
+	TraceResultsWithTransactionHash, TraceOptions, Page, StateAccessLog};
 
 /// Traces api implementation.
 // TODO: all calling APIs should be possible w. proved remote TX execution.
@@ -35,7 +35,11 @@ impl Traces for TracesClient {
 		Err(errors::light_unimplemented(None))
 	}
 
-	fn block_traces(&self, _block_number: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>> {
+	fn filter_page(&self, _filter: TraceFilter) -> Result<Option<Page<LocalizedTrace>>> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn block_traces(&self, _block_number: BlockNumber, _offset: Option<usize>, _limit: Option<usize>) -> Result<Option<Vec<LocalizedTrace>>> {
 		Err(errors::light_unimplemented(None))
 	}
 
@@ -66,4 +70,8 @@ impl Traces for TracesClient {
 	fn replay_block_transactions(&self,  _block_number: BlockNumber, _flags: TraceOptions) ->  Result<Vec<TraceResultsWithTransactionHash>> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn replay_block_state_access(&self, _block_number: BlockNumber) -> Result<Vec<StateAccessLog>> {
+		Err(errors::light_unimplemented(None))
+	}
 }
@@ -19,9 +19,11 @@ use std::sync::Arc;
 use std::collections::BTreeMap;
 
 use crypto::DEFAULT_MAC;
+use eip_712::EIP712;
 use ethereum_types::{H64, H160, H256, H512, U64, U256};
 use ethcore::client::Call;
-use client_traits::{BlockChainClient, StateClient};
+use machine::executed::Executed;
+use client_traits::{BlockChainClient, ReorgGuard, StateClient};
 use ethcore::miner::{self, MinerService, FilterOptions};
 use snapshot::SnapshotService;
 use account_state::state::StateInfo;
@@ -32,29 +34,42 @@ use ethstore::random_phrase;
 use jsonrpc_core::futures::future;
 use jsonrpc_core::{BoxFuture, Result};
 use sync::{SyncProvider, ManageNetwork};
+use rlp::Rlp;
 use types::{
+	client_types::StateResult,
+	header::Header,
 	ids::BlockId,
+	transaction::SignedTransaction,
 	verification::Unverified,
 	snapshot::RestorationStatus,
 };
 use updater::{Service as UpdateService};
 use version::version_data;
 
-use v1::helpers::{self, errors, fake_sign, ipfs, NetworkSettings, verify_signature};
+use v1::helpers::{
+	self, errors, fake_sign, ipfs, NetworkSettings, verify_signature, recover_typed_data_signer, recover_signer,
+	encode_is_valid_signature, is_eip1271_magic_value,
+};
 use v1::helpers::external_signer::{SigningQueue, SignerService};
+use v1::helpers::stratum::StratumWorkers;
+use v1::impls::eth::with_revert_reasons;
+use v1::informant::RpcStats;
 use v1::metadata::Metadata;
 use v1::traits::Parity;
 use v1::types::{
-	Bytes, CallRequest,
-	Peers, Transaction, RpcSettings, Histogram,
-	TransactionStats, LocalTransactionStatus,
-	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, ChainStatus, Log, Filter,
-	RichHeader, Receipt, RecoveredAccount,
+	Bytes, CallRequest, CallBundleResult,
+	Peers, ProtocolThroughput, Transaction, RpcSettings, RpcMethodStats, Histogram, FeeSuggestions, TransactionQueueStatus,
+	TransactionStats, LocalTransactionStatus, TransactionImportSimulation,
+	BackupStatus, BadBlock, BlockNumber, ConsensusCapability, ConsensusFault, VersionInfo,
+	OperationsInfo, ChainStatus, Log, Filter, SubmittedWork,
+	RichHeader, Receipt, RecoveredAccount, AccountHistoryEntry, StratumWorker,
 	block_number_to_id
 };
 use Host;
 
+/// Maximal number of blocks queried by a single `parity_getAccountHistory` call.
+const MAX_ACCOUNT_HISTORY_BLOCKS: usize = 1024;
+
 /// Parity implementation.
 pub struct ParityClient<C, M, U> {
 	client: Arc<C>,
@@ -66,7 +81,10 @@ pub struct ParityClient<C, M, U> {
 	settings: Arc<NetworkSettings>,
 	signer: Option<Arc<SignerService>>,
 	ws_address: Option<Host>,
+	rpc_stats: Arc<RpcStats>,
 	snapshot: Option<Arc<dyn SnapshotService>>,
+	stratum: Option<Arc<dyn StratumWorkers>>,
+	gas_price_percentile: usize,
 }
 
 impl<C, M, U> ParityClient<C, M, U> where
@@ -83,7 +101,10 @@ impl<C, M, U> ParityClient<C, M, U> where
 		settings: Arc<NetworkSettings>,
 		signer: Option<Arc<SignerService>>,
 		ws_address: Option<Host>,
+		rpc_stats: Arc<RpcStats>,
 		snapshot: Option<Arc<dyn SnapshotService>>,
+		stratum: Option<Arc<dyn StratumWorkers>>,
+		gas_price_percentile: usize,
 	) -> Self {
 		ParityClient {
 			client,
@@ -95,11 +116,62 @@ impl<C, M, U> ParityClient<C, M, U> where
 			settings,
 			signer,
 			ws_address,
+			rpc_stats,
 			snapshot,
+			stratum,
+			gas_price_percentile,
 		}
 	}
 }
 
+impl<C, M, U, S> ParityClient<C, M, U> where
+	S: StateInfo + 'static,
+	C: miner::BlockChainClient + BlockChainClient + StateClient<State=S> + Call<State=S> + 'static,
+	M: MinerService<State=S> + 'static,
+{
+	/// Fakes signatures of the call requests and executes them in order against the state at given block.
+	fn call_many(&self, requests: Vec<CallRequest>, num: Option<BlockNumber>) -> Result<Vec<Executed>> {
+		let requests = requests
+			.into_iter()
+			.map(|request| Ok((
+				fake_sign::sign_call(request.into())?,
+				Default::default()
+			)))
+			.collect::<Result<Vec<_>>>()?;
+
+		let num = num.unwrap_or_default();
+
+		let (mut state, header): (S, Header) = if num == BlockNumber::Pending {
+			let info = self.client.chain_info();
+			let state = self.miner.pending_state(info.best_block_number).ok_or_else(errors::state_pruned)?;
+			let header = self.miner.pending_block_header(info.best_block_number).ok_or_else(errors::state_pruned)?;
+
+			(state, header)
+		} else if let BlockNumber::StateRoot(root) = num {
+			// there is no block for an arbitrary state, execute on top of the best block
+			let state = self.client.state_at_root(root).ok_or_else(errors::state_pruned)?;
+
+			(state, self.client.best_block_header())
+		} else {
+			let id = match num {
+				BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
+				BlockNumber::Num(num) => BlockId::Number(num),
+				BlockNumber::Earliest => BlockId::Earliest,
+				BlockNumber::Latest => BlockId::Latest,
+				BlockNumber::Confirmed(_) => return Err(errors::confirmations_unsupported()),
+				BlockNumber::Pending | BlockNumber::StateRoot(_) => unreachable!(), // Already covered
+			};
+
+			let state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
+			let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?.decode().map_err(errors::decode)?;
+
+			(state, header)
+		};
+
+		self.client.call_many(&requests, &mut state, &header).map_err(errors::call)
+	}
+}
+
 impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 	S: StateInfo + 'static,
 	C: miner::BlockChainClient + BlockChainClient + StateClient<State=S> + Call<State=S> + 'static,
@@ -112,10 +184,18 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		Ok(self.miner.queue_status().limits.max_count)
 	}
 
+	fn transaction_queue_status(&self) -> Result<TransactionQueueStatus> {
+		Ok(self.miner.queue_status().into())
+	}
+
 	fn min_gas_price(&self) -> Result<U256> {
 		Ok(self.miner.queue_status().options.minimal_gas_price)
 	}
 
+	fn gas_price_floor(&self) -> Result<Option<U256>> {
+		Ok(self.miner.gas_price_floor())
+	}
+
 	fn extra_data(&self) -> Result<Bytes> {
 		Ok(Bytes::new(self.miner.authoring_params().extra_data))
 	}
@@ -135,7 +215,7 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 	}
 
 	fn dev_logs_levels(&self) -> Result<String> {
-		Ok(self.logger.levels().to_owned())
+		Ok(self.logger.levels())
 	}
 
 	fn net_chain(&self) -> Result<String> {
@@ -164,6 +244,10 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		Ok(self.settings.network_port)
 	}
 
+	fn net_throughput(&self) -> Result<BTreeMap<String, ProtocolThroughput>> {
+		Ok(ProtocolThroughput::by_protocol(self.net.traffic_stats()))
+	}
+
 	fn node_name(&self) -> Result<String> {
 		Ok(self.settings.name.clone())
 	}
@@ -180,6 +264,12 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		})
 	}
 
+	fn rpc_stats(&self) -> Result<BTreeMap<String, RpcMethodStats>> {
+		Ok(self.rpc_stats.methods().iter()
+			.map(|(method, stats)| (method.clone(), RpcMethodStats::new(method, stats)))
+			.collect())
+	}
+
 	fn default_extra_data(&self) -> Result<Bytes> {
 		Ok(Bytes::new(version_data()))
 	}
@@ -193,6 +283,10 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		))
 	}
 
+	fn fee_suggestions(&self) -> Result<FeeSuggestions> {
+		Ok(self.miner.fee_suggestions(&*self.client, self.gas_price_percentile).into())
+	}
+
 	fn unsigned_transactions_count(&self) -> Result<usize> {
 		match self.signer {
 			None => Err(errors::signer_disabled()),
@@ -214,6 +308,8 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 				warn!("BlockNumber::Pending is unsupported");
 				return Ok(None);
 			},
+			BlockNumber::StateRoot(_) => return Err(errors::state_root_unsupported()),
+			BlockNumber::Confirmed(_) => return Err(errors::confirmations_unsupported()),
 
 			num => block_number_to_id(num)
 		};
@@ -229,6 +325,8 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 				warn!("BlockNumber::Pending is unsupported");
 				return Ok(None);
 			},
+			BlockNumber::StateRoot(_) => return Err(errors::state_root_unsupported()),
+			BlockNumber::Confirmed(_) => return Err(errors::confirmations_unsupported()),
 
 			num => block_number_to_id(num)
 		};
@@ -238,13 +336,55 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			.map(|a| a.into_iter().map(Into::into).collect()))
 	}
 
+	fn pin_state(&self, block_number: Option<BlockNumber>) -> Result<H256> {
+		let id = match block_number.unwrap_or_default() {
+			BlockNumber::Pending => return Err(errors::invalid_params("block", "Pending state can't be pinned")),
+			BlockNumber::StateRoot(_) => return Err(errors::state_root_unsupported()),
+			BlockNumber::Confirmed(_) => return Err(errors::confirmations_unsupported()),
+			num => block_number_to_id(num),
+		};
+
+		if self.client.block_header(id).is_none() {
+			return Err(errors::unknown_block());
+		}
+		self.client.pin_state(id).ok_or_else(errors::state_pruned)
+	}
+
+	fn unpin_state(&self, hash: H256) -> Result<bool> {
+		Ok(self.client.unpin_state(&hash))
+	}
+
+	fn consensus_faults(&self) -> Result<Vec<ConsensusFault>> {
+		Ok(self.client.consensus_faults().into_iter().map(Into::into).collect())
+	}
+
+	fn backup_status(&self) -> Result<BackupStatus> {
+		Ok(self.client.backup_status().into())
+	}
+
+	fn bad_blocks(&self) -> Result<Vec<BadBlock>> {
+		Ok(self.client.bad_blocks().into_iter().map(Into::into).collect())
+	}
+
+	fn stratum_workers(&self) -> Result<Vec<StratumWorker>> {
+		match self.stratum {
+			Some(ref stratum) => Ok(stratum.workers()),
+			None => Err(errors::stratum_disabled()),
+		}
+	}
+
+	fn work_test_shares(&self) -> Result<U64> {
+		Ok(self.miner.work_test_shares().into())
+	}
+
 	fn encrypt_message(&self, key: H512, phrase: Bytes) -> Result<Bytes> {
 		ecies::encrypt(&key, &DEFAULT_MAC, &phrase.0)
 			.map_err(errors::encryption)
 			.map(Into::into)
 	}
 
-	fn pending_transactions(&self, limit: Option<usize>, filter: Option<FilterOptions>) -> Result<Vec<Transaction>> {
+	fn pending_transactions(&self, limit: Option<usize>, filter: Option<FilterOptions>, verbose: Option<bool>) -> Result<Vec<Transaction>> {
+		let verbose = verbose.unwrap_or(false);
 		let ready_transactions = self.miner.ready_transactions_filtered(
 			&*self.client,
 			limit.unwrap_or_else(usize::max_value),
@@ -255,6 +395,7 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		Ok(ready_transactions
 			.into_iter()
 			.map(|t| Transaction::from_pending(t.pending().clone()))
+			.map(|t| if verbose { t.with_method_details() } else { t })
 			.collect()
 		)
 	}
@@ -277,9 +418,10 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		Err(errors::deprecated("Use `parity_allTransaction` instead."))
 	}
 
-	fn pending_transactions_stats(&self) -> Result<BTreeMap<H256, TransactionStats>> {
+	fn pending_transactions_stats(&self, hash: Option<H256>) -> Result<BTreeMap<H256, TransactionStats>> {
 		let stats = self.sync.transactions_stats();
 		Ok(stats.into_iter()
+			.filter(|&(ref h, _)| hash.map_or(true, |hash| hash == *h))
 			.map(|(hash, stats)| (hash, stats.into()))
 			.collect()
 		)
@@ -303,6 +445,17 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		Box::new(future::ok(self.miner.next_nonce(&*self.client, &address)))
 	}
 
+	fn simulate_transaction_import(&self, raw: Bytes) -> Result<TransactionImportSimulation> {
+		let signed_transaction = Rlp::new(&raw.into_vec()).as_val()
+			.map_err(errors::rlp)
+			.and_then(|tx| SignedTransaction::new(tx).map_err(errors::transaction))?;
+		let hash = signed_transaction.hash();
+
+		self.miner.simulate_transaction_import(&*self.client, signed_transaction.into(), false)
+			.map(|simulation| TransactionImportSimulation::new(hash, simulation))
+			.map_err(errors::transaction)
+	}
+
 	fn mode(&self) -> Result<String> {
 		Ok(self.client.mode().to_string())
 	}
@@ -331,6 +484,7 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 
 		Ok(ChainStatus {
 			block_gap: gap,
+			halted_reorg: self.client.halted_reorg().map(Into::into),
 		})
 	}
 
@@ -359,6 +513,8 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 				BlockNumber::Num(num) => BlockId::Number(num),
 				BlockNumber::Earliest => BlockId::Earliest,
 				BlockNumber::Latest => BlockId::Latest,
+				BlockNumber::StateRoot(_) => return Box::new(future::err(errors::state_root_unsupported())),
+				BlockNumber::Confirmed(_) => return Box::new(future::err(errors::confirmations_unsupported())),
 				BlockNumber::Pending => unreachable!(), // Already covered
 			};
 
@@ -391,56 +547,81 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			BlockNumber::Num(num) => BlockId::Number(num),
 			BlockNumber::Earliest => BlockId::Earliest,
 			BlockNumber::Latest => BlockId::Latest,
+			BlockNumber::StateRoot(_) => return Box::new(future::err(errors::state_root_unsupported())),
+			BlockNumber::Confirmed(_) => return Box::new(future::err(errors::confirmations_unsupported())),
 		};
 		let receipts = try_bf!(self.client.localized_block_receipts(id).ok_or_else(errors::unknown_block));
-		Box::new(future::ok(receipts.into_iter().map(Into::into).collect()))
+		let receipts = receipts.into_iter().map(Into::into).collect();
+		Box::new(future::ok(with_revert_reasons(&*self.client, receipts)))
 	}
 
 	fn ipfs_cid(&self, content: Bytes) -> Result<String> {
 		ipfs::cid(content)
 	}
 
-	fn call(&self, requests: Vec<CallRequest>, num: Option<BlockNumber>) -> Result<Vec<Bytes>> {
-		let requests = requests
-			.into_iter()
-			.map(|request| Ok((
-				fake_sign::sign_call(request.into())?,
-				Default::default()
-			)))
-			.collect::<Result<Vec<_>>>()?;
-
-		let num = num.unwrap_or_default();
+	fn account_history(&self, address: H160, blocks: Vec<BlockNumber>) -> Result<Vec<AccountHistoryEntry>> {
+		if blocks.len() > MAX_ACCOUNT_HISTORY_BLOCKS {
+			return Err(errors::request_rejected_param_limit(MAX_ACCOUNT_HISTORY_BLOCKS as u64, "blocks"));
+		}
 
-		let (mut state, header) = if num == BlockNumber::Pending {
-			let info = self.client.chain_info();
-			let state = self.miner.pending_state(info.best_block_number).ok_or_else(errors::state_pruned)?;
-			let header = self.miner.pending_block_header(info.best_block_number).ok_or_else(errors::state_pruned)?;
+		let headers = blocks.into_iter()
+			.map(|num| match num {
+				BlockNumber::Pending => Err(errors::invalid_params("blocks", "pending block is not supported")),
+				BlockNumber::StateRoot(_) => Err(errors::state_root_unsupported()),
+				BlockNumber::Confirmed(_) => Err(errors::confirmations_unsupported()),
+				num => self.client.block_header(block_number_to_id(num)).ok_or_else(errors::unknown_block),
+			})
+			.collect::<Result<Vec<_>>>()?;
 
-			(state, header)
-		} else {
-			let id = match num {
-				BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
-				BlockNumber::Num(num) => BlockId::Number(num),
-				BlockNumber::Earliest => BlockId::Earliest,
-				BlockNumber::Latest => BlockId::Latest,
-				BlockNumber::Pending => unreachable!(), // Already covered
+		// Visit blocks in ascending order, so that adjacent blocks which haven't changed the state
+		// (same state root) reuse the previous lookup instead of opening the state again.
+		let mut order: Vec<usize> = (0..headers.len()).collect();
+		order.sort_by_key(|&i| headers[i].number());
+
+		let mut entries = vec![None; headers.len()];
+		let mut previous: Option<(H256, U256, U256)> = None;
+		for i in order {
+			let header = &headers[i];
+			let state_root = header.state_root();
+			let (balance, nonce) = match previous {
+				Some((root, balance, nonce)) if root == state_root => (balance, nonce),
+				_ => {
+					let state = self.client.state_at(BlockId::Hash(header.hash())).ok_or_else(errors::state_pruned)?;
+					let balance = state.balance(&address).map_err(|_| errors::state_corrupt())?;
+					let nonce = state.nonce(&address).map_err(|_| errors::state_corrupt())?;
+					(balance, nonce)
+				},
 			};
+			previous = Some((state_root, balance, nonce));
+			entries[i] = Some(AccountHistoryEntry {
+				block_number: header.number().into(),
+				block_hash: header.hash(),
+				balance,
+				nonce,
+			});
+		}
 
-			let state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
-			let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?.decode().map_err(errors::decode)?;
+		Ok(entries.into_iter().map(|entry| entry.expect("every block is visited exactly once; qed")).collect())
+	}
 
-			(state, header)
-		};
+	fn call(&self, requests: Vec<CallRequest>, num: Option<BlockNumber>) -> Result<Vec<Bytes>> {
+		self.call_many(requests, num)
+			.map(|res| res.into_iter().map(|res| res.output.into()).collect())
+	}
 
-		self.client.call_many(&requests, &mut state, &header)
-				.map(|res| res.into_iter().map(|res| res.output.into()).collect())
-				.map_err(errors::call)
+	fn call_bundle(&self, requests: Vec<CallRequest>, num: Option<BlockNumber>) -> Result<Vec<CallBundleResult>> {
+		self.call_many(requests, num)
+			.map(|res| res.into_iter().map(Into::into).collect())
 	}
 
 	fn submit_work_detail(&self, nonce: H64, pow_hash: H256, mix_hash: H256) -> Result<H256> {
 		helpers::submit_work_detail(&self.client, &self.miner, nonce, pow_hash, mix_hash)
 	}
 
+	fn submit_work_status(&self, nonce: H64, pow_hash: H256, mix_hash: H256) -> Result<SubmittedWork> {
+		helpers::submit_work_status(&self.client, &self.miner, nonce, pow_hash, mix_hash)
+	}
+
 	fn status(&self) -> Result<()> {
 		let has_peers = self.settings.is_dev_chain || self.sync.status().num_peers > 0;
 		let is_warping = match self.snapshot.as_ref().map(|s| s.status()) {
@@ -467,7 +648,40 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		verify_signature(is_prefixed, message, r, s, v, self.client.signing_chain_id())
 	}
 
+	fn recover_typed_data_signer(&self, typed_data: EIP712, r: H256, s: H256, v: U64) -> Result<RecoveredAccount> {
+		recover_typed_data_signer(typed_data, r, s, v, self.client.signing_chain_id())
+	}
+
+	fn is_valid_signature(&self, address: H160, hash: H256, signature: Bytes, number: Option<BlockNumber>) -> Result<bool> {
+		let id = match number.unwrap_or_default() {
+			BlockNumber::Pending | BlockNumber::StateRoot(_) =>
+				return Err(errors::invalid_params("block", "pending and state root are not supported")),
+			BlockNumber::Confirmed(_) => return Err(errors::confirmations_unsupported()),
+			number => block_number_to_id(number),
+		};
+
+		let code = match self.client.code(&address, id.into()) {
+			StateResult::Some(code) => code,
+			StateResult::Missing => return Err(errors::state_pruned()),
+		};
+
+		match code {
+			Some(ref code) if !code.is_empty() => {
+				let data = encode_is_valid_signature(&hash, &signature.0);
+				let output = self.client.call_contract(id, address, data).map_err(errors::execution)?;
+				Ok(is_eip1271_magic_value(&output))
+			},
+			_ => Ok(recover_signer(&hash, &signature.0) == Some(address)),
+		}
+	}
+
 	fn get_raw_block_by_number(&self, block_number: BlockNumber) -> BoxFuture<Option<Bytes>> {
+		match block_number {
+			BlockNumber::StateRoot(_) => return Box::new(future::err(errors::state_root_unsupported())),
+			BlockNumber::Confirmed(_) => return Box::new(future::err(errors::confirmations_unsupported())),
+			_ => {},
+		}
+
 		Box::new(futures::done(
 			Ok(
 				self.client
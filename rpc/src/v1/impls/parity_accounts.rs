@@ -25,12 +25,15 @@ use ethereum_types::{Address, H160, H256, H520};
 use ethkey::{Brain, Password};
 use crypto::publickey::{Generator, Secret};
 use ethstore::KeyFile;
-use accounts::AccountProvider;
+use accounts::{AccountProvider, AuditEvent};
 use jsonrpc_core::Result;
 use v1::helpers::deprecated::{self, DeprecationNotice};
 use v1::helpers::errors;
 use v1::traits::{ParityAccounts, ParityAccountsInfo};
-use v1::types::{Derive, DeriveHierarchical, DeriveHash, ExtAccountInfo, AccountInfo};
+use v1::types::{Derive, DeriveHierarchical, DeriveHash, ExtAccountInfo, AccountInfo, AccountAuditEntry};
+
+/// Default number of entries returned by `parity_accountAuditLog`.
+const DEFAULT_AUDIT_LOG_LIMIT: u64 = 100;
 
 /// Account management (personal) rpc implementation.
 pub struct ParityAccountsClient {
@@ -335,6 +338,33 @@ impl ParityAccounts for ParityAccountsClient {
 			.map(Into::into)
 			.map_err(|e| errors::account("Could not sign message.", e))
 	}
+
+	fn account_audit_log(&self, account: Option<H160>, limit: Option<u64>) -> Result<Vec<AccountAuditEntry>> {
+		let limit = limit.unwrap_or(DEFAULT_AUDIT_LOG_LIMIT) as usize;
+		Ok(self.accounts.audit_log(account.map(Into::into), limit)
+			.into_iter()
+			.map(|entry| AccountAuditEntry {
+				timestamp: entry.timestamp,
+				account: entry.account.into(),
+				event: audit_event_name(entry.event).into(),
+				duration: entry.duration,
+			})
+			.collect()
+		)
+	}
+}
+
+fn audit_event_name(event: AuditEvent) -> &'static str {
+	match event {
+		AuditEvent::UnlockPermanent => "unlockPermanent",
+		AuditEvent::UnlockTemporary => "unlockTemporary",
+		AuditEvent::UnlockTimed => "unlockTimed",
+		AuditEvent::Relock => "relock",
+		AuditEvent::Sign => "sign",
+		AuditEvent::SignDerived => "signDerived",
+		AuditEvent::Decrypt => "decrypt",
+		AuditEvent::Agree => "agree",
+	}
 }
 
 fn into_vec<A, B>(a: Vec<A>) -> Vec<B> where
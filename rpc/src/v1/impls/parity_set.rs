@@ -19,19 +19,21 @@ use std::io;
 use std::sync::Arc;
 use std::time::Duration;
 
-use client_traits::BlockChainClient;
+use client_traits::{BlockChainClient, ReorgGuard};
 use types::client_types::Mode;
 use ethcore::miner::{self, MinerService};
-use ethereum_types::{H160, H256, U256};
+use ethcore_logger;
+use ethereum_types::{H160, H256, H512, U256};
 use crypto::publickey::KeyPair;
 use fetch::{self, Fetch};
 use hash::keccak_buffer;
-use sync::ManageNetwork;
+use sync::{ManageNetwork, WarpPin};
 use updater::{Service as UpdateService};
 
 use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_core::futures::Future;
-use v1::helpers::errors;
+use parking_lot::Mutex;
+use v1::helpers::{errors, nonce};
 use v1::traits::ParitySet;
 use v1::types::{Bytes, ReleaseInfo, Transaction};
 
@@ -39,32 +41,40 @@ use v1::types::{Bytes, ReleaseInfo, Transaction};
 pub mod accounts {
 	use super::*;
 	use accounts::AccountProvider;
+	use types::transaction::{PendingTransaction, SignedTransaction, Transaction as EthTransaction};
 	use v1::traits::ParitySetAccounts;
 	use v1::helpers::deprecated::DeprecationNotice;
+	use v1::helpers::dispatch::FullDispatcher;
 	use v1::helpers::engine_signer::EngineSigner;
 
 	/// Parity-specific account-touching RPC interfaces.
-	pub struct ParitySetAccountsClient<M> {
+	pub struct ParitySetAccountsClient<C, M> {
+		client: Arc<C>,
 		miner: Arc<M>,
 		accounts: Arc<AccountProvider>,
 		deprecation_notice: DeprecationNotice,
 	}
 
-	impl<M> ParitySetAccountsClient<M> {
+	impl<C, M> ParitySetAccountsClient<C, M> {
 		/// Creates new ParitySetAccountsClient
 		pub fn new(
 			accounts: &Arc<AccountProvider>,
+			client: &Arc<C>,
 			miner: &Arc<M>,
 		) -> Self {
 			ParitySetAccountsClient {
 				accounts: accounts.clone(),
+				client: client.clone(),
 				miner: miner.clone(),
 				deprecation_notice: Default::default(),
 			}
 		}
 	}
 
-	impl<M: MinerService + 'static> ParitySetAccounts for ParitySetAccountsClient<M> {
+	impl<C, M> ParitySetAccounts for ParitySetAccountsClient<C, M> where
+		C: miner::BlockChainClient + BlockChainClient + 'static,
+		M: MinerService + 'static,
+	{
 		fn set_engine_signer(&self, address: H160, password: String) -> Result<bool> {
 			self.deprecation_notice.print(
 				"parity_setEngineSigner",
@@ -79,6 +89,31 @@ pub mod accounts {
 			self.miner.set_author(miner::Author::Sealer(signer));
 			Ok(true)
 		}
+
+		fn replace_transaction(&self, hash: H256, gas_price: Option<U256>) -> Result<H256> {
+			let queued = self.miner.transaction(&hash)
+				.ok_or_else(|| errors::invalid_params("hash", "Transaction is not in the queue."))?;
+			let pending = queued.pending();
+			let sender = pending.sender();
+			let min_gas_price = ::miner::pool::scoring::bump_gas_price(pending.as_unsigned().gas_price);
+			let gas_price = gas_price.unwrap_or(min_gas_price);
+			if gas_price < min_gas_price {
+				return Err(errors::invalid_params("gasPrice", format!("Replacement requires a gas price of at least {}.", min_gas_price)));
+			}
+
+			let chain_id = pending.chain_id();
+			let transaction = EthTransaction {
+				gas_price,
+				..pending.as_unsigned().clone()
+			};
+			let signature = self.accounts.sign(sender, None, transaction.hash(chain_id))
+				.map_err(|e| errors::account("Could not sign the replacement.", e))?;
+			let signed = SignedTransaction::new(transaction.with_signature(signature, chain_id))
+				.map_err(errors::encryption)?;
+
+			let replacement = PendingTransaction::new(signed, pending.condition.clone());
+			FullDispatcher::dispatch_transaction(&*self.client, &*self.miner, replacement, true)
+		}
 	}
 }
 
@@ -88,6 +123,7 @@ pub struct ParitySetClient<C, M, U, F = fetch::Client> {
 	miner: Arc<M>,
 	updater: Arc<U>,
 	net: Arc<dyn ManageNetwork>,
+	nonces: Arc<Mutex<nonce::Reservations>>,
 	fetch: F,
 }
 
@@ -100,6 +136,7 @@ impl<C, M, U, F> ParitySetClient<C, M, U, F>
 		miner: &Arc<M>,
 		updater: &Arc<U>,
 		net: &Arc<dyn ManageNetwork>,
+		nonces: &Arc<Mutex<nonce::Reservations>>,
 		fetch: F,
 	) -> Self {
 		ParitySetClient {
@@ -107,6 +144,7 @@ impl<C, M, U, F> ParitySetClient<C, M, U, F>
 			miner: miner.clone(),
 			updater: updater.clone(),
 			net: net.clone(),
+			nonces: nonces.clone(),
 			fetch,
 		}
 	}
@@ -155,6 +193,14 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(true)
 	}
 
+	fn set_work_test_difficulty(&self, difficulty: Option<U256>) -> Result<bool> {
+		if difficulty.map_or(false, |difficulty| difficulty.is_zero()) {
+			return Err(errors::invalid_params("difficulty", "Test difficulty must be greater than zero."));
+		}
+		self.miner.set_work_test_difficulty(difficulty);
+		Ok(true)
+	}
+
 	fn set_author(&self, address: H160) -> Result<bool> {
 		self.miner.set_author(miner::Author::External(address));
 		Ok(true)
@@ -246,4 +292,29 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 			.map(|t| Transaction::from_pending(t.pending().clone()))
 		)
 	}
+
+	fn acknowledge_reorg(&self, accept: bool) -> Result<bool> {
+		Ok(self.client.acknowledge_reorg(accept))
+	}
+
+	fn set_logging_level(&self, target: String, level: String) -> Result<bool> {
+		let target = if target.is_empty() { None } else { Some(target.as_str()) };
+		ethcore_logger::set_log_level(target, &level).map_err(|e| errors::invalid_params("level", e))?;
+		Ok(true)
+	}
+
+	fn reserve_nonce(&self, address: H160) -> Result<U256> {
+		let minimal = self.miner.next_nonce(&*self.client, &address);
+		Ok(self.nonces.lock().reserve_external(address, minimal))
+	}
+
+	fn release_nonce(&self, address: H160, nonce: U256) -> Result<bool> {
+		Ok(self.nonces.lock().release_external(&address, &nonce))
+	}
+
+	fn set_warp_pin(&self, manifest_hash: Option<H256>, node: Option<H512>) -> Result<bool> {
+		let pin = manifest_hash.map(|manifest_hash| WarpPin { manifest_hash, node });
+		self.net.set_warp_pin(pin).map_err(|e| errors::invalid_params("Warp pin", e))?;
+		Ok(true)
+	}
 }
@@ -120,10 +120,11 @@ impl<D: Dispatcher + 'static> Personal for PersonalClient<D> {
 			.map_err(|e| errors::account("Could not create account.", e))
 	}
 
-	fn unlock_account(&self, account: H160, account_pass: String, duration: Option<U128>) -> Result<bool> {
+	fn unlock_account(&self, meta: Metadata, account: H160, account_pass: String, duration: Option<U128>) -> Result<bool> {
 		self.deprecation_notice.print("personal_unlockAccount", deprecated::msgs::ACCOUNTS);
 		let account: Address = account.into();
 		let store = self.accounts.clone();
+		let origin = Some(meta.origin.to_string());
 		let duration = match duration {
 			None => None,
 			Some(duration) => {
@@ -138,14 +139,18 @@ impl<D: Dispatcher + 'static> Personal for PersonalClient<D> {
 		};
 
 		let r = match (self.allow_perm_unlock, duration) {
-			(false, None) => store.unlock_account_temporarily(account, account_pass.into()),
+			(false, None) => store.unlock_account_temporarily(account, account_pass.into(), origin),
 			(false, _) => return Err(errors::unsupported(
 				"Time-unlocking is not supported when permanent unlock is disabled.",
 				Some("Use personal_sendTransaction or enable permanent unlocking, instead."),
 			)),
-			(true, Some(0)) => store.unlock_account_permanently(account, account_pass.into()),
-			(true, Some(d)) => store.unlock_account_timed(account, account_pass.into(), Duration::from_secs(d.into())),
-			(true, None) => store.unlock_account_timed(account, account_pass.into(), Duration::from_secs(300)),
+			(true, Some(0)) if store.max_unlock_duration().is_some() => return Err(errors::unsupported(
+				"Permanent unlocking is disabled when the maximum unlock duration is limited.",
+				Some("Unlock the account for a limited duration, instead."),
+			)),
+			(true, Some(0)) => store.unlock_account_permanently(account, account_pass.into(), origin),
+			(true, Some(d)) => store.unlock_account_timed(account, account_pass.into(), Duration::from_secs(d.into()), origin),
+			(true, None) => store.unlock_account_timed(account, account_pass.into(), Duration::from_secs(300), origin),
 		};
 		match r {
 			Ok(_) => Ok(true),
@@ -74,6 +74,8 @@ impl Private for PrivateClient {
 		let addresses: Vec<Address> = validators.into_iter().map(Into::into).collect();
 		let id = match block_number {
 			BlockNumber::Pending => return Err(errors::private_message_block_id_not_supported()),
+			BlockNumber::StateRoot(_) => return Err(errors::state_root_unsupported()),
+			BlockNumber::Confirmed(_) => return Err(errors::confirmations_unsupported()),
 			num => block_number_to_id(num)
 		};
 
@@ -105,6 +107,8 @@ impl Private for PrivateClient {
 	fn private_call(&self, block_number: BlockNumber, request: CallRequest) -> Result<Bytes, Error> {
 		let id = match block_number {
 			BlockNumber::Pending => return Err(errors::private_message_block_id_not_supported()),
+			BlockNumber::StateRoot(_) => return Err(errors::state_root_unsupported()),
+			BlockNumber::Confirmed(_) => return Err(errors::confirmations_unsupported()),
 			num => block_number_to_id(num)
 		};
 
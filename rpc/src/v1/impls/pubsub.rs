@@ -16,24 +16,37 @@
 
 //! Parity-specific PUB-SUB rpc implementation.
 
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
 use parking_lot::RwLock;
 
 use jsonrpc_core::{self as core, Result, MetaIoHandler};
 use jsonrpc_core::futures::{future, Future, Stream, Sink};
-use jsonrpc_pubsub::typed::Subscriber;
+use jsonrpc_pubsub::typed::{self, Subscriber};
 use jsonrpc_pubsub::SubscriptionId;
+use serde_json;
 use tokio_timer;
 
+use client_traits::{BlockChainClient, ChainNotify};
+use ethereum_types::H256;
 use parity_runtime::Executor;
-use v1::helpers::GenericPollManager;
+use types::{chain_notify::NewBlocks, ids::BlockId};
+use v1::helpers::{errors, GenericPollManager, Subscribers};
 use v1::metadata::Metadata;
 use v1::traits::PubSub;
+use v1::types::pubsub::Reorg;
+
+/// Topic pushing chain reorganisations, served next to the polled RPC methods.
+const REORGS_TOPIC: &str = "reorgs";
+
+type ReorgsSubscribers = Arc<RwLock<Subscribers<typed::Sink<core::Value>>>>;
 
 /// Parity PubSub implementation.
 pub struct PubSubClient<S: core::Middleware<Metadata>> {
 	poll_manager: Arc<RwLock<GenericPollManager<S>>>,
+	reorgs_subscribers: ReorgsSubscribers,
+	reorgs_handler: Option<Arc<dyn ChainNotify>>,
 	executor: Executor,
 }
 
@@ -62,9 +75,61 @@ impl<S: core::Middleware<Metadata>> PubSubClient<S> {
 
 		PubSubClient {
 			poll_manager,
+			reorgs_subscribers: Default::default(),
+			reorgs_handler: None,
 			executor,
 		}
 	}
+
+	/// Enables the `reorgs` topic, returning the handler to register for chain notifications
+	/// of `client`. The handler lives as long as this `PubSubClient`.
+	pub fn reorgs_handler<C: BlockChainClient + 'static>(&mut self, client: Arc<C>) -> Arc<ReorgsNotificationHandler<C>> {
+		let handler = Arc::new(ReorgsNotificationHandler {
+			client,
+			executor: self.executor.clone(),
+			subscribers: self.reorgs_subscribers.clone(),
+		});
+		self.reorgs_handler = Some(handler.clone());
+		handler
+	}
+}
+
+/// Pushes chain reorganisations to `reorgs` subscribers.
+pub struct ReorgsNotificationHandler<C> {
+	client: Arc<C>,
+	executor: Executor,
+	subscribers: ReorgsSubscribers,
+}
+
+impl<C: BlockChainClient> ChainNotify for ReorgsNotificationHandler<C> {
+	fn new_blocks(&self, new_blocks: NewBlocks) {
+		let route = &new_blocks.route;
+		if route.retracted().is_empty() || self.subscribers.read().is_empty() { return }
+
+		let transactions = |blocks: &[H256]| blocks
+			.iter()
+			.filter_map(|hash| self.client.block_body(BlockId::Hash(*hash)))
+			.flat_map(|body| body.transaction_hashes())
+			.collect::<Vec<_>>();
+		let enacted_transactions = transactions(route.enacted()).into_iter().collect::<HashSet<_>>();
+		let reorg = Reorg {
+			retracted: route.retracted().to_vec(),
+			enacted: route.enacted().to_vec(),
+			retracted_transactions: transactions(route.retracted())
+				.into_iter()
+				.filter(|hash| !enacted_transactions.contains(hash))
+				.collect(),
+		};
+		let reorg = serde_json::to_value(reorg).expect("Reorg serialization is infallible; qed");
+
+		for subscriber in self.subscribers.read().values() {
+			self.executor.spawn(subscriber
+				.notify(Ok(reorg.clone()))
+				.map(|_| ())
+				.map_err(|e| warn!(target: "rpc", "Unable to send reorg notification: {}", e))
+			);
+		}
+	}
 }
 
 impl PubSubClient<core::NoopMiddleware> {
@@ -81,6 +146,22 @@ impl<S: core::Middleware<Metadata>> PubSub for PubSubClient<S> {
 	type Metadata = Metadata;
 
 	fn parity_subscribe(&self, mut meta: Metadata, subscriber: Subscriber<core::Value>, method: String, params: Option<core::Params>) {
+		if method == REORGS_TOPIC {
+			let no_params = match params {
+				None | Some(core::Params::None) => true,
+				Some(core::Params::Array(ref params)) => params.is_empty(),
+				Some(core::Params::Map(_)) => false,
+			};
+			if self.reorgs_handler.is_none() {
+				let _ = subscriber.reject(errors::unimplemented(None));
+			} else if !no_params {
+				let _ = subscriber.reject(errors::invalid_params(REORGS_TOPIC, "Expected no parameters."));
+			} else {
+				self.reorgs_subscribers.write().push(subscriber);
+			}
+			return;
+		}
+
 		let params = params.unwrap_or_else(|| core::Params::Array(vec![]));
 		// Make sure to get rid of PubSub session otherwise it will never be dropped.
 		meta.session = None;
@@ -101,6 +182,7 @@ impl<S: core::Middleware<Metadata>> PubSub for PubSubClient<S> {
 
 	fn parity_unsubscribe(&self, _: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool> {
 		let res = self.poll_manager.write().unsubscribe(&id);
-		Ok(res)
+		let res2 = self.reorgs_subscribers.write().remove(&id).is_some();
+		Ok(res || res2)
 	}
 }
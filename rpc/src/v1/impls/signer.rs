@@ -16,9 +16,10 @@
 
 //! Transactions Confirmations rpc implementation
 
+use std::collections::BTreeSet;
 use std::sync::Arc;
 
-use ethereum_types::{U256, H520};
+use ethereum_types::{Address, H256, U256, H520};
 use parity_runtime::Executor;
 use parking_lot::Mutex;
 use rlp::Rlp;
@@ -31,10 +32,15 @@ use jsonrpc_pubsub::{SubscriptionId, typed::{Sink, Subscriber}};
 use v1::helpers::deprecated::{self, DeprecationNotice};
 use v1::helpers::dispatch::{self, Dispatcher, WithToken, eth_data_hash};
 use v1::helpers::{errors, ConfirmationPayload, FilledTransactionRequest, Subscribers};
-use v1::helpers::external_signer::{SigningQueue, SignerService};
+use v1::helpers::external_signer::{
+	ConfirmationSender, SigningQueue, SignerService, callback_message, request_hash, CONFIRM_ACTION, REJECT_ACTION,
+};
 use v1::metadata::Metadata;
 use v1::traits::Signer;
-use v1::types::{TransactionModification, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken, Bytes};
+use v1::types::{
+	TransactionModification, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken,
+	ConfirmationOutcome, Bytes,
+};
 
 /// Transactions confirmation (personal) rpc implementation.
 pub struct SignerClient<D: Dispatcher> {
@@ -83,42 +89,62 @@ impl<D: Dispatcher + 'static> SignerClient<D> {
 		F: FnOnce(D, &Arc<dyn dispatch::Accounts>, ConfirmationPayload) -> T,
 		T: IntoFuture<Item=WithToken<ConfirmationResponse>, Error=Error>,
 		T::Future: Send + 'static
+	{
+		Box::new(self.signer.take(&id).map(|sender| {
+			Either::A(self.confirm_taken(sender, modification, f))
+		})
+		.unwrap_or_else(|| Either::B(future::err(errors::invalid_params("Unknown RequestID", id)))))
+	}
+
+	fn confirm_taken<F, T>(&self, sender: ConfirmationSender, modification: TransactionModification, f: F) -> BoxFuture<WithToken<ConfirmationResponse>> where
+		F: FnOnce(D, &Arc<dyn dispatch::Accounts>, ConfirmationPayload) -> T,
+		T: IntoFuture<Item=WithToken<ConfirmationResponse>, Error=Error>,
+		T::Future: Send + 'static
 	{
 		let dispatcher = self.dispatcher.clone();
 		let signer = self.signer.clone();
 
-		Box::new(signer.take(&id).map(|sender| {
-			let mut payload = sender.request.payload.clone();
-			// Modify payload
-			if let ConfirmationPayload::SendTransaction(ref mut request) = payload {
-				if let Some(sender) = modification.sender {
-					request.from = sender;
-					// Altering sender should always reset the nonce.
-					request.nonce = None;
-				}
-				if let Some(gas_price) = modification.gas_price {
-					request.gas_price = gas_price;
-				}
-				if let Some(gas) = modification.gas {
-					request.gas = gas;
-				}
-				if let Some(ref condition) = modification.condition {
-					request.condition = condition.clone().map(Into::into);
-				}
+		let mut payload = sender.request.payload.clone();
+		// Modify payload
+		if let ConfirmationPayload::SendTransaction(ref mut request) = payload {
+			if let Some(sender) = modification.sender {
+				request.from = sender;
+				// Altering sender should always reset the nonce.
+				request.nonce = None;
+			}
+			if let Some(gas_price) = modification.gas_price {
+				request.gas_price = gas_price;
+			}
+			if let Some(gas) = modification.gas {
+				request.gas = gas;
+			}
+			if let Some(ref condition) = modification.condition {
+				request.condition = condition.clone().map(Into::into);
+			}
+		}
+		let fut = f(dispatcher, &self.accounts, payload);
+		Box::new(fut.into_future().then(move |result| {
+			// Execute
+			if let Ok(ref response) = result {
+				signer.request_confirmed(sender, Ok((*response).clone()));
+			} else {
+				signer.request_untouched(sender);
 			}
-			let fut = f(dispatcher, &self.accounts, payload);
-			Either::A(fut.into_future().then(move |result| {
-				// Execute
-				if let Ok(ref response) = result {
-					signer.request_confirmed(sender, Ok((*response).clone()));
-				} else {
-					signer.request_untouched(sender);
-				}
 
-				result
-			}))
-		})
-		.unwrap_or_else(|| Either::B(future::err(errors::invalid_params("Unknown RequestID", id)))))
+			result
+		}))
+	}
+
+	fn verify_callback(&self, action: &str, id: U256, modification: Option<&TransactionModification>, mac: &Bytes) -> Result<()> {
+		let secret = self.signer.webhook_secret().ok_or_else(errors::signer_webhooks_disabled)?;
+		let request = self.signer.requests().into_iter()
+			.find(|request| request.id == id)
+			.ok_or_else(errors::request_not_found)?;
+		if secret.verify(&callback_message(action, id, request_hash(&request), secret.session(), modification), &mac.0) {
+			Ok(())
+		} else {
+			Err(errors::invalid_callback_mac())
+		}
 	}
 
 	fn verify_transaction<F>(bytes: Bytes, request: FilledTransactionRequest, process: F) -> Result<ConfirmationResponse> where
@@ -178,6 +204,45 @@ impl<D: Dispatcher + 'static> Signer for SignerClient<D> {
 		}).map(dispatch::WithToken::into_value))
 	}
 
+	fn confirm_requests(&self, ids: Vec<U256>, pass: String) -> BoxFuture<Vec<ConfirmationOutcome>> {
+		self.deprecation_notice.print("signer_confirmRequests", deprecated::msgs::ACCOUNTS);
+
+		// Take all the requests up front, so that none is confirmed unless all of them are queued.
+		let mut senders = Vec::with_capacity(ids.len());
+		for id in &ids {
+			match self.signer.take(id) {
+				Some(sender) => senders.push(sender),
+				None => {
+					senders.into_iter().for_each(|sender| self.signer.request_untouched(sender));
+					return Box::new(future::err(errors::invalid_params("Unknown RequestID", id)));
+				},
+			}
+		}
+
+		// Check the password of every account once, before anything gets signed or dispatched.
+		let accounts: BTreeSet<Address> = senders.iter().map(|sender| sender.request.payload.sender()).collect();
+		for address in accounts {
+			let pass = dispatch::SignWith::Password(pass.clone().into());
+			let check = self.accounts.sign_message(address, pass, dispatch::SignMessage::Hash(H256::zero()));
+			if let Err(e) = check {
+				senders.into_iter().for_each(|sender| self.signer.request_untouched(sender));
+				return Box::new(future::err(e));
+			}
+		}
+
+		let confirmations = ids.into_iter().zip(senders).map(|(id, sender)| {
+			let pass = pass.clone();
+			self.confirm_taken(sender, Default::default(), move |dis, accounts, payload| {
+				dispatch::execute(dis, accounts, payload, dispatch::SignWith::Password(pass.into()))
+			}).then(move |result| Ok::<_, Error>(match result {
+				Ok(response) => ConfirmationOutcome { id, result: Some(response.into_value()), error: None },
+				Err(e) => ConfirmationOutcome { id, result: None, error: Some(e) },
+			}))
+		}).collect::<Vec<_>>();
+
+		Box::new(future::join_all(confirmations))
+	}
+
 	fn confirm_request_with_token(&self, id: U256, modification: TransactionModification, token: String)
 		-> BoxFuture<ConfirmationResponseWithToken>
 	{
@@ -251,6 +316,30 @@ impl<D: Dispatcher + 'static> Signer for SignerClient<D> {
 		Ok(res.is_some())
 	}
 
+	fn confirm_request_with_mac(&self, id: U256, modification: TransactionModification, mac: Bytes, pass: Option<String>)
+		-> BoxFuture<ConfirmationResponse>
+	{
+		self.deprecation_notice.print("signer_confirmRequestWithMac", deprecated::msgs::ACCOUNTS);
+
+		if let Err(e) = self.verify_callback(CONFIRM_ACTION, id, Some(&modification), &mac) {
+			return Box::new(future::err(e));
+		}
+
+		let sign_with = pass.map_or(dispatch::SignWith::Nothing, |pass| dispatch::SignWith::Password(pass.into()));
+		Box::new(self.confirm_internal(id, modification, move |dis, accounts, payload| {
+			dispatch::execute(dis, accounts, payload, sign_with)
+		}).map(dispatch::WithToken::into_value))
+	}
+
+	fn reject_request_with_mac(&self, id: U256, mac: Bytes) -> Result<bool> {
+		self.deprecation_notice.print("signer_rejectRequestWithMac", deprecated::msgs::ACCOUNTS);
+
+		self.verify_callback(REJECT_ACTION, id, None, &mac)?;
+
+		let res = self.signer.take(&id).map(|sender| self.signer.request_rejected(sender));
+		Ok(res.is_some())
+	}
+
 	fn generate_token(&self) -> Result<String> {
 		self.deprecation_notice.print("signer_generateAuthorizationToken", deprecated::msgs::ACCOUNTS);
 
@@ -16,6 +16,7 @@
 
 //! Traces api implementation.
 
+use std::cmp;
 use std::sync::Arc;
 
 use account_state::state::StateInfo;
@@ -26,6 +27,7 @@ use rlp::Rlp;
 use types::{
 	call_analytics::CallAnalytics,
 	ids::{BlockId, TransactionId, TraceId},
+	trace_filter::Filter as EthcoreTraceFilter,
 	transaction::SignedTransaction,
 };
 
@@ -34,7 +36,7 @@ use v1::Metadata;
 use v1::traits::Traces;
 use v1::helpers::{errors, fake_sign};
 use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults,
-	TraceResultsWithTransactionHash, TraceOptions, block_number_to_id};
+	TraceResultsWithTransactionHash, TraceOptions, Cursor, Page, StateAccessLog, block_number_to_id};
 
 fn to_call_analytics(flags: TraceOptions) -> CallAnalytics {
 	CallAnalytics {
@@ -47,13 +49,15 @@ fn to_call_analytics(flags: TraceOptions) -> CallAnalytics {
 /// Traces api implementation.
 pub struct TracesClient<C> {
 	client: Arc<C>,
+	page_size: usize,
 }
 
 impl<C> TracesClient<C> {
-	/// Creates new Traces client.
-	pub fn new(client: &Arc<C>) -> Self {
+	/// Creates new Traces client, returning at most `page_size` traces in a page.
+	pub fn new(client: &Arc<C>, page_size: usize) -> Self {
 		TracesClient {
 			client: client.clone(),
+			page_size,
 		}
 	}
 }
@@ -65,17 +69,68 @@ impl<C, S> Traces for TracesClient<C> where
 	type Metadata = Metadata;
 
 	fn filter(&self, filter: TraceFilter) -> Result<Option<Vec<LocalizedTrace>>> {
-		Ok(self.client.filter_traces(filter.into())
+		Ok(self.client.filter_traces(filter.try_into()?)
 			.map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
 	}
 
-	fn block_traces(&self, block_number: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>> {
+	fn filter_page(&self, mut filter: TraceFilter) -> Result<Option<Page<LocalizedTrace>>> {
+		let cursor = filter.cursor.take();
+		let page_size = filter.count.take().map_or(self.page_size, |count| cmp::min(count, self.page_size));
+		// `after` only skips the traces preceding the first page
+		let mut after = filter.after.take().filter(|_| cursor.is_none()).unwrap_or(0);
+		let filter: EthcoreTraceFilter = filter.try_into()?;
+		if let Some(cursor) = cursor {
+			if self.client.block_hash(BlockId::Number(cursor.block)) != Some(cursor.hash) {
+				return Err(errors::stale_cursor());
+			}
+		}
+
+		let (from, to) = match (self.client.block_number(filter.range.start), self.client.block_number(filter.range.end)) {
+			(Some(from), Some(to)) => (from, to),
+			_ => return Ok(None),
+		};
+		let page = Page::scan(from, to, cursor, page_size, |start, end| {
+			let filter = EthcoreTraceFilter {
+				range: BlockId::Number(start)..BlockId::Number(end),
+				from_address: filter.from_address.clone(),
+				to_address: filter.to_address.clone(),
+				after: None,
+				count: None,
+			};
+			let traces = self.client.filter_traces(filter).ok_or(())?;
+
+			// windows hold whole blocks, so traces are counted from the start of their block
+			// and their positions do not depend on the page
+			let mut block = None;
+			let mut index = 0;
+			let traces = traces.into_iter().map(|trace| {
+				index = if block == Some(trace.block_number) { index + 1 } else { 0 };
+				block = Some(trace.block_number);
+				let position = Cursor {
+					block: trace.block_number,
+					hash: trace.block_hash,
+					transaction: trace.transaction_number.map_or(u64::max_value(), |n| n as u64),
+					index,
+				};
+				(position, trace)
+			});
+			let skip = cmp::min(after, traces.len());
+			after -= skip;
+			Ok(traces.skip(skip).collect())
+		});
+
+		Ok(page.ok().map(|page| page.map(LocalizedTrace::from)))
+	}
+
+	fn block_traces(&self, block_number: BlockNumber, offset: Option<usize>, limit: Option<usize>) -> Result<Option<Vec<LocalizedTrace>>> {
 		let id = match block_number {
 			BlockNumber::Pending => return Ok(None),
+			BlockNumber::StateRoot(_) => return Err(errors::state_root_unsupported()),
+			BlockNumber::Confirmed(_) => return Err(errors::confirmations_unsupported()),
 			num => block_number_to_id(num)
 		};
 
-		Ok(self.client.block_traces(id)
+		Ok(self.client.block_traces_page(id, offset.unwrap_or(0), limit.unwrap_or_else(usize::max_value))
 			.map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
 	}
 
@@ -107,6 +162,8 @@ impl<C, S> Traces for TracesClient<C> where
 			BlockNumber::Latest => BlockId::Latest,
 
 			BlockNumber::Pending => return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
+			BlockNumber::StateRoot(_) => return Err(errors::state_root_unsupported()),
+			BlockNumber::Confirmed(_) => return Err(errors::confirmations_unsupported()),
 		};
 
 		let mut state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
@@ -135,6 +192,8 @@ impl<C, S> Traces for TracesClient<C> where
 			BlockNumber::Latest => BlockId::Latest,
 
 			BlockNumber::Pending => return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
+			BlockNumber::StateRoot(_) => return Err(errors::state_root_unsupported()),
+			BlockNumber::Confirmed(_) => return Err(errors::confirmations_unsupported()),
 		};
 
 		let mut state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
@@ -158,6 +217,8 @@ impl<C, S> Traces for TracesClient<C> where
 			BlockNumber::Latest => BlockId::Latest,
 
 			BlockNumber::Pending => return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
+			BlockNumber::StateRoot(_) => return Err(errors::state_root_unsupported()),
+			BlockNumber::Confirmed(_) => return Err(errors::confirmations_unsupported()),
 		};
 
 		let mut state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
@@ -182,10 +243,29 @@ impl<C, S> Traces for TracesClient<C> where
 			BlockNumber::Latest => BlockId::Latest,
 
 			BlockNumber::Pending => return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
+			BlockNumber::StateRoot(_) => return Err(errors::state_root_unsupported()),
+			BlockNumber::Confirmed(_) => return Err(errors::confirmations_unsupported()),
 		};
 
 		self.client.replay_block_transactions(id, to_call_analytics(flags))
 			.map(|results| results.map(TraceResultsWithTransactionHash::from).collect())
 			.map_err(errors::call)
 	}
+
+	fn replay_block_state_access(&self, block_number: BlockNumber) -> Result<Vec<StateAccessLog>> {
+		let id = match block_number {
+			BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
+			BlockNumber::Num(num) => BlockId::Number(num),
+			BlockNumber::Earliest => BlockId::Earliest,
+			BlockNumber::Latest => BlockId::Latest,
+
+			BlockNumber::Pending => return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
+			BlockNumber::StateRoot(_) => return Err(errors::state_root_unsupported()),
+			BlockNumber::Confirmed(_) => return Err(errors::confirmations_unsupported()),
+		};
+
+		self.client.replay_block_with_access_log(id)
+			.map(|results| results.into_iter().map(StateAccessLog::from).collect())
+			.map_err(errors::call)
+	}
 }
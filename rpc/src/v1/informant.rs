@@ -16,15 +16,21 @@
 
 //! RPC Requests Statistics
 
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::sync::Arc;
-use std::sync::atomic::{self, AtomicUsize};
+use std::sync::atomic::{self, AtomicBool, AtomicUsize};
 use std::time;
 use parity_runtime;
 use jsonrpc_core as core;
 use jsonrpc_core::futures::future::Either;
+use futures::{executor, future, Async, Future, Poll};
 use order_stat;
 use parking_lot::RwLock;
+use serde_json;
+use v1::helpers::errors;
+use v1::helpers::deprecated;
 
 pub use self::parity_runtime::Executor;
 
@@ -131,12 +137,43 @@ impl<T: Default + Copy + Ord> StatsCalculator<T> {
 	}
 }
 
+/// Calls of a single RPC method.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct MethodStats {
+	/// Number of calls.
+	pub calls: u64,
+	/// Number of calls which returned an error.
+	pub errors: u64,
+	/// Total execution time of the calls in microseconds.
+	pub total_latency: u128,
+}
+
+impl MethodStats {
+	/// Returns the share of calls which returned an error.
+	pub fn error_rate(&self) -> f64 {
+		match self.calls {
+			0 => 0.0,
+			calls => self.errors as f64 / calls as f64,
+		}
+	}
+
+	/// Returns average execution time of the calls in microseconds.
+	pub fn average_latency(&self) -> u128 {
+		match self.calls {
+			0 => 0,
+			calls => self.total_latency / calls as u128,
+		}
+	}
+}
+
 /// RPC Statistics
 #[derive(Default, Debug)]
 pub struct RpcStats {
 	requests: RwLock<RateCalculator>,
 	roundtrips: RwLock<StatsCalculator<u128>>,
 	active_sessions: AtomicUsize,
+	request_ids: AtomicUsize,
+	methods: RwLock<HashMap<String, MethodStats>>,
 }
 
 impl RpcStats {
@@ -156,6 +193,11 @@ impl RpcStats {
 		self.requests.write().tick()
 	}
 
+	/// Returns correlation id for a new request, unique across all transports sharing the stats.
+	pub fn next_request_id(&self) -> usize {
+		self.request_ids.fetch_add(1, atomic::Ordering::Relaxed)
+	}
+
 	/// Add roundtrip time (microseconds)
 	pub fn add_roundtrip(&self, microseconds: u128) {
 		self.roundtrips.write().add(microseconds)
@@ -175,6 +217,20 @@ impl RpcStats {
 	pub fn approximated_roundtrip(&self) -> u128 {
 		self.roundtrips.read().approximated_median()
 	}
+
+	/// Count a call of given method taking `latency` to execute.
+	pub fn add_call(&self, method: &str, failed: bool, latency: time::Duration) {
+		let mut methods = self.methods.write();
+		let stats = methods.entry(method.to_owned()).or_default();
+		stats.calls += 1;
+		stats.errors += failed as u64;
+		stats.total_latency += latency.as_micros();
+	}
+
+	/// Returns calls statistics of every method called so far.
+	pub fn methods(&self) -> BTreeMap<String, MethodStats> {
+		self.methods.read().iter().map(|(method, stats)| (method.clone(), stats.clone())).collect()
+	}
 }
 
 /// Notifies about RPC activity.
@@ -183,25 +239,224 @@ pub trait ActivityNotifier: Send + Sync + 'static {
 	fn active(&self);
 }
 
+/// Limits applied to batch requests.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BatchLimits {
+	/// Batches with more calls are rejected without executing any of them.
+	pub max_size: Option<usize>,
+	/// Calls of a batch dispatched after the budget is exhausted are not executed.
+	pub time_budget: Option<time::Duration>,
+	/// Do not execute the remaining calls of a batch after a call failed with an internal error.
+	pub abort_on_error: bool,
+}
+
 /// Stats-counting RPC middleware
 pub struct Middleware<T: ActivityNotifier = ClientNotifier> {
 	stats: Arc<RpcStats>,
 	notifier: T,
+	slow_query_threshold: Option<time::Duration>,
+	batch_limits: BatchLimits,
+	deprecation_warnings: bool,
 }
 
 impl<T: ActivityNotifier> Middleware<T> {
 	/// Create new Middleware with stats counter and activity notifier.
-	pub fn new(stats: Arc<RpcStats>, notifier: T) -> Self {
+	/// Requests taking longer than `slow_query_threshold` are logged together with their timing breakdown.
+	/// With `deprecation_warnings` errors returned by deprecated methods carry a warning.
+	pub fn new(
+		stats: Arc<RpcStats>,
+		notifier: T,
+		slow_query_threshold: Option<time::Duration>,
+		batch_limits: BatchLimits,
+		deprecation_warnings: bool,
+	) -> Self {
 		Middleware {
 			stats,
 			notifier,
+			slow_query_threshold,
+			batch_limits,
+			deprecation_warnings,
+		}
+	}
+}
+
+/// Call being executed, accounted in the statistics once it resolves.
+struct CallRecord {
+	stats: Arc<RpcStats>,
+	method: Option<String>,
+	warning: Option<String>,
+	started: time::Instant,
+}
+
+impl CallRecord {
+	fn finish(self, mut output: Option<core::Output>) -> Option<core::Output> {
+		let latency = self.started.elapsed();
+		let failed = match output {
+			// unknown methods are not counted, so junk calls can't pile up in the statistics
+			Some(core::Output::Failure(ref failure)) if failure.error.code == core::ErrorCode::MethodNotFound => return output,
+			Some(core::Output::Failure(ref mut failure)) => {
+				if let Some(warning) = self.warning {
+					match failure.error.data {
+						None => {
+							let mut data = serde_json::Map::new();
+							data.insert("warning".into(), warning.into());
+							failure.error.data = Some(core::Value::Object(data));
+						},
+						Some(core::Value::Object(ref mut data)) => {
+							data.insert("warning".into(), warning.into());
+						},
+						Some(_) => {},
+					}
+				}
+				true
+			},
+			_ => false,
+		};
+
+		if let Some(ref method) = self.method {
+			self.stats.add_call(method, failed, latency);
+		}
+		output
+	}
+}
+
+/// Batch request which is being dispatched.
+struct BatchContext {
+	deadline: Option<time::Instant>,
+	abort_on_error: bool,
+	aborted: AtomicBool,
+}
+
+thread_local! {
+	// calls of a batch are dispatched one after another on the thread handling the batch request.
+	static CURRENT_BATCH: RefCell<Option<Arc<BatchContext>>> = RefCell::new(None);
+}
+
+impl BatchContext {
+	/// Returns the reason why the next call of the batch must not be executed, if any.
+	fn skip_reason(&self) -> Option<&'static str> {
+		if self.aborted.load(atomic::Ordering::SeqCst) {
+			Some("A previous call of the batch failed with an internal error.")
+		} else if self.deadline.map_or(false, |deadline| time::Instant::now() >= deadline) {
+			Some("Time budget of the batch is exhausted.")
+		} else {
+			None
+		}
+	}
+
+	fn note_output(&self, output: &Option<core::Output>) {
+		if let Some(core::Output::Failure(ref failure)) = *output {
+			if self.abort_on_error && failure.error.code == core::ErrorCode::InternalError {
+				self.aborted.store(true, atomic::Ordering::SeqCst);
+			}
+		}
+	}
+}
+
+/// Placeholder for the output of a call which has not been executed.
+fn not_executed(call: &core::Call, reason: &str) -> Option<core::Output> {
+	match *call {
+		core::Call::MethodCall(ref call) => Some(core::Output::from(Err(errors::not_executed(reason)), call.id.clone(), call.jsonrpc)),
+		core::Call::Notification(_) => None,
+		core::Call::Invalid { ref id } => Some(core::Output::from(Err(errors::not_executed(reason)), id.clone(), Some(core::Version::V2))),
+	}
+}
+
+struct NoopNotify;
+
+impl executor::Notify for NoopNotify {
+	fn notify(&self, _id: usize) {}
+}
+
+/// Method and size of params of a request, kept for the slow query log.
+struct RequestSummary {
+	method: String,
+	params_size: usize,
+}
+
+impl RequestSummary {
+	fn new(request: &core::Request) -> Self {
+		match *request {
+			core::Request::Single(ref call) => {
+				let (method, params_size) = Self::call(call);
+				RequestSummary { method: method.into(), params_size }
+			},
+			core::Request::Batch(ref calls) => RequestSummary {
+				method: format!("batch of {}", calls.len()),
+				params_size: calls.iter().map(|call| Self::call(call).1).sum(),
+			},
+		}
+	}
+
+	fn call(call: &core::Call) -> (&str, usize) {
+		let params_size = |params: &core::Params| serde_json::to_vec(params).map(|v| v.len()).unwrap_or(0);
+		match *call {
+			core::Call::MethodCall(ref call) => (&call.method, params_size(&call.params)),
+			core::Call::Notification(ref notification) => (&notification.method, params_size(&notification.params)),
+			core::Call::Invalid { .. } => ("<invalid>", 0),
+		}
+	}
+}
+
+/// Future measuring how long it waited for the first poll and how long it took to resolve afterwards.
+struct Timed<F> {
+	inner: F,
+	created: time::Instant,
+	first_poll: Option<time::Instant>,
+}
+
+impl<F> Timed<F> {
+	fn new(inner: F) -> Self {
+		Timed {
+			inner,
+			created: time::Instant::now(),
+			first_poll: None,
+		}
+	}
+}
+
+impl<F: Future> Future for Timed<F> {
+	type Item = (F::Item, time::Duration, time::Duration);
+	type Error = F::Error;
+
+	fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+		let first_poll = *self.first_poll.get_or_insert_with(time::Instant::now);
+		let item = try_ready!(self.inner.poll());
+		Ok(Async::Ready((item, first_poll - self.created, first_poll.elapsed())))
+	}
+}
+
+/// Adds the correlation id to the data of all errors in the response: as a `requestId` field of
+/// structured (object or missing) data, or as a suffix of string data so it stays a string.
+fn annotate_errors(response: &mut core::Response, request_id: usize) {
+	let outputs = match *response {
+		core::Response::Single(ref mut output) => ::std::slice::from_mut(output),
+		core::Response::Batch(ref mut outputs) => &mut outputs[..],
+	};
+
+	for output in outputs {
+		if let core::Output::Failure(ref mut failure) = *output {
+			match failure.error.data {
+				None => {
+					let mut data = serde_json::Map::new();
+					data.insert("requestId".into(), request_id.into());
+					failure.error.data = Some(core::Value::Object(data));
+				},
+				Some(core::Value::Object(ref mut data)) => {
+					data.insert("requestId".into(), request_id.into());
+				},
+				Some(core::Value::String(ref mut data)) => {
+					data.push_str(&format!(" (request id {})", request_id));
+				},
+				Some(_) => {},
+			}
 		}
 	}
 }
 
 impl<M: core::Metadata, T: ActivityNotifier> core::Middleware<M> for Middleware<T> {
 	type Future = core::FutureResponse;
-	type CallFuture = core::middleware::NoopCallFuture;
+	type CallFuture = core::FutureOutput;
 
 	fn on_request<F, X>(&self, request: core::Request, meta: M, process: F) -> Either<Self::Future, X> where
 		F: FnOnce(core::Request, M) -> X,
@@ -212,16 +467,64 @@ impl<M: core::Metadata, T: ActivityNotifier> core::Middleware<M> for Middleware<
 		self.notifier.active();
 		self.stats.count_request();
 
+		let mut batch = None;
+		if let core::Request::Batch(ref calls) = request {
+			if let Some(max_size) = self.batch_limits.max_size.filter(|max_size| calls.len() > *max_size) {
+				debug!(target: "rpc", "Rejecting batch of {} calls", calls.len());
+				let reason = format!("Batch of {} calls exceeds the limit of {} calls.", calls.len(), max_size);
+				let outputs: Vec<_> = calls.iter().filter_map(|call| not_executed(call, &reason)).collect();
+				let response = match outputs.is_empty() {
+					true => None,
+					false => Some(core::Response::Batch(outputs)),
+				};
+				return Either::A(Box::new(future::ok(response)));
+			}
+
+			if self.batch_limits.time_budget.is_some() || self.batch_limits.abort_on_error {
+				batch = Some(Arc::new(BatchContext {
+					deadline: self.batch_limits.time_budget.map(|budget| start + budget),
+					abort_on_error: self.batch_limits.abort_on_error,
+					aborted: AtomicBool::new(false),
+				}));
+			}
+		}
+
+		let request_id = self.stats.next_request_id();
 		let id = match request {
 			core::Request::Single(core::Call::MethodCall(ref call)) => Some(call.id.clone()),
 			_ => None,
 		};
+		let slow_query = self.slow_query_threshold.map(|threshold| (threshold, RequestSummary::new(&request)));
 		let stats = self.stats.clone();
 
-		let future = process(request, meta).map(move |res| {
-			let time = start.elapsed().as_micros();
+		let previous_batch = CURRENT_BATCH.with(|current| current.replace(batch));
+		let dispatched = Timed::new(process(request, meta));
+		CURRENT_BATCH.with(|current| *current.borrow_mut() = previous_batch);
+		let dispatch = start.elapsed();
+
+		let future = dispatched.map(move |(mut res, queue, execution)| {
+			let elapsed = start.elapsed();
+			let time = elapsed.as_micros();
 			if time > 10_000 {
-				debug!(target: "rpc", "[{:?}] Took {}ms", id, time / 1_000);
+				debug!(target: "rpc", "[{}] [{:?}] Took {}ms", request_id, id, time / 1_000);
+			}
+
+			if let Some((threshold, summary)) = slow_query {
+				if elapsed >= threshold {
+					let serialization_start = time::Instant::now();
+					let response_size = res.as_ref().and_then(|res| serde_json::to_vec(res).ok()).map_or(0, |v| v.len());
+					let serialization = serialization_start.elapsed();
+					warn!(
+						target: "rpc",
+						"[{}] Slow request {} ({} bytes of params) took {}ms: queue {}ms, execution {}ms, serialization {}ms ({} bytes)",
+						request_id, summary.method, summary.params_size, elapsed.as_millis(),
+						queue.as_millis(), (dispatch + execution).as_millis(), serialization.as_millis(), response_size,
+					);
+				}
+			}
+
+			if let Some(ref mut res) = res {
+				annotate_errors(res, request_id);
 			}
 			stats.add_roundtrip(time);
 			res
@@ -229,6 +532,52 @@ impl<M: core::Metadata, T: ActivityNotifier> core::Middleware<M> for Middleware<
 
 		Either::A(Box::new(future))
 	}
+
+	fn on_call<F, X>(&self, call: core::Call, meta: M, next: F) -> Either<Self::CallFuture, X> where
+		F: FnOnce(core::Call, M) -> X,
+		X: core::futures::Future<Item=Option<core::Output>, Error=()> + Send + 'static,
+	{
+		let batch = CURRENT_BATCH.with(|current| current.borrow().clone());
+		if let Some(reason) = batch.as_ref().and_then(|batch| batch.skip_reason()) {
+			return Either::A(Box::new(future::ok(not_executed(&call, reason))));
+		}
+
+		// notifications have no output telling whether the method exists, so only method calls are counted
+		let method = match call {
+			core::Call::MethodCall(ref call) => Some(call.method.clone()),
+			core::Call::Notification(_) | core::Call::Invalid { .. } => None,
+		};
+		let deprecation = method.as_ref()
+			.and_then(|method| deprecated::find(method))
+			.filter(|_| self.deprecation_warnings);
+		let record = CallRecord {
+			stats: self.stats.clone(),
+			method,
+			warning: deprecation.map(|(method, details)| deprecated::warning(method, details)),
+			started: time::Instant::now(),
+		};
+
+		let batch = match batch {
+			Some(batch) => batch,
+			None => return Either::A(Box::new(next(call, meta).map(move |output| record.finish(output)))),
+		};
+
+		// synchronous calls resolve right away, so their outcome is known before the next call is dispatched.
+		let mut spawned = executor::spawn(next(call, meta));
+		match spawned.poll_future_notify(&Arc::new(NoopNotify), 0) {
+			Ok(Async::Ready(output)) => {
+				let output = record.finish(output);
+				batch.note_output(&output);
+				Either::A(Box::new(future::ok(output)))
+			},
+			Ok(Async::NotReady) => Either::A(Box::new(spawned.into_inner().map(move |output| {
+				let output = record.finish(output);
+				batch.note_output(&output);
+				output
+			}))),
+			Err(()) => Either::A(Box::new(future::err(()))),
+		}
+	}
 }
 
 /// Client Notifier
@@ -246,7 +595,16 @@ impl ActivityNotifier for ClientNotifier {
 #[cfg(test)]
 mod tests {
 
-	use super::{RateCalculator, StatsCalculator, RpcStats};
+	use std::sync::Arc;
+	use std::time::Duration;
+	use jsonrpc_core::{MetaIoHandler, Error, Value};
+	use super::{ActivityNotifier, BatchLimits, Middleware, RateCalculator, StatsCalculator, RpcStats};
+
+	struct NoopNotifier;
+
+	impl ActivityNotifier for NoopNotifier {
+		fn active(&self) {}
+	}
 
 	#[test]
 	fn should_calculate_rate() {
@@ -303,6 +661,107 @@ mod tests {
 		assert_eq!(stats.approximated_roundtrip(), 125);
 	}
 
+	#[test]
+	fn should_add_request_id_to_errors() {
+		// given
+		let stats = Arc::new(RpcStats::default());
+		let mut io = MetaIoHandler::with_middleware(Middleware::new(stats, NoopNotifier, None, Default::default(), false));
+		io.add_method("fail", |_| Err(Error::internal_error()));
+		io.add_method("fail_with_data", |_| {
+			let mut error = Error::internal_error();
+			let mut data = ::serde_json::Map::new();
+			data.insert("reason".into(), "test".into());
+			error.data = Some(Value::Object(data));
+			Err(error)
+		});
+		io.add_method("fail_with_message", |_| {
+			let mut error = Error::internal_error();
+			error.data = Some(Value::String("test".into()));
+			Err(error)
+		});
+
+		// when
+		let first = io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"fail","params":[],"id":1}"#, ());
+		let second = io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"fail_with_data","params":[],"id":1}"#, ());
+		let third = io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"fail_with_message","params":[],"id":1}"#, ());
+
+		// then
+		assert_eq!(first, Some(r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error","data":{"requestId":0}},"id":1}"#.into()));
+		assert_eq!(second, Some(r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error","data":{"reason":"test","requestId":1}},"id":1}"#.into()));
+		assert_eq!(third, Some(r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error","data":"test (request id 2)"},"id":1}"#.into()));
+	}
+
+	fn batch_handler(limits: BatchLimits) -> MetaIoHandler<(), Middleware<NoopNotifier>> {
+		let mut io = MetaIoHandler::with_middleware(Middleware::new(Arc::new(RpcStats::default()), NoopNotifier, None, limits, false));
+		io.add_method("ok", |_| Ok(Value::Bool(true)));
+		io.add_method("fail", |_| Err(Error::internal_error()));
+		io
+	}
+
+	#[test]
+	fn should_reject_too_large_batches() {
+		let io = batch_handler(BatchLimits { max_size: Some(1), ..Default::default() });
+		let request = r#"[{"jsonrpc":"2.0","method":"ok","params":[],"id":1},{"jsonrpc":"2.0","method":"ok","params":[],"id":2}]"#;
+
+		assert_eq!(io.handle_request_sync(request, ()), Some(concat!(
+			r#"[{"jsonrpc":"2.0","error":{"code":-32043,"message":"Call of the batch has not been executed.","data":"Batch of 2 calls exceeds the limit of 1 calls."},"id":1},"#,
+			r#"{"jsonrpc":"2.0","error":{"code":-32043,"message":"Call of the batch has not been executed.","data":"Batch of 2 calls exceeds the limit of 1 calls."},"id":2}]"#,
+		).into()));
+		assert_eq!(
+			io.handle_request_sync(r#"[{"jsonrpc":"2.0","method":"ok","params":[],"id":1}]"#, ()),
+			Some(r#"[{"jsonrpc":"2.0","result":true,"id":1}]"#.into())
+		);
+	}
+
+	#[test]
+	fn should_skip_batch_calls_after_internal_error() {
+		let io = batch_handler(BatchLimits { abort_on_error: true, ..Default::default() });
+		let request = r#"[{"jsonrpc":"2.0","method":"ok","params":[],"id":1},{"jsonrpc":"2.0","method":"fail","params":[],"id":2},{"jsonrpc":"2.0","method":"ok","params":[],"id":3}]"#;
+
+		assert_eq!(io.handle_request_sync(request, ()), Some(concat!(
+			r#"[{"jsonrpc":"2.0","result":true,"id":1},"#,
+			r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error","data":{"requestId":0}},"id":2},"#,
+			r#"{"jsonrpc":"2.0","error":{"code":-32043,"message":"Call of the batch has not been executed.","data":"A previous call of the batch failed with an internal error."},"id":3}]"#,
+		).into()));
+	}
+
+	#[test]
+	fn should_skip_batch_calls_after_time_budget() {
+		let io = batch_handler(BatchLimits { time_budget: Some(Duration::from_secs(0)), ..Default::default() });
+		let request = r#"[{"jsonrpc":"2.0","method":"ok","params":[],"id":1}]"#;
+
+		assert_eq!(
+			io.handle_request_sync(request, ()),
+			Some(r#"[{"jsonrpc":"2.0","error":{"code":-32043,"message":"Call of the batch has not been executed.","data":"Time budget of the batch is exhausted."},"id":1}]"#.into())
+		);
+	}
+
+	#[test]
+	fn should_count_method_calls() {
+		// given
+		let stats = Arc::new(RpcStats::default());
+		let mut io = MetaIoHandler::with_middleware(Middleware::new(stats.clone(), NoopNotifier, None, Default::default(), true));
+		io.add_method("ok", |_| Ok(Value::Bool(true)));
+		io.add_method("personal_accounts", |_| Err(Error::internal_error()));
+
+		// when
+		io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"ok","params":[],"id":1}"#, ());
+		io.handle_request_sync(r#"[{"jsonrpc":"2.0","method":"ok","params":[],"id":1},{"jsonrpc":"2.0","method":"unknown","params":[],"id":2}]"#, ());
+		io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"ok","params":[]}"#, ());
+		let deprecated = io.handle_request_sync(r#"{"jsonrpc":"2.0","method":"personal_accounts","params":[],"id":1}"#, ());
+
+		// then
+		let methods = stats.methods();
+		assert_eq!(methods.keys().collect::<Vec<_>>(), vec!["ok", "personal_accounts"]);
+		assert_eq!((methods["ok"].calls, methods["ok"].errors), (2, 0));
+		assert_eq!((methods["personal_accounts"].calls, methods["personal_accounts"].errors), (1, 1));
+		assert_eq!(methods["personal_accounts"].error_rate(), 1.0);
+		assert_eq!(deprecated, Some(concat!(
+			r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error","data":{"requestId":3,"#,
+			r#""warning":"personal_accounts is deprecated and will be removed in future versions: Account management is being phased out see #9997 for alternatives."}},"id":1}"#,
+		).into()));
+	}
+
 	#[test]
 	fn should_be_sync_and_send() {
 		let stats = RpcStats::default();
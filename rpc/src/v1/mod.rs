@@ -43,7 +43,7 @@ pub mod traits;
 
 pub use self::traits::{Debug, Eth, EthFilter, EthPubSub, EthSigning, Net, Parity, ParityAccountsInfo, ParityAccounts, ParitySet, ParitySetAccounts, ParitySigning, Personal, PubSub, Private, Rpc, SecretStore, Signer, Traces, Web3};
 pub use self::impls::*;
-pub use self::helpers::{NetworkSettings, block_import, dispatch};
+pub use self::helpers::{NetworkSettings, block_import, dispatch, stratum};
 pub use self::metadata::Metadata;
 pub use self::types::Origin;
 pub use self::types::pubsub::PubSubSyncStatus;
@@ -53,6 +53,7 @@ pub use self::extractors::{RpcExtractor, WsExtractor, WsStats, WsDispatcher};
 pub mod signer {
 	#[cfg(any(test, feature = "accounts"))]
 	pub use super::helpers::engine_signer::EngineSigner;
-	pub use super::helpers::external_signer::{SignerService, ConfirmationsQueue};
+	pub use super::helpers::external_signer::{SignerService, ConfirmationsQueue, SignerWebhooks, WebhookSecret};
+	pub use super::helpers::remote_signer::{RemoteSigner, RemoteSignerEndpoint, DEFAULT_REMOTE_SIGNER_TIMEOUT};
 	pub use super::types::{ConfirmationRequest, TransactionModification, TransactionCondition};
 }
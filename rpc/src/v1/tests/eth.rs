@@ -18,13 +18,15 @@
 
 use std::{env, sync::Arc};
 
+use account_state::CleanupMode;
 use accounts::AccountProvider;
 use client_traits::{BlockChainClient, ChainInfo, ImportBlock};
-use ethcore::client::{Client, ClientConfig};
+use ethcore::client::{Client, ClientConfig, PrepareOpenBlock, ImportSealedBlock};
 use ethcore::miner::Miner;
 use spec::{Genesis, Spec, self};
 use ethcore::test_helpers;
 use verification::VerifierType;
+use ethcore_logger::RotatingLogger;
 use ethereum_types::{Address, H256, U256};
 use ethjson::test_helpers::blockchain::BlockChain;
 use ethjson::spec::ForkSpec;
@@ -40,10 +42,13 @@ use types::{
 use jsonrpc_core::IoHandler;
 use v1::helpers::dispatch::{self, FullDispatcher};
 use v1::helpers::nonce;
-use v1::impls::{EthClient, EthClientOptions, SigningUnsafeClient};
+use v1::helpers::NetworkSettings;
+use v1::impls::{EthClient, EthClientOptions, ParityClient, SigningUnsafeClient};
+use v1::informant::RpcStats;
 use v1::metadata::Metadata;
-use v1::tests::helpers::{TestSnapshotService, TestSyncProvider, Config};
-use v1::traits::{Eth, EthSigning};
+use v1::tests::helpers::{TestSnapshotService, TestSyncProvider, TestUpdater, Config};
+use v1::tests::mocked::manage_network::TestManageNetwork;
+use v1::traits::{Eth, EthSigning, Parity};
 
 fn account_provider() -> Arc<AccountProvider> {
 	Arc::new(AccountProvider::transient_provider())
@@ -74,7 +79,7 @@ fn make_spec(chain: &BlockChain) -> Spec {
 }
 
 struct EthTester {
-	_miner: Arc<Miner>,
+	miner: Arc<Miner>,
 	_runtime: Runtime,
 	_snapshot: Arc<TestSnapshotService>,
 	accounts: Arc<AccountProvider>,
@@ -144,7 +149,8 @@ impl EthTester {
 				gas_price_percentile: 50,
 				allow_experimental_rpcs: true,
 				allow_missing_blocks: false,
-				no_ancient_blocks: false
+				no_ancient_blocks: false,
+				latest_confirmations: 0,
 			},
 		);
 
@@ -162,7 +168,7 @@ impl EthTester {
 		handler.extend_with(eth_sign.to_delegate());
 
 		EthTester {
-			_miner: miner_service,
+			miner: miner_service,
 			_runtime: runtime,
 			_snapshot: snapshot_service,
 			accounts: account_provider,
@@ -170,6 +176,36 @@ impl EthTester {
 			handler: handler,
 		}
 	}
+
+	fn parity_handler(&self) -> IoHandler<Metadata> {
+		let parity_client = ParityClient::new(
+			self.client.clone(),
+			self.miner.clone(),
+			sync_provider(),
+			Arc::new(TestUpdater::default()),
+			Arc::new(TestManageNetwork),
+			Arc::new(RotatingLogger::new("rpc=trace".to_owned())),
+			Arc::new(NetworkSettings {
+				name: "mynode".to_owned(),
+				chain: "testchain".to_owned(),
+				is_dev_chain: false,
+				network_port: 30303,
+				rpc_enabled: true,
+				rpc_interface: "all".to_owned(),
+				rpc_port: 8545,
+			}),
+			None,
+			None,
+			Arc::new(RpcStats::default()),
+			None,
+			None,
+			50,
+		);
+
+		let mut handler = IoHandler::default();
+		handler.extend_with(parity_client.to_delegate());
+		handler
+	}
 }
 
 #[test]
@@ -369,7 +405,7 @@ fn eth_transaction_count() {
 	let secret = "8a283037bb19c4fed7b1c569e40c7dcff366165eb869110a1b11532963eb9cb2".parse().unwrap();
 	let tester = EthTester::from_spec(Spec::load(&env::temp_dir(), TRANSACTION_COUNT_SPEC).expect("invalid chain spec"));
 	let address = tester.accounts.insert_account(secret, &"".into()).unwrap();
-	tester.accounts.unlock_account_permanently(address, "".into()).unwrap();
+	tester.accounts.unlock_account_permanently(address, "".into(), None).unwrap();
 
 	let req_before = r#"{
 		"jsonrpc": "2.0",
@@ -512,3 +548,65 @@ fn starting_nonce_test() {
 register_test!(eth_transaction_count_1, verify_transaction_counts, "BlockchainTests/ValidBlocks/bcWalletTest/wallet2outOf3txs");
 register_test!(eth_transaction_count_2, verify_transaction_counts, "BlockchainTests/ValidBlocks/bcTotalDifficultyTest/sideChainWithMoreTransactions");
 register_test!(eth_transaction_count_3, verify_transaction_counts, "BlockchainTests/ValidBlocks/bcGasPricerTest/RPC_API_Test");
+
+#[test]
+fn parity_is_valid_signature_calls_contract() {
+	// returns the magic value as a single ABI word
+	let valid_code = "0x7f1626ba7e0000000000000000000000000000000000000000000000000000000060005260206000f3";
+	// returns its calldata, which starts with the magic value as the function selector
+	let echo_code = "0x366000600037366000f3";
+	let state: ethjson::spec::State = serde_json::from_str(&format!(r#"{{
+		"0x00000000000000000000000000000000000000aa": {{ "balance": "0x0", "code": "{}" }},
+		"0x00000000000000000000000000000000000000bb": {{ "balance": "0x0", "code": "{}" }}
+	}}"#, valid_code, echo_code)).unwrap();
+	let mut spec = spec::new_frontier_test();
+	spec.set_genesis_state(state.into()).unwrap();
+	let tester = EthTester::from_spec(spec);
+	let handler = tester.parity_handler();
+
+	let request = |address: &str| format!(r#"{{
+		"jsonrpc": "2.0",
+		"method": "parity_isValidSignature",
+		"params": [
+			"{}",
+			"0x4ba3495b8137ae09a4cad596b17d730763799338bc76f00e84dc0c8760f3fdc1",
+			"0x2089e84151c3cdc45255c07557b349f5bf2ed3e68f6098723eaa90a0f8b2b3e55f70e8df7bd0c4417afb5f5a39d82e15d03adeff8796725d8b14889ed1d1aa8a1c"
+		],
+		"id": 1
+	}}"#, address);
+
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(handler.handle_request_sync(&request("0x00000000000000000000000000000000000000aa")), Some(response.to_owned()));
+
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+	assert_eq!(handler.handle_request_sync(&request("0x00000000000000000000000000000000000000bb")), Some(response.to_owned()));
+}
+
+#[test]
+fn parity_get_account_history() {
+	let tester = EthTester::from_spec(spec::new_test());
+	let engine = spec::new_test().engine;
+	let address = Address::from_low_u64_be(0xaa);
+
+	// block 1 credits the account, block 2 leaves its state untouched
+	for credit in &[5, 0] {
+		let mut b = tester.client.prepare_open_block(Address::zero(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+		b.block_mut().state_mut().add_balance(&address, &U256::from(*credit), CleanupMode::NoEmpty).unwrap();
+		b.block_mut().state_mut().commit().unwrap();
+		let b = b.close_and_lock().unwrap().seal(&*engine, vec![]).unwrap();
+		tester.client.import_sealed_block(b).unwrap();
+	}
+	let hash = |number| tester.client.block_hash(BlockId::Number(number)).unwrap();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_getAccountHistory",
+		"params": ["0x00000000000000000000000000000000000000aa", ["0x2", "earliest", "0x1"]],
+		"id": 1
+	}"#;
+	let response = format!(
+		r#"{{"jsonrpc":"2.0","result":[{{"blockNumber":"0x2","blockHash":"{:#x}","balance":"0x5","nonce":"0x0"}},{{"blockNumber":"0x0","blockHash":"{:#x}","balance":"0x0","nonce":"0x0"}},{{"blockNumber":"0x1","blockHash":"{:#x}","balance":"0x5","nonce":"0x0"}}],"id":1}}"#,
+		hash(2), hash(0), hash(1),
+	);
+	assert_eq!(tester.parity_handler().handle_request_sync(request), Some(response));
+}
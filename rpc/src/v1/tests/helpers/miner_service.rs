@@ -20,15 +20,16 @@ use std::sync::Arc;
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use bytes::Bytes;
-use client_traits::{Nonce, StateClient, ForceUpdateSealing};
+use client_traits::{BlockChain, Nonce, StateClient, ForceUpdateSealing};
 use engine::{Engine, signer::EngineSigner};
 use ethcore::block::SealedBlock;
 use ethcore::client::{PrepareOpenBlock, EngineInfo};
-use ethcore::miner::{self, MinerService, AuthoringParams, FilterOptions};
+use ethcore::miner::{self, MinerService, AuthoringParams, FilterOptions, FeeSuggestions};
 use ethcore::test_helpers::TestState;
 use ethereum_types::{H256, U256, Address};
+use miner::fee_oracle::FeeOracle;
 use miner::pool::local_transactions::Status as LocalTransactionStatus;
-use miner::pool::{verifier, VerifiedTransaction, QueueStatus};
+use miner::pool::{verifier, VerifiedTransaction, ImportSimulation, QueueStatus, OriginCounts, OriginQuotas};
 use parking_lot::{RwLock, Mutex};
 use txpool;
 use types::{
@@ -57,6 +58,8 @@ pub struct TestMinerService {
 	pub min_gas_price: RwLock<Option<U256>>,
 	/// Signer (if any)
 	pub signer: RwLock<Option<Box<dyn EngineSigner>>>,
+	/// Lowered difficulty advertised to external miners
+	pub work_test_difficulty: RwLock<Option<U256>>,
 
 	authoring_params: RwLock<AuthoringParams>,
 }
@@ -76,6 +79,7 @@ impl Default for TestMinerService {
 				extra_data: vec![1, 2, 3, 4],
 			}),
 			signer: RwLock::new(None),
+			work_test_difficulty: RwLock::new(None),
 		}
 	}
 }
@@ -100,6 +104,10 @@ impl StateClient for TestMinerService {
 	fn state_at(&self, _id: BlockId) -> Option<Self::State> {
 		Some(TestState)
 	}
+
+	fn state_at_root(&self, _root: H256) -> Option<Self::State> {
+		Some(TestState)
+	}
 }
 
 impl EngineInfo for TestMinerService {
@@ -186,6 +194,20 @@ impl MinerService for TestMinerService {
 		Ok(())
 	}
 
+	/// Simulates import of the transaction, only rejecting already imported ones.
+	fn simulate_transaction_import<C: Nonce + Sync>(&self, _chain: &C, pending: PendingTransaction, _trusted: bool)
+		-> Result<ImportSimulation, transaction::Error> {
+		let imported = self.imported_transactions.lock();
+		if imported.iter().any(|tx| tx.hash() == pending.transaction.hash()) {
+			return Err(transaction::Error::AlreadyImported);
+		}
+
+		Ok(ImportSimulation {
+			replaces: None,
+			pending_position: Some(imported.len()),
+		})
+	}
+
 	/// Called when blocks are imported to chain, updates transactions queue.
 	fn chain_new_blocks<C>(&self, _chain: &C, _imported: &[H256], _invalid: &[H256], _enacted: &[H256], _retracted: &[H256], _is_internal: bool) {
 		unimplemented!();
@@ -292,6 +314,16 @@ impl MinerService for TestMinerService {
 				max_per_sender: 16,
 				max_mem_usage: 5_000,
 			},
+			origins: OriginCounts {
+				local: 2,
+				rpc: 10,
+				network: 40,
+			},
+			origin_quotas: OriginQuotas {
+				local: None,
+				rpc: Some(256),
+				network: Some(512),
+			},
 		}
 	}
 
@@ -301,6 +333,18 @@ impl MinerService for TestMinerService {
 		unimplemented!();
 	}
 
+	fn submit_work_seal(&self, _pow_hash: H256, _seal: Vec<Bytes>) -> Result<(SealedBlock, bool), Error> {
+		unimplemented!();
+	}
+
+	fn set_work_test_difficulty(&self, difficulty: Option<U256>) {
+		*self.work_test_difficulty.write() = difficulty;
+	}
+
+	fn work_test_shares(&self) -> u64 {
+		0
+	}
+
 	fn sensible_gas_price(&self) -> U256 {
 		20_000_000_000u64.into()
 	}
@@ -309,6 +353,14 @@ impl MinerService for TestMinerService {
 		0x5208.into()
 	}
 
+	fn gas_price_floor(&self) -> Option<U256> {
+		None
+	}
+
+	fn fee_suggestions<C: BlockChain>(&self, _chain: &C, percentile: usize) -> FeeSuggestions {
+		FeeOracle::default().suggestions(percentile, None, self.sensible_gas_price())
+	}
+
 	fn set_minimal_gas_price(&self, gas_price: U256) -> Result<bool, &str> {
 		let mut new_price = self.min_gas_price.write();
 		match *new_price {
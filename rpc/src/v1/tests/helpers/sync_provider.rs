@@ -89,6 +89,8 @@ impl SyncProvider for TestSyncProvider {
 					version: 62,
 					difficulty: Some(40.into()),
 					head: H256::from_low_u64_be(50),
+					snapshot_hash: Some(H256::from_low_u64_be(70)),
+					snapshot_number: Some(30000),
 				}),
 				pip_info: None,
 			},
@@ -102,6 +104,8 @@ impl SyncProvider for TestSyncProvider {
 					version: 64,
 					difficulty: None,
 					head: H256::from_low_u64_be(60),
+					snapshot_hash: None,
+					snapshot_number: None,
 				}),
 				pip_info: None,
 			}
@@ -116,12 +120,18 @@ impl SyncProvider for TestSyncProvider {
 		map![
 			H256::from_low_u64_be(1) => TransactionStats {
 				first_seen: 10,
+				first_propagated_at: 1500000000,
+				last_propagated_at: 1500000300,
+				rebroadcasts: 15,
 				propagated_to: map![
 					H512::from_low_u64_be(128) => 16
 				],
 			},
 			H256::from_low_u64_be(5) => TransactionStats {
 				first_seen: 16,
+				first_propagated_at: 1500000100,
+				last_propagated_at: 1500000100,
+				rebroadcasts: 0,
 				propagated_to: map![
 					H512::from_low_u64_be(16) => 1
 				],
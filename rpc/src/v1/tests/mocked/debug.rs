@@ -16,6 +16,8 @@
 
 use std::sync::Arc;
 
+use ethereum_types::{Address, H256};
+
 use ethcore::test_helpers::TestBlockChainClient;
 
 use jsonrpc_core::IoHandler;
@@ -35,3 +37,18 @@ fn rpc_debug_get_bad_blocks() {
 	let response = "{\"jsonrpc\":\"2.0\",\"result\":[{\"author\":\"0x0000000000000000000000000000000000000000\",\"difficulty\":\"0x0\",\"extraData\":\"0x\",\"gasLimit\":\"0x0\",\"gasUsed\":\"0x0\",\"hash\":\"0x27bfb37e507ce90da141307204b1c6ba24194380613590ac50ca4b1d7198ff65\",\"logsBloom\":\"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000\",\"miner\":\"0x0000000000000000000000000000000000000000\",\"number\":\"0x0\",\"parentHash\":\"0x0000000000000000000000000000000000000000000000000000000000000000\",\"reason\":\"Invalid block\",\"receiptsRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"rlp\":\"\\\"0x010203\\\"\",\"sealFields\":[],\"sha3Uncles\":\"0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347\",\"size\":\"0x3\",\"stateRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"timestamp\":\"0x0\",\"totalDifficulty\":null,\"transactions\":[],\"transactionsRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"uncles\":[]}],\"id\":1}";
 	assert_eq!(io().handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_debug_storage_range_at() {
+	let client = Arc::new(TestBlockChainClient::new());
+	let address = Address::from_low_u64_be(1);
+	for key in 0..3 {
+		client.set_storage(address, H256::from_low_u64_be(key), H256::from_low_u64_be(key + 10));
+	}
+	let mut io = IoHandler::new();
+	io.extend_with(DebugClient::new(client).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_storageRangeAt", "params": ["0x0000000000000000000000000000000000000001", null, 2], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"nextKey":"0x0000000000000000000000000000000000000000000000000000000000000001","storage":{"0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563":{"key":"0x0000000000000000000000000000000000000000000000000000000000000000","value":"0x000000000000000000000000000000000000000000000000000000000000000a"},"0x405787fa12a823e0f2b7631cc41b3ba8828b3321ca811111fa75cd3aa3bb5ace":{"key":"0x0000000000000000000000000000000000000000000000000000000000000002","value":"0x000000000000000000000000000000000000000000000000000000000000000c"}}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
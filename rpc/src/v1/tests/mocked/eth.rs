@@ -98,7 +98,7 @@ impl EthTester {
 		let hashrates = Arc::new(Mutex::new(HashMap::new()));
 		let external_miner = Arc::new(ExternalMiner::new(hashrates.clone()));
 		let eth = EthClient::new(&client, &snapshot, &sync, &opt_ap, &miner, &external_miner, options).to_delegate();
-		let filter = EthFilterClient::new(client.clone(), miner.clone(), 60).to_delegate();
+		let filter = EthFilterClient::new(client.clone(), miner.clone(), 60, options.latest_confirmations).to_delegate();
 
 		let mut io: IoHandler<Metadata> = IoHandler::default();
 		io.extend_with(eth);
@@ -148,14 +148,16 @@ fn rpc_eth_syncing() {
 	// "sync" to 1000 blocks.
 	// causes TestBlockChainClient to return 1000 for its best block number.
 	tester.add_blocks(1000, EachBlockWith::Nothing);
+	*tester.client.ancient_block.write() = Some((H256::zero(), 100));
+	*tester.client.first_block.write() = Some((H256::zero(), 1000));
 
-	let true_res = r#"{"jsonrpc":"2.0","result":{"currentBlock":"0x3e8","highestBlock":"0x9c4","startingBlock":"0x0","warpChunksAmount":null,"warpChunksProcessed":null},"id":1}"#;
+	let true_res = r#"{"jsonrpc":"2.0","result":{"currentBlock":"0x3e8","highestBlock":"0x9c4","parity":{"blockGap":["0x65","0x3e8"],"queueMemLimit":"0x0","queueMemUsed":"0x0","queuedBlocks":"0x0","verificationTime":"0x0","verifiedBlocks":"0x0"},"startingBlock":"0x0","warpChunksAmount":null,"warpChunksProcessed":null},"id":1}"#;
 	assert_eq!(tester.io.handle_request_sync(request), Some(true_res.to_owned()));
 
 	*tester.client.ancient_block.write() = None;
 	*tester.client.first_block.write() = None;
 
-	let snap_res = r#"{"jsonrpc":"2.0","result":{"currentBlock":"0x3e8","highestBlock":"0x9c4","startingBlock":"0x0","warpChunksAmount":"0x32","warpChunksProcessed":"0x18"},"id":1}"#;
+	let snap_res = r#"{"jsonrpc":"2.0","result":{"currentBlock":"0x3e8","highestBlock":"0x9c4","parity":{"queueMemLimit":"0x0","queueMemUsed":"0x0","queuedBlocks":"0x0","snapshot":{"blockChunksDone":"0x6","blockChunksTotal":"0xa","stateChunksDone":"0x12","stateChunksTotal":"0x28"},"verificationTime":"0x0","verifiedBlocks":"0x0"},"startingBlock":"0x0","warpChunksAmount":"0x32","warpChunksProcessed":"0x18"},"id":1}"#;
 	tester.snapshot.set_status(RestorationStatus::Ongoing {
 		state_chunks: 40,
 		block_chunks: 10,
@@ -242,6 +244,41 @@ fn rpc_eth_logs() {
 	assert_eq!(tester.io.handle_request_sync(request3), Some(response3.to_owned()));
 }
 
+#[test]
+fn rpc_eth_logs_page() {
+	let tester = EthTester::default();
+	tester.client.set_logs((0..2).map(|index| LocalizedLogEntry {
+		block_number: 1,
+		block_hash: H256::zero(),
+		entry: LogEntry {
+			address: Address::zero(),
+			topics: vec![],
+			data: vec![1,2,3],
+		},
+		transaction_index: 0,
+		transaction_log_index: index,
+		transaction_hash: H256::zero(),
+		log_index: index,
+	}).collect());
+	tester.client.numbers.write().insert(1, H256::zero());
+
+	let request1 = r#"{"jsonrpc": "2.0", "method": "eth_getLogsPage", "params": [{"fromBlock":"0x1","toBlock":"0x1","limit":1}], "id": 1}"#;
+	let request2 = r#"{"jsonrpc": "2.0", "method": "eth_getLogsPage", "params": [{"fromBlock":"0x1","toBlock":"0x1","limit":1,"cursor":"0x0000000000000001000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"}], "id": 1}"#;
+	let request3 = r#"{"jsonrpc": "2.0", "method": "eth_getLogsPage", "params": [{"cursor":"0x0001"}], "id": 1}"#;
+
+	let response1 = r#"{"jsonrpc":"2.0","result":{"items":[{"address":"0x0000000000000000000000000000000000000000","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000000","blockNumber":"0x1","data":"0x010203","logIndex":"0x0","removed":false,"topics":[],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","transactionLogIndex":"0x0","type":"mined"}],"cursor":"0x0000000000000001000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"},"id":1}"#;
+	let response2 = r#"{"jsonrpc":"2.0","result":{"items":[{"address":"0x0000000000000000000000000000000000000000","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000000","blockNumber":"0x1","data":"0x010203","logIndex":"0x1","removed":false,"topics":[],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","transactionLogIndex":"0x1","type":"mined"}],"cursor":null},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request1), Some(response1.to_owned()));
+	assert_eq!(tester.io.handle_request_sync(request2), Some(response2.to_owned()));
+	assert!(tester.io.handle_request_sync(request3).unwrap().contains("Invalid params"));
+
+	// plain queries don't page, so they must not silently ignore a cursor
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{"fromBlock":"0x1","toBlock":"0x1","cursor":"0x0000000000000001000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: cursor","data":"\"cursor is only supported by eth_getLogsPage\""},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_logs_error() {
 	fn h256_from_digit_be(d: u8) -> H256 {
@@ -410,6 +447,14 @@ fn rpc_eth_gas_price() {
 	assert_eq!(EthTester::default().io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_max_priority_fee_per_gas() {
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_maxPriorityFeePerGas", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x4a817c800","id":1}"#;
+
+	assert_eq!(EthTester::default().io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_accounts() {
 	let tester = EthTester::default();
@@ -561,6 +606,32 @@ fn rpc_eth_transaction_count_by_number_pending() {
 	assert_eq!(EthTester::default().io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_transaction_count_by_number_with_confirmations() {
+	let tester = EthTester::new_with_options(EthClientOptions::with(|options| {
+		options.latest_confirmations = 3;
+	}));
+	tester.add_blocks(7, EachBlockWith::Transaction);
+	tester.add_blocks(3, EachBlockWith::Nothing);
+
+	let request = |param: &str| format!(r#"{{
+		"jsonrpc": "2.0",
+		"method": "eth_getBlockTransactionCountByNumber",
+		"params": [{}],
+		"id": 1
+	}}"#, param);
+	let with_transaction = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+	let without_transaction = r#"{"jsonrpc":"2.0","result":"0x0","id":1}"#;
+
+	// "latest" is block 7, three blocks below the head
+	assert_eq!(tester.io.handle_request_sync(&request(r#""latest""#)), Some(with_transaction.to_owned()));
+	// explicit depth overrides the configured one
+	assert_eq!(tester.io.handle_request_sync(&request(r#"{"confirmations": "0x0"}"#)), Some(without_transaction.to_owned()));
+	assert_eq!(tester.io.handle_request_sync(&request(r#"{"confirmations": "0x5"}"#)), Some(with_transaction.to_owned()));
+	// explicit numbers are not affected
+	assert_eq!(tester.io.handle_request_sync(&request(r#""0xa""#)), Some(without_transaction.to_owned()));
+}
+
 #[test]
 fn rpc_eth_pending_transaction_by_hash() {
 	use ethereum_types::H256;
@@ -663,6 +734,54 @@ fn rpc_eth_call_latest() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_call_state_root() {
+	let tester = EthTester::default();
+	tester.client.set_execution_result(Ok(Executed {
+		exception: None,
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34, 0xff],
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+	}));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"data": "0xd46e8dd67c5d32be8d46e8dd67c5d32be8058bb8eb970870f072445675058bb8eb970870f072445675"
+		},
+		{"stateRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"}],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1234ff","id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_block_by_state_root_unsupported() {
+	let tester = EthTester::default();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getBlockByNumber",
+		"params": [{"stateRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"}, false],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Querying by state root is only supported by methods reading the state of a full node."},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_call_pending() {
 	let tester = EthTester::default();
@@ -900,7 +1019,7 @@ fn rpc_eth_send_raw_transaction_error() {
 fn rpc_eth_send_raw_transaction() {
 	let tester = EthTester::default();
 	let address = tester.accounts_provider.new_account(&"abcd".into()).unwrap();
-	tester.accounts_provider.unlock_account_permanently(address, "abcd".into()).unwrap();
+	tester.accounts_provider.unlock_account_permanently(address, "abcd".into(), None).unwrap();
 
 	let t = Transaction {
 		nonce: U256::zero(),
@@ -940,6 +1059,7 @@ fn rpc_eth_transaction_receipt() {
 		block_number: 0x4510c,
 		cumulative_gas_used: U256::from(0x20),
 		gas_used: U256::from(0x10),
+		effective_gas_price: U256::from(0x1),
 		contract_address: None,
 		logs: vec![LocalizedLogEntry {
 			entry: LogEntry {
@@ -971,11 +1091,83 @@ fn rpc_eth_transaction_receipt() {
 		"params": ["0xb903239f8543d04b5dc1ba6579132b143087c68db1b2168786408fcbce568238"],
 		"id": 1
 	}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x4510c","contractAddress":null,"cumulativeGasUsed":"0x20","from":"0xb60e8dd61c5d32be8058bb8eb970870f07233155","gasUsed":"0x10","logs":[{"address":"0x33990122638b9132ca29c723bdf037f1a891a70c","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x4510c","data":"0x","logIndex":"0x1","removed":false,"topics":["0xa6697e974e6a320f454390be03f74955e8978f1a6971ea6730542e37b66179bc","0x4861736852656700000000000000000000000000000000000000000000000000"],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","transactionLogIndex":"0x0","type":"mined"}],"logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","root":"0x0000000000000000000000000000000000000000000000000000000000000000","to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0"},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x4510c","contractAddress":null,"cumulativeGasUsed":"0x20","effectiveGasPrice":"0x1","from":"0xb60e8dd61c5d32be8058bb8eb970870f07233155","gasUsed":"0x10","logs":[{"address":"0x33990122638b9132ca29c723bdf037f1a891a70c","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x4510c","data":"0x","logIndex":"0x1","removed":false,"topics":["0xa6697e974e6a320f454390be03f74955e8978f1a6971ea6730542e37b66179bc","0x4861736852656700000000000000000000000000000000000000000000000000"],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","transactionLogIndex":"0x0","type":"mined"}],"logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","root":"0x0000000000000000000000000000000000000000000000000000000000000000","to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","type":"0x0"},"id":1}"#;
 
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_transaction_receipt_revert_reason() {
+	use trace::{LocalizedTrace, TraceError};
+	use trace::trace::{Action as TraceAction, Call, CallType, Res};
+
+	let hash = H256::from_low_u64_be(1);
+	let receipt = LocalizedReceipt {
+		from: Address::from_low_u64_be(2),
+		to: Some(Address::from_low_u64_be(3)),
+		transaction_hash: hash,
+		transaction_index: 0,
+		block_hash: H256::from_low_u64_be(4),
+		block_number: 1,
+		cumulative_gas_used: U256::from(0x5208),
+		gas_used: U256::from(0x5208),
+		effective_gas_price: U256::from(0x1),
+		contract_address: None,
+		logs: vec![],
+		log_bloom: Bloom::zero(),
+		outcome: TransactionOutcome::StatusCode(0),
+	};
+
+	let tester = EthTester::default();
+	tester.client.set_transaction_receipt(TransactionId::Hash(hash), receipt);
+	*tester.client.traces.write() = Some(vec![LocalizedTrace {
+		action: TraceAction::Call(Call {
+			from: Address::from_low_u64_be(2),
+			to: Address::from_low_u64_be(3),
+			value: 0.into(),
+			gas: 0x5208.into(),
+			input: vec![],
+			call_type: Some(CallType::Call).into(),
+		}),
+		result: Res::FailedCall(TraceError::Reverted),
+		subtraces: 0,
+		trace_address: vec![],
+		transaction_number: Some(0),
+		transaction_hash: Some(hash),
+		block_number: 1,
+		block_hash: H256::from_low_u64_be(4),
+	}]);
+	tester.client.set_execution_result(Ok(Executed {
+		exception: Some(::vm::Error::Reverted),
+		gas: U256::from(0x5208),
+		gas_used: U256::from(0x5208),
+		refunded: U256::zero(),
+		cumulative_gas_used: U256::from(0x5208),
+		logs: vec![],
+		contracts_created: vec![],
+		output: "08c379a0\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			0000000000000000000000000000000000000000000000000000000000000004\
+			6e6f706500000000000000000000000000000000000000000000000000000000".from_hex().unwrap(),
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+	}));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getTransactionReceipt",
+		"params": ["0x0000000000000000000000000000000000000000000000000000000000000001"],
+		"id": 1
+	}"#;
+	let response = format!(
+		r#"{{"jsonrpc":"2.0","result":{{"blockHash":"0x0000000000000000000000000000000000000000000000000000000000000004","blockNumber":"0x1","contractAddress":null,"cumulativeGasUsed":"0x5208","effectiveGasPrice":"0x1","from":"0x0000000000000000000000000000000000000002","gasUsed":"0x5208","logs":[],"logsBloom":"0x{}","revertReason":"nope","status":"0x0","to":"0x0000000000000000000000000000000000000003","transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000001","transactionIndex":"0x0","type":"0x0"}},"id":1}}"#,
+		"0".repeat(512),
+	);
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response));
+}
+
 #[test]
 fn rpc_eth_transaction_receipt_null() {
 	let tester = EthTester::default();
@@ -1000,6 +1192,7 @@ fn rpc_eth_pending_receipt() {
 		transaction_index: 0,
 		cumulative_gas_used: U256::from(0x20),
 		gas_used: U256::from(0x10),
+		effective_gas_price: U256::from(0x1),
 		contract_address: None,
 		logs: Vec::new(),
 		log_bloom: Bloom::zero(),
@@ -1015,7 +1208,7 @@ fn rpc_eth_pending_receipt() {
 		"params": ["0xb903239f8543d04b5dc1ba6579132b143087c68db1b2168786408fcbce568238"],
 		"id": 1
 	}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"blockHash":null,"blockNumber":null,"contractAddress":null,"cumulativeGasUsed":"0x20","from":"0xb60e8dd61c5d32be8058bb8eb970870f07233155","gasUsed":"0x10","logs":[],"logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","transactionHash":"0xb903239f8543d04b5dc1ba6579132b143087c68db1b2168786408fcbce568238","transactionIndex":"0x0"},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"blockHash":null,"blockNumber":null,"contractAddress":null,"cumulativeGasUsed":"0x20","effectiveGasPrice":"0x1","from":"0xb60e8dd61c5d32be8058bb8eb970870f07233155","gasUsed":"0x10","logs":[],"logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","transactionHash":"0xb903239f8543d04b5dc1ba6579132b143087c68db1b2168786408fcbce568238","transactionIndex":"0x0","type":"0x0"},"id":1}"#;
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
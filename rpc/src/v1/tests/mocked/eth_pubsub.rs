@@ -25,7 +25,8 @@ use std::time::Duration;
 use v1::{EthPubSub, EthPubSubClient, Metadata};
 use ethcore::test_helpers::{TestBlockChainClient, EachBlockWith};
 use parity_runtime::Runtime;
-use ethereum_types::{Address, H256};
+use ethereum_types::{Address, H256, U256};
+use miner::work_notify::NotifyWork;
 use client_traits::{BlockInfo, ChainNotify};
 use types::{
 	chain_notify::{NewBlocks, ChainRoute, ChainRouteType},
@@ -208,6 +209,82 @@ fn should_subscribe_to_pending_transactions() {
 	assert_eq!(res, None);
 }
 
+#[test]
+fn should_subscribe_to_new_work() {
+	// given
+	let el = Runtime::with_thread_count(1);
+	let client = TestBlockChainClient::new();
+	let (_, pool_receiver) = mpsc::unbounded();
+
+	let pubsub = EthPubSubClient::new(Arc::new(client), el.executor(), pool_receiver);
+	let notifier = pubsub.work_notifier();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["newWork"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x43ca64edf03768e1","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Check notifications
+	notifier.notify(H256::from_low_u64_be(1), U256::one(), 0);
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":["0x0000000000000000000000000000000000000000000000000000000000000001","0x0000000000000000000000000000000000000000000000000000000000000000","0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff","0x0"],"subscription":"0x43ca64edf03768e1"}}"#;
+	assert_eq!(res, Some(response.into()));
+
+	// And unsubscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_unsubscribe", "params": ["0x43ca64edf03768e1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	assert_eq!(res, None);
+}
+
+#[test]
+fn should_subscribe_to_accounts() {
+	// given
+	let el = Runtime::with_thread_count(1);
+	let client = TestBlockChainClient::new();
+	let (_, pool_receiver) = mpsc::unbounded();
+
+	let pubsub = EthPubSubClient::new(Arc::new(client), el.executor(), pool_receiver);
+	let notifier = pubsub.accounts_notifier();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["accounts"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x43ca64edf03768e1","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Check notifications
+	notifier.notify(Address::from_low_u64_be(5), true);
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":{"address":"0x0000000000000000000000000000000000000005","added":true},"subscription":"0x43ca64edf03768e1"}}"#;
+	assert_eq!(res, Some(response.into()));
+
+	// And unsubscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_unsubscribe", "params": ["0x43ca64edf03768e1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	assert_eq!(res, None);
+}
+
 #[test]
 fn eth_subscribe_syncing() {
 	// given
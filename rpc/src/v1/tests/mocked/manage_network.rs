@@ -15,8 +15,9 @@
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::ops::RangeInclusive;
-use sync::ManageNetwork;
-use self::ethcore_network::{ProtocolId, NetworkContext};
+use std::time::Duration;
+use sync::{ManageNetwork, WarpPin};
+use self::ethcore_network::{ProtocolId, NetworkContext, PacketTraffic, TrafficSample};
 
 extern crate ethcore_network;
 
@@ -32,4 +33,13 @@ impl ManageNetwork for TestManageNetwork {
 	fn stop_network(&self) {}
 	fn num_peers_range(&self) -> RangeInclusive<u32> { 25..=50 }
 	fn with_proto_context(&self, _: ProtocolId, _: &mut dyn FnMut(&dyn NetworkContext)) { }
+	fn traffic_stats(&self) -> Vec<PacketTraffic> {
+		vec![PacketTraffic {
+			protocol: *b"eth",
+			packet_id: 2,
+			total: TrafficSample { ingress: 1024, egress: 512 },
+			windows: vec![(Duration::from_secs(60), TrafficSample { ingress: 128, egress: 0 })],
+		}]
+	}
+	fn set_warp_pin(&self, _: Option<WarpPin>) -> Result<(), String> { Ok(()) }
 }
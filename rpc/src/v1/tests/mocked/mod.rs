@@ -20,7 +20,7 @@
 mod debug;
 mod eth;
 mod eth_pubsub;
-mod manage_network;
+pub mod manage_network;
 mod net;
 mod parity;
 #[cfg(any(test, feature = "accounts"))]
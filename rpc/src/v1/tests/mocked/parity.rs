@@ -24,6 +24,7 @@ use miner::pool::local_transactions::Status as LocalTransactionStatus;
 use sync::ManageNetwork;
 use types::{
 	ids::TransactionId,
+	log_entry::LogEntry,
 	receipt::{LocalizedReceipt, TransactionOutcome},
 };
 
@@ -32,6 +33,8 @@ use v1::{Parity, ParityClient};
 use v1::metadata::Metadata;
 use v1::helpers::NetworkSettings;
 use v1::helpers::external_signer::SignerService;
+use v1::helpers::stratum::{StratumWorker, StratumWorkers};
+use v1::informant::RpcStats;
 use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService, TestUpdater};
 use super::manage_network::TestManageNetwork;
 use Host;
@@ -47,6 +50,8 @@ pub struct Dependencies {
 	pub settings: Arc<NetworkSettings>,
 	pub network: Arc<dyn ManageNetwork>,
 	pub ws_address: Option<Host>,
+	pub rpc_stats: Arc<RpcStats>,
+	pub stratum: Option<Arc<dyn StratumWorkers>>,
 }
 
 impl Dependencies {
@@ -71,6 +76,8 @@ impl Dependencies {
 			}),
 			network: Arc::new(TestManageNetwork),
 			ws_address: Some("127.0.0.1:18546".into()),
+			rpc_stats: Arc::new(RpcStats::default()),
+			stratum: None,
 		}
 	}
 
@@ -85,7 +92,10 @@ impl Dependencies {
 			self.settings.clone(),
 			signer,
 			self.ws_address.clone(),
+			self.rpc_stats.clone(),
 			None,
+			self.stratum.clone(),
+			50,
 		)
 	}
 
@@ -155,6 +165,21 @@ fn rpc_parity_extra_data() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_fee_suggestions() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let tier = r#"{"maxPriorityFeePerGas":"0x4a817c800","gasPrice":"0x4a817c800","confidence":0.0}"#;
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_feeSuggestions", "params": [], "id": 1}"#;
+	let response = format!(
+		r#"{{"jsonrpc":"2.0","result":{{"baseFee":null,"slow":{},"standard":{},"fast":{}}},"id":1}}"#,
+		tier, tier, tier,
+	);
+
+	assert_eq!(io.handle_request_sync(request), Some(response));
+}
+
 #[test]
 fn rpc_parity_default_extra_data() {
 	use version::version_data;
@@ -191,6 +216,17 @@ fn rpc_parity_min_gas_price() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_gas_price_floor() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_gasPriceFloor", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_dev_logs() {
 	let deps = Dependencies::new();
@@ -227,6 +263,17 @@ fn rpc_parity_transactions_limit() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_transaction_queue_status() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_transactionQueueStatus", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"count":52,"limit":1024,"senders":1,"local":{"count":2,"quota":null},"rpc":{"count":10,"quota":256},"network":{"count":40,"quota":512}},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_net_chain() {
 	let deps = Dependencies::new();
@@ -255,7 +302,7 @@ fn rpc_parity_net_peers() {
 	let io = deps.default_client();
 
 	let request = r#"{"jsonrpc": "2.0", "method": "parity_netPeers", "params":[], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"active":0,"connected":120,"max":50,"peers":[{"caps":["eth/62","eth/63"],"id":"node1","name":{"ParityClient":{"can_handle_large_requests":true,"compiler":"rustc","identity":"1","name":"Parity-Ethereum","os":"linux","semver":"2.4.0"}},"network":{"localAddress":"127.0.0.1:8888","remoteAddress":"127.0.0.1:7777"},"protocols":{"eth":{"difficulty":"0x28","head":"0000000000000000000000000000000000000000000000000000000000000032","version":62},"pip":null}},{"caps":["eth/63","eth/64"],"id":null,"name":{"ParityClient":{"can_handle_large_requests":true,"compiler":"rustc","identity":"2","name":"Parity-Ethereum","os":"linux","semver":"2.4.0"}},"network":{"localAddress":"127.0.0.1:3333","remoteAddress":"Handshake"},"protocols":{"eth":{"difficulty":null,"head":"000000000000000000000000000000000000000000000000000000000000003c","version":64},"pip":null}}]},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"active":0,"connected":120,"max":50,"peers":[{"caps":["eth/62","eth/63"],"id":"node1","name":{"ParityClient":{"can_handle_large_requests":true,"compiler":"rustc","identity":"1","name":"Parity-Ethereum","os":"linux","semver":"2.4.0"}},"network":{"localAddress":"127.0.0.1:8888","remoteAddress":"127.0.0.1:7777"},"protocols":{"eth":{"difficulty":"0x28","head":"0000000000000000000000000000000000000000000000000000000000000032","snapshotHash":"0x0000000000000000000000000000000000000000000000000000000000000046","snapshotNumber":30000,"version":62},"pip":null}},{"caps":["eth/63","eth/64"],"id":null,"name":{"ParityClient":{"can_handle_large_requests":true,"compiler":"rustc","identity":"2","name":"Parity-Ethereum","os":"linux","semver":"2.4.0"}},"network":{"localAddress":"127.0.0.1:3333","remoteAddress":"Handshake"},"protocols":{"eth":{"difficulty":null,"head":"000000000000000000000000000000000000000000000000000000000000003c","snapshotHash":null,"snapshotNumber":null,"version":64},"pip":null}}]},"id":1}"#;
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
@@ -271,6 +318,31 @@ fn rpc_parity_net_port() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_net_throughput() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_netThroughput", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"eth":{"messages":{"0x02":{"total":{"egress":512,"ingress":1024},"windows":{"60":{"egress":0,"ingress":128}}}},"total":{"egress":512,"ingress":1024},"windows":{"60":{"egress":0,"ingress":128}}}},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_account_history_rejects_unknown_blocks() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getAccountHistory", "params":["0x0000000000000000000000000000000000000001", ["0x0", "0x64"]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Unknown block number"},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getAccountHistory", "params":["0x0000000000000000000000000000000000000001", ["pending"]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: blocks","data":"\"pending block is not supported\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_rpc_settings() {
 	let deps = Dependencies::new();
@@ -282,6 +354,21 @@ fn rpc_parity_rpc_settings() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_rpc_stats() {
+	use std::time::Duration;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	deps.rpc_stats.add_call("personal_accounts", true, Duration::from_micros(30));
+	deps.rpc_stats.add_call("personal_accounts", false, Duration::from_micros(10));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_rpcStats", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"personal_accounts":{"calls":2,"errors":1,"errorRate":0.5,"averageLatency":20,"deprecated":true}},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_node_name() {
 	let deps = Dependencies::new();
@@ -411,13 +498,57 @@ fn rpc_parity_next_nonce() {
 	assert_eq!(io2.handle_request_sync(&request), Some(response2.to_owned()));
 }
 
+#[test]
+fn rpc_parity_simulate_transaction_import() {
+	use rlp;
+	use rustc_hex::ToHex;
+	use types::transaction::{Action, Transaction};
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	let keypair = Random.generate().unwrap();
+	let t = Transaction {
+		nonce: 0.into(),
+		gas_price: 1.into(),
+		gas: 21_000.into(),
+		action: Action::Call(Address::zero()),
+		value: 1.into(),
+		data: vec![],
+	}.sign(keypair.secret(), None);
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_simulateTransactionImport",
+		"params": ["0x"#.to_owned() + &rlp::encode(&t).to_hex() + r#""],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"hash":""#.to_owned() + &format!("0x{:x}", t.hash()) + r#"","pendingPosition":"0x0","replaces":null},"id":1}"#;
+	assert_eq!(io.handle_request_sync(&request), Some(response));
+	assert!(deps.miner.imported_transactions.lock().is_empty());
+
+	deps.miner.imported_transactions.lock().push(t);
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32010,"message":"Transaction with the same hash was already imported."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_transactions_stats() {
 	let deps = Dependencies::new();
 	let io = deps.default_client();
 
 	let request = r#"{"jsonrpc": "2.0", "method": "parity_pendingTransactionsStats", "params":[], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"0x0000000000000000000000000000000000000000000000000000000000000001":{"firstSeen":10,"propagatedTo":{"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000080":16}},"0x0000000000000000000000000000000000000000000000000000000000000005":{"firstSeen":16,"propagatedTo":{"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010":1}}},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"0x0000000000000000000000000000000000000000000000000000000000000001":{"firstSeen":10,"peerCount":1,"firstPropagatedAt":1500000000,"lastPropagatedAt":1500000300,"rebroadcasts":15,"propagatedTo":{"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000080":16}},"0x0000000000000000000000000000000000000000000000000000000000000005":{"firstSeen":16,"peerCount":1,"firstPropagatedAt":1500000100,"lastPropagatedAt":1500000100,"rebroadcasts":0,"propagatedTo":{"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010":1}}},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_transactions_stats_by_hash() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_pendingTransactionsStats", "params":["0x0000000000000000000000000000000000000000000000000000000000000005"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"0x0000000000000000000000000000000000000000000000000000000000000005":{"firstSeen":16,"peerCount":1,"firstPropagatedAt":1500000100,"lastPropagatedAt":1500000100,"rebroadcasts":0,"propagatedTo":{"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010":1}}},"id":1}"#;
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
@@ -517,6 +648,49 @@ fn rpc_parity_call() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_call_bundle() {
+	let deps = Dependencies::new();
+	deps.client.set_execution_result(Ok(Executed {
+		exception: Some(::vm::Error::Reverted),
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![LogEntry {
+			address: Address::from_low_u64_be(1),
+			topics: vec![H256::from_low_u64_be(2)],
+			data: vec![0x03],
+		}],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34, 0xff],
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+	}));
+	let io = deps.default_client();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_callBundle",
+		"params": [[{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"data": "0x01"
+		}, {
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"data": "0x02"
+		}],
+		"latest"],
+		"id": 1
+	}"#;
+	let result = r#"{"error":"Reverted","gasUsed":"0xff30","logs":[{"address":"0x0000000000000000000000000000000000000001","blockHash":null,"blockNumber":null,"data":"0x03","logIndex":null,"removed":false,"topics":["0x0000000000000000000000000000000000000000000000000000000000000002"],"transactionHash":null,"transactionIndex":null,"transactionLogIndex":null,"type":"pending"}],"output":"0x1234ff"}"#;
+	let response = format!(r#"{{"jsonrpc":"2.0","result":[{},{}],"id":1}}"#, result, result);
+
+	assert_eq!(io.handle_request_sync(request), Some(response));
+}
+
 #[test]
 fn rpc_parity_block_receipts() {
 	let deps = Dependencies::new();
@@ -528,6 +702,7 @@ fn rpc_parity_block_receipts() {
 			block_number: 0,
 			cumulative_gas_used: 21_000.into(),
 			gas_used: 21_000.into(),
+			effective_gas_price: 1.into(),
 			contract_address: None,
 			logs: vec![],
 			log_bloom: Bloom::from_low_u64_be(1),
@@ -543,11 +718,83 @@ fn rpc_parity_block_receipts() {
 		"params": [],
 		"id": 1
 	}"#;
-	let response = r#"{"jsonrpc":"2.0","result":[{"blockHash":"0x0000000000000000000000000000000000000000000000000000000000000003","blockNumber":"0x0","contractAddress":null,"cumulativeGasUsed":"0x5208","from":"0x0000000000000000000000000000000000000009","gasUsed":"0x5208","logs":[],"logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001","to":null,"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000001","transactionIndex":"0x0"}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"blockHash":"0x0000000000000000000000000000000000000000000000000000000000000003","blockNumber":"0x0","contractAddress":null,"cumulativeGasUsed":"0x5208","effectiveGasPrice":"0x1","from":"0x0000000000000000000000000000000000000009","gasUsed":"0x5208","logs":[],"logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001","to":null,"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000001","transactionIndex":"0x0","type":"0x0"}],"id":1}"#;
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_block_receipts_revert_reason_of_creation() {
+	use rustc_hex::FromHex;
+	use trace::{LocalizedTrace, TraceError};
+	use trace::trace::{Action as TraceAction, Create, CreationMethod, Res};
+
+	let deps = Dependencies::new();
+	let hash = H256::from_low_u64_be(1);
+	deps.client.receipts.write()
+		.insert(TransactionId::Hash(hash), LocalizedReceipt {
+			transaction_hash: hash,
+			transaction_index: 0,
+			block_hash: H256::from_low_u64_be(3),
+			block_number: 0,
+			cumulative_gas_used: 21_000.into(),
+			gas_used: 21_000.into(),
+			effective_gas_price: 1.into(),
+			contract_address: None,
+			logs: vec![],
+			log_bloom: Bloom::zero(),
+			outcome: TransactionOutcome::StatusCode(0),
+			to: None,
+			from: Address::from_low_u64_be(9),
+		});
+	*deps.client.traces.write() = Some(vec![LocalizedTrace {
+		action: TraceAction::Create(Create {
+			from: Address::from_low_u64_be(9),
+			value: 0.into(),
+			gas: 21_000.into(),
+			init: vec![],
+			creation_method: Some(CreationMethod::Create),
+		}),
+		result: Res::FailedCreate(TraceError::Reverted),
+		subtraces: 0,
+		trace_address: vec![],
+		transaction_number: Some(0),
+		transaction_hash: Some(hash),
+		block_number: 0,
+		block_hash: H256::from_low_u64_be(3),
+	}]);
+	deps.client.set_execution_result(Ok(Executed {
+		exception: Some(::vm::Error::Reverted),
+		gas: 21_000.into(),
+		gas_used: 21_000.into(),
+		refunded: U256::zero(),
+		cumulative_gas_used: 21_000.into(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: "08c379a0\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			0000000000000000000000000000000000000000000000000000000000000004\
+			6e6f706500000000000000000000000000000000000000000000000000000000".from_hex().unwrap(),
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+	}));
+	let io = deps.default_client();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_getBlockReceipts",
+		"params": [],
+		"id": 1
+	}"#;
+	let response = format!(
+		r#"{{"jsonrpc":"2.0","result":[{{"blockHash":"0x0000000000000000000000000000000000000000000000000000000000000003","blockNumber":"0x0","contractAddress":null,"cumulativeGasUsed":"0x5208","effectiveGasPrice":"0x1","from":"0x0000000000000000000000000000000000000009","gasUsed":"0x5208","logs":[],"logsBloom":"0x{}","revertReason":"nope","status":"0x0","to":null,"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000001","transactionIndex":"0x0","type":"0x0"}}],"id":1}}"#,
+		"0".repeat(512),
+	);
+
+	assert_eq!(io.handle_request_sync(request), Some(response));
+}
+
 #[test]
 fn rpc_status_ok() {
 	let deps = Dependencies::new();
@@ -620,3 +867,118 @@ fn rpc_parity_verify_signature() {
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_parity_is_valid_signature() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	// Same signature as in `rpc_parity_verify_signature`, over the keccak hash of the message.
+	let request = |address: &str| format!(r#"{{
+		"jsonrpc": "2.0",
+		"method": "parity_isValidSignature",
+		"params": [
+			"{}",
+			"0x4ba3495b8137ae09a4cad596b17d730763799338bc76f00e84dc0c8760f3fdc1",
+			"0x2089e84151c3cdc45255c07557b349f5bf2ed3e68f6098723eaa90a0f8b2b3e55f70e8df7bd0c4417afb5f5a39d82e15d03adeff8796725d8b14889ed1d1aa8a1c"
+		],
+		"id": 1
+	}}"#, address);
+
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(&request("0x9a2a08a1170f51208c2f3cede0d29ada94481eed")), Some(response.to_owned()));
+
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+	assert_eq!(io.handle_request_sync(&request("0x0000000000000000000000000000000000000001")), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_pin_and_unpin_state() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	let hash = format!("{:?}", deps.client.genesis_hash);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_pinState", "params": ["latest"], "id": 1}"#;
+	let response = format!(r#"{{"jsonrpc":"2.0","result":"{}","id":1}}"#, hash);
+	assert_eq!(io.handle_request_sync(request), Some(response));
+	assert!(deps.client.pinned_states.read().contains(&deps.client.genesis_hash));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_pinState", "params": ["0x10"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Unknown block number"},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_unpinState", "params": ["{}"], "id": 1}}"#, hash);
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_consensus_faults() {
+	use types::engines::fault::{ConsensusFault, ConsensusFaultKind};
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	*deps.client.consensus_faults.write() = vec![ConsensusFault {
+		validator: Address::from_low_u64_be(1),
+		kind: ConsensusFaultKind::SkippedStep,
+		block_number: 5,
+		block_hash: H256::from_low_u64_be(2),
+		timestamp: 10,
+	}];
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_consensusFaults", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"validator":"0x0000000000000000000000000000000000000001","kind":"skippedStep","blockNumber":"0x5","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000002","timestamp":"0xa"}],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_backup_status() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_backupStatus", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"enabled":false,"inProgress":false,"lastBackup":null,"lastError":null},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_bad_blocks() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_badBlocks", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"hash":"0x27bfb37e507ce90da141307204b1c6ba24194380613590ac50ca4b1d7198ff65","number":"0x0","parentHash":"0x0000000000000000000000000000000000000000000000000000000000000000","author":"0x0000000000000000000000000000000000000000","reason":"invalidRoot","message":"Invalid block","rejectedAt":"0x59682f00"}],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+struct TestStratumWorkers;
+
+impl StratumWorkers for TestStratumWorkers {
+	fn workers(&self) -> Vec<StratumWorker> {
+		vec![StratumWorker {
+			worker_id: "miner1".into(),
+			address: "10.0.0.1:56324".into(),
+			accepted_shares: 10.into(),
+			rejected_shares: 1.into(),
+			last_share: Some(1_500_000_000.into()),
+			hashrate: Some(71_582_788.into()),
+		}]
+	}
+}
+
+#[test]
+fn rpc_parity_stratum_workers() {
+	let mut deps = Dependencies::new();
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stratumWorkers", "params": [], "id": 1}"#;
+
+	let io = deps.default_client();
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Stratum server is disabled. Run with --stratum to enable it."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	deps.stratum = Some(Arc::new(TestStratumWorkers));
+	let io = deps.default_client();
+	let response = r#"{"jsonrpc":"2.0","result":[{"workerId":"miner1","address":"10.0.0.1:56324","acceptedShares":"0xa","rejectedShares":"0x1","lastShare":"0x59682f00","hashrate":"0x4444444"}],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -21,6 +21,7 @@ use accounts::{AccountProvider, AccountProviderSettings};
 use ethereum_types::Address;
 use ethstore::EthStore;
 use ethstore::accounts_dir::RootDiskDirectory;
+use serde_json;
 use tempdir::TempDir;
 
 use jsonrpc_core::IoHandler;
@@ -480,3 +481,21 @@ fn should_sign_message() {
 	let res = tester.io.handle_request_sync(&request);
 	assert_eq!(res, Some(response.into()));
 }
+
+#[test]
+fn should_return_account_audit_log() {
+	let tester = setup();
+	let address = tester.accounts.new_account(&"password".into()).unwrap();
+	tester.accounts.unlock_account_temporarily(address, "password".into(), None).unwrap();
+	tester.accounts.sign(address, None, Default::default()).unwrap();
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_accountAuditLog", "params": ["0x{:x}"], "id": 1}}"#, address);
+	let res = tester.io.handle_request_sync(&request).unwrap();
+	let entries: serde_json::Value = serde_json::from_str(&res).unwrap();
+	let events: Vec<_> = entries["result"].as_array().unwrap().iter().map(|e| e["event"].as_str().unwrap().to_owned()).collect();
+	assert_eq!(events, vec!["unlockTemporary", "sign"]);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_accountAuditLog", "params": ["0x0000000000000000000000000000000000000001", 5], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.into()));
+}
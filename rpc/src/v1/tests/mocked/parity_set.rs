@@ -21,10 +21,13 @@ use ethereum_types::{U256, Address};
 
 use ethcore::miner::MinerService;
 use ethcore::test_helpers::TestBlockChainClient;
+use parity_runtime::Executor;
+use parking_lot::Mutex;
 use sync::ManageNetwork;
 
 use jsonrpc_core::IoHandler;
 use v1::{ParitySet, ParitySetClient};
+use v1::helpers::nonce;
 use v1::tests::helpers::{TestMinerService, TestUpdater};
 use super::manage_network::TestManageNetwork;
 
@@ -59,6 +62,7 @@ fn parity_set_client(
 		miner,
 		updater,
 		&(net.clone() as Arc<dyn ManageNetwork>),
+		&Arc::new(Mutex::new(nonce::Reservations::new(Executor::new_sync()))),
 		FakeFetch::new(Some(1)),
 	)
 }
@@ -169,6 +173,32 @@ fn rpc_parity_set_extra_data() {
 	assert_eq!(miner.authoring_params().extra_data, "cd1722f3947def4cf144679da39c4c32bdc35681".from_hex().unwrap());
 }
 
+#[test]
+fn rpc_parity_set_work_test_difficulty() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setWorkTestDifficulty", "params":["0x400"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(*miner.work_test_difficulty.read(), Some(0x400.into()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setWorkTestDifficulty", "params":[null], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(*miner.work_test_difficulty.read(), None);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setWorkTestDifficulty", "params":["0x0"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: difficulty","data":"\"Test difficulty must be greater than zero.\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(*miner.work_test_difficulty.read(), None);
+}
+
 #[test]
 fn rpc_parity_set_author() {
 	let miner = miner_service();
@@ -244,6 +274,58 @@ fn rpc_parity_remove_transaction() {
 	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_acknowledge_reorg_when_not_halted() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_acknowledgeReorg", "params":[true], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_set_logging_level_rejects_unknown_level() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setLoggingLevel", "params":["sync", "loud"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: level","data":"\"Invalid log level: loud\""},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_reserve_and_release_nonce() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let address = Address::from_low_u64_be(1);
+	miner.increment_nonce(&address);
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let reserve = r#"{"jsonrpc": "2.0", "method": "parity_reserveNonce", "params":["0x0000000000000000000000000000000000000001"], "id": 1}"#;
+	let release = r#"{"jsonrpc": "2.0", "method": "parity_releaseNonce", "params":["0x0000000000000000000000000000000000000001", "0x1"], "id": 1}"#;
+
+	assert_eq!(io.handle_request_sync(reserve), Some(r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#.to_owned()));
+	assert_eq!(io.handle_request_sync(reserve), Some(r#"{"jsonrpc":"2.0","result":"0x2","id":1}"#.to_owned()));
+	assert_eq!(io.handle_request_sync(release), Some(r#"{"jsonrpc":"2.0","result":true,"id":1}"#.to_owned()));
+	assert_eq!(io.handle_request_sync(release), Some(r#"{"jsonrpc":"2.0","result":false,"id":1}"#.to_owned()));
+	assert_eq!(io.handle_request_sync(reserve), Some(r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#.to_owned()));
+}
+
 #[test]
 fn rpc_parity_set_engine_signer() {
 	use accounts::AccountProvider;
@@ -255,9 +337,10 @@ fn rpc_parity_set_engine_signer() {
 	account_provider.insert_account(::hash::keccak("cow").into(), &"password".into()).unwrap();
 
 	let miner = miner_service();
+	let client = client_service();
 	let mut io = IoHandler::new();
 	io.extend_with(
-		ParitySetAccountsClient::new(&account_provider, &miner).to_delegate()
+		ParitySetAccountsClient::new(&account_provider, &client, &miner).to_delegate()
 	);
 
 	let request = r#"{"jsonrpc": "2.0", "method": "parity_setEngineSigner", "params":["0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826", "password"], "id": 1}"#;
@@ -268,3 +351,65 @@ fn rpc_parity_set_engine_signer() {
 	let signature = miner.signer.read().as_ref().unwrap().sign(::hash::keccak("x")).unwrap().to_vec();
 	assert_eq!(&format!("{}", signature.pretty()), "6f46069ded2154af6e806706e4f7f6fd310ac45f3c6dccb85f11c0059ee20a09245df0a0008bb84a10882b1298284bc93058e7bc5938ea728e77620061687a6401");
 }
+
+#[test]
+fn rpc_parity_replace_transaction() {
+	use accounts::AccountProvider;
+	use types::transaction::{Transaction, Action};
+	use v1::impls::ParitySetAccountsClient;
+	use v1::traits::ParitySetAccounts;
+
+	let account_provider = Arc::new(AccountProvider::transient_provider());
+	let address = account_provider.insert_account(::hash::keccak("cow").into(), &"password".into()).unwrap();
+	account_provider.unlock_account_permanently(address, "password".into(), None).unwrap();
+
+	let miner = miner_service();
+	let client = client_service();
+	let mut io = IoHandler::new();
+	io.extend_with(
+		ParitySetAccountsClient::new(&account_provider, &client, &miner).to_delegate()
+	);
+
+	let tx = Transaction {
+		nonce: 1.into(),
+		gas_price: 0x9184e72a000u64.into(),
+		gas: 0x76c0.into(),
+		action: Action::Call(Address::from_low_u64_be(5)),
+		value: 0x9184e72au64.into(),
+		data: vec![]
+	};
+	let signed = tx.sign(&::hash::keccak("cow").into(), None);
+	let hash = signed.hash();
+	miner.pending_transactions.lock().insert(hash, signed);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_replaceTransaction", "params":[""#.to_owned() + &format!("0x{:x}", hash) + r#"", "0x9184e72a001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: gasPrice","data":"\"Replacement requires a gas price of at least 11250000000000.\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_replaceTransaction", "params":[""#.to_owned() + &format!("0x{:x}", hash) + r#""], "id": 1}"#;
+	let response = io.handle_request_sync(&request);
+
+	let imported = miner.imported_transactions.lock();
+	assert_eq!(imported.len(), 1);
+	assert_eq!(imported[0].sender(), address);
+	assert_eq!(imported[0].nonce, 1.into());
+	assert_eq!(imported[0].gas_price, 0xa3b5840f400u64.into());
+	assert_eq!(response, Some(format!(r#"{{"jsonrpc":"2.0","result":"0x{:x}","id":1}}"#, imported[0].hash())));
+}
+
+#[test]
+fn rpc_parity_set_warp_pin() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setWarpPin", "params": ["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setWarpPin", "params": [], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
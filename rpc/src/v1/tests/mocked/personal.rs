@@ -229,7 +229,7 @@ fn sign_and_send_test(method: &str) {
 		value: U256::from(0x9184e72au64),
 		data: vec![]
 	};
-	tester.accounts.unlock_account_temporarily(address, "password123".into()).unwrap();
+	tester.accounts.unlock_account_temporarily(address, "password123".into(), None).unwrap();
 	let signature = tester.accounts.sign(address, None, t.hash(None)).unwrap();
 	let t = t.with_signature(signature, None);
 
@@ -247,7 +247,7 @@ fn sign_and_send_test(method: &str) {
 		value: U256::from(0x9184e72au64),
 		data: vec![]
 	};
-	tester.accounts.unlock_account_temporarily(address, "password123".into()).unwrap();
+	tester.accounts.unlock_account_temporarily(address, "password123".into(), None).unwrap();
 	let signature = tester.accounts.sign(address, None, t.hash(None)).unwrap();
 	let t = t.with_signature(signature, None);
 
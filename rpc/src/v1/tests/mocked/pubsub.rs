@@ -15,12 +15,19 @@
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::{atomic, Arc};
+use std::time::Duration;
 
 use jsonrpc_core::{self as core, MetaIoHandler};
 use jsonrpc_core::futures::{self, Stream, Future};
 use jsonrpc_pubsub::Session;
 
+use client_traits::{BlockInfo, ChainNotify};
+use ethcore::test_helpers::{TestBlockChainClient, EachBlockWith};
 use parity_runtime::Runtime;
+use types::{
+	chain_notify::{NewBlocks, ChainRoute, ChainRouteType},
+	ids::BlockId,
+};
 use v1::{PubSub, PubSubClient, Metadata};
 
 fn rpc() -> MetaIoHandler<Metadata, core::NoopMiddleware> {
@@ -75,3 +82,71 @@ fn should_subscribe_to_a_method() {
 	let (res, _receiver) = receiver.into_future().wait().unwrap();
 	assert_eq!(res, None);
 }
+
+#[test]
+fn should_subscribe_to_reorgs() {
+	// given
+	let el = Runtime::with_thread_count(1);
+	let mut client = TestBlockChainClient::new();
+	client.add_blocks(1, EachBlockWith::Transaction);
+	client.add_blocks(1, EachBlockWith::Nothing);
+	let h2 = client.block_hash_delta_minus(1);
+	let h1 = client.block_hash_delta_minus(2);
+	let tx_hash = client.block(BlockId::Hash(h1)).unwrap().transactions()[0].hash();
+
+	let mut pubsub = PubSubClient::new_test(rpc(), el.executor());
+	let handler = pubsub.reorgs_handler(Arc::new(client));
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub.to_delegate());
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_subscribe", "params": ["reorgs"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x43ca64edf03768e1","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// No notification without retracted blocks
+	let route = ChainRoute::new(vec![(h2, ChainRouteType::Enacted)]);
+	handler.new_blocks(NewBlocks::new(vec![], vec![], route, vec![], vec![], Duration::from_millis(0), false));
+
+	// Check notifications
+	let route = ChainRoute::new(vec![(h1, ChainRouteType::Retracted), (h2, ChainRouteType::Enacted)]);
+	handler.new_blocks(NewBlocks::new(vec![], vec![], route, vec![], vec![], Duration::from_millis(0), false));
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = format!(
+		r#"{{"jsonrpc":"2.0","method":"parity_subscription","params":{{"result":{{"enacted":["{:#x}"],"retracted":["{:#x}"],"retractedTransactions":["{:#x}"]}},"subscription":"0x43ca64edf03768e1"}}}}"#,
+		h2, h1, tx_hash,
+	);
+	assert_eq!(res, Some(response));
+
+	// And unsubscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_unsubscribe", "params": ["0x43ca64edf03768e1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	assert_eq!(res, None);
+}
+
+#[test]
+fn should_reject_reorgs_without_chain_notifications() {
+	// given
+	let el = Runtime::with_thread_count(1);
+	let pubsub = PubSubClient::new_test(rpc(), el.executor()).to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, _receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_subscribe", "params": ["reorgs"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not implemented yet. Please create an issue on Github repo."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+}
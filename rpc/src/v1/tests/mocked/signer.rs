@@ -20,6 +20,7 @@ use ethereum_types::{H520, U256, Address};
 use bytes::ToPretty;
 
 use accounts::AccountProvider;
+use fake_fetch::FakeFetch;
 use ethcore::test_helpers::TestBlockChainClient;
 use parity_runtime::Runtime;
 use parking_lot::Mutex;
@@ -31,9 +32,9 @@ use jsonrpc_core::IoHandler;
 use v1::{SignerClient, Signer, Origin};
 use v1::metadata::Metadata;
 use v1::tests::helpers::TestMinerService;
-use v1::types::Bytes as RpcBytes;
+use v1::types::{Bytes as RpcBytes, TransactionModification};
 use v1::helpers::{nonce, FilledTransactionRequest, ConfirmationPayload};
-use v1::helpers::external_signer::{SigningQueue, SignerService};
+use v1::helpers::external_signer::{SigningQueue, SignerService, SignerWebhooks, WebhookSecret, callback_message, request_hash};
 use v1::helpers::dispatch::{self, FullDispatcher, eth_data_hash};
 
 struct SignerTester {
@@ -59,7 +60,22 @@ fn miner_service() -> Arc<TestMinerService> {
 
 fn signer_tester() -> SignerTester {
 	let runtime = Runtime::with_thread_count(1);
-	let signer = Arc::new(SignerService::new_test(false));
+	signer_tester_with_service(runtime, SignerService::new_test(false))
+}
+
+fn webhooks_signer_tester(secret: &[u8]) -> SignerTester {
+	let runtime = Runtime::with_thread_count(1);
+	let webhooks = SignerWebhooks::new(
+		&[],
+		WebhookSecret::new(secret.to_vec()),
+		FakeFetch::new(None::<usize>),
+		runtime.executor(),
+	).unwrap();
+	signer_tester_with_service(runtime, SignerService::new_test(false).with_webhooks(webhooks))
+}
+
+fn signer_tester_with_service(runtime: Runtime, signer: SignerService) -> SignerTester {
+	let signer = Arc::new(signer);
 	let accounts = accounts_provider();
 	let account_signer = Arc::new(dispatch::Signer::new(accounts.clone()));
 	let client = blockchain_client();
@@ -136,6 +152,126 @@ fn should_reject_transaction_from_queue_without_dispatching() {
 	assert_eq!(tester.miner.imported_transactions.lock().len(), 0);
 }
 
+#[test]
+fn should_reject_request_with_valid_callback_mac() {
+	// given
+	let tester = webhooks_signer_tester(b"webhook secret");
+	let _confirmation_future = tester.signer.add_request(ConfirmationPayload::SendTransaction(FilledTransactionRequest {
+		from: Address::from_low_u64_be(1),
+		used_default_from: false,
+		to: Some(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap()),
+		gas_price: U256::from(10_000),
+		gas: U256::from(10_000_000),
+		value: U256::from(1),
+		data: vec![],
+		nonce: None,
+		condition: None,
+	}), Origin::Unknown).unwrap();
+	let secret = tester.signer.webhook_secret().unwrap().clone();
+	let hash = request_hash(&tester.signer.requests()[0]);
+	let confirm_mac = secret.sign(&callback_message("confirm", U256::from(1), hash, secret.session(), Some(&Default::default())));
+	let reject_mac = secret.sign(&callback_message("reject", U256::from(1), hash, secret.session(), None));
+	// same secret, but signed for a previous run of the node
+	let previous = WebhookSecret::new(b"webhook secret".to_vec());
+	let replayed_mac = previous.sign(&callback_message("reject", U256::from(1), hash, previous.session(), None));
+
+	// when
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Callback MAC does not match the request."},"id":1}"#;
+	for mac in &[confirm_mac, replayed_mac] {
+		let request = r#"{"jsonrpc":"2.0","method":"signer_rejectRequestWithMac","params":["0x1","0x"#.to_owned()
+			+ &mac.to_hex()
+			+ r#""],"id":1}"#;
+
+		// then
+		assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+		assert_eq!(tester.signer.requests().len(), 1);
+	}
+
+	// when
+	let request = r#"{"jsonrpc":"2.0","method":"signer_rejectRequestWithMac","params":["0x1","0x"#.to_owned()
+		+ &reject_mac.to_hex()
+		+ r#""],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+	assert_eq!(tester.signer.requests().len(), 0);
+	assert_eq!(tester.miner.imported_transactions.lock().len(), 0);
+}
+
+#[test]
+fn should_confirm_request_with_callback_mac_covering_modification() {
+	// given
+	let tester = webhooks_signer_tester(b"webhook secret");
+	let address = tester.accounts.new_account(&"test".into()).unwrap();
+	tester.accounts.unlock_account_permanently(address, "test".into(), None).unwrap();
+	let recipient = Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap();
+	let _confirmation_future = tester.signer.add_request(ConfirmationPayload::SendTransaction(FilledTransactionRequest {
+		from: address,
+		used_default_from: false,
+		to: Some(recipient),
+		gas_price: U256::from(10_000),
+		gas: U256::from(10_000_000),
+		value: U256::from(1),
+		data: vec![],
+		nonce: None,
+		condition: None,
+	}), Origin::Unknown).unwrap();
+	let secret = tester.signer.webhook_secret().unwrap().clone();
+	let hash = request_hash(&tester.signer.requests()[0]);
+	let modification = TransactionModification {
+		gas_price: Some(U256::from(0x1000)),
+		gas: Some(U256::from(0x50505)),
+		..Default::default()
+	};
+	let mac = secret.sign(&callback_message("confirm", U256::from(1), hash, secret.session(), Some(&modification))).to_hex();
+
+	let t = Transaction {
+		nonce: U256::zero(),
+		gas_price: U256::from(0x1000),
+		gas: U256::from(0x50505),
+		action: Action::Call(recipient),
+		value: U256::from(0x1),
+		data: vec![]
+	};
+	let signature = tester.accounts.sign(address, None, t.hash(None)).unwrap();
+	let t = t.with_signature(signature, None);
+
+	// when the gas price is raised on the way
+	let request = r#"{"jsonrpc":"2.0","method":"signer_confirmRequestWithMac","params":["0x1",{"gasPrice":"0x2000","gas":"0x50505"},"0x"#.to_owned()
+		+ &mac
+		+ r#""],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Callback MAC does not match the request."},"id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+	assert_eq!(tester.signer.requests().len(), 1);
+
+	// when the approved modification is sent, without a password
+	let request = r#"{"jsonrpc":"2.0","method":"signer_confirmRequestWithMac","params":["0x1",{"gasPrice":"0x1000","gas":"0x50505"},"0x"#.to_owned()
+		+ &mac
+		+ r#""],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":""#.to_owned() + format!("0x{:x}", t.hash()).as_ref() + r#"","id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+	assert_eq!(tester.signer.requests().len(), 0);
+	assert_eq!(tester.miner.imported_transactions.lock().len(), 1);
+}
+
+#[test]
+fn should_not_accept_callbacks_without_webhooks() {
+	// given
+	let tester = signer_tester();
+
+	// when
+	let request = r#"{"jsonrpc":"2.0","method":"signer_rejectRequestWithMac","params":["0x1","0x00"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Signer webhooks are not configured. Run with --signer-webhook-secret to enable authenticated callbacks."},"id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+}
+
 #[test]
 fn should_not_remove_transaction_if_password_is_invalid() {
 	// given
@@ -204,7 +340,7 @@ fn should_confirm_transaction_and_dispatch() {
 		value: U256::from(0x1),
 		data: vec![]
 	};
-	tester.accounts.unlock_account_temporarily(address, "test".into()).unwrap();
+	tester.accounts.unlock_account_temporarily(address, "test".into(), None).unwrap();
 	let signature = tester.accounts.sign(address, None, t.hash(None)).unwrap();
 	let t = t.with_signature(signature, None);
 
@@ -396,7 +532,7 @@ fn should_return_error_when_sender_does_not_match() {
 		value: U256::from(0x1),
 		data: vec![]
 	};
-	tester.accounts.unlock_account_temporarily(address, "test".into()).unwrap();
+	tester.accounts.unlock_account_temporarily(address, "test".into(), None).unwrap();
 	let signature = tester.accounts.sign(address, None, t.hash(None)).unwrap();
 	let t = t.with_signature(signature, None);
 	let rlp = encode(&t);
@@ -512,6 +648,55 @@ fn should_confirm_data_sign_with_signature() {
 	assert_eq!(tester.miner.imported_transactions.lock().len(), 0);
 }
 
+#[test]
+fn should_confirm_multiple_requests_with_one_password() {
+	// given
+	let tester = signer_tester();
+	let address = tester.accounts.new_account(&"test".into()).unwrap();
+	for data in &[vec![1], vec![2]] {
+		let _confirmation_future = tester.signer.add_request(ConfirmationPayload::EthSignMessage(
+			address,
+			data.clone().into(),
+		), Origin::Unknown).unwrap();
+	}
+	assert_eq!(tester.signer.requests().len(), 2);
+
+	let signature = |data: Vec<u8>| {
+		let signature = tester.accounts.sign(address, Some("test".into()), eth_data_hash(data)).unwrap();
+		format!("{:?}", H520(signature.into_electrum()))
+	};
+
+	// when
+	let request = r#"{"jsonrpc":"2.0","method":"signer_confirmRequests","params":[["0x1","0x2"],"test"],"id":1}"#;
+	let response = format!(
+		r#"{{"jsonrpc":"2.0","result":[{{"error":null,"id":"0x1","result":"{}"}},{{"error":null,"id":"0x2","result":"{}"}}],"id":1}}"#,
+		signature(vec![1]),
+		signature(vec![2]),
+	);
+
+	// then
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response));
+	assert_eq!(tester.signer.requests().len(), 0);
+}
+
+#[test]
+fn should_not_confirm_any_request_if_password_is_invalid() {
+	// given
+	let tester = signer_tester();
+	let address = tester.accounts.new_account(&"test".into()).unwrap();
+	let _first = tester.signer.add_request(ConfirmationPayload::EthSignMessage(address, vec![1].into()), Origin::Unknown).unwrap();
+	let _second = tester.signer.add_request(ConfirmationPayload::EthSignMessage(Address::zero(), vec![2].into()), Origin::Unknown).unwrap();
+	assert_eq!(tester.signer.requests().len(), 2);
+
+	// when
+	let request = r#"{"jsonrpc":"2.0","method":"signer_confirmRequests","params":[["0x1","0x2"],"test"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32021,"message":"Account password is invalid or account does not exist.","data":"SStore(InvalidAccount)"},"id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+	assert_eq!(tester.signer.requests().len(), 2);
+}
+
 #[test]
 fn should_confirm_decrypt_with_phrase() {
 	// given
@@ -92,7 +92,7 @@ fn rpc_eth_sign() {
 	let tester = eth_signing(true);
 
 	let account = tester.accounts.insert_account(Secret::from([69u8; 32]), &"abcd".into()).unwrap();
-	tester.accounts.unlock_account_permanently(account, "abcd".into()).unwrap();
+	tester.accounts.unlock_account_permanently(account, "abcd".into(), None).unwrap();
 	let _message = "0cc175b9c0f1b6a831c399e26977266192eb5ffee6ae2fec3ad71c777531578f".from_hex().unwrap();
 
 	let req = r#"{
@@ -271,7 +271,7 @@ fn eth_sign_locked_account() {
 		tester.io.handle_request_sync(&req_send_trans).unwrap()
 	);
 	// unlock the account
-	tester.accounts.unlock_account_permanently(address, "".into()).unwrap();
+	tester.accounts.unlock_account_permanently(address, "".into(), None).unwrap();
 
 	// try again, this time account is unlocked.
 	assert_eq!(
@@ -286,7 +286,7 @@ fn should_sign_if_account_is_unlocked() {
 	let tester = eth_signing(true);
 	let data = vec![5u8];
 	let acc = tester.accounts.insert_account(Secret::from([69u8; 32]), &"test".into()).unwrap();
-	tester.accounts.unlock_account_permanently(acc, "test".into()).unwrap();
+	tester.accounts.unlock_account_permanently(acc, "test".into(), None).unwrap();
 
 	// when
 	let request = r#"{
@@ -428,7 +428,7 @@ fn should_dispatch_transaction_if_account_is_unlock() {
 	// given
 	let tester = eth_signing(true);
 	let acc = tester.accounts.new_account(&"test".into()).unwrap();
-	tester.accounts.unlock_account_permanently(acc, "test".into()).unwrap();
+	tester.accounts.unlock_account_permanently(acc, "test".into(), None).unwrap();
 
 	let t = Transaction {
 		nonce: U256::zero(),
@@ -467,7 +467,7 @@ fn should_decrypt_message_if_account_is_unlocked() {
 	let parity = parity::Dependencies::new();
 	tester.io.extend_with(parity.client(None).to_delegate());
 	let (address, public) = tester.accounts.new_account_and_public(&"test".into()).unwrap();
-	tester.accounts.unlock_account_permanently(address, "test".into()).unwrap();
+	tester.accounts.unlock_account_permanently(address, "test".into(), None).unwrap();
 
 	// First encrypt message
 	let request = format!("{}0x{:x}{}",
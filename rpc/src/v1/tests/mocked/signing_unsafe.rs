@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -24,12 +25,13 @@ use parity_runtime::Runtime;
 use parking_lot::Mutex;
 use rlp;
 use rustc_hex::ToHex;
-use types::transaction::{Transaction, Action};
+use types::transaction::{Transaction, Action, SignedTransaction};
 
 use jsonrpc_core::IoHandler;
+use jsonrpc_core::futures::Future;
 use v1::{EthClientOptions, EthSigning, SigningUnsafeClient};
 use v1::helpers::nonce;
-use v1::helpers::dispatch::{self, FullDispatcher};
+use v1::helpers::dispatch::{self, FullDispatcher, NonceGuard, PostSign};
 use v1::tests::helpers::TestMinerService;
 use v1::metadata::Metadata;
 
@@ -60,8 +62,38 @@ impl Default for EthTester {
 	}
 }
 
+#[derive(Debug, Default)]
+struct TestNonceGuard(Mutex<HashMap<Address, U256>>);
+
+impl NonceGuard for TestNonceGuard {
+	fn nonce_floor(&self, sender: &Address) -> Option<U256> {
+		self.0.lock().get(sender).map(|nonce| nonce + 1)
+	}
+
+	fn note_signed(&self, sender: Address, nonce: U256) -> Option<U256> {
+		let mut nonces = self.0.lock();
+		let previous = nonces.get(&sender).cloned();
+		nonces.insert(sender, previous.map_or(nonce, |highest| ::std::cmp::max(highest, nonce)));
+		previous
+	}
+
+	fn rollback_signed(&self, sender: Address, nonce: U256, previous: Option<U256>) {
+		let mut nonces = self.0.lock();
+		if nonces.get(&sender) == Some(&nonce) {
+			match previous {
+				Some(previous) => nonces.insert(sender, previous),
+				None => nonces.remove(&sender),
+			};
+		}
+	}
+}
+
 impl EthTester {
 	pub fn new_with_options(options: EthClientOptions) -> Self {
+		Self::new_with_nonce_guard(options, None)
+	}
+
+	pub fn new_with_nonce_guard(options: EthClientOptions, nonce_guard: Option<Arc<dyn NonceGuard>>) -> Self {
 		let runtime = Runtime::with_thread_count(1);
 		let client = blockchain_client();
 		let accounts_provider = accounts_provider();
@@ -70,7 +102,10 @@ impl EthTester {
 		let gas_price_percentile = options.gas_price_percentile;
 		let reservations = Arc::new(Mutex::new(nonce::Reservations::new(runtime.executor())));
 
-		let dispatcher = FullDispatcher::new(client.clone(), miner.clone(), reservations, gas_price_percentile);
+		let mut dispatcher = FullDispatcher::new(client.clone(), miner.clone(), reservations, gas_price_percentile);
+		if let Some(nonce_guard) = nonce_guard {
+			dispatcher = dispatcher.with_nonce_guard(nonce_guard);
+		}
 		let sign = SigningUnsafeClient::new(&ap, dispatcher).to_delegate();
 		let mut io: IoHandler<Metadata> = IoHandler::default();
 		io.extend_with(sign);
@@ -89,7 +124,7 @@ impl EthTester {
 fn rpc_eth_send_transaction() {
 	let tester = EthTester::default();
 	let address = tester.accounts_provider.new_account(&"".into()).unwrap();
-	tester.accounts_provider.unlock_account_permanently(address, "".into()).unwrap();
+	tester.accounts_provider.unlock_account_permanently(address, "".into(), None).unwrap();
 	let request = r#"{
 		"jsonrpc": "2.0",
 		"method": "eth_sendTransaction",
@@ -136,11 +171,78 @@ fn rpc_eth_send_transaction() {
 	assert_eq!(tester.io.handle_request_sync(&request), Some(response));
 }
 
+#[test]
+fn rpc_eth_send_transaction_never_reuses_signed_nonce() {
+	let tester = EthTester::new_with_nonce_guard(Default::default(), Some(Arc::new(TestNonceGuard::default())));
+	let address = tester.accounts_provider.new_account(&"".into()).unwrap();
+	tester.accounts_provider.unlock_account_permanently(address, "".into(), None).unwrap();
+	let request = |nonce: &str| r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_sendTransaction",
+		"params": [{
+			"from": ""#.to_owned() + format!("0x{:x}", address).as_ref() + r#"",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "0x76c0",
+			"gasPrice": "0x9184e72a000",
+			"value": "0x9184e72a""# + nonce + r#"
+		}],
+		"id": 1
+	}"#;
+	let signed = |nonce: u64| {
+		let t = Transaction {
+			nonce: nonce.into(),
+			gas_price: U256::from(0x9184e72a000u64),
+			gas: U256::from(0x76c0),
+			action: Action::Call(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap()),
+			value: U256::from(0x9184e72au64),
+			data: vec![]
+		};
+		let signature = tester.accounts_provider.sign(address, None, t.hash(None)).unwrap();
+		t.with_signature(signature, None)
+	};
+	let signed_hash = |nonce: u64| signed(nonce).hash();
+
+	let response = |nonce: u64| r#"{"jsonrpc":"2.0","result":""#.to_owned() + format!("0x{:x}", signed_hash(nonce)).as_ref() + r#"","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request("")), Some(response(0)));
+
+	// the state nonce did not move, as if the node had been rolled back.
+	assert_eq!(tester.io.handle_request_sync(&request("")), Some(response(1)));
+
+	let error = r#"{"jsonrpc":"2.0","error":{"code":-32010,"message":"Nonce 1 has already been signed by this node, the lowest nonce which can be signed is 2."},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request(r#", "nonce": "0x1""#)), Some(error.into()));
+
+	// a transaction which is still queued can be replaced.
+	let queued = SignedTransaction::new(signed(1)).unwrap();
+	tester.miner.pending_transactions.lock().insert(queued.hash(), queued);
+	assert_eq!(tester.io.handle_request_sync(&request(r#", "nonce": "0x1""#)), Some(response(1)));
+}
+
+#[test]
+fn failed_send_rolls_signed_nonce_back() {
+	let guard = Arc::new(TestNonceGuard::default());
+	let sender = Address::from_low_u64_be(1);
+	let send = |nonce: u64, result: Result<(), jsonrpc_core::Error>| {
+		let signed = Transaction { nonce: nonce.into(), ..Default::default() }.fake_sign(sender);
+		dispatch::NoteSignedNonce::new(Some(guard.clone() as Arc<dyn NonceGuard>), move |_: dispatch::WithToken<SignedTransaction>| result)
+			.execute(dispatch::WithToken::No(signed))
+			.wait()
+	};
+
+	send(0, Ok(())).unwrap();
+	assert_eq!(guard.nonce_floor(&sender), Some(1.into()));
+
+	send(1, Err(jsonrpc_core::Error::internal_error())).unwrap_err();
+	assert_eq!(guard.nonce_floor(&sender), Some(1.into()));
+
+	send(1, Ok(())).unwrap();
+	assert_eq!(guard.nonce_floor(&sender), Some(2.into()));
+}
+
 #[test]
 fn rpc_eth_sign_transaction() {
 	let tester = EthTester::default();
 	let address = tester.accounts_provider.new_account(&"".into()).unwrap();
-	tester.accounts_provider.unlock_account_permanently(address, "".into()).unwrap();
+	tester.accounts_provider.unlock_account_permanently(address, "".into(), None).unwrap();
 	let request = r#"{
 		"jsonrpc": "2.0",
 		"method": "eth_signTransaction",
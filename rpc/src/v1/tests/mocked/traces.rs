@@ -18,7 +18,7 @@ use std::sync::Arc;
 
 use machine::executed::Executed;
 use trace::trace::{Action, Res, Call};
-use trace::LocalizedTrace;
+use trace::{LocalizedTrace, StateAccess};
 use ethcore::test_helpers::TestBlockChainClient;
 use ethereum_types::{Address, H256};
 
@@ -28,6 +28,7 @@ use trace::trace::CallType;
 use jsonrpc_core::IoHandler;
 use v1::tests::helpers::{TestMinerService};
 use v1::{Metadata, Traces, TracesClient};
+use v1::types::DEFAULT_PAGE_SIZE;
 
 struct Tester {
 	client: Arc<TestBlockChainClient>,
@@ -68,7 +69,7 @@ fn io() -> Tester {
 		state_diff: None,
 	}));
 	let miner = Arc::new(TestMinerService::default());
-	let traces = TracesClient::new(&client);
+	let traces = TracesClient::new(&client, DEFAULT_PAGE_SIZE);
 	let mut io = IoHandler::default();
 	io.extend_with(traces.to_delegate());
 
@@ -100,6 +101,34 @@ fn rpc_trace_filter_missing_trace() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_trace_filter_page() {
+	let tester = io();
+	let mut traces = tester.client.traces.read().clone().unwrap();
+	let mut second = traces[0].clone();
+	second.transaction_number = Some(1);
+	traces.push(second);
+	*tester.client.traces.write() = Some(traces);
+	tester.client.numbers.write().insert(10, H256::from_low_u64_be(10));
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_filterPage","params": [{"fromBlock":"0xa","toBlock":"0xa","count":1}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"items":[{"action":{"callType":"call","from":"0x000000000000000000000000000000000000000f","gas":"0x100","input":"0x010203","to":"0x0000000000000000000000000000000000000010","value":"0x1"},"blockHash":"0x000000000000000000000000000000000000000000000000000000000000000a","blockNumber":10,"result":null,"subtraces":0,"traceAddress":[0],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000005","transactionPosition":0,"type":"call"}],"cursor":"0x000000000000000a000000000000000000000000000000000000000000000000000000000000000a00000000000000000000000000000000"},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_filterPage","params": [{"fromBlock":"0xa","toBlock":"0xa","count":1,"cursor":"0x000000000000000a000000000000000000000000000000000000000000000000000000000000000a00000000000000000000000000000000"}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"items":[{"action":{"callType":"call","from":"0x000000000000000000000000000000000000000f","gas":"0x100","input":"0x010203","to":"0x0000000000000000000000000000000000000010","value":"0x1"},"blockHash":"0x000000000000000000000000000000000000000000000000000000000000000a","blockNumber":10,"result":null,"subtraces":0,"traceAddress":[0],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000005","transactionPosition":1,"type":"call"}],"cursor":null},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	// the block of the cursor was replaced by a reorganization
+	let request = r#"{"jsonrpc":"2.0","method":"trace_filterPage","params": [{"fromBlock":"0xa","toBlock":"0xa","cursor":"0x000000000000000a000000000000000000000000000000000000000000000000000000000000000b00000000000000000000000000000000"}],"id":1}"#;
+	assert!(tester.io.handle_request_sync(request).unwrap().contains("no longer in the canonical chain"));
+
+	// plain queries don't page, so they must not silently ignore a cursor
+	let request = r#"{"jsonrpc":"2.0","method":"trace_filter","params": [{"fromBlock":"0xa","toBlock":"0xa","cursor":"0x000000000000000a000000000000000000000000000000000000000000000000000000000000000a00000000000000000000000000000000"}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: cursor","data":"\"cursor is only supported by trace_filterPage\""},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_trace_block() {
 	let tester = io();
@@ -110,6 +139,19 @@ fn rpc_trace_block() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_trace_block_paged() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_block","params": ["0x10", 1, 10],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_block","params": ["0x10", 0, 1],"id":1}"#;
+	let response = tester.io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""traceAddress":[0]"#));
+}
+
 #[test]
 fn rpc_trace_block_missing_traces() {
 	let tester = io();
@@ -244,3 +286,18 @@ fn rpc_trace_replay_block_transactions() {
 
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_trace_replay_block_state_access() {
+	let tester = io();
+	*tester.client.state_accesses.write() = vec![(H256::from_low_u64_be(5), vec![
+		StateAccess::Account(Address::from_low_u64_be(0xf)),
+		StateAccess::StorageRead { address: Address::from_low_u64_be(0x10), key: H256::from_low_u64_be(1), value: H256::from_low_u64_be(2) },
+		StateAccess::StorageWrite { address: Address::from_low_u64_be(0x10), key: H256::from_low_u64_be(1), value: H256::from_low_u64_be(3) },
+	])];
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_replayBlockStateAccess","params":["0x10"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"accesses":[{"address":"0x000000000000000000000000000000000000000f","type":"account"},{"address":"0x0000000000000000000000000000000000000010","key":"0x0000000000000000000000000000000000000000000000000000000000000001","type":"storageRead","value":"0x0000000000000000000000000000000000000000000000000000000000000002"},{"address":"0x0000000000000000000000000000000000000010","key":"0x0000000000000000000000000000000000000000000000000000000000000001","type":"storageWrite","value":"0x0000000000000000000000000000000000000000000000000000000000000003"}],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000005"}],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -19,7 +19,8 @@
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
 
-use v1::types::RichBlock;
+use ethereum_types::{H160, H256};
+use v1::types::{BlockNumber, RichBlock, StorageRange};
 
 /// Debug RPC interface.
 #[rpc(server)]
@@ -27,4 +28,10 @@ pub trait Debug {
 	/// Returns recently seen bad blocks.
 	#[rpc(name = "debug_getBadBlocks")]
 	fn bad_blocks(&self) -> Result<Vec<RichBlock>>;
+
+	/// Returns at most `limit` storage entries of given contract, starting with given key.
+	/// Entries are ordered by the hash of their keys; use `nextKey` of the result to get the next page.
+	/// Returns `null` if fat DB is not enabled or the account does not exist.
+	#[rpc(name = "debug_storageRangeAt")]
+	fn storage_range_at(&self, _: H160, _: Option<H256>, _: u64, _: Option<BlockNumber>) -> Result<Option<StorageRange>>;
 }
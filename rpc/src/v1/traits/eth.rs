@@ -20,7 +20,7 @@ use jsonrpc_derive::rpc;
 use ethereum_types::{H64, H160, H256, U64, U256};
 
 use v1::types::{RichBlock, BlockNumber, Bytes, CallRequest, Filter, FilterChanges, Index, EthAccount};
-use v1::types::{Log, Receipt, SyncStatus, Transaction, Work};
+use v1::types::{Log, Page, Receipt, SyncStatus, Transaction, Work};
 
 /// Eth rpc interface.
 #[rpc(server)]
@@ -58,6 +58,10 @@ pub trait Eth {
 	#[rpc(name = "eth_gasPrice")]
 	fn gas_price(&self) -> BoxFuture<U256>;
 
+	/// Returns suggested tip per unit of gas paid to the block author.
+	#[rpc(name = "eth_maxPriorityFeePerGas")]
+	fn max_priority_fee_per_gas(&self) -> BoxFuture<U256>;
+
 	/// Returns accounts list.
 	#[rpc(name = "eth_accounts")]
 	fn accounts(&self) -> Result<Vec<H160>>;
@@ -174,6 +178,10 @@ pub trait Eth {
 	#[rpc(name = "eth_getLogs")]
 	fn logs(&self, _: Filter) -> BoxFuture<Vec<Log>>;
 
+	/// Returns a page of mined logs matching given filter object, continued from its cursor.
+	#[rpc(name = "eth_getLogsPage")]
+	fn logs_page(&self, _: Filter) -> Result<Page<Log>>;
+
 	/// Returns the hash of the current block, the seedHash, and the boundary condition to be met.
 	#[rpc(name = "eth_getWork")]
 	fn work(&self, _: Option<u64>) -> Result<Work>;
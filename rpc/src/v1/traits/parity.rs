@@ -18,17 +18,18 @@
 
 use std::collections::BTreeMap;
 
+use eip_712::EIP712;
 use ethereum_types::{H64, H160, H256, H512, U64, U256};
 use ethcore::miner::FilterOptions;
 use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_derive::rpc;
 use v1::types::{
-	Bytes, CallRequest,
-	Peers, Transaction, RpcSettings, Histogram, RecoveredAccount,
-	TransactionStats, LocalTransactionStatus,
-	BlockNumber, ConsensusCapability, VersionInfo,
+	Bytes, CallRequest, CallBundleResult,
+	Peers, ProtocolThroughput, Transaction, RpcSettings, RpcMethodStats, Histogram, RecoveredAccount, FeeSuggestions,
+	TransactionStats, LocalTransactionStatus, TransactionImportSimulation, TransactionQueueStatus,
+	BackupStatus, BadBlock, BlockNumber, ConsensusCapability, ConsensusFault, VersionInfo,
 	OperationsInfo, ChainStatus, Log, Filter,
-	RichHeader, Receipt,
+	RichHeader, Receipt, SubmittedWork, AccountHistoryEntry, StratumWorker,
 };
 
 /// Parity-specific rpc interface.
@@ -41,6 +42,10 @@ pub trait Parity {
 	#[rpc(name = "parity_transactionsLimit")]
 	fn transactions_limit(&self) -> Result<usize>;
 
+	/// Returns current occupancy and quotas of the transaction queue per transaction origin.
+	#[rpc(name = "parity_transactionQueueStatus")]
+	fn transaction_queue_status(&self) -> Result<TransactionQueueStatus>;
+
 	/// Returns mining extra data.
 	#[rpc(name = "parity_extraData")]
 	fn extra_data(&self) -> Result<Bytes>;
@@ -57,6 +62,11 @@ pub trait Parity {
 	#[rpc(name = "parity_minGasPrice")]
 	fn min_gas_price(&self) -> Result<U256>;
 
+	/// Returns the minimal gas price floor following the pressure on the transaction queue,
+	/// `null` if the floor is not enabled.
+	#[rpc(name = "parity_gasPriceFloor")]
+	fn gas_price_floor(&self) -> Result<Option<U256>>;
+
 	/// Returns latest logs
 	#[rpc(name = "parity_devLogs")]
 	fn dev_logs(&self) -> Result<Vec<String>>;
@@ -77,10 +87,19 @@ pub trait Parity {
 	#[rpc(name = "parity_netPort")]
 	fn net_port(&self) -> Result<u16>;
 
+	/// Returns bytes sent and received by devp2p subprotocol and message type,
+	/// in total and within sliding windows.
+	#[rpc(name = "parity_netThroughput")]
+	fn net_throughput(&self) -> Result<BTreeMap<String, ProtocolThroughput>>;
+
 	/// Returns rpc settings
 	#[rpc(name = "parity_rpcSettings")]
 	fn rpc_settings(&self) -> Result<RpcSettings>;
 
+	/// Returns calls count, error rate and average latency of every method called so far.
+	#[rpc(name = "parity_rpcStats")]
+	fn rpc_stats(&self) -> Result<BTreeMap<String, RpcMethodStats>>;
+
 	/// Returns node name
 	#[rpc(name = "parity_nodeName")]
 	fn node_name(&self) -> Result<String>;
@@ -93,6 +112,10 @@ pub trait Parity {
 	#[rpc(name = "parity_gasPriceHistogram")]
 	fn gas_price_histogram(&self) -> BoxFuture<Histogram>;
 
+	/// Returns fee suggestions for slow, standard and fast inclusion, based on the tips paid in latest blocks.
+	#[rpc(name = "parity_feeSuggestions")]
+	fn fee_suggestions(&self) -> Result<FeeSuggestions>;
+
 	/// Returns number of unsigned transactions waiting in the signer queue (if signer enabled)
 	/// Returns error when signer is disabled
 	#[rpc(name = "parity_unsignedTransactionsCount")]
@@ -125,14 +148,52 @@ pub trait Parity {
 		_: Option<BlockNumber>,
 	) -> Result<Option<Vec<H256>>>;
 
+	/// Keeps the state of the given block (default: latest) from being pruned, so that it can be
+	/// enumerated page by page with `parity_listAccounts` and `parity_listStorageKeys`.
+	/// Returns the block hash, which should be used as block parameter of the listing calls.
+	/// The state is released with `parity_unpinState` or after 10 minutes without being listed.
+	#[rpc(name = "parity_pinState")]
+	fn pin_state(&self, _: Option<BlockNumber>) -> Result<H256>;
+
+	/// Releases state pinned with `parity_pinState`. Returns `false` if it wasn't pinned.
+	#[rpc(name = "parity_unpinState")]
+	fn unpin_state(&self, _: H256) -> Result<bool>;
+
+	/// Returns consensus faults of validators (invalid seals, equivocations and skipped steps) observed
+	/// by the node within the configured window, oldest first. Only reported by PoA engines.
+	#[rpc(name = "parity_consensusFaults")]
+	fn consensus_faults(&self) -> Result<Vec<ConsensusFault>>;
+
+	/// Returns the status of the scheduled database backups: whether they are enabled, whether one
+	/// is running and the outcome of the most recent one.
+	#[rpc(name = "parity_backupStatus")]
+	fn backup_status(&self) -> Result<BackupStatus>;
+
+	/// Returns blocks recently rejected by the node, including before it was restarted, together with
+	/// the reason they were rejected for (bad seal, invalid root, banned or too far in the future),
+	/// oldest first.
+	#[rpc(name = "parity_badBlocks")]
+	fn bad_blocks(&self) -> Result<Vec<BadBlock>>;
+
+	/// Returns the workers connected to the stratum server with their accepted and rejected share
+	/// counts, the time of their last share and their hashrate estimated from the accepted shares.
+	#[rpc(name = "parity_stratumWorkers")]
+	fn stratum_workers(&self) -> Result<Vec<StratumWorker>>;
+
+	/// Returns the number of submitted solutions which met only the test difficulty set with
+	/// `parity_setWorkTestDifficulty`.
+	#[rpc(name = "parity_workTestShares")]
+	fn work_test_shares(&self) -> Result<U64>;
+
 	/// Encrypt some data with a public key under ECIES.
 	/// First parameter is the 512-byte destination public key, second is the message.
 	#[rpc(name = "parity_encryptMessage")]
 	fn encrypt_message(&self, _: H512, _: Bytes) -> Result<Bytes>;
 
 	/// Returns all pending transactions from transaction queue.
+	/// With the third parameter set to `true` the called method selector and well-known method signature are included.
 	#[rpc(name = "parity_pendingTransactions")]
-	fn pending_transactions(&self, _: Option<usize>, _: Option<FilterOptions>) -> Result<Vec<Transaction>>;
+	fn pending_transactions(&self, _: Option<usize>, _: Option<FilterOptions>, _: Option<bool>) -> Result<Vec<Transaction>>;
 
 	/// Returns all transactions from transaction queue.
 	///
@@ -149,8 +210,9 @@ pub trait Parity {
 	fn future_transactions(&self) -> Result<Vec<Transaction>>;
 
 	/// Returns propagation statistics on transactions pending in the queue.
+	/// If transaction hash is given, only statistics of that transaction are returned.
 	#[rpc(name = "parity_pendingTransactionsStats")]
-	fn pending_transactions_stats(&self) -> Result<BTreeMap<H256, TransactionStats>>;
+	fn pending_transactions_stats(&self, _: Option<H256>) -> Result<BTreeMap<H256, TransactionStats>>;
 
 	/// Returns a list of current and past local transactions with status details.
 	#[rpc(name = "parity_localTransactions")]
@@ -164,6 +226,11 @@ pub trait Parity {
 	#[rpc(name = "parity_nextNonce")]
 	fn next_nonce(&self, _: H160) -> BoxFuture<U256>;
 
+	/// Runs all transaction queue admission checks for given raw transaction without importing it.
+	/// Returns the rejection reason as an error or the predicted position in the pending set.
+	#[rpc(name = "parity_simulateTransactionImport")]
+	fn simulate_transaction_import(&self, _: Bytes) -> Result<TransactionImportSimulation>;
+
 	/// Get the mode. Returns one of: "active", "passive", "dark", "offline".
 	#[rpc(name = "parity_mode")]
 	fn mode(&self) -> Result<String>;
@@ -211,15 +278,30 @@ pub trait Parity {
 	#[rpc(name = "parity_cidV0")]
 	fn ipfs_cid(&self, _: Bytes) -> Result<String>;
 
+	/// Returns balance and nonce of given account at each of given blocks, in the order of the blocks.
+	/// Intended for reconstructing account history on archive nodes.
+	#[rpc(name = "parity_getAccountHistory")]
+	fn account_history(&self, _: H160, _: Vec<BlockNumber>) -> Result<Vec<AccountHistoryEntry>>;
+
 	/// Call contract, returning the output data.
 	#[rpc(name = "parity_call")]
 	fn call(&self, _: Vec<CallRequest>, _: Option<BlockNumber>) -> Result<Vec<Bytes>>;
 
+	/// Executes the calls in order against the same state, each call seeing the effects of the previous ones.
+	/// Returns output, gas used, logs and error of every call. Failed calls don't abort the bundle.
+	#[rpc(name = "parity_callBundle")]
+	fn call_bundle(&self, _: Vec<CallRequest>, _: Option<BlockNumber>) -> Result<Vec<CallBundleResult>>;
+
 	/// Used for submitting a proof-of-work solution (similar to `eth_submitWork`,
 	/// but returns block hash on success, and returns an explicit error message on failure).
 	#[rpc(name = "parity_submitWorkDetail")]
 	fn submit_work_detail(&self, _: H64, _: H256, _: H256) -> Result<H256>;
 
+	/// Used for submitting a proof-of-work solution (similar to `parity_submitWorkDetail`,
+	/// but also reports whether the solution was for stale work).
+	#[rpc(name = "parity_submitWorkStatus")]
+	fn submit_work_status(&self, _: H64, _: H256, _: H256) -> Result<SubmittedWork>;
+
 	/// Returns the status of the node. Used as the health endpoint.
 	///
 	/// The RPC returns successful response if:
@@ -235,6 +317,17 @@ pub trait Parity {
 	#[rpc(name = "parity_verifySignature")]
 	fn verify_signature(&self, _: bool, _: Bytes, _: H256, _: H256, _: U64) -> Result<RecoveredAccount>;
 
+	/// Extracts Address and public key of the signer of EIP-712 typed data using the r, s and v params,
+	/// as well as checks the signature for chain replay protection.
+	#[rpc(name = "parity_recoverTypedDataSigner")]
+	fn recover_typed_data_signer(&self, _: EIP712, _: H256, _: H256, _: U64) -> Result<RecoveredAccount>;
+
+	/// Checks whether the signature of the given hash is valid for the address at given block.
+	/// Contract accounts are asked through EIP-1271 `isValidSignature`, for other accounts the
+	/// signer is recovered from the 65 bytes `r || s || v` signature.
+	#[rpc(name = "parity_isValidSignature")]
+	fn is_valid_signature(&self, _: H160, _: H256, _: Bytes, _: Option<BlockNumber>) -> Result<bool>;
+
 	/// Returns logs matching given filter object.
 	/// Is allowed to skip filling transaction hash for faster query.
 	#[rpc(name = "parity_getLogsNoTransactionHash")]
@@ -23,7 +23,7 @@ use ethereum_types::{H160, H256, H520};
 use ethkey::Password;
 use ethstore::KeyFile;
 use v1::types::{DeriveHash, DeriveHierarchical, ExtAccountInfo};
-use v1::types::AccountInfo;
+use v1::types::{AccountInfo, AccountAuditEntry};
 
 /// Parity-specific read-only accounts rpc interface.
 #[rpc(server)]
@@ -149,4 +149,9 @@ pub trait ParityAccounts {
 	/// Sign raw hash with the key corresponding to address and password.
 	#[rpc(name = "parity_signMessage")]
 	fn sign_message(&self, _: H160, _: Password, _: H256) -> Result<H520>;
+
+	/// Returns most recent account unlock and signing events, optionally only for given account.
+	/// Second parameter limits the number of returned entries (defaults to 100).
+	#[rpc(name = "parity_accountAuditLog")]
+	fn account_audit_log(&self, _: Option<H160>, _: Option<u64>) -> Result<Vec<AccountAuditEntry>>;
 }
@@ -16,7 +16,7 @@
 
 //! Parity-specific rpc interface for operations altering the settings.
 
-use ethereum_types::{H160, H256, U256};
+use ethereum_types::{H160, H256, H512, U256};
 use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_derive::rpc;
 
@@ -28,6 +28,12 @@ pub trait ParitySetAccounts {
 	/// Sets account for signing consensus messages.
 	#[rpc(name = "parity_setEngineSigner")]
 	fn set_engine_signer(&self, _: H160, _: String) -> Result<bool>;
+
+	/// Replaces a queued transaction with a copy paying the given gas price, re-signed by its unlocked sender.
+	/// Without a gas price, it is bumped by the minimum required for the queue to accept the replacement.
+	/// Returns the hash of the new transaction.
+	#[rpc(name = "parity_replaceTransaction")]
+	fn replace_transaction(&self, _: H256, _: Option<U256>) -> Result<H256>;
 }
 
 /// Parity-specific rpc interface for operations altering the settings.
@@ -49,6 +55,11 @@ pub trait ParitySet {
 	#[rpc(name = "parity_setExtraData")]
 	fn set_extra_data(&self, _: Bytes) -> Result<bool>;
 
+	/// Sets a lowered difficulty advertised in mining work, to validate mining rigs against a live node.
+	/// Solutions meeting only this difficulty are counted as test shares. `null` advertises the block difficulty again.
+	#[rpc(name = "parity_setWorkTestDifficulty")]
+	fn set_work_test_difficulty(&self, _: Option<U256>) -> Result<bool>;
+
 	/// Sets new author for mined block.
 	#[rpc(name = "parity_setAuthor")]
 	fn set_author(&self, _: H160) -> Result<bool>;
@@ -125,4 +136,31 @@ pub trait ParitySet {
 	/// Returns `true` when transaction was removed, `false` if it was not found.
 	#[rpc(name = "parity_removeTransaction")]
 	fn remove_transaction(&self, _: H256) -> Result<Option<Transaction>>;
+
+	/// Resumes block import halted on a reorg deeper than `--max-reorg-depth`.
+	/// The reorg is applied if the argument is `true`, otherwise the offending block is marked as bad.
+	/// Returns `false` if block import was not halted.
+	#[rpc(name = "parity_acknowledgeReorg")]
+	fn acknowledge_reorg(&self, _: bool) -> Result<bool>;
+
+	/// Changes the log level of given target at runtime. An empty target changes the default level.
+	/// Level must be one of: "off", "error", "warn", "info", "debug", "trace".
+	#[rpc(name = "parity_setLoggingLevel")]
+	fn set_logging_level(&self, _: String, _: String) -> Result<bool>;
+
+	/// Reserves a nonce of given account for a transaction signed outside of the node.
+	/// Reserved nonces are skipped when the node signs transactions and are not handed out again
+	/// until released or used.
+	#[rpc(name = "parity_reserveNonce")]
+	fn reserve_nonce(&self, _: H160) -> Result<U256>;
+
+	/// Releases a nonce reserved with `parity_reserveNonce`, so that it can be reserved again.
+	/// Returns `false` if the nonce was not reserved.
+	#[rpc(name = "parity_releaseNonce")]
+	fn release_nonce(&self, _: H160, _: U256) -> Result<bool>;
+
+	/// Restricts warp sync to the snapshot with given manifest hash and optionally to a single peer
+	/// given by its node id. Passing no manifest hash lifts the restriction.
+	#[rpc(name = "parity_setWarpPin")]
+	fn set_warp_pin(&self, _: Option<H256>, _: Option<H512>) -> Result<bool>;
 }
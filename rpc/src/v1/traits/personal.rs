@@ -38,8 +38,8 @@ pub trait Personal {
 	fn new_account(&self, _: String) -> Result<H160>;
 
 	/// Unlocks specified account for use (can only be one unlocked account at one moment)
-	#[rpc(name = "personal_unlockAccount")]
-	fn unlock_account(&self, _: H160, _: String, _: Option<U128>) -> Result<bool>;
+	#[rpc(meta, name = "personal_unlockAccount")]
+	fn unlock_account(&self, _: Self::Metadata, _: H160, _: String, _: Option<U128>) -> Result<bool>;
 
 	/// Signs the hash of data with given account signature using the given password to unlock the account during
 	/// the request.
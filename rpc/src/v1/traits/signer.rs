@@ -21,7 +21,10 @@ use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
 use jsonrpc_derive::rpc;
 
-use v1::types::{Bytes, TransactionModification, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken};
+use v1::types::{
+	Bytes, TransactionModification, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken,
+	ConfirmationOutcome,
+};
 
 /// Signer extension for confirmations rpc interface.
 #[rpc(server)]
@@ -37,6 +40,12 @@ pub trait Signer {
 	#[rpc(name = "signer_confirmRequest")]
 	fn confirm_request(&self, _: U256, _: TransactionModification, _: String) -> BoxFuture<ConfirmationResponse>;
 
+	/// Confirm multiple requests with a single password.
+	/// None of the requests is confirmed unless all of them are queued and the password unlocks all their accounts.
+	/// Returns the outcome of every request in the order of the ids.
+	#[rpc(name = "signer_confirmRequests")]
+	fn confirm_requests(&self, _: Vec<U256>, _: String) -> BoxFuture<Vec<ConfirmationOutcome>>;
+
 	/// Confirm specific request with token.
 	#[rpc(name = "signer_confirmRequestWithToken")]
 	fn confirm_request_with_token(
@@ -54,6 +63,18 @@ pub trait Signer {
 	#[rpc(name = "signer_rejectRequest")]
 	fn reject_request(&self, _: U256) -> Result<bool>;
 
+	/// Confirm specific request on behalf of an external approver.
+	/// The MAC is the HMAC-SHA256 of `confirm:<id>:<requestHash>:<session>:<modification>` keyed with the webhook secret,
+	/// where the request hash and session come from the `newRequest` notification and the modification is serialized
+	/// as JSON with all of its fields. Without the optional password the account has to be unlocked.
+	#[rpc(name = "signer_confirmRequestWithMac")]
+	fn confirm_request_with_mac(&self, _: U256, _: TransactionModification, _: Bytes, _: Option<String>) -> BoxFuture<ConfirmationResponse>;
+
+	/// Reject the confirmation request on behalf of an external approver.
+	/// The last parameter is the HMAC-SHA256 of `reject:<id>:<requestHash>:<session>` keyed with the webhook secret.
+	#[rpc(name = "signer_rejectRequestWithMac")]
+	fn reject_request_with_mac(&self, _: U256, _: Bytes) -> Result<bool>;
+
 	/// Generates new authorization token.
 	#[rpc(name = "signer_generateAuthorizationToken")]
 	fn generate_token(&self) -> Result<String>;
@@ -20,7 +20,7 @@ use ethereum_types::H256;
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
 use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults,
-	TraceResultsWithTransactionHash, TraceOptions};
+	TraceResultsWithTransactionHash, TraceOptions, Page, StateAccessLog};
 
 /// Traces specific rpc interface.
 #[rpc(server)]
@@ -32,6 +32,10 @@ pub trait Traces {
 	#[rpc(name = "trace_filter")]
 	fn filter(&self, _: TraceFilter) -> Result<Option<Vec<LocalizedTrace>>>;
 
+	/// Returns a page of traces matching given filter, continued from its cursor.
+	#[rpc(name = "trace_filterPage")]
+	fn filter_page(&self, _: TraceFilter) -> Result<Option<Page<LocalizedTrace>>>;
+
 	/// Returns transaction trace at given index.
 	#[rpc(name = "trace_get")]
 	fn trace(&self, _: H256, _: Vec<Index>) -> Result<Option<LocalizedTrace>>;
@@ -40,9 +44,11 @@ pub trait Traces {
 	#[rpc(name = "trace_transaction")]
 	fn transaction_traces(&self, _: H256) -> Result<Option<Vec<LocalizedTrace>>>;
 
-	/// Returns all traces produced at given block.
+	/// Returns traces produced at given block.
+	///
+	/// Optional `offset` and `limit` allow fetching traces of huge blocks in pages.
 	#[rpc(name = "trace_block")]
-	fn block_traces(&self, _: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>>;
+	fn block_traces(&self, _: BlockNumber, _: Option<usize>, _: Option<usize>) -> Result<Option<Vec<LocalizedTrace>>>;
 
 	/// Executes the given call and returns a number of possible traces for it.
 	#[rpc(name = "trace_call")]
@@ -67,4 +73,9 @@ pub trait Traces {
 		_: BlockNumber,
 		_: TraceOptions
 	) -> Result<Vec<TraceResultsWithTransactionHash>>;
+
+	/// Executes all the transactions at the given block and returns the accounts and storage slots
+	/// each transaction read or wrote, in order of access.
+	#[rpc(name = "trace_replayBlockStateAccess")]
+	fn replay_block_state_access(&self, _: BlockNumber) -> Result<Vec<StateAccessLog>>;
 }
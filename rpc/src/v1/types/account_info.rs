@@ -16,7 +16,7 @@
 
 //! Return types for RPC calls
 
-use ethereum_types::{Public, Address, H160, H256, U256};
+use ethereum_types::{Public, Address, H160, H256, U64, U256};
 use v1::types::Bytes;
 
 /// Account information.
@@ -60,6 +60,34 @@ pub struct ExtAccountInfo {
 	pub uuid: Option<String>,
 }
 
+/// Account unlock audit log entry (used by `parity_accountAuditLog`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AccountAuditEntry {
+	/// UNIX timestamp (in seconds) of the event
+	pub timestamp: u64,
+	/// Account address
+	pub account: H160,
+	/// Event name (e.g. `unlockTimed`, `sign` or `relock`)
+	pub event: String,
+	/// Unlock duration in seconds (only for timed unlocks)
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub duration: Option<u64>,
+}
+
+/// Account state at given block (used by `parity_getAccountHistory`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountHistoryEntry {
+	/// Block number
+	pub block_number: U64,
+	/// Block hash
+	pub block_hash: H256,
+	/// Account balance at the end of the block
+	pub balance: U256,
+	/// Account nonce at the end of the block
+	pub nonce: U256,
+}
+
 /// account derived from a signature
 /// as well as information that tells if it is valid for
 /// the current chain
@@ -0,0 +1,92 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Database backup status return type.
+
+use ethereum_types::{H256, U64};
+use types::backup;
+
+/// A database backup which was successfully taken.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+	/// Number of the best block at the time of the backup
+	pub number: U64,
+	/// Hash of the best block at the time of the backup
+	pub hash: H256,
+	/// UNIX timestamp at which the backup finished
+	pub timestamp: U64,
+	/// Directory the backup was written to
+	pub path: String,
+}
+
+/// Status of the scheduled database backups.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupStatus {
+	/// Whether backups are enabled
+	pub enabled: bool,
+	/// Whether a backup is being taken at the moment
+	pub in_progress: bool,
+	/// The most recent successful backup
+	pub last_backup: Option<BackupInfo>,
+	/// Error of the most recent backup attempt, if it failed
+	pub last_error: Option<String>,
+}
+
+impl From<backup::BackupStatus> for BackupStatus {
+	fn from(s: backup::BackupStatus) -> Self {
+		BackupStatus {
+			enabled: s.enabled,
+			in_progress: s.in_progress,
+			last_backup: s.last_backup.map(|b| BackupInfo {
+				number: b.number.into(),
+				hash: b.hash,
+				timestamp: b.timestamp.into(),
+				path: b.path.to_string_lossy().into_owned(),
+			}),
+			last_error: s.last_error,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethereum_types::H256;
+	use types::backup;
+	use super::BackupStatus;
+
+	#[test]
+	fn backup_status_serialization() {
+		let status: BackupStatus = backup::BackupStatus {
+			enabled: true,
+			in_progress: false,
+			last_backup: Some(backup::BackupInfo {
+				number: 10_000,
+				hash: H256::from_low_u64_be(1),
+				timestamp: 1_500_000_000,
+				path: "/backups/backup-10000".into(),
+			}),
+			last_error: None,
+		}.into();
+
+		assert_eq!(
+			serde_json::to_string(&status).unwrap(),
+			r#"{"enabled":true,"inProgress":false,"lastBackup":{"number":"0x2710","hash":"0x0000000000000000000000000000000000000000000000000000000000000001","timestamp":"0x59682f00","path":"/backups/backup-10000"},"lastError":null}"#
+		);
+	}
+}
@@ -0,0 +1,107 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bad block return type.
+
+use ethereum_types::{H160, H256, U64};
+use types::bad_block::{self, BadBlockReason as Reason};
+
+/// Reason a block was rejected for.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BadBlockReason {
+	/// Invalid proof of work or engine seal.
+	BadSeal,
+	/// Invalid state, transactions, receipts or uncles root.
+	InvalidRoot,
+	/// Block or its ancestor is known to be bad, or it was rejected by the operator.
+	Banned,
+	/// Timestamp too far in the future.
+	TooFarInFuture,
+	/// Any other verification failure.
+	Other,
+}
+
+impl From<Reason> for BadBlockReason {
+	fn from(reason: Reason) -> Self {
+		match reason {
+			Reason::BadSeal => BadBlockReason::BadSeal,
+			Reason::InvalidRoot => BadBlockReason::InvalidRoot,
+			Reason::Banned => BadBlockReason::Banned,
+			Reason::TooFarInFuture => BadBlockReason::TooFarInFuture,
+			Reason::Other => BadBlockReason::Other,
+		}
+	}
+}
+
+/// Block recently rejected by the node.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BadBlock {
+	/// Hash of the block
+	pub hash: H256,
+	/// Number of the block
+	pub number: U64,
+	/// Hash of the parent block
+	pub parent_hash: H256,
+	/// Author of the block
+	pub author: H160,
+	/// Reason the block was rejected for
+	pub reason: BadBlockReason,
+	/// Detailed error message
+	pub message: String,
+	/// UNIX timestamp at which the block was rejected
+	pub rejected_at: U64,
+}
+
+impl From<bad_block::BadBlock> for BadBlock {
+	fn from(b: bad_block::BadBlock) -> Self {
+		BadBlock {
+			hash: b.block.header.hash(),
+			number: b.block.header.number().into(),
+			parent_hash: *b.block.header.parent_hash(),
+			author: *b.block.header.author(),
+			reason: b.reason.into(),
+			message: b.message,
+			rejected_at: b.rejected_at.into(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethereum_types::{H160, H256};
+	use super::{BadBlock, BadBlockReason};
+
+	#[test]
+	fn bad_block_serialization() {
+		let bad = BadBlock {
+			hash: H256::from_low_u64_be(2),
+			number: 5.into(),
+			parent_hash: H256::from_low_u64_be(1),
+			author: H160::from_low_u64_be(3),
+			reason: BadBlockReason::TooFarInFuture,
+			message: "Future timestamp".into(),
+			rejected_at: 1_500_000_000.into(),
+		};
+
+		assert_eq!(
+			serde_json::to_string(&bad).unwrap(),
+			r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000002","number":"0x5","parentHash":"0x0000000000000000000000000000000000000000000000000000000000000001","author":"0x0000000000000000000000000000000000000003","reason":"tooFarInFuture","message":"Future timestamp","rejectedAt":"0x59682f00"}"#
+		);
+	}
+}
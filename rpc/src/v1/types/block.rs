@@ -77,6 +77,9 @@ pub struct Block {
 	pub timestamp: U256,
 	/// Difficulty
 	pub difficulty: U256,
+	/// Base fee per gas, only present after EIP-1559 transition
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub base_fee_per_gas: Option<U256>,
 	/// Total difficulty
 	pub total_difficulty: Option<U256>,
 	/// Seal fields
@@ -124,6 +127,9 @@ pub struct Header {
 	pub timestamp: U256,
 	/// Difficulty
 	pub difficulty: U256,
+	/// Base fee per gas, only present after EIP-1559 transition
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub base_fee_per_gas: Option<U256>,
 	/// Seal fields
 	pub seal_fields: Vec<Bytes>,
 	/// Size in bytes
@@ -154,6 +160,7 @@ impl<'a> From<&'a EthHeader> for Header {
 			logs_bloom: h.log_bloom(),
 			timestamp: h.timestamp().into(),
 			difficulty: h.difficulty(),
+			base_fee_per_gas: h.base_fee(),
 			extra_data: h.extra_data().into(),
 			seal_fields: h.view().decode_seal()
 				.expect("Client/Miner returns only valid headers. We only serialize headers from Client/Miner; qed")
@@ -239,6 +246,7 @@ mod tests {
 			logs_bloom: Some(H2048::default()),
 			timestamp: U256::default(),
 			difficulty: U256::default(),
+			base_fee_per_gas: None,
 			total_difficulty: Some(U256::default()),
 			seal_fields: vec![Bytes::default(), Bytes::default()],
 			uncles: vec![],
@@ -277,6 +285,7 @@ mod tests {
 			logs_bloom: Some(H2048::default()),
 			timestamp: U256::default(),
 			difficulty: U256::default(),
+			base_fee_per_gas: None,
 			total_difficulty: Some(U256::default()),
 			seal_fields: vec![Bytes::default(), Bytes::default()],
 			uncles: vec![],
@@ -315,6 +324,7 @@ mod tests {
 			logs_bloom: H2048::default(),
 			timestamp: U256::default(),
 			difficulty: U256::default(),
+			base_fee_per_gas: None,
 			seal_fields: vec![Bytes::default(), Bytes::default()],
 			size: Some(69.into()),
 		};
@@ -331,4 +341,31 @@ mod tests {
 		assert_eq!(serialized_header, r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000000","parentHash":"0x0000000000000000000000000000000000000000000000000000000000000000","sha3Uncles":"0x0000000000000000000000000000000000000000000000000000000000000000","author":"0x0000000000000000000000000000000000000000","miner":"0x0000000000000000000000000000000000000000","stateRoot":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionsRoot":"0x0000000000000000000000000000000000000000000000000000000000000000","receiptsRoot":"0x0000000000000000000000000000000000000000000000000000000000000000","number":"0x0","gasUsed":"0x0","gasLimit":"0x0","extraData":"0x","logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","timestamp":"0x0","difficulty":"0x0","sealFields":["0x","0x"],"size":"0x45"}"#);
 		assert_eq!(serialized_rich_header, r#"{"author":"0x0000000000000000000000000000000000000000","difficulty":"0x0","extraData":"0x","gasLimit":"0x0","gasUsed":"0x0","hash":"0x0000000000000000000000000000000000000000000000000000000000000000","logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","miner":"0x0000000000000000000000000000000000000000","mixHash":"0x0000000000000000000000000000000000000000000000000000000000000000","nonce":"0x0000000000000000","number":"0x0","parentHash":"0x0000000000000000000000000000000000000000000000000000000000000000","receiptsRoot":"0x0000000000000000000000000000000000000000000000000000000000000000","sealFields":["0x","0x"],"sha3Uncles":"0x0000000000000000000000000000000000000000000000000000000000000000","size":"0x45","stateRoot":"0x0000000000000000000000000000000000000000000000000000000000000000","timestamp":"0x0","transactionsRoot":"0x0000000000000000000000000000000000000000000000000000000000000000"}"#);
 	}
+
+	#[test]
+	fn test_serialize_header_with_base_fee() {
+		let header = Header {
+			hash: None,
+			parent_hash: H256::zero(),
+			uncles_hash: H256::zero(),
+			author: H160::default(),
+			miner: H160::default(),
+			state_root: H256::zero(),
+			transactions_root: H256::zero(),
+			receipts_root: H256::zero(),
+			number: None,
+			gas_used: U256::default(),
+			gas_limit: U256::default(),
+			extra_data: Bytes::default(),
+			logs_bloom: H2048::default(),
+			timestamp: U256::default(),
+			difficulty: U256::default(),
+			base_fee_per_gas: Some(1_000_000_000.into()),
+			seal_fields: vec![],
+			size: None,
+		};
+		let serialized_header = serde_json::to_string(&header).unwrap();
+
+		assert!(serialized_header.contains(r#""difficulty":"0x0","baseFeePerGas":"0x3b9aca00","sealFields":[]"#));
+	}
 }
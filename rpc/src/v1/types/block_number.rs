@@ -17,8 +17,10 @@
 use std::fmt;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::{Error, Visitor, MapAccess};
+use jsonrpc_core::Error as RpcError;
 use types::ids::BlockId;
 use ethereum_types::H256;
+use v1::helpers::errors;
 
 /// Represents rpc api block number param.
 #[derive(Debug, PartialEq, Clone, Hash, Eq)]
@@ -38,6 +40,10 @@ pub enum BlockNumber {
 	Earliest,
 	/// Pending block (being mined)
 	Pending,
+	/// State with the given root, only accepted by methods reading state (archive nodes only)
+	StateRoot(H256),
+	/// Latest block with the given number of confirmations on top of it
+	Confirmed(u64),
 }
 
 impl Default for BlockNumber {
@@ -60,6 +66,19 @@ impl BlockNumber {
 			_ => None,
 		}
 	}
+
+	/// Resolve `Latest` and `Confirmed` to a block number relative to the chain head.
+	///
+	/// `Latest` is kept `confirmations` blocks behind `best_block`, an explicit
+	/// `Confirmed` depth takes precedence. Other variants are returned unchanged.
+	pub fn with_confirmations(self, best_block: u64, confirmations: u64) -> BlockNumber {
+		match self {
+			BlockNumber::Latest if confirmations == 0 => BlockNumber::Latest,
+			BlockNumber::Latest => BlockNumber::Num(best_block.saturating_sub(confirmations)),
+			BlockNumber::Confirmed(depth) => BlockNumber::Num(best_block.saturating_sub(depth)),
+			other => other,
+		}
+	}
 }
 
 /// BlockNumber to BlockId conversion
@@ -67,23 +86,26 @@ impl BlockNumber {
 /// NOTE use only for light clients.
 pub trait LightBlockNumber {
 	/// Convert block number to block id.
-	fn to_block_id(self) -> BlockId;
+	/// Fails for `StateRoot`, light clients can only fetch state by block.
+	fn to_block_id(self) -> Result<BlockId, RpcError>;
 }
 
 impl LightBlockNumber for BlockNumber {
-	fn to_block_id(self) -> BlockId {
+	fn to_block_id(self) -> Result<BlockId, RpcError> {
 		// NOTE Here we treat `Pending` as `Latest`.
 		// Since light clients don't produce pending blocks
 		// (they don't have state) we can safely fallback to `Latest`.
 		match self {
-			BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
-			BlockNumber::Num(n) => BlockId::Number(n),
-			BlockNumber::Earliest => BlockId::Earliest,
-			BlockNumber::Latest => BlockId::Latest,
+			BlockNumber::Hash { hash, .. } => Ok(BlockId::Hash(hash)),
+			BlockNumber::Num(n) => Ok(BlockId::Number(n)),
+			BlockNumber::Earliest => Ok(BlockId::Earliest),
+			BlockNumber::Latest => Ok(BlockId::Latest),
 			BlockNumber::Pending => {
 				warn!("`Pending` is deprecated and may be removed in future versions. Falling back to `Latest`");
-				BlockId::Latest
+				Ok(BlockId::Latest)
 			}
+			BlockNumber::StateRoot(_) => Err(errors::state_root_unsupported()),
+			BlockNumber::Confirmed(_) => Err(errors::confirmations_unsupported()),
 		}
 	}
 }
@@ -98,6 +120,12 @@ impl Serialize for BlockNumber {
 			BlockNumber::Latest => serializer.serialize_str("latest"),
 			BlockNumber::Earliest => serializer.serialize_str("earliest"),
 			BlockNumber::Pending => serializer.serialize_str("pending"),
+			BlockNumber::StateRoot(root) => serializer.serialize_str(
+				&format!("{{ 'stateRoot': '{}' }}", root)
+			),
+			BlockNumber::Confirmed(depth) => serializer.serialize_str(
+				&format!("{{ 'confirmations': '0x{:x}' }}", depth)
+			),
 		}
 	}
 }
@@ -113,6 +141,8 @@ impl<'a> Visitor<'a> for BlockNumberVisitor {
 
 	fn visit_map<V>(self, mut visitor: V) -> Result<Self::Value, V::Error> where V: MapAccess<'a> {
 		let (mut require_canonical, mut block_number, mut block_hash) = (false, None::<u64>, None::<H256>);
+		let mut state_root = None::<H256>;
+		let mut confirmations = None::<u64>;
 
 		loop {
 			let key_str: Option<String> = visitor.next_key()?;
@@ -127,7 +157,6 @@ impl<'a> Visitor<'a> for BlockNumberVisitor {
 							})?;
 
 							block_number = Some(number);
-							break;
 						} else {
 							return Err(Error::custom("Invalid block number: missing 0x prefix".to_string()))
 						}
@@ -138,6 +167,21 @@ impl<'a> Visitor<'a> for BlockNumberVisitor {
 					"requireCanonical" => {
 						require_canonical = visitor.next_value()?;
 					}
+					"stateRoot" => {
+						state_root = Some(visitor.next_value()?);
+					}
+					"confirmations" => {
+						let value: String = visitor.next_value()?;
+						if value.starts_with("0x") {
+							let depth = u64::from_str_radix(&value[2..], 16).map_err(|e| {
+								Error::custom(format!("Invalid confirmations: {}", e))
+							})?;
+
+							confirmations = Some(depth);
+						} else {
+							return Err(Error::custom("Invalid confirmations: missing 0x prefix".to_string()))
+						}
+					}
 					key => {
 						return Err(Error::custom(format!("Unknown key: {}", key)))
 					}
@@ -149,13 +193,30 @@ impl<'a> Visitor<'a> for BlockNumberVisitor {
 		}
 
 		if let Some(number) = block_number {
+			if confirmations.is_some() {
+				return Err(Error::custom("confirmations is mutually exclusive with blockNumber"))
+			}
 			return Ok(BlockNumber::Num(number))
 		}
 
+		if let Some(depth) = confirmations {
+			if block_hash.is_some() || state_root.is_some() {
+				return Err(Error::custom("confirmations is mutually exclusive with blockHash/stateRoot"))
+			}
+			return Ok(BlockNumber::Confirmed(depth))
+		}
+
 		if let Some(hash) = block_hash {
+			if state_root.is_some() {
+				return Err(Error::custom("stateRoot is mutually exclusive with blockHash"))
+			}
 			return Ok(BlockNumber::Hash { hash, require_canonical })
 		}
 
+		if let Some(root) = state_root {
+			return Ok(BlockNumber::StateRoot(root))
+		}
+
 		return Err(Error::custom("Invalid input"))
 	}
 
@@ -178,14 +239,17 @@ impl<'a> Visitor<'a> for BlockNumberVisitor {
 	}
 }
 
-/// Converts `BlockNumber` to `BlockId`, panics on `BlockNumber::Pending`
+/// Converts `BlockNumber` to `BlockId`, panics on `BlockNumber::Pending`, `BlockNumber::StateRoot`
+/// and `BlockNumber::Confirmed`
 pub fn block_number_to_id(number: BlockNumber) -> BlockId {
 	match number {
 		BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
 		BlockNumber::Num(num) => BlockId::Number(num),
 		BlockNumber::Earliest => BlockId::Earliest,
 		BlockNumber::Latest => BlockId::Latest,
-		BlockNumber::Pending => panic!("`BlockNumber::Pending` should be handled manually")
+		BlockNumber::Pending => panic!("`BlockNumber::Pending` should be handled manually"),
+		BlockNumber::StateRoot(_) => panic!("`BlockNumber::StateRoot` should be handled manually"),
+		BlockNumber::Confirmed(_) => panic!("`BlockNumber::Confirmed` should be handled manually"),
 	}
 }
 
@@ -205,7 +269,9 @@ mod tests {
 			"pending",
 			{"blockNumber": "0xa"},
 			{"blockHash": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"},
-			{"blockHash": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347", "requireCanonical": true}
+			{"blockHash": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347", "requireCanonical": true},
+			{"stateRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"},
+			{"confirmations": "0xc"}
 		]"#;
 		let deserialized: Vec<BlockNumber> = serde_json::from_str(s).unwrap();
 
@@ -218,7 +284,9 @@ mod tests {
 				BlockNumber::Pending,
 				BlockNumber::Num(10),
 				BlockNumber::Hash { hash: H256::from_str("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347").unwrap(), require_canonical: false },
-				BlockNumber::Hash { hash: H256::from_str("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347").unwrap(), require_canonical: true }
+				BlockNumber::Hash { hash: H256::from_str("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347").unwrap(), require_canonical: true },
+				BlockNumber::StateRoot(H256::from_str("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421").unwrap()),
+				BlockNumber::Confirmed(12)
 			]
 		)
 	}
@@ -227,6 +295,29 @@ mod tests {
 	fn should_not_deserialize() {
 		let s = r#"[{}, "10"]"#;
 		assert!(serde_json::from_str::<Vec<BlockNumber>>(s).is_err());
+
+		let s = r#"{"blockHash": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347", "stateRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"}"#;
+		assert!(serde_json::from_str::<BlockNumber>(s).is_err());
+
+		let s = r#"{"stateRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421", "confirmations": "0xc"}"#;
+		assert!(serde_json::from_str::<BlockNumber>(s).is_err());
+
+		let s = r#"{"blockNumber": "0xa", "confirmations": "0xc"}"#;
+		assert!(serde_json::from_str::<BlockNumber>(s).is_err());
+
+		let s = r#"{"confirmations": "0xc", "blockNumber": "0xa"}"#;
+		assert!(serde_json::from_str::<BlockNumber>(s).is_err());
+	}
+
+	#[test]
+	fn resolves_confirmations() {
+		assert_eq!(BlockNumber::Latest.with_confirmations(100, 0), BlockNumber::Latest);
+		assert_eq!(BlockNumber::Latest.with_confirmations(100, 12), BlockNumber::Num(88));
+		assert_eq!(BlockNumber::Confirmed(30).with_confirmations(100, 12), BlockNumber::Num(70));
+		assert_eq!(BlockNumber::Confirmed(0).with_confirmations(100, 12), BlockNumber::Num(100));
+		assert_eq!(BlockNumber::Latest.with_confirmations(5, 12), BlockNumber::Num(0));
+		assert_eq!(BlockNumber::Earliest.with_confirmations(100, 12), BlockNumber::Earliest);
+		assert_eq!(BlockNumber::Num(99).with_confirmations(100, 12), BlockNumber::Num(99));
 	}
 
 	#[test]
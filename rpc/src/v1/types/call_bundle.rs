@@ -0,0 +1,47 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Result of a call executed as part of a bundle.
+
+use ethereum_types::U256;
+use machine::executed::Executed;
+
+use v1::types::{Bytes, Log};
+
+/// Outcome of a single call of a bundle. Each call sees the effects of the preceding ones.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleResult {
+	/// Output data.
+	pub output: Bytes,
+	/// Gas used by the call.
+	pub gas_used: U256,
+	/// Logs emitted by the call.
+	pub logs: Vec<Log>,
+	/// Error message if the call failed or reverted.
+	pub error: Option<String>,
+}
+
+impl From<Executed> for CallBundleResult {
+	fn from(executed: Executed) -> Self {
+		CallBundleResult {
+			error: executed.exception.map(|e| e.to_string()),
+			output: executed.output.into(),
+			gas_used: executed.gas_used,
+			logs: executed.logs.into_iter().map(Into::into).collect(),
+		}
+	}
+}
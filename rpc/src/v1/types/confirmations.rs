@@ -181,6 +181,17 @@ impl Serialize for ConfirmationResponse {
 	}
 }
 
+/// Outcome of a single request confirmed in a batch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfirmationOutcome {
+	/// Id of the request
+	pub id: U256,
+	/// Response if the request has been confirmed
+	pub result: Option<ConfirmationResponse>,
+	/// Error if the request couldn't be confirmed
+	pub error: Option<jsonrpc_core::Error>,
+}
+
 /// Confirmation response with additional token for further requests
 #[derive(Clone, PartialEq, Serialize)]
 pub struct ConfirmationResponseWithToken {
@@ -242,7 +253,7 @@ impl ConfirmationPayload {
 }
 
 /// Possible modifications to the confirmed transaction sent by `Trusted Signer`
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "camelCase")]
 pub struct TransactionModification {
@@ -0,0 +1,93 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Consensus fault return type.
+
+use ethereum_types::{H160, H256, U64};
+use types::engines::fault::{self, ConsensusFaultKind as FaultKind};
+
+/// Kind of a consensus fault.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConsensusFaultKind {
+	/// Block with an invalid seal.
+	InvalidSeal,
+	/// Multiple blocks proposed for the same step.
+	Equivocation,
+	/// No block produced in the validator's step.
+	SkippedStep,
+}
+
+impl From<FaultKind> for ConsensusFaultKind {
+	fn from(kind: FaultKind) -> Self {
+		match kind {
+			FaultKind::InvalidSeal => ConsensusFaultKind::InvalidSeal,
+			FaultKind::Equivocation => ConsensusFaultKind::Equivocation,
+			FaultKind::SkippedStep => ConsensusFaultKind::SkippedStep,
+		}
+	}
+}
+
+/// Consensus fault attributed to a validator.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusFault {
+	/// Validator address
+	pub validator: H160,
+	/// Kind of the fault
+	pub kind: ConsensusFaultKind,
+	/// Number of the block revealing the fault
+	pub block_number: U64,
+	/// Hash of the block revealing the fault
+	pub block_hash: H256,
+	/// UNIX timestamp at which the fault was observed
+	pub timestamp: U64,
+}
+
+impl From<fault::ConsensusFault> for ConsensusFault {
+	fn from(f: fault::ConsensusFault) -> Self {
+		ConsensusFault {
+			validator: f.validator,
+			kind: f.kind.into(),
+			block_number: f.block_number.into(),
+			block_hash: f.block_hash,
+			timestamp: f.timestamp.into(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethereum_types::{H160, H256};
+	use super::{ConsensusFault, ConsensusFaultKind};
+
+	#[test]
+	fn consensus_fault_serialization() {
+		let fault = ConsensusFault {
+			validator: H160::from_low_u64_be(1),
+			kind: ConsensusFaultKind::SkippedStep,
+			block_number: 5.into(),
+			block_hash: H256::zero(),
+			timestamp: 1_500_000_000.into(),
+		};
+
+		assert_eq!(
+			serde_json::to_string(&fault).unwrap(),
+			r#"{"validator":"0x0000000000000000000000000000000000000001","kind":"skippedStep","blockNumber":"0x5","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000000","timestamp":"0x59682f00"}"#
+		);
+	}
+}
@@ -0,0 +1,67 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fee suggestions.
+
+use ethcore::miner;
+use ethereum_types::U256;
+
+/// Suggested fees for a single inclusion speed.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeTier {
+	/// Suggested tip per unit of gas.
+	pub max_priority_fee_per_gas: U256,
+	/// Suggested gas price, including the base fee of the next block.
+	pub gas_price: U256,
+	/// Fraction of recent blocks which included a transaction paying this tip or less.
+	pub confidence: f64,
+}
+
+impl From<miner::FeeTier> for FeeTier {
+	fn from(t: miner::FeeTier) -> Self {
+		FeeTier {
+			max_priority_fee_per_gas: t.max_priority_fee_per_gas,
+			gas_price: t.gas_price,
+			confidence: t.confidence,
+		}
+	}
+}
+
+/// Fee suggestions for slow, standard and fast inclusion.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeSuggestions {
+	/// Base fee of the next block, `null` before EIP-1559 transition.
+	pub base_fee: Option<U256>,
+	/// Tier for transactions which can wait a few blocks.
+	pub slow: FeeTier,
+	/// Tier for regular transactions.
+	pub standard: FeeTier,
+	/// Tier for transactions which should be included in the next block.
+	pub fast: FeeTier,
+}
+
+impl From<miner::FeeSuggestions> for FeeSuggestions {
+	fn from(s: miner::FeeSuggestions) -> Self {
+		FeeSuggestions {
+			base_fee: s.base_fee,
+			slow: s.slow.into(),
+			standard: s.standard.into(),
+			fast: s.fast.into(),
+		}
+	}
+}
@@ -22,8 +22,8 @@ use serde_json::{Value, from_value};
 use types::filter::Filter as EthFilter;
 use types::ids::BlockId;
 
-use v1::types::{BlockNumber, Log};
-use v1::helpers::errors::invalid_params;
+use v1::types::{BlockNumber, Cursor, Log};
+use v1::helpers::errors::{invalid_params, state_root_unsupported, confirmations_unsupported};
 
 /// Variadic value
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
@@ -73,26 +73,44 @@ pub struct Filter {
 	pub topics: Option<Vec<Topic>>,
 	/// Limit
 	pub limit: Option<usize>,
+	/// Position to continue a paged query from
+	pub cursor: Option<Cursor>,
 }
 
 impl Filter {
+	/// Resolve `Latest` and `Confirmed` bounds to block numbers relative to `best_block`,
+	/// see `BlockNumber::with_confirmations`. Missing bounds are read as `Latest`.
+	pub fn with_confirmations(mut self, best_block: u64, confirmations: u64) -> Filter {
+		if self.block_hash.is_none() {
+			let confirmed = |num: Option<BlockNumber>| Some(num.unwrap_or_default().with_confirmations(best_block, confirmations));
+			self.from_block = confirmed(self.from_block);
+			self.to_block = confirmed(self.to_block);
+		}
+		self
+	}
+
 	pub fn try_into(self) -> Result<EthFilter, RpcError> {
 		if self.block_hash.is_some() && (self.from_block.is_some() || self.to_block.is_some()) {
 			return Err(invalid_params("blockHash", "blockHash is mutually exclusive with fromBlock/toBlock"));
 		}
+		if self.cursor.is_some() {
+			return Err(invalid_params("cursor", "cursor is only supported by eth_getLogsPage"));
+		}
 
 		let num_to_id = |num| match num {
-			BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
-			BlockNumber::Num(n) => BlockId::Number(n),
-			BlockNumber::Earliest => BlockId::Earliest,
-			BlockNumber::Latest | BlockNumber::Pending => BlockId::Latest,
+			BlockNumber::Hash { hash, .. } => Ok(BlockId::Hash(hash)),
+			BlockNumber::Num(n) => Ok(BlockId::Number(n)),
+			BlockNumber::Earliest => Ok(BlockId::Earliest),
+			BlockNumber::Latest | BlockNumber::Pending => Ok(BlockId::Latest),
+			BlockNumber::StateRoot(_) => Err(state_root_unsupported()),
+			BlockNumber::Confirmed(_) => Err(confirmations_unsupported()),
 		};
 
 		let (from_block, to_block) = match self.block_hash {
 			Some(hash) => (BlockId::Hash(hash), BlockId::Hash(hash)),
 			None =>
-				(self.from_block.map_or_else(|| BlockId::Latest, &num_to_id),
-				 self.to_block.map_or_else(|| BlockId::Latest, &num_to_id)),
+				(self.from_block.map_or_else(|| Ok(BlockId::Latest), &num_to_id)?,
+				 self.to_block.map_or_else(|| Ok(BlockId::Latest), &num_to_id)?),
 		};
 
 		Ok(EthFilter {
@@ -177,6 +195,7 @@ mod tests {
 			address: None,
 			topics: None,
 			limit: None,
+			cursor: None,
 		});
 	}
 
@@ -193,6 +212,7 @@ mod tests {
 				VariadicValue::Null,
 			]),
 			limit: None,
+			cursor: None,
 		};
 
 		let eth_filter: EthFilter = filter.try_into().unwrap();
@@ -209,4 +229,25 @@ mod tests {
 			limit: None,
 		});
 	}
+
+	#[test]
+	fn filter_with_confirmations() {
+		let filter = |from_block, to_block| Filter {
+			from_block, to_block,
+			block_hash: None,
+			address: None,
+			topics: None,
+			limit: None,
+			cursor: None,
+		};
+
+		let confirmed = filter(None, Some(BlockNumber::Confirmed(2))).with_confirmations(10, 3);
+		assert_eq!(confirmed, filter(Some(BlockNumber::Num(7)), Some(BlockNumber::Num(8))));
+
+		let unchanged = filter(Some(BlockNumber::Earliest), Some(BlockNumber::Pending)).with_confirmations(10, 3);
+		assert_eq!(unchanged, filter(Some(BlockNumber::Earliest), Some(BlockNumber::Pending)));
+
+		let by_hash = Filter { block_hash: Some(H256::zero()), ..filter(None, None) };
+		assert_eq!(by_hash.clone().with_confirmations(10, 3), by_hash);
+	}
 }
@@ -20,29 +20,39 @@
 mod eth_types;
 
 mod account_info;
+mod backup;
+mod bad_block;
 mod block;
 mod block_number;
 mod bytes;
+mod call_bundle;
 mod call_request;
 mod confirmations;
+mod consensus_fault;
 mod consensus_status;
 mod derivation;
+mod fee_suggestions;
 mod filter;
 mod histogram;
 mod index;
 mod log;
 mod node_kind;
+mod page;
 mod private_receipt;
 mod private_log;
 mod provenance;
 mod receipt;
 mod rpc_settings;
+mod rpc_stats;
 mod secretstore;
+mod storage_range;
+mod stratum;
 mod sync;
 mod trace;
 mod trace_filter;
 mod transaction;
 mod transaction_request;
+mod transaction_queue_status;
 mod transaction_condition;
 mod work;
 mod eip191;
@@ -50,38 +60,49 @@ mod eip191;
 pub mod pubsub;
 
 pub use self::eip191::{EIP191Version, PresignedTransaction};
-pub use self::account_info::{AccountInfo, ExtAccountInfo, EthAccount, StorageProof, RecoveredAccount};
+pub use self::account_info::{AccountInfo, AccountAuditEntry, AccountHistoryEntry, ExtAccountInfo, EthAccount, StorageProof, RecoveredAccount};
+pub use self::backup::{BackupInfo, BackupStatus};
+pub use self::bad_block::{BadBlock, BadBlockReason};
 pub use self::bytes::Bytes;
 pub use self::block::{RichBlock, Block, BlockTransactions, Header, RichHeader, Rich};
 pub use self::block_number::{BlockNumber, LightBlockNumber, block_number_to_id};
+pub use self::call_bundle::CallBundleResult;
 pub use self::call_request::CallRequest;
 pub use self::confirmations::{
 	ConfirmationPayload, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken,
-	TransactionModification, EIP191SignRequest, EthSignRequest, DecryptRequest, Either
+	ConfirmationOutcome, TransactionModification, EIP191SignRequest, EthSignRequest, DecryptRequest, Either
 };
+pub use self::consensus_fault::{ConsensusFault, ConsensusFaultKind};
 pub use self::consensus_status::*;
 pub use self::derivation::{DeriveHash, DeriveHierarchical, Derive};
+pub use self::fee_suggestions::{FeeSuggestions, FeeTier};
 pub use self::filter::{Filter, FilterChanges};
 pub use self::histogram::Histogram;
 pub use self::index::Index;
 pub use self::log::Log;
 pub use self::node_kind::{NodeKind, Availability, Capability};
+pub use self::page::{Cursor, Page, DEFAULT_PAGE_SIZE};
 pub use self::private_receipt::{PrivateTransactionReceipt, PrivateTransactionReceiptAndTransaction};
 pub use self::private_log::PrivateTransactionLog;
 pub use self::provenance::Origin;
 pub use self::receipt::Receipt;
 pub use self::rpc_settings::RpcSettings;
+pub use self::rpc_stats::RpcMethodStats;
 pub use self::secretstore::EncryptedDocumentKey;
+pub use self::storage_range::{StorageEntry, StorageRange};
+pub use self::stratum::StratumWorker;
 pub use self::sync::{
-	SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
-	TransactionStats, ChainStatus, EthProtocolInfo, PipProtocolInfo,
+	SyncStatus, SyncInfo, SyncDetails, SnapshotRestoration, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
+	TransactionStats, ChainStatus, DeepReorg, EthProtocolInfo, PipProtocolInfo, Traffic, Throughput,
+	ProtocolThroughput,
 };
-pub use self::trace::{LocalizedTrace, TraceResults, TraceResultsWithTransactionHash};
+pub use self::trace::{LocalizedTrace, TraceResults, TraceResultsWithTransactionHash, StateAccessLog};
 pub use self::trace_filter::TraceFilter;
-pub use self::transaction::{Transaction, RichRawTransaction, LocalTransactionStatus};
+pub use self::transaction::{Transaction, RichRawTransaction, LocalTransactionStatus, TransactionImportSimulation};
 pub use self::transaction_request::TransactionRequest;
+pub use self::transaction_queue_status::{TransactionQueueStatus, OriginOccupancy};
 pub use self::transaction_condition::TransactionCondition;
-pub use self::work::Work;
+pub use self::work::{Work, SubmittedWork};
 
 // TODO [ToDr] Refactor to a proper type Vec of enums?
 /// Expected tracing type.
@@ -0,0 +1,201 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Cursor based pagination of large query results.
+
+use std::cmp;
+
+use ethereum_types::H256;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use v1::types::Bytes;
+
+/// Default maximal number of items in a page of results.
+pub const DEFAULT_PAGE_SIZE: usize = 10_000;
+
+/// Length of the encoded cursor.
+const CURSOR_LEN: usize = 56;
+
+/// Number of blocks queried at once while a page is filled.
+const PAGE_SCAN_BLOCKS: u64 = 1_000;
+
+/// Opaque position of an item in the results of a paged query.
+///
+/// Items are ordered by block, by transaction within the block and by their index among the results
+/// of the block. Items not produced by a transaction (block rewards) sort after the transactions.
+/// The hash of the block is kept to detect a cursor invalidated by a reorganization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cursor {
+	/// Block number
+	pub block: u64,
+	/// Block hash
+	pub hash: H256,
+	/// Index of the transaction in the block
+	pub transaction: u64,
+	/// Index of the item among the results of the block
+	pub index: u64,
+}
+
+impl Serialize for Cursor {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where S: Serializer {
+		let mut bytes = Vec::with_capacity(CURSOR_LEN);
+		bytes.extend_from_slice(&self.block.to_be_bytes());
+		bytes.extend_from_slice(self.hash.as_bytes());
+		bytes.extend_from_slice(&self.transaction.to_be_bytes());
+		bytes.extend_from_slice(&self.index.to_be_bytes());
+		Bytes::new(bytes).serialize(serializer)
+	}
+}
+
+impl<'a> Deserialize<'a> for Cursor {
+	fn deserialize<D>(deserializer: D) -> Result<Cursor, D::Error>
+	where D: Deserializer<'a> {
+		let bytes = Bytes::deserialize(deserializer)?.into_vec();
+		if bytes.len() != CURSOR_LEN {
+			return Err(D::Error::custom("Invalid cursor"));
+		}
+
+		let read = |offset: usize| {
+			let mut word = [0u8; 8];
+			word.copy_from_slice(&bytes[offset..offset + 8]);
+			u64::from_be_bytes(word)
+		};
+		Ok(Cursor {
+			block: read(0),
+			hash: H256::from_slice(&bytes[8..40]),
+			transaction: read(40),
+			index: read(48),
+		})
+	}
+}
+
+/// Page of query results. The query continues with the returned cursor until it is `null`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Page<T> {
+	/// Results in this page
+	pub items: Vec<T>,
+	/// Position of the last result if more are available
+	pub cursor: Option<Cursor>,
+}
+
+impl<T> Page<T> {
+	/// Takes up to `size` of the positioned results following `cursor`.
+	pub fn after<I>(items: I, cursor: Option<Cursor>, size: usize) -> Self where
+		I: IntoIterator<Item = (Cursor, T)>,
+	{
+		let mut items = items.into_iter()
+			.filter(|&(position, _)| cursor.map_or(true, |cursor| position > cursor))
+			.peekable();
+
+		let mut page = Vec::new();
+		let mut last = None;
+		while page.len() < size.max(1) {
+			match items.next() {
+				Some((position, item)) => {
+					last = Some(position);
+					page.push(item);
+				},
+				None => break,
+			}
+		}
+
+		Page {
+			items: page,
+			cursor: items.peek().and(last),
+		}
+	}
+
+	/// Fills a page with the results following `cursor` in blocks `from..=to`. The blocks are queried
+	/// in windows and the scan stops as soon as a result past the page is found.
+	pub fn scan<E, F>(from: u64, to: u64, cursor: Option<Cursor>, size: usize, mut query: F) -> Result<Self, E> where
+		F: FnMut(u64, u64) -> Result<Vec<(Cursor, T)>, E>,
+	{
+		let size = size.max(1);
+		let mut start = cursor.map_or(from, |cursor| cmp::max(from, cursor.block));
+		let mut items = Vec::new();
+		while start <= to && items.len() <= size {
+			let end = cmp::min(to, start.saturating_add(PAGE_SCAN_BLOCKS - 1));
+			items.extend(query(start, end)?.into_iter()
+				.filter(|&(position, _)| cursor.map_or(true, |cursor| position > cursor)));
+			start = match end.checked_add(1) {
+				Some(next) => next,
+				None => break,
+			};
+		}
+
+		Ok(Page::after(items, None, size))
+	}
+
+	/// Converts the results of the page.
+	pub fn map<U, F: FnMut(T) -> U>(self, f: F) -> Page<U> {
+		Page {
+			items: self.items.into_iter().map(f).collect(),
+			cursor: self.cursor,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H256;
+	use serde_json;
+	use super::{Cursor, Page, PAGE_SCAN_BLOCKS};
+
+	fn cursor(block: u64, index: u64) -> Cursor {
+		Cursor { block, hash: H256::from_low_u64_be(block), transaction: 0, index }
+	}
+
+	#[test]
+	fn cursor_serialization() {
+		let c = Cursor { block: 1, hash: H256::from_low_u64_be(0xff), transaction: 2, index: 3 };
+		let serialized = serde_json::to_string(&c).unwrap();
+		assert_eq!(serialized, r#""0x000000000000000100000000000000000000000000000000000000000000000000000000000000ff00000000000000020000000000000003""#);
+		assert_eq!(serde_json::from_str::<Cursor>(&serialized).unwrap(), c);
+		assert!(serde_json::from_str::<Cursor>(r#""0x0001""#).is_err());
+	}
+
+	#[test]
+	fn pages_after_cursor() {
+		let items = || (0..5).map(|i| (cursor(i / 2, i % 2), i));
+
+		let first = Page::after(items(), None, 2);
+		assert_eq!(first, Page { items: vec![0, 1], cursor: Some(cursor(0, 1)) });
+		let second = Page::after(items(), first.cursor, 2);
+		assert_eq!(second, Page { items: vec![2, 3], cursor: Some(cursor(1, 1)) });
+		let last = Page::after(items(), second.cursor, 2);
+		assert_eq!(last, Page { items: vec![4], cursor: None });
+		assert_eq!(Page::after(items(), None, 5).cursor, None);
+	}
+
+	#[test]
+	fn scan_stops_after_full_page() {
+		let mut queried = Vec::new();
+		let page = Page::scan::<(), _>(0, 10 * PAGE_SCAN_BLOCKS, Some(cursor(1, 0)), 2, |from, to| {
+			queried.push((from, to));
+			Ok((from..to + 1).map(|block| (cursor(block, 0), block)).collect())
+		}).unwrap();
+
+		assert_eq!(page, Page { items: vec![2, 3], cursor: Some(cursor(3, 0)) });
+		assert_eq!(queried, vec![(1, PAGE_SCAN_BLOCKS)]);
+
+		let last = Page::scan::<(), _>(0, 3, page.cursor, 2, |from, to| {
+			Ok((from..to + 1).map(|block| (cursor(block, 0), block)).collect())
+		}).unwrap();
+		assert_eq!(last, Page { items: vec![], cursor: None });
+	}
+}
@@ -16,11 +16,11 @@
 
 //! Pub-Sub types.
 
-use ethereum_types::H256;
+use ethereum_types::{H160, H256};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::Error;
 use serde_json::{Value, from_value};
-use v1::types::{RichHeader, Filter, Log};
+use v1::types::{RichHeader, Filter, Log, Work};
 
 /// Subscription result.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -32,7 +32,34 @@ pub enum Result {
 	/// Transaction hash
 	TransactionHash(H256),
 	/// SyncStatus
-	SyncState(PubSubSyncStatus)
+	SyncState(PubSubSyncStatus),
+	/// New mining work package
+	Work(Work),
+	/// Key file added or removed
+	Account(AccountChange),
+}
+
+/// Account added to or removed from the keys directory.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+#[serde(rename_all="camelCase")]
+pub struct AccountChange {
+	/// Address of the account.
+	pub address: H160,
+	/// Whether the key file was added, `false` if it was removed.
+	pub added: bool,
+}
+
+/// Chain reorganisation.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+#[serde(rename_all="camelCase")]
+pub struct Reorg {
+	/// Hashes of the blocks removed from the canonical chain.
+	pub retracted: Vec<H256>,
+	/// Hashes of the blocks added to the canonical chain.
+	pub enacted: Vec<H256>,
+	/// Hashes of the transactions from the retracted blocks which aren't part of the enacted ones,
+	/// i.e. dropped back to pending.
+	pub retracted_transactions: Vec<H256>,
 }
 
 /// PubSbub sync status
@@ -52,6 +79,8 @@ impl Serialize for Result {
 			Result::Log(ref log) => log.serialize(serializer),
 			Result::TransactionHash(ref hash) => hash.serialize(serializer),
 			Result::SyncState(ref sync) => sync.serialize(serializer),
+			Result::Work(ref work) => work.serialize(serializer),
+			Result::Account(ref account) => account.serialize(serializer),
 		}
 	}
 }
@@ -69,6 +98,10 @@ pub enum Kind {
 	NewPendingTransactions,
 	/// Node syncing status subscription.
 	Syncing,
+	/// New mining work packages subscription.
+	NewWork,
+	/// Accounts added or removed from the keys directory.
+	Accounts,
 }
 
 /// Subscription kind.
@@ -113,6 +146,8 @@ mod tests {
 		assert_eq!(serde_json::from_str::<Kind>(r#""logs""#).unwrap(), Kind::Logs);
 		assert_eq!(serde_json::from_str::<Kind>(r#""newPendingTransactions""#).unwrap(), Kind::NewPendingTransactions);
 		assert_eq!(serde_json::from_str::<Kind>(r#""syncing""#).unwrap(), Kind::Syncing);
+		assert_eq!(serde_json::from_str::<Kind>(r#""newWork""#).unwrap(), Kind::NewWork);
+		assert_eq!(serde_json::from_str::<Kind>(r#""accounts""#).unwrap(), Kind::Accounts);
 	}
 
 	#[test]
@@ -132,6 +167,7 @@ mod tests {
 			address: None,
 			topics: None,
 			limit: None,
+			cursor: None,
 		}));
 		assert_eq!(logs2, Params::Logs(Filter {
 			from_block: None,
@@ -140,6 +176,7 @@ mod tests {
 			address: None,
 			topics: None,
 			limit: Some(10),
+			cursor: None,
 		}));
 		assert_eq!(logs3, Params::Logs(Filter {
 			from_block: None,
@@ -150,6 +187,7 @@ mod tests {
 				VariadicValue::Single("000000000000000000000000a94f5374fce5edbc8e2a8697c15331677e6ebf0b".parse().unwrap()
 			)]),
 			limit: None,
+			cursor: None,
 		}));
 	}
 
@@ -173,6 +211,7 @@ mod tests {
 				logs_bloom: Default::default(),
 				timestamp: Default::default(),
 				difficulty: Default::default(),
+				base_fee_per_gas: None,
 				seal_fields: vec![Default::default(), Default::default()],
 				size: Some(69.into()),
 			},
@@ -38,6 +38,8 @@ pub struct Receipt {
 	pub cumulative_gas_used: U256,
 	/// Gas used
 	pub gas_used: Option<U256>,
+	/// Price paid per unit of gas
+	pub effective_gas_price: Option<U256>,
 	/// Contract address
 	pub contract_address: Option<H160>,
 	/// Logs
@@ -52,6 +54,12 @@ pub struct Receipt {
 	// NOTE(niklasad1): Unknown after EIP98 rules, if it's missing then skip serializing it
 	#[serde(skip_serializing_if = "Option::is_none", rename = "status")]
 	pub status_code: Option<U64>,
+	/// Transaction type, always `0x0` as only legacy transactions are supported
+	#[serde(rename = "type")]
+	pub transaction_type: U64,
+	/// Reason the transaction was reverted with, only available for failed transactions when tracing is enabled
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub revert_reason: Option<String>,
 }
 
 impl Receipt {
@@ -62,6 +70,27 @@ impl Receipt {
 		}
 	}
 
+	/// Decodes the `Error(string)` revert reason from the output of a reverted transaction.
+	pub fn decode_revert_reason(output: &[u8]) -> Option<String> {
+		const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+		if output.len() < 4 || output[..4] != ERROR_SELECTOR {
+			return None;
+		}
+		let data = &output[4..];
+		let word = |at: usize| -> Option<usize> {
+			let word = data.get(at..at.checked_add(32)?)?;
+			let value = U256::from_big_endian(word);
+			if value > U256::from(data.len()) { None } else { Some(value.as_usize()) }
+		};
+
+		let offset = word(0)?;
+		let len = word(offset)?;
+		let start = offset + 32;
+		let reason = data.get(start..start.checked_add(len)?)?;
+		String::from_utf8(reason.to_vec()).ok()
+	}
+
 	fn outcome_to_status_code(outcome: &TransactionOutcome) -> Option<U64> {
 		match *outcome {
 			TransactionOutcome::Unknown | TransactionOutcome::StateRoot(_) => None,
@@ -81,11 +110,14 @@ impl From<LocalizedReceipt> for Receipt {
 			block_number: Some(r.block_number.into()),
 			cumulative_gas_used: r.cumulative_gas_used,
 			gas_used: Some(r.gas_used),
+			effective_gas_price: Some(r.effective_gas_price),
 			contract_address: r.contract_address.map(Into::into),
 			logs: r.logs.into_iter().map(Into::into).collect(),
 			status_code: Self::outcome_to_status_code(&r.outcome),
 			state_root: Self::outcome_to_state_root(r.outcome),
 			logs_bloom: r.log_bloom,
+			transaction_type: U64::zero(),
+			revert_reason: None,
 		}
 	}
 }
@@ -101,11 +133,14 @@ impl From<RichReceipt> for Receipt {
 			block_number: None,
 			cumulative_gas_used: r.cumulative_gas_used,
 			gas_used: Some(r.gas_used),
+			effective_gas_price: Some(r.effective_gas_price),
 			contract_address: r.contract_address.map(Into::into),
 			logs: r.logs.into_iter().map(Into::into).collect(),
 			status_code: Self::outcome_to_status_code(&r.outcome),
 			state_root: Self::outcome_to_state_root(r.outcome),
 			logs_bloom: r.log_bloom,
+			transaction_type: U64::zero(),
+			revert_reason: None,
 		}
 	}
 }
@@ -121,11 +156,14 @@ impl From<EthReceipt> for Receipt {
 			block_number: None,
 			cumulative_gas_used: r.gas_used,
 			gas_used: None,
+			effective_gas_price: None,
 			contract_address: None,
 			logs: r.logs.into_iter().map(Into::into).collect(),
 			status_code: Self::outcome_to_status_code(&r.outcome),
 			state_root: Self::outcome_to_state_root(r.outcome),
 			logs_bloom: r.log_bloom,
+			transaction_type: U64::zero(),
+			revert_reason: None,
 		}
 	}
 }
@@ -133,12 +171,13 @@ impl From<EthReceipt> for Receipt {
 #[cfg(test)]
 mod tests {
 	use serde_json;
+	use rustc_hex::FromHex;
 	use v1::types::{Log, Receipt};
 	use ethereum_types::{H256, Bloom};
 
 	#[test]
 	fn receipt_serialization() {
-		let s = r#"{"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","from":null,"to":null,"blockNumber":"0x4510c","cumulativeGasUsed":"0x20","gasUsed":"0x10","contractAddress":null,"logs":[{"address":"0x33990122638b9132ca29c723bdf037f1a891a70c","topics":["0xa6697e974e6a320f454390be03f74955e8978f1a6971ea6730542e37b66179bc","0x4861736852656700000000000000000000000000000000000000000000000000"],"data":"0x","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x4510c","transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","logIndex":"0x1","transactionLogIndex":null,"type":"mined","removed":false}],"root":"0x000000000000000000000000000000000000000000000000000000000000000a","logsBloom":"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000f","status":"0x1"}"#;
+		let s = r#"{"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","from":null,"to":null,"blockNumber":"0x4510c","cumulativeGasUsed":"0x20","gasUsed":"0x10","effectiveGasPrice":"0x1","contractAddress":null,"logs":[{"address":"0x33990122638b9132ca29c723bdf037f1a891a70c","topics":["0xa6697e974e6a320f454390be03f74955e8978f1a6971ea6730542e37b66179bc","0x4861736852656700000000000000000000000000000000000000000000000000"],"data":"0x","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x4510c","transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","logIndex":"0x1","transactionLogIndex":null,"type":"mined","removed":false}],"root":"0x000000000000000000000000000000000000000000000000000000000000000a","logsBloom":"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000f","status":"0x1","type":"0x0"}"#;
 
 		let receipt = Receipt {
 			from: None,
@@ -149,6 +188,7 @@ mod tests {
 			block_number: Some(0x4510c.into()),
 			cumulative_gas_used: 0x20.into(),
 			gas_used: Some(0x10.into()),
+			effective_gas_price: Some(0x1.into()),
 			contract_address: None,
 			logs: vec![Log {
 				address: "33990122638b9132ca29c723bdf037f1a891a70c".parse().unwrap(),
@@ -169,9 +209,23 @@ mod tests {
 			logs_bloom: Bloom::from_low_u64_be(15),
 			state_root: Some(H256::from_low_u64_be(10)),
 			status_code: Some(1u64.into()),
+			transaction_type: 0u64.into(),
+			revert_reason: None,
 		};
 
 		let serialized = serde_json::to_string(&receipt).unwrap();
 		assert_eq!(serialized, s);
 	}
+
+	#[test]
+	fn should_decode_revert_reason() {
+		let output = "08c379a0\
+			0000000000000000000000000000000000000000000000000000000000000020\
+			000000000000000000000000000000000000000000000000000000000000001a\
+			4e6f7420656e6f7567682045746865722070726f76696465642e000000000000".from_hex().unwrap();
+
+		assert_eq!(Receipt::decode_revert_reason(&output), Some("Not enough Ether provided.".to_owned()));
+		assert_eq!(Receipt::decode_revert_reason(&output[..68]), None);
+		assert_eq!(Receipt::decode_revert_reason(&[]), None);
+	}
 }
@@ -0,0 +1,49 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC usage statistics.
+
+use v1::informant::MethodStats;
+use v1::helpers::deprecated;
+
+/// Calls of a single RPC method.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMethodStats {
+	/// Number of calls.
+	pub calls: u64,
+	/// Number of calls which returned an error.
+	pub errors: u64,
+	/// Share of calls which returned an error.
+	pub error_rate: f64,
+	/// Average execution time in microseconds.
+	pub average_latency: u64,
+	/// Whether the method is deprecated.
+	pub deprecated: bool,
+}
+
+impl RpcMethodStats {
+	/// Statistics of the given method.
+	pub fn new(method: &str, stats: &MethodStats) -> Self {
+		RpcMethodStats {
+			calls: stats.calls,
+			errors: stats.errors,
+			error_rate: stats.error_rate(),
+			average_latency: stats.average_latency() as u64,
+			deprecated: deprecated::find(method).is_some(),
+		}
+	}
+}
@@ -0,0 +1,64 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Storage range return types.
+
+use std::collections::BTreeMap;
+
+use ethereum_types::H256;
+use hash::keccak;
+
+/// A single storage entry.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StorageEntry {
+	/// Storage key
+	pub key: H256,
+	/// Storage value
+	pub value: H256,
+}
+
+/// A page of contract storage (used by `debug_storageRangeAt`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageRange {
+	/// Storage entries by the hash of their keys
+	pub storage: BTreeMap<H256, StorageEntry>,
+	/// Key the next page starts with, `None` if this is the last page
+	pub next_key: Option<H256>,
+}
+
+impl From<(Vec<(H256, H256)>, Option<H256>)> for StorageRange {
+	fn from((entries, next_key): (Vec<(H256, H256)>, Option<H256>)) -> Self {
+		StorageRange {
+			storage: entries.into_iter().map(|(key, value)| (keccak(key), StorageEntry { key, value })).collect(),
+			next_key,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethereum_types::H256;
+	use super::StorageRange;
+
+	#[test]
+	fn storage_range_serialization() {
+		let range: StorageRange = (vec![(H256::zero(), H256::from_low_u64_be(1))], None).into();
+		let serialized = serde_json::to_string(&range).unwrap();
+		assert_eq!(serialized, r#"{"storage":{"0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563":{"key":"0x0000000000000000000000000000000000000000000000000000000000000000","value":"0x0000000000000000000000000000000000000000000000000000000000000001"}},"nextKey":null}"#);
+	}
+}
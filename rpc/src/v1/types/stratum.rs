@@ -0,0 +1,60 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Stratum worker statistics return type.
+
+use ethereum_types::{U64, U256};
+
+/// Statistics of a worker connected to the stratum server.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StratumWorker {
+	/// Worker id given on authorization
+	pub worker_id: String,
+	/// Address the worker connected from
+	pub address: String,
+	/// Number of accepted shares
+	pub accepted_shares: U64,
+	/// Number of rejected shares
+	pub rejected_shares: U64,
+	/// UNIX timestamp of the last submitted share
+	pub last_share: Option<U64>,
+	/// Hashes per second estimated from the accepted shares, `null` if their difficulty is unknown
+	pub hashrate: Option<U256>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::StratumWorker;
+
+	#[test]
+	fn stratum_worker_serialization() {
+		let worker = StratumWorker {
+			worker_id: "miner1".into(),
+			address: "10.0.0.1:56324".into(),
+			accepted_shares: 10.into(),
+			rejected_shares: 1.into(),
+			last_share: Some(1_500_000_000.into()),
+			hashrate: None,
+		};
+
+		assert_eq!(
+			serde_json::to_string(&worker).unwrap(),
+			r#"{"workerId":"miner1","address":"10.0.0.1:56324","acceptedShares":"0xa","rejectedShares":"0x1","lastShare":"0x59682f00","hashrate":null}"#
+		);
+	}
+}
@@ -17,9 +17,10 @@
 use network::client_version::ClientVersion;
 use std::collections::BTreeMap;
 
-use ethereum_types::{U256, H512};
+use ethereum_types::{U256, H256, H512};
 use sync::{self, PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats};
 use serde::{Serialize, Serializer};
+use types::chain_notify;
 
 /// Sync info
 #[derive(Default, Debug, Serialize, PartialEq)]
@@ -35,6 +36,45 @@ pub struct SyncInfo {
 	pub warp_chunks_amount: Option<U256>,
 	/// Warp sync snpashot chunks processed.
 	pub warp_chunks_processed: Option<U256>,
+	/// Parity-specific details, nested to keep the response compatible with other clients.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub parity: Option<SyncDetails>,
+}
+
+/// Parity-specific sync details.
+#[derive(Default, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncDetails {
+	/// Snapshot restoration progress, if a snapshot is being restored.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub snapshot: Option<SnapshotRestoration>,
+	/// Range of ancient blocks still to be downloaded, if there is one: (first, last)
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub block_gap: Option<(U256, U256)>,
+	/// Number of queued blocks awaiting verification.
+	pub queued_blocks: U256,
+	/// Number of verified blocks awaiting import.
+	pub verified_blocks: U256,
+	/// Memory used by the verification queue in bytes.
+	pub queue_mem_used: U256,
+	/// Verification queue memory limit in bytes.
+	pub queue_mem_limit: U256,
+	/// Average time it takes to verify a block in microseconds.
+	pub verification_time: U256,
+}
+
+/// Snapshot restoration progress.
+#[derive(Default, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotRestoration {
+	/// Number of state chunks restored.
+	pub state_chunks_done: U256,
+	/// Total number of state chunks.
+	pub state_chunks_total: U256,
+	/// Number of block chunks restored.
+	pub block_chunks_done: U256,
+	/// Total number of block chunks.
+	pub block_chunks_total: U256,
 }
 
 /// Peers info
@@ -93,6 +133,12 @@ pub struct EthProtocolInfo {
 	pub difficulty: Option<U256>,
 	/// SHA3 of peer best block hash
 	pub head: String,
+	/// Manifest hash of the best snapshot the peer advertises
+	#[serde(rename = "snapshotHash")]
+	pub snapshot_hash: Option<H256>,
+	/// Block number of the best snapshot the peer advertises
+	#[serde(rename = "snapshotNumber")]
+	pub snapshot_number: Option<u64>,
 }
 
 impl From<sync::EthProtocolInfo> for EthProtocolInfo {
@@ -101,6 +147,8 @@ impl From<sync::EthProtocolInfo> for EthProtocolInfo {
 			version: info.version,
 			difficulty: info.difficulty.map(Into::into),
 			head: format!("{:x}", info.head),
+			snapshot_hash: info.snapshot_hash,
+			snapshot_number: info.snapshot_number,
 		}
 	}
 }
@@ -151,6 +199,14 @@ impl Serialize for SyncStatus {
 pub struct TransactionStats {
 	/// Block no this transaction was first seen.
 	pub first_seen: u64,
+	/// Number of distinct peers this transaction was propagated to.
+	pub peer_count: usize,
+	/// Unix timestamp (in seconds) of the first propagation.
+	pub first_propagated_at: u64,
+	/// Unix timestamp (in seconds) of the most recent propagation.
+	pub last_propagated_at: u64,
+	/// Number of times this transaction was re-sent to a peer.
+	pub rebroadcasts: usize,
 	/// Peers this transaction was propagated to with count.
 	pub propagated_to: BTreeMap<H512, usize>,
 }
@@ -177,6 +233,10 @@ impl From<SyncTransactionStats> for TransactionStats {
 	fn from(s: SyncTransactionStats) -> Self {
 		TransactionStats {
 			first_seen: s.first_seen,
+			peer_count: s.propagated_to.len(),
+			first_propagated_at: s.first_propagated_at,
+			last_propagated_at: s.last_propagated_at,
+			rebroadcasts: s.rebroadcasts,
 			propagated_to: s.propagated_to
 				.into_iter()
 				.map(|(id, count)| (id, count))
@@ -185,19 +245,120 @@ impl From<SyncTransactionStats> for TransactionStats {
 	}
 }
 
+/// Number of bytes transferred in each direction.
+#[derive(Default, Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct Traffic {
+	/// Bytes received from peers.
+	pub ingress: u64,
+	/// Bytes sent to peers.
+	pub egress: u64,
+}
+
+impl From<sync::TrafficSample> for Traffic {
+	fn from(s: sync::TrafficSample) -> Self {
+		Traffic {
+			ingress: s.ingress,
+			egress: s.egress,
+		}
+	}
+}
+
+/// Traffic of a single message type.
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct Throughput {
+	/// Bytes transferred since the network was started.
+	pub total: Traffic,
+	/// Bytes transferred within the last N seconds, by N.
+	pub windows: BTreeMap<u64, Traffic>,
+}
+
+/// Traffic of a devp2p subprotocol, broken down by message type.
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct ProtocolThroughput {
+	/// Bytes transferred since the network was started.
+	pub total: Traffic,
+	/// Bytes transferred within the last N seconds, by N.
+	pub windows: BTreeMap<u64, Traffic>,
+	/// Traffic by message id.
+	pub messages: BTreeMap<String, Throughput>,
+}
+
+impl ProtocolThroughput {
+	/// Group message traffic by subprotocol name.
+	pub fn by_protocol(traffic: Vec<sync::PacketTraffic>) -> BTreeMap<String, ProtocolThroughput> {
+		fn add(a: &mut Traffic, b: Traffic) {
+			a.ingress += b.ingress;
+			a.egress += b.egress;
+		}
+
+		let mut protocols = BTreeMap::new();
+		for packet in traffic {
+			let protocol: &mut ProtocolThroughput = protocols
+				.entry(String::from_utf8_lossy(&packet.protocol).into_owned())
+				.or_default();
+			let windows: BTreeMap<_, _> = packet.windows.into_iter()
+				.map(|(window, sample)| (window.as_secs(), Traffic::from(sample)))
+				.collect();
+
+			add(&mut protocol.total, packet.total.into());
+			for (window, traffic) in &windows {
+				add(protocol.windows.entry(*window).or_default(), *traffic);
+			}
+			protocol.messages.insert(format!("{:#04x}", packet.packet_id), Throughput {
+				total: packet.total.into(),
+				windows,
+			});
+		}
+		protocols
+	}
+}
+
 /// Chain status.
 #[derive(Default, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChainStatus {
 	/// Describes the gap in the blockchain, if there is one: (first, last)
 	pub block_gap: Option<(U256, U256)>,
+	/// Reorg block import is halted on, awaiting `parity_acknowledgeReorg`.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub halted_reorg: Option<DeepReorg>,
+}
+
+/// Reorg deeper than the configured limit.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepReorg {
+	/// Hash of the block which would cause the reorg.
+	pub hash: H256,
+	/// Number of the block which would cause the reorg.
+	pub number: U256,
+	/// Best block at the time the reorg was detected.
+	pub best_block_hash: H256,
+	/// Common ancestor of the canonical and the new chain.
+	pub ancestor: H256,
+	/// Number of canonical blocks which would be retracted.
+	pub depth: U256,
+}
+
+impl From<chain_notify::DeepReorg> for DeepReorg {
+	fn from(reorg: chain_notify::DeepReorg) -> Self {
+		DeepReorg {
+			hash: reorg.hash,
+			number: reorg.number.into(),
+			best_block_hash: reorg.best,
+			ancestor: reorg.ancestor,
+			depth: reorg.depth.into(),
+		}
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use serde_json;
 	use std::collections::BTreeMap;
-	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus, H512};
+	use std::time::Duration;
+	use sync::{PacketTraffic, TrafficSample};
+	use super::{SyncInfo, SyncDetails, SnapshotRestoration, SyncStatus, Peers, TransactionStats, ChainStatus, DeepReorg, ProtocolThroughput, H256, H512};
 
 	#[test]
 	fn test_serialize_sync_info() {
@@ -206,6 +367,29 @@ mod tests {
 		assert_eq!(serialized, r#"{"startingBlock":"0x0","currentBlock":"0x0","highestBlock":"0x0","warpChunksAmount":null,"warpChunksProcessed":null}"#);
 	}
 
+	#[test]
+	fn test_serialize_sync_details() {
+		let t = SyncInfo {
+			parity: Some(SyncDetails {
+				snapshot: Some(SnapshotRestoration {
+					state_chunks_done: 1.into(),
+					state_chunks_total: 4.into(),
+					block_chunks_done: 2.into(),
+					block_chunks_total: 3.into(),
+				}),
+				block_gap: Some((1.into(), 100.into())),
+				queued_blocks: 5.into(),
+				verified_blocks: 6.into(),
+				queue_mem_used: 1024.into(),
+				queue_mem_limit: 4096.into(),
+				verification_time: 1500.into(),
+			}),
+			..Default::default()
+		};
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"startingBlock":"0x0","currentBlock":"0x0","highestBlock":"0x0","warpChunksAmount":null,"warpChunksProcessed":null,"parity":{"snapshot":{"stateChunksDone":"0x1","stateChunksTotal":"0x4","blockChunksDone":"0x2","blockChunksTotal":"0x3"},"blockGap":["0x1","0x64"],"queuedBlocks":"0x5","verifiedBlocks":"0x6","queueMemUsed":"0x400","queueMemLimit":"0x1000","verificationTime":"0x5dc"}}"#);
+	}
+
 	#[test]
 	fn test_serialize_peers() {
 		let t = Peers::default();
@@ -213,6 +397,24 @@ mod tests {
 		assert_eq!(serialized, r#"{"active":0,"connected":0,"max":0,"peers":[]}"#);
 	}
 
+	#[test]
+	fn test_serialize_protocol_throughput() {
+		let sample = |ingress, egress| TrafficSample { ingress, egress };
+		let packet = |packet_id, total, last_minute, last_five_minutes| PacketTraffic {
+			protocol: *b"eth",
+			packet_id,
+			total,
+			windows: vec![(Duration::from_secs(60), last_minute), (Duration::from_secs(300), last_five_minutes)],
+		};
+
+		let t = ProtocolThroughput::by_protocol(vec![
+			packet(2, sample(100, 50), sample(10, 0), sample(10, 50)),
+			packet(3, sample(1, 2), sample(1, 2), sample(1, 2)),
+		]);
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"eth":{"total":{"ingress":101,"egress":52},"windows":{"60":{"ingress":11,"egress":2},"300":{"ingress":11,"egress":52}},"messages":{"0x02":{"total":{"ingress":100,"egress":50},"windows":{"60":{"ingress":10,"egress":0},"300":{"ingress":10,"egress":50}}},"0x03":{"total":{"ingress":1,"egress":2},"windows":{"60":{"ingress":1,"egress":2},"300":{"ingress":1,"egress":2}}}}}}"#);
+	}
+
 	#[test]
 	fn test_serialize_sync_status() {
 		let t = SyncStatus::None;
@@ -236,16 +438,37 @@ mod tests {
 		assert_eq!(serialized, r#"{"blockGap":["0x1","0x5"]}"#);
 	}
 
+	#[test]
+	fn test_serialize_halted_reorg() {
+		let t = ChainStatus {
+			block_gap: None,
+			halted_reorg: Some(DeepReorg {
+				hash: H256::from_low_u64_be(1),
+				number: 10.into(),
+				best_block_hash: H256::from_low_u64_be(2),
+				ancestor: H256::from_low_u64_be(3),
+				depth: 5.into(),
+			}),
+		};
+
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"blockGap":null,"haltedReorg":{"hash":"0x0000000000000000000000000000000000000000000000000000000000000001","number":"0xa","bestBlockHash":"0x0000000000000000000000000000000000000000000000000000000000000002","ancestor":"0x0000000000000000000000000000000000000000000000000000000000000003","depth":"0x5"}}"#);
+	}
+
 	#[test]
 	fn test_serialize_transaction_stats() {
 		let stats = TransactionStats {
 			first_seen: 100,
+			peer_count: 1,
+			first_propagated_at: 1500000000,
+			last_propagated_at: 1500000060,
+			rebroadcasts: 49,
 			propagated_to: map![
 				H512::from_low_u64_be(10) => 50
 			],
 		};
 
 		let serialized = serde_json::to_string(&stats).unwrap();
-		assert_eq!(serialized, r#"{"firstSeen":100,"propagatedTo":{"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a":50}}"#)
+		assert_eq!(serialized, r#"{"firstSeen":100,"peerCount":1,"firstPropagatedAt":1500000000,"lastPropagatedAt":1500000060,"rebroadcasts":49,"propagatedTo":{"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a":50}}"#)
 	}
 }
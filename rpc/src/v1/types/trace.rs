@@ -672,6 +672,64 @@ impl From<(H256, Executed)> for TraceResultsWithTransactionHash {
 	}
 }
 
+/// Account or storage slot accessed by a replayed transaction.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum StateAccess {
+	/// Account sent from, called, created or queried for its balance or code.
+	Account {
+		/// Address of the account.
+		address: H160,
+	},
+	/// Storage slot read.
+	StorageRead {
+		/// Account owning the storage.
+		address: H160,
+		/// Storage key.
+		key: H256,
+		/// Value read.
+		value: H256,
+	},
+	/// Storage slot written.
+	StorageWrite {
+		/// Account owning the storage.
+		address: H160,
+		/// Storage key.
+		key: H256,
+		/// Value written.
+		value: H256,
+	},
+}
+
+impl From<et::StateAccess> for StateAccess {
+	fn from(access: et::StateAccess) -> Self {
+		match access {
+			et::StateAccess::Account(address) => StateAccess::Account { address },
+			et::StateAccess::StorageRead { address, key, value } => StateAccess::StorageRead { address, key, value },
+			et::StateAccess::StorageWrite { address, key, value } => StateAccess::StorageWrite { address, key, value },
+		}
+	}
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// State accessed by a replayed transaction, in order of access.
+pub struct StateAccessLog {
+	/// The transaction hash.
+	pub transaction_hash: H256,
+	/// Accounts and storage slots read or written by the transaction.
+	pub accesses: Vec<StateAccess>,
+}
+
+impl From<(H256, Vec<et::StateAccess>)> for StateAccessLog {
+	fn from(t: (H256, Vec<et::StateAccess>)) -> Self {
+		StateAccessLog {
+			transaction_hash: t.0,
+			accesses: t.1.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_json;
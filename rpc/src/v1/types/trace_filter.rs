@@ -17,11 +17,13 @@
 //! Trace filter deserialization.
 
 use ethereum_types::H160;
+use jsonrpc_core::{Error as RpcError};
 use types::{
 	ids::BlockId,
 	trace_filter::Filter,
 };
-use v1::types::BlockNumber;
+use v1::types::{BlockNumber, Cursor};
+use v1::helpers::errors::{invalid_params, state_root_unsupported, confirmations_unsupported};
 
 /// Trace filter
 #[derive(Debug, PartialEq, Deserialize)]
@@ -40,29 +42,38 @@ pub struct TraceFilter {
 	pub after: Option<usize>,
 	/// Output amount
 	pub count: Option<usize>,
+	/// Position to continue a paged query from
+	pub cursor: Option<Cursor>,
 }
 
-impl Into<Filter> for TraceFilter {
-	fn into(self) -> Filter {
+impl TraceFilter {
+	/// Convert into a trace filter, rejecting block numbers traces can't be filtered by and cursors,
+	/// which only paged queries follow.
+	pub fn try_into(self) -> Result<Filter, RpcError> {
+		if self.cursor.is_some() {
+			return Err(invalid_params("cursor", "cursor is only supported by trace_filterPage"));
+		}
 		let num_to_id = |num| match num {
-			BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
-			BlockNumber::Num(n) => BlockId::Number(n),
-			BlockNumber::Earliest => BlockId::Earliest,
-			BlockNumber::Latest => BlockId::Latest,
+			BlockNumber::Hash { hash, .. } => Ok(BlockId::Hash(hash)),
+			BlockNumber::Num(n) => Ok(BlockId::Number(n)),
+			BlockNumber::Earliest => Ok(BlockId::Earliest),
+			BlockNumber::Latest => Ok(BlockId::Latest),
 			BlockNumber::Pending => {
 				warn!("Pending traces are not supported and might be removed in future versions. Falling back to Latest");
-				BlockId::Latest
+				Ok(BlockId::Latest)
 			}
+			BlockNumber::StateRoot(_) => Err(state_root_unsupported()),
+			BlockNumber::Confirmed(_) => Err(confirmations_unsupported()),
 		};
-		let start = self.from_block.map_or(BlockId::Latest, &num_to_id);
-		let end = self.to_block.map_or(BlockId::Latest, &num_to_id);
-		Filter {
+		let start = self.from_block.map_or_else(|| Ok(BlockId::Latest), &num_to_id)?;
+		let end = self.to_block.map_or_else(|| Ok(BlockId::Latest), &num_to_id)?;
+		Ok(Filter {
 			range: start..end,
 			from_address: self.from_address.map_or_else(Vec::new, |x| x.into_iter().map(Into::into).collect()),
 			to_address: self.to_address.map_or_else(Vec::new, |x| x.into_iter().map(Into::into).collect()),
 			after: self.after,
 			count: self.count,
-		}
+		})
 	}
 }
 
@@ -83,6 +94,7 @@ mod tests {
 			to_address: None,
 			after: None,
 			count: None,
+			cursor: None,
 		});
 	}
 
@@ -104,6 +116,7 @@ mod tests {
 			to_address: Some(vec![Address::from_low_u64_be(5).into()]),
 			after: 50.into(),
 			count: 100.into(),
+			cursor: None,
 		});
 	}
 }
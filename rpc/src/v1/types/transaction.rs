@@ -21,6 +21,7 @@ use serde::ser::SerializeStruct;
 use machine::executive::{contract_address};
 use vm::CreateContractAddress;
 use ethereum_types::{H160, H256, H512, U64, U256};
+use hash::keccak;
 use miner;
 use types::transaction::{LocalizedTransaction, Action, PendingTransaction, SignedTransaction};
 use v1::types::{Bytes, TransactionCondition};
@@ -69,6 +70,59 @@ pub struct Transaction {
 	pub s: U256,
 	/// Transaction activates at specified block.
 	pub condition: Option<TransactionCondition>,
+	/// 4-byte selector of the called method, only in verbose output.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub method_id: Option<Bytes>,
+	/// Signature of the called method if it is a well-known one, only in verbose output.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub method_name: Option<String>,
+}
+
+/// Signatures of commonly called contract methods, used to decode method selectors.
+const KNOWN_METHODS: &[&str] = &[
+	"transfer(address,uint256)",
+	"transferFrom(address,address,uint256)",
+	"approve(address,uint256)",
+	"increaseAllowance(address,uint256)",
+	"decreaseAllowance(address,uint256)",
+	"mint(address,uint256)",
+	"burn(uint256)",
+	"deposit()",
+	"withdraw(uint256)",
+	"safeTransferFrom(address,address,uint256)",
+	"safeTransferFrom(address,address,uint256,bytes)",
+	"setApprovalForAll(address,bool)",
+	"multicall(bytes[])",
+];
+
+/// Returns the signature of a well-known method with the given selector.
+fn known_method(selector: &[u8]) -> Option<&'static str> {
+	KNOWN_METHODS.iter()
+		.find(|signature| &keccak(signature.as_bytes())[..4] == selector)
+		.cloned()
+}
+
+/// Predicted outcome of importing a transaction to the queue.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionImportSimulation {
+	/// Transaction hash.
+	pub hash: H256,
+	/// Hash of the queued transaction that would be replaced.
+	pub replaces: Option<H256>,
+	/// Predicted position in the pending set or `None` if the transaction would be queued as future.
+	pub pending_position: Option<U256>,
+}
+
+impl TransactionImportSimulation {
+	/// Convert `ImportSimulation` of transaction with given hash.
+	pub fn new(hash: H256, simulation: miner::pool::ImportSimulation) -> Self {
+		TransactionImportSimulation {
+			hash,
+			replaces: simulation.replaces,
+			pending_position: simulation.pending_position.map(Into::into),
+		}
+	}
 }
 
 /// Local Transaction Status
@@ -204,6 +258,8 @@ impl Transaction {
 			r: signature.r().into(),
 			s: signature.s().into(),
 			condition: None,
+			method_id: None,
+			method_name: None,
 		}
 	}
 
@@ -238,6 +294,8 @@ impl Transaction {
 			r: signature.r().into(),
 			s: signature.s().into(),
 			condition: None,
+			method_id: None,
+			method_name: None,
 		}
 	}
 
@@ -247,6 +305,17 @@ impl Transaction {
 		r.condition = r.condition.map(Into::into);
 		r
 	}
+
+	/// Include the selector and, if it is well-known, the signature of the called method.
+	/// Contract creations and plain transfers are left untouched.
+	pub fn with_method_details(mut self) -> Transaction {
+		if self.to.is_some() && self.input.0.len() >= 4 {
+			let selector = &self.input.0[..4];
+			self.method_name = known_method(selector).map(Into::into);
+			self.method_id = Some(Bytes::new(selector.to_vec()));
+		}
+		self
+	}
 }
 
 impl LocalTransactionStatus {
@@ -276,7 +345,10 @@ impl LocalTransactionStatus {
 #[cfg(test)]
 mod tests {
 	use super::{Transaction, LocalTransactionStatus};
+	use ethereum_types::H160;
+	use rustc_hex::FromHex;
 	use serde_json;
+	use v1::types::Bytes;
 
 	#[test]
 	fn test_transaction_serialize() {
@@ -285,6 +357,29 @@ mod tests {
 		assert_eq!(serialized, r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000000","nonce":"0x0","blockHash":null,"blockNumber":null,"transactionIndex":null,"from":"0x0000000000000000000000000000000000000000","to":null,"value":"0x0","gasPrice":"0x0","gas":"0x0","input":"0x","creates":null,"raw":"0x","publicKey":null,"chainId":null,"standardV":"0x0","v":"0x0","r":"0x0","s":"0x0","condition":null}"#);
 	}
 
+	#[test]
+	fn test_transaction_with_method_details() {
+		let call = |input: &str| Transaction {
+			to: Some(H160::from_low_u64_be(1)),
+			input: Bytes::new(input.from_hex().unwrap()),
+			..Default::default()
+		}.with_method_details();
+
+		let transfer = call("a9059cbb000000000000000000000000000000000000000000000000000000000000000a");
+		assert_eq!(transfer.method_id, Some(Bytes::new(vec![0xa9, 0x05, 0x9c, 0xbb])));
+		assert_eq!(transfer.method_name, Some("transfer(address,uint256)".into()));
+		let serialized = serde_json::to_string(&transfer).unwrap();
+		assert!(serialized.ends_with(r#""condition":null,"methodId":"0xa9059cbb","methodName":"transfer(address,uint256)"}"#));
+
+		let unknown = call("12345678");
+		assert_eq!(unknown.method_id, Some(Bytes::new(vec![0x12, 0x34, 0x56, 0x78])));
+		assert_eq!(unknown.method_name, None);
+
+		assert_eq!(call("").method_id, None);
+		let create = Transaction { input: Bytes::new(vec![0xa9, 0x05, 0x9c, 0xbb]), ..Default::default() }.with_method_details();
+		assert_eq!(create.method_id, None);
+	}
+
 	#[test]
 	fn test_local_transaction_status_serialize() {
 		use ethereum_types::H256;
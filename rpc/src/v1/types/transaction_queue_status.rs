@@ -0,0 +1,65 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction queue status.
+
+use miner::pool::{self, TransactionOrigin};
+
+/// Occupancy of the transaction queue by transactions from a single origin.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OriginOccupancy {
+	/// Number of queued transactions.
+	pub count: usize,
+	/// Maximal number of queued transactions, `null` if only the queue limits apply.
+	pub quota: Option<usize>,
+}
+
+/// Transaction queue status.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionQueueStatus {
+	/// Number of queued transactions.
+	pub count: usize,
+	/// Maximal number of queued transactions.
+	pub limit: usize,
+	/// Number of distinct senders.
+	pub senders: usize,
+	/// Transactions from local accounts or treated as local.
+	pub local: OriginOccupancy,
+	/// Transactions submitted over RPC which are not treated as local.
+	pub rpc: OriginOccupancy,
+	/// Transactions received from the network.
+	pub network: OriginOccupancy,
+}
+
+impl From<pool::QueueStatus> for TransactionQueueStatus {
+	fn from(s: pool::QueueStatus) -> Self {
+		let occupancy = |origin| OriginOccupancy {
+			count: s.origins.get(origin),
+			quota: s.origin_quotas.get(origin),
+		};
+
+		TransactionQueueStatus {
+			count: s.status.transaction_count,
+			limit: s.limits.max_count,
+			senders: s.status.senders,
+			local: occupancy(TransactionOrigin::Local),
+			rpc: occupancy(TransactionOrigin::Rpc),
+			network: occupancy(TransactionOrigin::Network),
+		}
+	}
+}
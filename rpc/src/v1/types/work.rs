@@ -20,7 +20,7 @@ use serde::{Serialize, Serializer};
 
 /// The result of an `eth_getWork` call: it differs based on an option
 /// whether to send the block number.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Work {
 	/// The proof-of-work hash.
 	pub pow_hash: H256,
@@ -32,6 +32,16 @@ pub struct Work {
 	pub number: Option<u64>,
 }
 
+/// The result of a `parity_submitWorkStatus` call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmittedWork {
+	/// Hash of the imported block.
+	pub block_hash: H256,
+	/// Whether the solution was for stale work, i.e. newer work has been handed out since.
+	pub stale: bool,
+}
+
 impl Serialize for Work {
 	fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error> where S: Serializer {
 		match self.number.as_ref() {
@@ -40,3 +50,19 @@ impl Serialize for Work {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H256;
+	use serde_json;
+	use super::SubmittedWork;
+
+	#[test]
+	fn should_serialize_submitted_work() {
+		let work = SubmittedWork { block_hash: H256::from_low_u64_be(1), stale: true };
+		assert_eq!(
+			serde_json::to_string(&work).unwrap(),
+			r#"{"blockHash":"0x0000000000000000000000000000000000000000000000000000000000000001","stale":true}"#
+		);
+	}
+}
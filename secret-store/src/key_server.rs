@@ -16,7 +16,7 @@
 
 use std::collections::BTreeSet;
 use std::sync::Arc;
-use futures::{future::{err, result}, Future};
+use futures::{future::{err, join_all, result}, Future};
 use parking_lot::Mutex;
 use crypto::DEFAULT_MAC;
 use crypto::publickey::public_to_address;
@@ -205,6 +205,19 @@ impl DocumentKeyServer for KeyServerImpl {
 		return_session(self.data.lock().cluster.new_decryption_session(key_id,
 			None, requester.clone(), None, true, false))
 	}
+
+	fn restore_document_key_shadows(
+		&self,
+		requests: Vec<(ServerKeyId, Requester)>,
+	) -> Box<dyn Future<Item=Vec<Result<EncryptedDocumentKeyShadow, Error>>, Error=Error> + Send> {
+		// one decryption session per document: the cluster has no session decrypting several documents
+		let data = self.data.lock();
+		let sessions = requests.into_iter()
+			.map(|(key_id, requester)| return_session(data.cluster.new_decryption_session(key_id,
+				None, requester, None, true, false)).then(Ok::<_, Error>))
+			.collect::<Vec<_>>();
+		Box::new(join_all(sessions))
+	}
 }
 
 impl MessageSigner for KeyServerImpl {
@@ -285,6 +298,7 @@ impl KeyServerCore {
 			listen_address: (config.listener_address.address.clone(), config.listener_address.port),
 			allow_connecting_to_higher_nodes: config.allow_connecting_to_higher_nodes,
 			auto_migrate_enabled: config.auto_migrate_enabled,
+			tls: config.tls.clone(),
 		};
 
 		let core = new_network_cluster(executor, cconfig, net_config)?;
@@ -400,6 +414,13 @@ pub mod tests {
 		) -> Box<dyn Future<Item=EncryptedDocumentKeyShadow, Error=Error> + Send> {
 			unimplemented!("test-only")
 		}
+
+		fn restore_document_key_shadows(
+			&self,
+			_requests: Vec<(ServerKeyId, Requester)>,
+		) -> Box<dyn Future<Item=Vec<Result<EncryptedDocumentKeyShadow, Error>>, Error=Error> + Send> {
+			unimplemented!("test-only")
+		}
 	}
 
 	impl MessageSigner for DummyKeyServer {
@@ -438,6 +459,7 @@ pub mod tests {
 				allow_connecting_to_higher_nodes: false,
 				admin_public: None,
 				auto_migrate_enabled: false,
+				tls: None,
 			}).collect();
 		let key_servers_set: BTreeMap<Public, SocketAddr> = configs[0].nodes.iter()
 			.map(|(k, a)| (k.clone(), format!("{}:{}", a.address, a.port).parse().unwrap()))
@@ -707,6 +729,32 @@ pub mod tests {
 		drop(runtime);
 	}
 
+	#[test]
+	fn document_key_shadows_are_restored_per_document() {
+		let _ = ::env_logger::try_init();
+		let (key_servers, _, runtime) = make_key_servers(6122, 1);
+
+		// generate key of the first document only
+		let documents: Vec<_> = (0..2).map(|_| Random.generate().unwrap().secret().clone()).collect();
+		let secret = Random.generate().unwrap().secret().clone();
+		let signatures: Vec<Requester> = documents.iter()
+			.map(|document| crypto::publickey::sign(&secret, document).unwrap().into())
+			.collect();
+		key_servers[0].generate_document_key(*documents[0], signatures[0].clone(), 0).wait().unwrap();
+
+		// the unknown document fails on its own, without failing the batch
+		let shadows = key_servers[0].restore_document_key_shadows(vec![
+			(*documents[0], signatures[0].clone()),
+			(*documents[1], signatures[1].clone()),
+		]).wait().unwrap();
+		assert_eq!(shadows.len(), 2);
+		let shadow = shadows[0].as_ref().unwrap();
+		assert!(shadow.common_point.is_some());
+		assert!(shadow.decrypt_shadows.is_some());
+		assert!(shadows[1].is_err());
+		drop(runtime);
+	}
+
 	#[test]
 	fn servers_set_change_session_works_over_network() {
 		// TODO [Test]
@@ -29,6 +29,7 @@ use crypto::publickey::KeyPair;
 use parity_runtime::Executor;
 use blockchain::SigningKeyPair;
 use key_server_cluster::{Error, NodeId, ClusterConfiguration};
+use types::TlsConfiguration;
 use key_server_cluster::cluster_connections::{ConnectionProvider, Connection, ConnectionManager};
 use key_server_cluster::connection_trigger::{Maintain, ConnectionTrigger};
 use key_server_cluster::cluster_message_processor::MessageProcessor;
@@ -36,7 +37,7 @@ use key_server_cluster::io::{DeadlineStatus, ReadMessage, SharedTcpStream,
 	read_encrypted_message, WriteMessage, write_encrypted_message};
 use key_server_cluster::message::{self, ClusterMessage, Message};
 use key_server_cluster::net::{accept_connection as io_accept_connection,
-	connect as io_connect, Connection as IoConnection};
+	connect as io_connect, Connection as IoConnection, TlsContext};
 
 /// Empty future.
 pub type BoxedEmptyFuture = Box<dyn Future<Item = (), Error = ()> + Send>;
@@ -63,6 +64,8 @@ pub struct NetConnectionsManagerConfig {
 	/// True if we should autostart key servers set change session when servers set changes?
 	/// This will only work when servers set is configured using KeyServerSet contract.
 	pub auto_migrate_enabled: bool,
+	/// Mutual TLS configuration. If None, plain TCP connections are used.
+	pub tls: Option<TlsConfiguration>,
 }
 
 /// Network connections manager.
@@ -81,6 +84,8 @@ struct NetConnectionsData {
 	executor: Executor,
 	/// Key pair of this node.
 	self_key_pair: Arc<dyn SigningKeyPair>,
+	/// Mutual TLS context, if enabled.
+	tls: Option<Arc<TlsContext>>,
 	/// Network messages processor.
 	message_processor: Arc<dyn MessageProcessor>,
 	/// Connections trigger.
@@ -131,6 +136,10 @@ impl NetConnectionsManager {
 		let listen_address = make_socket_address(
 			&net_config.listen_address.0,
 			net_config.listen_address.1)?;
+		let tls = match net_config.tls {
+			Some(ref tls) => Some(Arc::new(TlsContext::new(tls)?)),
+			None => None,
+		};
 
 		Ok(NetConnectionsManager {
 			listen_address,
@@ -139,6 +148,7 @@ impl NetConnectionsManager {
 				executor,
 				message_processor,
 				self_key_pair: config.self_key_pair.clone(),
+				tls,
 				trigger: Mutex::new(trigger),
 				container,
 			}),
@@ -345,7 +355,7 @@ fn net_accept_connection(
 
 /// Accept incoming connection future.
 fn net_accept_connection_future(data: Arc<NetConnectionsData>, stream: TcpStream) -> BoxedEmptyFuture {
-	Box::new(io_accept_connection(stream, data.self_key_pair.clone())
+	Box::new(io_accept_connection(stream, data.self_key_pair.clone(), data.tls.clone())
 		.then(move |result| net_process_connection_result(data, None, result))
 		.then(|_| future::ok(())))
 }
@@ -364,7 +374,7 @@ fn net_connect_future(
 	remote: SocketAddr,
 ) -> BoxedEmptyFuture {
 	let disconnected_nodes = data.container.disconnected_nodes();
-	Box::new(io_connect(&remote, data.self_key_pair.clone(), disconnected_nodes)
+	Box::new(io_connect(&remote, data.self_key_pair.clone(), disconnected_nodes, data.tls.clone())
 		.then(move |result| net_process_connection_result(data, Some(remote), result))
 		.then(|_| future::ok(())))
 }
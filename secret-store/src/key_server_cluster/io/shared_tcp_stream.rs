@@ -18,18 +18,67 @@ use std::sync::Arc;
 use std::net::Shutdown;
 use std::io::{Read, Write, Error};
 use futures::Poll;
+use parking_lot::Mutex;
 use tokio_io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpStream;
+use tokio_rustls::{client, server};
 
-/// Read+Write implementation for Arc<TcpStream>.
+/// TLS session over TCP stream, established either by connecting or by accepting node.
+pub enum TlsStream {
+	/// Outbound TLS session.
+	Client(client::TlsStream<TcpStream>),
+	/// Inbound TLS session.
+	Server(server::TlsStream<TcpStream>),
+}
+
+impl TlsStream {
+	fn tcp_stream(&self) -> &TcpStream {
+		match *self {
+			TlsStream::Client(ref stream) => stream.get_ref().0,
+			TlsStream::Server(ref stream) => stream.get_ref().0,
+		}
+	}
+}
+
+impl Read for TlsStream {
+	fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+		match *self {
+			TlsStream::Client(ref mut stream) => stream.read(buf),
+			TlsStream::Server(ref mut stream) => stream.read(buf),
+		}
+	}
+}
+
+impl Write for TlsStream {
+	fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+		match *self {
+			TlsStream::Client(ref mut stream) => stream.write(buf),
+			TlsStream::Server(ref mut stream) => stream.write(buf),
+		}
+	}
+
+	fn flush(&mut self) -> Result<(), Error> {
+		match *self {
+			TlsStream::Client(ref mut stream) => stream.flush(),
+			TlsStream::Server(ref mut stream) => stream.flush(),
+		}
+	}
+}
+
+enum SharedIo {
+	Plain(Arc<TcpStream>),
+	Tls(Arc<Mutex<TlsStream>>),
+}
+
+/// Read+Write implementation for Arc<TcpStream> or shared TLS session.
 pub struct SharedTcpStream {
-	io: Arc<TcpStream>,
+	io: SharedIo,
 }
 
 impl SharedTcpStream {
 	pub fn new(a: Arc<TcpStream>) -> Self {
 		SharedTcpStream {
-			io: a,
+			io: SharedIo::Plain(a),
 		}
 	}
 }
@@ -40,32 +89,57 @@ impl From<TcpStream> for SharedTcpStream {
 	}
 }
 
+impl From<TlsStream> for SharedTcpStream {
+	fn from(a: TlsStream) -> Self {
+		SharedTcpStream {
+			io: SharedIo::Tls(Arc::new(Mutex::new(a))),
+		}
+	}
+}
+
 impl AsyncRead for SharedTcpStream {}
 
 impl AsyncWrite for SharedTcpStream {
 	fn shutdown(&mut self) -> Poll<(), Error> {
-		self.io.shutdown(Shutdown::Both).map(Into::into)
+		match self.io {
+			SharedIo::Plain(ref io) => io.shutdown(Shutdown::Both).map(Into::into),
+			SharedIo::Tls(ref io) => io.lock().tcp_stream().shutdown(Shutdown::Both).map(Into::into),
+		}
 	}
 }
 
 impl Read for SharedTcpStream {
 	fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
-		Read::read(&mut (&*self.io as &TcpStream), buf)
+		match self.io {
+			SharedIo::Plain(ref io) => Read::read(&mut (&**io as &TcpStream), buf),
+			SharedIo::Tls(ref io) => io.lock().read(buf),
+		}
 	}
 }
 
 impl Write for SharedTcpStream {
 	fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
-		Write::write(&mut (&*self.io as &TcpStream), buf)
+		match self.io {
+			SharedIo::Plain(ref io) => Write::write(&mut (&**io as &TcpStream), buf),
+			SharedIo::Tls(ref io) => io.lock().write(buf),
+		}
 	}
 
 	fn flush(&mut self) -> Result<(), Error> {
-		Write::flush(&mut (&*self.io as &TcpStream))
+		match self.io {
+			SharedIo::Plain(ref io) => Write::flush(&mut (&**io as &TcpStream)),
+			SharedIo::Tls(ref io) => io.lock().flush(),
+		}
 	}
 }
 
 impl Clone for SharedTcpStream {
 	fn clone(&self) -> Self {
-		SharedTcpStream::new(self.io.clone())
+		SharedTcpStream {
+			io: match self.io {
+				SharedIo::Plain(ref io) => SharedIo::Plain(io.clone()),
+				SharedIo::Tls(ref io) => SharedIo::Tls(io.clone()),
+			},
+		}
 	}
 }
@@ -18,31 +18,43 @@ use std::io;
 use std::sync::Arc;
 use std::net::SocketAddr;
 use std::time::Duration;
-use futures::{Future, Poll};
+use futures::{Future, Poll, Async};
 use tokio::net::TcpStream;
 use blockchain::SigningKeyPair;
 use key_server_cluster::Error;
-use key_server_cluster::io::{accept_handshake, Handshake, Deadline, deadline};
-use key_server_cluster::net::Connection;
+use key_server_cluster::io::{accept_handshake, Handshake, Deadline, deadline, SharedTcpStream};
+use key_server_cluster::net::{Connection, TlsContext, TlsHandshake};
 
 /// Create future for accepting incoming connection.
-pub fn accept_connection(stream: TcpStream, self_key_pair: Arc<dyn SigningKeyPair>) -> Deadline<AcceptConnection> {
+pub fn accept_connection(stream: TcpStream, self_key_pair: Arc<dyn SigningKeyPair>, tls: Option<Arc<TlsContext>>) -> Deadline<AcceptConnection> {
 	// TODO: This could fail so it would be better either to accept the
 	// address as a separate argument or return a result.
 	let address = stream.peer_addr().expect("Unable to determine tcp peer address");
 
+	let state = match tls {
+		Some(tls) => AcceptState::TlsAccept(tls.accept(stream)),
+		None => AcceptState::Handshake(accept_handshake(SharedTcpStream::from(stream), self_key_pair.clone())),
+	};
 	let accept = AcceptConnection {
-		handshake: accept_handshake(stream, self_key_pair),
+		state: state,
 		address: address,
+		self_key_pair: self_key_pair,
 	};
 
 	deadline(Duration::new(5, 0), accept).expect("Failed to create timeout")
 }
 
+enum AcceptState {
+	TlsAccept(TlsHandshake),
+	Handshake(Handshake<SharedTcpStream>),
+	Accepted,
+}
+
 /// Future for accepting incoming connection.
 pub struct AcceptConnection {
-	handshake: Handshake<TcpStream>,
+	state: AcceptState,
 	address: SocketAddr,
+	self_key_pair: Arc<dyn SigningKeyPair>,
 }
 
 impl Future for AcceptConnection {
@@ -50,17 +62,36 @@ impl Future for AcceptConnection {
 	type Error = io::Error;
 
 	fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
-		let (stream, result) = try_ready!(self.handshake.poll());
-		let result = match result {
-			Ok(result) => result,
-			Err(err) => return Ok(Err(err).into()),
+		let (next, result) = match self.state {
+			AcceptState::TlsAccept(ref mut future) => {
+				let stream = match try_ready!(future.poll()) {
+					Ok(stream) => stream,
+					Err(err) => return Ok(Err(err).into()),
+				};
+				(AcceptState::Handshake(accept_handshake(stream, self.self_key_pair.clone())), Async::NotReady)
+			},
+			AcceptState::Handshake(ref mut future) => {
+				let (stream, result) = try_ready!(future.poll());
+				let result = match result {
+					Ok(result) => result,
+					Err(err) => return Ok(Err(err).into()),
+				};
+				let connection = Connection {
+					stream: stream,
+					address: self.address,
+					node_id: result.node_id,
+					key: result.shared_key,
+				};
+				(AcceptState::Accepted, Async::Ready(Ok(connection)))
+			},
+			AcceptState::Accepted => panic!("poll AcceptConnection after it's done"),
 		};
-		let connection = Connection {
-			stream: stream.into(),
-			address: self.address,
-			node_id: result.node_id,
-			key: result.shared_key,
-		};
-		Ok(Ok(connection).into())
+
+		self.state = next;
+		match result {
+			// by polling again, we register new future
+			Async::NotReady => self.poll(),
+			result => Ok(result)
+		}
 	}
 }
@@ -23,16 +23,22 @@ use futures::{Future, Poll, Async};
 use tokio::net::{TcpStream, tcp::ConnectFuture};
 use blockchain::SigningKeyPair;
 use key_server_cluster::{Error, NodeId};
-use key_server_cluster::io::{handshake, Handshake, Deadline, deadline};
-use key_server_cluster::net::Connection;
+use key_server_cluster::io::{handshake, Handshake, Deadline, deadline, SharedTcpStream};
+use key_server_cluster::net::{Connection, TlsContext, TlsHandshake};
 
 /// Create future for connecting to other node.
-pub fn connect(address: &SocketAddr, self_key_pair: Arc<dyn SigningKeyPair>, trusted_nodes: BTreeSet<NodeId>) -> Deadline<Connect> {
+pub fn connect(
+	address: &SocketAddr,
+	self_key_pair: Arc<dyn SigningKeyPair>,
+	trusted_nodes: BTreeSet<NodeId>,
+	tls: Option<Arc<TlsContext>>,
+) -> Deadline<Connect> {
 	let connect = Connect {
 		state: ConnectState::TcpConnect(TcpStream::connect(address)),
 		address: address.clone(),
 		self_key_pair: self_key_pair,
 		trusted_nodes: trusted_nodes,
+		tls: tls,
 	};
 
 	deadline(Duration::new(5, 0), connect).expect("Failed to create timeout")
@@ -40,7 +46,8 @@ pub fn connect(address: &SocketAddr, self_key_pair: Arc<dyn SigningKeyPair>, tru
 
 enum ConnectState {
 	TcpConnect(ConnectFuture),
-	Handshake(Handshake<TcpStream>),
+	TlsConnect(TlsHandshake),
+	Handshake(Handshake<SharedTcpStream>),
 	Connected,
 }
 
@@ -50,6 +57,7 @@ pub struct Connect {
 	address: SocketAddr,
 	self_key_pair: Arc<dyn SigningKeyPair>,
 	trusted_nodes: BTreeSet<NodeId>,
+	tls: Option<Arc<TlsContext>>,
 }
 
 impl Future for Connect {
@@ -60,6 +68,19 @@ impl Future for Connect {
 		let (next, result) = match self.state {
 			ConnectState::TcpConnect(ref mut future) => {
 				let stream = try_ready!(future.poll());
+				match self.tls {
+					Some(ref tls) => (ConnectState::TlsConnect(tls.connect(stream)), Async::NotReady),
+					None => {
+						let handshake = handshake(SharedTcpStream::from(stream), self.self_key_pair.clone(), self.trusted_nodes.clone());
+						(ConnectState::Handshake(handshake), Async::NotReady)
+					},
+				}
+			},
+			ConnectState::TlsConnect(ref mut future) => {
+				let stream = match try_ready!(future.poll()) {
+					Ok(stream) => stream,
+					Err(err) => return Ok(Async::Ready(Err(err))),
+				};
 				let handshake = handshake(stream, self.self_key_pair.clone(), self.trusted_nodes.clone());
 				(ConnectState::Handshake(handshake), Async::NotReady)
 			},
@@ -70,7 +91,7 @@ impl Future for Connect {
 					Err(err) => return Ok(Async::Ready(Err(err))),
 				};
 				let connection = Connection {
-					stream: stream.into(),
+					stream: stream,
 					address: self.address,
 					node_id: result.node_id,
 					key: result.shared_key,
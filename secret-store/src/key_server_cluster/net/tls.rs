@@ -0,0 +1,213 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::sync::Arc;
+use ethereum_types::H256;
+use futures::Future;
+use tokio::net::TcpStream;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use tokio_rustls::rustls::{AllowAnyAuthenticatedClient, Certificate, ClientConfig,
+	RootCertStore, ServerConfig, Session};
+use tokio_rustls::webpki::{DNSName, DNSNameRef};
+use crypto::digest;
+use rustls_pem::{read_certificates, read_private_key};
+use key_server_cluster::Error;
+use key_server_cluster::io::{SharedTcpStream, TlsStream};
+use types::TlsConfiguration;
+
+/// Future establishing TLS session over TCP stream.
+pub type TlsHandshake = Box<dyn Future<Item = Result<SharedTcpStream, Error>, Error = io::Error> + Send>;
+
+/// Mutual TLS context, shared by all connections of this node.
+pub struct TlsContext {
+	/// Connector for outbound connections.
+	connector: TlsConnector,
+	/// Acceptor for inbound connections.
+	acceptor: TlsAcceptor,
+	/// DNS name that certificates of all nodes are issued for.
+	server_name: DNSName,
+	/// Fingerprints of allowed peers certificates.
+	pinned_certificates: Arc<BTreeSet<H256>>,
+}
+
+impl TlsContext {
+	/// Load certificates and private key from configured files.
+	pub fn new(config: &TlsConfiguration) -> Result<Self, Error> {
+		let certificates = read_certificates(&config.certificate_path).map_err(|e| Error::Internal(e.to_string()))?;
+		let private_key = read_private_key(&config.private_key_path).map_err(|e| Error::Internal(e.to_string()))?;
+
+		let mut roots = RootCertStore::empty();
+		let (valid, _) = roots.add_pem_file(&mut BufReader::new(File::open(&config.ca_certificates_path)?))
+			.map_err(|_| Error::Internal(format!("Invalid CA certificates file {}", config.ca_certificates_path)))?;
+		if valid == 0 {
+			return Err(Error::Internal(format!("No CA certificates found in {}", config.ca_certificates_path)));
+		}
+
+		let server_name = DNSNameRef::try_from_ascii_str(&config.server_name)
+			.map_err(|_| Error::Internal(format!("Invalid TLS server name {}", config.server_name)))?
+			.to_owned();
+
+		let mut server_config = ServerConfig::new(AllowAnyAuthenticatedClient::new(roots.clone()));
+		server_config.set_single_cert(certificates.clone(), private_key.clone())
+			.map_err(|e| Error::Internal(format!("Invalid TLS certificate or private key: {}", e)))?;
+
+		let mut client_config = ClientConfig::new();
+		client_config.root_store = roots;
+		client_config.set_single_client_cert(certificates, private_key);
+
+		Ok(TlsContext {
+			connector: Arc::new(client_config).into(),
+			acceptor: Arc::new(server_config).into(),
+			server_name,
+			pinned_certificates: Arc::new(config.pinned_certificates.clone()),
+		})
+	}
+
+	/// Establish TLS session over outbound TCP stream.
+	pub fn connect(&self, stream: TcpStream) -> TlsHandshake {
+		let pinned_certificates = self.pinned_certificates.clone();
+		Box::new(self.connector.connect(self.server_name.as_ref(), stream)
+			.map(move |stream| -> Result<SharedTcpStream, Error> {
+				check_pinned_certificate(&pinned_certificates, stream.get_ref().1)?;
+				Ok(TlsStream::Client(stream).into())
+			}))
+	}
+
+	/// Establish TLS session over inbound TCP stream.
+	pub fn accept(&self, stream: TcpStream) -> TlsHandshake {
+		let pinned_certificates = self.pinned_certificates.clone();
+		Box::new(self.acceptor.accept(stream)
+			.map(move |stream| -> Result<SharedTcpStream, Error> {
+				check_pinned_certificate(&pinned_certificates, stream.get_ref().1)?;
+				Ok(TlsStream::Server(stream).into())
+			}))
+	}
+}
+
+/// SHA-256 fingerprint of DER-encoded certificate.
+pub fn certificate_fingerprint(certificate: &Certificate) -> H256 {
+	H256::from_slice(&digest::sha256(&certificate.0))
+}
+
+/// Check that the peer has presented one of pinned certificates (if any).
+fn check_pinned_certificate(pinned_certificates: &BTreeSet<H256>, session: &dyn Session) -> Result<(), Error> {
+	if pinned_certificates.is_empty() {
+		return Ok(());
+	}
+
+	let fingerprint = session.get_peer_certificates()
+		.and_then(|certificates| certificates.first().map(certificate_fingerprint));
+	match fingerprint {
+		Some(ref fingerprint) if pinned_certificates.contains(fingerprint) => Ok(()),
+		Some(fingerprint) => Err(Error::Internal(format!("Peer certificate {:x} is not pinned", fingerprint))),
+		None => Err(Error::Internal("Peer has not presented a certificate".into())),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeSet;
+	use futures::{Future, Stream};
+	use tokio::net::{TcpListener, TcpStream};
+	use tokio::runtime::current_thread::Runtime;
+	use tokio_rustls::rustls::Certificate;
+	use rustls_pem::read_certificates;
+	use ethereum_types::H256;
+	use key_server_cluster::Error;
+	use types::TlsConfiguration;
+	use super::{certificate_fingerprint, TlsContext};
+
+	fn res(file: &str) -> String {
+		format!("{}/res/tls/{}", env!("CARGO_MANIFEST_DIR"), file)
+	}
+
+	fn fingerprint(node: &str) -> H256 {
+		certificate_fingerprint(&read_certificates(&res(&format!("{}.pem", node))).unwrap()[0])
+	}
+
+	fn context(node: &str, pinned_certificates: BTreeSet<H256>) -> TlsContext {
+		TlsContext::new(&TlsConfiguration {
+			certificate_path: res(&format!("{}.pem", node)),
+			private_key_path: res(&format!("{}.key", node)),
+			ca_certificates_path: res("ca.pem"),
+			server_name: "localhost".into(),
+			pinned_certificates,
+		}).unwrap()
+	}
+
+	/// Handshake of `client` connecting to `server`, returns whether each side accepted the other.
+	fn handshake(server: &TlsContext, client: &TlsContext) -> (Result<(), Error>, Result<(), Error>) {
+		let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+		let addr = listener.local_addr().unwrap();
+		let accept = listener.incoming().into_future()
+			.map_err(|(e, _)| e)
+			.and_then(|(stream, _)| server.accept(stream.expect("listener is open")));
+		let connect = TcpStream::connect(&addr)
+			.and_then(|stream| client.connect(stream));
+
+		let (accepted, connected) = Runtime::new().unwrap().block_on(accept.join(connect)).unwrap();
+		(accepted.map(|_| ()), connected.map(|_| ()))
+	}
+
+	#[test]
+	fn certificate_fingerprint_is_sha256_of_der() {
+		let fingerprint = certificate_fingerprint(&Certificate(b"abc".to_vec()));
+		assert_eq!(format!("{:x}", fingerprint), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+
+		let pinned: BTreeSet<_> = vec![fingerprint].into_iter().collect();
+		assert!(pinned.contains(&certificate_fingerprint(&Certificate(b"abc".to_vec()))));
+		assert!(!pinned.contains(&certificate_fingerprint(&Certificate(b"abd".to_vec()))));
+	}
+
+	#[test]
+	fn handshake_succeeds_with_pinned_certificates() {
+		let node1 = context("node1", vec![fingerprint("node2")].into_iter().collect());
+		let node2 = context("node2", vec![fingerprint("node1")].into_iter().collect());
+
+		let (accepted, connected) = handshake(&node1, &node2);
+		assert!(accepted.is_ok());
+		assert!(connected.is_ok());
+
+		// every certificate issued by the CA is accepted if none is pinned
+		let (accepted, connected) = handshake(&context("node1", BTreeSet::new()), &context("node2", BTreeSet::new()));
+		assert!(accepted.is_ok());
+		assert!(connected.is_ok());
+	}
+
+	#[test]
+	fn handshake_rejects_unpinned_certificates() {
+		// node1 only trusts itself
+		let node1 = context("node1", vec![fingerprint("node1")].into_iter().collect());
+		let node2 = context("node2", BTreeSet::new());
+
+		let (accepted, connected) = handshake(&node1, &node2);
+		match accepted {
+			Err(Error::Internal(ref message)) => assert!(message.contains("is not pinned"), "{}", message),
+			_ => panic!("unpinned client certificate should be rejected"),
+		}
+		assert!(connected.is_ok());
+
+		let (accepted, connected) = handshake(&node2, &node1);
+		assert!(accepted.is_ok());
+		match connected {
+			Err(Error::Internal(ref message)) => assert!(message.contains("is not pinned"), "{}", message),
+			_ => panic!("unpinned server certificate should be rejected"),
+		}
+	}
+}
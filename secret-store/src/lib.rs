@@ -27,11 +27,13 @@ extern crate parity_runtime;
 extern crate parking_lot;
 extern crate percent_encoding;
 extern crate rustc_hex;
+extern crate rustls_pem;
 extern crate serde;
 extern crate serde_json;
 extern crate tiny_keccak;
 extern crate tokio;
 extern crate tokio_io;
+extern crate tokio_rustls;
 extern crate tokio_service;
 extern crate url;
 extern crate jsonrpc_server_utils;
@@ -73,7 +75,7 @@ use kvdb_rocksdb::{Database, DatabaseConfig};
 use parity_runtime::Executor;
 
 pub use types::{ServerKeyId, EncryptedDocumentKey, RequestSignature, Public,
-	Error, NodeAddress, ServiceConfiguration, ClusterConfiguration};
+	Error, NodeAddress, ServiceConfiguration, ClusterConfiguration, TlsConfiguration};
 pub use traits::KeyServer;
 pub use blockchain::{SecretStoreChain, SigningKeyPair, ContractAddress, BlockId, BlockNumber, NewBlocksNotify, Filter};
 pub use self::node_key_pair::PlainNodeKeyPair;
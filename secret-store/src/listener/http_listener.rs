@@ -32,7 +32,8 @@ use futures::{future, Future, Stream};
 use percent_encoding::percent_decode;
 
 use traits::KeyServer;
-use serialization::{SerializableEncryptedDocumentKeyShadow, SerializableBytes, SerializablePublic};
+use serialization::{SerializableEncryptedDocumentKeyShadow, SerializableDocumentKeyShadowResult, SerializableBytes,
+	SerializablePublic, SerializableH256, SerializableSignature};
 use types::{Error, Public, MessageHash, NodeAddress, RequestSignature, ServerKeyId,
 	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId};
 use jsonrpc_server_utils::cors::{self, AllowCors, AccessControlAllowOrigin};
@@ -44,12 +45,17 @@ use jsonrpc_server_utils::cors::{self, AllowCors, AccessControlAllowOrigin};
 /// To get public portion of server key:			GET			/server/{server_key_id}/{signature}
 /// To get document key:							GET			/{server_key_id}/{signature}
 /// To get document key shadow:						GET			/shadow/{server_key_id}/{signature}
+/// To get document key shadows of many documents:	POST		/shadow/batch + BODY: json array of [server_key_id, signature] pairs
 /// To generate Schnorr signature with server key:	GET			/schnorr/{server_key_id}/{signature}/{message_hash}
 /// To generate ECDSA signature with server key:	GET			/ecdsa/{server_key_id}/{signature}/{message_hash}
 /// To change servers set:							POST		/admin/servers_set_change/{old_signature}/{new_signature} + BODY: json array of hex-encoded nodes ids
 
 type CorsDomains = Option<Vec<AccessControlAllowOrigin>>;
 
+/// Maximal number of documents in a single batched document key shadow request. Each of them
+/// runs its own decryption session.
+const MAX_SHADOW_BATCH_SIZE: usize = 256;
+
 pub struct KeyServerHttpListener {
 	_executor: Executor,
 	_handler: Arc<KeyServerSharedHttpHandler>,
@@ -72,6 +78,8 @@ enum Request {
 	GetDocumentKey(ServerKeyId, RequestSignature),
 	/// Request shadow of encryption key of given document for given requestor.
 	GetDocumentKeyShadow(ServerKeyId, RequestSignature),
+	/// Request shadows of encryption keys of given documents.
+	GetDocumentKeyShadows(Vec<(ServerKeyId, RequestSignature)>),
 	/// Generate Schnorr signature for the message.
 	SchnorrSignMessage(ServerKeyId, RequestSignature, MessageHash),
 	/// Generate ECDSA signature for the message.
@@ -180,6 +188,12 @@ impl KeyServerHttpHandler {
 				Box::new(result(self.key_server())
 					.and_then(move |key_server| key_server.restore_document_key_shadow(document, signature.into()))
 					.then(move |result| ok(return_document_key_shadow("GetDocumentKeyShadow", &req_uri, cors, result)))),
+			Request::GetDocumentKeyShadows(documents) =>
+				Box::new(result(self.key_server())
+					.and_then(move |key_server| key_server.restore_document_key_shadows(
+						documents.into_iter().map(|(document, signature)| (document, signature.into())).collect(),
+					))
+					.then(move |result| ok(return_document_key_shadows("GetDocumentKeyShadows", &req_uri, cors, result)))),
 			Request::SchnorrSignMessage(document, signature, message_hash) =>
 				Box::new(result(self.key_server())
 					.and_then(move |key_server| key_server.sign_message_schnorr(
@@ -286,11 +300,29 @@ fn return_document_key_shadow(
 	cors: AllowCors<AccessControlAllowOrigin>,
 	document_key_shadow: Result<EncryptedDocumentKeyShadow, Error>,
 ) -> HttpResponse<Body> {
-	return_bytes(req_type, req_uri, cors, document_key_shadow.map(|k| Some(SerializableEncryptedDocumentKeyShadow {
+	return_bytes(req_type, req_uri, cors, document_key_shadow.map(|k| Some(serializable_document_key_shadow(k))))
+}
+
+fn return_document_key_shadows(
+	req_type: &str,
+	req_uri: &Uri,
+	cors: AllowCors<AccessControlAllowOrigin>,
+	document_key_shadows: Result<Vec<Result<EncryptedDocumentKeyShadow, Error>>, Error>,
+) -> HttpResponse<Body> {
+	return_bytes(req_type, req_uri, cors, document_key_shadows.map(|shadows| Some(shadows.into_iter()
+		.map(|shadow| match shadow {
+			Ok(k) => SerializableDocumentKeyShadowResult::Ok(serializable_document_key_shadow(k)),
+			Err(err) => SerializableDocumentKeyShadowResult::Err(err.to_string()),
+		})
+		.collect::<Vec<_>>())))
+}
+
+fn serializable_document_key_shadow(k: EncryptedDocumentKeyShadow) -> SerializableEncryptedDocumentKeyShadow {
+	SerializableEncryptedDocumentKeyShadow {
 		decrypted_secret: k.decrypted_secret.into(),
 		common_point: k.common_point.expect("always filled when requesting document_key_shadow; qed").into(),
 		decrypt_shadows: k.decrypt_shadows.expect("always filled when requesting document_key_shadow; qed").into_iter().map(Into::into).collect()
-	})))
+	}
 }
 
 fn return_bytes<T: Serialize>(
@@ -381,6 +413,10 @@ fn parse_request(method: &HttpMethod, uri_path: &str, body: &[u8]) -> Request {
 		return parse_admin_request(method, path, body);
 	}
 
+	if path[0] == "shadow" && path.len() == 2 && path[1] == "batch" {
+		return parse_shadow_batch_request(method, body);
+	}
+
 	let is_known_prefix = &path[0] == "shadow" || &path[0] == "schnorr" || &path[0] == "ecdsa" || &path[0] == "server";
 	let (prefix, args_offset) = if is_known_prefix { (&*path[0], 1) } else { ("", 0) };
 	let args_count = path.len() - args_offset;
@@ -447,6 +483,25 @@ fn parse_admin_request(method: &HttpMethod, path: Vec<String>, body: &[u8]) -> R
 		new_servers_set.into_iter().map(Into::into).collect())
 }
 
+fn parse_shadow_batch_request(method: &HttpMethod, body: &[u8]) -> Request {
+	if *method != HttpMethod::POST {
+		return Request::Invalid;
+	}
+
+	let documents: Vec<(SerializableH256, SerializableSignature)> = match serde_json::from_slice(body) {
+		Ok(documents) => documents,
+		_ => return Request::Invalid,
+	};
+
+	if documents.is_empty() || documents.len() > MAX_SHADOW_BATCH_SIZE {
+		return Request::Invalid;
+	}
+
+	Request::GetDocumentKeyShadows(documents.into_iter()
+		.map(|(document, signature)| (document.into(), signature.into()))
+		.collect())
+}
+
 #[cfg(test)]
 mod tests {
 	use std::sync::Arc;
@@ -503,6 +558,18 @@ mod tests {
 		assert_eq!(parse_request(&HttpMethod::GET, "/shadow/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", Default::default()),
 			Request::GetDocumentKeyShadow(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
 				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()));
+		// POST		/shadow/batch + body												=> get document key shadows
+		assert_eq!(parse_request(&HttpMethod::POST, "/shadow/batch",
+			&r#"[["0x0000000000000000000000000000000000000000000000000000000000000001",
+				"0xa199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01"],
+				["0x0000000000000000000000000000000000000000000000000000000000000002",
+				"0xb199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01"]]"#.as_bytes()),
+			Request::GetDocumentKeyShadows(vec![
+				(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
+					"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()),
+				(H256::from_str("0000000000000000000000000000000000000000000000000000000000000002").unwrap(),
+					"b199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap()),
+			]));
 		// GET		/schnorr/{server_key_id}/{signature}/{message_hash}					=> schnorr-sign message with server key
 		assert_eq!(parse_request(&HttpMethod::GET, "/schnorr/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/281b6bf43cb86d0dc7b98e1b7def4a80f3ce16d28d2308f934f116767306f06c", Default::default()),
 			Request::SchnorrSignMessage(H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap(),
@@ -544,5 +611,8 @@ mod tests {
 			Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::POST, "/admin/servers_set_change/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", "".as_bytes()),
 			Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::GET, "/shadow/batch", "[]".as_bytes()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/shadow/batch", "[]".as_bytes()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/shadow/batch", r#"[["0x01", "0x02"]]"#.as_bytes()), Request::Invalid);
 	}
 }
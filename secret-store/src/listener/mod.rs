@@ -126,6 +126,13 @@ impl DocumentKeyServer for Listener {
 	) -> Box<dyn Future<Item=EncryptedDocumentKeyShadow, Error=Error> + Send> {
 		self.key_server.restore_document_key_shadow(key_id, requester)
 	}
+
+	fn restore_document_key_shadows(
+		&self,
+		requests: Vec<(ServerKeyId, Requester)>,
+	) -> Box<dyn Future<Item=Vec<Result<EncryptedDocumentKeyShadow, Error>>, Error=Error> + Send> {
+		self.key_server.restore_document_key_shadows(requests)
+	}
 }
 
 impl MessageSigner for Listener {
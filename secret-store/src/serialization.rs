@@ -153,6 +153,16 @@ pub struct SerializableEncryptedDocumentKeyShadow {
 	pub decrypt_shadows: Vec<SerializableBytes>,
 }
 
+/// Serializable result of a single document key shadow retrieval from a batch.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SerializableDocumentKeyShadowResult {
+	/// Document key shadow.
+	Ok(SerializableEncryptedDocumentKeyShadow),
+	/// Retrieval error.
+	Err(String),
+}
+
 /// Serializable requester identification data.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum SerializableRequester {
@@ -91,6 +91,15 @@ pub trait DocumentKeyServer: ServerKeyGenerator {
 		key_id: ServerKeyId,
 		requester: Requester,
 	) -> Box<dyn Future<Item=EncryptedDocumentKeyShadow, Error=Error> + Send>;
+	/// Restore previously stored DKs shadows of multiple documents.
+	/// Every document still gets its own decryption session, so the cost of setting sessions up is not
+	/// shared. The sessions are started at once and run concurrently rather than one document after another.
+	/// `requests` is a list of (`key_id`, `requester`) pairs, see `restore_document_key_shadow`.
+	/// Result contains DK shadow (or error) for every request, in the order of requests.
+	fn restore_document_key_shadows(
+		&self,
+		requests: Vec<(ServerKeyId, Requester)>,
+	) -> Box<dyn Future<Item=Vec<Result<EncryptedDocumentKeyShadow, Error>>, Error=Error> + Send>;
 }
 
 /// Message signer.
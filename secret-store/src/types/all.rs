@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use blockchain::ContractAddress;
 use {bytes, ethereum_types};
@@ -83,6 +83,25 @@ pub struct ClusterConfiguration {
 	/// Should key servers set change session should be started when servers set changes.
 	/// This will only work when servers set is configured using KeyServerSet contract.
 	pub auto_migrate_enabled: bool,
+	/// Mutual TLS configuration of connections between key servers. If None, connections are
+	/// only protected by the handshake and messages encryption.
+	pub tls: Option<TlsConfiguration>,
+}
+
+/// Mutual TLS configuration of connections between key servers.
+#[derive(Debug, Clone)]
+pub struct TlsConfiguration {
+	/// Path to PEM file with certificate chain of this node.
+	pub certificate_path: String,
+	/// Path to PEM file with private key of this node.
+	pub private_key_path: String,
+	/// Path to PEM file with CA certificates, used to verify certificates of other nodes.
+	pub ca_certificates_path: String,
+	/// DNS name that certificates of all nodes are issued for.
+	pub server_name: String,
+	/// SHA-256 fingerprints of allowed certificates of other nodes.
+	/// If empty, every certificate issued by CA is accepted.
+	pub pinned_certificates: BTreeSet<ethereum_types::H256>,
 }
 
 /// Shadow decryption result.
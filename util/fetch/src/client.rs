@@ -413,12 +413,14 @@ impl Request {
 impl From<Request> for hyper::Request<hyper::Body> {
 	fn from(req: Request) -> hyper::Request<hyper::Body> {
 		let uri: hyper::Uri = req.url.as_ref().parse().expect("Every valid URLis also a URI.");
-		hyper::Request::builder()
+		let mut request = hyper::Request::builder()
 			.method(req.method)
 			.uri(uri)
 			.header(header::USER_AGENT, HeaderValue::from_static("Parity Fetch Neo"))
 			.body(req.body.into())
-			.expect("Header, uri, method, and body are already valid and can not fail to parse; qed")
+			.expect("Header, uri, method, and body are already valid and can not fail to parse; qed");
+		request.headers_mut().extend(req.headers);
+		request
 	}
 }
 
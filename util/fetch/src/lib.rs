@@ -37,4 +37,4 @@ pub mod client;
 
 pub use url::Url;
 pub use self::client::{Client, Fetch, Error, Response, Request, Abort, BodyReader};
-pub use hyper::Method;
+pub use hyper::{Method, header};
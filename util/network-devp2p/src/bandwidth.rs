@@ -0,0 +1,346 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Outbound bandwidth scheduling between traffic classes.
+//!
+//! Packets to the same peer are always sent in the order they were scheduled, so a response can't
+//! overtake the handshake queued before it. Traffic classes only decide which peer is served next.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+use log::debug;
+use parking_lot::Mutex;
+
+use network::{BandwidthPolicy, Error, PacketId, PeerId, ProtocolId, TrafficClass};
+
+/// Maximum number of packets waiting for bandwidth, per traffic class.
+const MAX_QUEUED_PACKETS: usize = 4096;
+
+const CLASSES: [TrafficClass; 3] = [TrafficClass::Sync, TrafficClass::Transactions, TrafficClass::Light];
+
+/// Packet to be sent to a peer.
+pub struct Packet {
+	pub protocol: ProtocolId,
+	pub peer: PeerId,
+	pub packet_id: PacketId,
+	pub data: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Bucket {
+	/// Bytes which may be sent right away. Goes negative after sending a packet larger than the balance.
+	tokens: f64,
+	/// Number of queued packets of the class.
+	queued: usize,
+}
+
+/// Packet waiting for bandwidth.
+struct Queued {
+	/// Order in which packets were queued, across all peers.
+	seq: u64,
+	class: usize,
+	packet: Packet,
+}
+
+struct Buckets {
+	refilled: Instant,
+	buckets: [Bucket; 3],
+	/// Waiting packets of each peer, in the order they must be sent.
+	peers: HashMap<PeerId, VecDeque<Queued>>,
+	next_seq: u64,
+}
+
+/// Token bucket per traffic class, refilled at the class share of the bandwidth limit.
+/// Buckets hold at most one second worth of their share.
+pub struct BandwidthScheduler {
+	policy: BandwidthPolicy,
+	total_weight: u64,
+	buckets: Mutex<Buckets>,
+}
+
+impl BandwidthScheduler {
+	/// Create a scheduler with full buckets.
+	pub fn new(policy: BandwidthPolicy) -> Self {
+		let total_weight = CLASSES.iter().map(|&c| policy.weight(c) as u64).sum();
+		let scheduler = BandwidthScheduler {
+			policy,
+			total_weight,
+			buckets: Mutex::new(Buckets {
+				refilled: Instant::now(),
+				buckets: Default::default(),
+				peers: HashMap::new(),
+				next_seq: 0,
+			}),
+		};
+		{
+			let mut buckets = scheduler.buckets.lock();
+			for (i, bucket) in buckets.buckets.iter_mut().enumerate() {
+				bucket.tokens = scheduler.rate(CLASSES[i]);
+			}
+		}
+		scheduler
+	}
+
+	/// Whether outbound traffic is scheduled at all.
+	pub fn is_enabled(&self) -> bool {
+		self.policy.max_bytes_per_second > 0 && self.total_weight > 0
+	}
+
+	/// Returns the packet if it may be sent right away, queues it otherwise. Packets are queued when
+	/// their class is out of bandwidth, or an earlier packet to the same peer is still waiting.
+	/// Fails if the queue of the packet's traffic class is full, the packet is dropped then.
+	pub fn schedule(&self, packet: Packet) -> Result<Option<Packet>, Error> {
+		self.schedule_at(packet, Instant::now())
+	}
+
+	/// Queued packets which may be sent now.
+	pub fn release(&self) -> Vec<Packet> {
+		self.release_at(Instant::now())
+	}
+
+	/// Drop the packets queued for a disconnected peer.
+	pub fn remove_peer(&self, peer: PeerId) {
+		if !self.is_enabled() {
+			return;
+		}
+		let mut buckets = self.buckets.lock();
+		if let Some(queue) = buckets.peers.remove(&peer) {
+			for queued in queue {
+				buckets.buckets[queued.class].queued -= 1;
+			}
+		}
+	}
+
+	fn schedule_at(&self, packet: Packet, now: Instant) -> Result<Option<Packet>, Error> {
+		if !self.is_enabled() {
+			return Ok(Some(packet));
+		}
+
+		let class = self.policy.classify(packet.protocol, packet.packet_id);
+		let mut buckets = self.buckets.lock();
+		self.refill(&mut buckets, now);
+
+		let class_index = index(class);
+		let peer_waiting = buckets.peers.contains_key(&packet.peer);
+		let bucket = &mut buckets.buckets[class_index];
+		if !peer_waiting && bucket.queued == 0 && bucket.tokens > 0.0 {
+			bucket.tokens -= packet.data.len() as f64;
+			return Ok(Some(packet));
+		}
+		if bucket.queued >= MAX_QUEUED_PACKETS {
+			debug!(target: "network", "Dropping packet {} to peer {}: {:?} traffic queue is full", packet.packet_id, packet.peer, class);
+			return Err(Error::OutboundQueueFull);
+		}
+		bucket.queued += 1;
+		let seq = buckets.next_seq;
+		buckets.next_seq += 1;
+		buckets.peers.entry(packet.peer).or_insert_with(VecDeque::new).push_back(Queued { seq, class: class_index, packet });
+		Ok(None)
+	}
+
+	fn release_at(&self, now: Instant) -> Vec<Packet> {
+		if !self.is_enabled() {
+			return Vec::new();
+		}
+
+		let mut buckets = self.buckets.lock();
+		self.refill(&mut buckets, now);
+
+		// Serve the peer whose next packet waits the longest among those with bandwidth left in their class.
+		let mut released = Vec::new();
+		loop {
+			let next = {
+				let classes = &buckets.buckets;
+				buckets.peers.iter()
+					.filter_map(|(peer, queue)| queue.front().map(|queued| (queued, *peer)))
+					.filter(|(queued, _)| classes[queued.class].tokens > 0.0)
+					.min_by_key(|(queued, _)| queued.seq)
+					.map(|(_, peer)| peer)
+			};
+			let peer = match next {
+				Some(peer) => peer,
+				None => break,
+			};
+			let queued = {
+				let queue = buckets.peers.get_mut(&peer).expect("peer was just found in the map; qed");
+				let queued = queue.pop_front().expect("peers are removed once their queue is empty; qed");
+				if queue.is_empty() {
+					buckets.peers.remove(&peer);
+				}
+				queued
+			};
+			let bucket = &mut buckets.buckets[queued.class];
+			bucket.queued -= 1;
+			bucket.tokens -= queued.packet.data.len() as f64;
+			released.push(queued.packet);
+		}
+		released
+	}
+
+	/// Bytes per second granted to a traffic class.
+	fn rate(&self, class: TrafficClass) -> f64 {
+		if self.total_weight == 0 {
+			return 0.0;
+		}
+		self.policy.max_bytes_per_second as f64 * self.policy.weight(class) as f64 / self.total_weight as f64
+	}
+
+	fn refill(&self, buckets: &mut Buckets, now: Instant) {
+		let elapsed = now.saturating_duration_since(buckets.refilled).as_secs_f64();
+		buckets.refilled = now;
+
+		// Tokens overflowing the buckets of idle classes are lent to classes with packets waiting.
+		let mut spare = 0.0;
+		for (i, bucket) in buckets.buckets.iter_mut().enumerate() {
+			let rate = self.rate(CLASSES[i]);
+			bucket.tokens += rate * elapsed;
+			if bucket.tokens > rate {
+				spare += bucket.tokens - rate;
+				bucket.tokens = rate;
+			}
+		}
+
+		let waiting: Vec<_> = (0..CLASSES.len()).filter(|&i| buckets.buckets[i].queued > 0).collect();
+		// Classes of weight `0` only ever get spare bandwidth, so they still need a share of it.
+		let waiting_weight: u64 = waiting.iter().map(|&i| self.policy.weight(CLASSES[i]) as u64 + 1).sum();
+		for i in waiting {
+			let weight = self.policy.weight(CLASSES[i]) as u64 + 1;
+			buckets.buckets[i].tokens += spare * weight as f64 / waiting_weight as f64;
+		}
+	}
+}
+
+fn index(class: TrafficClass) -> usize {
+	match class {
+		TrafficClass::Sync => 0,
+		TrafficClass::Transactions => 1,
+		TrafficClass::Light => 2,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+	use network::{BandwidthPolicy, Error};
+	use super::{BandwidthScheduler, Packet, MAX_QUEUED_PACKETS};
+
+	fn packet(protocol: [u8; 3], peer: usize, len: usize) -> Packet {
+		Packet { protocol, peer, packet_id: 0, data: vec![0; len] }
+	}
+
+	#[test]
+	fn should_not_schedule_without_limit() {
+		let scheduler = BandwidthScheduler::new(BandwidthPolicy::default());
+		for _ in 0..10 {
+			assert!(scheduler.schedule(packet(*b"eth", 1, 1_000_000)).unwrap().is_some());
+		}
+		assert!(scheduler.release().is_empty());
+	}
+
+	#[test]
+	fn should_share_bandwidth_by_weight() {
+		let scheduler = BandwidthScheduler::new(BandwidthPolicy {
+			max_bytes_per_second: 4000,
+			sync_weight: 3,
+			transactions_weight: 0,
+			light_weight: 1,
+			light_protocols: vec![*b"pip"],
+			..Default::default()
+		});
+		let start = scheduler.buckets.lock().refilled;
+
+		// light serving has a 1000 bytes burst, sync 3000
+		assert!(scheduler.schedule_at(packet(*b"pip", 1, 1000), start).unwrap().is_some());
+		assert!(scheduler.schedule_at(packet(*b"pip", 1, 1000), start).unwrap().is_none());
+		assert!(scheduler.schedule_at(packet(*b"eth", 2, 2000), start).unwrap().is_some());
+		assert!(scheduler.schedule_at(packet(*b"eth", 2, 2000), start).unwrap().is_some());
+		assert!(scheduler.schedule_at(packet(*b"eth", 2, 2000), start).unwrap().is_none());
+
+		// a quarter second refills 250 bytes of light serving, not enough to clear the sync debt
+		let released = scheduler.release_at(start + Duration::from_millis(250));
+		assert_eq!(released.iter().map(|p| p.peer).collect::<Vec<_>>(), vec![1]);
+		let released = scheduler.release_at(start + Duration::from_millis(750));
+		assert_eq!(released.iter().map(|p| p.peer).collect::<Vec<_>>(), vec![2]);
+	}
+
+	#[test]
+	fn should_lend_unused_bandwidth() {
+		let scheduler = BandwidthScheduler::new(BandwidthPolicy {
+			max_bytes_per_second: 1000,
+			sync_weight: 1,
+			transactions_weight: 1,
+			light_weight: 1,
+			light_protocols: vec![*b"pip"],
+			..Default::default()
+		});
+		let start = scheduler.buckets.lock().refilled;
+
+		assert!(scheduler.schedule_at(packet(*b"pip", 1, 400), start).unwrap().is_some());
+		assert!(scheduler.schedule_at(packet(*b"pip", 1, 400), start).unwrap().is_none());
+		// sync and transactions are idle with full buckets, their refill goes to light serving
+		let released = scheduler.release_at(start + Duration::from_millis(100));
+		assert_eq!(released.len(), 1);
+	}
+
+	#[test]
+	fn should_keep_packet_order_per_peer() {
+		let scheduler = BandwidthScheduler::new(BandwidthPolicy {
+			max_bytes_per_second: 2000,
+			sync_weight: 1,
+			transactions_weight: 1,
+			light_weight: 0,
+			transaction_packets: vec![(*b"eth", 0x02)],
+			..Default::default()
+		});
+		let start = scheduler.buckets.lock().refilled;
+
+		// sync is out of bandwidth, so the next sync packet to peer 1 waits
+		assert!(scheduler.schedule_at(Packet { packet_id: 0x03, ..packet(*b"eth", 1, 1500) }, start).unwrap().is_some());
+		assert!(scheduler.schedule_at(Packet { packet_id: 0x03, ..packet(*b"eth", 1, 10) }, start).unwrap().is_none());
+		// other peers are not held back
+		assert!(scheduler.schedule_at(Packet { packet_id: 0x02, ..packet(*b"eth", 2, 10) }, start).unwrap().is_some());
+		// transactions still have bandwidth, but must not overtake the queued sync packet of the same peer
+		assert!(scheduler.schedule_at(Packet { packet_id: 0x02, ..packet(*b"eth", 1, 10) }, start).unwrap().is_none());
+
+		let released = scheduler.release_at(start + Duration::from_secs(1));
+		assert_eq!(released.iter().map(|p| p.packet_id).collect::<Vec<_>>(), vec![0x03, 0x02]);
+	}
+
+	#[test]
+	fn should_drop_packets_of_removed_peer() {
+		let scheduler = BandwidthScheduler::new(BandwidthPolicy { max_bytes_per_second: 100, ..Default::default() });
+		let start = Instant::now();
+		assert!(scheduler.schedule_at(packet(*b"eth", 1, 1000), start).unwrap().is_some());
+		assert!(scheduler.schedule_at(packet(*b"eth", 1, 10), start).unwrap().is_none());
+		scheduler.remove_peer(1);
+		assert!(scheduler.release_at(start + Duration::from_secs(60)).is_empty());
+	}
+
+	#[test]
+	fn should_fail_when_queue_is_full() {
+		let scheduler = BandwidthScheduler::new(BandwidthPolicy { max_bytes_per_second: 100, ..Default::default() });
+		let start = Instant::now();
+		assert!(scheduler.schedule_at(packet(*b"eth", 1, 1000), start).unwrap().is_some());
+		for _ in 0..MAX_QUEUED_PACKETS {
+			assert!(scheduler.schedule_at(packet(*b"eth", 1, 10), start).unwrap().is_none());
+		}
+		match scheduler.schedule_at(packet(*b"eth", 1, 10), start) {
+			Err(Error::OutboundQueueFull) => {},
+			_ => panic!("Expected the packet to be rejected"),
+		}
+	}
+}
@@ -0,0 +1,197 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Outbound dialing policy: dial rate limiting, peer set diversity and reserved peers weighting.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use network::DialPolicy;
+
+use crate::node_table::NodeId;
+
+/// Network prefix grouping addresses of a single operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Subnet {
+	V4([u8; 3]),
+	V6([u8; 8]),
+}
+
+impl From<IpAddr> for Subnet {
+	fn from(ip: IpAddr) -> Self {
+		match ip {
+			IpAddr::V4(ip) => {
+				let o = ip.octets();
+				Subnet::V4([o[0], o[1], o[2]])
+			},
+			IpAddr::V6(ip) => {
+				let mut prefix = [0u8; 8];
+				prefix.copy_from_slice(&ip.octets()[..8]);
+				Subnet::V6(prefix)
+			},
+		}
+	}
+}
+
+/// Dial candidate or connected peer, with its address if known.
+pub type Peer = (NodeId, Option<IpAddr>);
+
+/// Selects nodes to dial according to the `DialPolicy`.
+pub struct Dialer {
+	policy: DialPolicy,
+	/// Start of the current rate limiting window and number of dials made within it.
+	window: Mutex<(Instant, u32)>,
+}
+
+impl Dialer {
+	/// Create a new dialer.
+	pub fn new(policy: DialPolicy) -> Self {
+		Dialer {
+			policy,
+			window: Mutex::new((Instant::now(), 0)),
+		}
+	}
+
+	/// Pick up to `slots` nodes to dial, out of reserved and discovered candidates,
+	/// given the peers which are already connected or connecting.
+	pub fn select(&self, reserved: Vec<Peer>, discovered: Vec<Peer>, connected: &[Peer], slots: usize) -> Vec<NodeId> {
+		self.select_at(reserved, discovered, connected, slots, Instant::now())
+	}
+
+	fn select_at(&self, reserved: Vec<Peer>, discovered: Vec<Peer>, connected: &[Peer], slots: usize, now: Instant) -> Vec<NodeId> {
+		let mut window = self.window.lock();
+		if now.saturating_duration_since(window.0) >= Duration::from_secs(1) {
+			*window = (now, 0);
+		}
+		let slots = match self.policy.max_dials_per_second {
+			0 => slots,
+			max => slots.min(max.saturating_sub(window.1) as usize),
+		};
+
+		let reserved_slots = if discovered.is_empty() {
+			slots
+		} else {
+			(slots * self.policy.reserved_weight.min(100) as usize + 99) / 100
+		};
+
+		let mut subnets: HashMap<Subnet, u32> = HashMap::new();
+		let mut buckets: HashMap<u8, u32> = HashMap::new();
+		for (id, ip) in connected {
+			*buckets.entry(id[0]).or_insert(0) += 1;
+			if let Some(ip) = ip {
+				*subnets.entry(Subnet::from(*ip)).or_insert(0) += 1;
+			}
+		}
+
+		let mut reserved = reserved.into_iter().map(|(id, _)| id);
+		let mut selected: Vec<NodeId> = reserved.by_ref().take(reserved_slots).collect();
+		for (id, ip) in discovered {
+			if selected.len() >= slots {
+				break;
+			}
+			if !self.admit(&mut subnets, &mut buckets, &id, ip) {
+				continue;
+			}
+			selected.push(id);
+		}
+		// discovered peers have left some slots unused
+		let remaining = slots - selected.len();
+		selected.extend(reserved.take(remaining));
+
+		window.1 += selected.len() as u32;
+		selected
+	}
+
+	/// Check subnet and node id bucket limits for a discovered peer, and account for it if admitted.
+	fn admit(&self, subnets: &mut HashMap<Subnet, u32>, buckets: &mut HashMap<u8, u32>, id: &NodeId, ip: Option<IpAddr>) -> bool {
+		let max_subnet = self.policy.max_peers_per_subnet;
+		let max_bucket = self.policy.max_peers_per_id_bucket;
+		let subnet = ip.map(Subnet::from);
+
+		if max_subnet != 0 && subnet.map_or(false, |s| subnets.get(&s).cloned().unwrap_or(0) >= max_subnet) {
+			return false;
+		}
+		if max_bucket != 0 && buckets.get(&id[0]).cloned().unwrap_or(0) >= max_bucket {
+			return false;
+		}
+
+		*buckets.entry(id[0]).or_insert(0) += 1;
+		if let Some(subnet) = subnet {
+			*subnets.entry(subnet).or_insert(0) += 1;
+		}
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::net::IpAddr;
+	use std::time::{Duration, Instant};
+	use network::DialPolicy;
+	use crate::node_table::NodeId;
+	use super::{Dialer, Peer};
+
+	fn peer(first_byte: u8, n: u8, ip: &str) -> Peer {
+		let mut id = NodeId::zero();
+		id[0] = first_byte;
+		id[63] = n;
+		(id, Some(ip.parse::<IpAddr>().unwrap()))
+	}
+
+	#[test]
+	fn should_limit_dial_rate() {
+		let dialer = Dialer::new(DialPolicy { max_dials_per_second: 3, ..Default::default() });
+		let now = Instant::now();
+		let candidates: Vec<_> = (0..5).map(|n| peer(n, n, &format!("10.0.{}.1", n))).collect();
+
+		assert_eq!(dialer.select_at(vec![], candidates.clone(), &[], 10, now).len(), 3);
+		assert_eq!(dialer.select_at(vec![], candidates.clone(), &[], 10, now + Duration::from_millis(500)).len(), 0);
+		assert_eq!(dialer.select_at(vec![], candidates, &[], 10, now + Duration::from_secs(1)).len(), 3);
+	}
+
+	#[test]
+	fn should_diversify_subnets_and_id_buckets() {
+		let dialer = Dialer::new(DialPolicy { max_peers_per_subnet: 1, max_peers_per_id_bucket: 2, ..Default::default() });
+		let connected = vec![peer(1, 0, "10.0.0.1")];
+		let candidates = vec![
+			peer(2, 1, "10.0.0.2"), // same subnet as connected peer
+			peer(1, 2, "10.0.1.1"),
+			peer(1, 3, "10.0.2.1"), // bucket 1 is full
+			peer(3, 4, "10.0.1.2"), // subnet of the previously selected peer
+			peer(4, 5, "10.0.3.1"),
+		];
+
+		let selected = dialer.select(vec![], candidates.clone(), &connected, 10);
+		assert_eq!(selected, vec![candidates[1].0, candidates[4].0]);
+	}
+
+	#[test]
+	fn should_weight_reserved_and_discovered_peers() {
+		let dialer = Dialer::new(DialPolicy { reserved_weight: 25, ..Default::default() });
+		let reserved: Vec<_> = (0..4).map(|n| peer(0, n, "10.0.0.1")).collect();
+		let discovered: Vec<_> = (0..4).map(|n| peer(1, n, "10.0.1.1")).collect();
+
+		let selected = dialer.select(reserved.clone(), discovered.clone(), &[], 4);
+		assert_eq!(selected, vec![reserved[0].0, discovered[0].0, discovered[1].0, discovered[2].0]);
+
+		// unused slots of discovered peers go to reserved ones
+		let selected = dialer.select(reserved.clone(), discovered[..1].to_vec(), &[], 4);
+		assert_eq!(selected, vec![reserved[0].0, discovered[0].0, reserved[1].0, reserved[2].0]);
+	}
+}
@@ -17,7 +17,10 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::collections::hash_map::Entry;
 use std::default::Default;
+use std::fs;
 use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use ethereum_types::{H256, H520};
@@ -26,6 +29,7 @@ use log::{debug, trace, warn};
 use lru_cache::LruCache;
 use parity_bytes::Bytes;
 use rlp::{Rlp, RlpStream};
+use serde::{Deserialize, Serialize};
 
 use parity_crypto::publickey::{KeyPair, recover, Secret, sign};
 use network::Error;
@@ -61,6 +65,9 @@ const NODE_LAST_SEEN_TIMEOUT: Duration = Duration::from_secs(24*60*60);
 
 const OBSERVED_NODES_MAX_SIZE: usize = 10_000;
 
+const TABLE_FILE: &str = "discovery.json";
+const TABLE_TEMP_FILE: &str = "discovery.json.tmp";
+
 #[derive(Clone, Debug)]
 pub struct NodeEntry {
 	pub id: NodeId,
@@ -232,6 +239,39 @@ impl<'a> Discovery<'a> {
 		}
 	}
 
+	/// Returns the nodes in the buckets together with the time they were last seen.
+	pub fn table_entries(&self) -> Vec<(NodeEntry, SystemTime)> {
+		let (now, system_now) = (Instant::now(), SystemTime::now());
+		self.node_buckets.iter()
+			.flat_map(|bucket| bucket.nodes.iter())
+			.map(|node| {
+				let last_seen = system_now.checked_sub(now.duration_since(node.last_seen)).unwrap_or(UNIX_EPOCH);
+				(node.address.clone(), last_seen)
+			})
+			.collect()
+	}
+
+	/// Restore the buckets saved by a previous run. Nodes not seen within `NODE_LAST_SEEN_TIMEOUT`
+	/// are dropped, the others are pinged, most recently seen first, and only enter the buckets
+	/// once they answer.
+	pub fn restore_table(&mut self, entries: Vec<(NodeEntry, SystemTime)>) {
+		let now = SystemTime::now();
+		let mut entries: Vec<_> = entries.into_iter()
+			.filter_map(|(node, last_seen)| {
+				let age = now.duration_since(last_seen).unwrap_or_default();
+				if age < NODE_LAST_SEEN_TIMEOUT { Some((node, age)) } else { None }
+			})
+			.collect();
+		entries.sort_by_key(|&(_, age)| age);
+
+		debug!(target: "discovery", "Restoring {} nodes", entries.len());
+		let queued = self.adding_nodes.len();
+		self.add_node_list(entries.into_iter().map(|(node, _)| node).collect());
+		// nodes beyond the ping limit are popped from the back of `adding_nodes`, so the most
+		// recently seen ones have to be queued last
+		self.adding_nodes[queued..].reverse();
+	}
+
 	fn update_bucket_record(&mut self, e: NodeEntry) -> Result<(), BucketError> {
 		let id_hash = keccak(e.id);
 		let dist = match Discovery::distance(&self.id_hash, &id_hash) {
@@ -856,6 +896,71 @@ impl<'a> Discovery<'a> {
 	}
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TableFile {
+	nodes: Vec<TableFileNode>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TableFileNode {
+	url: String,
+	last_seen: u64,
+}
+
+/// Save the discovery table entries to `discovery.json` in given directory.
+pub fn save_table(dir: &Path, entries: Vec<(NodeEntry, SystemTime)>) {
+	if let Err(e) = fs::create_dir_all(dir) {
+		warn!(target: "discovery", "Error creating discovery table directory: {:?}", e);
+		return;
+	}
+	let nodes = entries.into_iter()
+		.map(|(node, last_seen)| TableFileNode {
+			url: Node::new(node.id, node.endpoint).to_string(),
+			last_seen: last_seen.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+		})
+		.collect();
+
+	// the table is replaced at once, so an interrupted write never leaves a truncated file behind
+	let temp_path = dir.join(TABLE_TEMP_FILE);
+	match fs::File::create(&temp_path) {
+		Ok(file) => {
+			if let Err(e) = serde_json::to_writer_pretty(file, &TableFile { nodes }) {
+				warn!(target: "discovery", "Error writing discovery table file: {:?}", e);
+				return;
+			}
+			if let Err(e) = fs::rename(&temp_path, dir.join(TABLE_FILE)) {
+				warn!(target: "discovery", "Error replacing discovery table file: {:?}", e);
+			}
+		},
+		Err(e) => warn!(target: "discovery", "Error creating discovery table file: {:?}", e),
+	}
+}
+
+/// Load the discovery table entries saved by `save_table`.
+pub fn load_table(dir: &Path) -> Vec<(NodeEntry, SystemTime)> {
+	let file = match fs::File::open(dir.join(TABLE_FILE)) {
+		Ok(file) => file,
+		Err(e) => {
+			debug!(target: "discovery", "Error opening discovery table file: {:?}", e);
+			return Vec::new();
+		},
+	};
+	match serde_json::from_reader::<_, TableFile>(file) {
+		Ok(table) => table.nodes.into_iter()
+			.filter_map(|n| Node::from_str(&n.url).ok().map(|node| (
+				NodeEntry { id: node.id, endpoint: node.endpoint },
+				UNIX_EPOCH + Duration::from_secs(n.last_seen),
+			)))
+			.collect(),
+		Err(e) => {
+			warn!(target: "discovery", "Error reading discovery table file: {:?}", e);
+			Vec::new()
+		},
+	}
+}
+
 fn append_expiration(rlp: &mut RlpStream) {
 	let expiry = SystemTime::now() + EXPIRY_TIME;
 	let timestamp = expiry.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as u32;
@@ -900,6 +1005,7 @@ mod tests {
 	use std::str::FromStr;
 
 	use rustc_hex::FromHex;
+	use tempdir::TempDir;
 
 	use parity_crypto::publickey::{Generator, Random};
 
@@ -944,6 +1050,62 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn restore_table() {
+		let key = Random.generate().unwrap();
+		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40445").unwrap(), udp_port: 40445 };
+		let mut discovery = Discovery::new(&key, ep.clone(), IpFilter::default());
+
+		let fresh = NodeEntry { id: NodeId::random(), endpoint: ep.clone() };
+		let stale = NodeEntry { id: NodeId::random(), endpoint: ep.clone() };
+		let now = SystemTime::now();
+		discovery.restore_table(vec![
+			(fresh.clone(), now - Duration::from_secs(60)),
+			(stale.clone(), now - NODE_LAST_SEEN_TIMEOUT - Duration::from_secs(60)),
+		]);
+
+		// restored nodes enter the buckets only after they answer the ping
+		assert!(discovery.table_entries().is_empty());
+		assert!(discovery.in_flight_pings.contains_key(&fresh.id));
+		assert!(!discovery.in_flight_pings.contains_key(&stale.id));
+	}
+
+	#[test]
+	fn restore_table_most_recently_seen_first() {
+		let key = Random.generate().unwrap();
+		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40447").unwrap(), udp_port: 40447 };
+		let mut discovery = Discovery::new(&key, ep.clone(), IpFilter::default());
+
+		let now = SystemTime::now();
+		let nodes: Vec<_> = (0..MAX_NODES_PING + 10).map(|_| NodeEntry { id: NodeId::random(), endpoint: ep.clone() }).collect();
+		// oldest first, the restored order must not depend on the saved one
+		discovery.restore_table(nodes.iter().enumerate().rev()
+			.map(|(i, node)| (node.clone(), now - Duration::from_secs(60 * (i as u64 + 1))))
+			.collect());
+
+		assert!(nodes[..MAX_NODES_PING].iter().all(|node| discovery.in_flight_pings.contains_key(&node.id)));
+		assert_eq!(discovery.adding_nodes.len(), 10);
+		for node in &nodes[MAX_NODES_PING..] {
+			assert_eq!(discovery.adding_nodes.pop().map(|n| n.id), Some(node.id));
+		}
+	}
+
+	#[test]
+	fn save_and_load_table() {
+		let temp_path = TempDir::new("discovery").unwrap();
+		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40445").unwrap(), udp_port: 40446 };
+		let node = NodeEntry { id: NodeId::random(), endpoint: ep };
+		let last_seen = UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+
+		save_table(temp_path.path(), vec![(node.clone(), last_seen)]);
+		let loaded = load_table(temp_path.path());
+		assert_eq!(loaded.len(), 1);
+		assert_eq!(loaded[0].0.id, node.id);
+		assert_eq!(loaded[0].0.endpoint, node.endpoint);
+		assert_eq!(loaded[0].1, last_seen);
+		assert!(!temp_path.path().join(TABLE_TEMP_FILE).exists());
+	}
+
 	#[test]
 	fn discovery() {
 		let mut discovery_handlers = (0..5).map(|i| {
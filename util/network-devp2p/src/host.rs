@@ -43,16 +43,19 @@ use parity_crypto::publickey::{Generator, KeyPair, Random, Secret};
 use network::{
 	client_version::ClientVersion, ConnectionDirection, ConnectionFilter, DisconnectReason, Error,
 	NetworkConfiguration, NetworkContext as NetworkContextTrait, NetworkIoMessage, NetworkProtocolHandler,
-	NonReservedPeerMode, PacketId, PeerId, ProtocolId, SessionInfo
+	NonReservedPeerMode, PacketId, PacketTraffic, PeerId, ProtocolId, SessionInfo
 };
 
 use crate::{
 	connection::PAYLOAD_SOFT_LIMIT,
-	discovery::{Discovery, MAX_DATAGRAM_SIZE, NodeEntry, TableUpdates},
+	discovery::{self, Discovery, MAX_DATAGRAM_SIZE, NodeEntry, TableUpdates},
 	ip_utils::{map_external_address, select_public_address},
 	node_table::*,
 	PROTOCOL_VERSION,
-	session::{Session, SessionData}
+	session::{Session, SessionData},
+	traffic::TrafficStats,
+	dial::Dialer,
+	bandwidth::{BandwidthScheduler, Packet},
 };
 
 type Slab<T> = ::slab::Slab<T, usize>;
@@ -70,6 +73,7 @@ const DISCOVERY_REFRESH: TimerToken = SYS_TIMER + 4;
 const FAST_DISCOVERY_REFRESH: TimerToken = SYS_TIMER + 5;
 const DISCOVERY_ROUND: TimerToken = SYS_TIMER + 6;
 const NODE_TABLE: TimerToken = SYS_TIMER + 7;
+const BANDWIDTH: TimerToken = SYS_TIMER + 8;
 const FIRST_SESSION: StreamToken = 0;
 const LAST_SESSION: StreamToken = FIRST_SESSION + MAX_SESSIONS - 1;
 const USER_TIMER: TimerToken = LAST_SESSION + 256;
@@ -86,6 +90,8 @@ const FAST_DISCOVERY_REFRESH_TIMEOUT: Duration = Duration::from_secs(10);
 const DISCOVERY_ROUND_TIMEOUT: Duration = Duration::from_millis(300);
 // for NODE_TABLE TimerToken
 const NODE_TABLE_TIMEOUT: Duration = Duration::from_secs(300);
+// for BANDWIDTH TimerToken
+const BANDWIDTH_TIMEOUT: Duration = Duration::from_millis(50);
 
 #[derive(Debug, PartialEq, Eq)]
 /// Protocol info
@@ -114,6 +120,8 @@ pub struct NetworkContext<'s> {
 	session: Option<SharedSession>,
 	session_id: Option<StreamToken>,
 	reserved_peers: &'s HashSet<NodeId>,
+	traffic: &'s TrafficStats,
+	bandwidth: &'s BandwidthScheduler,
 }
 
 impl<'s> NetworkContext<'s> {
@@ -124,6 +132,8 @@ impl<'s> NetworkContext<'s> {
 		session: Option<SharedSession>,
 		sessions: Arc<RwLock<Slab<SharedSession>>>,
 		reserved_peers: &'s HashSet<NodeId>,
+		traffic: &'s TrafficStats,
+		bandwidth: &'s BandwidthScheduler,
 	) -> NetworkContext<'s> {
 		let id = session.as_ref().map(|s| s.lock().token());
 		NetworkContext {
@@ -133,6 +143,8 @@ impl<'s> NetworkContext<'s> {
 			session,
 			sessions,
 			reserved_peers: reserved_peers,
+			traffic,
+			bandwidth,
 		}
 	}
 
@@ -152,7 +164,11 @@ impl<'s> NetworkContextTrait for NetworkContext<'s> {
 	fn send_protocol(&self, protocol: ProtocolId, peer: PeerId, packet_id: PacketId, data: Vec<u8>) -> Result<(), Error> {
 		let session = self.resolve_session(peer);
 		if let Some(session) = session {
-			session.lock().send_packet(self.io, Some(protocol), packet_id as u8, &data)?;
+			// packets over the bandwidth share of their class are sent later by the host
+			if let Some(packet) = self.bandwidth.schedule(Packet { protocol, peer, packet_id, data })? {
+				session.lock().send_packet(self.io, Some(protocol), packet_id as u8, &packet.data)?;
+				self.traffic.egress(protocol, packet_id, packet.data.len());
+			}
 		} else  {
 			trace!(target: "network", "Send: Peer no longer exist")
 		}
@@ -275,6 +291,9 @@ pub struct Host {
 	reserved_nodes: RwLock<HashSet<NodeId>>,
 	stopping: AtomicBool,
 	filter: Option<Arc<dyn ConnectionFilter>>,
+	traffic: TrafficStats,
+	dialer: Dialer,
+	bandwidth: BandwidthScheduler,
 }
 
 impl Host {
@@ -308,6 +327,8 @@ impl Host {
 
 		let boot_nodes = config.boot_nodes.clone();
 		let reserved_nodes = config.reserved_nodes.clone();
+		let dialer = Dialer::new(config.dial_policy.clone());
+		let bandwidth = BandwidthScheduler::new(config.bandwidth_policy.clone());
 		config.max_handshakes = min(config.max_handshakes, MAX_HANDSHAKES as u32);
 
 		let mut host = Host {
@@ -331,6 +352,9 @@ impl Host {
 			reserved_nodes: RwLock::new(HashSet::new()),
 			stopping: AtomicBool::new(false),
 			filter,
+			traffic: TrafficStats::new(),
+			dialer,
+			bandwidth,
 		};
 
 		for n in boot_nodes {
@@ -432,9 +456,26 @@ impl Host {
 			trace!(target: "network", "Disconnecting on shutdown: {}", p);
 			self.kill_connection(p, io, true);
 		}
+		self.save_discovery_table();
 		io.unregister_handler();
 	}
 
+	/// Persist the discovery table so that the next start can skip bootstrapping from scratch.
+	fn save_discovery_table(&self) {
+		let path = match self.info.read().config.net_config_path {
+			Some(ref path) => PathBuf::from(path),
+			None => return,
+		};
+		if let Some(ref discovery) = *self.discovery.lock() {
+			discovery::save_table(&path, discovery.table_entries());
+		}
+	}
+
+	/// Get traffic statistics by subprotocol and message type.
+	pub fn traffic_stats(&self) -> Vec<PacketTraffic> {
+		self.traffic.snapshot()
+	}
+
 	/// Get all connected peers.
 	pub fn connected_peers(&self) -> Vec<PeerId> {
 		let sessions = self.sessions.read();
@@ -495,6 +536,9 @@ impl Host {
 			let socket = UdpSocket::bind(&udp_addr).expect("Error binding UDP socket");
 			*self.udp_socket.lock() = Some(socket);
 
+			if let Some(ref path) = self.info.read().config.net_config_path {
+				discovery.restore_table(discovery::load_table(Path::new(path)));
+			}
 			discovery.add_node_list(self.nodes.read().entries());
 			*self.discovery.lock() = Some(discovery);
 			io.register_stream(DISCOVERY)?;
@@ -509,6 +553,7 @@ impl Host {
 
 	fn maintain_network(&self, io: &IoContext<NetworkIoMessage>) {
 		self.keep_alive(io);
+		self.rotate_sessions(io);
 		self.connect_peers(io);
 	}
 
@@ -550,6 +595,37 @@ impl Host {
 		}
 	}
 
+	/// Gracefully disconnect a session older than the configured maximum age, so that it is
+	/// re-established with fresh session keys. At most one session is rotated per maintenance round
+	/// and none while we are short of peers, so the peer count stays stable.
+	fn rotate_sessions(&self, io: &IoContext<NetworkIoMessage>) {
+		let max_age = match self.info.read().config.max_session_age {
+			Some(max_age) => max_age,
+			None => return,
+		};
+		if !self.has_enough_peers() {
+			return;
+		}
+
+		let mut to_rotate = None;
+		for e in self.sessions.read().iter() {
+			let mut s = e.lock();
+			let rotate = match s.id() {
+				Some(id) => s.is_ready() && !s.expired() && s.age() > session_rotation_age(max_age, id),
+				None => false,
+			};
+			if rotate {
+				s.disconnect(io, DisconnectReason::DisconnectRequested);
+				to_rotate = Some(s.token());
+				break;
+			}
+		}
+		if let Some(p) = to_rotate {
+			debug!(target: "network", "Rotating session {} after reaching maximum age", p);
+			self.kill_connection(p, io, false);
+		}
+	}
+
 	fn has_enough_peers(&self) -> bool {
 		let min_peers = {
 			let info = self.info.read();
@@ -590,22 +666,36 @@ impl Host {
 			return;
 		}
 
-		// iterate over all nodes, reserved ones coming first.
+		// peers we are connected or connecting to, with their addresses
+		let peers: Vec<_> = self.sessions.read().iter().filter_map(|s| {
+			let s = s.lock();
+			s.id().map(|id| (*id, s.remote_addr().ok().map(|a| a.ip())))
+		}).collect();
+
 		// if we are pinned to only reserved nodes, ignore all others.
-		let nodes = reserved_nodes.iter().cloned().chain(if !pin {
-			self.nodes.read().nodes(&allow_ips)
-		} else {
-			Vec::new()
-		});
+		let (reserved, discovered) = {
+			let nodes = self.nodes.read();
+			let candidate = |id: NodeId| {
+				if id == self_id
+					|| peers.iter().any(|&(p, _)| p == id)
+					|| !self.filter.as_ref().map_or(true, |f| f.connection_allowed(&self_id, &id, ConnectionDirection::Outbound)) {
+					return None;
+				}
+				Some((id, nodes.get(&id).map(|n| n.endpoint.address.ip())))
+			};
+			let reserved: Vec<_> = reserved_nodes.iter().cloned().filter_map(&candidate).collect();
+			let discovered: Vec<_> = if !pin {
+				nodes.nodes(&allow_ips).into_iter().filter(|id| !reserved_nodes.contains(id)).filter_map(&candidate).collect()
+			} else {
+				Vec::new()
+			};
+			(reserved, discovered)
+		};
 
 		let max_handshakes_per_round = max_handshakes / 2;
+		let slots = min(max_handshakes_per_round, max_handshakes - handshake_count);
 		let mut started: usize = 0;
-		for id in nodes.filter(|id|
-				!self.have_session(id) &&
-				!self.connecting_to(id) &&
-				*id != self_id &&
-				self.filter.as_ref().map_or(true, |f| f.connection_allowed(&self_id, &id, ConnectionDirection::Outbound))
-			).take(min(max_handshakes_per_round, max_handshakes - handshake_count)) {
+		for id in self.dialer.select(reserved, discovered, &peers, slots) {
 			self.connect_peer(&id, io);
 			started += 1;
 		}
@@ -848,7 +938,7 @@ impl Host {
 				for p in ready_data {
 					let reserved = self.reserved_nodes.read();
 					if let Some(h) = handlers.get(&p) {
-						h.connected(&NetworkContext::new(io, p, Some(session.clone()), self.sessions.clone(), &reserved), &token);
+						h.connected(&NetworkContext::new(io, p, Some(session.clone()), self.sessions.clone(), &reserved, &self.traffic, &self.bandwidth), &token);
 						// accumulate pending packets.
 						let mut session = session.lock();
 						packet_data.extend(session.mark_connected(p));
@@ -859,7 +949,8 @@ impl Host {
 			for (p, packet_id, data) in packet_data {
 				let reserved = self.reserved_nodes.read();
 				if let Some(h) = handlers.get(&p) {
-					h.read(&NetworkContext::new(io, p, Some(session.clone()), self.sessions.clone(), &reserved), &token, packet_id, &data);
+					self.traffic.ingress(p, packet_id, data.len());
+					h.read(&NetworkContext::new(io, p, Some(session.clone()), self.sessions.clone(), &reserved, &self.traffic, &self.bandwidth), &token, packet_id, &data);
 				}
 			}
 		}
@@ -934,6 +1025,7 @@ impl Host {
 		let mut deregister = false;
 		let mut expired_session = None;
 		if let FIRST_SESSION ..= LAST_SESSION = token {
+			self.bandwidth.remove_peer(token);
 			let sessions = self.sessions.read();
 			if let Some(session) = sessions.get(token).cloned() {
 				expired_session = Some(session.clone());
@@ -960,7 +1052,7 @@ impl Host {
 		for p in to_disconnect {
 			let reserved = self.reserved_nodes.read();
 			if let Some(h) = self.handlers.read().get(&p) {
-				h.disconnected(&NetworkContext::new(io, p, expired_session.clone(), self.sessions.clone(), &reserved), &token);
+				h.disconnected(&NetworkContext::new(io, p, expired_session.clone(), self.sessions.clone(), &reserved, &self.traffic, &self.bandwidth), &token);
 			}
 		}
 		if deregister {
@@ -968,6 +1060,18 @@ impl Host {
 		}
 	}
 
+	fn send_scheduled(&self, io: &IoContext<NetworkIoMessage>) {
+		for packet in self.bandwidth.release() {
+			let session = self.sessions.read().get(packet.peer).cloned();
+			if let Some(session) = session {
+				match session.lock().send_packet(io, Some(packet.protocol), packet.packet_id as u8, &packet.data) {
+					Ok(()) => self.traffic.egress(packet.protocol, packet.packet_id, packet.data.len()),
+					Err(e) => debug!(target: "network", "Error sending scheduled packet to peer {}: {:?}", packet.peer, e),
+				}
+			}
+		}
+	}
+
 	fn update_nodes(&self, _io: &IoContext<NetworkIoMessage>, node_changes: TableUpdates) {
 		let mut to_remove: Vec<PeerId> = Vec::new();
 		{
@@ -991,14 +1095,14 @@ impl Host {
 	pub fn with_context<F>(&self, protocol: ProtocolId, io: &IoContext<NetworkIoMessage>, action: F) where F: FnOnce(&dyn NetworkContextTrait) {
 		let reserved = { self.reserved_nodes.read() };
 
-		let context = NetworkContext::new(io, protocol, None, self.sessions.clone(), &reserved);
+		let context = NetworkContext::new(io, protocol, None, self.sessions.clone(), &reserved, &self.traffic, &self.bandwidth);
 		action(&context);
 	}
 
 	pub fn with_context_eval<F, T>(&self, protocol: ProtocolId, io: &IoContext<NetworkIoMessage>, action: F) -> T where F: FnOnce(&dyn NetworkContextTrait) -> T {
 		let reserved = { self.reserved_nodes.read() };
 
-		let context = NetworkContext::new(io, protocol, None, self.sessions.clone(), &reserved);
+		let context = NetworkContext::new(io, protocol, None, self.sessions.clone(), &reserved, &self.traffic, &self.bandwidth);
 		action(&context)
 	}
 }
@@ -1007,6 +1111,9 @@ impl IoHandler<NetworkIoMessage> for Host {
 	/// Initialize networking
 	fn initialize(&self, io: &IoContext<NetworkIoMessage>) {
 		io.register_timer(IDLE, MAINTENANCE_TIMEOUT).expect("Error registering Network idle timer");
+		if self.bandwidth.is_enabled() {
+			io.register_timer(BANDWIDTH, BANDWIDTH_TIMEOUT).expect("Error registering bandwidth timer");
+		}
 		io.message(NetworkIoMessage::InitPublicInterface).unwrap_or_else(|e| warn!("Error sending IO notification: {:?}", e));
 		self.maintain_network(io)
 	}
@@ -1071,16 +1178,20 @@ impl IoHandler<NetworkIoMessage> for Host {
 			},
 			NODE_TABLE => {
 				trace!(target: "network", "Refreshing node table");
-				let mut nodes = self.nodes.write();
-				nodes.clear_useless();
-				nodes.save();
+				{
+					let mut nodes = self.nodes.write();
+					nodes.clear_useless();
+					nodes.save();
+				}
+				self.save_discovery_table();
 			},
+			BANDWIDTH => self.send_scheduled(io),
 			_ => match self.timers.read().get(&token).cloned() {
 				Some(timer) => match self.handlers.read().get(&timer.protocol).cloned() {
 					None => { warn!(target: "network", "No handler found for protocol: {:?}", timer.protocol) },
 					Some(h) => {
 						let reserved = self.reserved_nodes.read();
-						h.timeout(&NetworkContext::new(io, timer.protocol, None, self.sessions.clone(), &reserved), timer.token);
+						h.timeout(&NetworkContext::new(io, timer.protocol, None, self.sessions.clone(), &reserved, &self.traffic, &self.bandwidth), timer.token);
 					}
 				},
 				None => { warn!("Unknown timer token: {}", token); } // timer is not registerd through us
@@ -1101,7 +1212,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 				let h = handler.clone();
 				let reserved = self.reserved_nodes.read();
 				h.initialize(
-					&NetworkContext::new(io, *protocol, None, self.sessions.clone(), &reserved),
+					&NetworkContext::new(io, *protocol, None, self.sessions.clone(), &reserved, &self.traffic, &self.bandwidth),
 				);
 				self.handlers.write().insert(*protocol, h);
 				let mut info = self.info.write();
@@ -1268,6 +1379,21 @@ fn load_key(path: &Path) -> Option<Secret> {
 	}
 }
 
+/// Age after which the session with given peer is rotated. Sessions are spread over the last quarter
+/// of `max_age` based on the node id, so that connections established together are not dropped together.
+fn session_rotation_age(max_age: Duration, id: &NodeId) -> Duration {
+	let spread = (id.to_low_u64_be() % 1000) as u32;
+	max_age - max_age / 4 * spread / 1000
+}
+
+#[test]
+fn session_rotation_age_is_staggered() {
+	let max_age = Duration::from_secs(4000);
+	assert_eq!(session_rotation_age(max_age, &NodeId::from_low_u64_be(0)), max_age);
+	assert_eq!(session_rotation_age(max_age, &NodeId::from_low_u64_be(500)), Duration::from_secs(3500));
+	assert_eq!(session_rotation_age(max_age, &NodeId::from_low_u64_be(1999)), Duration::from_secs(3001));
+}
+
 #[test]
 fn key_save_load() {
 	use tempdir::TempDir;
@@ -73,5 +73,8 @@ mod discovery;
 mod service;
 mod node_table;
 mod ip_utils;
+mod traffic;
+mod dial;
+mod bandwidth;
 
 const PROTOCOL_VERSION: u32 = 5;
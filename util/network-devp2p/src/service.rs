@@ -25,7 +25,7 @@ use parking_lot::RwLock;
 use ethcore_io::{IoContext, IoHandler, IoService};
 use network::{
 	ConnectionFilter, Error, NetworkConfiguration, NetworkContext,
-	NetworkIoMessage, NetworkProtocolHandler, NonReservedPeerMode, PeerId, ProtocolId,
+	NetworkIoMessage, NetworkProtocolHandler, NonReservedPeerMode, PacketTraffic, PeerId, ProtocolId,
 
 };
 
@@ -154,6 +154,11 @@ impl NetworkService {
 		self.host.read().as_ref().map(|h| h.connected_peers()).unwrap_or_else(Vec::new)
 	}
 
+	/// Get traffic statistics by subprotocol and message type. Empty if the network isn't running.
+	pub fn traffic_stats(&self) -> Vec<PacketTraffic> {
+		self.host.read().as_ref().map(|h| h.traffic_stats()).unwrap_or_else(Vec::new)
+	}
+
 	/// Try to add a reserved peer.
 	pub fn add_reserved_peer(&self, peer: &str) -> Result<(), Error> {
 		let host = self.host.read();
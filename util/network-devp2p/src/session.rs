@@ -65,6 +65,8 @@ pub struct Session {
 	expired: bool,
 	ping_time: Instant,
 	pong_time: Option<Instant>,
+	/// Time the connection was established.
+	created: Instant,
 	state: State,
 	// Protocol states -- accumulates pending packets until signaled as ready.
 	protocol_states: HashMap<ProtocolId, ProtocolState>,
@@ -129,6 +131,7 @@ impl Session {
 			},
 			ping_time: Instant::now(),
 			pong_time: None,
+			created: Instant::now(),
 			expired: false,
 			protocol_states: HashMap::new(),
 			compression: false,
@@ -165,6 +168,11 @@ impl Session {
 		self.had_hello
 	}
 
+	/// Time elapsed since the connection was established.
+	pub fn age(&self) -> Duration {
+		self.created.elapsed()
+	}
+
 	/// Mark this session as inactive to be deleted lated.
 	pub fn set_expired(&mut self) {
 		self.expired = true;
@@ -0,0 +1,142 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Subprotocol traffic accounting.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use network::{PacketId, PacketTraffic, ProtocolId, TrafficSample};
+
+/// Granularity of the sliding windows.
+const BUCKET_SECS: u64 = 10;
+/// Lengths of the reported sliding windows, in seconds.
+const WINDOW_SECS: [u64; 2] = [60, 300];
+/// Number of buckets needed to cover the longest window.
+const MAX_BUCKETS: u64 = 300 / BUCKET_SECS;
+
+#[derive(Default)]
+struct Counters {
+	total: TrafficSample,
+	/// Bucket index and traffic recorded within it, oldest first.
+	buckets: VecDeque<(u64, TrafficSample)>,
+}
+
+/// Bytes sent and received, by subprotocol and message type.
+pub struct TrafficStats {
+	started: Instant,
+	counters: Mutex<HashMap<(ProtocolId, PacketId), Counters>>,
+}
+
+impl TrafficStats {
+	/// Create empty statistics.
+	pub fn new() -> Self {
+		TrafficStats {
+			started: Instant::now(),
+			counters: Mutex::new(HashMap::new()),
+		}
+	}
+
+	/// Note a message received from a peer.
+	pub fn ingress(&self, protocol: ProtocolId, packet_id: PacketId, bytes: usize) {
+		self.record(protocol, packet_id, TrafficSample { ingress: bytes as u64, egress: 0 }, Instant::now());
+	}
+
+	/// Note a message sent to a peer.
+	pub fn egress(&self, protocol: ProtocolId, packet_id: PacketId, bytes: usize) {
+		self.record(protocol, packet_id, TrafficSample { ingress: 0, egress: bytes as u64 }, Instant::now());
+	}
+
+	/// Current traffic of every message type seen so far, sorted by protocol and message id.
+	pub fn snapshot(&self) -> Vec<PacketTraffic> {
+		self.snapshot_at(Instant::now())
+	}
+
+	fn bucket(&self, now: Instant) -> u64 {
+		now.saturating_duration_since(self.started).as_secs() / BUCKET_SECS
+	}
+
+	fn record(&self, protocol: ProtocolId, packet_id: PacketId, sample: TrafficSample, now: Instant) {
+		let bucket = self.bucket(now);
+		let mut counters = self.counters.lock();
+		let counters = counters.entry((protocol, packet_id)).or_default();
+
+		counters.total.ingress += sample.ingress;
+		counters.total.egress += sample.egress;
+
+		while counters.buckets.front().map_or(false, |&(b, _)| b + MAX_BUCKETS <= bucket) {
+			counters.buckets.pop_front();
+		}
+		match counters.buckets.back_mut() {
+			Some((b, current)) if *b == bucket => {
+				current.ingress += sample.ingress;
+				current.egress += sample.egress;
+			},
+			_ => counters.buckets.push_back((bucket, sample)),
+		}
+	}
+
+	fn snapshot_at(&self, now: Instant) -> Vec<PacketTraffic> {
+		let bucket = self.bucket(now);
+		let counters = self.counters.lock();
+		let mut traffic: Vec<_> = counters.iter().map(|(&(protocol, packet_id), counters)| {
+			let windows = WINDOW_SECS.iter().map(|&secs| {
+				let mut sample = TrafficSample::default();
+				for (_, s) in counters.buckets.iter().filter(|&&(b, _)| b + secs / BUCKET_SECS > bucket) {
+					sample.ingress += s.ingress;
+					sample.egress += s.egress;
+				}
+				(Duration::from_secs(secs), sample)
+			}).collect();
+
+			PacketTraffic { protocol, packet_id, total: counters.total, windows }
+		}).collect();
+
+		traffic.sort_by_key(|t| (t.protocol, t.packet_id));
+		traffic
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+	use network::TrafficSample;
+	use super::TrafficStats;
+
+	#[test]
+	fn should_account_traffic_in_sliding_windows() {
+		let stats = TrafficStats::new();
+		let start = Instant::now();
+		let sample = |ingress, egress| TrafficSample { ingress, egress };
+
+		stats.record(*b"eth", 2, sample(100, 0), start);
+		stats.record(*b"eth", 2, sample(0, 50), start + Duration::from_secs(200));
+		stats.record(*b"eth", 2, sample(10, 0), start + Duration::from_secs(280));
+		stats.record(*b"pip", 0, sample(0, 7), start + Duration::from_secs(290));
+
+		let traffic = stats.snapshot_at(start + Duration::from_secs(330));
+		assert_eq!(traffic.len(), 2);
+		assert_eq!((traffic[0].protocol, traffic[0].packet_id), (*b"eth", 2));
+		assert_eq!(traffic[0].total, sample(110, 50));
+		assert_eq!(traffic[0].windows, vec![
+			(Duration::from_secs(60), sample(10, 0)),
+			(Duration::from_secs(300), sample(10, 50)),
+		]);
+		assert_eq!((traffic[1].protocol, traffic[1].total), (*b"pip", sample(0, 7)));
+	}
+}
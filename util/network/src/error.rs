@@ -117,6 +117,9 @@ pub enum Error {
 	/// Packet size is over the protocol limit
 	#[display(fmt = "Packet is too large")]
 	OversizedPacket,
+	/// Too many packets are waiting for outbound bandwidth
+	#[display(fmt = "Outbound traffic queue is full")]
+	OutboundQueueFull,
 	/// Reached system resource limits for this process
 	#[display(fmt = "Too many open files in this process. Check your resource limits and restart parity")]
 	ProcessTooManyFiles,
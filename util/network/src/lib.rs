@@ -122,6 +122,28 @@ pub struct SessionInfo {
 	pub local_address: String,
 }
 
+/// Number of bytes transferred in each direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrafficSample {
+	/// Bytes received from peers.
+	pub ingress: u64,
+	/// Bytes sent to peers.
+	pub egress: u64,
+}
+
+/// Traffic of a single subprotocol message type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PacketTraffic {
+	/// Subprotocol the message belongs to.
+	pub protocol: ProtocolId,
+	/// Message id within the subprotocol.
+	pub packet_id: PacketId,
+	/// Bytes transferred since the network was started.
+	pub total: TrafficSample,
+	/// Bytes transferred within each sliding window, by window length.
+	pub windows: Vec<(Duration, TrafficSample)>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PeerCapabilityInfo {
 	pub protocol: ProtocolId,
@@ -183,6 +205,94 @@ pub enum NatType {
 	NatPMP,
 }
 
+/// Outbound dialing policy. Limits set to `0` are disabled.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DialPolicy {
+	/// Maximum number of outbound connection attempts per second.
+	pub max_dials_per_second: u32,
+	/// Maximum number of peers within a single /24 IPv4 (/64 IPv6) subnet. Reserved peers are exempt.
+	pub max_peers_per_subnet: u32,
+	/// Maximum number of peers sharing the first byte of node id. Reserved peers are exempt.
+	pub max_peers_per_id_bucket: u32,
+	/// Percentage of connection attempts given to reserved peers when discovered peers are dialed as well.
+	pub reserved_weight: u8,
+}
+
+impl Default for DialPolicy {
+	fn default() -> Self {
+		DialPolicy {
+			max_dials_per_second: 0,
+			max_peers_per_subnet: 0,
+			max_peers_per_id_bucket: 0,
+			reserved_weight: 100,
+		}
+	}
+}
+
+/// Class of outbound traffic sharing the bandwidth limit.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TrafficClass {
+	/// Block sync and anything not otherwise classified.
+	Sync,
+	/// Transaction propagation.
+	Transactions,
+	/// Light client serving.
+	Light,
+}
+
+/// Outbound bandwidth policy. Traffic classes get shares of the limit proportional to their weights,
+/// bandwidth left unused by a class is lent to the others. A limit of `0` disables scheduling.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BandwidthPolicy {
+	/// Maximum number of bytes sent per second.
+	pub max_bytes_per_second: u64,
+	/// Weight of block sync.
+	pub sync_weight: u32,
+	/// Weight of transaction propagation.
+	pub transactions_weight: u32,
+	/// Weight of light client serving.
+	pub light_weight: u32,
+	/// Subprotocol packets classified as transaction propagation.
+	pub transaction_packets: Vec<(ProtocolId, PacketId)>,
+	/// Subprotocols classified as light client serving.
+	pub light_protocols: Vec<ProtocolId>,
+}
+
+impl Default for BandwidthPolicy {
+	fn default() -> Self {
+		BandwidthPolicy {
+			max_bytes_per_second: 0,
+			sync_weight: 4,
+			transactions_weight: 2,
+			light_weight: 1,
+			transaction_packets: Vec::new(),
+			light_protocols: Vec::new(),
+		}
+	}
+}
+
+impl BandwidthPolicy {
+	/// Class of a packet sent over the given subprotocol.
+	pub fn classify(&self, protocol: ProtocolId, packet_id: PacketId) -> TrafficClass {
+		if self.light_protocols.contains(&protocol) {
+			TrafficClass::Light
+		} else if self.transaction_packets.contains(&(protocol, packet_id)) {
+			TrafficClass::Transactions
+		} else {
+			TrafficClass::Sync
+		}
+	}
+
+	/// Weight of the given traffic class.
+	pub fn weight(&self, class: TrafficClass) -> u32 {
+		match class {
+			TrafficClass::Sync => self.sync_weight,
+			TrafficClass::Transactions => self.transactions_weight,
+			TrafficClass::Light => self.light_weight,
+		}
+	}
+}
+
 /// Network service configuration
 #[derive(Debug, PartialEq, Clone)]
 pub struct NetworkConfiguration {
@@ -222,6 +332,13 @@ pub struct NetworkConfiguration {
 	pub ip_filter: IpFilter,
 	/// Client identifier
 	pub client_version: String,
+	/// Maximum age of a session after which it is gracefully rotated. RLPx has no in-session rekeying,
+	/// so rotation is what renews the session keys of long-lived connections.
+	pub max_session_age: Option<Duration>,
+	/// Outbound dialing policy
+	pub dial_policy: DialPolicy,
+	/// Outbound bandwidth policy
+	pub bandwidth_policy: BandwidthPolicy,
 }
 
 impl Default for NetworkConfiguration {
@@ -252,6 +369,9 @@ impl NetworkConfiguration {
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: NonReservedPeerMode::Accept,
 			client_version: "Parity-network".into(),
+			max_session_age: None,
+			dial_policy: DialPolicy::default(),
+			bandwidth_policy: BandwidthPolicy::default(),
 		}
 	}
 
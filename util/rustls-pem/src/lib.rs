@@ -0,0 +1,53 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Loading of TLS certificates and private keys from PEM files.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use tokio_rustls::rustls::{Certificate, PrivateKey};
+use tokio_rustls::rustls::internal::pemfile;
+
+/// Reads the certificate chain from a PEM file. Fails if the file holds no certificate.
+pub fn read_certificates(path: &str) -> io::Result<Vec<Certificate>> {
+	let certificates = read_pem(path, pemfile::certs)?;
+	if certificates.is_empty() {
+		return Err(invalid_data(format!("No certificates found in {}", path)));
+	}
+	Ok(certificates)
+}
+
+/// Reads the first private key from a PEM file, in PKCS8 or RSA format.
+pub fn read_private_key(path: &str) -> io::Result<PrivateKey> {
+	let mut keys = read_pem(path, pemfile::pkcs8_private_keys)?;
+	if keys.is_empty() {
+		keys = read_pem(path, pemfile::rsa_private_keys)?;
+	}
+	keys.into_iter().next()
+		.ok_or_else(|| invalid_data(format!("No private key found in {}", path)))
+}
+
+fn read_pem<T>(path: &str, parse: fn(&mut dyn BufRead) -> Result<Vec<T>, ()>) -> io::Result<Vec<T>> {
+	let file = File::open(path)
+		.map_err(|e| io::Error::new(e.kind(), format!("Unable to open {}: {}", path, e)))?;
+	parse(&mut BufReader::new(file))
+		.map_err(|_| invalid_data(format!("Invalid PEM file {}", path)))
+}
+
+fn invalid_data(message: String) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, message)
+}